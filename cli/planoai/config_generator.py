@@ -7,6 +7,8 @@ from jsonschema import validate, ValidationError
 from urllib.parse import urlparse
 from copy import deepcopy
 from planoai.consts import DEFAULT_OTEL_TRACING_GRPC_ENDPOINT
+from planoai.secret_refs import is_secret_reference, validate_secret_reference
+from planoai.config_loader import load_plano_config, resolve_provider_profiles
 
 SUPPORTED_PROVIDERS_WITH_BASE_URL = [
     "azure_openai",
@@ -23,12 +25,16 @@ SUPPORTED_PROVIDERS_WITHOUT_BASE_URL = [
     "openai",
     "xiaomi",
     "gemini",
+    "vertex_ai",
     "anthropic",
     "together_ai",
     "xai",
     "moonshotai",
     "zhipu",
     "digitalocean",
+    "cohere",
+    "deepgram",
+    "mock",
 ]
 
 SUPPORTED_PROVIDERS = (
@@ -36,6 +42,15 @@ SUPPORTED_PROVIDERS = (
 )
 
 
+def vertex_ai_base_url(project_id, location):
+    host = (
+        "aiplatform.googleapis.com"
+        if location == "global"
+        else f"{location}-aiplatform.googleapis.com"
+    )
+    return f"https://{host}/v1/projects/{project_id}/locations/{location}"
+
+
 def get_endpoint_and_port(endpoint, protocol):
     endpoint_tokens = endpoint.split(":")
     if len(endpoint_tokens) > 1:
@@ -50,6 +65,73 @@ def get_endpoint_and_port(endpoint, protocol):
         return endpoint, port
 
 
+def parse_failover_base_urls(model_provider, primary):
+    """Parse a provider's failover_base_urls, in priority order.
+
+    Failover URLs share the primary's Envoy cluster, so they must use the same
+    scheme and path prefix as base_url.
+    """
+    endpoints = []
+    for url in model_provider["failover_base_urls"]:
+        result = urlparse(url)
+        if result.scheme != primary.scheme or not result.hostname:
+            raise Exception(
+                f"Failover url {url} for model provider '{model_provider.get('name')}' must use the same scheme as base_url ({primary.scheme})"
+            )
+        if result.path.rstrip("/") != primary.path.rstrip("/"):
+            raise Exception(
+                f"Failover url {url} for model provider '{model_provider.get('name')}' must use the same path as base_url ({primary.path or '/'})"
+            )
+        port = result.port or (80 if result.scheme == "http" else 443)
+        endpoints.append({"endpoint": result.hostname, "port": port})
+    return endpoints
+
+
+def parse_proxy_url(proxy_url):
+    """Parse an http:// forward proxy URL into an Envoy socket address."""
+    result = urlparse(proxy_url)
+    if result.scheme != "http" or not result.hostname:
+        raise Exception(
+            f"Invalid upstream proxy '{proxy_url}': only http://host:port proxies are supported"
+        )
+    if result.username or result.password:
+        raise Exception(
+            f"Invalid upstream proxy '{proxy_url}': proxy credentials are not supported"
+        )
+    return {"address": result.hostname, "port": result.port or 80}
+
+
+def is_no_proxy_host(host, no_proxy):
+    """Match host against a NO_PROXY style list (exact hosts, .domain suffixes or *)."""
+    for entry in no_proxy.split(","):
+        entry = entry.strip().lower()
+        if not entry:
+            continue
+        if entry == "*":
+            return True
+        domain = entry.lstrip("*").lstrip(".")
+        if host == domain or host.endswith("." + domain):
+            return True
+    return False
+
+
+def make_upstream_proxy_resolver(default_proxy, no_proxy):
+    """Build the template helper that picks the CONNECT proxy for an upstream host.
+
+    A provider's own ``proxy`` wins; otherwise the default (overrides.upstream_proxy
+    or HTTPS_PROXY) applies unless the host is listed in NO_PROXY.
+    """
+
+    def upstream_proxy_for(host, provider_proxy=None):
+        if provider_proxy:
+            return parse_proxy_url(provider_proxy)
+        if not default_proxy or is_no_proxy_host(str(host).lower(), no_proxy):
+            return None
+        return parse_proxy_url(default_proxy)
+
+    return upstream_proxy_for
+
+
 def validate_and_render_schema():
     ENVOY_CONFIG_TEMPLATE_FILE = os.getenv(
         "ENVOY_CONFIG_TEMPLATE_FILE", "envoy.template.yaml"
@@ -74,13 +156,11 @@ def validate_and_render_schema():
         print(str(e))
         exit(1)  # validate_prompt_config failed. Exit
 
-    with open(PLANO_CONFIG_FILE, "r") as file:
-        plano_config = file.read()
+    config_yaml = load_plano_config(PLANO_CONFIG_FILE)
 
     with open(PLANO_CONFIG_SCHEMA_FILE, "r") as file:
         plano_config_schema = file.read()
 
-    config_yaml = yaml.safe_load(plano_config)
     _ = yaml.safe_load(plano_config_schema)
     inferred_clusters = {}
 
@@ -93,6 +173,8 @@ def validate_and_render_schema():
         config_yaml["model_providers"] = config_yaml["llm_providers"]
         del config_yaml["llm_providers"]
 
+    resolve_provider_profiles(config_yaml)
+
     listeners, llm_gateway, prompt_gateway = convert_legacy_listeners(
         config_yaml.get("listeners"), config_yaml.get("model_providers")
     )
@@ -189,6 +271,7 @@ def validate_and_render_schema():
     llms_with_endpoint = []
     llms_with_endpoint_cluster_names = set()
     updated_model_providers = []
+    failover_endpoints = {}
     model_provider_name_set = set()
     llms_with_usage = []
     model_name_keys = set()
@@ -319,6 +402,17 @@ def validate_and_render_schema():
                     f"The access_key will be ignored and the client's Authorization header will be forwarded instead."
                 )
 
+            # file: and vault: references stay in the rendered config;
+            # brightstaff resolves them so keys never land on disk
+            for key_field in ("access_key", "secondary_access_key"):
+                if is_secret_reference(model_provider.get(key_field)):
+                    try:
+                        validate_secret_reference(model_provider[key_field])
+                    except Exception as e:
+                        raise Exception(
+                            f"Invalid {key_field} for model provider '{model_provider.get('name')}': {e}"
+                        )
+
             model_provider["model"] = model_id
             model_provider["provider_interface"] = provider
             model_provider_name_set.add(model_provider.get("name"))
@@ -334,6 +428,23 @@ def validate_and_render_schema():
                 del model_provider["provider"]
             updated_model_providers.append(model_provider)
 
+            if provider == "vertex_ai" and not model_provider.get("base_url"):
+                vertex_ai = model_provider.get("vertex_ai")
+                if not vertex_ai:
+                    raise Exception(
+                        f"Model provider '{model_provider.get('name')}' needs vertex_ai.project_id and vertex_ai.location, or a base_url"
+                    )
+                model_provider["base_url"] = vertex_ai_base_url(
+                    vertex_ai["project_id"], vertex_ai["location"]
+                )
+
+            if model_provider.get("tls") and not str(
+                model_provider.get("base_url", "")
+            ).startswith("https://"):
+                raise Exception(
+                    f"Model provider '{model_provider.get('name')}' configures tls client certificates but has no https base_url"
+                )
+
             if model_provider.get("base_url", None):
                 base_url = model_provider["base_url"]
                 urlparse_result = urlparse(base_url)
@@ -364,10 +475,38 @@ def validate_and_render_schema():
                     provider + "_" + endpoint
                 )  # make name unique by appending endpoint
                 model_provider["cluster_name"] = cluster_name
+                failover = (
+                    parse_failover_base_urls(model_provider, urlparse_result)
+                    if model_provider.get("failover_base_urls")
+                    else []
+                )
                 # Only add if cluster_name is not already present to avoid duplicates
                 if cluster_name not in llms_with_endpoint_cluster_names:
                     llms_with_endpoint.append(model_provider)
                     llms_with_endpoint_cluster_names.add(cluster_name)
+                    if failover:
+                        failover_endpoints[cluster_name] = failover
+                else:
+                    existing = next(
+                        p
+                        for p in llms_with_endpoint
+                        if p["cluster_name"] == cluster_name
+                    )
+                    if existing.get("tls") != model_provider.get("tls"):
+                        raise Exception(
+                            f"Model providers '{existing.get('name')}' and '{model_provider.get('name')}' share endpoint {endpoint} but configure different tls settings"
+                        )
+                    # the cluster is rendered once, from the first provider
+                    if failover_endpoints.get(cluster_name, []) != failover:
+                        raise Exception(
+                            f"Model providers '{existing.get('name')}' and '{model_provider.get('name')}' share endpoint {endpoint} but configure different failover_base_urls"
+                        )
+                    if existing.get("health_check") != model_provider.get(
+                        "health_check"
+                    ):
+                        raise Exception(
+                            f"Model providers '{existing.get('name')}' and '{model_provider.get('name')}' share endpoint {endpoint} but configure different health_check settings"
+                        )
 
     overrides_config = config_yaml.get("overrides", {})
     # Build lookup of model names (already prefix-stripped by config processing)
@@ -431,6 +570,15 @@ def validate_and_render_schema():
                     "Please provide model_providers either under listeners or at root level, not both. Currently we don't support multiple listeners with model_providers"
                 )
 
+    for listener in listeners:
+        listener_tls = listener.get("tls") or {}
+        if listener_tls.get("require_client_cert") and not listener_tls.get(
+            "client_ca_path"
+        ):
+            raise Exception(
+                f"Listener '{listener.get('name', 'unknown')}' sets tls.require_client_cert but has no tls.client_ca_path to verify client certificates"
+            )
+
     # Validate input_filters IDs on listeners reference valid agent/filter IDs
     for listener in listeners:
         listener_input_filters = listener.get("input_filters", [])
@@ -445,6 +593,8 @@ def validate_and_render_schema():
     if "model_aliases" in config_yaml:
         model_aliases = config_yaml["model_aliases"]
         for alias_name, alias_config in model_aliases.items():
+            if alias_config.get("blocked"):
+                continue
             target = alias_config.get("target")
             if target not in model_name_keys:
                 raise Exception(
@@ -480,6 +630,26 @@ def validate_and_render_schema():
     upstream_tls_ca_path = overrides.get(
         "upstream_tls_ca_path", "/etc/ssl/certs/ca-certificates.crt"
     )
+    upstream_endpoint_override = overrides.get(
+        "allow_upstream_endpoint_override", False
+    )
+    if upstream_endpoint_override and not overrides.get(
+        "upstream_endpoint_allowed_hosts"
+    ):
+        raise Exception(
+            "allow_upstream_endpoint_override requires upstream_endpoint_allowed_hosts"
+        )
+    default_upstream_proxy = overrides.get("upstream_proxy") or os.getenv(
+        "HTTPS_PROXY", os.getenv("https_proxy")
+    )
+    no_proxy = os.getenv("NO_PROXY", os.getenv("no_proxy", ""))
+    upstream_proxy_for = make_upstream_proxy_resolver(default_upstream_proxy, no_proxy)
+    # fail fast on malformed proxy urls instead of at template render time
+    if default_upstream_proxy:
+        parse_proxy_url(default_upstream_proxy)
+    for model_provider in updated_model_providers:
+        if model_provider.get("proxy"):
+            parse_proxy_url(model_provider["proxy"])
 
     data = {
         "prompt_gateway_listener": prompt_gateway,
@@ -487,13 +657,21 @@ def validate_and_render_schema():
         "plano_config": plano_config_string,
         "plano_llm_config": plano_llm_config_string,
         "plano_clusters": inferred_clusters,
-        "plano_model_providers": updated_model_providers,
+        # mock providers are answered by brightstaff and need no Envoy route
+        "plano_model_providers": [
+            mp
+            for mp in updated_model_providers
+            if mp.get("provider_interface") != "mock"
+        ],
         "plano_tracing": plano_tracing,
         "local_llms": llms_with_endpoint,
         "agent_orchestrator": agent_orchestrator,
         "listeners": listeners,
         "upstream_connect_timeout": upstream_connect_timeout,
         "upstream_tls_ca_path": upstream_tls_ca_path,
+        "upstream_endpoint_override": upstream_endpoint_override,
+        "upstream_proxy_for": upstream_proxy_for,
+        "failover_endpoints": failover_endpoints,
     }
 
     rendered = template.render(data)
@@ -507,13 +685,11 @@ def validate_and_render_schema():
 
 
 def validate_prompt_config(plano_config_file, plano_config_schema_file):
-    with open(plano_config_file, "r") as file:
-        plano_config = file.read()
+    config_yaml = load_plano_config(plano_config_file)
 
     with open(plano_config_schema_file, "r") as file:
         plano_config_schema = file.read()
 
-    config_yaml = yaml.safe_load(plano_config)
     config_schema_yaml = yaml.safe_load(plano_config_schema)
 
     try:
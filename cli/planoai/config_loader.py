@@ -0,0 +1,159 @@
+"""Loading of plano_config.yaml files that are split across several files.
+
+A config may pull in shared fragments with a top-level ``include:`` key:
+
+    include:
+      - shared/providers.yaml
+      - shared/tracing.yaml
+
+Included paths are relative to the file that includes them and are merged
+in order, with the including file applied last. Overlay files (for example
+a per-environment override passed with ``planoai up --overlay``) are merged
+on top of the fully resolved base config.
+
+Merging rules:
+
+- mappings merge key by key, recursively
+- a key set to ``null`` in a later file removes it
+- lists of mappings whose entries all carry the same identity key
+  (``name``, ``model`` or ``id``) merge entry by entry, new entries appended
+- any other value, including other lists, is replaced
+"""
+
+import os
+
+import yaml
+
+INCLUDE_KEY = "include"
+LIST_IDENTITY_KEYS = ("name", "model", "id")
+
+
+def _list_identity_key(*lists):
+    for key in LIST_IDENTITY_KEYS:
+        if all(
+            isinstance(item, dict) and key in item for items in lists for item in items
+        ):
+            return key
+    return None
+
+
+def _merge_lists(base, override):
+    key = _list_identity_key(base, override)
+    if key is None or not base or not override:
+        return override
+    merged = list(base)
+    positions = {item[key]: index for index, item in enumerate(merged)}
+    for item in override:
+        index = positions.get(item[key])
+        if index is None:
+            positions[item[key]] = len(merged)
+            merged.append(item)
+        else:
+            merged[index] = deep_merge(merged[index], item)
+    return merged
+
+
+def deep_merge(base, override):
+    """Merge ``override`` onto ``base`` and return the result; neither input
+    is modified."""
+    if isinstance(base, dict) and isinstance(override, dict):
+        merged = dict(base)
+        for key, value in override.items():
+            if value is None:
+                merged.pop(key, None)
+            elif key in merged:
+                merged[key] = deep_merge(merged[key], value)
+            else:
+                merged[key] = value
+        return merged
+    if isinstance(base, list) and isinstance(override, list):
+        return _merge_lists(base, override)
+    return override
+
+
+def _read_yaml(path):
+    try:
+        with open(path, "r") as file:
+            content = yaml.safe_load(file)
+    except OSError as e:
+        raise Exception(f"Unable to read config file {path}: {e.strerror}")
+    if content is None:
+        return {}
+    if not isinstance(content, dict):
+        raise Exception(f"Config file {path} must contain a YAML mapping")
+    return content
+
+
+def _load_with_includes(path, stack):
+    path = os.path.abspath(path)
+    if path in stack:
+        chain = " -> ".join(stack + [path])
+        raise Exception(f"Config include cycle detected: {chain}")
+    config = _read_yaml(path)
+
+    includes = config.pop(INCLUDE_KEY, None) or []
+    if isinstance(includes, str):
+        includes = [includes]
+    if not isinstance(includes, list) or not all(
+        isinstance(include, str) for include in includes
+    ):
+        raise Exception(f"'{INCLUDE_KEY}' in {path} must be a path or list of paths")
+
+    merged = {}
+    base_dir = os.path.dirname(path)
+    for include in includes:
+        include_path = os.path.join(base_dir, os.path.expanduser(include))
+        merged = deep_merge(merged, _load_with_includes(include_path, stack + [path]))
+    return deep_merge(merged, config)
+
+
+def load_plano_config(path, overlays=()):
+    """Load ``path`` with its includes resolved and ``overlays`` merged on top."""
+    config = _load_with_includes(path, [])
+    for overlay in overlays:
+        config = deep_merge(config, _load_with_includes(overlay, []))
+    return config
+
+
+def needs_composition(path, overlays=()):
+    if overlays:
+        return True
+    try:
+        return INCLUDE_KEY in _read_yaml(path)
+    except Exception:
+        return False
+
+
+def compose_config_file(path, output_path, overlays=()):
+    """Write the merged config to ``output_path`` and return it, or return
+    ``path`` unchanged when it has no includes and no overlays apply."""
+    if not needs_composition(path, overlays):
+        return path
+    config = load_plano_config(path, overlays)
+    os.makedirs(os.path.dirname(os.path.abspath(output_path)), exist_ok=True)
+    with open(output_path, "w") as file:
+        yaml.safe_dump(config, file, sort_keys=False)
+    return output_path
+
+
+def resolve_provider_profiles(config):
+    """Merge each provider's ``profile`` (a name or list of names from the
+    top-level ``provider_profiles``) underneath the provider's own keys.
+    Removes ``provider_profiles`` and ``profile`` from ``config`` in place."""
+    profiles = config.pop("provider_profiles", None) or {}
+    for key in ("model_providers", "llm_providers"):
+        providers = config.get(key) or []
+        for index, provider in enumerate(providers):
+            names = provider.pop("profile", None) or []
+            if isinstance(names, str):
+                names = [names]
+            merged = {}
+            for name in names:
+                if name not in profiles:
+                    raise Exception(
+                        f"Model provider '{provider.get('name') or provider.get('model')}' uses unknown profile '{name}'. "
+                        f"Available profiles: {', '.join(sorted(profiles)) or 'none'}"
+                    )
+                merged = deep_merge(merged, profiles[name])
+            providers[index] = deep_merge(merged, provider)
+    return config
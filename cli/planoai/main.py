@@ -8,6 +8,7 @@ import logging
 import rich_click as click
 import yaml
 from planoai import targets
+from planoai.config_loader import compose_config_file
 from planoai.defaults import (
     DEFAULT_LLM_LISTENER_PORT,
     detect_providers,
@@ -331,6 +332,13 @@ def build(docker):
     show_default=True,
     help="Override the LLM listener port when running without a config file. Ignored when a config file is present.",
 )
+@click.option(
+    "--overlay",
+    "overlays",
+    multiple=True,
+    envvar="PLANO_CONFIG_OVERLAY",
+    help="Config file merged on top of the base config, e.g. an environment-specific override. Repeatable.",
+)
 def up(
     file,
     path,
@@ -340,6 +348,7 @@ def up(
     docker,
     verbose,
     listener_port,
+    overlays,
 ):
     """Starts Plano."""
     from rich.status import Status
@@ -368,6 +377,20 @@ def up(
                 f"[dim]No plano config found; using defaults ({detection.summary}). "
                 f"Listening on :{listener_port}, tracing -> http://localhost:4317.[/dim]"
             )
+        else:
+            # Resolve include: directives and --overlay files into a single
+            # config so validation, key detection and both runtimes see the
+            # same merged result.
+            try:
+                plano_config_file = compose_config_file(
+                    plano_config_file,
+                    os.path.expanduser("~/.plano/composed_config.yaml"),
+                    overlays,
+                )
+            except Exception as e:
+                console.print(f"[red]✗[/red] Failed to load configuration")
+                console.print(f"  [dim]{str(e).strip()}[/dim]")
+                sys.exit(1)
 
         if not docker:
             from planoai.native_runner import native_validate_config
@@ -452,6 +475,13 @@ def up(
             _print_missing_keys(console, missing_keys)
             sys.exit(1)
 
+        # brightstaff resolves vault: access keys itself
+        for vault_var in (
+            "VAULT_ADDR", "VAULT_TOKEN", "VAULT_TOKEN_FILE", "VAULT_NAMESPACE"
+        ):
+            if env.get(vault_var):
+                env_stage[vault_var] = env[vault_var]
+
         # Pass log level to the Docker container — supervisord uses LOG_LEVEL
         # to set RUST_LOG (brightstaff) and envoy component log levels
         env_stage["LOG_LEVEL"] = os.environ.get("LOG_LEVEL", "info")
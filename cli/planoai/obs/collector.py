@@ -205,7 +205,10 @@ def span_to_llm_call(
         ),
     )
 
-    if pricing is not None:
+    # Plano prices the call itself from the config pricing table when it can;
+    # the catalog is only a fallback for spans without a cost attribute.
+    call.cost_usd = _maybe_float(attrs.get("llm.usage.cost_usd"))
+    if call.cost_usd is None and pricing is not None:
         call.cost_usd = pricing.cost_for_call(call)
 
     return call
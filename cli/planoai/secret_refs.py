@@ -0,0 +1,38 @@
+"""Secret references used in place of raw provider credentials.
+
+A credential in plano_config.yaml may be written as:
+
+    file:/run/secrets/openai_key      contents of the file, trailing newline stripped
+    vault:secret/data/plano#openai    field of a HashiCorp Vault KV secret
+
+References are copied into the rendered config as they are; brightstaff reads
+the secrets when it loads the config (and again when model_providers is
+reloaded) and keeps them in memory, so no resolved key is written to disk.
+Vault is reached at VAULT_ADDR with VAULT_TOKEN or VAULT_TOKEN_FILE from
+brightstaff's environment.
+"""
+
+FILE_PREFIX = "file:"
+VAULT_PREFIX = "vault:"
+
+
+def is_secret_reference(value):
+    return isinstance(value, str) and (
+        value.startswith(FILE_PREFIX) or value.startswith(VAULT_PREFIX)
+    )
+
+
+def validate_secret_reference(value):
+    """Raise when a reference is malformed, so the mistake surfaces when the
+    config is rendered rather than when brightstaff starts."""
+    if value.startswith(FILE_PREFIX):
+        if not value[len(FILE_PREFIX) :]:
+            raise Exception(
+                f"Invalid secret reference '{value}', expected file:<path>"
+            )
+        return
+    path, sep, field = value[len(VAULT_PREFIX) :].partition("#")
+    if not sep or not path.strip("/") or not field:
+        raise Exception(
+            f"Invalid secret reference '{value}', expected vault:<path>#<field>"
+        )
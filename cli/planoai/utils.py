@@ -5,6 +5,8 @@ import sys
 import yaml
 import logging
 from planoai.consts import PLANO_DOCKER_NAME
+from planoai.config_loader import resolve_provider_profiles
+from planoai.secret_refs import is_secret_reference
 
 # Standard env var for log level across all Plano components
 LOG_LEVEL_ENV = "LOG_LEVEL"
@@ -184,6 +186,8 @@ def get_llm_provider_access_keys(plano_config_file):
         plano_config_yaml["model_providers"] = plano_config_yaml["llm_providers"]
         del plano_config_yaml["llm_providers"]
 
+    resolve_provider_profiles(plano_config_yaml)
+
     listeners, _, _ = convert_legacy_listeners(
         plano_config_yaml.get("listeners"), plano_config_yaml.get("model_providers")
     )
@@ -202,9 +206,11 @@ def get_llm_provider_access_keys(plano_config_file):
 
     for listener in listeners:
         for llm_provider in listener.get("model_providers", []):
-            access_key = llm_provider.get("access_key")
-            if access_key is not None:
-                access_key_list.append(access_key)
+            for key_field in ("access_key", "secondary_access_key"):
+                access_key = llm_provider.get(key_field)
+                # file: and vault: references are resolved by brightstaff, not from env
+                if access_key is not None and not is_secret_reference(access_key):
+                    access_key_list.append(access_key)
 
     # Extract environment variables from state_storage.connection_string
     state_storage = plano_config_yaml.get("state_storage_v1_responses")
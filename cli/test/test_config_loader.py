@@ -0,0 +1,152 @@
+import pytest
+import yaml
+
+from planoai.config_loader import (
+    compose_config_file,
+    deep_merge,
+    load_plano_config,
+    resolve_provider_profiles,
+)
+
+
+def write_yaml(path, data):
+    path.parent.mkdir(parents=True, exist_ok=True)
+    path.write_text(yaml.safe_dump(data))
+    return path
+
+
+def test_deep_merge_rules():
+    base = {
+        "tracing": {"random_sampling": 10, "trace_arch_internal": True},
+        "model_providers": [
+            {"model": "openai/gpt-4o", "access_key": "$OPENAI_API_KEY"},
+            {"model": "anthropic/claude-sonnet-4", "access_key": "$ANTHROPIC"},
+        ],
+        "ports": [80, 443],
+    }
+    override = {
+        "tracing": {"random_sampling": 100, "trace_arch_internal": None},
+        "model_providers": [
+            {"model": "openai/gpt-4o", "default": True},
+            {"model": "mistral/mistral-small-latest", "access_key": "$MISTRAL"},
+        ],
+        "ports": [8080],
+    }
+    merged = deep_merge(base, override)
+    assert merged["tracing"] == {"random_sampling": 100}
+    assert merged["model_providers"] == [
+        {"model": "openai/gpt-4o", "access_key": "$OPENAI_API_KEY", "default": True},
+        {"model": "anthropic/claude-sonnet-4", "access_key": "$ANTHROPIC"},
+        {"model": "mistral/mistral-small-latest", "access_key": "$MISTRAL"},
+    ]
+    assert merged["ports"] == [8080]
+    # inputs are left untouched
+    assert base["tracing"]["random_sampling"] == 10
+
+
+def test_includes_resolve_relative_to_including_file(tmp_path):
+    write_yaml(
+        tmp_path / "shared" / "providers.yaml",
+        {"model_providers": [{"model": "openai/gpt-4o", "default": True}]},
+    )
+    write_yaml(
+        tmp_path / "shared" / "base.yaml",
+        {"include": "providers.yaml", "version": "v0.3.0"},
+    )
+    config = write_yaml(
+        tmp_path / "plano_config.yaml",
+        {"include": ["shared/base.yaml"], "tracing": {"random_sampling": 5}},
+    )
+    assert load_plano_config(str(config)) == {
+        "model_providers": [{"model": "openai/gpt-4o", "default": True}],
+        "version": "v0.3.0",
+        "tracing": {"random_sampling": 5},
+    }
+
+
+def test_overlay_applies_on_top_of_base(tmp_path):
+    base = write_yaml(
+        tmp_path / "base.yaml",
+        {
+            "version": "v0.3.0",
+            "model_providers": [
+                {"model": "openai/gpt-4o", "base_url": "https://api.openai.com"}
+            ],
+        },
+    )
+    overlay = write_yaml(
+        tmp_path / "staging.yaml",
+        {
+            "model_providers": [
+                {"model": "openai/gpt-4o", "base_url": "https://staging-proxy.local"}
+            ]
+        },
+    )
+    config = load_plano_config(str(base), [str(overlay)])
+    assert config["model_providers"][0]["base_url"] == "https://staging-proxy.local"
+
+
+def test_include_cycle_is_reported(tmp_path):
+    write_yaml(tmp_path / "a.yaml", {"include": "b.yaml"})
+    write_yaml(tmp_path / "b.yaml", {"include": "a.yaml"})
+    with pytest.raises(Exception, match="include cycle"):
+        load_plano_config(str(tmp_path / "a.yaml"))
+
+
+def test_compose_only_writes_when_needed(tmp_path):
+    plain = write_yaml(tmp_path / "plain.yaml", {"version": "v0.3.0"})
+    output = tmp_path / "out" / "composed.yaml"
+    assert compose_config_file(str(plain), str(output)) == str(plain)
+    assert not output.exists()
+
+    overlay = write_yaml(tmp_path / "prod.yaml", {"tracing": {"random_sampling": 1}})
+    assert compose_config_file(str(plain), str(output), [str(overlay)]) == str(
+        output
+    )
+    assert yaml.safe_load(output.read_text()) == {
+        "version": "v0.3.0",
+        "tracing": {"random_sampling": 1},
+    }
+
+
+def test_provider_profiles_are_inherited():
+    config = {
+        "provider_profiles": {
+            "standard": {
+                "timeout": "60s",
+                "max_retries": 2,
+                "param_limits": {"temperature": 1.0, "max_tokens": 4096},
+            },
+            "long_running": {"timeout": "600s"},
+        },
+        "model_providers": [
+            {"model": "openai/gpt-4o", "profile": "standard"},
+            {
+                "model": "openai/o3",
+                "profile": ["standard", "long_running"],
+                "param_limits": {"max_tokens": 32000},
+            },
+            {"model": "openai/gpt-4o-mini"},
+        ],
+    }
+    resolve_provider_profiles(config)
+    assert "provider_profiles" not in config
+    gpt4o, o3, mini = config["model_providers"]
+    assert gpt4o == {
+        "model": "openai/gpt-4o",
+        "timeout": "60s",
+        "max_retries": 2,
+        "param_limits": {"temperature": 1.0, "max_tokens": 4096},
+    }
+    assert o3["timeout"] == "600s"
+    assert o3["param_limits"] == {"temperature": 1.0, "max_tokens": 32000}
+    assert mini == {"model": "openai/gpt-4o-mini"}
+
+
+def test_unknown_provider_profile_is_reported():
+    config = {
+        "provider_profiles": {"standard": {"timeout": "60s"}},
+        "model_providers": [{"model": "openai/gpt-4o", "profile": "strict"}],
+    }
+    with pytest.raises(Exception, match="unknown profile 'strict'"):
+        resolve_provider_profiles(config)
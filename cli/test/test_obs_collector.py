@@ -102,6 +102,24 @@ def test_pricing_lookup_attaches_cost():
     assert call.cost_usd == pytest.approx(0.26)
 
 
+def test_span_cost_attribute_takes_precedence_over_catalog():
+    class StubPricing:
+        def cost_for_call(self, call):
+            return 99.0
+
+    span = _mk_span(
+        {
+            "llm.model": "gpt-4o",
+            "llm.usage.prompt_tokens": 10,
+            "llm.usage.completion_tokens": 2,
+            "llm.usage.cost_usd": 0.000045,
+        }
+    )
+    call = span_to_llm_call(span, "plano(llm)", pricing=StubPricing())
+    assert call is not None
+    assert call.cost_usd == pytest.approx(0.000045)
+
+
 def test_tpt_and_tokens_per_sec_derived():
     call = LLMCall(
         request_id="x",
@@ -0,0 +1,25 @@
+import pytest
+
+from planoai.secret_refs import is_secret_reference, validate_secret_reference
+
+
+def test_plain_values_are_not_references():
+    assert not is_secret_reference("$OPENAI_API_KEY")
+    assert not is_secret_reference("sk-literal")
+    assert not is_secret_reference(None)
+    assert is_secret_reference("file:/run/secrets/openai_key")
+    assert is_secret_reference("vault:secret/data/plano#openai")
+
+
+def test_valid_references():
+    validate_secret_reference("file:/run/secrets/openai_key")
+    validate_secret_reference("vault:secret/data/plano#openai")
+
+
+def test_malformed_references():
+    with pytest.raises(Exception, match="expected file:<path>"):
+        validate_secret_reference("file:")
+    with pytest.raises(Exception, match="expected vault:<path>#<field>"):
+        validate_secret_reference("vault:secret/plano")
+    with pytest.raises(Exception, match="expected vault:<path>#<field>"):
+        validate_secret_reference("vault:#openai")
@@ -0,0 +1,170 @@
+//! Active credential per provider, for zero-downtime key rotation.
+//!
+//! A provider may configure a `secondary_access_key` next to its
+//! `access_key`. Brightstaff tells the LLM gateway which of the two to use on
+//! each request through [`ARCH_ACCESS_KEY_SLOT_HEADER`]; the
+//! `/admin/providers/rotate-key` call switches the slot atomically. Slots are
+//! not persisted: after a restart every provider is back on its primary key,
+//! so swap the keys in the config once the old one is revoked.
+//!
+//! Rotation only covers traffic routed through brightstaff. Requests sent to
+//! the LLM listener directly keep using the primary key: the gateway ignores
+//! the slot header unless the connection comes from the local host.
+//!
+//! Keys configured as `file:`/`vault:` references (see [`crate::secret_keys`])
+//! are sent along for the active slot in [`ARCH_UPSTREAM_ACCESS_KEY_HEADER`].
+
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use common::consts::{ARCH_ACCESS_KEY_SLOT_HEADER, ARCH_UPSTREAM_ACCESS_KEY_HEADER};
+use serde::{Deserialize, Serialize};
+
+use crate::secret_keys::SecretKeys;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeySlot {
+    #[default]
+    Primary,
+    Secondary,
+}
+
+impl KeySlot {
+    pub fn other(self) -> Self {
+        match self {
+            KeySlot::Primary => KeySlot::Secondary,
+            KeySlot::Secondary => KeySlot::Primary,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct AccessKeySlots {
+    /// Providers switched away from their primary key, by provider name.
+    active: RwLock<HashMap<String, KeySlot>>,
+    /// Keys read from `file:`/`vault:` references.
+    secrets: RwLock<SecretKeys>,
+}
+
+impl AccessKeySlots {
+    pub fn active(&self, provider: &str) -> KeySlot {
+        self.active
+            .read()
+            .unwrap()
+            .get(provider)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Switch `provider` to `slot`, or to the other slot when `None`.
+    /// Returns the slot now in use.
+    pub fn rotate(&self, provider: &str, slot: Option<KeySlot>) -> KeySlot {
+        let mut active = self.active.write().unwrap();
+        let current = active.get(provider).copied().unwrap_or_default();
+        let next = slot.unwrap_or(current.other());
+        if next == KeySlot::Primary {
+            active.remove(provider);
+        } else {
+            active.insert(provider.to_string(), next);
+        }
+        next
+    }
+
+    /// Providers not on their primary key.
+    pub fn rotated(&self) -> HashMap<String, KeySlot> {
+        self.active.read().unwrap().clone()
+    }
+
+    /// Replace the resolved reference keys, e.g. after providers were reloaded.
+    pub fn set_secrets(&self, secrets: SecretKeys) {
+        *self.secrets.write().unwrap() = secrets;
+    }
+
+    /// Set the slot header for `provider` on an upstream request, and the
+    /// resolved key when that slot's key is a reference, replacing any value
+    /// the client sent.
+    pub fn apply(&self, provider: &str, headers: &mut hyper::HeaderMap) {
+        headers.remove(ARCH_ACCESS_KEY_SLOT_HEADER);
+        headers.remove(ARCH_UPSTREAM_ACCESS_KEY_HEADER);
+        let slot = self.active(provider);
+        if slot == KeySlot::Secondary {
+            headers.insert(
+                ARCH_ACCESS_KEY_SLOT_HEADER,
+                hyper::header::HeaderValue::from_static("secondary"),
+            );
+        }
+        // The gateway falls back to the primary key when no secondary is configured
+        let secrets = self.secrets.read().unwrap();
+        if let Some(value) = secrets
+            .get(&(provider.to_string(), slot))
+            .or_else(|| secrets.get(&(provider.to_string(), KeySlot::Primary)))
+            .and_then(|key| hyper::header::HeaderValue::from_str(key).ok())
+        {
+            headers.insert(ARCH_UPSTREAM_ACCESS_KEY_HEADER, value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rotate_toggles_and_sets_header() {
+        let slots = AccessKeySlots::default();
+        assert_eq!(slots.rotate("openai/gpt-4o", None), KeySlot::Secondary);
+
+        let mut headers = hyper::HeaderMap::new();
+        slots.apply("openai/gpt-4o", &mut headers);
+        assert_eq!(headers[ARCH_ACCESS_KEY_SLOT_HEADER], "secondary");
+
+        assert_eq!(slots.rotate("openai/gpt-4o", None), KeySlot::Primary);
+        slots.apply("openai/gpt-4o", &mut headers);
+        assert!(headers.get(ARCH_ACCESS_KEY_SLOT_HEADER).is_none());
+        assert!(slots.rotated().is_empty());
+    }
+
+    #[test]
+    fn resolved_key_follows_active_slot() {
+        let slots = AccessKeySlots::default();
+        let provider = "openai/gpt-4o".to_string();
+        slots.set_secrets(SecretKeys::from([
+            (
+                (provider.clone(), KeySlot::Primary),
+                "sk-primary".to_string(),
+            ),
+            (
+                (provider.clone(), KeySlot::Secondary),
+                "sk-secondary".to_string(),
+            ),
+        ]));
+
+        let mut headers = hyper::HeaderMap::new();
+        headers.insert(
+            ARCH_UPSTREAM_ACCESS_KEY_HEADER,
+            "sk-client".parse().unwrap(),
+        );
+        slots.apply(&provider, &mut headers);
+        assert_eq!(headers[ARCH_UPSTREAM_ACCESS_KEY_HEADER], "sk-primary");
+
+        slots.rotate(&provider, Some(KeySlot::Secondary));
+        slots.apply(&provider, &mut headers);
+        assert_eq!(headers[ARCH_UPSTREAM_ACCESS_KEY_HEADER], "sk-secondary");
+
+        slots.apply("anthropic/claude-sonnet-4", &mut headers);
+        assert!(headers.get(ARCH_UPSTREAM_ACCESS_KEY_HEADER).is_none());
+    }
+
+    #[test]
+    fn explicit_slot_is_idempotent() {
+        let slots = AccessKeySlots::default();
+        slots.rotate("anthropic/claude-sonnet-4", Some(KeySlot::Secondary));
+        slots.rotate("anthropic/claude-sonnet-4", Some(KeySlot::Secondary));
+        assert_eq!(
+            slots.active("anthropic/claude-sonnet-4"),
+            KeySlot::Secondary
+        );
+        assert_eq!(slots.active("openai/gpt-4o"), KeySlot::Primary);
+    }
+}
@@ -1,12 +1,38 @@
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 
-use common::configuration::{Agent, FilterPipeline, Listener, ModelAlias, SpanAttributes};
+use common::configuration::{
+    Agent, ConversationTitleSettings, FilterPipeline, Listener, ModelDeprecation, SpanAttributes,
+};
 use common::llm_providers::LlmProviders;
+use common::model_aliases::ModelAliasTable;
+use common::pricing::PricingTable;
 use tokio::sync::RwLock;
 
+use crate::access_keys::AccessKeySlots;
+use crate::archive::Archiver;
+use crate::aws_auth::BedrockCredentials;
+use crate::chaos::Chaos;
+use crate::concurrency::ConcurrencyLimiter;
+use crate::config_store::ConfigStore;
+use crate::cooldown::ProviderCooldowns;
+use crate::event_bus::EventBus;
+use crate::image_fetch::ImageInliner;
+use crate::moderation::Moderator;
+use crate::plugins::PluginHost;
+use crate::provider_health::ProviderHealth;
+use crate::response_cache::ResponseCache;
+use crate::retrieval::Retriever;
 use crate::router::orchestrator::OrchestratorService;
+use crate::secret_guardrail::SecretGuardrail;
 use crate::state::StateStorage;
+use crate::stream_resumption::ReplayBuffers;
+use crate::tenancy::Tenancy;
+use crate::traffic_recording::TrafficRecorder;
+use crate::usage_export::UsageLedger;
+use crate::vertex_auth::VertexTokens;
+use crate::warmup::Warmup;
 
 /// Shared application state bundled into a single Arc-wrapped struct.
 ///
@@ -14,7 +40,9 @@ use crate::state::StateStorage;
 /// `Arc<AppState>` is cloned once and passed to the request handler.
 pub struct AppState {
     pub orchestrator_service: Arc<OrchestratorService>,
-    pub model_aliases: Option<HashMap<String, ModelAlias>>,
+    pub model_aliases: ModelAliasTable,
+    /// Retired model names, keyed by the name clients still send.
+    pub model_deprecations: HashMap<String, ModelDeprecation>,
     pub llm_providers: Arc<RwLock<LlmProviders>>,
     pub agents_list: Option<Vec<Agent>>,
     pub listeners: Vec<Listener>,
@@ -24,4 +52,53 @@ pub struct AppState {
     /// Shared HTTP client for upstream LLM requests (connection pooling / keep-alive).
     pub http_client: reqwest::Client,
     pub filter_pipeline: Arc<FilterPipeline>,
+    pub concurrency_limiter: Arc<ConcurrencyLimiter>,
+    /// Token prices for span cost attributes and cost-aware routing.
+    pub pricing: Arc<PricingTable>,
+    /// Versioned effective configuration served by the `/admin/config` API.
+    pub config_store: Arc<ConfigStore>,
+    /// Bearer token for `/admin` requests; `None` disables the admin API.
+    pub admin_api_key: Option<String>,
+    /// Tenants selected by request header; disabled when none are configured.
+    pub tenancy: Arc<Tenancy>,
+    /// Which of each provider's access keys is in use, switched by `/admin`.
+    pub access_key_slots: Arc<AccessKeySlots>,
+    /// Access tokens minted for `vertex_ai` providers with a service account key.
+    pub vertex_tokens: Arc<VertexTokens>,
+    /// AWS credentials from the default chain for Bedrock providers signing with SigV4.
+    pub bedrock_credentials: Arc<BedrockCredentials>,
+    /// Provider warm-up runs and their results, reported by `/readyz`.
+    pub warmup: Arc<Warmup>,
+    /// Providers rate limited upstream, skipped by spillover routing until their `retry-after`.
+    pub provider_cooldowns: Arc<ProviderCooldowns>,
+    /// Moving-average upstream latency, used to try the fastest healthy provider first.
+    pub provider_health: Arc<ProviderHealth>,
+    /// Inlines remote images for providers that reject image URLs.
+    pub image_inliner: Arc<ImageInliner>,
+    /// Model and turn limit for `/v1/conversations/title`; `None` disables it.
+    pub conversation_title: Option<ConversationTitleSettings>,
+    /// WASM request and response hooks from the `plugins` config section.
+    pub plugins: Arc<PluginHost>,
+    /// Complete responses cached by routes with a `response_cache`.
+    pub response_cache: Arc<ResponseCache>,
+    /// Saves or replays upstream exchanges; `None` unless `traffic_recording` is set.
+    pub traffic_recorder: Option<Arc<TrafficRecorder>>,
+    /// Staging-only fault injection into model responses.
+    pub chaos: Option<Arc<Chaos>>,
+    /// Usage totals awaiting the hourly export.
+    pub usage_ledger: Option<Arc<UsageLedger>>,
+    /// Replay windows of streams clients may resume with `Last-Event-ID`.
+    pub stream_replay: Option<Arc<ReplayBuffers>>,
+    /// Interval of SSE keep-alive comments before a stream's first token.
+    pub stream_keep_alive: Option<Duration>,
+    /// Ships finished exchanges to the `archive` sink.
+    pub archiver: Option<Arc<Archiver>>,
+    /// Publishes completion events; `None` unless `event_bus` is set.
+    pub event_bus: Option<Arc<EventBus>>,
+    /// Injects retrieved documents before routing; `None` unless `retrieval` is set.
+    pub retriever: Option<Arc<Retriever>>,
+    /// Masks or blocks credentials in prompts; `None` unless `secret_guardrail` is set.
+    pub secret_guardrail: Option<Arc<SecretGuardrail>>,
+    /// Backends for `/v1/moderations` and optional prompt screening.
+    pub moderator: Arc<Moderator>,
 }
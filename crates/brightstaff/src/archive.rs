@@ -0,0 +1,447 @@
+//! Asynchronous archival of completed exchanges.
+//!
+//! With `archive` configured, every response that finishes streaming yields a
+//! record of the exchange: the request and response bodies with
+//! `redact_fields` blanked, usage and cost, and the signal report. Records are
+//! queued without blocking the request and shipped in batches by a background
+//! task to S3, a Kafka REST Proxy or an HTTP collector. When the queue is full,
+//! or the sink rejects a batch, records are dropped rather than held.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use bytes::Bytes;
+use chrono::Utc;
+use common::configuration::{ArchiveSettings, HttpArchiveSink, KafkaRestProxy};
+use object_store::aws::AmazonS3Builder;
+use object_store::path::Path;
+use object_store::ObjectStore;
+use serde::Serialize;
+use serde_json::{json, Value};
+use tokio::sync::mpsc;
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+use crate::kafka_rest;
+use crate::signals::SignalReport;
+
+const DEFAULT_BATCH_SIZE: usize = 100;
+const DEFAULT_FLUSH_INTERVAL_MS: u64 = 5000;
+const DEFAULT_QUEUE_SIZE: usize = 10_000;
+const REDACTED: &str = "[redacted]";
+
+/// Request side of an archive record, completed once the response is over.
+pub struct ArchiveEntry {
+    request_id: String,
+    path: String,
+    model: String,
+    resolved_model: String,
+    status: u16,
+    request: Bytes,
+}
+
+impl ArchiveEntry {
+    pub fn new(
+        request_id: impl Into<String>,
+        path: impl Into<String>,
+        model: impl Into<String>,
+        resolved_model: impl Into<String>,
+        status: u16,
+        request: Bytes,
+    ) -> Self {
+        Self {
+            request_id: request_id.into(),
+            path: path.into(),
+            model: model.into(),
+            resolved_model: resolved_model.into(),
+            status,
+            request,
+        }
+    }
+}
+
+/// What the response added to an [`ArchiveEntry`].
+#[derive(Debug, Default)]
+pub struct ArchiveOutcome {
+    /// Response bytes as received, possibly cut short.
+    pub response: Vec<u8>,
+    pub response_truncated: bool,
+    pub usage: Option<ArchivedUsage>,
+    pub signals: Option<SignalReport>,
+    pub duration_ms: u64,
+    pub time_to_first_token_ms: Option<u64>,
+}
+
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct ArchivedUsage {
+    pub prompt_tokens: Option<i64>,
+    pub completion_tokens: Option<i64>,
+    pub total_tokens: Option<i64>,
+    pub cost_usd: Option<f64>,
+}
+
+struct PendingRecord {
+    entry: ArchiveEntry,
+    outcome: ArchiveOutcome,
+    archived_at: String,
+}
+
+enum Sink {
+    S3 {
+        store: Arc<dyn ObjectStore>,
+        prefix: Option<String>,
+    },
+    Kafka(KafkaRestProxy),
+    Http(HttpArchiveSink),
+}
+
+pub struct Archiver {
+    queue: mpsc::Sender<PendingRecord>,
+    dropped: AtomicU64,
+}
+
+impl Archiver {
+    /// Start the background task shipping records to the configured sink.
+    pub fn spawn(
+        settings: &ArchiveSettings,
+        http_client: reqwest::Client,
+    ) -> Result<Arc<Self>, Box<dyn std::error::Error + Send + Sync>> {
+        let sink = if let Some(url) = &settings.s3_url {
+            let location = url.strip_prefix("s3://").unwrap_or(url);
+            let (bucket, prefix) = match location.split_once('/') {
+                Some((bucket, prefix)) => (bucket, Some(prefix.trim_matches('/').to_string())),
+                None => (location, None),
+            };
+            Sink::S3 {
+                store: Arc::new(
+                    AmazonS3Builder::from_env()
+                        .with_bucket_name(bucket)
+                        .build()?,
+                ),
+                prefix: prefix.filter(|p| !p.is_empty()),
+            }
+        } else if let Some(kafka) = &settings.kafka {
+            Sink::Kafka(kafka.clone())
+        } else if let Some(http) = &settings.http {
+            Sink::Http(http.clone())
+        } else {
+            return Err("archive needs exactly one of s3_url, kafka or http".into());
+        };
+
+        let (queue, records) =
+            mpsc::channel(settings.queue_size.unwrap_or(DEFAULT_QUEUE_SIZE).max(1));
+        let shipper = Shipper {
+            sink,
+            http_client,
+            redact_fields: settings.redact_fields.clone().unwrap_or_default(),
+            batch_size: settings.batch_size.unwrap_or(DEFAULT_BATCH_SIZE).max(1),
+            flush_interval: Duration::from_millis(
+                settings
+                    .flush_interval_ms
+                    .unwrap_or(DEFAULT_FLUSH_INTERVAL_MS)
+                    .max(1),
+            ),
+        };
+        tokio::spawn(shipper.run(records));
+        Ok(Arc::new(Self {
+            queue,
+            dropped: AtomicU64::new(0),
+        }))
+    }
+
+    /// Queue a finished exchange without waiting; dropped if the queue is full.
+    pub fn submit(&self, entry: ArchiveEntry, outcome: ArchiveOutcome) {
+        let record = PendingRecord {
+            entry,
+            outcome,
+            archived_at: Utc::now().to_rfc3339(),
+        };
+        if self.queue.try_send(record).is_err() {
+            let dropped = self.dropped.fetch_add(1, Ordering::Relaxed) + 1;
+            if dropped.is_power_of_two() {
+                warn!(dropped, "archive queue full, dropping records");
+            }
+        }
+    }
+}
+
+struct Shipper {
+    sink: Sink,
+    http_client: reqwest::Client,
+    redact_fields: Vec<String>,
+    batch_size: usize,
+    flush_interval: Duration,
+}
+
+impl Shipper {
+    async fn run(self, mut records: mpsc::Receiver<PendingRecord>) {
+        let mut batch: Vec<Value> = Vec::with_capacity(self.batch_size);
+        let mut ticker = tokio::time::interval(self.flush_interval);
+        loop {
+            tokio::select! {
+                record = records.recv() => match record {
+                    Some(record) => {
+                        batch.push(to_json(record, &self.redact_fields));
+                        if batch.len() >= self.batch_size {
+                            self.flush(&mut batch).await;
+                        }
+                    }
+                    None => {
+                        self.flush(&mut batch).await;
+                        return;
+                    }
+                },
+                _ = ticker.tick() => self.flush(&mut batch).await,
+            }
+        }
+    }
+
+    async fn flush(&self, batch: &mut Vec<Value>) {
+        if batch.is_empty() {
+            return;
+        }
+        let records = std::mem::take(batch);
+        match self.ship(&records).await {
+            Ok(()) => debug!(records = records.len(), "archived exchanges"),
+            Err(error) => {
+                warn!(error = %error, records = records.len(), "failed to archive exchanges")
+            }
+        }
+    }
+
+    async fn ship(&self, records: &[Value]) -> Result<(), String> {
+        match &self.sink {
+            Sink::S3 { store, prefix } => {
+                let now = Utc::now();
+                let name = format!(
+                    "{}/{}-{}.jsonl",
+                    now.format("%Y-%m-%d"),
+                    now.format("%H%M%S"),
+                    Uuid::new_v4().simple()
+                );
+                let path = match prefix {
+                    Some(prefix) => Path::from(format!("{prefix}/{name}")),
+                    None => Path::from(name),
+                };
+                store
+                    .put(&path, Bytes::from(json_lines(records)).into())
+                    .await
+                    .map(|_| ())
+                    .map_err(|e| e.to_string())
+            }
+            Sink::Kafka(kafka) => {
+                let records = records
+                    .iter()
+                    .map(|record| (record["request_id"].clone(), record.clone()));
+                kafka_rest::produce(&self.http_client, kafka, records).await
+            }
+            Sink::Http(http) => {
+                let mut request = self
+                    .http_client
+                    .post(&http.url)
+                    .header(reqwest::header::CONTENT_TYPE, "application/x-ndjson")
+                    .body(json_lines(records));
+                for (name, value) in http.headers.iter().flatten() {
+                    request = request.header(name, value);
+                }
+                send(request).await
+            }
+        }
+    }
+}
+
+async fn send(request: reqwest::RequestBuilder) -> Result<(), String> {
+    let response = request.send().await.map_err(|e| e.to_string())?;
+    if response.status().is_success() {
+        Ok(())
+    } else {
+        Err(format!("sink answered {}", response.status()))
+    }
+}
+
+fn json_lines(records: &[Value]) -> String {
+    records.iter().map(|record| format!("{record}\n")).collect()
+}
+
+fn to_json(record: PendingRecord, redact_fields: &[String]) -> Value {
+    let PendingRecord {
+        entry,
+        outcome,
+        archived_at,
+    } = record;
+    let mut request = body_json(&entry.request);
+    let mut response = response_json(&outcome.response);
+    redact(&mut request, redact_fields);
+    redact(&mut response, redact_fields);
+    json!({
+        "request_id": entry.request_id,
+        "archived_at": archived_at,
+        "path": entry.path,
+        "model": entry.model,
+        "resolved_model": entry.resolved_model,
+        "status": entry.status,
+        "duration_ms": outcome.duration_ms,
+        "time_to_first_token_ms": outcome.time_to_first_token_ms,
+        "request": request,
+        "response": response,
+        "response_truncated": outcome.response_truncated,
+        "usage": outcome.usage,
+        "signals": outcome.signals,
+    })
+}
+
+fn body_json(body: &[u8]) -> Value {
+    serde_json::from_slice(body)
+        .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(body).into_owned()))
+}
+
+/// A JSON response as is; a streamed one as the list of its event payloads.
+fn response_json(body: &[u8]) -> Value {
+    if let Ok(value) = serde_json::from_slice(body) {
+        return value;
+    }
+    let text = String::from_utf8_lossy(body);
+    let events: Vec<Value> = text
+        .lines()
+        .filter_map(|line| line.strip_prefix("data:"))
+        .map(str::trim)
+        .filter(|data| !data.is_empty() && *data != "[DONE]")
+        .map(|data| serde_json::from_str(data).unwrap_or_else(|_| Value::String(data.to_string())))
+        .collect();
+    if events.is_empty() {
+        Value::String(text.into_owned())
+    } else {
+        Value::Array(events)
+    }
+}
+
+/// Blank `fields` wherever they appear in `value`.
+fn redact(value: &mut Value, fields: &[String]) {
+    match value {
+        Value::Object(map) => {
+            for (name, field) in map.iter_mut() {
+                if fields.iter().any(|f| f == name) {
+                    *field = Value::String(REDACTED.to_string());
+                } else {
+                    redact(field, fields);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(|item| redact(item, fields)),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyper::service::service_fn;
+    use hyper_util::rt::TokioIo;
+    use tokio::net::TcpListener;
+
+    fn entry() -> ArchiveEntry {
+        ArchiveEntry::new(
+            "req-1",
+            "/v1/chat/completions",
+            "fast",
+            "openai/gpt-4o-mini",
+            200,
+            Bytes::from(
+                r#"{"model":"fast","user":"alice@example.com","messages":[{"role":"user","content":"hi","metadata":{"user":"alice"}}]}"#,
+            ),
+        )
+    }
+
+    #[test]
+    fn records_are_sanitized_and_streams_are_split_into_events() {
+        let outcome = ArchiveOutcome {
+            response: b"data: {\"choices\":[{\"delta\":{\"content\":\"Hel\"}}]}\n\ndata: {\"choices\":[{\"delta\":{\"content\":\"lo\"}}],\"usage\":{\"prompt_tokens\":3}}\n\ndata: [DONE]\n\n".to_vec(),
+            usage: Some(ArchivedUsage {
+                prompt_tokens: Some(3),
+                completion_tokens: Some(2),
+                total_tokens: Some(5),
+                cost_usd: Some(0.00001),
+            }),
+            duration_ms: 120,
+            ..Default::default()
+        };
+        let record = to_json(
+            PendingRecord {
+                entry: entry(),
+                outcome,
+                archived_at: "2026-10-16T00:00:00Z".to_string(),
+            },
+            &["user".to_string()],
+        );
+        assert_eq!(record["request"]["user"], REDACTED);
+        assert_eq!(
+            record["request"]["messages"][0]["metadata"]["user"],
+            REDACTED
+        );
+        assert_eq!(record["request"]["messages"][0]["content"], "hi");
+        assert_eq!(record["response"].as_array().unwrap().len(), 2);
+        assert_eq!(
+            record["response"][0]["choices"][0]["delta"]["content"],
+            "Hel"
+        );
+        assert_eq!(record["usage"]["total_tokens"], 5);
+        assert_eq!(record["resolved_model"], "openai/gpt-4o-mini");
+        assert!(record["signals"].is_null());
+    }
+
+    #[tokio::test]
+    async fn batches_are_posted_to_the_http_collector() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let (received, mut bodies) = mpsc::channel::<(String, String)>(4);
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let service = service_fn(move |req: hyper::Request<hyper::body::Incoming>| {
+                let received = received.clone();
+                async move {
+                    let token = req.headers()["x-collector-token"]
+                        .to_str()
+                        .unwrap()
+                        .to_string();
+                    let body = http_body_util::BodyExt::collect(req.into_body())
+                        .await
+                        .unwrap()
+                        .to_bytes();
+                    received
+                        .send((token, String::from_utf8(body.to_vec()).unwrap()))
+                        .await
+                        .unwrap();
+                    Ok::<_, hyper::Error>(hyper::Response::new(
+                        http_body_util::Empty::<Bytes>::new(),
+                    ))
+                }
+            });
+            hyper::server::conn::http1::Builder::new()
+                .serve_connection(TokioIo::new(stream), service)
+                .await
+                .unwrap();
+        });
+
+        let settings = ArchiveSettings {
+            http: Some(HttpArchiveSink {
+                url: format!("http://{address}/ingest"),
+                headers: Some([("x-collector-token".to_string(), "s3cret".to_string())].into()),
+            }),
+            batch_size: Some(2),
+            ..Default::default()
+        };
+        let archiver = Archiver::spawn(&settings, reqwest::Client::new()).unwrap();
+        archiver.submit(entry(), ArchiveOutcome::default());
+        archiver.submit(entry(), ArchiveOutcome::default());
+
+        let (token, body) = bodies.recv().await.unwrap();
+        assert_eq!(token, "s3cret");
+        let lines: Vec<Value> = body
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["request_id"], "req-1");
+    }
+}
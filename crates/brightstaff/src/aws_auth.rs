@@ -0,0 +1,299 @@
+//! AWS credentials for `amazon_bedrock` providers that sign with SigV4.
+//!
+//! Providers with `amazon_bedrock.auth: sigv4` and no configured keys use the
+//! default AWS credential chain: environment variables, the shared
+//! credentials file, the ECS container endpoint, then the EC2 instance
+//! metadata service. A background task resolves credentials at startup and
+//! again every few minutes so temporary ones are replaced before they
+//! expire. Requests carry them to the LLM gateway in
+//! [`ARCH_UPSTREAM_AWS_CREDENTIALS_HEADER`]; the gateway signs the final
+//! upstream request with them.
+
+use std::collections::HashSet;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use common::configuration::LlmProvider;
+use common::consts::ARCH_UPSTREAM_AWS_CREDENTIALS_HEADER;
+use hermesllm::clients::aws_sigv4::AwsCredentials;
+use serde::Deserialize;
+use tracing::{info, warn};
+
+/// Temporary credentials are rotated at least five minutes before expiry.
+const REFRESH_INTERVAL: Duration = Duration::from_secs(240);
+/// Wait before trying again after no source had credentials.
+const RETRY_INTERVAL: Duration = Duration::from_secs(30);
+/// Metadata endpoints answer quickly or not at all.
+const METADATA_TIMEOUT: Duration = Duration::from_secs(2);
+
+const ECS_CREDENTIALS_HOST: &str = "http://169.254.170.2";
+const IMDS_ENDPOINT: &str = "http://169.254.169.254";
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+#[derive(Debug, Default)]
+pub struct BedrockCredentials {
+    /// Providers whose requests carry the chain's credentials.
+    providers: HashSet<String>,
+    credentials: RwLock<Option<AwsCredentials>>,
+}
+
+impl BedrockCredentials {
+    /// Start resolving credentials from the default chain when any provider
+    /// signs with SigV4 without configured keys.
+    pub fn spawn(providers: &[LlmProvider], http_client: reqwest::Client) -> Arc<Self> {
+        let providers: HashSet<String> = providers
+            .iter()
+            .filter(|provider| {
+                provider.bedrock_sigv4().is_some_and(|bedrock| {
+                    bedrock.access_key_id.is_none() || bedrock.secret_access_key.is_none()
+                })
+            })
+            .map(|provider| provider.name.clone())
+            .collect();
+        let has_providers = !providers.is_empty();
+        let credentials = Arc::new(BedrockCredentials {
+            providers,
+            ..Default::default()
+        });
+        if has_providers {
+            info!("resolving aws credentials from the default chain for bedrock sigv4");
+            tokio::spawn(Arc::clone(&credentials).refresh(http_client));
+        }
+        credentials
+    }
+
+    /// Set the credentials header for `provider` on an upstream request,
+    /// replacing any value the client sent.
+    pub fn apply(&self, provider: &str, headers: &mut hyper::HeaderMap) {
+        headers.remove(ARCH_UPSTREAM_AWS_CREDENTIALS_HEADER);
+        if !self.providers.contains(provider) {
+            return;
+        }
+        let credentials = self.credentials.read().unwrap();
+        if let Some(value) = credentials
+            .as_ref()
+            .and_then(|credentials| serde_json::to_string(credentials).ok())
+            .and_then(|json| hyper::header::HeaderValue::from_str(&json).ok())
+        {
+            headers.insert(ARCH_UPSTREAM_AWS_CREDENTIALS_HEADER, value);
+        }
+    }
+
+    async fn refresh(self: Arc<Self>, http_client: reqwest::Client) {
+        loop {
+            let wait = match resolve(&http_client).await {
+                Ok(credentials) => {
+                    *self.credentials.write().unwrap() = Some(credentials);
+                    REFRESH_INTERVAL
+                }
+                Err(error) => {
+                    warn!(%error, "failed to resolve aws credentials");
+                    RETRY_INTERVAL
+                }
+            };
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+async fn resolve(http_client: &reqwest::Client) -> Result<AwsCredentials, BoxError> {
+    let env = |name: &str| std::env::var(name).ok().filter(|value| !value.is_empty());
+
+    if let Some(credentials) = from_env(env) {
+        return Ok(credentials);
+    }
+
+    let credentials_file = env("AWS_SHARED_CREDENTIALS_FILE")
+        .or_else(|| env("HOME").map(|home| format!("{}/.aws/credentials", home)));
+    if let Some(contents) = credentials_file.and_then(|path| std::fs::read_to_string(path).ok()) {
+        let profile = env("AWS_PROFILE").unwrap_or_else(|| "default".to_string());
+        if let Some(credentials) = from_credentials_file(&contents, &profile) {
+            return Ok(credentials);
+        }
+    }
+
+    let container_uri = env("AWS_CONTAINER_CREDENTIALS_RELATIVE_URI")
+        .map(|path| format!("{}{}", ECS_CREDENTIALS_HOST, path))
+        .or_else(|| env("AWS_CONTAINER_CREDENTIALS_FULL_URI"));
+    if let Some(uri) = container_uri {
+        let mut request = http_client.get(&uri).timeout(METADATA_TIMEOUT);
+        if let Some(token) = env("AWS_CONTAINER_AUTHORIZATION_TOKEN") {
+            request = request.header("authorization", token);
+        }
+        let body = request.send().await?.error_for_status()?.bytes().await?;
+        return Ok(serde_json::from_slice::<MetadataCredentials>(&body)?.into());
+    }
+
+    if env("AWS_EC2_METADATA_DISABLED").is_some_and(|value| value.eq_ignore_ascii_case("true")) {
+        return Err("no aws credentials in the environment, credentials file or container".into());
+    }
+    from_instance_metadata(http_client).await
+}
+
+fn from_env(env: impl Fn(&str) -> Option<String>) -> Option<AwsCredentials> {
+    Some(AwsCredentials {
+        access_key_id: env("AWS_ACCESS_KEY_ID")?,
+        secret_access_key: env("AWS_SECRET_ACCESS_KEY")?,
+        session_token: env("AWS_SESSION_TOKEN"),
+    })
+}
+
+/// Keys of `profile` in an INI-style shared credentials file.
+fn from_credentials_file(contents: &str, profile: &str) -> Option<AwsCredentials> {
+    let mut in_profile = false;
+    let mut access_key_id = None;
+    let mut secret_access_key = None;
+    let mut session_token = None;
+    for line in contents.lines().map(str::trim) {
+        if line.starts_with('#') || line.starts_with(';') || line.is_empty() {
+            continue;
+        }
+        if let Some(section) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            in_profile = section.trim() == profile;
+            continue;
+        }
+        if !in_profile {
+            continue;
+        }
+        if let Some((key, value)) = line.split_once('=') {
+            let value = Some(value.trim().to_string());
+            match key.trim() {
+                "aws_access_key_id" => access_key_id = value,
+                "aws_secret_access_key" => secret_access_key = value,
+                "aws_session_token" => session_token = value,
+                _ => {}
+            }
+        }
+    }
+    Some(AwsCredentials {
+        access_key_id: access_key_id?,
+        secret_access_key: secret_access_key?,
+        session_token,
+    })
+}
+
+/// Instance profile credentials through IMDSv2.
+async fn from_instance_metadata(http_client: &reqwest::Client) -> Result<AwsCredentials, BoxError> {
+    let token = http_client
+        .put(format!("{}/latest/api/token", IMDS_ENDPOINT))
+        .header("x-aws-ec2-metadata-token-ttl-seconds", "21600")
+        .timeout(METADATA_TIMEOUT)
+        .send()
+        .await?
+        .error_for_status()?
+        .text()
+        .await?;
+    let credentials_path = format!(
+        "{}/latest/meta-data/iam/security-credentials/",
+        IMDS_ENDPOINT
+    );
+    let role = http_client
+        .get(&credentials_path)
+        .header("x-aws-ec2-metadata-token", &token)
+        .timeout(METADATA_TIMEOUT)
+        .send()
+        .await?
+        .error_for_status()?
+        .text()
+        .await?;
+    let role = role.lines().next().unwrap_or_default().trim();
+    let body = http_client
+        .get(format!("{}{}", credentials_path, role))
+        .header("x-aws-ec2-metadata-token", &token)
+        .timeout(METADATA_TIMEOUT)
+        .send()
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?;
+    Ok(serde_json::from_slice::<MetadataCredentials>(&body)?.into())
+}
+
+/// Credentials document served by the ECS and EC2 metadata endpoints.
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct MetadataCredentials {
+    access_key_id: String,
+    secret_access_key: String,
+    token: Option<String>,
+}
+
+impl From<MetadataCredentials> for AwsCredentials {
+    fn from(credentials: MetadataCredentials) -> Self {
+        AwsCredentials {
+            access_key_id: credentials.access_key_id,
+            secret_access_key: credentials.secret_access_key,
+            session_token: credentials.token,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn credentials_file_profiles() {
+        let contents = "\
+[default]
+aws_access_key_id = AKIDDEFAULT
+aws_secret_access_key = default-secret
+
+# temporary credentials
+[bedrock]
+aws_access_key_id=AKIDBEDROCK
+aws_secret_access_key=bedrock-secret
+aws_session_token=bedrock-token
+";
+        let default = from_credentials_file(contents, "default").unwrap();
+        assert_eq!(default.access_key_id, "AKIDDEFAULT");
+        assert_eq!(default.session_token, None);
+
+        let bedrock = from_credentials_file(contents, "bedrock").unwrap();
+        assert_eq!(bedrock.secret_access_key, "bedrock-secret");
+        assert_eq!(bedrock.session_token.as_deref(), Some("bedrock-token"));
+
+        assert!(from_credentials_file(contents, "missing").is_none());
+    }
+
+    #[test]
+    fn env_credentials_need_both_keys() {
+        let env = |name: &str| match name {
+            "AWS_ACCESS_KEY_ID" => Some("AKIDENV".to_string()),
+            _ => None,
+        };
+        assert!(from_env(env).is_none());
+
+        let env = |name: &str| match name {
+            "AWS_ACCESS_KEY_ID" => Some("AKIDENV".to_string()),
+            "AWS_SECRET_ACCESS_KEY" => Some("env-secret".to_string()),
+            _ => None,
+        };
+        assert_eq!(from_env(env).unwrap().access_key_id, "AKIDENV");
+    }
+
+    #[test]
+    fn apply_replaces_client_supplied_credentials() {
+        let bedrock = BedrockCredentials {
+            providers: HashSet::from(["amazon_bedrock/claude".to_string()]),
+            credentials: RwLock::new(Some(AwsCredentials {
+                access_key_id: "AKIDCHAIN".to_string(),
+                secret_access_key: "chain-secret".to_string(),
+                session_token: None,
+            })),
+        };
+
+        let mut headers = hyper::HeaderMap::new();
+        headers.insert(ARCH_UPSTREAM_AWS_CREDENTIALS_HEADER, "{}".parse().unwrap());
+        bedrock.apply("amazon_bedrock/claude", &mut headers);
+        assert_eq!(
+            headers[ARCH_UPSTREAM_AWS_CREDENTIALS_HEADER],
+            r#"{"access_key_id":"AKIDCHAIN","secret_access_key":"chain-secret"}"#
+        );
+
+        headers.insert(ARCH_UPSTREAM_AWS_CREDENTIALS_HEADER, "{}".parse().unwrap());
+        bedrock.apply("openai/gpt-4o", &mut headers);
+        assert!(headers.get(ARCH_UPSTREAM_AWS_CREDENTIALS_HEADER).is_none());
+    }
+}
@@ -0,0 +1,411 @@
+//! `brightstaff bench`: load generation for capacity planning.
+//!
+//! Replays a corpus of request bodies (one JSON object per line) against a
+//! gateway endpoint at a fixed concurrency and reports throughput and the
+//! latency and time-to-first-token percentiles. Pointed at a `mock` provider
+//! it measures the gateway's own overhead; pointed at a real one, what a
+//! rollout can expect.
+
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use bytes::Bytes;
+use futures::StreamExt;
+use hyper::header::{HeaderName, HeaderValue, CONTENT_TYPE};
+use serde_json::Value;
+
+const DEFAULT_URL: &str = "http://localhost:12000/v1/chat/completions";
+const DEFAULT_CONCURRENCY: usize = 8;
+
+pub const USAGE: &str = "usage: brightstaff bench --corpus <requests.jsonl> [--url <endpoint>] \
+[--concurrency <n>] [--requests <n>] [--header <name: value>]...";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchOptions {
+    pub url: String,
+    pub corpus: String,
+    pub concurrency: usize,
+    /// Requests to send in total, cycling through the corpus. Defaults to
+    /// one pass over it.
+    pub requests: Option<usize>,
+    pub headers: Vec<(String, String)>,
+}
+
+impl BenchOptions {
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut args = args.into_iter();
+        let mut url = DEFAULT_URL.to_string();
+        let mut corpus = None;
+        let mut concurrency = DEFAULT_CONCURRENCY;
+        let mut requests = None;
+        let mut headers = Vec::new();
+        while let Some(flag) = args.next() {
+            let mut value = || args.next().ok_or_else(|| format!("{flag} needs a value"));
+            match flag.as_str() {
+                "--url" => url = value()?,
+                "--corpus" => corpus = Some(value()?),
+                "--concurrency" => concurrency = positive(&flag, &value()?)?,
+                "--requests" => requests = Some(positive(&flag, &value()?)?),
+                "--header" => {
+                    let header = value()?;
+                    let (name, value) = header
+                        .split_once(':')
+                        .ok_or_else(|| format!("--header expects `name: value`, got '{header}'"))?;
+                    headers.push((name.trim().to_string(), value.trim().to_string()));
+                }
+                other => return Err(format!("unknown argument '{other}'")),
+            }
+        }
+        Ok(Self {
+            url,
+            corpus: corpus.ok_or("--corpus is required")?,
+            concurrency,
+            requests,
+            headers,
+        })
+    }
+}
+
+fn positive(flag: &str, value: &str) -> Result<usize, String> {
+    value
+        .parse::<usize>()
+        .ok()
+        .filter(|n| *n > 0)
+        .ok_or_else(|| format!("{flag} expects a positive integer, got '{value}'"))
+}
+
+/// One request's outcome.
+#[derive(Debug, Clone)]
+struct Sample {
+    status: Option<u16>,
+    latency: Duration,
+    /// Time until the first byte of the response body.
+    ttft: Option<Duration>,
+    output_tokens: u64,
+}
+
+impl Sample {
+    fn succeeded(&self) -> bool {
+        self.status.is_some_and(|s| (200..300).contains(&s))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Percentiles {
+    pub p50: Duration,
+    pub p90: Duration,
+    pub p99: Duration,
+}
+
+impl Percentiles {
+    /// Nearest-rank percentiles, `None` for no samples.
+    fn of(mut samples: Vec<Duration>) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+        samples.sort();
+        let rank = |p: f64| samples[((p * samples.len() as f64).ceil() as usize).max(1) - 1];
+        Some(Self {
+            p50: rank(0.50),
+            p90: rank(0.90),
+            p99: rank(0.99),
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BenchReport {
+    pub requests: usize,
+    pub failures: usize,
+    pub elapsed: Duration,
+    pub latency: Option<Percentiles>,
+    pub ttft: Option<Percentiles>,
+    pub output_tokens: u64,
+}
+
+impl BenchReport {
+    fn from_samples(samples: &[Sample], elapsed: Duration) -> Self {
+        let succeeded: Vec<&Sample> = samples.iter().filter(|s| s.succeeded()).collect();
+        Self {
+            requests: samples.len(),
+            failures: samples.len() - succeeded.len(),
+            elapsed,
+            latency: Percentiles::of(succeeded.iter().map(|s| s.latency).collect()),
+            ttft: Percentiles::of(succeeded.iter().filter_map(|s| s.ttft).collect()),
+            output_tokens: succeeded.iter().map(|s| s.output_tokens).sum(),
+        }
+    }
+
+    fn per_second(&self, count: f64) -> f64 {
+        count / self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+}
+
+impl fmt::Display for BenchReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "requests:    {} ({} failed) in {:.2}s",
+            self.requests,
+            self.failures,
+            self.elapsed.as_secs_f64()
+        )?;
+        writeln!(
+            f,
+            "throughput:  {:.1} req/s, {:.1} output tokens/s",
+            self.per_second((self.requests - self.failures) as f64),
+            self.per_second(self.output_tokens as f64)
+        )?;
+        for (name, percentiles) in [("latency", self.latency), ("ttft", self.ttft)] {
+            match percentiles {
+                Some(p) => writeln!(
+                    f,
+                    "{:<12} p50 {}ms  p90 {}ms  p99 {}ms",
+                    format!("{name}:"),
+                    p.p50.as_millis(),
+                    p.p90.as_millis(),
+                    p.p99.as_millis()
+                )?,
+                None => writeln!(f, "{:<12} no successful requests", format!("{name}:"))?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Entry point for `brightstaff bench`; returns the process exit code.
+pub async fn main(args: impl IntoIterator<Item = String>) -> i32 {
+    let options = match BenchOptions::parse(args) {
+        Ok(options) => options,
+        Err(error) => {
+            eprintln!("{error}\n{USAGE}");
+            return 2;
+        }
+    };
+    match run(&options).await {
+        Ok(report) => {
+            print!("{report}");
+            i32::from(report.failures > 0)
+        }
+        Err(error) => {
+            eprintln!("bench failed: {error}");
+            1
+        }
+    }
+}
+
+pub async fn run(options: &BenchOptions) -> Result<BenchReport, String> {
+    let contents = std::fs::read_to_string(&options.corpus)
+        .map_err(|e| format!("failed to read {}: {e}", options.corpus))?;
+    let corpus = parse_corpus(&contents)?;
+    let mut headers = hyper::HeaderMap::new();
+    for (name, value) in &options.headers {
+        headers.insert(
+            HeaderName::try_from(name.as_str()).map_err(|e| format!("header '{name}': {e}"))?,
+            HeaderValue::try_from(value.as_str()).map_err(|e| format!("header '{name}': {e}"))?,
+        );
+    }
+    headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+
+    let total = options.requests.unwrap_or(corpus.len());
+    let client = reqwest::Client::new();
+    let corpus = Arc::new(corpus);
+    let next = Arc::new(AtomicUsize::new(0));
+    let started = Instant::now();
+    let workers: Vec<_> = (0..options.concurrency.min(total))
+        .map(|_| {
+            let (client, corpus, next) = (client.clone(), Arc::clone(&corpus), Arc::clone(&next));
+            let (url, headers) = (options.url.clone(), headers.clone());
+            tokio::spawn(async move {
+                let mut samples = Vec::new();
+                loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    if index >= total {
+                        break samples;
+                    }
+                    let body = corpus[index % corpus.len()].clone();
+                    samples.push(send(&client, &url, &headers, body).await);
+                }
+            })
+        })
+        .collect();
+
+    let mut samples = Vec::with_capacity(total);
+    for worker in workers {
+        samples.extend(worker.await.map_err(|e| e.to_string())?);
+    }
+    Ok(BenchReport::from_samples(&samples, started.elapsed()))
+}
+
+fn parse_corpus(contents: &str) -> Result<Vec<Bytes>, String> {
+    let corpus: Vec<Bytes> = contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            serde_json::from_str::<Value>(line)
+                .map(|_| Bytes::from(line.to_string()))
+                .map_err(|e| format!("corpus line {}: {e}", index + 1))
+        })
+        .collect::<Result<_, _>>()?;
+    if corpus.is_empty() {
+        return Err("corpus has no requests".to_string());
+    }
+    Ok(corpus)
+}
+
+async fn send(
+    client: &reqwest::Client,
+    url: &str,
+    headers: &hyper::HeaderMap,
+    body: Bytes,
+) -> Sample {
+    let start = Instant::now();
+    let failed = |status| Sample {
+        status,
+        latency: start.elapsed(),
+        ttft: None,
+        output_tokens: 0,
+    };
+    let response = match client
+        .post(url)
+        .headers(headers.clone())
+        .body(body)
+        .send()
+        .await
+    {
+        Ok(response) => response,
+        Err(_) => return failed(None),
+    };
+    let status = response.status().as_u16();
+    let mut ttft = None;
+    let mut received = Vec::new();
+    let mut stream = response.bytes_stream();
+    while let Some(chunk) = stream.next().await {
+        let Ok(chunk) = chunk else {
+            return failed(Some(status));
+        };
+        if ttft.is_none() && !chunk.is_empty() {
+            ttft = Some(start.elapsed());
+        }
+        received.extend_from_slice(&chunk);
+    }
+    Sample {
+        status: Some(status),
+        latency: start.elapsed(),
+        ttft,
+        output_tokens: output_tokens(&received),
+    }
+}
+
+/// Output tokens reported in a JSON response or in the `usage` of any event
+/// of an SSE stream (the largest, since some APIs report running totals).
+fn output_tokens(body: &[u8]) -> u64 {
+    let usage_tokens = |value: &Value| {
+        let usage = value
+            .get("usage")
+            .or_else(|| value.pointer("/response/usage"))
+            .or_else(|| value.pointer("/message/usage"))?;
+        usage
+            .get("completion_tokens")
+            .or_else(|| usage.get("output_tokens"))
+            .and_then(Value::as_u64)
+    };
+    if let Ok(value) = serde_json::from_slice::<Value>(body) {
+        return usage_tokens(&value).unwrap_or(0);
+    }
+    String::from_utf8_lossy(body)
+        .lines()
+        .filter_map(|line| line.strip_prefix("data:"))
+        .filter_map(|data| serde_json::from_str::<Value>(data.trim()).ok())
+        .filter_map(|event| usage_tokens(&event))
+        .max()
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &str) -> Vec<String> {
+        args.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn parses_options() {
+        let options = BenchOptions::parse(args(
+            "--corpus requests.jsonl --concurrency 32 --requests 1000 --header x-api-key:secret",
+        ))
+        .unwrap();
+        assert_eq!(options.url, DEFAULT_URL);
+        assert_eq!(options.concurrency, 32);
+        assert_eq!(options.requests, Some(1000));
+        assert_eq!(
+            options.headers,
+            vec![("x-api-key".to_string(), "secret".to_string())]
+        );
+
+        assert!(BenchOptions::parse(args("--concurrency 4")).is_err());
+        assert!(BenchOptions::parse(args("--corpus c.jsonl --concurrency 0")).is_err());
+        assert!(BenchOptions::parse(args("--corpus")).is_err());
+    }
+
+    #[test]
+    fn percentiles_use_nearest_rank() {
+        let samples = (1..=100).map(Duration::from_millis).collect();
+        assert_eq!(
+            Percentiles::of(samples),
+            Some(Percentiles {
+                p50: Duration::from_millis(50),
+                p90: Duration::from_millis(90),
+                p99: Duration::from_millis(99),
+            })
+        );
+        assert_eq!(Percentiles::of(Vec::new()), None);
+    }
+
+    #[test]
+    fn counts_output_tokens_from_json_and_streams() {
+        assert_eq!(
+            output_tokens(br#"{"usage":{"prompt_tokens":9,"completion_tokens":12}}"#),
+            12
+        );
+        let stream = b"event: message_start\ndata: {\"type\":\"message_start\",\"message\":{\"usage\":{\"output_tokens\":1}}}\n\n\
+            event: message_delta\ndata: {\"type\":\"message_delta\",\"usage\":{\"output_tokens\":7}}\n\n";
+        assert_eq!(output_tokens(stream), 7);
+        assert_eq!(output_tokens(b"data: [DONE]\n\n"), 0);
+    }
+
+    #[tokio::test]
+    async fn runs_the_corpus_at_concurrency() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/v1/chat/completions")
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"usage":{"completion_tokens":5}}"#)
+            .expect(6)
+            .create_async()
+            .await;
+        let corpus = std::env::temp_dir().join(format!("bench-{}.jsonl", uuid::Uuid::new_v4()));
+        std::fs::write(&corpus, "{\"model\":\"a\"}\n\n{\"model\":\"b\"}\n").unwrap();
+
+        let report = run(&BenchOptions {
+            url: format!("{}/v1/chat/completions", server.url()),
+            corpus: corpus.to_string_lossy().into_owned(),
+            concurrency: 4,
+            requests: Some(6),
+            headers: Vec::new(),
+        })
+        .await
+        .unwrap();
+        std::fs::remove_file(corpus).unwrap();
+
+        mock.assert_async().await;
+        assert_eq!((report.requests, report.failures), (6, 0));
+        assert_eq!(report.output_tokens, 30);
+        assert!(report.latency.is_some() && report.ttft.is_some());
+        assert!(report.to_string().contains("6 (0 failed)"));
+    }
+}
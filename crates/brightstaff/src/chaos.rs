@@ -0,0 +1,206 @@
+//! Fault injection for staging.
+//!
+//! With a `chaos` config section, model responses are at random delayed,
+//! replaced with an error, or (for event streams) cut off or corrupted with a
+//! malformed SSE event. Faults are injected where brightstaff receives the
+//! upstream response, so its retries, spillover and fallbacks react to them
+//! exactly as they would to a misbehaving provider.
+
+use std::time::Duration;
+
+use bytes::Bytes;
+use common::configuration::ChaosSettings;
+use common::utils::parse_duration_ms;
+use futures::StreamExt;
+use hyper::header::{CONTENT_LENGTH, CONTENT_TYPE};
+use hyper::StatusCode;
+use serde_json::json;
+use tracing::info;
+
+const DEFAULT_ERROR_STATUS: u16 = 503;
+/// Chunks a truncated or corrupted stream delivers intact first.
+const CHUNKS_BEFORE_FAULT: usize = 2;
+const MALFORMED_EVENT: &str = "data: {\"choices\": [{\"delta\": {\"content\": \"\n\n";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StreamFault {
+    Truncate,
+    Malformed,
+}
+
+pub struct Chaos {
+    settings: ChaosSettings,
+    latency: Duration,
+}
+
+impl Chaos {
+    pub fn new(settings: &ChaosSettings) -> Self {
+        Self {
+            latency: Duration::from_millis(
+                settings
+                    .latency
+                    .as_deref()
+                    .and_then(parse_duration_ms)
+                    .unwrap_or(0),
+            ),
+            settings: settings.clone(),
+        }
+    }
+
+    pub async fn apply(&self, response: reqwest::Response) -> reqwest::Response {
+        if roll(self.settings.latency_rate) {
+            info!(
+                delay_ms = self.latency.as_millis() as u64,
+                "chaos: delaying response"
+            );
+            tokio::time::sleep(self.latency).await;
+        }
+        if roll(self.settings.error_rate) {
+            let status = self.settings.error_status.unwrap_or(DEFAULT_ERROR_STATUS);
+            info!(status, "chaos: replacing response with an error");
+            return error_response(status);
+        }
+
+        let is_stream = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.starts_with("text/event-stream"));
+        if !is_stream || !response.status().is_success() {
+            return response;
+        }
+        let fault = if roll(self.settings.truncate_rate) {
+            StreamFault::Truncate
+        } else if roll(self.settings.malformed_sse_rate) {
+            StreamFault::Malformed
+        } else {
+            return response;
+        };
+        info!(fault = ?fault, "chaos: corrupting stream");
+        corrupt_stream(response, fault)
+    }
+}
+
+fn roll(rate: Option<f64>) -> bool {
+    rate.is_some_and(|rate| rand::random::<f64>() < rate)
+}
+
+fn error_response(status: u16) -> reqwest::Response {
+    let status = StatusCode::from_u16(status).unwrap_or(StatusCode::SERVICE_UNAVAILABLE);
+    let body = json!({
+        "error": {"message": "fault injected by chaos testing", "type": "chaos", "code": status.as_u16()},
+    });
+    let response = hyper::http::Response::builder()
+        .status(status)
+        .header(CONTENT_TYPE, "application/json")
+        .body(reqwest::Body::from(body.to_string()))
+        .unwrap();
+    reqwest::Response::from(response)
+}
+
+/// The stream after `CHUNKS_BEFORE_FAULT` chunks either fails like a reset
+/// connection or gets a malformed event spliced in before it continues.
+fn corrupt_stream(response: reqwest::Response, fault: StreamFault) -> reqwest::Response {
+    let mut builder = hyper::http::Response::builder().status(response.status());
+    if let Some(headers) = builder.headers_mut() {
+        *headers = response.headers().clone();
+        headers.remove(CONTENT_LENGTH);
+    }
+    let chunks = response
+        .bytes_stream()
+        .map(|chunk| chunk.map_err(std::io::Error::other))
+        .enumerate()
+        .flat_map(move |(index, chunk)| {
+            let injected = (index + 1 == CHUNKS_BEFORE_FAULT).then(|| match fault {
+                StreamFault::Truncate => Err(std::io::Error::new(
+                    std::io::ErrorKind::ConnectionReset,
+                    "stream truncated by chaos testing",
+                )),
+                StreamFault::Malformed => Ok(Bytes::from_static(MALFORMED_EVENT.as_bytes())),
+            });
+            futures::stream::iter(std::iter::once(chunk).chain(injected))
+        });
+    let chunks = match fault {
+        // Nothing after the reset reaches the client.
+        StreamFault::Truncate => chunks.take(CHUNKS_BEFORE_FAULT + 1).left_stream(),
+        StreamFault::Malformed => chunks.right_stream(),
+    };
+    reqwest::Response::from(builder.body(reqwest::Body::wrap_stream(chunks)).unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stream(chunks: &[&'static str]) -> reqwest::Response {
+        let chunks: Vec<Result<Bytes, std::io::Error>> = chunks
+            .iter()
+            .map(|c| Ok(Bytes::from_static(c.as_bytes())))
+            .collect();
+        let response = hyper::http::Response::builder()
+            .header(CONTENT_TYPE, "text/event-stream")
+            .body(reqwest::Body::wrap_stream(futures::stream::iter(chunks)))
+            .unwrap();
+        reqwest::Response::from(response)
+    }
+
+    const EVENTS: [&str; 4] = [
+        "data: 1\n\n",
+        "data: 2\n\n",
+        "data: 3\n\n",
+        "data: [DONE]\n\n",
+    ];
+
+    #[tokio::test]
+    async fn injects_errors_and_leaves_clean_responses_alone() {
+        let chaos = Chaos::new(&ChaosSettings {
+            error_rate: Some(1.0),
+            error_status: Some(502),
+            ..Default::default()
+        });
+        assert_eq!(
+            chaos.apply(stream(&EVENTS)).await.status(),
+            StatusCode::BAD_GATEWAY
+        );
+
+        let calm = Chaos::new(&ChaosSettings {
+            error_rate: Some(0.0),
+            truncate_rate: Some(0.0),
+            ..Default::default()
+        });
+        let body = calm.apply(stream(&EVENTS)).await.text().await.unwrap();
+        assert_eq!(body, EVENTS.concat());
+    }
+
+    #[tokio::test]
+    async fn truncates_and_corrupts_streams() {
+        let truncating = Chaos::new(&ChaosSettings {
+            truncate_rate: Some(1.0),
+            ..Default::default()
+        });
+        let chunks: Vec<_> = truncating
+            .apply(stream(&EVENTS))
+            .await
+            .bytes_stream()
+            .collect()
+            .await;
+        assert_eq!(chunks.len(), CHUNKS_BEFORE_FAULT + 1);
+        assert!(chunks[..CHUNKS_BEFORE_FAULT].iter().all(Result::is_ok));
+        assert!(chunks[CHUNKS_BEFORE_FAULT].is_err());
+
+        let corrupting = Chaos::new(&ChaosSettings {
+            malformed_sse_rate: Some(1.0),
+            ..Default::default()
+        });
+        let body = corrupting
+            .apply(stream(&EVENTS))
+            .await
+            .text()
+            .await
+            .unwrap();
+        assert_eq!(
+            body,
+            format!("data: 1\n\ndata: 2\n\n{MALFORMED_EVENT}data: 3\n\ndata: [DONE]\n\n")
+        );
+    }
+}
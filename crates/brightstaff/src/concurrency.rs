@@ -0,0 +1,486 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use bytes::Bytes;
+use common::configuration::{ConcurrencyLimits, LlmProvider, TenancyConfig};
+use hyper::HeaderMap;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tracing::{debug, warn};
+
+use crate::streaming::StreamProcessor;
+
+const DEFAULT_KEY_HEADER: &str = "authorization";
+const DEFAULT_PRIORITY_HEADER: &str = "x-arch-priority";
+const DEFAULT_BATCH_SHARE: f64 = 0.8;
+/// Once the per-key table grows past this size, idle entries are pruned on insert.
+const PER_KEY_PRUNE_THRESHOLD: usize = 1024;
+
+/// Priority class of a request, chosen by the priority header or the client key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Priority {
+    Interactive,
+    Batch,
+}
+
+/// One capped scope. Batch requests also take a permit from `batch`, which
+/// holds only a share of the slots, so the rest stay free for interactive
+/// requests and batch traffic is the first to queue or shed.
+#[derive(Clone)]
+struct Limit {
+    all: Arc<Semaphore>,
+    batch: Arc<Semaphore>,
+}
+
+impl Limit {
+    fn new(max: usize, batch_share: f64) -> Self {
+        let batch_max = ((max as f64 * batch_share).floor() as usize)
+            .max(1)
+            .min(max);
+        Self {
+            all: Arc::new(Semaphore::new(max)),
+            batch: Arc::new(Semaphore::new(batch_max)),
+        }
+    }
+}
+
+/// Semaphore-based caps on in-flight upstream requests, applied globally,
+/// per provider, per tenant and per client key.
+///
+/// Permits are acquired most-specific first (key, tenant, provider, global) so a
+/// request never holds a global slot while it waits on its own key's limit.
+pub struct ConcurrencyLimiter {
+    global: Option<Limit>,
+    per_provider: HashMap<String, Limit>,
+    per_tenant: HashMap<String, Limit>,
+    per_key_limit: Option<usize>,
+    /// Keyed by a hash of the client key so raw credentials are not retained.
+    per_key: Mutex<HashMap<u64, Limit>>,
+    key_header: String,
+    queue_timeout: Duration,
+    priority_header: String,
+    /// Hashes of client keys whose requests are always batch.
+    batch_keys: HashSet<u64>,
+    batch_share: f64,
+    batch_queue_timeout: Duration,
+}
+
+/// Permits held for the lifetime of one upstream request. Dropping releases them.
+#[derive(Default)]
+pub struct ConcurrencyPermits {
+    permits: Vec<OwnedSemaphorePermit>,
+}
+
+impl ConcurrencyPermits {
+    pub fn is_empty(&self) -> bool {
+        self.permits.is_empty()
+    }
+}
+
+impl ConcurrencyLimiter {
+    pub fn new(limits: Option<&ConcurrencyLimits>, providers: &[LlmProvider]) -> Self {
+        let limits = limits.cloned().unwrap_or_default();
+        let batch_share = limits.batch_share.unwrap_or(DEFAULT_BATCH_SHARE);
+
+        let per_provider = providers
+            .iter()
+            .filter_map(|p| {
+                p.max_concurrent_requests
+                    .map(|max| (p.name.clone(), Limit::new(max, batch_share)))
+            })
+            .collect();
+        let queue_timeout = Duration::from_millis(limits.queue_timeout_ms.unwrap_or(0));
+
+        Self {
+            global: limits
+                .max_concurrent_requests
+                .map(|max| Limit::new(max, batch_share)),
+            per_provider,
+            per_tenant: HashMap::new(),
+            per_key_limit: limits.max_concurrent_requests_per_key,
+            per_key: Mutex::new(HashMap::new()),
+            key_header: limits
+                .key_header
+                .unwrap_or_else(|| DEFAULT_KEY_HEADER.to_string()),
+            queue_timeout,
+            priority_header: limits
+                .priority_header
+                .unwrap_or_else(|| DEFAULT_PRIORITY_HEADER.to_string()),
+            batch_keys: limits
+                .batch_keys
+                .iter()
+                .flatten()
+                .map(|key| hash_key(key))
+                .collect(),
+            batch_share,
+            batch_queue_timeout: limits
+                .batch_queue_timeout_ms
+                .map_or(queue_timeout, Duration::from_millis),
+        }
+    }
+
+    /// Add the `max_concurrent_requests` caps of configured tenants.
+    pub fn with_tenant_limits(mut self, tenancy: Option<&TenancyConfig>) -> Self {
+        self.per_tenant = tenancy
+            .map(|t| t.tenants.as_slice())
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|t| {
+                t.max_concurrent_requests
+                    .map(|max| (t.id.clone(), Limit::new(max, self.batch_share)))
+            })
+            .collect();
+        self
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.global.is_some()
+            || !self.per_provider.is_empty()
+            || !self.per_tenant.is_empty()
+            || self.per_key_limit.is_some()
+    }
+
+    /// Priority named by the priority header, else `batch` for configured
+    /// batch keys, else `interactive`.
+    pub fn priority(&self, headers: &HeaderMap) -> Priority {
+        let header = headers
+            .get(self.priority_header.as_str())
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.trim().to_ascii_lowercase());
+        match header.as_deref() {
+            Some("batch") => Priority::Batch,
+            Some("interactive") => Priority::Interactive,
+            _ if self
+                .client_key(headers)
+                .is_some_and(|key| self.batch_keys.contains(&hash_key(key))) =>
+            {
+                Priority::Batch
+            }
+            _ => Priority::Interactive,
+        }
+    }
+
+    /// Acquire every permit that applies to a request bound for `provider_name`.
+    ///
+    /// Waits up to the queue timeout of the request's priority class; on expiry
+    /// returns the scope (`key`, `tenant`, `provider` or `global`) whose limit
+    /// could not be satisfied.
+    pub async fn acquire(
+        &self,
+        provider_name: &str,
+        tenant: Option<&str>,
+        headers: &HeaderMap,
+    ) -> Result<ConcurrencyPermits, &'static str> {
+        let mut scopes: Vec<(&'static str, Limit)> = Vec::with_capacity(4);
+        if let Some(limit) = self.key_limit(headers) {
+            scopes.push(("key", limit));
+        }
+        if let Some(limit) = tenant.and_then(|t| self.per_tenant.get(t)) {
+            scopes.push(("tenant", limit.clone()));
+        }
+        if let Some(limit) = self.provider_limit(provider_name) {
+            scopes.push(("provider", limit));
+        }
+        if let Some(limit) = &self.global {
+            scopes.push(("global", limit.clone()));
+        }
+
+        let priority = self.priority(headers);
+        let queue_timeout = match priority {
+            Priority::Interactive => self.queue_timeout,
+            Priority::Batch => self.batch_queue_timeout,
+        };
+        let mut permits = ConcurrencyPermits::default();
+        for (scope, limit) in scopes {
+            let semaphores = match priority {
+                Priority::Interactive => vec![limit.all],
+                Priority::Batch => vec![limit.batch, limit.all],
+            };
+            for sem in semaphores {
+                let permit = match sem.clone().try_acquire_owned() {
+                    Ok(permit) => permit,
+                    Err(_) if queue_timeout.is_zero() => {
+                        warn!(scope = scope, ?priority, provider = %provider_name, "concurrency limit reached, shedding request");
+                        return Err(scope);
+                    }
+                    Err(_) => {
+                        debug!(scope = scope, ?priority, provider = %provider_name, "concurrency limit reached, queueing request");
+                        match tokio::time::timeout(queue_timeout, sem.acquire_owned()).await {
+                            Ok(Ok(permit)) => permit,
+                            _ => {
+                                warn!(scope = scope, ?priority, provider = %provider_name, "timed out waiting for concurrency slot, shedding request");
+                                return Err(scope);
+                            }
+                        }
+                    }
+                };
+                permits.permits.push(permit);
+            }
+        }
+        Ok(permits)
+    }
+
+    fn provider_limit(&self, provider_name: &str) -> Option<Limit> {
+        if let Some(limit) = self.per_provider.get(provider_name) {
+            return Some(limit.clone());
+        }
+        // Providers expanded from a wildcard entry share the wildcard's limit.
+        let (prefix, _) = provider_name.split_once('/')?;
+        self.per_provider.get(&format!("{}/*", prefix)).cloned()
+    }
+
+    fn client_key<'a>(&self, headers: &'a HeaderMap) -> Option<&'a str> {
+        headers
+            .get(self.key_header.as_str())
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.strip_prefix("Bearer ").unwrap_or(v))
+            .filter(|v| !v.is_empty())
+    }
+
+    fn key_limit(&self, headers: &HeaderMap) -> Option<Limit> {
+        let limit = self.per_key_limit?;
+        let key_hash = hash_key(self.client_key(headers)?);
+
+        let mut per_key = self.per_key.lock().unwrap_or_else(|e| e.into_inner());
+        if per_key.len() >= PER_KEY_PRUNE_THRESHOLD && !per_key.contains_key(&key_hash) {
+            // Outstanding permits hold a reference to their semaphore, so a
+            // count of one means the key is idle and its entry can be dropped.
+            per_key.retain(|_, limit| Arc::strong_count(&limit.all) > 1);
+        }
+        Some(
+            per_key
+                .entry(key_hash)
+                .or_insert_with(|| Limit::new(limit, self.batch_share))
+                .clone(),
+        )
+    }
+}
+
+fn hash_key(key: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Wraps another processor and keeps the request's concurrency permits alive
+/// until the response stream has been fully forwarded to the client.
+pub struct PermitHoldingProcessor<P: StreamProcessor> {
+    inner: P,
+    _permits: ConcurrencyPermits,
+}
+
+impl<P: StreamProcessor> PermitHoldingProcessor<P> {
+    pub fn new(inner: P, permits: ConcurrencyPermits) -> Self {
+        Self {
+            inner,
+            _permits: permits,
+        }
+    }
+}
+
+impl<P: StreamProcessor> StreamProcessor for PermitHoldingProcessor<P> {
+    fn process_chunk(&mut self, chunk: Bytes) -> Result<Option<Bytes>, String> {
+        self.inner.process_chunk(chunk)
+    }
+
+    fn on_first_bytes(&mut self) {
+        self.inner.on_first_bytes()
+    }
+
+    fn on_complete(&mut self) {
+        self.inner.on_complete()
+    }
+
+    fn on_error(&mut self, error: &str) {
+        self.inner.on_error(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyper::header::HeaderValue;
+
+    fn provider(name: &str, max: Option<usize>) -> LlmProvider {
+        LlmProvider {
+            name: name.to_string(),
+            max_concurrent_requests: max,
+            ..Default::default()
+        }
+    }
+
+    fn headers_with_key(key: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("authorization", HeaderValue::from_str(key).unwrap());
+        headers
+    }
+
+    #[tokio::test]
+    async fn disabled_without_limits() {
+        let limiter = ConcurrencyLimiter::new(None, &[provider("openai/gpt-4o", None)]);
+        assert!(!limiter.is_enabled());
+        let permits = limiter
+            .acquire("openai/gpt-4o", None, &HeaderMap::new())
+            .await
+            .unwrap();
+        assert!(permits.is_empty());
+    }
+
+    #[tokio::test]
+    async fn global_limit_sheds_when_exhausted() {
+        let limits = ConcurrencyLimits {
+            max_concurrent_requests: Some(1),
+            ..Default::default()
+        };
+        let limiter = ConcurrencyLimiter::new(Some(&limits), &[]);
+        let held = limiter.acquire("a", None, &HeaderMap::new()).await.unwrap();
+        assert_eq!(
+            limiter.acquire("b", None, &HeaderMap::new()).await.err(),
+            Some("global")
+        );
+        drop(held);
+        assert!(limiter.acquire("b", None, &HeaderMap::new()).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn tenant_limit_only_counts_that_tenant() {
+        let tenancy = TenancyConfig {
+            tenants: vec![common::configuration::TenantConfig {
+                id: "search".to_string(),
+                models: None,
+                max_concurrent_requests: Some(1),
+                routing_preferences: None,
+                output_token_budget: None,
+            }],
+            ..Default::default()
+        };
+        let limiter = ConcurrencyLimiter::new(None, &[]).with_tenant_limits(Some(&tenancy));
+        assert!(limiter.is_enabled());
+        let held = limiter
+            .acquire("a", Some("search"), &HeaderMap::new())
+            .await
+            .unwrap();
+        assert_eq!(
+            limiter
+                .acquire("a", Some("search"), &HeaderMap::new())
+                .await
+                .err(),
+            Some("tenant")
+        );
+        assert!(limiter
+            .acquire("a", Some("ads"), &HeaderMap::new())
+            .await
+            .is_ok());
+        drop(held);
+    }
+
+    #[tokio::test]
+    async fn provider_limit_applies_to_wildcard_expansions() {
+        let limiter = ConcurrencyLimiter::new(None, &[provider("openai/*", Some(1))]);
+        let _held = limiter
+            .acquire("openai/gpt-4o", None, &HeaderMap::new())
+            .await
+            .unwrap();
+        assert_eq!(
+            limiter
+                .acquire("openai/gpt-4o-mini", None, &HeaderMap::new())
+                .await
+                .err(),
+            Some("provider")
+        );
+        assert!(limiter
+            .acquire("anthropic/claude", None, &HeaderMap::new())
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn per_key_limit_is_isolated_between_keys() {
+        let limits = ConcurrencyLimits {
+            max_concurrent_requests_per_key: Some(1),
+            ..Default::default()
+        };
+        let limiter = ConcurrencyLimiter::new(Some(&limits), &[]);
+        let _held = limiter
+            .acquire("p", None, &headers_with_key("Bearer a"))
+            .await
+            .unwrap();
+        assert_eq!(
+            limiter
+                .acquire("p", None, &headers_with_key("Bearer a"))
+                .await
+                .err(),
+            Some("key")
+        );
+        assert!(limiter
+            .acquire("p", None, &headers_with_key("Bearer b"))
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn queued_request_gets_slot_when_released() {
+        let limits = ConcurrencyLimits {
+            max_concurrent_requests: Some(1),
+            queue_timeout_ms: Some(1000),
+            ..Default::default()
+        };
+        let limiter = Arc::new(ConcurrencyLimiter::new(Some(&limits), &[]));
+        let held = limiter.acquire("a", None, &HeaderMap::new()).await.unwrap();
+        let waiter = {
+            let limiter = Arc::clone(&limiter);
+            tokio::spawn(async move { limiter.acquire("b", None, &HeaderMap::new()).await.is_ok() })
+        };
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        drop(held);
+        assert!(waiter.await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn batch_sheds_first_and_leaves_room_for_interactive() {
+        let limits = ConcurrencyLimits {
+            max_concurrent_requests: Some(4),
+            batch_share: Some(0.5),
+            ..Default::default()
+        };
+        let limiter = ConcurrencyLimiter::new(Some(&limits), &[]);
+        let mut batch = HeaderMap::new();
+        batch.insert(DEFAULT_PRIORITY_HEADER, HeaderValue::from_static("batch"));
+
+        let _b1 = limiter.acquire("p", None, &batch).await.unwrap();
+        let _b2 = limiter.acquire("p", None, &batch).await.unwrap();
+        assert_eq!(
+            limiter.acquire("p", None, &batch).await.err(),
+            Some("global")
+        );
+
+        let _i1 = limiter.acquire("p", None, &HeaderMap::new()).await.unwrap();
+        let _i2 = limiter.acquire("p", None, &HeaderMap::new()).await.unwrap();
+        assert!(limiter.acquire("p", None, &HeaderMap::new()).await.is_err());
+    }
+
+    #[test]
+    fn priority_from_header_or_batch_key() {
+        let limits = ConcurrencyLimits {
+            batch_keys: Some(vec!["sk-nightly".to_string()]),
+            ..Default::default()
+        };
+        let limiter = ConcurrencyLimiter::new(Some(&limits), &[]);
+        assert_eq!(limiter.priority(&HeaderMap::new()), Priority::Interactive);
+
+        let mut headers = headers_with_key("Bearer sk-nightly");
+        assert_eq!(limiter.priority(&headers), Priority::Batch);
+        headers.insert(
+            DEFAULT_PRIORITY_HEADER,
+            HeaderValue::from_static("interactive"),
+        );
+        assert_eq!(limiter.priority(&headers), Priority::Interactive);
+
+        let mut headers = headers_with_key("Bearer sk-chat");
+        assert_eq!(limiter.priority(&headers), Priority::Interactive);
+        headers.insert(DEFAULT_PRIORITY_HEADER, HeaderValue::from_static("Batch"));
+        assert_eq!(limiter.priority(&headers), Priority::Batch);
+    }
+}
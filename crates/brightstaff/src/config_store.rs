@@ -0,0 +1,197 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use common::configuration::Configuration;
+use serde::Serialize;
+use thiserror::Error;
+
+pub const DEFAULT_CONFIG_HISTORY: usize = 10;
+
+/// One accepted revision of the effective configuration.
+#[derive(Debug, Serialize)]
+pub struct ConfigVersion {
+    pub version: u64,
+    /// Seconds since the Unix epoch at which this version was stored.
+    pub created_at: u64,
+    /// Version this one was rolled back from, when it was created by a rollback.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rolled_back_from: Option<u64>,
+    pub config: Configuration,
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum ConfigStoreError {
+    #[error("configuration version mismatch: expected {expected}, current is {current}")]
+    VersionConflict { expected: u64, current: u64 },
+
+    #[error("configuration version {0} is not in the retained history")]
+    UnknownVersion(u64),
+}
+
+/// Versioned, in-memory history of the effective configuration.
+///
+/// Every update gets the next version number. Updates name the version they
+/// were based on and are rejected if another update landed in between, so
+/// concurrent writers cannot silently overwrite each other. The most recent
+/// `max_history` versions are kept for rollback.
+pub struct ConfigStore {
+    history: RwLock<VecDeque<Arc<ConfigVersion>>>,
+    max_history: usize,
+}
+
+impl ConfigStore {
+    pub fn new(config: Configuration, max_history: Option<usize>) -> Self {
+        let initial = Arc::new(ConfigVersion {
+            version: 1,
+            created_at: now_secs(),
+            rolled_back_from: None,
+            config,
+        });
+        Self {
+            history: RwLock::new(VecDeque::from([initial])),
+            max_history: max_history.unwrap_or(DEFAULT_CONFIG_HISTORY).max(1),
+        }
+    }
+
+    pub fn current(&self) -> Arc<ConfigVersion> {
+        let history = self.history.read().unwrap();
+        Arc::clone(history.back().expect("config history is never empty"))
+    }
+
+    /// Retained versions, oldest first.
+    pub fn versions(&self) -> Vec<Arc<ConfigVersion>> {
+        self.history.read().unwrap().iter().cloned().collect()
+    }
+
+    /// Store `config` as the new current version if `expected_version` is
+    /// still current.
+    pub fn update(
+        &self,
+        expected_version: u64,
+        config: Configuration,
+    ) -> Result<Arc<ConfigVersion>, ConfigStoreError> {
+        let mut history = self.history.write().unwrap();
+        self.push(&mut history, expected_version, config, None)
+    }
+
+    /// Re-apply a retained version as a new version.
+    pub fn rollback(
+        &self,
+        expected_version: u64,
+        target_version: u64,
+    ) -> Result<Arc<ConfigVersion>, ConfigStoreError> {
+        let mut history = self.history.write().unwrap();
+        let config = history
+            .iter()
+            .find(|v| v.version == target_version)
+            .map(|v| v.config.clone())
+            .ok_or(ConfigStoreError::UnknownVersion(target_version))?;
+        self.push(&mut history, expected_version, config, Some(target_version))
+    }
+
+    fn push(
+        &self,
+        history: &mut VecDeque<Arc<ConfigVersion>>,
+        expected_version: u64,
+        config: Configuration,
+        rolled_back_from: Option<u64>,
+    ) -> Result<Arc<ConfigVersion>, ConfigStoreError> {
+        let current = history
+            .back()
+            .expect("config history is never empty")
+            .version;
+        if current != expected_version {
+            return Err(ConfigStoreError::VersionConflict {
+                expected: expected_version,
+                current,
+            });
+        }
+        let next = Arc::new(ConfigVersion {
+            version: current + 1,
+            created_at: now_secs(),
+            rolled_back_from,
+            config,
+        });
+        history.push_back(Arc::clone(&next));
+        while history.len() > self.max_history {
+            history.pop_front();
+        }
+        Ok(next)
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::config_validation::validate_config;
+
+    fn config(model: &str) -> Configuration {
+        validate_config(&format!(
+            r#"
+version: v0.3.0
+listeners:
+  - name: egress_traffic
+    type: model
+    port: 12000
+model_providers:
+  - name: {model}
+    model: {model}
+    provider_interface: openai
+"#
+        ))
+        .into_result()
+        .unwrap()
+    }
+
+    fn model_of(version: &ConfigVersion) -> &str {
+        &version.config.model_providers[0].name
+    }
+
+    #[test]
+    fn update_requires_current_version() {
+        let store = ConfigStore::new(config("openai/gpt-4o"), None);
+        assert_eq!(store.current().version, 1);
+
+        let v2 = store.update(1, config("openai/gpt-4o-mini")).unwrap();
+        assert_eq!(v2.version, 2);
+        assert_eq!(model_of(&store.current()), "openai/gpt-4o-mini");
+
+        // a writer still holding version 1 loses
+        assert_eq!(
+            store.update(1, config("openai/o3")).unwrap_err(),
+            ConfigStoreError::VersionConflict {
+                expected: 1,
+                current: 2
+            }
+        );
+        assert_eq!(store.current().version, 2);
+    }
+
+    #[test]
+    fn history_is_bounded_and_rollback_creates_new_version() {
+        let store = ConfigStore::new(config("m1"), Some(3));
+        store.update(1, config("m2")).unwrap();
+        store.update(2, config("m3")).unwrap();
+        store.update(3, config("m4")).unwrap();
+
+        let retained: Vec<u64> = store.versions().iter().map(|v| v.version).collect();
+        assert_eq!(retained, vec![2, 3, 4]);
+        assert_eq!(
+            store.rollback(4, 1).unwrap_err(),
+            ConfigStoreError::UnknownVersion(1)
+        );
+
+        let rolled_back = store.rollback(4, 2).unwrap();
+        assert_eq!(rolled_back.version, 5);
+        assert_eq!(rolled_back.rolled_back_from, Some(2));
+        assert_eq!(model_of(&rolled_back), "m2");
+    }
+}
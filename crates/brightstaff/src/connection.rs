@@ -0,0 +1,129 @@
+use std::time::Duration;
+
+use common::configuration::{ListenerConnectionSettings, UpstreamConnectionSettings};
+use hyper::server::conn::http1;
+use hyper_util::rt::TokioTimer;
+use socket2::{SockRef, TcpKeepalive};
+use tokio::net::TcpStream;
+
+/// Apply listener socket options to a freshly accepted client connection.
+pub fn configure_accepted_stream(
+    stream: &TcpStream,
+    settings: &ListenerConnectionSettings,
+) -> std::io::Result<()> {
+    if let Some(nodelay) = settings.tcp_nodelay {
+        stream.set_nodelay(nodelay)?;
+    }
+    if let Some(keepalive) = tcp_keepalive(
+        settings.tcp_keepalive_secs,
+        settings.tcp_keepalive_interval_secs,
+    ) {
+        SockRef::from(stream).set_tcp_keepalive(&keepalive)?;
+    }
+    Ok(())
+}
+
+fn tcp_keepalive(idle_secs: Option<u64>, interval_secs: Option<u64>) -> Option<TcpKeepalive> {
+    if idle_secs.is_none() && interval_secs.is_none() {
+        return None;
+    }
+    let mut keepalive = TcpKeepalive::new();
+    if let Some(idle) = idle_secs {
+        keepalive = keepalive.with_time(Duration::from_secs(idle));
+    }
+    if let Some(interval) = interval_secs {
+        keepalive = keepalive.with_interval(Duration::from_secs(interval));
+    }
+    Some(keepalive)
+}
+
+/// Build the HTTP/1 connection builder used for every accepted client connection.
+pub fn http1_builder(settings: Option<&ListenerConnectionSettings>) -> http1::Builder {
+    let mut builder = http1::Builder::new();
+    let Some(settings) = settings else {
+        return builder;
+    };
+    if let Some(keep_alive) = settings.http1_keep_alive {
+        builder.keep_alive(keep_alive);
+    }
+    if let Some(timeout_ms) = settings.http1_header_read_timeout_ms {
+        builder
+            .timer(TokioTimer::new())
+            .header_read_timeout(Duration::from_millis(timeout_ms));
+    }
+    builder
+}
+
+/// Build the shared HTTP client used for upstream LLM calls.
+///
+/// Requests go to the local Envoy listener, which applies any egress proxy
+/// itself, so `HTTP(S)_PROXY` from the environment is deliberately ignored.
+pub fn upstream_client(
+    settings: Option<&UpstreamConnectionSettings>,
+) -> reqwest::Result<reqwest::Client> {
+    let mut builder = reqwest::Client::builder().no_proxy();
+    let Some(settings) = settings else {
+        return builder.build();
+    };
+    if let Some(nodelay) = settings.tcp_nodelay {
+        builder = builder.tcp_nodelay(nodelay);
+    }
+    if let Some(idle) = settings.tcp_keepalive_secs {
+        builder = builder.tcp_keepalive(Duration::from_secs(idle));
+    }
+    if let Some(interval) = settings.tcp_keepalive_interval_secs {
+        builder = builder.tcp_keepalive_interval(Duration::from_secs(interval));
+    }
+    if let Some(timeout_ms) = settings.read_timeout_ms {
+        builder = builder.read_timeout(Duration::from_millis(timeout_ms));
+    }
+    if let Some(idle) = settings.pool_idle_timeout_secs {
+        builder = builder.pool_idle_timeout(Duration::from_secs(idle));
+    }
+    builder.build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn keepalive_only_when_configured() {
+        assert!(tcp_keepalive(None, None).is_none());
+        assert!(tcp_keepalive(Some(30), None).is_some());
+        assert!(tcp_keepalive(None, Some(10)).is_some());
+    }
+
+    #[tokio::test]
+    async fn accepted_stream_gets_socket_options() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let _client = TcpStream::connect(addr).await.unwrap();
+        let (stream, _) = listener.accept().await.unwrap();
+
+        let settings = ListenerConnectionSettings {
+            tcp_nodelay: Some(true),
+            tcp_keepalive_secs: Some(45),
+            tcp_keepalive_interval_secs: Some(5),
+            ..Default::default()
+        };
+        configure_accepted_stream(&stream, &settings).unwrap();
+
+        assert!(stream.nodelay().unwrap());
+        assert!(SockRef::from(&stream).keepalive().unwrap());
+    }
+
+    #[test]
+    fn upstream_client_builds_with_settings() {
+        let settings = UpstreamConnectionSettings {
+            tcp_nodelay: Some(true),
+            tcp_keepalive_secs: Some(60),
+            tcp_keepalive_interval_secs: Some(15),
+            read_timeout_ms: Some(30_000),
+            pool_idle_timeout_secs: Some(50),
+        };
+        assert!(upstream_client(Some(&settings)).is_ok());
+        assert!(upstream_client(None).is_ok());
+    }
+}
@@ -0,0 +1,398 @@
+//! Rate-limit cooldowns for spillover routing.
+//!
+//! When a provider answers 429 with a `retry-after`, it is cooling down for
+//! that long: requests routed to it spill to the next ranked candidate
+//! instead, and only when every candidate is cooling down does the client
+//! see a 429 (carrying the shortest remaining wait).
+//!
+//! Upstream 429s are rewritten into OpenAI-style error bodies with a
+//! `retry-after` computed from whatever the provider sent, and the last
+//! rate-limit budget each provider reported is kept for the admin API.
+
+use std::collections::HashMap;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+use bytes::Bytes;
+use hyper::header::RETRY_AFTER;
+use hyper::{HeaderMap, StatusCode};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+/// Sent by OpenAI alongside `retry-after`, with millisecond precision.
+const RETRY_AFTER_MS_HEADER: &str = "retry-after-ms";
+/// Remaining-budget and reset headers per limit: OpenAI sends resets as
+/// durations (`6m0s`), Anthropic as RFC 3339 timestamps.
+const REQUEST_LIMIT_HEADERS: [(&str, &str); 2] = [
+    (
+        "x-ratelimit-remaining-requests",
+        "x-ratelimit-reset-requests",
+    ),
+    (
+        "anthropic-ratelimit-requests-remaining",
+        "anthropic-ratelimit-requests-reset",
+    ),
+];
+const TOKEN_LIMIT_HEADERS: [(&str, &str); 4] = [
+    ("x-ratelimit-remaining-tokens", "x-ratelimit-reset-tokens"),
+    (
+        "anthropic-ratelimit-tokens-remaining",
+        "anthropic-ratelimit-tokens-reset",
+    ),
+    (
+        "anthropic-ratelimit-input-tokens-remaining",
+        "anthropic-ratelimit-input-tokens-reset",
+    ),
+    (
+        "anthropic-ratelimit-output-tokens-remaining",
+        "anthropic-ratelimit-output-tokens-reset",
+    ),
+];
+/// Upper bound on a single cooldown, so a bogus `retry-after` cannot take a
+/// provider out of rotation for hours.
+const MAX_COOLDOWN: Duration = Duration::from_secs(600);
+
+#[derive(Debug, Default)]
+pub struct ProviderCooldowns {
+    /// Cooldown deadline per provider name.
+    until: RwLock<HashMap<String, Instant>>,
+    /// Rate-limit budget last reported by each provider.
+    observed: RwLock<HashMap<String, ObservedLimits>>,
+}
+
+#[derive(Debug, Default, Clone)]
+struct ObservedLimits {
+    remaining_requests: Option<u64>,
+    remaining_tokens: Option<u64>,
+    rate_limited: u64,
+    last_rate_limited_at: Option<String>,
+}
+
+/// Rate-limit state of one provider, as shown by the admin API.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ProviderRateLimit {
+    pub provider: String,
+    pub remaining_requests: Option<u64>,
+    pub remaining_tokens: Option<u64>,
+    /// 429s answered by the provider since startup.
+    pub rate_limited: u64,
+    pub last_rate_limited_at: Option<String>,
+    /// Seconds until the provider is back in rotation, if cooling down.
+    pub cooling_down_secs: Option<u64>,
+}
+
+impl ProviderCooldowns {
+    /// Take `provider` out of rotation for `duration` (capped), extending any
+    /// cooldown already in place but never shortening it.
+    pub fn cool_down(&self, provider: &str, duration: Duration) {
+        let deadline = Instant::now() + duration.min(MAX_COOLDOWN);
+        let mut until = self.until.write().unwrap();
+        until.retain(|_, d| *d > Instant::now());
+        let entry = until.entry(provider.to_string()).or_insert(deadline);
+        *entry = (*entry).max(deadline);
+    }
+
+    /// Time left before `provider` may be used again, if it is cooling down.
+    pub fn remaining(&self, provider: &str) -> Option<Duration> {
+        self.until
+            .read()
+            .unwrap()
+            .get(provider)
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()))
+            .filter(|remaining| !remaining.is_zero())
+    }
+
+    /// Record the rate-limit headers of a response from `provider`.
+    pub fn observe(&self, provider: &str, status: StatusCode, headers: &HeaderMap) {
+        let remaining = |pairs: &[(&str, &str)]| {
+            pairs
+                .iter()
+                .filter_map(|(remaining, _)| header(headers, remaining)?.parse::<u64>().ok())
+                .min()
+        };
+        let remaining_requests = remaining(&REQUEST_LIMIT_HEADERS);
+        let remaining_tokens = remaining(&TOKEN_LIMIT_HEADERS);
+        let rate_limited = status == StatusCode::TOO_MANY_REQUESTS;
+        if !rate_limited && remaining_requests.is_none() && remaining_tokens.is_none() {
+            return;
+        }
+        let mut observed = self.observed.write().unwrap();
+        let entry = observed.entry(provider.to_string()).or_default();
+        if remaining_requests.is_some() {
+            entry.remaining_requests = remaining_requests;
+        }
+        if remaining_tokens.is_some() {
+            entry.remaining_tokens = remaining_tokens;
+        }
+        if rate_limited {
+            entry.rate_limited += 1;
+            entry.last_rate_limited_at = Some(chrono::Utc::now().to_rfc3339());
+        }
+    }
+
+    /// Rate-limit state of every provider seen so far, sorted by name.
+    pub fn snapshot(&self) -> Vec<ProviderRateLimit> {
+        let observed = self.observed.read().unwrap().clone();
+        let mut providers: Vec<String> = observed.keys().cloned().collect();
+        for provider in self.until.read().unwrap().keys() {
+            if !observed.contains_key(provider) {
+                providers.push(provider.clone());
+            }
+        }
+        providers.sort();
+        providers
+            .into_iter()
+            .map(|provider| {
+                let limits = observed.get(&provider).cloned().unwrap_or_default();
+                let cooling_down_secs = self
+                    .remaining(&provider)
+                    .map(|wait| wait.as_secs_f64().ceil() as u64);
+                ProviderRateLimit {
+                    provider,
+                    remaining_requests: limits.remaining_requests,
+                    remaining_tokens: limits.remaining_tokens,
+                    rate_limited: limits.rate_limited,
+                    last_rate_limited_at: limits.last_rate_limited_at,
+                    cooling_down_secs,
+                }
+            })
+            .collect()
+    }
+}
+
+fn header<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .map(str::trim)
+}
+
+/// Wait requested by a 429 response: `retry-after-ms`, else `retry-after` as
+/// delta-seconds or an HTTP date, else the reset of the exhausted limit.
+pub fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    if let Some(ms) = header(headers, RETRY_AFTER_MS_HEADER).and_then(|v| v.parse::<f64>().ok()) {
+        return (ms.is_finite() && ms >= 0.0).then(|| Duration::from_millis(ms as u64));
+    }
+    let Some(value) = header(headers, RETRY_AFTER.as_str()) else {
+        return limit_reset(headers);
+    };
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    (date.with_timezone(&chrono::Utc) - chrono::Utc::now())
+        .to_std()
+        .ok()
+}
+
+/// Longest reset among the limits reported as exhausted, or among all
+/// reported limits when none says so.
+fn limit_reset(headers: &HeaderMap) -> Option<Duration> {
+    let limits: Vec<(bool, Duration)> = REQUEST_LIMIT_HEADERS
+        .iter()
+        .chain(&TOKEN_LIMIT_HEADERS)
+        .filter_map(|(remaining, reset)| {
+            let reset = parse_reset(header(headers, reset)?)?;
+            Some((header(headers, remaining) == Some("0"), reset))
+        })
+        .collect();
+    let exhausted = limits.iter().any(|(exhausted, _)| *exhausted);
+    limits
+        .into_iter()
+        .filter(|(e, _)| *e || !exhausted)
+        .map(|(_, reset)| reset)
+        .max()
+}
+
+/// A reset header value: an RFC 3339 timestamp, a Go-style duration such
+/// as `1m30.5s` or `250ms`, or plain seconds.
+fn parse_reset(value: &str) -> Option<Duration> {
+    if let Ok(date) = chrono::DateTime::parse_from_rfc3339(value) {
+        return Some(
+            (date.with_timezone(&chrono::Utc) - chrono::Utc::now())
+                .to_std()
+                .unwrap_or_default(),
+        );
+    }
+    if let Ok(seconds) = value.parse::<f64>() {
+        return (seconds.is_finite() && seconds >= 0.0).then(|| Duration::from_secs_f64(seconds));
+    }
+    let mut total = 0.0;
+    let mut rest = value;
+    while !rest.is_empty() {
+        let number_end = rest.find(|c: char| !(c.is_ascii_digit() || c == '.'))?;
+        let number: f64 = rest[..number_end].parse().ok()?;
+        rest = &rest[number_end..];
+        let unit_end = rest
+            .find(|c: char| c.is_ascii_digit() || c == '.')
+            .unwrap_or(rest.len());
+        total += number
+            * match &rest[..unit_end] {
+                "h" => 3600.0,
+                "m" => 60.0,
+                "s" => 1.0,
+                "ms" => 1e-3,
+                "us" | "µs" => 1e-6,
+                "ns" => 1e-9,
+                _ => return None,
+            };
+        rest = &rest[unit_end..];
+    }
+    Some(Duration::from_secs_f64(total))
+}
+
+/// OpenAI-style error body for an upstream 429, keeping the provider's
+/// message when it sent one.
+pub fn rate_limit_body(upstream: &[u8], model: &str) -> Bytes {
+    let upstream: Option<Value> = serde_json::from_slice(upstream).ok();
+    let message = upstream
+        .as_ref()
+        .and_then(|body| {
+            body.pointer("/error/message")
+                .or_else(|| body.get("message"))
+                .and_then(Value::as_str)
+        })
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("Rate limit exceeded for model '{model}'"));
+    Bytes::from(
+        json!({
+            "error": {
+                "message": message,
+                "type": "rate_limit_error",
+                "param": null,
+                "code": "rate_limit_exceeded",
+            }
+        })
+        .to_string(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyper::header::HeaderValue;
+
+    #[test]
+    fn parses_retry_after_forms() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(retry_after(&headers), None);
+
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("20"));
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(20)));
+
+        headers.insert(RETRY_AFTER_MS_HEADER, HeaderValue::from_static("1500"));
+        assert_eq!(retry_after(&headers), Some(Duration::from_millis(1500)));
+
+        let mut headers = HeaderMap::new();
+        let later = chrono::Utc::now() + chrono::Duration::seconds(120);
+        headers.insert(
+            RETRY_AFTER,
+            HeaderValue::from_str(&later.format("%a, %d %b %Y %H:%M:%S GMT").to_string()).unwrap(),
+        );
+        let wait = retry_after(&headers).unwrap();
+        assert!(wait > Duration::from_secs(100) && wait <= Duration::from_secs(120));
+
+        headers.insert(
+            RETRY_AFTER,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        assert_eq!(retry_after(&headers), None);
+    }
+
+    #[test]
+    fn falls_back_to_the_exhausted_limit_reset() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "x-ratelimit-remaining-requests",
+            HeaderValue::from_static("12"),
+        );
+        headers.insert("x-ratelimit-reset-requests", HeaderValue::from_static("1s"));
+        headers.insert(
+            "x-ratelimit-remaining-tokens",
+            HeaderValue::from_static("0"),
+        );
+        headers.insert(
+            "x-ratelimit-reset-tokens",
+            HeaderValue::from_static("1m30.5s"),
+        );
+        assert_eq!(retry_after(&headers), Some(Duration::from_millis(90_500)));
+
+        let mut headers = HeaderMap::new();
+        let reset = chrono::Utc::now() + chrono::Duration::seconds(30);
+        headers.insert(
+            "anthropic-ratelimit-requests-reset",
+            HeaderValue::from_str(&reset.to_rfc3339()).unwrap(),
+        );
+        let wait = retry_after(&headers).unwrap();
+        assert!(wait > Duration::from_secs(25) && wait <= Duration::from_secs(30));
+        assert_eq!(parse_reset("250ms"), Some(Duration::from_millis(250)));
+        assert_eq!(parse_reset("soon"), None);
+    }
+
+    #[test]
+    fn tracks_reported_budgets_and_rate_limits() {
+        let cooldowns = ProviderCooldowns::default();
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "anthropic-ratelimit-requests-remaining",
+            HeaderValue::from_static("40"),
+        );
+        headers.insert(
+            "anthropic-ratelimit-input-tokens-remaining",
+            HeaderValue::from_static("9000"),
+        );
+        headers.insert(
+            "anthropic-ratelimit-output-tokens-remaining",
+            HeaderValue::from_static("800"),
+        );
+        cooldowns.observe("anthropic/claude-sonnet-4", StatusCode::OK, &headers);
+        cooldowns.observe("openai/gpt-4o", StatusCode::OK, &HeaderMap::new());
+        cooldowns.observe(
+            "openai/gpt-4o",
+            StatusCode::TOO_MANY_REQUESTS,
+            &HeaderMap::new(),
+        );
+        cooldowns.cool_down("openai/gpt-4o", Duration::from_secs(20));
+
+        let snapshot = cooldowns.snapshot();
+        assert_eq!(snapshot.len(), 2);
+        assert_eq!(snapshot[0].provider, "anthropic/claude-sonnet-4");
+        assert_eq!(snapshot[0].remaining_requests, Some(40));
+        assert_eq!(snapshot[0].remaining_tokens, Some(800));
+        assert_eq!(snapshot[0].rate_limited, 0);
+        assert_eq!(snapshot[1].rate_limited, 1);
+        assert!(snapshot[1].last_rate_limited_at.is_some());
+        assert_eq!(snapshot[1].cooling_down_secs, Some(20));
+    }
+
+    #[test]
+    fn rate_limit_body_keeps_the_upstream_message() {
+        let anthropic = br#"{"type":"error","error":{"type":"rate_limit_error","message":"Number of requests has exceeded your rate limit"}}"#;
+        let body: Value = serde_json::from_slice(&rate_limit_body(anthropic, "claude")).unwrap();
+        assert_eq!(
+            body["error"]["message"],
+            "Number of requests has exceeded your rate limit"
+        );
+        assert_eq!(body["error"]["code"], "rate_limit_exceeded");
+
+        let body: Value =
+            serde_json::from_slice(&rate_limit_body(b"Too Many Requests", "gpt-4o")).unwrap();
+        assert_eq!(
+            body["error"]["message"],
+            "Rate limit exceeded for model 'gpt-4o'"
+        );
+    }
+
+    #[test]
+    fn cooldown_is_capped_and_never_shortened() {
+        let cooldowns = ProviderCooldowns::default();
+        assert_eq!(cooldowns.remaining("openai/gpt-4o"), None);
+
+        cooldowns.cool_down("openai/gpt-4o", Duration::from_secs(3600));
+        assert!(cooldowns.remaining("openai/gpt-4o").unwrap() <= MAX_COOLDOWN);
+
+        cooldowns.cool_down("openai/gpt-4o", Duration::from_secs(1));
+        assert!(cooldowns.remaining("openai/gpt-4o").unwrap() > Duration::from_secs(500));
+        assert_eq!(cooldowns.remaining("anthropic/claude-sonnet-4"), None);
+    }
+}
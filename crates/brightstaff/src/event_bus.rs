@@ -0,0 +1,239 @@
+//! Completion events for other services.
+//!
+//! With `event_bus` configured, every finished request publishes a small JSON
+//! event to NATS and/or Kafka (through a Kafka REST Proxy), so billing,
+//! alerting and analytics can react as requests complete instead of polling
+//! the gateway. Publishing happens on a background task; when it falls behind
+//! and the queue fills up, events are dropped.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use bytes::Bytes;
+use chrono::Utc;
+use common::configuration::{EventBusSettings, KafkaRestProxy};
+use serde::Serialize;
+use serde_json::Value;
+use tokio::sync::mpsc;
+use tracing::warn;
+
+use crate::kafka_rest;
+use crate::signals::InteractionQuality;
+
+const DEFAULT_SUBJECT: &str = "plano.completions";
+const DEFAULT_QUEUE_SIZE: usize = 10_000;
+/// Events sent to the Kafka REST Proxy in one request.
+const KAFKA_BATCH_SIZE: usize = 100;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CompletionEvent {
+    pub request_id: String,
+    pub timestamp: String,
+    pub tenant: Option<String>,
+    pub model: String,
+    pub resolved_model: String,
+    pub status: u16,
+    pub latency_ms: u64,
+    pub time_to_first_token_ms: Option<u64>,
+    pub prompt_tokens: Option<i64>,
+    pub completion_tokens: Option<i64>,
+    pub total_tokens: Option<i64>,
+    pub cost_usd: Option<f64>,
+    pub quality: Option<InteractionQuality>,
+}
+
+impl CompletionEvent {
+    /// An event for a request whose response has just started; the rest is
+    /// filled in when it completes.
+    pub fn new(
+        request_id: impl Into<String>,
+        tenant: Option<String>,
+        model: impl Into<String>,
+        resolved_model: impl Into<String>,
+        status: u16,
+    ) -> Self {
+        Self {
+            request_id: request_id.into(),
+            timestamp: String::new(),
+            tenant,
+            model: model.into(),
+            resolved_model: resolved_model.into(),
+            status,
+            latency_ms: 0,
+            time_to_first_token_ms: None,
+            prompt_tokens: None,
+            completion_tokens: None,
+            total_tokens: None,
+            cost_usd: None,
+            quality: None,
+        }
+    }
+}
+
+pub struct EventBus {
+    queue: mpsc::Sender<CompletionEvent>,
+    dropped: AtomicU64,
+}
+
+impl EventBus {
+    /// Connect to the configured buses and start publishing. The NATS
+    /// connection is retried in the background, so an unavailable server
+    /// does not hold up startup.
+    pub async fn connect(
+        settings: &EventBusSettings,
+        http_client: reqwest::Client,
+    ) -> Result<Arc<Self>, Box<dyn std::error::Error + Send + Sync>> {
+        let nats = match &settings.nats {
+            Some(nats) => Some((
+                async_nats::ConnectOptions::new()
+                    .retry_on_initial_connect()
+                    .connect(nats.url.as_str())
+                    .await?,
+                nats.subject
+                    .clone()
+                    .unwrap_or_else(|| DEFAULT_SUBJECT.to_string()),
+            )),
+            None => None,
+        };
+        let (queue, events) =
+            mpsc::channel(settings.queue_size.unwrap_or(DEFAULT_QUEUE_SIZE).max(1));
+        let publisher = Publisher {
+            nats,
+            kafka: settings.kafka.clone(),
+            http_client,
+        };
+        tokio::spawn(publisher.run(events));
+        Ok(Arc::new(Self {
+            queue,
+            dropped: AtomicU64::new(0),
+        }))
+    }
+
+    /// Queue `event` without waiting; dropped if the queue is full.
+    pub fn publish(&self, mut event: CompletionEvent) {
+        event.timestamp = Utc::now().to_rfc3339();
+        if self.queue.try_send(event).is_err() {
+            let dropped = self.dropped.fetch_add(1, Ordering::Relaxed) + 1;
+            if dropped.is_power_of_two() {
+                warn!(dropped, "event bus queue full, dropping completion events");
+            }
+        }
+    }
+}
+
+struct Publisher {
+    nats: Option<(async_nats::Client, String)>,
+    kafka: Option<KafkaRestProxy>,
+    http_client: reqwest::Client,
+}
+
+impl Publisher {
+    async fn run(self, mut events: mpsc::Receiver<CompletionEvent>) {
+        while let Some(event) = events.recv().await {
+            let mut batch = vec![event];
+            while batch.len() < KAFKA_BATCH_SIZE {
+                match events.try_recv() {
+                    Ok(event) => batch.push(event),
+                    Err(_) => break,
+                }
+            }
+            let payloads: Vec<Value> = batch
+                .iter()
+                .filter_map(|event| serde_json::to_value(event).ok())
+                .collect();
+
+            if let Some((client, subject)) = &self.nats {
+                for payload in &payloads {
+                    let payload = Bytes::from(payload.to_string());
+                    if let Err(error) = client.publish(subject.clone(), payload).await {
+                        warn!(error = %error, "failed to publish completion event to nats");
+                    }
+                }
+            }
+            if let Some(kafka) = &self.kafka {
+                let records = payloads
+                    .into_iter()
+                    .map(|payload| (payload["request_id"].clone(), payload));
+                if let Err(error) = kafka_rest::produce(&self.http_client, kafka, records).await {
+                    warn!(
+                        error = %error,
+                        events = batch.len(),
+                        "failed to publish completion events to kafka"
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyper::service::service_fn;
+    use hyper_util::rt::TokioIo;
+    use tokio::net::TcpListener;
+
+    #[tokio::test]
+    async fn events_are_produced_through_the_kafka_rest_proxy() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let (received, mut requests) = mpsc::channel::<(String, String, Value)>(4);
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let service = service_fn(move |req: hyper::Request<hyper::body::Incoming>| {
+                let received = received.clone();
+                async move {
+                    let path = req.uri().path().to_string();
+                    let content_type = req.headers()[hyper::header::CONTENT_TYPE]
+                        .to_str()
+                        .unwrap()
+                        .to_string();
+                    let body = http_body_util::BodyExt::collect(req.into_body())
+                        .await
+                        .unwrap()
+                        .to_bytes();
+                    let body = serde_json::from_slice(&body).unwrap();
+                    received.send((path, content_type, body)).await.unwrap();
+                    Ok::<_, hyper::Error>(hyper::Response::new(
+                        http_body_util::Empty::<Bytes>::new(),
+                    ))
+                }
+            });
+            hyper::server::conn::http1::Builder::new()
+                .serve_connection(TokioIo::new(stream), service)
+                .await
+                .unwrap();
+        });
+
+        let settings = EventBusSettings {
+            kafka: Some(KafkaRestProxy {
+                rest_proxy_url: format!("http://{address}/"),
+                topic: "llm-completions".to_string(),
+            }),
+            ..Default::default()
+        };
+        let bus = EventBus::connect(&settings, reqwest::Client::new())
+            .await
+            .unwrap();
+        let mut event = CompletionEvent::new(
+            "req-1",
+            Some("acme".to_string()),
+            "fast",
+            "openai/gpt-4o-mini",
+            200,
+        );
+        event.total_tokens = Some(42);
+        event.quality = Some(InteractionQuality::Good);
+        bus.publish(event);
+
+        let (path, content_type, body) = requests.recv().await.unwrap();
+        assert_eq!(path, "/topics/llm-completions");
+        assert_eq!(content_type, "application/vnd.kafka.json.v2+json");
+        let record = &body["records"][0];
+        assert_eq!(record["key"], "req-1");
+        assert_eq!(record["value"]["tenant"], "acme");
+        assert_eq!(record["value"]["total_tokens"], 42);
+        assert_eq!(record["value"]["quality"], "Good");
+        assert!(!record["value"]["timestamp"].as_str().unwrap().is_empty());
+    }
+}
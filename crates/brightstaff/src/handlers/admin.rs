@@ -0,0 +1,515 @@
+//! Runtime configuration API.
+//!
+//! - `GET  /admin/config`            effective configuration, `ETag` carries its version
+//! - `PUT  /admin/config`            replace it; `If-Match` must name the current version
+//! - `GET  /admin/config/versions`   retained versions
+//! - `POST /admin/config/rollback`   `{"version": N}` re-applies a retained version
+//! - `GET  /admin/providers/keys`     providers switched to their secondary key
+//! - `POST /admin/providers/rotate-key` `{"provider": name, "slot": "primary" | "secondary"}`
+//!   switches the key a provider uses; without `slot` it flips to the other key
+//! - `GET  /admin/providers/rate-limits` remaining budget, 429 count and
+//!   cooldown of every provider that has reported rate-limit state
+//!
+//! Every request needs `Authorization: Bearer <admin.api_key>`; without a
+//! configured key the API answers 404. Credentials are redacted in responses,
+//! and a redacted value sent back in a `PUT` keeps the current credential.
+//! `model_providers` changes are applied to brightstaff's routing immediately
+//! (and re-run provider warm-up when configured), reading `file:`/`vault:`
+//! keys again. Nothing else is reloaded: the response to a `PUT` or rollback
+//! lists every changed section in `restart_required`, including
+//! `model_providers`, which the LLM gateway only reads at startup.
+
+use std::sync::Arc;
+
+use bytes::Bytes;
+use common::config_validation::validate_config;
+use common::configuration::Configuration;
+use common::llm_providers::LlmProviders;
+use http_body_util::combinators::BoxBody;
+use http_body_util::BodyExt;
+use hyper::body::Incoming;
+use hyper::header::{AUTHORIZATION, CONTENT_TYPE, ETAG, IF_MATCH};
+use hyper::{Method, Request, Response, StatusCode};
+use serde::Deserialize;
+use serde_json::json;
+use tracing::{info, warn};
+
+use super::full;
+use crate::access_keys::KeySlot;
+use crate::app_state::AppState;
+use crate::config_store::{ConfigStoreError, ConfigVersion};
+use crate::secret_keys;
+
+pub const ADMIN_PATH_PREFIX: &str = "/admin/";
+pub const REDACTED: &str = "<redacted>";
+
+#[derive(Deserialize)]
+struct RollbackRequest {
+    version: u64,
+}
+
+#[derive(Deserialize)]
+struct RotateKeyRequest {
+    provider: String,
+    slot: Option<KeySlot>,
+}
+
+pub async fn admin_config(
+    req: Request<Incoming>,
+    state: Arc<AppState>,
+) -> Result<Response<BoxBody<Bytes, hyper::Error>>, hyper::Error> {
+    let Some(api_key) = state.admin_api_key.as_deref() else {
+        return Ok(json_response(
+            StatusCode::NOT_FOUND,
+            json!({"error": "not found"}),
+        ));
+    };
+    if !is_authorized(&req, api_key) {
+        return Ok(json_response(
+            StatusCode::UNAUTHORIZED,
+            json!({"error": "invalid or missing admin api key"}),
+        ));
+    }
+
+    let method = req.method().clone();
+    let path = req.uri().path().to_string();
+    match (method, path.as_str()) {
+        (Method::GET, "/admin/config") => Ok(version_response(&state.config_store.current())),
+        (Method::GET, "/admin/config/versions") => {
+            let versions: Vec<_> = state
+                .config_store
+                .versions()
+                .iter()
+                .map(|v| {
+                    json!({
+                        "version": v.version,
+                        "created_at": v.created_at,
+                        "rolled_back_from": v.rolled_back_from,
+                    })
+                })
+                .collect();
+            Ok(json_response(
+                StatusCode::OK,
+                json!({ "current": state.config_store.current().version, "versions": versions }),
+            ))
+        }
+        (Method::PUT, "/admin/config") => {
+            let Some(expected) = if_match_version(&req) else {
+                return Ok(precondition_required());
+            };
+            let body = req.collect().await?.to_bytes();
+            let report = validate_config(&String::from_utf8_lossy(&body));
+            let mut config = match report.into_result() {
+                Ok(config) => config,
+                Err(errors) => {
+                    let issues: Vec<String> = errors.0.iter().map(|i| i.to_string()).collect();
+                    return Ok(json_response(
+                        StatusCode::BAD_REQUEST,
+                        json!({"error": "invalid configuration", "issues": issues}),
+                    ));
+                }
+            };
+            if let Err(message) =
+                restore_redacted(&mut config, &state.config_store.current().config)
+            {
+                return Ok(json_response(
+                    StatusCode::BAD_REQUEST,
+                    json!({"error": message}),
+                ));
+            }
+            let previous = state.config_store.current();
+            apply(
+                &state,
+                &previous,
+                state.config_store.update(expected, config),
+            )
+            .await
+        }
+        (Method::POST, "/admin/config/rollback") => {
+            let Some(expected) = if_match_version(&req) else {
+                return Ok(precondition_required());
+            };
+            let body = req.collect().await?.to_bytes();
+            let target = match serde_json::from_slice::<RollbackRequest>(&body) {
+                Ok(request) => request.version,
+                Err(e) => {
+                    return Ok(json_response(
+                        StatusCode::BAD_REQUEST,
+                        json!({"error": format!("invalid rollback request: {e}")}),
+                    ))
+                }
+            };
+            let previous = state.config_store.current();
+            apply(
+                &state,
+                &previous,
+                state.config_store.rollback(expected, target),
+            )
+            .await
+        }
+        (Method::GET, "/admin/providers/keys") => {
+            let mut secondary: Vec<_> = state.access_key_slots.rotated().into_keys().collect();
+            secondary.sort();
+            Ok(json_response(
+                StatusCode::OK,
+                json!({ "secondary": secondary }),
+            ))
+        }
+        (Method::GET, "/admin/providers/rate-limits") => Ok(json_response(
+            StatusCode::OK,
+            json!({ "providers": state.provider_cooldowns.snapshot() }),
+        )),
+        (Method::GET, "/admin/providers/latency") => Ok(json_response(
+            StatusCode::OK,
+            json!({ "providers": state.provider_health.snapshot() }),
+        )),
+        (Method::POST, "/admin/providers/rotate-key") => {
+            let body = req.collect().await?.to_bytes();
+            match serde_json::from_slice::<RotateKeyRequest>(&body) {
+                Ok(request) => Ok(rotate_key(&state, request).await),
+                Err(e) => Ok(json_response(
+                    StatusCode::BAD_REQUEST,
+                    json!({"error": format!("invalid rotate-key request: {e}")}),
+                )),
+            }
+        }
+        _ => Ok(json_response(
+            StatusCode::NOT_FOUND,
+            json!({"error": "not found"}),
+        )),
+    }
+}
+
+/// Make a newly stored version live and describe the outcome, including the
+/// sections that changed since `previous` and only take effect on restart.
+async fn apply(
+    state: &AppState,
+    previous: &ConfigVersion,
+    result: Result<Arc<ConfigVersion>, ConfigStoreError>,
+) -> Result<Response<BoxBody<Bytes, hyper::Error>>, hyper::Error> {
+    let version = match result {
+        Ok(version) => version,
+        Err(e @ ConfigStoreError::VersionConflict { .. }) => {
+            return Ok(json_response(
+                StatusCode::PRECONDITION_FAILED,
+                json!({"error": e.to_string()}),
+            ))
+        }
+        Err(e @ ConfigStoreError::UnknownVersion(_)) => {
+            return Ok(json_response(
+                StatusCode::NOT_FOUND,
+                json!({"error": e.to_string()}),
+            ))
+        }
+    };
+
+    let model_providers = &version.config.model_providers;
+    let secrets = secret_keys::resolve(model_providers, &state.http_client).await;
+    match LlmProviders::try_from(model_providers.clone())
+        .map_err(|e| e.to_string())
+        .and_then(|providers| Ok((providers, secrets?)))
+    {
+        Ok((providers, secrets)) => {
+            state.access_key_slots.set_secrets(secrets);
+            state.warmup.start(
+                &providers,
+                state.http_client.clone(),
+                state.llm_provider_url.clone(),
+            );
+            *state.llm_providers.write().await = providers;
+        }
+        Err(e) => warn!(
+            version = version.version,
+            error = %e,
+            "stored configuration but could not apply model_providers"
+        ),
+    }
+    let restart_required = changed_sections(&previous.config, &version.config);
+    info!(
+        version = version.version,
+        rolled_back_from = ?version.rolled_back_from,
+        restart_required = ?restart_required,
+        "runtime configuration updated"
+    );
+    let mut body = version_body(&version);
+    body["restart_required"] = json!(restart_required);
+    Ok(versioned_response(&version, body))
+}
+
+/// Top-level config sections that differ between `previous` and `next`,
+/// sorted. Every one of them needs a restart to take full effect.
+fn changed_sections(previous: &Configuration, next: &Configuration) -> Vec<String> {
+    let (Ok(serde_json::Value::Object(previous)), Ok(serde_json::Value::Object(next))) =
+        (serde_json::to_value(previous), serde_json::to_value(next))
+    else {
+        return Vec::new();
+    };
+    let mut changed: Vec<String> = previous
+        .keys()
+        .chain(next.keys())
+        .filter(|key| previous.get(*key) != next.get(*key))
+        .cloned()
+        .collect();
+    changed.sort();
+    changed.dedup();
+    changed
+}
+
+async fn rotate_key(
+    state: &AppState,
+    request: RotateKeyRequest,
+) -> Response<BoxBody<Bytes, hyper::Error>> {
+    let Some(provider) = state.llm_providers.read().await.get(&request.provider) else {
+        return json_response(
+            StatusCode::NOT_FOUND,
+            json!({"error": format!("unknown model provider '{}'", request.provider)}),
+        );
+    };
+    let next = request
+        .slot
+        .unwrap_or_else(|| state.access_key_slots.active(&provider.name).other());
+    if next == KeySlot::Secondary && provider.secondary_access_key.is_none() {
+        return json_response(
+            StatusCode::CONFLICT,
+            json!({"error": format!("model provider '{}' has no secondary_access_key", provider.name)}),
+        );
+    }
+    let active = state.access_key_slots.rotate(&provider.name, Some(next));
+    info!(provider = %provider.name, slot = ?active, "switched provider access key");
+    json_response(
+        StatusCode::OK,
+        json!({"provider": provider.name, "active": active}),
+    )
+}
+
+fn is_authorized<T>(req: &Request<T>, api_key: &str) -> bool {
+    let provided = req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .unwrap_or_default();
+    // Compare without short-circuiting so response time does not leak the key prefix.
+    provided.len() == api_key.len()
+        && provided
+            .bytes()
+            .zip(api_key.bytes())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+/// Version named by `If-Match`, accepting both `"3"` and bare `3`.
+fn if_match_version<T>(req: &Request<T>) -> Option<u64> {
+    req.headers()
+        .get(IF_MATCH)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.trim().trim_start_matches("W/").trim_matches('"'))
+        .and_then(|v| v.parse().ok())
+}
+
+fn precondition_required() -> Response<BoxBody<Bytes, hyper::Error>> {
+    json_response(
+        StatusCode::PRECONDITION_REQUIRED,
+        json!({"error": "If-Match header with the current configuration version is required"}),
+    )
+}
+
+fn version_response(version: &ConfigVersion) -> Response<BoxBody<Bytes, hyper::Error>> {
+    versioned_response(version, version_body(version))
+}
+
+fn version_body(version: &ConfigVersion) -> serde_json::Value {
+    json!({
+        "version": version.version,
+        "created_at": version.created_at,
+        "rolled_back_from": version.rolled_back_from,
+        "config": redacted(&version.config),
+    })
+}
+
+/// `body` with the version's `ETag`
+fn versioned_response(
+    version: &ConfigVersion,
+    body: serde_json::Value,
+) -> Response<BoxBody<Bytes, hyper::Error>> {
+    let mut response = json_response(StatusCode::OK, body);
+    if let Ok(etag) = format!("\"{}\"", version.version).parse() {
+        response.headers_mut().insert(ETAG, etag);
+    }
+    response
+}
+
+fn json_response(
+    status: StatusCode,
+    body: serde_json::Value,
+) -> Response<BoxBody<Bytes, hyper::Error>> {
+    Response::builder()
+        .status(status)
+        .header(CONTENT_TYPE, "application/json")
+        .body(full(body.to_string()))
+        .unwrap()
+}
+
+/// Copy of `config` with credentials replaced by [`REDACTED`].
+pub fn redacted(config: &Configuration) -> Configuration {
+    let mut config = config.clone();
+    for provider in &mut config.model_providers {
+        if provider.access_key.is_some() {
+            provider.access_key = Some(REDACTED.to_string());
+        }
+        if provider.secondary_access_key.is_some() {
+            provider.secondary_access_key = Some(REDACTED.to_string());
+        }
+    }
+    if let Some(admin) = config.admin.as_mut() {
+        if admin.api_key.is_some() {
+            admin.api_key = Some(REDACTED.to_string());
+        }
+    }
+    if let Some(storage) = config.state_storage.as_mut() {
+        if storage.connection_string.is_some() {
+            storage.connection_string = Some(REDACTED.to_string());
+        }
+    }
+    config
+}
+
+/// Replace [`REDACTED`] placeholders in an incoming config with the values
+/// from `current`, matching providers by name.
+pub fn restore_redacted(config: &mut Configuration, current: &Configuration) -> Result<(), String> {
+    for provider in &mut config.model_providers {
+        let existing = current
+            .model_providers
+            .iter()
+            .find(|p| p.name == provider.name);
+        if provider.access_key.as_deref() == Some(REDACTED) {
+            provider.access_key = existing.and_then(|p| p.access_key.clone());
+            if provider.access_key.is_none() {
+                return Err(format!(
+                    "model provider '{}' has a redacted access_key but no current key to keep",
+                    provider.name
+                ));
+            }
+        }
+        if provider.secondary_access_key.as_deref() == Some(REDACTED) {
+            provider.secondary_access_key = existing.and_then(|p| p.secondary_access_key.clone());
+            if provider.secondary_access_key.is_none() {
+                return Err(format!(
+                    "model provider '{}' has a redacted secondary_access_key but no current key to keep",
+                    provider.name
+                ));
+            }
+        }
+    }
+    if let Some(admin) = config.admin.as_mut() {
+        if admin.api_key.as_deref() == Some(REDACTED) {
+            admin.api_key = current.admin.as_ref().and_then(|a| a.api_key.clone());
+        }
+    }
+    if let Some(storage) = config.state_storage.as_mut() {
+        if storage.connection_string.as_deref() == Some(REDACTED) {
+            storage.connection_string = current
+                .state_storage
+                .as_ref()
+                .and_then(|s| s.connection_string.clone());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(access_key: &str) -> Configuration {
+        validate_config(&format!(
+            r#"
+version: v0.3.0
+listeners:
+  - name: egress_traffic
+    type: model
+    port: 12000
+model_providers:
+  - name: openai/gpt-4o
+    model: gpt-4o
+    provider_interface: openai
+    access_key: {access_key}
+    secondary_access_key: sk-next
+admin:
+  api_key: admin-secret
+"#
+        ))
+        .into_result()
+        .unwrap()
+    }
+
+    #[test]
+    fn redacted_config_round_trips_to_current_credentials() {
+        let current = config("sk-live");
+        let mut shown = redacted(&current);
+        assert_eq!(
+            shown.model_providers[0].access_key.as_deref(),
+            Some(REDACTED)
+        );
+        assert_eq!(
+            shown.admin.as_ref().unwrap().api_key.as_deref(),
+            Some(REDACTED)
+        );
+
+        assert_eq!(
+            shown.model_providers[0].secondary_access_key.as_deref(),
+            Some(REDACTED)
+        );
+
+        restore_redacted(&mut shown, &current).unwrap();
+        assert_eq!(
+            shown.model_providers[0].access_key.as_deref(),
+            Some("sk-live")
+        );
+        assert_eq!(
+            shown.model_providers[0].secondary_access_key.as_deref(),
+            Some("sk-next")
+        );
+        assert_eq!(
+            shown.admin.unwrap().api_key.as_deref(),
+            Some("admin-secret")
+        );
+    }
+
+    #[test]
+    fn redacted_key_for_new_provider_is_rejected() {
+        let current = config("sk-live");
+        let mut incoming = redacted(&current);
+        incoming.model_providers[0].name = "openai/gpt-4o-mini".to_string();
+        assert!(restore_redacted(&mut incoming, &current).is_err());
+    }
+
+    #[test]
+    fn changed_sections_need_restart() {
+        let current = config("sk-live");
+        assert!(changed_sections(&current, &current).is_empty());
+
+        let mut next = config("sk-rotated");
+        next.admin.as_mut().unwrap().config_history = Some(5);
+        assert_eq!(
+            changed_sections(&current, &next),
+            vec!["admin".to_string(), "model_providers".to_string()]
+        );
+    }
+
+    #[test]
+    fn auth_and_if_match_parsing() {
+        let req = Request::builder()
+            .header(AUTHORIZATION, "Bearer admin-secret")
+            .header(IF_MATCH, "W/\"7\"")
+            .body(())
+            .unwrap();
+        assert!(is_authorized(&req, "admin-secret"));
+        assert!(!is_authorized(&req, "admin-secreT"));
+        assert_eq!(if_match_version(&req), Some(7));
+
+        let req = Request::builder().body(()).unwrap();
+        assert!(!is_authorized(&req, "admin-secret"));
+        assert_eq!(if_match_version(&req), None);
+    }
+}
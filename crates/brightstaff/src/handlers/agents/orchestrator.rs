@@ -393,6 +393,7 @@ async fn execute_agent_chain(
             name: Some(agent_name.clone()),
             tool_calls: None,
             tool_call_id: None,
+            thinking_blocks: None,
         });
 
         current_messages.push(last_message);
@@ -199,6 +199,9 @@ mod tests {
             output_filters: None,
             port: 8080,
             router: None,
+            tls: None,
+            system_prompt: None,
+            transform_mode: Default::default(),
         }
     }
 
@@ -0,0 +1,205 @@
+//! `POST /v1/audio/transcriptions`: OpenAI speech-to-text for any configured
+//! provider.
+//!
+//! The request is a multipart form. Its `model` field is resolved and
+//! rewritten here and the provider's credentials attached; the LLM gateway
+//! forwards the form to OpenAI-compatible providers (OpenAI, Groq, ...) and
+//! translates it for Deepgram. Streamed transcriptions are relayed as they
+//! arrive. Calls are traced and metered like chat completions.
+
+use std::sync::Arc;
+
+use bytes::Bytes;
+use common::consts::{
+    ARCH_IS_STREAMING_HEADER, ARCH_PROVIDER_HINT_HEADER, AUDIO_TRANSCRIPTIONS_PATH,
+};
+use common::errors::BrightStaffError;
+use hermesllm::apis::audio::TranscriptionRequest;
+use hermesllm::apis::multipart::form_boundary;
+use http_body_util::combinators::BoxBody;
+use http_body_util::BodyExt;
+use hyper::body::Incoming;
+use hyper::header::{self, HeaderValue};
+use hyper::{Request, Response, StatusCode};
+use opentelemetry::global;
+use opentelemetry::trace::get_active_span;
+use opentelemetry::KeyValue;
+use opentelemetry_http::HeaderInjector;
+use tracing::{debug, info_span, warn, Instrument};
+
+use super::llm::resolve_model_alias;
+use super::{extract_request_id, full};
+use crate::app_state::AppState;
+use crate::streaming::{create_streaming_response, ObservableStreamProcessor};
+use crate::tracing::{http, llm, operation_component, set_service_name};
+
+pub async fn transcriptions(
+    req: Request<Incoming>,
+    state: Arc<AppState>,
+) -> Result<Response<BoxBody<Bytes, hyper::Error>>, hyper::Error> {
+    let request_id = extract_request_id(&req);
+    let request_span = info_span!(
+        "llm",
+        component = "llm",
+        request_id = %request_id,
+        http.method = %req.method(),
+        http.path = AUDIO_TRANSCRIPTIONS_PATH,
+        llm.model = tracing::field::Empty,
+    );
+    transcriptions_inner(req, state)
+        .instrument(request_span)
+        .await
+}
+
+async fn transcriptions_inner(
+    req: Request<Incoming>,
+    state: Arc<AppState>,
+) -> Result<Response<BoxBody<Bytes, hyper::Error>>, hyper::Error> {
+    set_service_name(operation_component::LLM);
+    let request_start_time = std::time::Instant::now();
+    let mut request_headers = req.headers().clone();
+
+    let tenant = match state.tenancy.resolve(&request_headers) {
+        Ok(tenant) => tenant,
+        Err(err) => return Ok(err.into_response()),
+    };
+    // Keyed before provider credentials are put on the headers.
+    let usage_ledger = state.usage_ledger.as_ref().map(|ledger| {
+        let client_key =
+            ledger.client_key(&request_headers, tenant.as_ref().map(|t| t.id.as_str()));
+        (Arc::clone(ledger), client_key)
+    });
+
+    let Some(boundary) = request_headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(form_boundary)
+    else {
+        return Ok(BrightStaffError::InvalidRequest(
+            "transcription requests must be multipart/form-data".to_string(),
+        )
+        .into_response());
+    };
+    let body = req.collect().await?.to_bytes();
+    let mut request = match TranscriptionRequest::parse(&body, &boundary) {
+        Ok(request) => request,
+        Err(e) => {
+            return Ok(BrightStaffError::InvalidRequest(format!(
+                "invalid transcription request: {e}"
+            ))
+            .into_response())
+        }
+    };
+
+    let model_from_request = request.model().to_string();
+    let resolved_model = match resolve_model_alias(&model_from_request, &state.model_aliases) {
+        Ok(model) => model,
+        Err(err) => return Ok(err.into_response()),
+    };
+    let Some(provider) = state.llm_providers.read().await.get(&resolved_model) else {
+        return Ok(BrightStaffError::ModelNotFound(resolved_model).into_response());
+    };
+    if let Some(tenant) = tenant.as_ref() {
+        if !tenant.allows_model(&provider.name) {
+            warn!(tenant = %tenant.id, model = %provider.name, "model not available to tenant");
+            return Ok(BrightStaffError::ModelNotAllowedForTenant {
+                model: provider.name.clone(),
+                tenant: tenant.id.clone(),
+            }
+            .into_response());
+        }
+    }
+    let upstream_model = resolved_model
+        .split_once('/')
+        .map(|(_, model)| model.to_string())
+        .unwrap_or_else(|| resolved_model.clone());
+    request.set_model(&upstream_model);
+
+    tracing::Span::current().record(llm::MODEL_NAME, resolved_model.as_str());
+    let span_name = format!("POST {} {}", AUDIO_TRANSCRIPTIONS_PATH, resolved_model);
+    get_active_span(|span| {
+        span.update_name(span_name.clone());
+        span.set_attribute(KeyValue::new(llm::MODEL_NAME, resolved_model.clone()));
+        span.set_attribute(KeyValue::new(llm::OPERATION_TYPE, "transcription"));
+        span.set_attribute(KeyValue::new(llm::PROVIDER, provider.name.clone()));
+    });
+
+    state
+        .access_key_slots
+        .apply(&provider.name, &mut request_headers);
+    state
+        .vertex_tokens
+        .apply(&provider.name, &mut request_headers);
+    state
+        .bedrock_credentials
+        .apply(&provider.name, &mut request_headers);
+    if let Ok(value) = HeaderValue::from_str(&resolved_model) {
+        request_headers.insert(ARCH_PROVIDER_HINT_HEADER, value);
+    }
+    let is_streaming = request.stream();
+    request_headers.insert(
+        ARCH_IS_STREAMING_HEADER,
+        HeaderValue::from_static(if is_streaming { "true" } else { "false" }),
+    );
+    request_headers.remove(header::CONTENT_LENGTH);
+    global::get_text_map_propagator(|propagator| {
+        let cx = tracing_opentelemetry::OpenTelemetrySpanExt::context(&tracing::Span::current());
+        propagator.inject_context(&cx, &mut HeaderInjector(&mut request_headers));
+    });
+
+    // The boundary is reused, so the client's content type stays valid
+    let body = request.form.encode(&boundary);
+    debug!(provider_hint = %resolved_model, upstream_model = %upstream_model, "routing transcription");
+    let upstream = match state
+        .http_client
+        .post(format!(
+            "{}{}",
+            state.llm_provider_url, AUDIO_TRANSCRIPTIONS_PATH
+        ))
+        .headers(request_headers)
+        .body(body)
+        .send()
+        .await
+    {
+        Ok(response) => response,
+        Err(err) => {
+            let mut internal_error = Response::new(full(format!("Failed to send request: {err}")));
+            *internal_error.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
+            return Ok(internal_error);
+        }
+    };
+
+    let upstream_status = upstream.status();
+    get_active_span(|span| {
+        span.set_attribute(KeyValue::new(
+            http::STATUS_CODE,
+            upstream_status.as_u16() as i64,
+        ));
+    });
+    let mut response = Response::builder().status(upstream_status);
+    if let Some(headers) = response.headers_mut() {
+        for (name, value) in upstream.headers() {
+            headers.insert(name, value.clone());
+        }
+    }
+
+    let processor = ObservableStreamProcessor::new(
+        operation_component::LLM,
+        span_name,
+        request_start_time,
+        None,
+    )
+    .with_pricing(Arc::clone(&state.pricing), &resolved_model);
+    let processor = match usage_ledger {
+        Some((ledger, client_key)) => processor.with_usage_ledger(ledger, client_key),
+        None => processor,
+    };
+    let streaming_response = create_streaming_response(upstream.bytes_stream(), processor);
+    match response.body(streaming_response.body) {
+        Ok(response) => Ok(response),
+        Err(err) => Ok(BrightStaffError::InternalServerError(format!(
+            "failed to build transcription response: {err}"
+        ))
+        .into_response()),
+    }
+}
@@ -0,0 +1,424 @@
+//! `/v1/batches`: the OpenAI Batch API for any configured provider.
+//!
+//! Batches on OpenAI-compatible providers are forwarded untouched, with the
+//! input file uploaded to the provider beforehand. Anthropic has no files, so
+//! a batch for an Anthropic model takes its JSONL lines inline in `requests`;
+//! they are sent as a Message Batch, and its status and results are
+//! translated back. Results are read from `/v1/files/{output_file_id}/content`
+//! either way.
+//!
+//! Batch and file ids handed to clients carry the model that serves them, so
+//! polling needs no state here.
+
+use std::sync::Arc;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use bytes::Bytes;
+use common::configuration::{LlmProvider, LlmProviderType};
+use common::consts::{
+    ANTHROPIC_MESSAGE_BATCHES_PATH, ARCH_IS_STREAMING_HEADER, ARCH_PROVIDER_HINT_HEADER,
+    BATCHES_PATH, FILES_PATH,
+};
+use common::errors::BrightStaffError;
+use hermesllm::apis::anthropic::{
+    MessageBatch, MessageBatchCreateRequest, MessageBatchRequest, MessageBatchResult,
+};
+use hermesllm::apis::batches::{Batch, BatchRequestLine, BatchResponseLine, CreateBatchRequest};
+use http_body_util::combinators::BoxBody;
+use http_body_util::BodyExt;
+use hyper::body::Incoming;
+use hyper::header::CONTENT_TYPE;
+use hyper::{Method, Request, Response, StatusCode};
+use serde::Deserialize;
+use serde_json::json;
+use tracing::{debug, warn};
+
+use super::full;
+use super::llm::resolve_model_alias;
+use crate::app_state::AppState;
+
+type HandlerResponse = Response<BoxBody<Bytes, hyper::Error>>;
+type ParseBatch = fn(&[u8]) -> Result<Batch, serde_json::Error>;
+
+/// OpenAI's create body, plus the model that routes the batch and, for
+/// Anthropic, the request lines themselves.
+#[derive(Deserialize)]
+struct CreateBatch {
+    #[serde(flatten)]
+    batch: CreateBatchRequest,
+    /// Defaults to the model of the first request line
+    model: Option<String>,
+    requests: Option<Vec<BatchRequestLine>>,
+}
+
+/// Provider serving a batch, found from the model in the request or the id.
+struct BatchRoute {
+    resolved_model: String,
+    provider: Arc<LlmProvider>,
+}
+
+impl BatchRoute {
+    fn is_anthropic(&self) -> bool {
+        self.provider.provider_interface == LlmProviderType::Anthropic
+    }
+}
+
+pub async fn batches(
+    req: Request<Incoming>,
+    state: Arc<AppState>,
+) -> Result<HandlerResponse, hyper::Error> {
+    let method = req.method().clone();
+    let path = req.uri().path().to_string();
+    let segments: Vec<&str> = path.trim_start_matches('/').split('/').collect();
+    let response = match (&method, segments.as_slice()) {
+        (&Method::POST, ["v1", "batches"]) => create_batch(req, &state).await?,
+        (&Method::GET, ["v1", "batches", id]) => retrieve_batch(&state, id, false).await,
+        (&Method::POST, ["v1", "batches", id, "cancel"]) => retrieve_batch(&state, id, true).await,
+        (&Method::GET, ["v1", "files", id, "content"]) => batch_results(&state, id).await,
+        _ => not_found(&format!("no batch endpoint at {} {}", method, path)),
+    };
+    Ok(response)
+}
+
+async fn create_batch(
+    req: Request<Incoming>,
+    state: &AppState,
+) -> Result<HandlerResponse, hyper::Error> {
+    let body = req.collect().await?.to_bytes();
+    let create: CreateBatch = match serde_json::from_slice(&body) {
+        Ok(create) => create,
+        Err(e) => return Ok(invalid(format!("invalid batch request: {e}"))),
+    };
+    let model = create.model.clone().or_else(|| {
+        create.requests.as_ref().and_then(|requests| {
+            requests
+                .first()
+                .and_then(|line| line.body["model"].as_str().map(str::to_string))
+        })
+    });
+    let Some(model) = model else {
+        return Ok(invalid(
+            "batch request needs a `model`, or `requests` that name one".to_string(),
+        ));
+    };
+    let route = match route_for_model(state, &model).await {
+        Ok(route) => route,
+        Err(response) => return Ok(response),
+    };
+
+    if !route.is_anthropic() {
+        if create.requests.is_some() {
+            return Ok(invalid(format!(
+                "inline `requests` are only supported for anthropic providers; upload the input file to '{}' and pass `input_file_id`",
+                route.provider.name
+            )));
+        }
+        let body = serde_json::to_vec(&create.batch).unwrap_or_default();
+        let upstream = send(state, &route, Method::POST, BATCHES_PATH, Some(body)).await;
+        return Ok(batch_response(&route, upstream, |body| {
+            serde_json::from_slice::<Batch>(body)
+        })
+        .await);
+    }
+
+    let Some(lines) = create.requests else {
+        return Ok(invalid(
+            "batches for anthropic providers take their request lines inline in `requests`"
+                .to_string(),
+        ));
+    };
+    let mut requests = Vec::with_capacity(lines.len());
+    for mut line in lines {
+        let line_model = match line.body["model"].as_str() {
+            Some(model) => match resolve_model_alias(model, &state.model_aliases) {
+                Ok(model) => model,
+                Err(err) => return Ok(err.into_response()),
+            },
+            None => route.resolved_model.clone(),
+        };
+        line.body["model"] = json!(model_name_only(&line_model));
+        match MessageBatchRequest::try_from(line) {
+            Ok(request) => requests.push(request),
+            Err(e) => return Ok(invalid(e.to_string())),
+        }
+    }
+    let body = serde_json::to_vec(&MessageBatchCreateRequest { requests }).unwrap_or_default();
+    let upstream = send(
+        state,
+        &route,
+        Method::POST,
+        ANTHROPIC_MESSAGE_BATCHES_PATH,
+        Some(body),
+    )
+    .await;
+    let metadata = create.batch.metadata;
+    Ok(batch_response(&route, upstream, move |body| {
+        serde_json::from_slice::<MessageBatch>(body).map(|batch| Batch {
+            metadata: metadata.clone(),
+            ..Batch::from(batch)
+        })
+    })
+    .await)
+}
+
+async fn retrieve_batch(state: &AppState, id: &str, cancel: bool) -> HandlerResponse {
+    let (route, upstream_id) = match route_for_id(state, id).await {
+        Ok(route) => route,
+        Err(response) => return response,
+    };
+    let (base, parse): (&str, ParseBatch) = if route.is_anthropic() {
+        (ANTHROPIC_MESSAGE_BATCHES_PATH, |body| {
+            serde_json::from_slice::<MessageBatch>(body).map(Batch::from)
+        })
+    } else {
+        (BATCHES_PATH, |body| serde_json::from_slice::<Batch>(body))
+    };
+    let (method, path) = if cancel {
+        (Method::POST, format!("{base}/{upstream_id}/cancel"))
+    } else {
+        (Method::GET, format!("{base}/{upstream_id}"))
+    };
+    let upstream = send(state, &route, method, &path, None).await;
+    batch_response(&route, upstream, parse).await
+}
+
+/// Output file of a batch. For Anthropic the file id is the batch id, and the
+/// results are translated line by line.
+async fn batch_results(state: &AppState, file_id: &str) -> HandlerResponse {
+    let (route, upstream_id) = match route_for_id(state, file_id).await {
+        Ok(route) => route,
+        Err(response) => return response,
+    };
+    let path = if route.is_anthropic() {
+        format!("{ANTHROPIC_MESSAGE_BATCHES_PATH}/{upstream_id}/results")
+    } else {
+        format!("{FILES_PATH}/{upstream_id}/content")
+    };
+    let upstream = match send(state, &route, Method::GET, &path, None).await {
+        Ok(upstream) => upstream,
+        Err(response) => return response,
+    };
+    let status = upstream.status();
+    let body = match upstream.bytes().await {
+        Ok(body) => body,
+        Err(e) => return bad_gateway(e),
+    };
+    if !status.is_success() || !route.is_anthropic() {
+        return forward(status, body);
+    }
+
+    let lines: Result<Vec<String>, String> = body
+        .split(|byte| *byte == b'\n')
+        .filter(|line| !line.trim_ascii().is_empty())
+        .map(|line| {
+            let result: MessageBatchResult =
+                serde_json::from_slice(line).map_err(|e| e.to_string())?;
+            let line = BatchResponseLine::try_from(result).map_err(|e| e.to_string())?;
+            serde_json::to_string(&line).map_err(|e| e.to_string())
+        })
+        .collect();
+    match lines {
+        Ok(lines) => Response::builder()
+            .status(StatusCode::OK)
+            .header(CONTENT_TYPE, "application/jsonl")
+            .body(full(lines.join("\n") + "\n"))
+            .unwrap(),
+        Err(e) => {
+            warn!(error = %e, "failed to translate message batch results");
+            BrightStaffError::InternalServerError(format!("failed to translate batch results: {e}"))
+                .into_response()
+        }
+    }
+}
+
+async fn route_for_model(state: &AppState, model: &str) -> Result<BatchRoute, HandlerResponse> {
+    let resolved_model =
+        resolve_model_alias(model, &state.model_aliases).map_err(|err| err.into_response())?;
+    let provider = state
+        .llm_providers
+        .read()
+        .await
+        .get(&resolved_model)
+        .ok_or_else(|| BrightStaffError::ModelNotFound(resolved_model.clone()).into_response())?;
+    Ok(BatchRoute {
+        resolved_model,
+        provider,
+    })
+}
+
+async fn route_for_id(state: &AppState, id: &str) -> Result<(BatchRoute, String), HandlerResponse> {
+    let Some((model, upstream_id)) = decode_id(id) else {
+        return Err(not_found(&format!("no batch or file with id '{id}'")));
+    };
+    Ok((route_for_model(state, &model).await?, upstream_id))
+}
+
+/// Send a batch request to the LLM gateway, which forwards it to the provider.
+async fn send(
+    state: &AppState,
+    route: &BatchRoute,
+    method: Method,
+    path: &str,
+    body: Option<Vec<u8>>,
+) -> Result<reqwest::Response, HandlerResponse> {
+    let mut headers = hyper::HeaderMap::new();
+    state
+        .access_key_slots
+        .apply(&route.provider.name, &mut headers);
+    state
+        .vertex_tokens
+        .apply(&route.provider.name, &mut headers);
+    state
+        .bedrock_credentials
+        .apply(&route.provider.name, &mut headers);
+    debug!(method = %method, path = %path, provider_hint = %route.resolved_model, "sending batch request");
+
+    let mut request = state
+        .http_client
+        .request(method, format!("{}{}", state.llm_provider_url, path))
+        .headers(headers)
+        .header(ARCH_PROVIDER_HINT_HEADER, &route.resolved_model)
+        .header(ARCH_IS_STREAMING_HEADER, "false");
+    if let Some(body) = body {
+        request = request.header(CONTENT_TYPE, "application/json").body(body);
+    }
+    request.send().await.map_err(|e| {
+        let mut internal_error = Response::new(full(format!("Failed to send request: {e}")));
+        *internal_error.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
+        internal_error
+    })
+}
+
+/// The upstream batch, parsed with `parse` and with its ids rewritten to
+/// route back here; upstream errors are forwarded as-is.
+async fn batch_response<F>(
+    route: &BatchRoute,
+    upstream: Result<reqwest::Response, HandlerResponse>,
+    parse: F,
+) -> HandlerResponse
+where
+    F: Fn(&[u8]) -> Result<Batch, serde_json::Error>,
+{
+    let upstream = match upstream {
+        Ok(upstream) => upstream,
+        Err(response) => return response,
+    };
+    let status = upstream.status();
+    let body = match upstream.bytes().await {
+        Ok(body) => body,
+        Err(e) => return bad_gateway(e),
+    };
+    if !status.is_success() {
+        return forward(status, body);
+    }
+    let mut batch = match parse(&body) {
+        Ok(batch) => batch,
+        Err(e) => {
+            warn!(error = %e, body = %String::from_utf8_lossy(&body), "failed to parse upstream batch");
+            return BrightStaffError::InternalServerError(format!(
+                "failed to parse upstream batch: {e}"
+            ))
+            .into_response();
+        }
+    };
+    batch.id = encode_id(&route.resolved_model, &batch.id);
+    for file_id in [&mut batch.output_file_id, &mut batch.error_file_id]
+        .into_iter()
+        .flatten()
+    {
+        *file_id = encode_id(&route.resolved_model, file_id);
+    }
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(CONTENT_TYPE, "application/json")
+        .body(full(serde_json::to_vec(&batch).unwrap_or_default()))
+        .unwrap()
+}
+
+/// `{base64url(model)}.{upstream id}`; the dot is outside base64url's alphabet.
+fn encode_id(resolved_model: &str, upstream_id: &str) -> String {
+    format!(
+        "{}.{}",
+        URL_SAFE_NO_PAD.encode(resolved_model.as_bytes()),
+        upstream_id
+    )
+}
+
+fn decode_id(id: &str) -> Option<(String, String)> {
+    let (model, upstream_id) = id.split_once('.')?;
+    let model = String::from_utf8(URL_SAFE_NO_PAD.decode(model).ok()?).ok()?;
+    Some((model, upstream_id.to_string()))
+}
+
+fn model_name_only(resolved_model: &str) -> &str {
+    resolved_model
+        .split_once('/')
+        .map_or(resolved_model, |(_, model)| model)
+}
+
+fn forward(status: reqwest::StatusCode, body: Bytes) -> HandlerResponse {
+    Response::builder()
+        .status(status.as_u16())
+        .header(CONTENT_TYPE, "application/json")
+        .body(full(body))
+        .unwrap()
+}
+
+fn invalid(reason: String) -> HandlerResponse {
+    BrightStaffError::InvalidRequest(reason).into_response()
+}
+
+fn not_found(message: &str) -> HandlerResponse {
+    Response::builder()
+        .status(StatusCode::NOT_FOUND)
+        .header(CONTENT_TYPE, "application/json")
+        .body(full(json!({"error": message}).to_string()))
+        .unwrap()
+}
+
+fn bad_gateway(err: reqwest::Error) -> HandlerResponse {
+    let mut bad_gateway = Response::new(full(format!("Failed to read upstream response: {err}")));
+    *bad_gateway.status_mut() = StatusCode::BAD_GATEWAY;
+    bad_gateway
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn batch_ids_carry_their_model() {
+        let id = encode_id(
+            "anthropic/claude-sonnet-4",
+            "msgbatch_013Zva2CMHLNnXjNJJKqJ2EF",
+        );
+        assert_eq!(
+            decode_id(&id),
+            Some((
+                "anthropic/claude-sonnet-4".to_string(),
+                "msgbatch_013Zva2CMHLNnXjNJJKqJ2EF".to_string()
+            ))
+        );
+        assert_eq!(decode_id("batch_abc123"), None);
+    }
+
+    #[test]
+    fn create_batch_accepts_inline_requests() {
+        let create: CreateBatch = serde_json::from_value(json!({
+            "endpoint": "/v1/chat/completions",
+            "requests": [{
+                "custom_id": "request-1",
+                "method": "POST",
+                "url": "/v1/chat/completions",
+                "body": {"model": "claude-sonnet-4", "messages": [{"role": "user", "content": "Hi"}]}
+            }],
+            "metadata": {"job": "nightly"}
+        }))
+        .unwrap();
+        assert_eq!(create.batch.completion_window, "24h");
+        assert!(create.batch.input_file_id.is_none());
+        assert_eq!(create.requests.unwrap().len(), 1);
+        assert!(create.model.is_none());
+    }
+}
@@ -0,0 +1,259 @@
+use std::io::Write;
+
+use bytes::Bytes;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use http_body_util::combinators::BoxBody;
+use http_body_util::BodyExt;
+use hyper::header::{self, HeaderValue};
+use hyper::{Response, StatusCode};
+use tracing::{debug, warn};
+
+use super::full;
+
+/// Bodies smaller than this are sent as-is; compressing them rarely pays off.
+const MIN_COMPRESSIBLE_BYTES: usize = 1024;
+const BROTLI_QUALITY: u32 = 5;
+const BROTLI_WINDOW: u32 = 22;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentCoding {
+    Gzip,
+    Brotli,
+}
+
+impl ContentCoding {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ContentCoding::Gzip => "gzip",
+            ContentCoding::Brotli => "br",
+        }
+    }
+
+    fn encode(&self, body: &[u8]) -> std::io::Result<Vec<u8>> {
+        match self {
+            ContentCoding::Gzip => {
+                let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+                encoder.write_all(body)?;
+                encoder.finish()
+            }
+            ContentCoding::Brotli => {
+                let mut out = Vec::new();
+                {
+                    let mut writer = brotli::CompressorWriter::new(
+                        &mut out,
+                        4096,
+                        BROTLI_QUALITY,
+                        BROTLI_WINDOW,
+                    );
+                    writer.write_all(body)?;
+                }
+                Ok(out)
+            }
+        }
+    }
+}
+
+/// Pick a content coding from an `Accept-Encoding` header value.
+///
+/// Honors q-values (`q=0` disables a coding) and prefers brotli over gzip
+/// when both are equally acceptable. `*` is treated as gzip.
+pub fn negotiate_encoding(accept_encoding: Option<&str>) -> Option<ContentCoding> {
+    let mut best: Option<(ContentCoding, f32)> = None;
+    for entry in accept_encoding?.split(',') {
+        let mut parts = entry.split(';');
+        let coding = match parts.next().map(|c| c.trim().to_ascii_lowercase()) {
+            Some(c) if c == "br" => ContentCoding::Brotli,
+            Some(c) if c == "gzip" || c == "*" => ContentCoding::Gzip,
+            _ => continue,
+        };
+        let q = parts
+            .filter_map(|p| p.trim().strip_prefix("q="))
+            .find_map(|q| q.parse::<f32>().ok())
+            .unwrap_or(1.0);
+        if q <= 0.0 {
+            continue;
+        }
+        let better = match best {
+            None => true,
+            Some((current, best_q)) => {
+                q > best_q || (q == best_q && coding == ContentCoding::Brotli && current != coding)
+            }
+        };
+        if better {
+            best = Some((coding, q));
+        }
+    }
+    best.map(|(coding, _)| coding)
+}
+
+/// Whether a response is a buffered JSON body that is safe to compress.
+/// Streaming (SSE) responses and bodies that are already encoded are left alone.
+fn is_compressible(response: &Response<BoxBody<Bytes, hyper::Error>>) -> bool {
+    if matches!(
+        response.status(),
+        StatusCode::NO_CONTENT | StatusCode::NOT_MODIFIED
+    ) || response.headers().contains_key(header::CONTENT_ENCODING)
+    {
+        return false;
+    }
+    response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(is_json_media_type)
+}
+
+/// `application/json` or a `+json` structured syntax type such as
+/// `application/problem+json`. Streamed `application/x-ndjson` and
+/// `application/jsonl` bodies are not JSON documents and don't match.
+fn is_json_media_type(content_type: &str) -> bool {
+    let essence = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    essence == "application/json"
+        || essence
+            .strip_prefix("application/")
+            .is_some_and(|subtype| subtype.ends_with("+json"))
+}
+
+/// Compress a non-streaming JSON response with the coding negotiated from
+/// the client's `Accept-Encoding`. Other responses are returned unchanged.
+pub async fn compress_response(
+    response: Response<BoxBody<Bytes, hyper::Error>>,
+    accept_encoding: Option<&str>,
+) -> Result<Response<BoxBody<Bytes, hyper::Error>>, hyper::Error> {
+    let Some(coding) = negotiate_encoding(accept_encoding) else {
+        return Ok(response);
+    };
+    if !is_compressible(&response) {
+        return Ok(response);
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let body = body.collect().await?.to_bytes();
+    if body.len() < MIN_COMPRESSIBLE_BYTES {
+        return Ok(Response::from_parts(parts, full(body)));
+    }
+
+    match coding.encode(&body) {
+        Ok(encoded) => {
+            debug!(
+                encoding = coding.as_str(),
+                original_bytes = body.len(),
+                compressed_bytes = encoded.len(),
+                "compressed response body"
+            );
+            parts.headers.remove(header::CONTENT_LENGTH);
+            parts.headers.insert(
+                header::CONTENT_ENCODING,
+                HeaderValue::from_static(coding.as_str()),
+            );
+            parts
+                .headers
+                .append(header::VARY, HeaderValue::from_static("accept-encoding"));
+            Ok(Response::from_parts(parts, full(encoded)))
+        }
+        Err(err) => {
+            warn!(error = %err, encoding = coding.as_str(), "failed to compress response body");
+            Ok(Response::from_parts(parts, full(body)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::GzDecoder;
+    use std::io::Read;
+
+    fn json_response(body: String) -> Response<BoxBody<Bytes, hyper::Error>> {
+        Response::builder()
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(full(body))
+            .unwrap()
+    }
+
+    #[test]
+    fn negotiate_prefers_brotli_on_tie() {
+        assert_eq!(
+            negotiate_encoding(Some("gzip, br")),
+            Some(ContentCoding::Brotli)
+        );
+        assert_eq!(
+            negotiate_encoding(Some("gzip;q=1.0, br;q=0.5")),
+            Some(ContentCoding::Gzip)
+        );
+    }
+
+    #[test]
+    fn negotiate_respects_q_zero_and_unknown_codings() {
+        assert_eq!(
+            negotiate_encoding(Some("br;q=0, gzip")),
+            Some(ContentCoding::Gzip)
+        );
+        assert_eq!(negotiate_encoding(Some("identity, deflate")), None);
+        assert_eq!(negotiate_encoding(Some("*")), Some(ContentCoding::Gzip));
+        assert_eq!(negotiate_encoding(None), None);
+    }
+
+    #[test]
+    fn only_json_documents_are_compressible() {
+        assert!(is_json_media_type("application/json"));
+        assert!(is_json_media_type("Application/JSON; charset=utf-8"));
+        assert!(is_json_media_type("application/problem+json"));
+        assert!(!is_json_media_type("application/x-ndjson"));
+        assert!(!is_json_media_type("application/jsonl"));
+        assert!(!is_json_media_type("text/json-seq"));
+    }
+
+    #[tokio::test]
+    async fn gzip_roundtrip_for_large_json() {
+        let payload = format!("{{\"data\":\"{}\"}}", "x".repeat(4096));
+        let response = compress_response(json_response(payload.clone()), Some("gzip"))
+            .await
+            .unwrap();
+        assert_eq!(response.headers()[header::CONTENT_ENCODING], "gzip");
+
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let mut decoded = String::new();
+        GzDecoder::new(&bytes[..])
+            .read_to_string(&mut decoded)
+            .unwrap();
+        assert_eq!(decoded, payload);
+    }
+
+    #[tokio::test]
+    async fn brotli_roundtrip_for_large_json() {
+        let payload = format!("{{\"data\":\"{}\"}}", "y".repeat(4096));
+        let response = compress_response(json_response(payload.clone()), Some("br"))
+            .await
+            .unwrap();
+        assert_eq!(response.headers()[header::CONTENT_ENCODING], "br");
+
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let mut decoded = String::new();
+        brotli::Decompressor::new(&bytes[..], 4096)
+            .read_to_string(&mut decoded)
+            .unwrap();
+        assert_eq!(decoded, payload);
+    }
+
+    #[tokio::test]
+    async fn small_and_streaming_bodies_are_untouched() {
+        let response = compress_response(json_response("{}".to_string()), Some("gzip"))
+            .await
+            .unwrap();
+        assert!(!response.headers().contains_key(header::CONTENT_ENCODING));
+
+        let sse = Response::builder()
+            .header(header::CONTENT_TYPE, "text/event-stream")
+            .body(full("data: x\n\n".repeat(1000)))
+            .unwrap();
+        let response = compress_response(sse, Some("gzip")).await.unwrap();
+        assert!(!response.headers().contains_key(header::CONTENT_ENCODING));
+    }
+}
@@ -0,0 +1,254 @@
+//! `POST /v1/conversations/title`: a short title for a conversation.
+//!
+//! Takes a Chat Completions style `messages` array and asks the model from
+//! the `conversation_title` config section to title its first turns. The
+//! request goes through the LLM gateway like any other, so the model may be
+//! any configured provider or alias.
+
+use std::sync::Arc;
+
+use bytes::Bytes;
+use common::consts::{ARCH_IS_STREAMING_HEADER, ARCH_PROVIDER_HINT_HEADER, CHAT_COMPLETIONS_PATH};
+use hermesllm::apis::openai::{Message, Role};
+use hermesllm::transforms::lib::ExtractText;
+use http_body_util::combinators::BoxBody;
+use http_body_util::BodyExt;
+use hyper::body::Incoming;
+use hyper::header::CONTENT_TYPE;
+use hyper::{Request, Response, StatusCode};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tracing::{debug, warn};
+
+use super::full;
+use super::llm::resolve_model_alias;
+use crate::app_state::AppState;
+
+const DEFAULT_MAX_TURNS: usize = 4;
+/// Characters of each message shown to the model; the opening is what sets the topic.
+const MAX_MESSAGE_CHARS: usize = 1000;
+const MAX_TITLE_CHARS: usize = 80;
+const TITLE_MAX_TOKENS: u32 = 32;
+const TITLE_PROMPT: &str = "You write titles for chat conversations. Reply with a title of at \
+    most six words that captures the topic of the conversation below. Reply with the title \
+    only: no quotes, no trailing punctuation.";
+
+#[derive(Deserialize)]
+struct TitleRequest {
+    messages: Vec<Message>,
+}
+
+pub async fn conversation_title(
+    req: Request<Incoming>,
+    state: Arc<AppState>,
+) -> Result<Response<BoxBody<Bytes, hyper::Error>>, hyper::Error> {
+    let Some(settings) = state.conversation_title.as_ref() else {
+        return Ok(json_response(
+            StatusCode::NOT_FOUND,
+            json!({"error": "conversation titles are not enabled; configure conversation_title"}),
+        ));
+    };
+    let body = req.collect().await?.to_bytes();
+    let request: TitleRequest = match serde_json::from_slice(&body) {
+        Ok(request) => request,
+        Err(e) => {
+            return Ok(json_response(
+                StatusCode::BAD_REQUEST,
+                json!({"error": format!("invalid title request: {e}")}),
+            ))
+        }
+    };
+    let Some(transcript) = transcript(
+        &request.messages,
+        settings.max_turns.unwrap_or(DEFAULT_MAX_TURNS),
+    ) else {
+        return Ok(json_response(
+            StatusCode::BAD_REQUEST,
+            json!({"error": "conversation has no user or assistant text to title"}),
+        ));
+    };
+
+    let resolved_model = match resolve_model_alias(&settings.model, &state.model_aliases) {
+        Ok(model) => model,
+        Err(err) => return Ok(err.into_response()),
+    };
+    let provider_name = state
+        .llm_providers
+        .read()
+        .await
+        .get(&resolved_model)
+        .map(|p| p.name.clone())
+        .unwrap_or_else(|| resolved_model.clone());
+    let model_name_only = resolved_model
+        .split_once('/')
+        .map(|(_, model)| model.to_string())
+        .unwrap_or_else(|| resolved_model.clone());
+
+    match generate_title(
+        &state,
+        &provider_name,
+        &resolved_model,
+        &model_name_only,
+        transcript,
+    )
+    .await
+    {
+        Ok(title) => {
+            debug!(model = %resolved_model, title = %title, "generated conversation title");
+            Ok(json_response(
+                StatusCode::OK,
+                json!({"title": title, "model": resolved_model}),
+            ))
+        }
+        Err(error) => {
+            warn!(model = %resolved_model, error = %error, "conversation title generation failed");
+            Ok(json_response(
+                StatusCode::BAD_GATEWAY,
+                json!({"error": format!("title generation failed: {error}")}),
+            ))
+        }
+    }
+}
+
+/// Ask the title model, routed through the LLM gateway so the configured
+/// credentials are applied.
+async fn generate_title(
+    state: &AppState,
+    provider_name: &str,
+    resolved_model: &str,
+    model_name_only: &str,
+    transcript: String,
+) -> Result<String, String> {
+    let body = json!({
+        "model": model_name_only,
+        "messages": [
+            {"role": "system", "content": TITLE_PROMPT},
+            {"role": "user", "content": transcript},
+        ],
+        "max_tokens": TITLE_MAX_TOKENS,
+        "temperature": 0.2,
+        "stream": false,
+    });
+    let mut headers = hyper::HeaderMap::new();
+    state.access_key_slots.apply(provider_name, &mut headers);
+    state.vertex_tokens.apply(provider_name, &mut headers);
+    state.bedrock_credentials.apply(provider_name, &mut headers);
+
+    let response = state
+        .http_client
+        .post(format!(
+            "{}{}",
+            state.llm_provider_url, CHAT_COMPLETIONS_PATH
+        ))
+        .headers(headers)
+        .header(ARCH_PROVIDER_HINT_HEADER, resolved_model)
+        .header(ARCH_IS_STREAMING_HEADER, "false")
+        .json(&body)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    let status = response.status();
+    let payload: Value = response.json().await.map_err(|e| e.to_string())?;
+    if !status.is_success() {
+        return Err(format!("upstream returned {status}: {payload}"));
+    }
+    payload["choices"][0]["message"]["content"]
+        .as_str()
+        .and_then(clean_title)
+        .ok_or_else(|| "response has no title".to_string())
+}
+
+/// The first `max_turns` user and assistant messages as a plain transcript,
+/// or `None` when there is no text to title.
+fn transcript(messages: &[Message], max_turns: usize) -> Option<String> {
+    let lines: Vec<String> = messages
+        .iter()
+        .filter_map(|message| {
+            let speaker = match message.role {
+                Role::User => "User",
+                Role::Assistant => "Assistant",
+                _ => return None,
+            };
+            let text = message.content.extract_text();
+            let text = text.trim();
+            (!text.is_empty()).then(|| format!("{speaker}: {}", truncate(text, MAX_MESSAGE_CHARS)))
+        })
+        .take(max_turns)
+        .collect();
+    (!lines.is_empty()).then(|| lines.join("\n\n"))
+}
+
+/// The model's reply reduced to a bare title: first line, without a
+/// `Title:` label, wrapping quotes or a trailing period.
+fn clean_title(reply: &str) -> Option<String> {
+    let line = reply.lines().map(str::trim).find(|l| !l.is_empty())?;
+    let line = ["Title:", "title:", "TITLE:"]
+        .iter()
+        .find_map(|label| line.strip_prefix(label))
+        .unwrap_or(line);
+    let title = line
+        .trim()
+        .trim_matches(|c| matches!(c, '"' | '\'' | '*' | '#' | '`'))
+        .trim_end_matches('.')
+        .trim();
+    (!title.is_empty()).then(|| truncate(title, MAX_TITLE_CHARS).to_string())
+}
+
+fn truncate(text: &str, max_chars: usize) -> &str {
+    text.char_indices()
+        .nth(max_chars)
+        .map_or(text, |(end, _)| text[..end].trim_end())
+}
+
+fn json_response(status: StatusCode, body: Value) -> Response<BoxBody<Bytes, hyper::Error>> {
+    Response::builder()
+        .status(status)
+        .header(CONTENT_TYPE, "application/json")
+        .body(full(body.to_string()))
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages(body: Value) -> Vec<Message> {
+        serde_json::from_value(body).unwrap()
+    }
+
+    #[test]
+    fn transcript_keeps_leading_user_and_assistant_turns() {
+        let conversation = messages(json!([
+            {"role": "system", "content": "You are a helpful assistant."},
+            {"role": "user", "content": "How do I reverse a list in Python?"},
+            {"role": "assistant", "content": "Use reversed() or slicing."},
+            {"role": "user", "content": [{"type": "text", "text": "And in place?"}]},
+            {"role": "assistant", "content": "Call list.reverse()."}
+        ]));
+        assert_eq!(
+            transcript(&conversation, 2).unwrap(),
+            "User: How do I reverse a list in Python?\n\nAssistant: Use reversed() or slicing."
+        );
+        assert!(transcript(&conversation, 10)
+            .unwrap()
+            .ends_with("User: And in place?\n\nAssistant: Call list.reverse()."));
+        assert!(transcript(&conversation[..1], 4).is_none());
+    }
+
+    #[test]
+    fn cleans_model_replies() {
+        assert_eq!(
+            clean_title("\"Reversing Python Lists.\"").as_deref(),
+            Some("Reversing Python Lists")
+        );
+        assert_eq!(
+            clean_title("\nTitle: **Trip to Lisbon**\nHope this helps!").as_deref(),
+            Some("Trip to Lisbon")
+        );
+        assert_eq!(clean_title("  \n "), None);
+        assert_eq!(
+            clean_title(&"word ".repeat(40)).unwrap().chars().count(),
+            79
+        );
+    }
+}
@@ -2,8 +2,9 @@ use bytes::Bytes;
 use eventsource_stream::Eventsource;
 use futures::StreamExt;
 use hermesllm::apis::openai::{
-    ChatCompletionsRequest, ChatCompletionsResponse, Choice, FinishReason, FunctionCall, Message,
-    MessageContent, ResponseMessage, Role, Tool, ToolCall, Usage,
+    ChatCompletionsRequest, ChatCompletionsResponse, ChatCompletionsStreamResponse, Choice,
+    FinishReason, FunctionCall, Message, MessageContent, ResponseMessage, Role, Tool, ToolCall,
+    Usage,
 };
 use http_body_util::{combinators::BoxBody, BodyExt, Full};
 use hyper::body::Incoming;
@@ -441,10 +442,8 @@ impl ArchFunctionHandler {
                 }
             }
             // Handle str/string conversions
-            "str" | "string" => {
-                if !value.is_string() {
-                    return Ok(json!(value.to_string()));
-                }
+            "str" | "string" if !value.is_string() => {
+                return Ok(json!(value.to_string()));
             }
             _ => {}
         }
@@ -642,6 +641,7 @@ impl ArchFunctionHandler {
                 name: None,
                 tool_calls: None,
                 tool_call_id: None,
+                thinking_blocks: None,
             });
         }
 
@@ -727,6 +727,7 @@ impl ArchFunctionHandler {
                 name: message.name.clone(),
                 tool_calls: None,
                 tool_call_id: None,
+                thinking_blocks: None,
             });
         }
 
@@ -808,6 +809,7 @@ impl ArchFunctionHandler {
             name: None,
             tool_calls: None,
             tool_call_id: None,
+            thinking_blocks: None,
         });
         messages
     }
@@ -845,7 +847,13 @@ impl ArchFunctionHandler {
         &self,
         request: ChatCompletionsRequest,
     ) -> Result<
-        std::pin::Pin<Box<dyn futures::Stream<Item = std::result::Result<Value, String>> + Send>>,
+        std::pin::Pin<
+            Box<
+                dyn futures::Stream<
+                        Item = std::result::Result<ChatCompletionsStreamResponse, String>,
+                    > + Send,
+            >,
+        >,
     > {
         let request_body = serde_json::to_string(&request).map_err(|e| {
             FunctionCallingError::InvalidModelResponse(format!(
@@ -884,9 +892,8 @@ impl ArchFunctionHandler {
                     if event.data == "[DONE]" {
                         return None;
                     }
-                    // Parse JSON
-                    match serde_json::from_str::<Value>(&event.data) {
-                        Ok(json) => Some(Ok(json)),
+                    match serde_json::from_str::<ChatCompletionsStreamResponse>(&event.data) {
+                        Ok(chunk) => Some(Ok(chunk)),
                         Err(e) => Some(Err(format!("JSON parse error: {}", e))),
                     }
                 }
@@ -978,17 +985,12 @@ impl ArchFunctionHandler {
         if use_agent_orchestrator {
             while let Some(chunk_result) = stream.next().await {
                 let chunk = chunk_result.map_err(FunctionCallingError::InvalidModelResponse)?;
-                // Extract content from JSON response
-                if let Some(choices) = chunk.get("choices").and_then(|v| v.as_array()) {
-                    if let Some(choice) = choices.first() {
-                        if let Some(content) = choice
-                            .get("delta")
-                            .and_then(|d| d.get("content"))
-                            .and_then(|c| c.as_str())
-                        {
-                            model_response.push_str(content);
-                        }
-                    }
+                if let Some(content) = chunk
+                    .choices
+                    .first()
+                    .and_then(|c| c.delta.content.as_deref())
+                {
+                    model_response.push_str(content);
                 }
             }
             info!("agent orchestrator response received");
@@ -1000,41 +1002,29 @@ impl ArchFunctionHandler {
             while let Some(chunk_result) = stream.next().await {
                 let chunk = chunk_result.map_err(FunctionCallingError::InvalidModelResponse)?;
 
-                // Extract content and logprobs from JSON response
-                if let Some(choices) = chunk.get("choices").and_then(|v| v.as_array()) {
-                    if let Some(choice) = choices.first() {
-                        if let Some(content) = choice
-                            .get("delta")
-                            .and_then(|d| d.get("content"))
-                            .and_then(|c| c.as_str())
-                        {
-                            // Extract logprobs
-                            let logprobs: Vec<f64> = choice
-                                .get("logprobs")
-                                .and_then(|lp| lp.get("content"))
-                                .and_then(|c| c.as_array())
-                                .and_then(|arr| arr.first())
-                                .and_then(|token| token.get("top_logprobs"))
-                                .and_then(|tlp| tlp.as_array())
-                                .map(|arr| {
-                                    arr.iter()
-                                        .filter_map(|v| v.get("logprob").and_then(|lp| lp.as_f64()))
-                                        .collect()
-                                })
-                                .unwrap_or_default();
-
-                            if hallucination_state
-                                .append_and_check_token_hallucination(content.to_string(), logprobs)
-                            {
-                                has_hallucination = true;
-                                break;
-                            }
+                let Some(choice) = chunk.choices.first() else {
+                    continue;
+                };
+                if let Some(content) = &choice.delta.content {
+                    // Top logprobs of the chunk's first token
+                    let logprobs: Vec<f64> = choice
+                        .logprobs
+                        .as_ref()
+                        .and_then(|lp| lp.content.as_ref())
+                        .and_then(|tokens| tokens.first())
+                        .map(|token| token.top_logprobs.iter().map(|top| top.logprob).collect())
+                        .unwrap_or_default();
+
+                    if hallucination_state
+                        .append_and_check_token_hallucination(content.clone(), logprobs)
+                    {
+                        has_hallucination = true;
+                        break;
+                    }
 
-                            if hallucination_state.tokens.len() > 5 && has_tool_calls.is_none() {
-                                let collected_content = hallucination_state.tokens.join("");
-                                has_tool_calls = Some(collected_content.contains("tool_calls"));
-                            }
-                        }
+                    if hallucination_state.tokens.len() > 5 && has_tool_calls.is_none() {
+                        let collected_content = hallucination_state.tokens.join("");
+                        has_tool_calls = Some(collected_content.contains("tool_calls"));
                     }
                 }
             }
@@ -1061,16 +1051,12 @@ impl ArchFunctionHandler {
         } else {
             while let Some(chunk_result) = stream.next().await {
                 let chunk = chunk_result.map_err(FunctionCallingError::InvalidModelResponse)?;
-                if let Some(choices) = chunk.get("choices").and_then(|v| v.as_array()) {
-                    if let Some(choice) = choices.first() {
-                        if let Some(content) = choice
-                            .get("delta")
-                            .and_then(|d| d.get("content"))
-                            .and_then(|c| c.as_str())
-                        {
-                            model_response.push_str(content);
-                        }
-                    }
+                if let Some(content) = chunk
+                    .choices
+                    .first()
+                    .and_then(|c| c.delta.content.as_deref())
+                {
+                    model_response.push_str(content);
                 }
             }
         }
@@ -1093,32 +1079,38 @@ impl ArchFunctionHandler {
             ResponseMessage {
                 role: Role::Assistant,
                 content: Some(String::new()),
+                reasoning_content: None,
                 refusal: None,
                 annotations: None,
                 audio: None,
                 function_call: None,
                 tool_calls: None,
+                thinking_blocks: None,
             }
         } else if !response_dict.required_functions.is_empty() {
             if !use_agent_orchestrator {
                 ResponseMessage {
                     role: Role::Assistant,
                     content: Some(response_dict.clarification.clone()),
+                    reasoning_content: None,
                     refusal: None,
                     annotations: None,
                     audio: None,
                     function_call: None,
                     tool_calls: None,
+                    thinking_blocks: None,
                 }
             } else {
                 ResponseMessage {
                     role: Role::Assistant,
                     content: Some(String::new()),
+                    reasoning_content: None,
                     refusal: None,
                     annotations: None,
                     audio: None,
                     function_call: None,
                     tool_calls: None,
+                    thinking_blocks: None,
                 }
             }
         } else if !response_dict.tool_calls.is_empty() {
@@ -1139,22 +1131,26 @@ impl ArchFunctionHandler {
                             ResponseMessage {
                                 role: Role::Assistant,
                                 content: Some(String::new()),
+                                reasoning_content: None,
                                 refusal: None,
                                 annotations: None,
                                 audio: None,
                                 function_call: None,
                                 tool_calls: Some(response_dict.tool_calls.clone()),
+                                thinking_blocks: None,
                             }
                         } else {
                             error!(error = %verification.error_message, "invalid tool call");
                             ResponseMessage {
                                 role: Role::Assistant,
                                 content: Some(String::new()),
+                                reasoning_content: None,
                                 refusal: None,
                                 annotations: None,
                                 audio: None,
                                 function_call: None,
                                 tool_calls: None,
+                                thinking_blocks: None,
                             }
                         }
                     } else {
@@ -1162,11 +1158,13 @@ impl ArchFunctionHandler {
                         ResponseMessage {
                             role: Role::Assistant,
                             content: Some(String::new()),
+                            reasoning_content: None,
                             refusal: None,
                             annotations: None,
                             audio: None,
                             function_call: None,
                             tool_calls: None,
+                            thinking_blocks: None,
                         }
                     }
                 } else {
@@ -1181,11 +1179,13 @@ impl ArchFunctionHandler {
                     ResponseMessage {
                         role: Role::Assistant,
                         content: Some(String::new()),
+                        reasoning_content: None,
                         refusal: None,
                         annotations: None,
                         audio: None,
                         function_call: None,
                         tool_calls: Some(response_dict.tool_calls.clone()),
+                        thinking_blocks: None,
                     }
                 }
             } else {
@@ -1196,11 +1196,13 @@ impl ArchFunctionHandler {
                 ResponseMessage {
                     role: Role::Assistant,
                     content: Some(String::new()),
+                    reasoning_content: None,
                     refusal: None,
                     annotations: None,
                     audio: None,
                     function_call: None,
                     tool_calls: None,
+                    thinking_blocks: None,
                 }
             }
         } else {
@@ -1208,11 +1210,13 @@ impl ArchFunctionHandler {
             ResponseMessage {
                 role: Role::Assistant,
                 content: Some(String::new()),
+                reasoning_content: None,
                 refusal: None,
                 annotations: None,
                 audio: None,
                 function_call: None,
                 tool_calls: None,
+                thinking_blocks: None,
             }
         };
 
@@ -1244,6 +1248,8 @@ impl ArchFunctionHandler {
             },
             system_fingerprint: None,
             service_tier: None,
+            citations: None,
+            search_results: None,
             metadata: Some(metadata),
         };
 
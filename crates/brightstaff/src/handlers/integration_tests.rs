@@ -34,6 +34,7 @@ mod tests {
             name: None,
             tool_calls: None,
             tool_call_id: None,
+            thinking_blocks: None,
         }
     }
 
@@ -80,6 +81,9 @@ mod tests {
             output_filters: None,
             port: 8080,
             router: None,
+            tls: None,
+            system_prompt: None,
+            transform_mode: Default::default(),
         };
 
         let listeners = vec![listener];
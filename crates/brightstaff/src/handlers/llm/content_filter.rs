@@ -0,0 +1,83 @@
+//! Detection of content-filter refusals, for routes with a `content_filter_fallback`.
+//!
+//! A refusal is either a successful response whose completion was cut by the
+//! provider's safety filter, or an error such as Azure's content-policy 400.
+//! Bodies are in the client's API shape (the gateway has already translated
+//! them), so each client API's refusal marker is checked.
+
+use hyper::StatusCode;
+use serde_json::Value;
+
+/// Whether a response with this status may be a refusal and is worth buffering.
+/// Streamed successes are already on their way to the client and can't be retried.
+pub(crate) fn may_be_refusal(status: StatusCode, is_streaming: bool) -> bool {
+    status == StatusCode::BAD_REQUEST || (status.is_success() && !is_streaming)
+}
+
+pub(crate) fn is_content_filtered(status: StatusCode, body: &[u8]) -> bool {
+    let Ok(json) = serde_json::from_slice::<Value>(body) else {
+        return false;
+    };
+    if status.is_success() {
+        // Chat Completions, Anthropic Messages and Responses API respectively.
+        json["choices"].as_array().is_some_and(|choices| {
+            choices
+                .iter()
+                .any(|choice| choice["finish_reason"] == "content_filter")
+        }) || json["stop_reason"] == "refusal"
+            || json["incomplete_details"]["reason"] == "content_filter"
+    } else {
+        let error = &json["error"];
+        error["code"] == "content_filter"
+            || error["code"] == "content_policy_violation"
+            || error["innererror"]["code"] == "ResponsibleAIPolicyViolation"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn filtered(status: StatusCode, body: Value) -> bool {
+        is_content_filtered(status, body.to_string().as_bytes())
+    }
+
+    #[test]
+    fn detects_refusals_across_client_apis() {
+        assert!(filtered(
+            StatusCode::OK,
+            json!({"choices": [{"index": 0, "finish_reason": "content_filter"}]})
+        ));
+        assert!(filtered(
+            StatusCode::OK,
+            json!({"type": "message", "stop_reason": "refusal"})
+        ));
+        assert!(filtered(
+            StatusCode::OK,
+            json!({"status": "incomplete", "incomplete_details": {"reason": "content_filter"}})
+        ));
+        assert!(filtered(
+            StatusCode::BAD_REQUEST,
+            json!({"error": {"code": "content_filter", "innererror": {"code": "ResponsibleAIPolicyViolation"}}})
+        ));
+
+        assert!(!filtered(
+            StatusCode::OK,
+            json!({"choices": [{"index": 0, "finish_reason": "stop"}]})
+        ));
+        assert!(!filtered(
+            StatusCode::BAD_REQUEST,
+            json!({"error": {"code": "context_length_exceeded"}})
+        ));
+        assert!(!is_content_filtered(StatusCode::OK, b"not json"));
+    }
+
+    #[test]
+    fn streamed_successes_are_not_buffered() {
+        assert!(may_be_refusal(StatusCode::OK, false));
+        assert!(!may_be_refusal(StatusCode::OK, true));
+        assert!(may_be_refusal(StatusCode::BAD_REQUEST, true));
+        assert!(!may_be_refusal(StatusCode::INTERNAL_SERVER_ERROR, false));
+    }
+}
@@ -0,0 +1,102 @@
+//! Per-request cost reporting in `x-arch-cost-estimate` / `x-arch-cost-actual`.
+//!
+//! The estimate is known before the response: the prompt at roughly four
+//! characters per token plus the request's output limit, when it sets one, at
+//! the output rate. Complete responses also get the actual cost from the
+//! usage the provider reported, added to the body as `usage.cost_usd` as well.
+//! Streamed responses only carry the estimate, since their headers are sent
+//! before usage is known.
+
+use bytes::Bytes;
+use common::pricing::PricingTable;
+use serde::Deserialize;
+use serde_json::Value;
+
+/// Same rough ratio the router uses to size prompts.
+const CHARS_PER_TOKEN: usize = 4;
+
+/// Output limit as each client API names it.
+#[derive(Deserialize)]
+struct OutputLimit {
+    max_tokens: Option<u64>,
+    max_completion_tokens: Option<u64>,
+    max_output_tokens: Option<u64>,
+}
+
+/// Expected USD cost of sending `prompt_chars` characters of prompt with
+/// `request` to `model`.
+pub(crate) fn estimate_usd(
+    pricing: &PricingTable,
+    model: &str,
+    prompt_chars: usize,
+    request: &[u8],
+) -> Option<f64> {
+    pricing.price_for(model)?;
+    let output_tokens = serde_json::from_slice::<OutputLimit>(request)
+        .ok()
+        .and_then(|limit| {
+            limit
+                .max_completion_tokens
+                .or(limit.max_tokens)
+                .or(limit.max_output_tokens)
+        })
+        .unwrap_or(0);
+    pricing.cost_usd(
+        model,
+        prompt_chars.div_ceil(CHARS_PER_TOKEN) as i64,
+        output_tokens as i64,
+        0,
+    )
+}
+
+/// Header value for a USD amount.
+pub(crate) fn format_usd(cost: f64) -> String {
+    format!("{cost:.6}")
+}
+
+/// `body` with `usage.cost_usd` set, if it is a JSON object with usage.
+pub(crate) fn with_cost_metadata(body: &[u8], cost: f64) -> Option<Bytes> {
+    let mut response: Value = serde_json::from_slice(body).ok()?;
+    response.get_mut("usage")?.as_object_mut()?.insert(
+        "cost_usd".to_string(),
+        Value::from((cost * 1e6).round() / 1e6),
+    );
+    serde_json::to_vec(&response).ok().map(Bytes::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn estimate_prices_prompt_and_output_limit() {
+        let pricing = PricingTable::default();
+        // gpt-4o: 2.5 in, 10 out per million; 4000 chars is about 1000 tokens
+        let request = json!({"model": "gpt-4o", "max_completion_tokens": 500});
+        let estimate = estimate_usd(
+            &pricing,
+            "openai/gpt-4o",
+            4000,
+            request.to_string().as_bytes(),
+        )
+        .unwrap();
+        assert!((estimate - 0.0075).abs() < 1e-9);
+
+        let unbounded = estimate_usd(&pricing, "gpt-4o", 4000, b"{}").unwrap();
+        assert!((unbounded - 0.0025).abs() < 1e-9);
+        assert_eq!(estimate_usd(&pricing, "custom/unknown", 4000, b"{}"), None);
+        assert_eq!(format_usd(0.0075), "0.007500");
+    }
+
+    #[test]
+    fn cost_goes_into_the_usage_object() {
+        let body = json!({"id": "msg_1", "usage": {"input_tokens": 10, "output_tokens": 5}});
+        let annotated = with_cost_metadata(body.to_string().as_bytes(), 0.000_123_456_7).unwrap();
+        let annotated: Value = serde_json::from_slice(&annotated).unwrap();
+        assert_eq!(annotated["usage"]["cost_usd"], 0.000123);
+        assert_eq!(annotated["usage"]["input_tokens"], 10);
+
+        assert!(with_cost_metadata(br#"{"id": "x"}"#, 0.1).is_none());
+    }
+}
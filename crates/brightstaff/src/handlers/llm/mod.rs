@@ -1,7 +1,19 @@
 use bytes::Bytes;
-use common::configuration::{FilterPipeline, ModelAlias};
-use common::consts::{ARCH_IS_STREAMING_HEADER, ARCH_PROVIDER_HINT_HEADER, MODEL_AFFINITY_HEADER};
+use common::configuration::{
+    FilterPipeline, ListenerType, LlmProviderType, ModelDeprecation, OutputTokenBudget,
+    SystemPromptPolicy, TrafficRecordingMode, TransformMode,
+};
+use common::consts::{
+    ARCH_CACHE_HEADER, ARCH_COST_ACTUAL_HEADER, ARCH_COST_ESTIMATE_HEADER,
+    ARCH_FALLBACK_REASON_HEADER, ARCH_IS_STREAMING_HEADER, ARCH_PROVIDER_HINT_HEADER,
+    ARCH_SERVED_BY_HEADER, ARCH_STRICT_TRANSFORM_HEADER, MODEL_AFFINITY_HEADER,
+};
+use common::errors::BrightStaffError;
 use common::llm_providers::LlmProviders;
+use common::model_aliases::{AliasResolution, ModelAliasTable};
+use common::pricing::PricingTable;
+use common::utils::parse_duration_ms;
+use futures::stream::{BoxStream, StreamExt};
 use hermesllm::apis::openai::Message;
 use hermesllm::apis::openai_responses::InputParam;
 use hermesllm::clients::{SupportedAPIsFromClient, SupportedUpstreamAPIs};
@@ -13,29 +25,53 @@ use hyper::{Request, Response, StatusCode};
 use opentelemetry::global;
 use opentelemetry::trace::get_active_span;
 use opentelemetry_http::HeaderInjector;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::RwLock;
 use tracing::{debug, info, info_span, warn, Instrument};
 
+mod content_filter;
+mod cost;
 pub(crate) mod model_selection;
 
 use crate::app_state::AppState;
+use crate::archive::{ArchiveEntry, Archiver};
+use crate::concurrency::{ConcurrencyPermits, PermitHoldingProcessor};
+use crate::cooldown::{rate_limit_body, retry_after};
+use crate::event_bus::{CompletionEvent, EventBus};
 use crate::handlers::agents::pipeline::PipelineProcessor;
 use crate::handlers::extract_request_id;
 use crate::handlers::full;
+use crate::handlers::responses;
+use crate::hedging::{self, Winner};
+use crate::mock_provider;
+use crate::moderation;
+use crate::output_budget::{self, OutputBudgetProcessor};
+use crate::plugins::Hook;
+use crate::prompt_compression;
+use crate::provider_health::ProviderHealth;
+use crate::response_cache::{CachePolicy, CachedResponse, Lookup, ResponseCache};
+use crate::secret_guardrail::Verdict;
 use crate::state::response_state_processor::ResponsesStateProcessor;
 use crate::state::{
     extract_input_items, retrieve_and_combine_input, StateStorage, StateStorageError,
 };
+use crate::stream_resumption::{resume_response, ReplayStream, ResumableStreamProcessor};
 use crate::streaming::{
-    create_streaming_response, create_streaming_response_with_output_filter, truncate_message,
-    ObservableStreamProcessor, StreamProcessor,
+    create_streaming_response, create_streaming_response_with_output_filter, response_cost_usd,
+    truncate_message, with_keep_alive, with_stream_error_event, ObservableStreamProcessor,
+    StreamProcessor,
 };
+use crate::structured_output::{StructuredOutputCheck, StructuredOutputProcessor};
+use crate::system_prompt;
+use crate::tenancy::TenantStateStorage;
 use crate::tracing::{
     collect_custom_trace_attributes, llm as tracing_llm, operation_component,
     plano as tracing_plano, set_service_name,
 };
+use crate::usage_export::UsageLedger;
+use content_filter::{is_content_filtered, may_be_refusal};
 use model_selection::router_chat_get_upstream_model;
 
 const PERPLEXITY_PROVIDER_PREFIX: &str = "perplexity/";
@@ -92,17 +128,40 @@ async fn llm_chat_inner(
         }
     });
 
+    let tenant = match state.tenancy.resolve(&request_headers) {
+        Ok(tenant) => tenant,
+        Err(err) => return Ok(err.into_response()),
+    };
+    let state_storage = TenantStateStorage::scope(state.state_storage.clone(), tenant.as_deref());
+    // Keyed before provider credentials are put on the headers.
+    let usage_ledger = state.usage_ledger.as_ref().map(|ledger| {
+        let client_key =
+            ledger.client_key(&request_headers, tenant.as_ref().map(|t| t.id.as_str()));
+        (Arc::clone(ledger), client_key)
+    });
+
+    // A reconnecting client picks up the stream it lost instead of starting over.
+    if let Some((stream, position)) = state
+        .stream_replay
+        .as_ref()
+        .and_then(|replay| replay.resume(&request_headers, tenant.as_ref().map(|t| t.id.as_str())))
+    {
+        return Ok(resume_response(stream, position));
+    }
+
     // Session pinning: extract session ID and check cache before routing
     let session_id: Option<String> = request_headers
         .get(MODEL_AFFINITY_HEADER)
         .and_then(|h| h.to_str().ok())
         .map(|s| s.to_string());
-    let tenant_id: Option<String> = state
-        .orchestrator_service
-        .tenant_header()
-        .and_then(|hdr| request_headers.get(hdr))
-        .and_then(|v| v.to_str().ok())
-        .map(|s| s.to_string());
+    let tenant_id: Option<String> = tenant.as_ref().map(|t| t.id.clone()).or_else(|| {
+        state
+            .orchestrator_service
+            .tenant_header()
+            .and_then(|hdr| request_headers.get(hdr))
+            .and_then(|v| v.to_str().ok())
+            .map(|s| s.to_string())
+    });
     let cached_route = if let Some(ref sid) = session_id {
         state
             .orchestrator_service
@@ -141,6 +200,7 @@ async fn llm_chat_inner(
         request,
         &request_path,
         &state.model_aliases,
+        &state.model_deprecations,
         &state.llm_providers,
     )
     .await
@@ -153,6 +213,7 @@ async fn llm_chat_inner(
         mut client_request,
         chat_request_bytes,
         model_from_request,
+        deprecation,
         alias_resolved_model,
         model_name_only,
         is_streaming_request,
@@ -165,6 +226,8 @@ async fn llm_chat_inner(
         client_api,
         provider_id,
     } = parsed;
+    let inline_routing_preferences = inline_routing_preferences
+        .or_else(|| tenant.as_ref().and_then(|t| t.routing_preferences.clone()));
 
     // Record LLM-specific span attributes
     let span = tracing::Span::current();
@@ -180,8 +243,23 @@ async fn llm_chat_inner(
         span.record(tracing_llm::TOOLS, formatted_tools.as_str());
     }
     if let Some(preview) = &user_message_preview {
+        let preview = match &state.secret_guardrail {
+            Some(guardrail) => guardrail.mask(preview),
+            None => preview.clone(),
+        };
         span.record(tracing_llm::USER_MESSAGE_PREVIEW, preview.as_str());
     }
+    if deprecation.is_some() {
+        get_active_span(|span| {
+            span.set_attribute(opentelemetry::KeyValue::new(
+                tracing_llm::DEPRECATED_MODEL,
+                model_from_request.clone(),
+            ));
+        });
+    }
+    let deprecation_warning = deprecation
+        .as_ref()
+        .and_then(|d| header::HeaderValue::from_str(&warning_text(&model_from_request, d)).ok());
 
     // --- Phase 1b: Input filter processing for model listener ---
     if let Some(ref input_chain) = state.filter_pipeline.input {
@@ -256,11 +334,32 @@ async fn llm_chat_inner(
         client_request.normalize_for_upstream(provider_id, &upstream_api);
     }
 
+    // `background` responses are answered at once and finished off the request
+    // path; the flag itself is not sent upstream.
+    let background = match &mut client_request {
+        ProviderRequestType::ResponsesAPIRequest(req) => req.background.take().unwrap_or(false),
+        _ => false,
+    };
+    if background {
+        if is_streaming_request {
+            return Ok(BrightStaffError::InvalidRequest(
+                "background responses cannot be streamed".to_string(),
+            )
+            .into_response());
+        }
+        if state_storage.is_none() {
+            return Ok(BrightStaffError::InvalidRequest(
+                "background responses need state_storage to be configured".to_string(),
+            )
+            .into_response());
+        }
+    }
+
     // --- Phase 2: Resolve conversation state (v1/responses API) ---
     let state_ctx = match resolve_conversation_state(
         &mut client_request,
         is_responses_api_client,
-        &state.state_storage,
+        &state_storage,
         &state.llm_providers,
         &alias_resolved_model,
         &request_path,
@@ -272,6 +371,68 @@ async fn llm_chat_inner(
         Err(response) => return Ok(response),
     };
 
+    // --- Phase 2b: Mask or refuse credentials before anything leaves the gateway ---
+    if let Some(guardrail) = &state.secret_guardrail {
+        if let Ok(body) = ProviderRequestType::to_bytes(&client_request) {
+            match guardrail.check(&body) {
+                Verdict::Clean => {}
+                Verdict::Masked(masked, kinds) => {
+                    warn!(kinds = ?kinds, "masked credentials in prompt");
+                    let api = client_api
+                        .as_ref()
+                        .expect("endpoint validated in parse_and_validate_request");
+                    match ProviderRequestType::try_from((&masked[..], api)) {
+                        Ok(updated_request) => client_request = updated_request,
+                        Err(err) => {
+                            warn!(error = %err, "masked request did not parse");
+                            return Ok(BrightStaffError::SecretDetected {
+                                kinds: kinds.iter().map(|k| k.to_string()).collect(),
+                            }
+                            .into_response());
+                        }
+                    }
+                }
+                Verdict::Blocked(kinds) => {
+                    warn!(kinds = ?kinds, "refused request carrying credentials");
+                    return Ok(BrightStaffError::SecretDetected {
+                        kinds: kinds.iter().map(|k| k.to_string()).collect(),
+                    }
+                    .into_response());
+                }
+            }
+        }
+    }
+
+    // --- Phase 2b': Refuse prompts flagged by moderation ---
+    if let Err(err) = moderation::screen(
+        &state,
+        client_request.extract_messages_text(),
+        &request_headers,
+        &request_id,
+    )
+    .await
+    {
+        return Ok(err.into_response());
+    }
+
+    // --- Phase 2c: Inject retrieved documents ahead of the user's message,
+    // so routing sees the augmented conversation ---
+    if let (Some(retriever), Some(api)) = (state.retriever.as_ref(), client_api.as_ref()) {
+        if let Ok(body) = ProviderRequestType::to_bytes(&client_request) {
+            if let Some(augmented) = retriever.augment(&body, api, &model_name_only).await {
+                match ProviderRequestType::try_from((&augmented[..], api)) {
+                    Ok(updated_request) => client_request = updated_request,
+                    Err(err) => warn!(
+                        error = %err,
+                        "request with retrieved context did not parse, sending it without"
+                    ),
+                }
+            }
+        }
+    }
+
+    let prompt_chars = client_request.extract_messages_text().len();
+
     // Serialize request for upstream BEFORE router consumes it
     let client_request_bytes_for_upstream: Bytes =
         match ProviderRequestType::to_bytes(&client_request) {
@@ -285,13 +446,14 @@ async fn llm_chat_inner(
         };
 
     // --- Phase 3: Route the request (or use pinned model from session cache) ---
-    let resolved_model = if let Some(cached_model) = pinned_model {
+    let route_preferences = inline_routing_preferences.clone();
+    let (resolved_model, ranked_models, route_name) = if let Some(cached_model) = pinned_model {
         info!(
             session_id = %session_id.as_deref().unwrap_or(""),
             model = %cached_model,
             "using pinned routing decision from cache"
         );
-        cached_model
+        (cached_model, Vec::new(), pinned_route_name)
     } else {
         let routing_span = info_span!(
             "routing",
@@ -325,8 +487,11 @@ async fn llm_chat_inner(
             }
         };
 
-        let (router_selected_model, route_name) =
-            (routing_result.model_name, routing_result.route_name);
+        let (router_selected_model, route_name, ranked_models) = (
+            routing_result.model_name,
+            routing_result.route_name,
+            routing_result.models,
+        );
         let model = if router_selected_model != "none" {
             router_selected_model
         } else {
@@ -348,31 +513,644 @@ async fn llm_chat_inner(
         if let Some(ref sid) = session_id {
             state
                 .orchestrator_service
-                .cache_route(sid.clone(), tenant_id.as_deref(), model.clone(), route_name)
+                .cache_route(
+                    sid.clone(),
+                    tenant_id.as_deref(),
+                    model.clone(),
+                    route_name.clone(),
+                )
+                .await;
+        }
+
+        (model, ranked_models, route_name)
+    };
+
+    // --- Phase 3b: Pick spillover candidates: the routed model, then the
+    // router's lower-ranked choices, minus those the tenant may not use or
+    // that are cooling down after a 429.
+    let mut candidates: Vec<(String, String)> = Vec::new();
+    let route_preference = route_name.as_deref().and_then(|name| {
+        route_preferences
+            .iter()
+            .flatten()
+            .find(|p| p.name == name)
+            .or_else(|| state.orchestrator_service.route_preference(name))
+    });
+    let mut content_filter_fallback: Option<(String, String)> = None;
+    let mut hedge_model: Option<(String, String)> = None;
+    let mut needs_inline_images = false;
+    {
+        let providers = state.llm_providers.read().await;
+        let provider_name = |model: &str| {
+            providers
+                .get(model)
+                .map(|p| p.name.clone())
+                .unwrap_or_else(|| model.to_string())
+        };
+        if let Some(fallback) = route_preference.and_then(|p| p.content_filter_fallback.clone()) {
+            let fallback_provider = provider_name(&fallback);
+            content_filter_fallback = Some((fallback, fallback_provider));
+        }
+        if let Some(model) = route_preference.and_then(|p| p.hedging.as_ref()?.model.clone()) {
+            let hedge_provider = provider_name(&model);
+            hedge_model = Some((model, hedge_provider));
+        }
+        for model in std::iter::once(resolved_model.clone()).chain(ranked_models) {
+            if model == "none" || candidates.iter().any(|(m, _)| *m == model) {
+                continue;
+            }
+            // A bare model name served by several providers tries them
+            // fastest healthy first.
+            let mut group = vec![(model.clone(), provider_name(&model))];
+            group.extend(
+                providers
+                    .serving_same_model(&model)
+                    .into_iter()
+                    .map(|p| (p.name.clone(), p.name.clone())),
+            );
+            group.retain(|(_, provider)| !candidates.iter().any(|(_, p)| p == provider));
+            state
+                .provider_health
+                .order_fastest_first(&mut group, is_streaming_request);
+            for (model, provider_name) in group {
+                needs_inline_images |= providers
+                    .get(&model)
+                    .is_some_and(|p| state.image_inliner.applies_to(&p.provider_interface));
+                candidates.push((model, provider_name));
+            }
+        }
+    }
+    if let Some(tenant) = tenant.as_ref() {
+        let (allowed, denied): (Vec<_>, Vec<_>) = candidates
+            .into_iter()
+            .partition(|(_, provider_name)| tenant.allows_model(provider_name));
+        if allowed.is_empty() {
+            let model = denied
+                .into_iter()
+                .next()
+                .map(|(_, p)| p)
+                .unwrap_or_default();
+            warn!(tenant = %tenant.id, model = %model, "model not available to tenant");
+            return Ok(common::errors::BrightStaffError::ModelNotAllowedForTenant {
+                model,
+                tenant: tenant.id.clone(),
+            }
+            .into_response());
+        }
+        candidates = allowed;
+        content_filter_fallback =
+            content_filter_fallback.filter(|(_, provider_name)| tenant.allows_model(provider_name));
+        hedge_model = hedge_model.filter(|(_, provider_name)| tenant.allows_model(provider_name));
+    }
+    let (ready, cooling): (Vec<_>, Vec<_>) =
+        candidates.into_iter().partition(|(_, provider_name)| {
+            state.provider_cooldowns.remaining(provider_name).is_none()
+        });
+    if ready.is_empty() {
+        let retry_after = cooling
+            .iter()
+            .filter_map(|(_, provider_name)| state.provider_cooldowns.remaining(provider_name))
+            .min()
+            .unwrap_or_default();
+        warn!(models = ?cooling, "all candidate models are cooling down");
+        return Ok(common::errors::BrightStaffError::ProvidersCoolingDown {
+            models: cooling.into_iter().map(|(_, p)| p).collect(),
+            retry_after_secs: retry_after.as_secs_f64().ceil() as u64,
+        }
+        .into_response());
+    }
+    if !cooling.is_empty() {
+        info!(skipped = ?cooling, "skipping models cooling down after a rate limit");
+    }
+
+    // Let plugins rewrite the request; server-side policies below still apply.
+    let client_request_bytes_for_upstream = if state.plugins.has_hooks(Hook::Request) {
+        Arc::clone(&state.plugins)
+            .apply(Hook::Request, client_request_bytes_for_upstream)
+            .await
+    } else {
+        client_request_bytes_for_upstream
+    };
+
+    let model_listener = state
+        .listeners
+        .iter()
+        .find(|l| l.listener_type == ListenerType::Model);
+
+    // Enforce the route's and then the model listener's system prompts.
+    let system_prompts: Vec<&SystemPromptPolicy> = route_preference
+        .and_then(|p| p.system_prompt.as_ref())
+        .into_iter()
+        .chain(model_listener.and_then(|l| l.system_prompt.as_ref()))
+        .collect();
+
+    // The listener, not the client, decides whether the gateway may drop
+    // input it can't convert for the upstream.
+    if model_listener.is_some_and(|l| l.transform_mode == TransformMode::Strict) {
+        request_headers.insert(
+            ARCH_STRICT_TRANSFORM_HEADER,
+            header::HeaderValue::from_static("true"),
+        );
+    } else {
+        request_headers.remove(ARCH_STRICT_TRANSFORM_HEADER);
+    }
+    let client_request_bytes_for_upstream = client_api
+        .as_ref()
+        .and_then(|api| {
+            system_prompt::apply(&client_request_bytes_for_upstream, api, &system_prompts)
+        })
+        .unwrap_or(client_request_bytes_for_upstream);
+
+    // Prune long tool results and history on routes that ask for it.
+    let client_request_bytes_for_upstream = match (
+        route_preference.and_then(|p| p.prompt_compression.as_ref()),
+        client_api.as_ref(),
+    ) {
+        (Some(settings), Some(api)) => {
+            prompt_compression::apply(&client_request_bytes_for_upstream, api, settings)
+                .unwrap_or(client_request_bytes_for_upstream)
+        }
+        _ => client_request_bytes_for_upstream,
+    };
+
+    // Hold the output to the route's and the tenant's token budgets.
+    let output_budgets: Vec<&OutputTokenBudget> = route_preference
+        .and_then(|p| p.output_token_budget.as_ref())
+        .into_iter()
+        .chain(tenant.as_ref().and_then(|t| t.output_token_budget.as_ref()))
+        .collect();
+    let output_token_limit = output_budgets.iter().map(|b| b.max_tokens).min();
+    let client_request_bytes_for_upstream = match output_budget::enforce(
+        &client_request_bytes_for_upstream,
+        &output_budgets,
+        is_responses_api_client,
+    ) {
+        Ok(budgeted) => budgeted.unwrap_or(client_request_bytes_for_upstream),
+        Err(err) => {
+            warn!(error = %err, "request exceeds output token budget");
+            return Ok(err.into_response());
+        }
+    };
+
+    // Inline remote images once for all candidates; data URLs are accepted everywhere.
+    let client_request_bytes_for_upstream = if needs_inline_images {
+        state
+            .image_inliner
+            .inline(client_request_bytes_for_upstream)
+            .await
+    } else {
+        client_request_bytes_for_upstream
+    };
+
+    // --- Phase 3c': Queue background responses and call the top candidate
+    // on a task of its own; clients poll `GET /v1/responses/{id}` ---
+    if let Some(storage) = state_storage.as_ref().filter(|_| background) {
+        let (model, provider_name) = ready[0].clone();
+        let mut queued = match responses::enqueue(
+            storage,
+            &model_from_request,
+            &provider_name,
+            state_ctx.original_input_items.clone(),
+        )
+        .await
+        {
+            Ok(queued) => queued,
+            Err(err) => {
+                warn!(error = %err, "failed to queue background response");
+                return Ok(BrightStaffError::InternalServerError(err.to_string()).into_response());
+            }
+        };
+        let accepted = responses::json_response(
+            queued
+                .response
+                .as_ref()
+                .expect("queued responses are stored with their response"),
+        );
+        info!(response_id = %queued.response_id, model = %model, "queued background response");
+
+        let state = Arc::clone(&state);
+        let storage = Arc::clone(storage);
+        let tenant_id = tenant_id.clone();
+        let request_headers = request_headers.clone();
+        let url = full_qualified_llm_provider_url.clone();
+        let body = client_request_bytes_for_upstream.clone();
+        let model_name_only = model_name_only.clone();
+        let client_api = client_api.clone();
+        tokio::spawn(
+            async move {
+                responses::start(&storage, &mut queued).await;
+                let call = UpstreamCall {
+                    state: &state,
+                    tenant_id: tenant_id.as_deref(),
+                    request_headers: &request_headers,
+                    upstream_url: &url,
+                    client_api: client_api.as_ref(),
+                    body: &body,
+                    model_name_only: &model_name_only,
+                    is_streaming_request: false,
+                };
+                // The concurrency slots are held until the answer is stored.
+                let (outcome, _permits) = match call.attempt(&model, &provider_name, false).await {
+                    Ok(attempt) => {
+                        state.provider_cooldowns.observe(
+                            &provider_name,
+                            attempt.response.status(),
+                            attempt.response.headers(),
+                        );
+                        (Ok(attempt.response), Some(attempt.permits))
+                    }
+                    Err(response) => (Err(response), None),
+                };
+                responses::complete(&storage, queued, outcome).await;
+            }
+            .in_current_span(),
+        );
+        return Ok(with_warning(accepted, deprecation_warning));
+    }
+
+    // --- Phase 3c: Serve repeated requests from the route's response cache,
+    // refreshing stale entries in the background. Providers billed to the
+    // client's own key are never cached: the key is not part of the cache key ---
+    let passthrough_auth = {
+        let providers = state.llm_providers.read().await;
+        let mut serving = ready
+            .iter()
+            .chain(hedge_model.iter())
+            .chain(content_filter_fallback.iter());
+        serving.any(|(_, provider_name)| {
+            providers
+                .get(provider_name)
+                .is_some_and(|p| p.passthrough_auth == Some(true))
+        })
+    };
+    let cache = route_preference
+        .and_then(|p| p.response_cache.as_ref())
+        .and_then(CachePolicy::from_settings)
+        .filter(|_| !is_streaming_request && !state_ctx.should_manage_state && !passthrough_auth)
+        .map(|policy| {
+            let key = ResponseCache::key(
+                tenant_id.as_deref(),
+                &resolved_model,
+                &client_request_bytes_for_upstream,
+            );
+            (key, policy)
+        });
+    if let Some((key, policy)) = cache {
+        match state.response_cache.lookup(key) {
+            Lookup::Fresh(cached) => {
+                return Ok(with_warning(
+                    cached.into_response("hit"),
+                    deprecation_warning,
+                ))
+            }
+            Lookup::Stale(cached) => {
+                if state.response_cache.begin_refresh(key) {
+                    let (model, provider_name) = ready[0].clone();
+                    let state = Arc::clone(&state);
+                    let mut headers = request_headers.clone();
+                    let url = full_qualified_llm_provider_url.clone();
+                    let body = client_request_bytes_for_upstream.clone();
+                    let model_name_only = model_name_only.clone();
+                    let client_api = client_api.clone();
+                    tokio::spawn(async move {
+                        state.access_key_slots.apply(&provider_name, &mut headers);
+                        state.vertex_tokens.apply(&provider_name, &mut headers);
+                        state
+                            .bedrock_credentials
+                            .apply(&provider_name, &mut headers);
+                        if let Ok(response) = call_provider(
+                            &state,
+                            client_api.as_ref(),
+                            &url,
+                            &mut headers,
+                            body,
+                            &model,
+                            &model_name_only,
+                            false,
+                        )
+                        .await
+                        {
+                            refresh_cached_response(&state, key, policy, response).await;
+                        }
+                        state.response_cache.end_refresh(key);
+                    });
+                }
+                return Ok(with_warning(
+                    cached.into_response("stale"),
+                    deprecation_warning,
+                ));
+            }
+            Lookup::Miss => {}
+        }
+    }
+
+    // --- Phase 4: Forward to upstream, spilling over to the next candidate on
+    // 429 and retrying on the route's fallback model after a content-filter refusal ---
+    let request_start_time = std::time::Instant::now();
+    let mut annotate_served_by = content_filter_fallback.is_some();
+    let mut fallback_reason: Option<&'static str> = None;
+    // Only the first attempt is hedged, and never one that may call tools.
+    let mut hedge_delay = route_preference
+        .and_then(|p| p.hedging.as_ref())
+        .filter(|_| tool_names.as_ref().is_none_or(|tools| tools.is_empty()))
+        .and_then(|h| parse_duration_ms(&h.delay))
+        .map(Duration::from_millis);
+    let mut candidates: VecDeque<(String, String)> = ready.into();
+    let (mut llm_response, resolved_model, provider_name, concurrency_permits) = loop {
+        let (mut model, mut provider_name) =
+            candidates.pop_front().expect("at least one candidate");
+        let hedge = hedge_delay.take().and_then(|delay| {
+            hedge_model
+                .clone()
+                .or_else(|| candidates.front().cloned())
+                .filter(|(hedge_model, hedge_provider)| {
+                    *hedge_model != model
+                        && state.provider_cooldowns.remaining(hedge_provider).is_none()
+                })
+                .map(|target| (delay, target))
+        });
+        let call = UpstreamCall {
+            state: &state,
+            tenant_id: tenant.as_ref().map(|t| t.id.as_str()),
+            request_headers: &request_headers,
+            upstream_url: &full_qualified_llm_provider_url,
+            client_api: client_api.as_ref(),
+            body: &client_request_bytes_for_upstream,
+            model_name_only: &model_name_only,
+            is_streaming_request,
+        };
+        let attempt = match hedge {
+            None => call.attempt(&model, &provider_name, false).await,
+            Some((delay, (hedge_model, hedge_provider))) => {
+                let (attempt, winner) = hedging::race(
+                    call.attempt(&model, &provider_name, true),
+                    delay,
+                    || call.attempt(&hedge_model, &hedge_provider, true),
+                    |attempt| {
+                        attempt
+                            .as_ref()
+                            .is_ok_and(|a| a.response.status().is_success())
+                    },
+                )
                 .await;
+                if winner == Winner::Hedge {
+                    info!(model = %model, hedge = %hedge_model, "hedged request answered first");
+                    candidates.retain(|(m, _)| *m != hedge_model);
+                    model = hedge_model;
+                    provider_name = hedge_provider;
+                    annotate_served_by = true;
+                    fallback_reason = Some("hedge");
+                }
+                attempt
+            }
+        };
+        let Attempt {
+            response: llm_response,
+            permits: concurrency_permits,
+            headers: attempt_headers,
+        } = match attempt {
+            Ok(attempt) => attempt,
+            Err(response) => return Ok(response),
+        };
+        state.provider_cooldowns.observe(
+            &provider_name,
+            llm_response.status(),
+            llm_response.headers(),
+        );
+        if llm_response.status().is_server_error() {
+            state.provider_health.record_failure(&provider_name);
         }
 
-        model
+        let llm_response = match content_filter_fallback
+            .as_ref()
+            .filter(|_| may_be_refusal(llm_response.status(), is_streaming_request))
+        {
+            Some(_) => {
+                let (llm_response, body) = match buffer_response(llm_response).await {
+                    Ok(buffered) => buffered,
+                    Err(err) => return Ok(read_failed(err)),
+                };
+                let fallback = if is_content_filtered(llm_response.status(), &body) {
+                    content_filter_fallback.take_if(|(fallback_model, _)| *fallback_model != model)
+                } else {
+                    None
+                };
+                if let Some(fallback) = fallback {
+                    warn!(
+                        model = %model,
+                        fallback = %fallback.0,
+                        "content filter refusal, retrying on fallback model"
+                    );
+                    fallback_reason = Some("content_filter");
+                    candidates = VecDeque::from([fallback]);
+                    continue;
+                }
+                llm_response
+            }
+            None => llm_response,
+        };
+
+        if llm_response.status() == StatusCode::TOO_MANY_REQUESTS {
+            let wait = retry_after(llm_response.headers());
+            if let Some(wait) = wait {
+                state.provider_cooldowns.cool_down(&provider_name, wait);
+            }
+            if !candidates.is_empty() {
+                warn!(
+                    model = %model,
+                    retry_after_ms = ?wait.map(|w| w.as_millis()),
+                    "upstream rate limited, spilling over to next candidate"
+                );
+                continue;
+            }
+        }
+
+        request_headers = attempt_headers;
+        break (llm_response, model, provider_name, concurrency_permits);
     };
     tracing::Span::current().record(tracing_llm::MODEL_NAME, resolved_model.as_str());
 
-    // --- Phase 4: Forward to upstream and stream back ---
-    send_upstream(
-        &state.http_client,
-        &full_qualified_llm_provider_url,
-        &mut request_headers,
-        client_request_bytes_for_upstream,
+    if llm_response.status() == StatusCode::TOO_MANY_REQUESTS {
+        llm_response = match normalize_rate_limit(llm_response, &resolved_model).await {
+            Ok(normalized) => normalized,
+            Err(err) => return Ok(read_failed(err)),
+        };
+    }
+
+    // Let plugins rewrite complete responses; streamed ones pass through as is.
+    if !is_streaming_request
+        && llm_response.status().is_success()
+        && state.plugins.has_hooks(Hook::Response)
+    {
+        let (buffered, body) = match buffer_response(llm_response).await {
+            Ok(buffered) => buffered,
+            Err(err) => return Ok(read_failed(err)),
+        };
+        let rewritten = Arc::clone(&state.plugins)
+            .apply(Hook::Response, body.clone())
+            .await;
+        llm_response = if rewritten == body {
+            buffered
+        } else {
+            replace_body(buffered, rewritten)
+        };
+    }
+    if annotate_served_by {
+        if let Ok(served_by) = header::HeaderValue::from_str(&provider_name) {
+            llm_response
+                .headers_mut()
+                .insert(ARCH_SERVED_BY_HEADER, served_by);
+        }
+        if let Some(reason) = fallback_reason {
+            llm_response.headers_mut().insert(
+                ARCH_FALLBACK_REASON_HEADER,
+                header::HeaderValue::from_static(reason),
+            );
+        }
+    }
+
+    if let Some(warning) = deprecation_warning {
+        llm_response.headers_mut().insert(header::WARNING, warning);
+    }
+
+    if let Some((key, policy)) = cache {
+        if llm_response.status().is_success() {
+            let (buffered, body) = match buffer_response(llm_response).await {
+                Ok(buffered) => buffered,
+                Err(err) => return Ok(read_failed(err)),
+            };
+            let content_type = buffered.headers().get(header::CONTENT_TYPE).cloned();
+            state.response_cache.store(
+                key,
+                CachedResponse::new(buffered.status(), content_type, body),
+                policy,
+            );
+            llm_response = buffered;
+        }
+        llm_response
+            .headers_mut()
+            .insert(ARCH_CACHE_HEADER, header::HeaderValue::from_static("miss"));
+    }
+
+    // Report the expected cost and, for complete responses, the actual one.
+    if let Some(estimate) = cost::estimate_usd(
+        &state.pricing,
+        &resolved_model,
+        prompt_chars,
+        &client_request_bytes_for_upstream,
+    ) {
+        if let Ok(value) = header::HeaderValue::from_str(&cost::format_usd(estimate)) {
+            llm_response
+                .headers_mut()
+                .insert(ARCH_COST_ESTIMATE_HEADER, value);
+        }
+    }
+    if !is_streaming_request
+        && llm_response.status().is_success()
+        && state.pricing.price_for(&resolved_model).is_some()
+    {
+        let (buffered, body) = match buffer_response(llm_response).await {
+            Ok(buffered) => buffered,
+            Err(err) => return Ok(read_failed(err)),
+        };
+        llm_response = buffered;
+        if let Some(actual) = response_cost_usd(&state.pricing, &resolved_model, &body) {
+            if let Some(annotated) = cost::with_cost_metadata(&body, actual) {
+                llm_response = replace_body(llm_response, annotated);
+            }
+            if let Ok(value) = header::HeaderValue::from_str(&cost::format_usd(actual)) {
+                llm_response
+                    .headers_mut()
+                    .insert(ARCH_COST_ACTUAL_HEADER, value);
+            }
+        }
+    }
+
+    // Validate streamed `json_object` / `json_schema` output as it arrives.
+    let structured_output = client_api
+        .as_ref()
+        .filter(|_| is_streaming_request)
+        .and_then(|api| {
+            StructuredOutputCheck::for_request(&client_request_bytes_for_upstream, api)
+        });
+
+    let is_event_stream = is_streaming_request
+        && llm_response.status().is_success()
+        && llm_response
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.starts_with("text/event-stream"));
+
+    // Serve event streams through a replay window so they can be resumed.
+    let replay = state
+        .stream_replay
+        .as_ref()
+        .filter(|_| is_event_stream)
+        .map(|replay| replay.register(tenant.as_ref().map(|t| t.id.as_str())));
+
+    // Keep event streams alive while the model works on its first token.
+    let keep_alive = state.stream_keep_alive.filter(|_| is_event_stream);
+
+    let archive = state.archiver.as_ref().map(|archiver| {
+        (
+            Arc::clone(archiver),
+            ArchiveEntry::new(
+                request_id.clone(),
+                request_path.clone(),
+                model_from_request.clone(),
+                resolved_model.clone(),
+                llm_response.status().as_u16(),
+                client_request_bytes_for_upstream.clone(),
+            ),
+        )
+    });
+
+    let completion_event = state.event_bus.as_ref().map(|bus| {
+        (
+            Arc::clone(bus),
+            CompletionEvent::new(
+                request_id.clone(),
+                tenant.as_ref().map(|t| t.id.clone()),
+                model_from_request.clone(),
+                resolved_model.clone(),
+                llm_response.status().as_u16(),
+            ),
+        )
+    });
+
+    // Only answered requests are latency samples; server errors were
+    // recorded as failures when they came back.
+    let provider_health = llm_response
+        .status()
+        .is_success()
+        .then(|| (Arc::clone(&state.provider_health), provider_name.clone()));
+
+    stream_upstream_response(
+        llm_response,
+        request_start_time,
+        &request_headers,
         &model_from_request,
         &alias_resolved_model,
         &resolved_model,
-        &model_name_only,
         &request_path,
         is_streaming_request,
         messages_for_signals,
         state_ctx,
-        state.state_storage.clone(),
+        state_storage,
         request_id,
         &state.filter_pipeline,
+        concurrency_permits,
+        output_token_limit.filter(|_| is_streaming_request),
+        structured_output,
+        usage_ledger,
+        &state.pricing,
+        replay,
+        keep_alive,
+        archive,
+        completion_event,
+        provider_health,
+        client_api.filter(|_| is_event_stream),
     )
     .await
 }
@@ -386,6 +1164,8 @@ struct PreparedRequest {
     client_request: ProviderRequestType,
     chat_request_bytes: Bytes,
     model_from_request: String,
+    /// Set when `model_from_request` is deprecated and was rewritten.
+    deprecation: Option<ModelDeprecation>,
     alias_resolved_model: String,
     model_name_only: String,
     is_streaming_request: bool,
@@ -405,7 +1185,8 @@ struct PreparedRequest {
 async fn parse_and_validate_request(
     request: Request<hyper::body::Incoming>,
     request_path: &str,
-    model_aliases: &Option<HashMap<String, ModelAlias>>,
+    model_aliases: &ModelAliasTable,
+    model_deprecations: &HashMap<String, ModelDeprecation>,
     llm_providers: &Arc<RwLock<LlmProviders>>,
 ) -> Result<PreparedRequest, Response<BoxBody<Bytes, hyper::Error>>> {
     let raw_bytes = request
@@ -454,7 +1235,24 @@ async fn parse_and_validate_request(
     let model_from_request = client_request.model().to_string();
     let temperature = client_request.get_temperature();
     let is_streaming_request = client_request.is_streaming();
-    let alias_resolved_model = resolve_model_alias(&model_from_request, model_aliases);
+    // Retired model names are served by their replacement.
+    let deprecation = model_deprecations.get(&model_from_request).cloned();
+    let requested_model = match &deprecation {
+        Some(deprecation) => {
+            info!(
+                model = %model_from_request,
+                replacement = %deprecation.replacement,
+                "rewriting deprecated model to its replacement"
+            );
+            deprecation.replacement.as_str()
+        }
+        None => model_from_request.as_str(),
+    };
+    let alias_resolved_model =
+        resolve_model_alias(requested_model, model_aliases).map_err(|err| {
+            warn!(model = %model_from_request, "requested model is blocked by an alias");
+            err.into_response()
+        })?;
     let (provider_id, _, _) = get_provider_info(llm_providers, &alias_resolved_model).await;
 
     // Validate model exists in configuration
@@ -500,6 +1298,7 @@ async fn parse_and_validate_request(
         client_request,
         chat_request_bytes,
         model_from_request,
+        deprecation,
         alias_resolved_model,
         model_name_only,
         is_streaming_request,
@@ -628,36 +1427,152 @@ async fn resolve_conversation_state(
 // Phase 4 — Forward to upstream and stream the response back
 // ---------------------------------------------------------------------------
 
+/// Send the request upstream, or answer it in-process when `resolved_model`
+/// is a `mock` provider or traffic is being replayed. Configured chaos faults
+/// are injected into the response either way.
 #[allow(clippy::too_many_arguments)]
-async fn send_upstream(
-    http_client: &reqwest::Client,
+/// The parts of an upstream call shared by every attempt of a request.
+struct UpstreamCall<'a> {
+    state: &'a AppState,
+    tenant_id: Option<&'a str>,
+    request_headers: &'a hyper::HeaderMap,
+    upstream_url: &'a str,
+    client_api: Option<&'a SupportedAPIsFromClient>,
+    body: &'a Bytes,
+    model_name_only: &'a str,
+    is_streaming_request: bool,
+}
+
+/// An upstream response with the concurrency slots it holds and the
+/// headers it was requested with.
+struct Attempt {
+    response: reqwest::Response,
+    permits: ConcurrencyPermits,
+    headers: hyper::HeaderMap,
+}
+
+impl UpstreamCall<'_> {
+    /// Call `model`. A hedged stream only counts as answered once its first
+    /// bytes are in.
+    async fn attempt(
+        &self,
+        model: &str,
+        provider_name: &str,
+        hedged: bool,
+    ) -> Result<Attempt, Response<BoxBody<Bytes, hyper::Error>>> {
+        let mut headers = self.request_headers.clone();
+        self.state
+            .access_key_slots
+            .apply(provider_name, &mut headers);
+        self.state.vertex_tokens.apply(provider_name, &mut headers);
+        self.state
+            .bedrock_credentials
+            .apply(provider_name, &mut headers);
+
+        // Reserve concurrency slots for the upstream call
+        let permits = if self.state.concurrency_limiter.is_enabled() {
+            self.state
+                .concurrency_limiter
+                .acquire(provider_name, self.tenant_id, &headers)
+                .await
+                .map_err(|scope| {
+                    BrightStaffError::ConcurrencyLimitExceeded(scope.to_string()).into_response()
+                })?
+        } else {
+            ConcurrencyPermits::default()
+        };
+
+        let mut response = call_provider(
+            self.state,
+            self.client_api,
+            self.upstream_url,
+            &mut headers,
+            self.body.clone(),
+            model,
+            self.model_name_only,
+            self.is_streaming_request,
+        )
+        .await?;
+        if hedged && self.is_streaming_request {
+            response = hedging::first_bytes(response).await.map_err(read_failed)?;
+        }
+        Ok(Attempt {
+            response,
+            permits,
+            headers,
+        })
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn call_provider(
+    state: &AppState,
+    client_api: Option<&SupportedAPIsFromClient>,
     upstream_url: &str,
     request_headers: &mut hyper::HeaderMap,
     body: bytes::Bytes,
-    model_from_request: &str,
-    alias_resolved_model: &str,
     resolved_model: &str,
     model_name_only: &str,
-    request_path: &str,
     is_streaming_request: bool,
-    messages_for_signals: Option<Vec<Message>>,
-    state_ctx: ConversationStateContext,
-    state_storage: Option<Arc<dyn StateStorage>>,
-    request_id: String,
-    filter_pipeline: &Arc<FilterPipeline>,
-) -> Result<Response<BoxBody<Bytes, hyper::Error>>, hyper::Error> {
-    let span_name = if model_from_request == resolved_model {
-        format!("POST {} {}", request_path, resolved_model)
-    } else {
-        format!(
-            "POST {} {} -> {}",
-            request_path, model_from_request, resolved_model
-        )
-    };
-    get_active_span(|span| {
-        span.update_name(span_name.clone());
-    });
+) -> Result<reqwest::Response, Response<BoxBody<Bytes, hyper::Error>>> {
+    let recorder = state.traffic_recorder.as_ref();
+    let provider = state.llm_providers.read().await.get(resolved_model);
+    let response =
+        if let Some(recorder) = recorder.filter(|r| r.mode() == TrafficRecordingMode::Replay) {
+            recorder
+                .replay(resolved_model, &body)
+                .await
+                .ok_or_else(|| {
+                    common::errors::BrightStaffError::NoRecordedResponse(resolved_model.to_string())
+                        .into_response()
+                })?
+        } else if let Some(provider) =
+            provider.filter(|p| p.provider_interface == LlmProviderType::Mock)
+        {
+            debug!(provider = %provider.name, "answering with mock provider");
+            mock_provider::respond(
+                provider.mock.as_ref(),
+                client_api,
+                model_name_only,
+                &body,
+                is_streaming_request,
+                request_headers,
+            )
+            .await
+        } else {
+            let response = send_upstream_request(
+                &state.http_client,
+                upstream_url,
+                request_headers,
+                body.clone(),
+                resolved_model,
+                model_name_only,
+                is_streaming_request,
+            )
+            .await?;
+            match recorder {
+                Some(recorder) => recorder.record(resolved_model, &body, response),
+                None => response,
+            }
+        };
+    Ok(match &state.chaos {
+        Some(chaos) => chaos.apply(response).await,
+        None => response,
+    })
+}
 
+/// Send the request to the LLM gateway with `resolved_model` as the provider hint.
+///
+/// Returns `Err(Response)` when the gateway cannot be reached.
+async fn send_upstream_request(
+    http_client: &reqwest::Client,
+    upstream_url: &str,
+    request_headers: &mut hyper::HeaderMap,
+    body: bytes::Bytes,
+    resolved_model: &str,
+    model_name_only: &str,
+    is_streaming_request: bool,
+) -> Result<reqwest::Response, Response<BoxBody<Bytes, hyper::Error>>> {
     debug!(
         url = %upstream_url,
         provider_hint = %resolved_model,
@@ -684,23 +1599,142 @@ async fn send_upstream(
         propagator.inject_context(&cx, &mut HeaderInjector(request_headers));
     });
 
-    let request_start_time = std::time::Instant::now();
-
-    let llm_response = match http_client
+    http_client
         .post(upstream_url)
         .headers(request_headers.clone())
         .body(body)
         .send()
         .await
-    {
-        Ok(res) => res,
-        Err(err) => {
+        .map_err(|err| {
             let err_msg = format!("Failed to send request: {}", err);
             let mut internal_error = Response::new(full(err_msg));
             *internal_error.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
-            return Ok(internal_error);
-        }
+            internal_error
+        })
+}
+
+/// Read the whole upstream body, returning the response rebuilt around it
+/// so it can still be streamed to the client.
+async fn buffer_response(
+    response: reqwest::Response,
+) -> Result<(reqwest::Response, Bytes), reqwest::Error> {
+    let status = response.status();
+    let version = response.version();
+    let headers = response.headers().clone();
+    let body = response.bytes().await?;
+    let mut rebuilt = hyper::Response::new(body.clone());
+    *rebuilt.status_mut() = status;
+    *rebuilt.version_mut() = version;
+    *rebuilt.headers_mut() = headers;
+    Ok((reqwest::Response::from(rebuilt), body))
+}
+
+/// Store a background refresh of a stale cache entry, run through the
+/// plugins' response hooks like the response it replaces.
+async fn refresh_cached_response(
+    state: &Arc<AppState>,
+    key: u64,
+    policy: CachePolicy,
+    response: reqwest::Response,
+) {
+    if !response.status().is_success() {
+        warn!(status = %response.status(), "response cache refresh failed, keeping stale entry");
+        return;
+    }
+    let status = response.status();
+    let content_type = response.headers().get(header::CONTENT_TYPE).cloned();
+    let Ok(body) = response.bytes().await else {
+        return;
     };
+    let body = if state.plugins.has_hooks(Hook::Response) {
+        Arc::clone(&state.plugins).apply(Hook::Response, body).await
+    } else {
+        body
+    };
+    state
+        .response_cache
+        .store(key, CachedResponse::new(status, content_type, body), policy);
+    debug!("refreshed stale response cache entry");
+}
+
+/// `response` with its body swapped for `body`.
+fn replace_body(response: reqwest::Response, body: Bytes) -> reqwest::Response {
+    let mut rebuilt = hyper::Response::new(body);
+    *rebuilt.status_mut() = response.status();
+    *rebuilt.version_mut() = response.version();
+    *rebuilt.headers_mut() = response.headers().clone();
+    rebuilt.headers_mut().remove(header::CONTENT_LENGTH);
+    reqwest::Response::from(rebuilt)
+}
+
+/// Rewrite an upstream 429 into an OpenAI-style error with a `retry-after`
+/// in seconds, computed from the provider's own headers when it sent none.
+async fn normalize_rate_limit(
+    response: reqwest::Response,
+    model: &str,
+) -> Result<reqwest::Response, reqwest::Error> {
+    let wait = retry_after(response.headers());
+    let (response, body) = buffer_response(response).await?;
+    let mut response = replace_body(response, rate_limit_body(&body, model));
+    let headers = response.headers_mut();
+    headers.insert(
+        header::CONTENT_TYPE,
+        header::HeaderValue::from_static("application/json"),
+    );
+    headers.remove(header::CONTENT_ENCODING);
+    if let Some(wait) = wait {
+        headers.insert(
+            header::RETRY_AFTER,
+            header::HeaderValue::from(wait.as_secs_f64().ceil() as u64),
+        );
+    }
+    Ok(response)
+}
+
+fn read_failed(err: reqwest::Error) -> Response<BoxBody<Bytes, hyper::Error>> {
+    let mut bad_gateway = Response::new(full(format!("Failed to read upstream response: {}", err)));
+    *bad_gateway.status_mut() = StatusCode::BAD_GATEWAY;
+    bad_gateway
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn stream_upstream_response(
+    llm_response: reqwest::Response,
+    request_start_time: std::time::Instant,
+    request_headers: &hyper::HeaderMap,
+    model_from_request: &str,
+    alias_resolved_model: &str,
+    resolved_model: &str,
+    request_path: &str,
+    is_streaming_request: bool,
+    messages_for_signals: Option<Vec<Message>>,
+    state_ctx: ConversationStateContext,
+    state_storage: Option<Arc<dyn StateStorage>>,
+    request_id: String,
+    filter_pipeline: &Arc<FilterPipeline>,
+    concurrency_permits: ConcurrencyPermits,
+    output_token_limit: Option<u32>,
+    structured_output: Option<StructuredOutputCheck>,
+    usage_ledger: Option<(Arc<UsageLedger>, String)>,
+    pricing: &Arc<PricingTable>,
+    replay: Option<Arc<ReplayStream>>,
+    keep_alive: Option<Duration>,
+    archive: Option<(Arc<Archiver>, ArchiveEntry)>,
+    completion_event: Option<(Arc<EventBus>, CompletionEvent)>,
+    provider_health: Option<(Arc<ProviderHealth>, String)>,
+    stream_error_api: Option<SupportedAPIsFromClient>,
+) -> Result<Response<BoxBody<Bytes, hyper::Error>>, hyper::Error> {
+    let span_name = if model_from_request == resolved_model {
+        format!("POST {} {}", request_path, resolved_model)
+    } else {
+        format!(
+            "POST {} {} -> {}",
+            request_path, model_from_request, resolved_model
+        )
+    };
+    get_active_span(|span| {
+        span.update_name(span_name.clone());
+    });
 
     // Propagate upstream headers and status
     let response_headers = llm_response.headers().clone();
@@ -743,6 +1777,11 @@ async fn send_upstream(
     }
 
     let byte_stream = llm_response.bytes_stream();
+    // A broken event stream ends with an error event rather than a bare disconnect.
+    let byte_stream: BoxStream<'static, Result<Bytes, reqwest::Error>> = match stream_error_api {
+        Some(client_api) => with_stream_error_event(byte_stream, client_api).boxed(),
+        None => byte_stream.boxed(),
+    };
 
     // Create base processor for metrics and tracing
     let base_processor = ObservableStreamProcessor::new(
@@ -750,7 +1789,26 @@ async fn send_upstream(
         span_name,
         request_start_time,
         messages_for_signals,
-    );
+    )
+    .with_pricing(Arc::clone(pricing), resolved_model);
+    let base_processor = match usage_ledger {
+        Some((ledger, client_key)) => base_processor.with_usage_ledger(ledger, client_key),
+        None => base_processor,
+    };
+    let base_processor = match archive {
+        Some((archiver, entry)) => base_processor.with_archive(archiver, entry),
+        None => base_processor,
+    };
+    let base_processor = match completion_event {
+        Some((bus, event)) => base_processor.with_completion_event(bus, event),
+        None => base_processor,
+    };
+    let base_processor = match provider_health {
+        Some((health, provider)) => {
+            base_processor.with_provider_health(health, provider, is_streaming_request)
+        }
+        None => base_processor,
+    };
 
     let output_filter_request_headers = if filter_pipeline.has_output_filters() {
         Some(request_headers.clone())
@@ -784,6 +1842,31 @@ async fn send_upstream(
         Box::new(base_processor)
     };
 
+    // Hold concurrency slots until the response has been fully streamed back.
+    let processor: Box<dyn StreamProcessor> = if concurrency_permits.is_empty() {
+        processor
+    } else {
+        Box::new(PermitHoldingProcessor::new(processor, concurrency_permits))
+    };
+
+    // Cut off streams that run past the output token budget.
+    let processor: Box<dyn StreamProcessor> = match output_token_limit {
+        Some(max_tokens) => Box::new(OutputBudgetProcessor::new(processor, max_tokens)),
+        None => processor,
+    };
+
+    // End streams whose output breaks the requested JSON format.
+    let processor: Box<dyn StreamProcessor> = match structured_output {
+        Some(check) => Box::new(StructuredOutputProcessor::new(processor, check)),
+        None => processor,
+    };
+
+    // Events go to the replay window, and the client reads them from there.
+    let processor: Box<dyn StreamProcessor> = match &replay {
+        Some(stream) => Box::new(ResumableStreamProcessor::new(processor, Arc::clone(stream))),
+        None => processor,
+    };
+
     let streaming_response = if let (Some(output_chain), Some(filter_headers)) = (
         filter_pipeline.output.as_ref().filter(|c| !c.is_empty()),
         output_filter_request_headers,
@@ -799,7 +1882,20 @@ async fn send_upstream(
         create_streaming_response(byte_stream, processor)
     };
 
-    match response.body(streaming_response.body) {
+    let body = match replay {
+        Some(stream) => {
+            if let Some(headers) = response.headers_mut() {
+                headers.remove(header::CONTENT_LENGTH);
+            }
+            stream.body(0)
+        }
+        None => streaming_response.body,
+    };
+    let body = match keep_alive {
+        Some(interval) => with_keep_alive(body, interval),
+        None => body,
+    };
+    match response.body(body) {
         Ok(response) => Ok(response),
         Err(err) => {
             let err_msg = format!("Failed to create response: {}", err);
@@ -814,22 +1910,50 @@ async fn send_upstream(
 // Helpers
 // ---------------------------------------------------------------------------
 
-/// Resolves model aliases by looking up the requested model in the model_aliases map.
-/// Returns the target model if an alias is found, otherwise returns the original model.
-fn resolve_model_alias(
+/// Resolves model aliases by looking up the requested model in the model_aliases table.
+/// Returns the target model if an alias matches, otherwise the original model, and an
+/// error when a blocking alias matches.
+pub(crate) fn resolve_model_alias(
     model_from_request: &str,
-    model_aliases: &Option<HashMap<String, ModelAlias>>,
-) -> String {
-    if let Some(aliases) = model_aliases.as_ref() {
-        if let Some(model_alias) = aliases.get(model_from_request) {
+    model_aliases: &ModelAliasTable,
+) -> Result<String, BrightStaffError> {
+    match model_aliases.resolve(model_from_request) {
+        Some(AliasResolution::Target(target)) => {
             debug!(
                 "Model Alias: 'From {}' -> 'To {}'",
-                model_from_request, model_alias.target
+                model_from_request, target
             );
-            return model_alias.target.clone();
+            Ok(target.to_string())
         }
+        Some(AliasResolution::Blocked(alias)) => Err(BrightStaffError::ModelBlocked {
+            model: model_from_request.to_string(),
+            alias: alias.to_string(),
+        }),
+        None => Ok(model_from_request.to_string()),
+    }
+}
+
+/// `Warning` header text telling the client which model replaced a deprecated one.
+fn warning_text(model: &str, deprecation: &ModelDeprecation) -> String {
+    let sunset = deprecation
+        .sunset
+        .as_deref()
+        .map(|sunset| format!(" (sunset {sunset})"))
+        .unwrap_or_default();
+    format!(
+        "299 - \"model '{model}' is deprecated{sunset}; served by '{}'\"",
+        deprecation.replacement
+    )
+}
+
+fn with_warning(
+    mut response: Response<BoxBody<Bytes, hyper::Error>>,
+    warning: Option<header::HeaderValue>,
+) -> Response<BoxBody<Bytes, hyper::Error>> {
+    if let Some(warning) = warning {
+        response.headers_mut().insert(header::WARNING, warning);
     }
-    model_from_request.to_string()
+    response
 }
 
 /// Calculates the upstream path for the provider based on the model name.
@@ -854,6 +1978,7 @@ async fn get_upstream_path(
         is_streaming,
         base_url_path_prefix.as_deref(),
         use_unversioned_paths,
+        None,
     )
 }
 
@@ -884,8 +2009,8 @@ async fn get_provider_info(
 
 #[cfg(test)]
 mod tests {
-    use super::{get_provider_info, get_upstream_path};
-    use common::configuration::{LlmProvider, LlmProviderType};
+    use super::{get_provider_info, get_upstream_path, warning_text};
+    use common::configuration::{LlmProvider, LlmProviderType, ModelDeprecation};
     use common::llm_providers::LlmProviders;
     use hermesllm::apis::OpenAIApi;
     use hermesllm::clients::SupportedAPIsFromClient;
@@ -977,8 +2102,24 @@ mod tests {
                 false,
                 None,
                 false,
+                None,
             );
         assert_eq!(fail_path, "/v1/chat/completions");
         assert_ne!(success_path, fail_path);
     }
+
+    #[test]
+    fn test_deprecation_warning_names_the_replacement() {
+        let mut deprecation = ModelDeprecation {
+            replacement: "gpt-4o".to_string(),
+            sunset: None,
+        };
+        assert_eq!(
+            warning_text("gpt-4-32k", &deprecation),
+            "299 - \"model 'gpt-4-32k' is deprecated; served by 'gpt-4o'\""
+        );
+        deprecation.sunset = Some("2025-06-06".to_string());
+        assert!(warning_text("gpt-4-32k", &deprecation)
+            .contains("deprecated (sunset 2025-06-06); served by 'gpt-4o'"));
+    }
 }
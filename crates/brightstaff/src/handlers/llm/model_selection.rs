@@ -1,6 +1,7 @@
 use common::configuration::TopLevelRoutingPreference;
+use hermesllm::apis::openai::ChatCompletionsRequest;
 use hermesllm::clients::endpoints::SupportedUpstreamAPIs;
-use hermesllm::ProviderRequestType;
+use hermesllm::{ProviderId, ProviderRequestType};
 use hyper::StatusCode;
 use std::sync::Arc;
 use tracing::{debug, info, warn};
@@ -53,6 +54,10 @@ pub async fn router_chat_get_upstream_model(
             ProviderRequestType::MessagesRequest(_)
             | ProviderRequestType::BedrockConverse(_)
             | ProviderRequestType::BedrockConverseStream(_)
+            | ProviderRequestType::BedrockInvokeModel(_)
+            | ProviderRequestType::GeminiGenerateContent(_)
+            | ProviderRequestType::CohereChat(_)
+            | ProviderRequestType::OllamaChat(_)
             | ProviderRequestType::ResponsesAPIRequest(_),
         ) => {
             warn!("unexpected: got non-ChatCompletions request after converting to OpenAI format");
@@ -113,6 +118,7 @@ pub async fn router_chat_get_upstream_model(
     match routing_result {
         Ok(route) => match route {
             Some((route_name, ranked_models)) => {
+                let ranked_models = retain_capable_models(ranked_models, &chat_request);
                 let model_name = ranked_models.first().cloned().unwrap_or_default();
                 current_span.record("route.selected_model", model_name.as_str());
                 Ok(RoutingResult {
@@ -143,3 +149,25 @@ pub async fn router_chat_get_upstream_model(
         }
     }
 }
+
+/// Drop ranked models that can't take `request`, such as models without
+/// vision for a request with images. Models named without a known provider
+/// prefix are kept, and so is the ranking when no model can take it.
+fn retain_capable_models(models: Vec<String>, request: &ChatCompletionsRequest) -> Vec<String> {
+    let (capable, incapable): (Vec<String>, Vec<String>) =
+        models.iter().cloned().partition(|model| {
+            let Some((provider, name)) = model.split_once('/') else {
+                return true;
+            };
+            ProviderId::try_from(provider).map_or(true, |provider_id| {
+                provider_id.capabilities(name).can_serve(request)
+            })
+        });
+    if capable.is_empty() {
+        return models;
+    }
+    if !incapable.is_empty() {
+        info!(skipped = ?incapable, "skipping models that can't take the request");
+    }
+    capable
+}
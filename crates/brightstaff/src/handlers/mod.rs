@@ -1,9 +1,19 @@
+pub mod admin;
 pub mod agents;
+pub mod audio;
+pub mod batches;
+pub mod compression;
+pub mod conversation_title;
 pub mod function_calling;
 pub mod llm;
+pub mod model_apis;
 pub mod models;
+pub mod moderations;
+pub mod realtime;
 pub mod response;
+pub mod responses;
 pub mod routing_service;
+pub mod tokenize;
 
 #[cfg(test)]
 mod integration_tests;
@@ -0,0 +1,275 @@
+//! OpenAI endpoints whose JSON body names the model, served for any
+//! configured provider:
+//!
+//! - `POST /v1/embeddings`; the LLM gateway translates to Cohere `embed`,
+//!   Gemini `embedContent` or Bedrock Titan.
+//! - `POST /v1/completions`; sent as-is to providers that still serve the
+//!   legacy API and through chat completions to the rest.
+//! - `POST /v1/images/generations`; translated to Imagen on Vertex AI and
+//!   Gemini, and to Titan Image Generator on Bedrock.
+//!
+//! The model alias is resolved and the provider's credentials attached here.
+//! Calls are traced, priced and metered like chat completions.
+
+use std::sync::Arc;
+
+use bytes::Bytes;
+use common::consts::{
+    ARCH_IS_STREAMING_HEADER, ARCH_PROVIDER_HINT_HEADER, COMPLETIONS_PATH, EMBEDDINGS_PATH,
+    IMAGES_GENERATIONS_PATH,
+};
+use common::errors::BrightStaffError;
+use hermesllm::apis::embeddings::EmbeddingsRequest;
+use hermesllm::apis::images::ImageGenerationRequest;
+use hermesllm::apis::openai::CompletionsRequest;
+use http_body_util::combinators::BoxBody;
+use http_body_util::BodyExt;
+use hyper::body::Incoming;
+use hyper::header::{self, HeaderValue};
+use hyper::{Request, Response, StatusCode};
+use opentelemetry::global;
+use opentelemetry::trace::get_active_span;
+use opentelemetry::KeyValue;
+use opentelemetry_http::HeaderInjector;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tracing::{debug, info_span, warn, Instrument};
+
+use super::llm::resolve_model_alias;
+use super::{extract_request_id, full};
+use crate::app_state::AppState;
+use crate::streaming::{create_streaming_response, ObservableStreamProcessor};
+use crate::tracing::{http, llm, operation_component, set_service_name};
+
+/// A request body that names the model to run it on
+trait ModelRequest: DeserializeOwned + Serialize {
+    fn model(&self) -> &str;
+    fn set_model(&mut self, model: String);
+    fn is_streaming(&self) -> bool {
+        false
+    }
+}
+
+impl ModelRequest for EmbeddingsRequest {
+    fn model(&self) -> &str {
+        &self.model
+    }
+    fn set_model(&mut self, model: String) {
+        self.model = model;
+    }
+}
+
+impl ModelRequest for CompletionsRequest {
+    fn model(&self) -> &str {
+        &self.model
+    }
+    fn set_model(&mut self, model: String) {
+        self.model = model;
+    }
+    fn is_streaming(&self) -> bool {
+        self.stream.unwrap_or(false)
+    }
+}
+
+impl ModelRequest for ImageGenerationRequest {
+    fn model(&self) -> &str {
+        &self.model
+    }
+    fn set_model(&mut self, model: String) {
+        self.model = model;
+    }
+}
+
+pub async fn embeddings(
+    req: Request<Incoming>,
+    state: Arc<AppState>,
+) -> Result<Response<BoxBody<Bytes, hyper::Error>>, hyper::Error> {
+    forward::<EmbeddingsRequest>(req, state, EMBEDDINGS_PATH, "embedding").await
+}
+
+pub async fn completions(
+    req: Request<Incoming>,
+    state: Arc<AppState>,
+) -> Result<Response<BoxBody<Bytes, hyper::Error>>, hyper::Error> {
+    forward::<CompletionsRequest>(req, state, COMPLETIONS_PATH, "completion").await
+}
+
+pub async fn image_generations(
+    req: Request<Incoming>,
+    state: Arc<AppState>,
+) -> Result<Response<BoxBody<Bytes, hyper::Error>>, hyper::Error> {
+    forward::<ImageGenerationRequest>(req, state, IMAGES_GENERATIONS_PATH, "image_generation").await
+}
+
+async fn forward<R: ModelRequest>(
+    req: Request<Incoming>,
+    state: Arc<AppState>,
+    path: &'static str,
+    operation: &'static str,
+) -> Result<Response<BoxBody<Bytes, hyper::Error>>, hyper::Error> {
+    let request_id = extract_request_id(&req);
+    let request_span = info_span!(
+        "llm",
+        component = "llm",
+        request_id = %request_id,
+        http.method = %req.method(),
+        http.path = path,
+        llm.model = tracing::field::Empty,
+    );
+    forward_inner::<R>(req, state, path, operation)
+        .instrument(request_span)
+        .await
+}
+
+async fn forward_inner<R: ModelRequest>(
+    req: Request<Incoming>,
+    state: Arc<AppState>,
+    path: &'static str,
+    operation: &'static str,
+) -> Result<Response<BoxBody<Bytes, hyper::Error>>, hyper::Error> {
+    set_service_name(operation_component::LLM);
+    let request_start_time = std::time::Instant::now();
+    let mut request_headers = req.headers().clone();
+
+    let tenant = match state.tenancy.resolve(&request_headers) {
+        Ok(tenant) => tenant,
+        Err(err) => return Ok(err.into_response()),
+    };
+    // Keyed before provider credentials are put on the headers.
+    let usage_ledger = state.usage_ledger.as_ref().map(|ledger| {
+        let client_key =
+            ledger.client_key(&request_headers, tenant.as_ref().map(|t| t.id.as_str()));
+        (Arc::clone(ledger), client_key)
+    });
+
+    let body = req.collect().await?.to_bytes();
+    let mut request: R = match serde_json::from_slice(&body) {
+        Ok(request) => request,
+        Err(e) => {
+            return Ok(BrightStaffError::InvalidRequest(format!(
+                "invalid {operation} request: {e}"
+            ))
+            .into_response())
+        }
+    };
+
+    let model_from_request = request.model().to_string();
+    let resolved_model = match resolve_model_alias(&model_from_request, &state.model_aliases) {
+        Ok(model) => model,
+        Err(err) => return Ok(err.into_response()),
+    };
+    let Some(provider) = state.llm_providers.read().await.get(&resolved_model) else {
+        return Ok(BrightStaffError::ModelNotFound(resolved_model).into_response());
+    };
+    if let Some(tenant) = tenant.as_ref() {
+        if !tenant.allows_model(&provider.name) {
+            warn!(tenant = %tenant.id, model = %provider.name, "model not available to tenant");
+            return Ok(BrightStaffError::ModelNotAllowedForTenant {
+                model: provider.name.clone(),
+                tenant: tenant.id.clone(),
+            }
+            .into_response());
+        }
+    }
+    request.set_model(
+        resolved_model
+            .split_once('/')
+            .map(|(_, model)| model.to_string())
+            .unwrap_or_else(|| resolved_model.clone()),
+    );
+
+    tracing::Span::current().record(llm::MODEL_NAME, resolved_model.as_str());
+    let span_name = format!("POST {} {}", path, resolved_model);
+    get_active_span(|span| {
+        span.update_name(span_name.clone());
+        span.set_attribute(KeyValue::new(llm::MODEL_NAME, resolved_model.clone()));
+        span.set_attribute(KeyValue::new(llm::OPERATION_TYPE, operation));
+        span.set_attribute(KeyValue::new(llm::PROVIDER, provider.name.clone()));
+    });
+
+    state
+        .access_key_slots
+        .apply(&provider.name, &mut request_headers);
+    state
+        .vertex_tokens
+        .apply(&provider.name, &mut request_headers);
+    state
+        .bedrock_credentials
+        .apply(&provider.name, &mut request_headers);
+    if let Ok(value) = HeaderValue::from_str(&resolved_model) {
+        request_headers.insert(ARCH_PROVIDER_HINT_HEADER, value);
+    }
+    request_headers.insert(
+        ARCH_IS_STREAMING_HEADER,
+        HeaderValue::from_static(if request.is_streaming() {
+            "true"
+        } else {
+            "false"
+        }),
+    );
+    request_headers.remove(header::CONTENT_LENGTH);
+    global::get_text_map_propagator(|propagator| {
+        let cx = tracing_opentelemetry::OpenTelemetrySpanExt::context(&tracing::Span::current());
+        propagator.inject_context(&cx, &mut HeaderInjector(&mut request_headers));
+    });
+
+    let body = match serde_json::to_vec(&request) {
+        Ok(body) => body,
+        Err(e) => {
+            return Ok(BrightStaffError::InternalServerError(format!(
+                "failed to serialize {operation} request: {e}"
+            ))
+            .into_response())
+        }
+    };
+    debug!(provider_hint = %resolved_model, upstream_model = %request.model(), operation, "routing by model");
+    let upstream = match state
+        .http_client
+        .post(format!("{}{}", state.llm_provider_url, path))
+        .headers(request_headers)
+        .body(body)
+        .send()
+        .await
+    {
+        Ok(response) => response,
+        Err(err) => {
+            let mut internal_error = Response::new(full(format!("Failed to send request: {err}")));
+            *internal_error.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
+            return Ok(internal_error);
+        }
+    };
+
+    let upstream_status = upstream.status();
+    get_active_span(|span| {
+        span.set_attribute(KeyValue::new(
+            http::STATUS_CODE,
+            upstream_status.as_u16() as i64,
+        ));
+    });
+    let mut response = Response::builder().status(upstream_status);
+    if let Some(headers) = response.headers_mut() {
+        for (name, value) in upstream.headers() {
+            headers.insert(name, value.clone());
+        }
+    }
+
+    let processor = ObservableStreamProcessor::new(
+        operation_component::LLM,
+        span_name,
+        request_start_time,
+        None,
+    )
+    .with_pricing(Arc::clone(&state.pricing), &resolved_model);
+    let processor = match usage_ledger {
+        Some((ledger, client_key)) => processor.with_usage_ledger(ledger, client_key),
+        None => processor,
+    };
+    let streaming_response = create_streaming_response(upstream.bytes_stream(), processor);
+    match response.body(streaming_response.body) {
+        Ok(response) => Ok(response),
+        Err(err) => Ok(BrightStaffError::InternalServerError(format!(
+            "failed to build {operation} response: {err}"
+        ))
+        .into_response()),
+    }
+}
@@ -1,4 +1,5 @@
 use bytes::Bytes;
+use common::configuration::TenantConfig;
 use common::llm_providers::LlmProviders;
 use http_body_util::combinators::BoxBody;
 use hyper::{Response, StatusCode};
@@ -6,11 +7,16 @@ use std::sync::Arc;
 
 use super::full;
 
+/// Models from `llm_providers`, limited to those `tenant` may use.
 pub async fn list_models(
     llm_providers: Arc<tokio::sync::RwLock<LlmProviders>>,
+    tenant: Option<&TenantConfig>,
 ) -> Response<BoxBody<Bytes, hyper::Error>> {
     let prov = llm_providers.read().await;
-    let models = prov.to_models();
+    let mut models = prov.to_models();
+    if let Some(tenant) = tenant {
+        models.data.retain(|model| tenant.allows_model(&model.id));
+    }
 
     match serde_json::to_string(&models) {
         Ok(json) => Response::builder()
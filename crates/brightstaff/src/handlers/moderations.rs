@@ -0,0 +1,102 @@
+//! `POST /v1/moderations`, answered by an OpenAI moderation model, a Bedrock
+//! guardrail or the local classifier, depending on the model the request
+//! names (or `moderation.model` when it names none).
+
+use std::sync::Arc;
+
+use bytes::Bytes;
+use common::consts::MODERATIONS_PATH;
+use common::errors::BrightStaffError;
+use hermesllm::apis::moderations::ModerationRequest;
+use http_body_util::combinators::BoxBody;
+use http_body_util::BodyExt;
+use hyper::body::Incoming;
+use hyper::header::{self, HeaderValue};
+use hyper::{Request, Response, StatusCode};
+use opentelemetry::global;
+use opentelemetry::trace::get_active_span;
+use opentelemetry::KeyValue;
+use opentelemetry_http::HeaderInjector;
+use tracing::{info_span, Instrument};
+
+use super::{extract_request_id, full};
+use crate::app_state::AppState;
+use crate::moderation::moderate;
+use crate::tracing::{llm, operation_component, set_service_name};
+
+pub async fn moderations(
+    req: Request<Incoming>,
+    state: Arc<AppState>,
+) -> Result<Response<BoxBody<Bytes, hyper::Error>>, hyper::Error> {
+    let request_id = extract_request_id(&req);
+    let request_span = info_span!(
+        "llm",
+        component = "llm",
+        request_id = %request_id,
+        http.method = %req.method(),
+        http.path = MODERATIONS_PATH,
+    );
+    moderations_inner(req, state, request_id)
+        .instrument(request_span)
+        .await
+}
+
+async fn moderations_inner(
+    req: Request<Incoming>,
+    state: Arc<AppState>,
+    request_id: String,
+) -> Result<Response<BoxBody<Bytes, hyper::Error>>, hyper::Error> {
+    set_service_name(operation_component::LLM);
+    let mut request_headers = req.headers().clone();
+    let tenant = match state.tenancy.resolve(&request_headers) {
+        Ok(tenant) => tenant,
+        Err(err) => return Ok(err.into_response()),
+    };
+
+    let body = req.collect().await?.to_bytes();
+    let request: ModerationRequest = match serde_json::from_slice(&body) {
+        Ok(request) => request,
+        Err(e) => {
+            return Ok(
+                BrightStaffError::InvalidRequest(format!("invalid moderation request: {e}"))
+                    .into_response(),
+            )
+        }
+    };
+
+    let model = request
+        .model
+        .clone()
+        .unwrap_or_else(|| state.moderator.default_model().to_string());
+    get_active_span(|span| {
+        span.update_name(format!("POST {} {}", MODERATIONS_PATH, model));
+        span.set_attribute(KeyValue::new(llm::MODEL_NAME, model.clone()));
+        span.set_attribute(KeyValue::new(llm::OPERATION_TYPE, "moderation"));
+    });
+    global::get_text_map_propagator(|propagator| {
+        let cx = tracing_opentelemetry::OpenTelemetrySpanExt::context(&tracing::Span::current());
+        propagator.inject_context(&cx, &mut HeaderInjector(&mut request_headers));
+    });
+
+    match moderate(
+        &state,
+        request,
+        request_headers,
+        tenant.as_deref(),
+        &request_id,
+    )
+    .await
+    {
+        Ok(response) => {
+            let body = serde_json::to_vec(&response).unwrap_or_default();
+            let mut response = Response::new(full(body));
+            *response.status_mut() = StatusCode::OK;
+            response.headers_mut().insert(
+                header::CONTENT_TYPE,
+                HeaderValue::from_static("application/json"),
+            );
+            Ok(response)
+        }
+        Err(err) => Ok(err.into_response()),
+    }
+}
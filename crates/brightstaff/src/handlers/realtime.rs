@@ -0,0 +1,394 @@
+//! `GET /v1/realtime`: OpenAI Realtime API sessions over WebSocket.
+//!
+//! The client's upgrade is accepted only once the upstream connection,
+//! through the LLM gateway with the provider's credentials, is open; a
+//! session that cannot be routed fails as an ordinary HTTP error. Events are
+//! then relayed both ways as they come. A `session.update` naming a model
+//! has it pinned to the session's upstream model, and the usage reported by
+//! every `response.done` is priced, metered and added to the session span.
+
+use std::sync::Arc;
+use std::time::Instant;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use bytes::Bytes;
+use common::consts::{ARCH_IS_STREAMING_HEADER, ARCH_PROVIDER_HINT_HEADER, REALTIME_PATH};
+use common::errors::BrightStaffError;
+use futures::{SinkExt, StreamExt};
+use hermesllm::apis::realtime::{error_event, pin_session_model, RealtimeServerEvent};
+use http_body_util::combinators::BoxBody;
+use hyper::body::Incoming;
+use hyper::header::{self, HeaderMap, HeaderValue};
+use hyper::{Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use opentelemetry::global;
+use opentelemetry::trace::get_active_span;
+use opentelemetry::KeyValue;
+use opentelemetry_http::HeaderInjector;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio_websockets::{ClientBuilder, Message, ServerBuilder, WebSocketStream};
+use tracing::{debug, info, info_span, warn, Instrument};
+
+use super::llm::resolve_model_alias;
+use super::{empty, extract_request_id};
+use crate::app_state::AppState;
+use crate::tracing::{llm, operation_component, set_service_name};
+
+/// Appended to `Sec-WebSocket-Key` before hashing, per RFC 6455.
+const WEBSOCKET_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+/// Subprotocol browsers offer, alongside one carrying their API key.
+const REALTIME_SUBPROTOCOL: &str = "realtime";
+/// Client headers belonging to the client's own handshake.
+const HANDSHAKE_HEADERS: [&str; 8] = [
+    "host",
+    "connection",
+    "upgrade",
+    "content-length",
+    "sec-websocket-key",
+    "sec-websocket-version",
+    "sec-websocket-extensions",
+    "sec-websocket-protocol",
+];
+
+/// What a session has used so far.
+#[derive(Debug, Default)]
+struct SessionUsage {
+    responses: u64,
+    input_tokens: u64,
+    output_tokens: u64,
+    cached_input_tokens: u64,
+}
+
+pub async fn realtime(
+    req: Request<Incoming>,
+    state: Arc<AppState>,
+) -> Result<Response<BoxBody<Bytes, hyper::Error>>, hyper::Error> {
+    let request_id = extract_request_id(&req);
+    let request_span = info_span!(
+        "llm",
+        component = "llm",
+        request_id = %request_id,
+        http.method = %req.method(),
+        http.path = REALTIME_PATH,
+        llm.model = tracing::field::Empty,
+    );
+    Ok(realtime_inner(req, state)
+        .instrument(request_span)
+        .await
+        .unwrap_or_else(BrightStaffError::into_response))
+}
+
+async fn realtime_inner(
+    mut req: Request<Incoming>,
+    state: Arc<AppState>,
+) -> Result<Response<BoxBody<Bytes, hyper::Error>>, BrightStaffError> {
+    set_service_name(operation_component::LLM);
+    let Some(accept) = websocket_accept(req.headers()) else {
+        return Err(BrightStaffError::InvalidRequest(format!(
+            "{} expects a WebSocket upgrade",
+            REALTIME_PATH
+        )));
+    };
+
+    let tenant = state.tenancy.resolve(req.headers())?;
+    let usage_ledger = state.usage_ledger.as_ref().map(|ledger| {
+        let client_key = ledger.client_key(req.headers(), tenant.as_ref().map(|t| t.id.as_str()));
+        (Arc::clone(ledger), client_key)
+    });
+    let model_from_request = req
+        .uri()
+        .query()
+        .and_then(|query| {
+            query
+                .split('&')
+                .find_map(|pair| pair.strip_prefix("model="))
+        })
+        .map(str::to_string)
+        .ok_or_else(|| BrightStaffError::InvalidRequest("missing model parameter".to_string()))?;
+    let resolved_model = resolve_model_alias(&model_from_request, &state.model_aliases)?;
+    let Some(provider) = state.llm_providers.read().await.get(&resolved_model) else {
+        return Err(BrightStaffError::ModelNotFound(resolved_model));
+    };
+    if let Some(tenant) = tenant.as_ref() {
+        if !tenant.allows_model(&provider.name) {
+            return Err(BrightStaffError::ModelNotAllowedForTenant {
+                model: provider.name.clone(),
+                tenant: tenant.id.clone(),
+            });
+        }
+    }
+    let upstream_model = resolved_model
+        .split_once('/')
+        .map_or(resolved_model.as_str(), |(_, model)| model)
+        .to_string();
+
+    tracing::Span::current().record(llm::MODEL_NAME, resolved_model.as_str());
+    get_active_span(|span| {
+        span.update_name(format!("GET {} {}", REALTIME_PATH, resolved_model));
+        span.set_attribute(KeyValue::new(llm::MODEL_NAME, resolved_model.clone()));
+        span.set_attribute(KeyValue::new(llm::OPERATION_TYPE, "realtime"));
+        span.set_attribute(KeyValue::new(llm::PROVIDER, provider.name.clone()));
+        span.set_attribute(KeyValue::new(llm::IS_STREAMING, true));
+    });
+
+    let mut upstream_headers = req.headers().clone();
+    for name in HANDSHAKE_HEADERS {
+        upstream_headers.remove(name);
+    }
+    state
+        .access_key_slots
+        .apply(&provider.name, &mut upstream_headers);
+    state
+        .vertex_tokens
+        .apply(&provider.name, &mut upstream_headers);
+    state
+        .bedrock_credentials
+        .apply(&provider.name, &mut upstream_headers);
+    if let Ok(value) = HeaderValue::from_str(&resolved_model) {
+        upstream_headers.insert(ARCH_PROVIDER_HINT_HEADER, value);
+    }
+    upstream_headers.insert(ARCH_IS_STREAMING_HEADER, HeaderValue::from_static("true"));
+    global::get_text_map_propagator(|propagator| {
+        let cx = tracing_opentelemetry::OpenTelemetrySpanExt::context(&tracing::Span::current());
+        propagator.inject_context(&cx, &mut HeaderInjector(&mut upstream_headers));
+    });
+
+    let uri = format!(
+        "{}{}?model={}",
+        state
+            .llm_provider_url
+            .replacen("https://", "wss://", 1)
+            .replacen("http://", "ws://", 1),
+        REALTIME_PATH,
+        upstream_model
+    );
+    let mut client = ClientBuilder::new()
+        .uri(&uri)
+        .map_err(|e| BrightStaffError::InternalServerError(format!("invalid gateway uri: {e}")))?;
+    for (name, value) in &upstream_headers {
+        client = client.add_header(name.clone(), value.clone());
+    }
+    let (upstream, _) = client.connect().await.map_err(|e| {
+        warn!(error = %e, model = %resolved_model, "realtime upstream connection failed");
+        BrightStaffError::ForwardedError {
+            status_code: StatusCode::BAD_GATEWAY,
+            message: format!("realtime upstream connection failed: {e}"),
+        }
+    })?;
+    debug!(model = %resolved_model, "realtime upstream connected");
+
+    let offers_realtime = req
+        .headers()
+        .get(header::SEC_WEBSOCKET_PROTOCOL)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|protocols| {
+            protocols
+                .split(',')
+                .any(|protocol| protocol.trim() == REALTIME_SUBPROTOCOL)
+        });
+    let on_upgrade = hyper::upgrade::on(&mut req);
+    let pricing = Arc::clone(&state.pricing);
+    let session_span = tracing::Span::current();
+    tokio::spawn(
+        async move {
+            let client = match on_upgrade.await {
+                Ok(upgraded) => ServerBuilder::new().serve(TokioIo::new(upgraded)),
+                Err(error) => {
+                    warn!(%error, "realtime client upgrade failed");
+                    return;
+                }
+            };
+            let started = Instant::now();
+            let usage = relay(client, upstream, &upstream_model).await;
+            let cost = pricing.cost_usd(
+                &resolved_model,
+                usage.input_tokens as i64,
+                usage.output_tokens as i64,
+                usage.cached_input_tokens as i64,
+            );
+            if let Some((ledger, client_key)) = usage_ledger {
+                ledger.record(
+                    &client_key,
+                    &resolved_model,
+                    usage.input_tokens,
+                    usage.output_tokens,
+                    cost,
+                );
+            }
+            get_active_span(|span| {
+                span.set_attribute(KeyValue::new(
+                    llm::DURATION_MS,
+                    started.elapsed().as_millis() as i64,
+                ));
+                span.set_attribute(KeyValue::new(llm::PROMPT_TOKENS, usage.input_tokens as i64));
+                span.set_attribute(KeyValue::new(
+                    llm::COMPLETION_TOKENS,
+                    usage.output_tokens as i64,
+                ));
+                span.set_attribute(KeyValue::new(
+                    llm::TOTAL_TOKENS,
+                    (usage.input_tokens + usage.output_tokens) as i64,
+                ));
+                span.set_attribute(KeyValue::new(
+                    llm::CACHED_INPUT_TOKENS,
+                    usage.cached_input_tokens as i64,
+                ));
+                if let Some(cost) = cost {
+                    span.set_attribute(KeyValue::new(llm::COST_USD, cost));
+                }
+            });
+            info!(
+                responses = usage.responses,
+                input_tokens = usage.input_tokens,
+                output_tokens = usage.output_tokens,
+                "realtime session closed"
+            );
+        }
+        .instrument(session_span),
+    );
+
+    let mut response = Response::builder()
+        .status(StatusCode::SWITCHING_PROTOCOLS)
+        .header(header::CONNECTION, "upgrade")
+        .header(header::UPGRADE, "websocket")
+        .header(header::SEC_WEBSOCKET_ACCEPT, accept);
+    if offers_realtime {
+        response = response.header(header::SEC_WEBSOCKET_PROTOCOL, REALTIME_SUBPROTOCOL);
+    }
+    Ok(response.body(empty())?)
+}
+
+/// `Sec-WebSocket-Accept` for a valid upgrade request.
+fn websocket_accept(headers: &HeaderMap) -> Option<String> {
+    let is_upgrade = headers
+        .get(header::UPGRADE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.eq_ignore_ascii_case("websocket"));
+    let key = headers.get(header::SEC_WEBSOCKET_KEY)?.to_str().ok()?;
+    if !is_upgrade {
+        return None;
+    }
+    let digest = ring::digest::digest(
+        &ring::digest::SHA1_FOR_LEGACY_USE_ONLY,
+        format!("{}{}", key.trim(), WEBSOCKET_GUID).as_bytes(),
+    );
+    Some(STANDARD.encode(digest.as_ref()))
+}
+
+/// Relay events until either side closes, returning what the session used.
+async fn relay<C, U>(
+    mut client: WebSocketStream<C>,
+    mut upstream: WebSocketStream<U>,
+    upstream_model: &str,
+) -> SessionUsage
+where
+    C: AsyncRead + AsyncWrite + Unpin,
+    U: AsyncRead + AsyncWrite + Unpin,
+{
+    let mut usage = SessionUsage::default();
+    loop {
+        tokio::select! {
+            message = client.next() => {
+                let message = match message {
+                    Some(Ok(message)) => message,
+                    Some(Err(error)) => {
+                        debug!(%error, "realtime client connection ended");
+                        let _ = upstream.close().await;
+                        break;
+                    }
+                    None => {
+                        let _ = upstream.close().await;
+                        break;
+                    }
+                };
+                if message.is_close() {
+                    let _ = upstream.send(message).await;
+                    break;
+                }
+                let message = match message.as_text().and_then(|event| pin_session_model(event, upstream_model)) {
+                    Some(event) => Message::text(event),
+                    None => message,
+                };
+                if upstream.send(message).await.is_err() {
+                    let _ = client
+                        .send(Message::text(error_event(
+                            "upstream_disconnected",
+                            "the upstream realtime connection was lost",
+                        )))
+                        .await;
+                    let _ = client.close().await;
+                    break;
+                }
+            }
+            message = upstream.next() => {
+                let message = match message {
+                    Some(Ok(message)) => message,
+                    Some(Err(error)) => {
+                        warn!(%error, "realtime upstream connection failed");
+                        let _ = client
+                            .send(Message::text(error_event(
+                                "upstream_disconnected",
+                                "the upstream realtime connection was lost",
+                            )))
+                            .await;
+                        let _ = client.close().await;
+                        break;
+                    }
+                    None => {
+                        let _ = client.close().await;
+                        break;
+                    }
+                };
+                if let Some(event) = message.as_text() {
+                    match RealtimeServerEvent::parse(event) {
+                        RealtimeServerEvent::ResponseDone { response } => {
+                            usage.responses += 1;
+                            if let Some(response_usage) = response.usage {
+                                usage.input_tokens += response_usage.input_tokens as u64;
+                                usage.output_tokens += response_usage.output_tokens as u64;
+                                usage.cached_input_tokens += response_usage
+                                    .input_token_details
+                                    .and_then(|details| details.cached_tokens)
+                                    .unwrap_or(0) as u64;
+                            }
+                        }
+                        RealtimeServerEvent::Error { error } => {
+                            warn!(code = ?error.code, message = %error.message, "realtime upstream error event");
+                        }
+                        RealtimeServerEvent::Other => {}
+                    }
+                }
+                let is_close = message.is_close();
+                if client.send(message).await.is_err() || is_close {
+                    let _ = upstream.close().await;
+                    break;
+                }
+            }
+        }
+    }
+    usage
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_websocket_accept() {
+        // The handshake example from RFC 6455 section 1.3
+        let mut headers = HeaderMap::new();
+        headers.insert(header::UPGRADE, HeaderValue::from_static("websocket"));
+        headers.insert(
+            header::SEC_WEBSOCKET_KEY,
+            HeaderValue::from_static("dGhlIHNhbXBsZSBub25jZQ=="),
+        );
+        assert_eq!(
+            websocket_accept(&headers).as_deref(),
+            Some("s3pPLMBiTxaQ9kYGzzhZRbK+xOo=")
+        );
+
+        headers.remove(header::UPGRADE);
+        assert_eq!(websocket_accept(&headers), None);
+    }
+}
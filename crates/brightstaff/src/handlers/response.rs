@@ -2,7 +2,7 @@ use bytes::Bytes;
 use common::errors::BrightStaffError;
 use hermesllm::apis::OpenAIApi;
 use hermesllm::clients::{SupportedAPIsFromClient, SupportedUpstreamAPIs};
-use hermesllm::SseEvent;
+use hermesllm::{SseEvent, SseEventStream};
 use http_body_util::combinators::BoxBody;
 use http_body_util::StreamBody;
 use hyper::body::Frame;
@@ -128,29 +128,26 @@ impl ResponseHandler {
         &self,
         llm_response: reqwest::Response,
     ) -> Result<String, BrightStaffError> {
-        use hermesllm::apis::streaming_shapes::sse::SseStreamIter;
-
         let response_headers = llm_response.headers();
         let is_sse_streaming = response_headers
             .get(hyper::header::CONTENT_TYPE)
             .is_some_and(|v| v.to_str().unwrap_or("").contains("text/event-stream"));
 
-        let response_bytes = llm_response.bytes().await.map_err(|e| {
-            BrightStaffError::StreamError(format!("Failed to read response: {}", e))
-        })?;
-
         if is_sse_streaming {
             let client_api =
                 SupportedAPIsFromClient::OpenAIChatCompletions(OpenAIApi::ChatCompletions);
             let upstream_api =
                 SupportedUpstreamAPIs::OpenAIChatCompletions(OpenAIApi::ChatCompletions);
 
-            let sse_iter = SseStreamIter::try_from(response_bytes.as_ref()).map_err(|e| {
-                BrightStaffError::StreamError(format!("Failed to parse SSE stream: {}", e))
-            })?;
+            // Parse events as chunks arrive rather than buffering the whole body
+            let mut read_error = None;
+            let chunks = llm_response
+                .bytes_stream()
+                .map_while(|chunk| chunk.map_err(|e| read_error = Some(e)).ok());
+            let mut sse_events = SseEventStream::new(chunks);
             let mut accumulated_text = String::new();
 
-            for sse_event in sse_iter {
+            while let Some(sse_event) = sse_events.next().await {
                 // Skip [DONE] markers and event-only lines
                 if sse_event.is_done() || sse_event.is_event_only() {
                     continue;
@@ -179,8 +176,19 @@ impl ResponseHandler {
                     }
                 }
             }
+            drop(sse_events);
+            if let Some(e) = read_error {
+                return Err(BrightStaffError::StreamError(format!(
+                    "Failed to read response: {}",
+                    e
+                )));
+            }
             Ok(accumulated_text)
         } else {
+            let response_bytes = llm_response.bytes().await.map_err(|e| {
+                BrightStaffError::StreamError(format!("Failed to read response: {}", e))
+            })?;
+
             // If not SSE, treat as regular text response
             let response_text = String::from_utf8(response_bytes.to_vec()).map_err(|e| {
                 BrightStaffError::StreamError(format!("Failed to decode response: {}", e))
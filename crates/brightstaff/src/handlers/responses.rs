@@ -0,0 +1,355 @@
+//! Stored responses of the Responses API, served at `/v1/responses/{id}`.
+//!
+//! `GET` returns a stored response and `DELETE` removes its conversation
+//! state, as on OpenAI. Responses whose state was stored without the
+//! response itself are rebuilt from the conversation: the output is the last
+//! turn of stored input.
+//!
+//! A `/v1/responses` request with `background: true` is answered at once with
+//! a `queued` response and finished off the request path: [`enqueue`] stores
+//! the queued response, [`start`] and [`complete`] record its progress and
+//! result, and clients poll `GET /v1/responses/{id}` until it settles.
+//! Responses live in the configured state storage, scoped to the tenant.
+
+use std::sync::Arc;
+
+use bytes::Bytes;
+use common::errors::BrightStaffError;
+use hermesllm::apis::openai_responses::{
+    DeleteResponseResponse, InputItem, ResponseError, ResponseErrorCode, ResponseStatus,
+    ResponsesAPIResponse,
+};
+use hermesllm::transforms::response::output_to_input::{last_turn_outputs, outputs_to_inputs};
+use http_body_util::combinators::BoxBody;
+use http_body_util::BodyExt;
+use hyper::body::Incoming;
+use hyper::header::{self, HeaderValue};
+use hyper::{Method, Request, Response, StatusCode};
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+use super::{empty, full};
+use crate::app_state::AppState;
+use crate::state::{OpenAIConversationState, StateStorage, StateStorageError};
+use crate::tenancy::TenantStateStorage;
+
+type HandlerResponse = Response<BoxBody<Bytes, hyper::Error>>;
+
+pub async fn responses(
+    req: Request<Incoming>,
+    state: Arc<AppState>,
+) -> Result<HandlerResponse, hyper::Error> {
+    let tenant = match state.tenancy.resolve(req.headers()) {
+        Ok(tenant) => tenant,
+        Err(err) => return Ok(err.into_response()),
+    };
+    let Some(storage) = TenantStateStorage::scope(state.state_storage.clone(), tenant.as_deref())
+    else {
+        return Ok(BrightStaffError::InvalidRequest(
+            "stored responses need state_storage to be configured".to_string(),
+        )
+        .into_response());
+    };
+
+    let path = req.uri().path();
+    let segments: Vec<&str> = path.trim_start_matches('/').split('/').collect();
+    Ok(match (req.method(), segments.as_slice()) {
+        (&Method::GET, ["v1", "responses", id]) => retrieve(&storage, id).await,
+        (&Method::DELETE, ["v1", "responses", id]) => delete(&storage, id).await,
+        _ => {
+            debug!(method = %req.method(), path = %path, "no stored response endpoint");
+            let mut not_found = Response::new(empty());
+            *not_found.status_mut() = StatusCode::NOT_FOUND;
+            not_found
+        }
+    })
+}
+
+async fn retrieve(storage: &Arc<dyn StateStorage>, id: &str) -> HandlerResponse {
+    match storage.get(id).await {
+        Ok(state) => match &state.response {
+            Some(response) => json_response(response),
+            None => json_response(&rebuild(&state)),
+        },
+        Err(StateStorageError::NotFound(_)) => {
+            BrightStaffError::ResponseNotFound(id.to_string()).into_response()
+        }
+        Err(err) => BrightStaffError::InternalServerError(err.to_string()).into_response(),
+    }
+}
+
+async fn delete(storage: &Arc<dyn StateStorage>, id: &str) -> HandlerResponse {
+    match storage.delete(id).await {
+        Ok(()) => {
+            debug!(response_id = %id, "deleted stored response");
+            let deleted = DeleteResponseResponse {
+                id: id.to_string(),
+                object: "response".to_string(),
+                deleted: true,
+            };
+            let mut response =
+                Response::new(full(serde_json::to_vec(&deleted).unwrap_or_default()));
+            response.headers_mut().insert(
+                header::CONTENT_TYPE,
+                HeaderValue::from_static("application/json"),
+            );
+            response
+        }
+        Err(StateStorageError::NotFound(_)) => {
+            BrightStaffError::ResponseNotFound(id.to_string()).into_response()
+        }
+        Err(err) => BrightStaffError::InternalServerError(err.to_string()).into_response(),
+    }
+}
+
+/// A completed response whose output is the last turn of `state`'s input.
+fn rebuild(state: &OpenAIConversationState) -> ResponsesAPIResponse {
+    let mut response = ResponsesAPIResponse::in_progress(
+        state.response_id.clone(),
+        state.model.clone(),
+        state.created_at,
+    );
+    response.status = ResponseStatus::Completed;
+    response.output = last_turn_outputs(&state.input_items);
+    response
+}
+
+/// Store a `queued` response for a background request to `model` and return
+/// its state, to be passed on to [`start`] and [`complete`].
+pub(crate) async fn enqueue(
+    storage: &Arc<dyn StateStorage>,
+    model: &str,
+    provider: &str,
+    input_items: Vec<InputItem>,
+) -> Result<OpenAIConversationState, StateStorageError> {
+    let created_at = chrono::Utc::now().timestamp();
+    let response_id = format!("resp_{}", Uuid::new_v4().simple());
+    let mut response =
+        ResponsesAPIResponse::in_progress(response_id.clone(), model.to_string(), created_at);
+    response.status = ResponseStatus::Queued;
+    response.background = Some(true);
+    let queued = OpenAIConversationState {
+        response_id,
+        input_items,
+        created_at,
+        model: model.to_string(),
+        provider: provider.to_string(),
+        response: Some(response),
+    };
+    storage.put(queued.clone()).await?;
+    Ok(queued)
+}
+
+/// Mark a queued response as `in_progress` once its upstream call begins.
+pub(crate) async fn start(storage: &Arc<dyn StateStorage>, state: &mut OpenAIConversationState) {
+    if let Some(response) = state.response.as_mut() {
+        response.status = ResponseStatus::InProgress;
+    }
+    if let Err(err) = storage.put(state.clone()).await {
+        warn!(response_id = %state.response_id, error = %err, "failed to store background response");
+    }
+}
+
+/// Store the upstream answer to a background request under its queued id,
+/// or a `failed` response carrying the upstream error. A completed response
+/// extends the stored input so it can be continued with
+/// `previous_response_id`.
+pub(crate) async fn complete(
+    storage: &Arc<dyn StateStorage>,
+    mut state: OpenAIConversationState,
+    outcome: Result<reqwest::Response, HandlerResponse>,
+) {
+    let result = match outcome {
+        Ok(response) => {
+            let status = response.status();
+            match response.bytes().await {
+                Ok(body) if status.is_success() => {
+                    serde_json::from_slice::<ResponsesAPIResponse>(&body)
+                        .map_err(|e| format!("invalid upstream response: {e}"))
+                }
+                Ok(body) => Err(String::from_utf8_lossy(&body).into_owned()),
+                Err(e) => Err(format!("failed to read upstream response: {e}")),
+            }
+        }
+        Err(response) => Err(match response.into_body().collect().await {
+            Ok(body) => String::from_utf8_lossy(&body.to_bytes()).into_owned(),
+            Err(e) => e.to_string(),
+        }),
+    };
+
+    let queued = state.response.take().unwrap_or_else(|| {
+        ResponsesAPIResponse::in_progress(
+            state.response_id.clone(),
+            state.model.clone(),
+            state.created_at,
+        )
+    });
+    state.response = Some(match result {
+        Ok(mut response) => {
+            response.id = state.response_id.clone();
+            response.created_at = queued.created_at;
+            response.background = Some(true);
+            state
+                .input_items
+                .extend(outputs_to_inputs(&response.output));
+            debug!(response_id = %state.response_id, "background response completed");
+            response
+        }
+        Err(message) => {
+            warn!(response_id = %state.response_id, error = %message, "background response failed");
+            ResponsesAPIResponse {
+                status: ResponseStatus::Failed,
+                error: Some(ResponseError {
+                    code: ResponseErrorCode::ServerError,
+                    message,
+                }),
+                ..queued
+            }
+        }
+    });
+    if let Err(err) = storage.put(state).await {
+        warn!(error = %err, "failed to store background response");
+    }
+}
+
+pub(crate) fn json_response(response: &ResponsesAPIResponse) -> HandlerResponse {
+    let body = serde_json::to_vec(response).unwrap_or_default();
+    let mut response = Response::new(full(body));
+    response.headers_mut().insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("application/json"),
+    );
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::memory::MemoryConversationalStorage;
+    use serde_json::json;
+
+    fn upstream(status: StatusCode, body: serde_json::Value) -> reqwest::Response {
+        let mut response = hyper::Response::new(Bytes::from(body.to_string()));
+        *response.status_mut() = status;
+        reqwest::Response::from(response)
+    }
+
+    #[tokio::test]
+    async fn test_background_response_lifecycle() {
+        let storage: Arc<dyn StateStorage> = Arc::new(MemoryConversationalStorage::new());
+        let mut queued = enqueue(&storage, "claude-sonnet-4", "anthropic", vec![])
+            .await
+            .unwrap();
+        let id = queued.response_id.clone();
+        let stored = storage.get(&id).await.unwrap().response.unwrap();
+        assert!(matches!(stored.status, ResponseStatus::Queued));
+        assert_eq!(stored.background, Some(true));
+
+        start(&storage, &mut queued).await;
+        let answer = json!({
+            "id": "msg_upstream",
+            "object": "response",
+            "created_at": 1,
+            "status": "completed",
+            "model": "claude-sonnet-4",
+            "output": [{
+                "type": "message",
+                "id": "msg_1",
+                "status": "completed",
+                "role": "assistant",
+                "content": [{"type": "output_text", "text": "Hi!", "annotations": []}]
+            }],
+            "parallel_tool_calls": true,
+            "tools": [],
+            "tool_choice": "auto",
+            "temperature": 1.0,
+            "top_p": 1.0,
+            "metadata": {}
+        });
+        complete(&storage, queued, Ok(upstream(StatusCode::OK, answer))).await;
+
+        let body = retrieve(&storage, &id)
+            .await
+            .into_body()
+            .collect()
+            .await
+            .unwrap()
+            .to_bytes();
+        let polled: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(polled["id"], id.as_str());
+        assert_eq!(polled["status"], "completed");
+        assert_eq!(polled["background"], true);
+        assert_eq!(polled["output"][0]["content"][0]["text"], "Hi!");
+        assert_eq!(storage.get(&id).await.unwrap().input_items.len(), 1);
+
+        let failed = enqueue(&storage, "gpt-4o", "openai", vec![]).await.unwrap();
+        let failed_id = failed.response_id.clone();
+        let error = json!({"error": {"message": "overloaded"}});
+        complete(
+            &storage,
+            failed,
+            Ok(upstream(StatusCode::SERVICE_UNAVAILABLE, error)),
+        )
+        .await;
+        let stored = storage.get(&failed_id).await.unwrap().response.unwrap();
+        assert!(matches!(stored.status, ResponseStatus::Failed));
+        assert!(stored.error.unwrap().message.contains("overloaded"));
+
+        assert_eq!(
+            retrieve(&storage, "resp_missing").await.status(),
+            StatusCode::NOT_FOUND
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rebuild_and_delete_stored_response() {
+        let storage: Arc<dyn StateStorage> = Arc::new(MemoryConversationalStorage::new());
+        storage
+            .put(OpenAIConversationState {
+                response_id: "resp_1".to_string(),
+                input_items: serde_json::from_value(json!([
+                    {"role": "user", "content": "Hi"},
+                    {"role": "assistant", "content": [{"type": "output_text", "text": "Hello!"}]}
+                ]))
+                .unwrap(),
+                created_at: 1700000000,
+                model: "claude-sonnet-4".to_string(),
+                provider: "anthropic".to_string(),
+                response: None,
+            })
+            .await
+            .unwrap();
+
+        let body = retrieve(&storage, "resp_1")
+            .await
+            .into_body()
+            .collect()
+            .await
+            .unwrap()
+            .to_bytes();
+        let rebuilt: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(rebuilt["id"], "resp_1");
+        assert_eq!(rebuilt["status"], "completed");
+        assert_eq!(rebuilt["created_at"], 1700000000);
+        assert_eq!(rebuilt["output"].as_array().unwrap().len(), 1);
+        assert_eq!(rebuilt["output"][0]["content"][0]["text"], "Hello!");
+
+        let body = delete(&storage, "resp_1")
+            .await
+            .into_body()
+            .collect()
+            .await
+            .unwrap()
+            .to_bytes();
+        let deleted: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            deleted,
+            json!({"id": "resp_1", "object": "response", "deleted": true})
+        );
+        assert!(!storage.exists("resp_1").await.unwrap());
+        assert_eq!(
+            delete(&storage, "resp_1").await.status(),
+            StatusCode::NOT_FOUND
+        );
+    }
+}
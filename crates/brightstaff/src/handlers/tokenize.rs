@@ -0,0 +1,353 @@
+//! `POST /v1/tokenize`: count the input tokens of a chat request before sending it.
+//!
+//! Accepts a Chat Completions body (only `model`, `messages` and `tools` are
+//! used). Anthropic models are counted by the provider's
+//! `/v1/messages/count_tokens`; everything else is counted locally with
+//! tiktoken, which is exact for OpenAI models and an estimate for the rest.
+//!
+//! `POST /v1/messages/count_tokens` does the same for Anthropic Messages
+//! bodies and answers in Anthropic's `{"input_tokens": N}` shape.
+
+use std::sync::Arc;
+
+use bytes::Bytes;
+use common::configuration::LlmProviderType;
+use common::consts::{ANTHROPIC_COUNT_TOKENS_PATH, ARCH_PROVIDER_HINT_HEADER};
+use common::errors::BrightStaffError;
+use common::tokenizer::token_count;
+use hermesllm::apis::anthropic::MessagesRequest as AnthropicMessagesRequest;
+use hermesllm::apis::openai::{ChatCompletionsRequest, ContentPart, Message, MessageContent};
+use http_body_util::combinators::BoxBody;
+use http_body_util::BodyExt;
+use hyper::body::Incoming;
+use hyper::header::CONTENT_TYPE;
+use hyper::{Request, Response, StatusCode};
+use serde_json::{json, Value};
+use tracing::{debug, warn};
+
+use super::full;
+use super::llm::resolve_model_alias;
+use crate::app_state::AppState;
+
+/// Tokens OpenAI's chat format adds around every message, and once to prime the reply.
+const TOKENS_PER_MESSAGE: usize = 3;
+const REPLY_PRIMING_TOKENS: usize = 3;
+
+/// Fields Anthropic's count_tokens accepts; anything else is rejected upstream.
+const COUNT_TOKENS_FIELDS: [&str; 6] = [
+    "model",
+    "messages",
+    "system",
+    "tools",
+    "tool_choice",
+    "thinking",
+];
+
+pub async fn tokenize(
+    req: Request<Incoming>,
+    state: Arc<AppState>,
+) -> Result<Response<BoxBody<Bytes, hyper::Error>>, hyper::Error> {
+    let body = req.collect().await?.to_bytes();
+    let request: ChatCompletionsRequest = match serde_json::from_slice(&body) {
+        Ok(request) => request,
+        Err(e) => {
+            return Ok(json_response(
+                StatusCode::BAD_REQUEST,
+                json!({"error": format!("invalid tokenize request: {e}")}),
+            ))
+        }
+    };
+
+    let resolved_model = match resolve_model_alias(&request.model, &state.model_aliases) {
+        Ok(model) => model,
+        Err(err) => return Ok(err.into_response()),
+    };
+    let Some(provider) = state.llm_providers.read().await.get(&resolved_model) else {
+        return Ok(json_response(
+            StatusCode::BAD_REQUEST,
+            json!({"error": format!("Model '{resolved_model}' not found in configured providers")}),
+        ));
+    };
+    let model_name_only = resolved_model
+        .split_once('/')
+        .map(|(_, model)| model.to_string())
+        .unwrap_or_else(|| resolved_model.clone());
+
+    let (input_tokens, counter) = match provider.provider_interface {
+        LlmProviderType::Anthropic => {
+            let counted = match count_tokens_body(&model_name_only, request.clone()) {
+                Ok(body) => {
+                    count_with_anthropic(&state, &provider.name, &resolved_model, body).await
+                }
+                Err(error) => Err(error),
+            };
+            match counted {
+                Ok(tokens) => (tokens, "anthropic"),
+                Err(error) => {
+                    warn!(
+                        model = %resolved_model,
+                        error = %error,
+                        "anthropic count_tokens failed, estimating locally"
+                    );
+                    (estimate_tokens(&model_name_only, &request), "estimate")
+                }
+            }
+        }
+        LlmProviderType::OpenAI | LlmProviderType::AzureOpenAI => {
+            (estimate_tokens(&model_name_only, &request), "tiktoken")
+        }
+        _ => (estimate_tokens(&model_name_only, &request), "estimate"),
+    };
+    debug!(model = %resolved_model, input_tokens, counter, "counted input tokens");
+
+    Ok(json_response(
+        StatusCode::OK,
+        json!({
+            "model": resolved_model,
+            "input_tokens": input_tokens,
+            "counter": counter,
+        }),
+    ))
+}
+
+pub async fn count_tokens(
+    req: Request<Incoming>,
+    state: Arc<AppState>,
+) -> Result<Response<BoxBody<Bytes, hyper::Error>>, hyper::Error> {
+    let body = req.collect().await?.to_bytes();
+    let mut body: Value = match serde_json::from_slice(&body) {
+        Ok(body) => body,
+        Err(e) => {
+            return Ok(BrightStaffError::InvalidRequest(format!(
+                "invalid count_tokens request: {e}"
+            ))
+            .into_response())
+        }
+    };
+    let request = match chat_request_from_count_tokens(body.clone()) {
+        Ok(request) => request,
+        Err(e) => {
+            return Ok(BrightStaffError::InvalidRequest(format!(
+                "invalid count_tokens request: {e}"
+            ))
+            .into_response())
+        }
+    };
+
+    let resolved_model = match resolve_model_alias(&request.model, &state.model_aliases) {
+        Ok(model) => model,
+        Err(err) => return Ok(err.into_response()),
+    };
+    let Some(provider) = state.llm_providers.read().await.get(&resolved_model) else {
+        return Ok(BrightStaffError::ModelNotFound(resolved_model).into_response());
+    };
+    let model_name_only = resolved_model
+        .split_once('/')
+        .map(|(_, model)| model.to_string())
+        .unwrap_or_else(|| resolved_model.clone());
+
+    let input_tokens = match provider.provider_interface {
+        LlmProviderType::Anthropic => {
+            body["model"] = json!(model_name_only);
+            let body = retain_count_tokens_fields(body);
+            match count_with_anthropic(&state, &provider.name, &resolved_model, body).await {
+                Ok(tokens) => tokens,
+                Err(error) => {
+                    warn!(
+                        model = %resolved_model,
+                        error = %error,
+                        "anthropic count_tokens failed, estimating locally"
+                    );
+                    estimate_tokens(&model_name_only, &request)
+                }
+            }
+        }
+        _ => estimate_tokens(&model_name_only, &request),
+    };
+    debug!(model = %resolved_model, input_tokens, "counted input tokens");
+
+    Ok(json_response(
+        StatusCode::OK,
+        json!({"input_tokens": input_tokens}),
+    ))
+}
+
+/// Ask the provider to count the request, routed through the LLM gateway so
+/// the configured credentials are applied.
+async fn count_with_anthropic(
+    state: &AppState,
+    provider_name: &str,
+    resolved_model: &str,
+    body: Value,
+) -> Result<usize, String> {
+    let mut headers = hyper::HeaderMap::new();
+    state.access_key_slots.apply(provider_name, &mut headers);
+    state.vertex_tokens.apply(provider_name, &mut headers);
+    state.bedrock_credentials.apply(provider_name, &mut headers);
+
+    let response = state
+        .http_client
+        .post(format!(
+            "{}{}",
+            state.llm_provider_url, ANTHROPIC_COUNT_TOKENS_PATH
+        ))
+        .headers(headers)
+        .header(ARCH_PROVIDER_HINT_HEADER, resolved_model)
+        .json(&body)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    let status = response.status();
+    let payload: Value = response.json().await.map_err(|e| e.to_string())?;
+    if !status.is_success() {
+        return Err(format!("upstream returned {status}: {payload}"));
+    }
+    payload["input_tokens"]
+        .as_u64()
+        .map(|tokens| tokens as usize)
+        .ok_or_else(|| "response has no input_tokens".to_string())
+}
+
+/// Anthropic Messages form of `request` for `model`, restricted to the
+/// fields count_tokens accepts.
+fn count_tokens_body(model: &str, mut request: ChatCompletionsRequest) -> Result<Value, String> {
+    request.model = model.to_string();
+    let anthropic = AnthropicMessagesRequest::try_from(request).map_err(|e| e.to_string())?;
+    let body = serde_json::to_value(anthropic).map_err(|e| e.to_string())?;
+    Ok(retain_count_tokens_fields(body))
+}
+
+fn retain_count_tokens_fields(mut body: Value) -> Value {
+    if let Some(fields) = body.as_object_mut() {
+        fields.retain(|key, _| COUNT_TOKENS_FIELDS.contains(&key.as_str()));
+    }
+    body
+}
+
+/// Chat Completions form of an Anthropic count_tokens body, used for the
+/// local estimate. count_tokens bodies carry no `max_tokens`, which the
+/// Messages API otherwise requires.
+fn chat_request_from_count_tokens(mut body: Value) -> Result<ChatCompletionsRequest, String> {
+    if let Some(fields) = body.as_object_mut() {
+        fields.entry("max_tokens").or_insert(json!(1));
+    }
+    let request: AnthropicMessagesRequest =
+        serde_json::from_value(body).map_err(|e| e.to_string())?;
+    ChatCompletionsRequest::try_from(request).map_err(|e| e.to_string())
+}
+
+/// tiktoken count following OpenAI's chat accounting: each message's text and
+/// tool calls plus framing tokens, and the tool definitions.
+fn estimate_tokens(model: &str, request: &ChatCompletionsRequest) -> usize {
+    let count = |text: &str| token_count(model, text).unwrap_or(text.len() / 4);
+    let messages: usize = request
+        .messages
+        .iter()
+        .map(|message| count(&message_text(message)) + TOKENS_PER_MESSAGE)
+        .sum();
+    let tools = request
+        .tools
+        .as_ref()
+        .and_then(|tools| serde_json::to_string(tools).ok())
+        .map_or(0, |tools| count(&tools));
+    messages + tools + REPLY_PRIMING_TOKENS
+}
+
+fn message_text(message: &Message) -> String {
+    let mut parts: Vec<String> = Vec::new();
+    if let Some(name) = &message.name {
+        parts.push(name.clone());
+    }
+    match &message.content {
+        Some(MessageContent::Text(text)) => parts.push(text.clone()),
+        Some(MessageContent::Parts(content)) => {
+            parts.extend(content.iter().filter_map(|part| match part {
+                ContentPart::Text { text } => Some(text.clone()),
+                ContentPart::ImageUrl { .. }
+                | ContentPart::File { .. }
+                | ContentPart::InputAudio { .. } => None,
+            }))
+        }
+        None => {}
+    }
+    if let Some(tool_calls) = &message.tool_calls {
+        parts.extend(serde_json::to_string(tool_calls).ok());
+    }
+    parts.join("\n")
+}
+
+fn json_response(status: StatusCode, body: Value) -> Response<BoxBody<Bytes, hyper::Error>> {
+    Response::builder()
+        .status(status)
+        .header(CONTENT_TYPE, "application/json")
+        .body(full(body.to_string()))
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(body: Value) -> ChatCompletionsRequest {
+        serde_json::from_value(body).unwrap()
+    }
+
+    #[test]
+    fn estimate_counts_messages_framing_and_tools() {
+        let plain = request(json!({
+            "model": "gpt-4o",
+            "messages": [{"role": "user", "content": "How many tokens does this sentence have?"}]
+        }));
+        let text_tokens =
+            token_count("gpt-4o", "How many tokens does this sentence have?").unwrap();
+        assert_eq!(
+            estimate_tokens("gpt-4o", &plain),
+            text_tokens + TOKENS_PER_MESSAGE + REPLY_PRIMING_TOKENS
+        );
+
+        let with_tools = request(json!({
+            "model": "gpt-4o",
+            "messages": [{"role": "user", "content": "How many tokens does this sentence have?"}],
+            "tools": [{"type": "function", "function": {"name": "get_weather", "parameters": {"type": "object"}}}]
+        }));
+        assert!(estimate_tokens("gpt-4o", &with_tools) > estimate_tokens("gpt-4o", &plain));
+    }
+
+    #[test]
+    fn count_tokens_body_drops_generation_params() {
+        let body = count_tokens_body(
+            "claude-sonnet-4",
+            request(json!({
+                "model": "claude-sonnet-4",
+                "max_tokens": 512,
+                "temperature": 0.2,
+                "messages": [
+                    {"role": "system", "content": "Be brief."},
+                    {"role": "user", "content": "Hello"}
+                ]
+            })),
+        )
+        .unwrap();
+        let mut keys: Vec<_> = body.as_object().unwrap().keys().cloned().collect();
+        keys.sort();
+        assert_eq!(keys, ["messages", "model", "system"]);
+        assert_eq!(body["model"], "claude-sonnet-4");
+    }
+
+    #[test]
+    fn count_tokens_body_needs_no_max_tokens() {
+        let chat = chat_request_from_count_tokens(json!({
+            "model": "claude-sonnet-4",
+            "system": "Be brief.",
+            "messages": [{"role": "user", "content": "How many tokens does this sentence have?"}]
+        }))
+        .unwrap();
+        assert_eq!(chat.messages.len(), 2);
+        assert!(
+            estimate_tokens("claude-sonnet-4", &chat)
+                > token_count("gpt-4o", "How many tokens does this sentence have?").unwrap()
+        );
+
+        assert!(chat_request_from_count_tokens(json!({"model": "claude-sonnet-4"})).is_err());
+    }
+}
@@ -0,0 +1,148 @@
+//! Hedged requests for latency-critical routes.
+//!
+//! On a route with `hedging`, a request that has not produced its first
+//! bytes after `delay` is also sent to a second model. Whichever answers
+//! first with a usable response is served and the other call is dropped,
+//! which aborts its connection. Requests that declare tools are never
+//! hedged, so a slow provider cannot leave two billed tool-calling
+//! completions behind for a single request.
+
+use std::future::Future;
+use std::time::Duration;
+
+use bytes::Bytes;
+use futures::StreamExt;
+
+/// Which call produced the response.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Winner {
+    Primary,
+    Hedge,
+}
+
+/// Run `primary`, and `hedge` as well once `delay` passes without an
+/// answer. The first result passing `usable` wins; when neither does, the
+/// primary's result is returned.
+pub async fn race<T, P, H, HF>(
+    primary: P,
+    delay: Duration,
+    hedge: H,
+    usable: impl Fn(&T) -> bool,
+) -> (T, Winner)
+where
+    P: Future<Output = T>,
+    H: FnOnce() -> HF,
+    HF: Future<Output = T>,
+{
+    tokio::pin!(primary);
+    tokio::select! {
+        result = &mut primary => return (result, Winner::Primary),
+        _ = tokio::time::sleep(delay) => {}
+    }
+    let hedge = hedge();
+    tokio::pin!(hedge);
+    tokio::select! {
+        result = &mut primary => {
+            if usable(&result) {
+                return (result, Winner::Primary);
+            }
+            let hedged = hedge.await;
+            if usable(&hedged) {
+                (hedged, Winner::Hedge)
+            } else {
+                (result, Winner::Primary)
+            }
+        }
+        hedged = &mut hedge => {
+            if usable(&hedged) {
+                return (hedged, Winner::Hedge);
+            }
+            (primary.await, Winner::Primary)
+        }
+    }
+}
+
+/// Wait for the first bytes of a successful response body, so a stream
+/// counts as answered only once it starts producing tokens. The bytes read
+/// are put back in front of the body.
+pub async fn first_bytes(response: reqwest::Response) -> Result<reqwest::Response, reqwest::Error> {
+    if !response.status().is_success() {
+        return Ok(response);
+    }
+    let status = response.status();
+    let version = response.version();
+    let headers = response.headers().clone();
+    let mut body = response.bytes_stream();
+    let first: Option<Result<Bytes, reqwest::Error>> = match body.next().await {
+        Some(Err(err)) => return Err(err),
+        first => first,
+    };
+    let body = futures::stream::iter(first).chain(body);
+    let mut rebuilt = hyper::Response::new(reqwest::Body::wrap_stream(body));
+    *rebuilt.status_mut() = status;
+    *rebuilt.version_mut() = version;
+    *rebuilt.headers_mut() = headers;
+    Ok(reqwest::Response::from(rebuilt))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    async fn answer(after_ms: u64, value: u16) -> u16 {
+        tokio::time::sleep(Duration::from_millis(after_ms)).await;
+        value
+    }
+
+    fn ok(status: &u16) -> bool {
+        *status == 200
+    }
+
+    #[tokio::test]
+    async fn fast_primary_never_starts_the_hedge() {
+        let hedged = AtomicBool::new(false);
+        let result = race(
+            answer(10, 200),
+            Duration::from_millis(200),
+            || {
+                hedged.store(true, Ordering::SeqCst);
+                answer(0, 200)
+            },
+            ok,
+        )
+        .await;
+        assert_eq!(result, (200, Winner::Primary));
+        assert!(!hedged.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn first_usable_answer_wins() {
+        let delay = Duration::from_millis(50);
+        let slow_primary = race(answer(600, 200), delay, || answer(100, 200), ok).await;
+        assert_eq!(slow_primary, (200, Winner::Hedge));
+
+        let primary_catches_up = race(answer(150, 200), delay, || answer(300, 200), ok).await;
+        assert_eq!(primary_catches_up, (200, Winner::Primary));
+
+        let failed_hedge = race(answer(300, 200), delay, || answer(10, 503), ok).await;
+        assert_eq!(failed_hedge, (200, Winner::Primary));
+
+        let both_failed = race(answer(300, 429), delay, || answer(10, 503), ok).await;
+        assert_eq!(both_failed, (429, Winner::Primary));
+    }
+
+    #[tokio::test]
+    async fn first_bytes_keeps_the_whole_body() {
+        let chunks: Vec<Result<Bytes, std::io::Error>> = vec![
+            Ok(Bytes::from("data: a\n\n")),
+            Ok(Bytes::from("data: b\n\n")),
+        ];
+        let upstream =
+            hyper::Response::new(reqwest::Body::wrap_stream(futures::stream::iter(chunks)));
+        let response = first_bytes(reqwest::Response::from(upstream))
+            .await
+            .unwrap();
+        assert_eq!(response.bytes().await.unwrap(), "data: a\n\ndata: b\n\n");
+    }
+}
@@ -0,0 +1,285 @@
+//! Remote image inlining.
+//!
+//! OpenAI accepts `image_url`s pointing anywhere, but Anthropic and Bedrock
+//! reject many remote URLs. With `image_fetch` configured, requests bound for
+//! those providers have their remote images fetched (allowlisted hosts only,
+//! size-limited) and replaced by base64 data before they leave brightstaff.
+//! An image that cannot be fetched is left as is for the provider to judge.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use bytes::Bytes;
+use common::configuration::{ImageFetchSettings, LlmProviderType};
+use common::utils::parse_duration_ms;
+use futures::future::join_all;
+use serde_json::{json, Value};
+use tracing::{debug, warn};
+
+const DEFAULT_MAX_BYTES: usize = 5 * 1024 * 1024;
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A fetched image: media type and base64 data.
+type InlineImage = (String, String);
+
+#[derive(Debug, Default)]
+pub struct ImageInliner {
+    settings: Option<ImageFetchSettings>,
+    /// Does not follow redirects, which could lead off the allowlisted hosts.
+    http_client: reqwest::Client,
+}
+
+impl ImageInliner {
+    pub fn new(settings: Option<ImageFetchSettings>) -> Self {
+        let http_client = reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .unwrap_or_default();
+        Self {
+            settings,
+            http_client,
+        }
+    }
+
+    /// Whether requests to a provider of this interface get inlined images.
+    pub fn applies_to(&self, provider_interface: &LlmProviderType) -> bool {
+        let Some(settings) = &self.settings else {
+            return false;
+        };
+        match &settings.providers {
+            Some(providers) => providers.contains(provider_interface),
+            None => matches!(
+                provider_interface,
+                LlmProviderType::Anthropic | LlmProviderType::AmazonBedrock
+            ),
+        }
+    }
+
+    /// `body` with every fetchable remote image inlined. Returned unchanged
+    /// when it is not JSON or has nothing to inline.
+    pub async fn inline(&self, body: Bytes) -> Bytes {
+        let Some(settings) = &self.settings else {
+            return body;
+        };
+        let Ok(mut json) = serde_json::from_slice::<Value>(&body) else {
+            return body;
+        };
+
+        let mut urls = Vec::new();
+        visit_image_urls(&mut json, &mut |url| {
+            if !urls.iter().any(|u| u == url) && host_allowed(url, &settings.allowed_hosts) {
+                urls.push(url.to_string());
+            }
+            None
+        });
+        if urls.is_empty() {
+            return body;
+        }
+
+        let max_bytes = settings.max_bytes.unwrap_or(DEFAULT_MAX_BYTES);
+        let timeout = settings
+            .timeout
+            .as_deref()
+            .and_then(parse_duration_ms)
+            .map_or(DEFAULT_TIMEOUT, Duration::from_millis);
+        let fetched: HashMap<String, InlineImage> = join_all(urls.into_iter().map(|url| async {
+            match fetch_image(&self.http_client, &url, max_bytes, timeout).await {
+                Ok(image) => Some((url, image)),
+                Err(error) => {
+                    warn!(url = %url, error = %error, "failed to fetch image, sending url as is");
+                    None
+                }
+            }
+        }))
+        .await
+        .into_iter()
+        .flatten()
+        .collect();
+        if fetched.is_empty() {
+            return body;
+        }
+
+        visit_image_urls(&mut json, &mut |url| fetched.get(url).cloned());
+        debug!(images = fetched.len(), "inlined remote images");
+        serde_json::to_vec(&json).map_or(body, Bytes::from)
+    }
+}
+
+/// Calls `replace` with the URL of every remote image in a Chat Completions,
+/// Anthropic Messages or Responses request, swapping in the image it returns.
+fn visit_image_urls(value: &mut Value, replace: &mut impl FnMut(&str) -> Option<InlineImage>) {
+    match value {
+        Value::Array(items) => items
+            .iter_mut()
+            .for_each(|item| visit_image_urls(item, replace)),
+        Value::Object(fields) => {
+            let data_url = |(media_type, data): InlineImage| {
+                Value::String(format!("data:{media_type};base64,{data}"))
+            };
+            match fields.get("type").and_then(Value::as_str) {
+                // Chat Completions: {"type": "image_url", "image_url": {"url": ...}}
+                Some("image_url") => {
+                    if let Some(url) = fields.get_mut("image_url").and_then(|u| u.get_mut("url")) {
+                        if let Some(image) = url
+                            .as_str()
+                            .filter(|u| is_remote(u))
+                            .and_then(&mut *replace)
+                        {
+                            *url = data_url(image);
+                        }
+                    }
+                }
+                // Responses: {"type": "input_image", "image_url": "..."}
+                Some("input_image") => {
+                    if let Some(url) = fields.get_mut("image_url") {
+                        if let Some(image) = url
+                            .as_str()
+                            .filter(|u| is_remote(u))
+                            .and_then(&mut *replace)
+                        {
+                            *url = data_url(image);
+                        }
+                    }
+                }
+                // Anthropic Messages: {"type": "image", "source": {"type": "url", "url": ...}}
+                Some("image") => {
+                    if let Some(source) = fields.get_mut("source") {
+                        if source["type"] == "url" {
+                            if let Some((media_type, data)) = source["url"]
+                                .as_str()
+                                .filter(|u| is_remote(u))
+                                .and_then(&mut *replace)
+                            {
+                                *source = json!({"type": "base64", "media_type": media_type, "data": data});
+                            }
+                        }
+                    }
+                }
+                _ => fields
+                    .values_mut()
+                    .for_each(|field| visit_image_urls(field, replace)),
+            }
+        }
+        _ => {}
+    }
+}
+
+fn is_remote(url: &str) -> bool {
+    url.starts_with("https://") || url.starts_with("http://")
+}
+
+fn host_allowed(url: &str, allowed_hosts: &[String]) -> bool {
+    let Some(host) = reqwest::Url::parse(url)
+        .ok()
+        .and_then(|u| u.host_str().map(str::to_ascii_lowercase))
+    else {
+        return false;
+    };
+    allowed_hosts.iter().any(|allowed| {
+        let allowed = allowed.to_ascii_lowercase();
+        match allowed.strip_prefix("*.") {
+            Some(domain) => host == domain || host.ends_with(&format!(".{domain}")),
+            None => host == allowed,
+        }
+    })
+}
+
+async fn fetch_image(
+    http_client: &reqwest::Client,
+    url: &str,
+    max_bytes: usize,
+    timeout: Duration,
+) -> Result<InlineImage, String> {
+    let mut response = http_client
+        .get(url)
+        .timeout(timeout)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    if !response.status().is_success() {
+        return Err(format!("status {}", response.status()));
+    }
+    let media_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.split(';').next())
+        .map(|v| v.trim().to_ascii_lowercase())
+        .filter(|v| v.starts_with("image/"))
+        .ok_or("response is not an image")?;
+    if response
+        .content_length()
+        .is_some_and(|len| len as usize > max_bytes)
+    {
+        return Err(format!("image larger than {max_bytes} bytes"));
+    }
+
+    let mut data = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(|e| e.to_string())? {
+        if data.len() + chunk.len() > max_bytes {
+            return Err(format!("image larger than {max_bytes} bytes"));
+        }
+        data.extend_from_slice(&chunk);
+    }
+    Ok((media_type, STANDARD.encode(data)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn host_allowlist_matches_exact_and_subdomains() {
+        let allowed = vec!["images.example.com".to_string(), "*.cdn.net".to_string()];
+        assert!(host_allowed("https://images.example.com/a.png", &allowed));
+        assert!(host_allowed("https://eu.cdn.net/a.png", &allowed));
+        assert!(host_allowed("https://cdn.net/a.png", &allowed));
+        assert!(!host_allowed("https://example.com/a.png", &allowed));
+        assert!(!host_allowed("https://evilcdn.net/a.png", &allowed));
+        assert!(!host_allowed("not a url", &allowed));
+    }
+
+    #[test]
+    fn replaces_images_in_every_client_shape() {
+        let mut body = json!({
+            "messages": [{"role": "user", "content": [
+                {"type": "text", "text": "what is this?"},
+                {"type": "image_url", "image_url": {"url": "https://img.test/a.png"}},
+                {"type": "image_url", "image_url": {"url": "data:image/png;base64,AAAA"}},
+                {"type": "image", "source": {"type": "url", "url": "https://img.test/a.png"}}
+            ]}],
+            "input": [{"role": "user", "content": [
+                {"type": "input_image", "image_url": "https://img.test/a.png"}
+            ]}]
+        });
+        let mut seen = Vec::new();
+        visit_image_urls(&mut body, &mut |url| {
+            seen.push(url.to_string());
+            Some(("image/png".to_string(), "iVBO".to_string()))
+        });
+
+        assert_eq!(seen.len(), 3);
+        let content = &body["messages"][0]["content"];
+        assert_eq!(content[1]["image_url"]["url"], "data:image/png;base64,iVBO");
+        assert_eq!(content[2]["image_url"]["url"], "data:image/png;base64,AAAA");
+        assert_eq!(
+            content[3]["source"],
+            json!({"type": "base64", "media_type": "image/png", "data": "iVBO"})
+        );
+        assert_eq!(
+            body["input"][0]["content"][0]["image_url"],
+            "data:image/png;base64,iVBO"
+        );
+    }
+
+    #[test]
+    fn applies_to_anthropic_and_bedrock_by_default() {
+        let inliner = ImageInliner::new(Some(ImageFetchSettings::default()));
+        assert!(inliner.applies_to(&LlmProviderType::Anthropic));
+        assert!(inliner.applies_to(&LlmProviderType::AmazonBedrock));
+        assert!(!inliner.applies_to(&LlmProviderType::OpenAI));
+        assert!(!ImageInliner::new(None).applies_to(&LlmProviderType::Anthropic));
+    }
+}
@@ -0,0 +1,37 @@
+//! Producing to Kafka through a Kafka REST Proxy (v2 API), which keeps a
+//! native Kafka client out of the gateway.
+
+use common::configuration::KafkaRestProxy;
+use serde_json::{json, Value};
+
+const KAFKA_JSON_CONTENT_TYPE: &str = "application/vnd.kafka.json.v2+json";
+
+/// Write `records`, each a key and a JSON value, to the proxy's topic in one
+/// request.
+pub async fn produce(
+    http_client: &reqwest::Client,
+    proxy: &KafkaRestProxy,
+    records: impl IntoIterator<Item = (Value, Value)>,
+) -> Result<(), String> {
+    let records: Vec<Value> = records
+        .into_iter()
+        .map(|(key, value)| json!({"key": key, "value": value}))
+        .collect();
+    let url = format!(
+        "{}/topics/{}",
+        proxy.rest_proxy_url.trim_end_matches('/'),
+        proxy.topic
+    );
+    let response = http_client
+        .post(url)
+        .header(reqwest::header::CONTENT_TYPE, KAFKA_JSON_CONTENT_TYPE)
+        .body(json!({ "records": records }).to_string())
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    if response.status().is_success() {
+        Ok(())
+    } else {
+        Err(format!("kafka rest proxy answered {}", response.status()))
+    }
+}
@@ -1,8 +1,39 @@
+pub mod access_keys;
 pub mod app_state;
+pub mod archive;
+pub mod aws_auth;
+pub mod bench;
+pub mod chaos;
+pub mod concurrency;
+pub mod config_store;
+pub mod connection;
+pub mod cooldown;
+pub mod event_bus;
 pub mod handlers;
+pub mod hedging;
+pub mod image_fetch;
+pub mod kafka_rest;
+pub mod mock_provider;
+pub mod moderation;
+pub mod output_budget;
+pub mod plugins;
+pub mod prompt_compression;
+pub mod provider_health;
+pub mod response_cache;
+pub mod retrieval;
 pub mod router;
+pub mod secret_guardrail;
+pub mod secret_keys;
 pub mod session_cache;
 pub mod signals;
 pub mod state;
+pub mod stream_resumption;
 pub mod streaming;
+pub mod structured_output;
+pub mod system_prompt;
+pub mod tenancy;
 pub mod tracing;
+pub mod traffic_recording;
+pub mod usage_export;
+pub mod vertex_auth;
+pub mod warmup;
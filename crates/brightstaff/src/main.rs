@@ -1,27 +1,68 @@
+use brightstaff::access_keys::AccessKeySlots;
 use brightstaff::app_state::AppState;
+use brightstaff::archive::Archiver;
+use brightstaff::aws_auth::BedrockCredentials;
+use brightstaff::chaos::Chaos;
+use brightstaff::concurrency::ConcurrencyLimiter;
+use brightstaff::config_store::ConfigStore;
+use brightstaff::connection;
+use brightstaff::cooldown::ProviderCooldowns;
+use brightstaff::event_bus::EventBus;
+use brightstaff::handlers::admin::{admin_config, ADMIN_PATH_PREFIX};
 use brightstaff::handlers::agents::orchestrator::agent_chat;
-use brightstaff::handlers::empty;
+use brightstaff::handlers::audio::transcriptions;
+use brightstaff::handlers::batches::batches;
+use brightstaff::handlers::compression;
+use brightstaff::handlers::conversation_title::conversation_title;
 use brightstaff::handlers::function_calling::function_calling_chat_handler;
 use brightstaff::handlers::llm::llm_chat;
+use brightstaff::handlers::model_apis::{completions, embeddings, image_generations};
 use brightstaff::handlers::models::list_models;
+use brightstaff::handlers::moderations::moderations;
+use brightstaff::handlers::realtime::realtime;
+use brightstaff::handlers::responses::responses;
 use brightstaff::handlers::routing_service::routing_decision;
+use brightstaff::handlers::tokenize::{count_tokens, tokenize};
+use brightstaff::handlers::{empty, full};
+use brightstaff::image_fetch::ImageInliner;
+use brightstaff::moderation::Moderator;
+use brightstaff::plugins::PluginHost;
+use brightstaff::provider_health::ProviderHealth;
+use brightstaff::response_cache::ResponseCache;
+use brightstaff::retrieval::Retriever;
 use brightstaff::router::model_metrics::ModelMetricsService;
 use brightstaff::router::orchestrator::OrchestratorService;
+use brightstaff::secret_guardrail::SecretGuardrail;
+use brightstaff::secret_keys;
 use brightstaff::session_cache::init_session_cache;
 use brightstaff::state::memory::MemoryConversationalStorage;
 use brightstaff::state::postgresql::PostgreSQLConversationStorage;
 use brightstaff::state::StateStorage;
+use brightstaff::stream_resumption::ReplayBuffers;
+use brightstaff::tenancy::Tenancy;
 use brightstaff::tracing::init_tracer;
+use brightstaff::traffic_recording::TrafficRecorder;
+use brightstaff::usage_export::{UsageExporter, UsageLedger};
+use brightstaff::vertex_auth::VertexTokens;
+use brightstaff::warmup::Warmup;
 use bytes::Bytes;
+use common::config_validation::validate_config;
 use common::configuration::{
-    Agent, Configuration, FilterPipeline, ListenerType, ResolvedFilterChain,
+    Agent, Configuration, FilterPipeline, ListenerConnectionSettings, ListenerType,
+    ResolvedFilterChain,
+};
+use common::consts::{
+    ANTHROPIC_COUNT_TOKENS_PATH, AUDIO_TRANSCRIPTIONS_PATH, BATCHES_PATH, CHAT_COMPLETIONS_PATH,
+    COMPLETIONS_PATH, CONVERSATION_TITLE_PATH, EMBEDDINGS_PATH, FILES_PATH,
+    IMAGES_GENERATIONS_PATH, MESSAGES_PATH, MODERATIONS_PATH, OPENAI_RESPONSES_API_PATH,
+    READYZ_PATH, REALTIME_PATH, TOKENIZE_PATH,
 };
-use common::consts::{CHAT_COMPLETIONS_PATH, MESSAGES_PATH, OPENAI_RESPONSES_API_PATH};
 use common::llm_providers::LlmProviders;
+use common::model_aliases::ModelAliasTable;
+use common::pricing::PricingTable;
 use http_body_util::combinators::BoxBody;
 use hyper::body::Incoming;
 use hyper::header::HeaderValue;
-use hyper::server::conn::http1;
 use hyper::service::service_fn;
 use hyper::{Method, Request, Response, StatusCode};
 use hyper_util::rt::TokioIo;
@@ -30,6 +71,7 @@ use opentelemetry::trace::FutureExt;
 use opentelemetry_http::HeaderExtractor;
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 use std::{env, fs};
 use tokio::net::TcpListener;
 use tokio::sync::RwLock;
@@ -78,16 +120,48 @@ fn cors_preflight() -> Result<Response<BoxBody<Bytes, hyper::Error>>, hyper::Err
 /// The path is read from `PLANO_CONFIG_PATH_RENDERED` (env) or falls back to
 /// `./plano_config_rendered.yaml`.
 fn load_config() -> Result<Configuration, Box<dyn std::error::Error + Send + Sync>> {
-    let path = env::var("PLANO_CONFIG_PATH_RENDERED")
-        .unwrap_or_else(|_| "./plano_config_rendered.yaml".to_string());
+    let path = config_path();
     eprintln!("loading plano_config.yaml from {}", path);
 
     let contents = fs::read_to_string(&path).map_err(|e| format!("failed to read {path}: {e}"))?;
 
-    let config: Configuration =
-        serde_yaml::from_str(&contents).map_err(|e| format!("failed to parse {path}: {e}"))?;
+    validate_config(&contents)
+        .into_result()
+        .map_err(|errors| format!("invalid configuration {path}:\n{errors}").into())
+}
 
-    Ok(config)
+fn config_path() -> String {
+    env::var("PLANO_CONFIG_PATH_RENDERED")
+        .unwrap_or_else(|_| "./plano_config_rendered.yaml".to_string())
+}
+
+/// `--validate-config [path]`: report every problem in the configuration and
+/// exit non-zero if any of them is an error. Does not start the server.
+fn run_validate_config(path: Option<String>) -> i32 {
+    let path = path.unwrap_or_else(config_path);
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(e) => {
+            eprintln!("failed to read {path}: {e}");
+            return 1;
+        }
+    };
+
+    let report = validate_config(&contents);
+    for issue in &report.issues {
+        eprintln!("{path}: {issue}");
+    }
+    if report.has_errors() {
+        eprintln!(
+            "{path}: {} error(s), {} warning(s)",
+            report.errors().count(),
+            report.warnings().count()
+        );
+        1
+    } else {
+        eprintln!("{path}: configuration is valid");
+        0
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -183,7 +257,7 @@ async fn init_app_state(
             .flat_map(|p| std::iter::once(p.name.as_str()).chain(p.model.as_deref()))
             .collect();
         for pref in route_prefs {
-            for model in &pref.models {
+            for model in pref.models.iter().chain(&pref.content_filter_fallback) {
                 if !provider_model_names.contains(model.as_str()) {
                     return Err(format!(
                         "routing_preferences route '{}' references model '{}' \
@@ -196,11 +270,25 @@ async fn init_app_state(
         }
     }
 
-    // Validate and initialize ModelMetricsService if model_metrics_sources is configured.
-    let metrics_service: Option<Arc<ModelMetricsService>> = if let Some(ref sources) =
-        config.model_metrics_sources
+    let pricing = Arc::new(PricingTable::new(config.pricing.as_ref()));
+
+    // Cost ranking works from the pricing table alone; latency ranking needs a source.
+    let needs_cost_ranking = config
+        .routing_preferences
+        .as_deref()
+        .unwrap_or_default()
+        .iter()
+        .any(|p| p.selection_policy.prefer == common::configuration::SelectionPreference::Cheapest);
+
+    // Validate and initialize ModelMetricsService if model_metrics_sources is configured
+    // or a route ranks models by cost.
+    let metrics_service: Option<Arc<ModelMetricsService>> = if config
+        .model_metrics_sources
+        .is_some()
+        || needs_cost_ranking
     {
         use common::configuration::MetricsSource;
+        let sources = config.model_metrics_sources.as_deref().unwrap_or_default();
         let cost_count = sources
             .iter()
             .filter(|s| matches!(s, MetricsSource::Cost(_)))
@@ -215,7 +303,8 @@ async fn init_app_state(
         if latency_count > 1 {
             return Err("model_metrics_sources: only one latency metrics source is allowed".into());
         }
-        let svc = ModelMetricsService::new(sources, reqwest::Client::new()).await;
+        let svc =
+            ModelMetricsService::new(sources, reqwest::Client::new(), Arc::clone(&pricing)).await;
         Some(Arc::new(svc))
     } else {
         None
@@ -225,12 +314,6 @@ async fn init_app_state(
     if let Some(ref prefs) = config.routing_preferences {
         use common::configuration::{MetricsSource, SelectionPreference};
 
-        let has_cost_source = config
-            .model_metrics_sources
-            .as_deref()
-            .unwrap_or_default()
-            .iter()
-            .any(|s| matches!(s, MetricsSource::Cost(_)));
         let has_latency_source = config
             .model_metrics_sources
             .as_deref()
@@ -239,14 +322,6 @@ async fn init_app_state(
             .any(|s| matches!(s, MetricsSource::Latency(_)));
 
         for pref in prefs {
-            if pref.selection_policy.prefer == SelectionPreference::Cheapest && !has_cost_source {
-                return Err(format!(
-                    "routing_preferences route '{}' uses prefer: cheapest but no cost metrics source is configured — \
-                     add a cost metrics source to model_metrics_sources",
-                    pref.name
-                )
-                .into());
-            }
             if pref.selection_policy.prefer == SelectionPreference::Fastest && !has_latency_source {
                 return Err(format!(
                     "routing_preferences route '{}' uses prefer: fastest but no latency metrics source is configured — \
@@ -260,13 +335,12 @@ async fn init_app_state(
 
     // Warn about models in routing_preferences that have no matching pricing/latency data.
     if let (Some(ref prefs), Some(ref svc)) = (&config.routing_preferences, &metrics_service) {
-        let cost_data = svc.cost_snapshot().await;
         let latency_data = svc.latency_snapshot().await;
         for pref in prefs {
             use common::configuration::SelectionPreference;
             for model in &pref.models {
                 let missing = match pref.selection_policy.prefer {
-                    SelectionPreference::Cheapest => !cost_data.contains_key(model.as_str()),
+                    SelectionPreference::Cheapest => !svc.has_cost_data(model).await,
                     SelectionPreference::Fastest => !latency_data.contains_key(model.as_str()),
                     _ => false,
                 };
@@ -321,22 +395,161 @@ async fn init_app_state(
 
     let state_storage = init_state_storage(config).await?;
 
+    let concurrency_limiter = Arc::new(
+        ConcurrencyLimiter::new(config.concurrency_limits.as_ref(), &config.model_providers)
+            .with_tenant_limits(config.tenancy.as_ref()),
+    );
+    if concurrency_limiter.is_enabled() {
+        info!("upstream concurrency limits enabled");
+    }
+
+    let http_client = connection::upstream_client(
+        config
+            .connection_settings
+            .as_ref()
+            .and_then(|c| c.upstream.as_ref()),
+    )?;
+
     let span_attributes = config
         .tracing
         .as_ref()
         .and_then(|tracing| tracing.span_attributes.clone());
 
+    let traffic_recorder = config
+        .traffic_recording
+        .as_ref()
+        .map(TrafficRecorder::new)
+        .transpose()?
+        .map(Arc::new);
+    if let Some(settings) = &config.traffic_recording {
+        info!(mode = ?settings.mode, directory = %settings.directory, "traffic recording enabled");
+    }
+
+    if config.chaos.is_some() {
+        warn!("chaos fault injection is enabled; model responses will be delayed, failed or corrupted");
+    }
+
+    let usage_ledger = match &config.usage_export {
+        Some(settings) => {
+            let ledger = Arc::new(UsageLedger::new(settings));
+            UsageExporter::new(settings, Arc::clone(&ledger))?.spawn();
+            info!(format = ?settings.format, "hourly usage export enabled");
+            Some(ledger)
+        }
+        None => None,
+    };
+
+    let archiver = config
+        .archive
+        .as_ref()
+        .map(|settings| Archiver::spawn(settings, http_client.clone()))
+        .transpose()?;
+    if archiver.is_some() {
+        info!("archiving completed exchanges");
+    }
+
+    let event_bus = match &config.event_bus {
+        Some(settings) => {
+            let bus = EventBus::connect(settings, http_client.clone()).await?;
+            info!(
+                nats = settings.nats.is_some(),
+                kafka = settings.kafka.is_some(),
+                "publishing completion events"
+            );
+            Some(bus)
+        }
+        None => None,
+    };
+
+    let retriever = config
+        .retrieval
+        .clone()
+        .map(|settings| Arc::new(Retriever::new(settings, http_client.clone())));
+
+    let secret_guardrail = config
+        .secret_guardrail
+        .as_ref()
+        .map(SecretGuardrail::new)
+        .transpose()?
+        .map(Arc::new);
+    if let Some(settings) = &config.secret_guardrail {
+        info!(action = ?settings.action, "scanning prompts for credentials");
+    }
+
+    let moderator = Arc::new(Moderator::new(
+        &config.moderation.clone().unwrap_or_default(),
+    )?);
+    if config
+        .moderation
+        .as_ref()
+        .is_some_and(|settings| settings.screen_prompts)
+    {
+        info!(
+            model = moderator.default_model(),
+            "screening prompts with moderation"
+        );
+    }
+
+    let access_key_slots = Arc::new(AccessKeySlots::default());
+    access_key_slots
+        .set_secrets(secret_keys::resolve(&config.model_providers, &http_client).await?);
+    let vertex_tokens = VertexTokens::spawn(&config.model_providers, http_client.clone())?;
+    let bedrock_credentials =
+        BedrockCredentials::spawn(&config.model_providers, http_client.clone());
+
+    let admin = config.admin.clone().unwrap_or_default();
+    if admin.api_key.is_some() {
+        info!("admin api enabled at /admin/config");
+    }
+
     Ok(AppState {
         orchestrator_service,
-        model_aliases: config.model_aliases.clone(),
+        model_aliases: ModelAliasTable::new(config.model_aliases.as_ref()),
+        model_deprecations: config.model_deprecations.clone().unwrap_or_default(),
         llm_providers: Arc::new(RwLock::new(llm_providers)),
         agents_list: Some(all_agents),
         listeners: config.listeners.clone(),
         state_storage,
         llm_provider_url,
         span_attributes,
-        http_client: reqwest::Client::new(),
+        http_client,
         filter_pipeline,
+        concurrency_limiter,
+        pricing,
+        config_store: Arc::new(ConfigStore::new(config.clone(), admin.config_history)),
+        admin_api_key: admin.api_key,
+        tenancy: Arc::new(Tenancy::new(config.tenancy.as_ref())),
+        access_key_slots,
+        vertex_tokens,
+        bedrock_credentials,
+        warmup: Arc::new(Warmup::new(config.warmup.clone())),
+        provider_cooldowns: Arc::new(ProviderCooldowns::default()),
+        provider_health: Arc::new(ProviderHealth::default()),
+        image_inliner: Arc::new(ImageInliner::new(config.image_fetch.clone())),
+        conversation_title: config.conversation_title.clone(),
+        plugins: Arc::new(PluginHost::load(
+            config.plugins.as_deref().unwrap_or_default(),
+        )?),
+        response_cache: Arc::new(ResponseCache::default()),
+        traffic_recorder,
+        chaos: config
+            .chaos
+            .as_ref()
+            .map(|chaos| Arc::new(Chaos::new(chaos))),
+        usage_ledger,
+        stream_replay: config
+            .stream_resumption
+            .as_ref()
+            .map(|settings| Arc::new(ReplayBuffers::new(settings))),
+        stream_keep_alive: config
+            .stream_keep_alive
+            .as_ref()
+            .map(|settings| Duration::from_secs(settings.interval_secs.unwrap_or(15).max(1))),
+        archiver,
+        event_bus,
+        retriever,
+        secret_guardrail,
+        moderator,
     })
 }
 
@@ -422,6 +635,11 @@ async fn route(
         }
     }
 
+    // --- Runtime configuration API (/admin/...) ---
+    if path.starts_with(ADMIN_PATH_PREFIX) {
+        return admin_config(req, Arc::clone(&state)).await;
+    }
+
     // --- Standard routes ---
     match (req.method(), path.as_str()) {
         (&Method::POST, CHAT_COMPLETIONS_PATH | MESSAGES_PATH | OPENAI_RESPONSES_API_PATH) => {
@@ -435,10 +653,46 @@ async fn route(
                 .with_context(parent_cx)
                 .await
         }
+        (&Method::GET, READYZ_PATH) => {
+            let status = if state.warmup.is_ready() {
+                StatusCode::OK
+            } else {
+                StatusCode::SERVICE_UNAVAILABLE
+            };
+            Ok(Response::builder()
+                .status(status)
+                .header(hyper::header::CONTENT_TYPE, "application/json")
+                .body(full(state.warmup.readiness().to_string()))
+                .unwrap())
+        }
+        (&Method::POST, TOKENIZE_PATH) => tokenize(req, Arc::clone(&state)).await,
+        (&Method::POST, ANTHROPIC_COUNT_TOKENS_PATH) => count_tokens(req, Arc::clone(&state)).await,
+        (&Method::POST, COMPLETIONS_PATH) => completions(req, Arc::clone(&state)).await,
+        (&Method::POST, EMBEDDINGS_PATH) => embeddings(req, Arc::clone(&state)).await,
+        (&Method::POST, IMAGES_GENERATIONS_PATH) => {
+            image_generations(req, Arc::clone(&state)).await
+        }
+        (&Method::POST, AUDIO_TRANSCRIPTIONS_PATH) => transcriptions(req, Arc::clone(&state)).await,
+        (&Method::POST, MODERATIONS_PATH) => moderations(req, Arc::clone(&state)).await,
+        (&Method::GET, REALTIME_PATH) => realtime(req, Arc::clone(&state)).await,
+        (&Method::POST, CONVERSATION_TITLE_PATH) => {
+            conversation_title(req, Arc::clone(&state)).await
+        }
         (&Method::GET, "/v1/models" | "/agents/v1/models") => {
-            Ok(list_models(Arc::clone(&state.llm_providers)).await)
+            match state.tenancy.resolve(req.headers()) {
+                Ok(tenant) => {
+                    Ok(list_models(Arc::clone(&state.llm_providers), tenant.as_deref()).await)
+                }
+                Err(err) => Ok(err.into_response()),
+            }
         }
         (&Method::OPTIONS, "/v1/models" | "/agents/v1/models") => cors_preflight(),
+        (_, route) if route.starts_with(BATCHES_PATH) || route.starts_with(FILES_PATH) => {
+            batches(req, Arc::clone(&state)).await
+        }
+        (&Method::GET | &Method::DELETE, route) if route.starts_with(OPENAI_RESPONSES_API_PATH) => {
+            responses(req, Arc::clone(&state)).await
+        }
         _ => {
             debug!(method = %req.method(), path = %path, "no route found");
             let mut not_found = Response::new(empty());
@@ -456,7 +710,10 @@ async fn route(
 ///
 /// Listens for `SIGINT` / `ctrl-c` and shuts down gracefully, allowing
 /// in-flight connections to finish.
-async fn run_server(state: Arc<AppState>) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+async fn run_server(
+    state: Arc<AppState>,
+    listener_settings: Option<ListenerConnectionSettings>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let bind_address = env::var("BIND_ADDRESS").unwrap_or_else(|_| BIND_ADDRESS.to_string());
     let listener = TcpListener::bind(&bind_address).await?;
     info!(address = %bind_address, "server listening");
@@ -469,6 +726,12 @@ async fn run_server(state: Arc<AppState>) -> Result<(), Box<dyn std::error::Erro
             result = listener.accept() => {
                 let (stream, _) = result?;
                 let peer_addr = stream.peer_addr()?;
+                if let Some(settings) = &listener_settings {
+                    if let Err(err) = connection::configure_accepted_stream(&stream, settings) {
+                        warn!(peer = ?peer_addr, error = %err, "failed to apply listener socket options");
+                    }
+                }
+                let builder = connection::http1_builder(listener_settings.as_ref());
                 let io = TokioIo::new(stream);
                 let state = Arc::clone(&state);
 
@@ -477,10 +740,19 @@ async fn run_server(state: Arc<AppState>) -> Result<(), Box<dyn std::error::Erro
 
                     let service = service_fn(move |req| {
                         let state = Arc::clone(&state);
-                        async move { route(req, state).await }
+                        async move {
+                            let accept_encoding = req
+                                .headers()
+                                .get(hyper::header::ACCEPT_ENCODING)
+                                .and_then(|v| v.to_str().ok())
+                                .map(str::to_string);
+                            let response = route(req, state).await?;
+                            compression::compress_response(response, accept_encoding.as_deref())
+                                .await
+                        }
                     });
 
-                    if let Err(err) = http1::Builder::new().serve_connection(io, service).await {
+                    if let Err(err) = builder.serve_connection(io, service).with_upgrades().await {
                         warn!(error = ?err, "error serving connection");
                     }
                 });
@@ -501,9 +773,28 @@ async fn run_server(state: Arc<AppState>) -> Result<(), Box<dyn std::error::Erro
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let mut args = env::args().skip(1);
+    match args.next().as_deref() {
+        Some("--validate-config") => std::process::exit(run_validate_config(args.next())),
+        Some("bench") => std::process::exit(brightstaff::bench::main(args).await),
+        _ => {}
+    }
+
     let config = load_config()?;
     let _tracer_provider = init_tracer(config.tracing.as_ref());
     info!("loaded plano_config.yaml");
     let state = Arc::new(init_app_state(&config).await?);
-    run_server(state).await
+    if state.warmup.is_enabled() {
+        info!("warming up model provider connections");
+        state.warmup.start(
+            &*state.llm_providers.read().await,
+            state.http_client.clone(),
+            state.llm_provider_url.clone(),
+        );
+    }
+    let listener_settings = config
+        .connection_settings
+        .as_ref()
+        .and_then(|c| c.listener.clone());
+    run_server(state, listener_settings).await
 }
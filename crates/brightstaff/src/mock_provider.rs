@@ -0,0 +1,455 @@
+//! The built-in `mock` provider.
+//!
+//! Providers with `provider_interface: mock` never reach Envoy: brightstaff
+//! answers them with a canned completion in the client's API shape, streamed
+//! word by word when the client asked for a stream. The `mock` settings add a
+//! delay, pace the stream and inject errors, and the `x-arch-mock-status`
+//! request header fails a single request with the given status, so e2e tests
+//! and SDK development can exercise the gateway without real API keys.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use bytes::Bytes;
+use common::configuration::MockProviderSettings;
+use common::consts::ARCH_MOCK_STATUS_HEADER;
+use common::utils::parse_duration_ms;
+use futures::StreamExt;
+use hermesllm::clients::SupportedAPIsFromClient;
+use hyper::header::{HeaderMap, CONTENT_TYPE, RETRY_AFTER};
+use hyper::StatusCode;
+use serde_json::{json, Value};
+
+const DEFAULT_ERROR_STATUS: u16 = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Shape {
+    ChatCompletions,
+    Messages,
+    Responses,
+}
+
+impl Shape {
+    fn of(client_api: Option<&SupportedAPIsFromClient>) -> Self {
+        match client_api {
+            Some(SupportedAPIsFromClient::AnthropicMessagesAPI(_)) => Shape::Messages,
+            Some(SupportedAPIsFromClient::OpenAIResponsesAPI(_)) => Shape::Responses,
+            _ => Shape::ChatCompletions,
+        }
+    }
+}
+
+/// The mock provider's answer to a request for `model`, shaped as a response
+/// from the upstream so the rest of the pipeline treats it like any other.
+pub async fn respond(
+    settings: Option<&MockProviderSettings>,
+    client_api: Option<&SupportedAPIsFromClient>,
+    model: &str,
+    request_body: &[u8],
+    is_streaming: bool,
+    request_headers: &HeaderMap,
+) -> reqwest::Response {
+    let default_settings = MockProviderSettings::default();
+    let settings = settings.unwrap_or(&default_settings);
+    if let Some(delay) = settings.delay.as_deref().and_then(parse_duration_ms) {
+        tokio::time::sleep(Duration::from_millis(delay)).await;
+    }
+
+    let shape = Shape::of(client_api);
+    if let Some(status) = injected_error(settings, request_headers) {
+        return error_response(shape, status);
+    }
+
+    let text = settings
+        .response
+        .clone()
+        .unwrap_or_else(|| format!("This is a mock response from {model}."));
+    let completion = Completion {
+        model,
+        text: &text,
+        // Roughly four bytes per token, enough for usage accounting in tests.
+        input_tokens: (request_body.len() as u64 / 4).max(1),
+        output_tokens: words(&text).count() as u64,
+        created: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs()),
+    };
+
+    let response = if is_streaming {
+        let pace = settings
+            .tokens_per_second
+            .filter(|rate| *rate > 0.0)
+            .map(|rate| Duration::from_secs_f64(1.0 / rate));
+        let events = completion.events(shape);
+        let stream = futures::stream::iter(events.into_iter().enumerate()).then(
+            move |(index, event)| async move {
+                if let Some(pace) = pace.filter(|_| index > 0) {
+                    tokio::time::sleep(pace).await;
+                }
+                Ok::<_, std::io::Error>(Bytes::from(event))
+            },
+        );
+        hyper::http::Response::builder()
+            .header(CONTENT_TYPE, "text/event-stream")
+            .body(reqwest::Body::wrap_stream(stream))
+    } else {
+        hyper::http::Response::builder()
+            .header(CONTENT_TYPE, "application/json")
+            .body(reqwest::Body::from(completion.body(shape).to_string()))
+    };
+    reqwest::Response::from(response.unwrap())
+}
+
+/// The status to fail with: the request's `x-arch-mock-status`, else
+/// `error_status` for a random `error_rate` share of requests.
+fn injected_error(settings: &MockProviderSettings, headers: &HeaderMap) -> Option<StatusCode> {
+    let forced = headers
+        .get(ARCH_MOCK_STATUS_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<u16>().ok());
+    let status = forced.or_else(|| {
+        settings
+            .error_rate
+            .filter(|rate| rand::random::<f64>() < *rate)
+            .map(|_| settings.error_status.unwrap_or(DEFAULT_ERROR_STATUS))
+    })?;
+    StatusCode::from_u16(status)
+        .ok()
+        .filter(|s| !s.is_success())
+}
+
+fn error_response(shape: Shape, status: StatusCode) -> reqwest::Response {
+    let message = format!("mock provider injected a {} error", status.as_u16());
+    let body = match shape {
+        Shape::Messages => json!({
+            "type": "error",
+            "error": {"type": "api_error", "message": message},
+        }),
+        _ => json!({
+            "error": {"message": message, "type": "mock_error", "code": status.as_u16()},
+        }),
+    };
+    let mut response = hyper::http::Response::builder()
+        .status(status)
+        .header(CONTENT_TYPE, "application/json");
+    if status == StatusCode::TOO_MANY_REQUESTS {
+        response = response.header(RETRY_AFTER, "1");
+    }
+    reqwest::Response::from(
+        response
+            .body(reqwest::Body::from(body.to_string()))
+            .unwrap(),
+    )
+}
+
+/// Words with their trailing whitespace, so streamed deltas join back into
+/// the original text.
+fn words(text: &str) -> impl Iterator<Item = &str> {
+    text.split_inclusive(char::is_whitespace)
+}
+
+struct Completion<'a> {
+    model: &'a str,
+    text: &'a str,
+    input_tokens: u64,
+    output_tokens: u64,
+    created: u64,
+}
+
+impl Completion<'_> {
+    fn body(&self, shape: Shape) -> Value {
+        match shape {
+            Shape::ChatCompletions => json!({
+                "id": "chatcmpl-mock",
+                "object": "chat.completion",
+                "created": self.created,
+                "model": self.model,
+                "choices": [{
+                    "index": 0,
+                    "message": {"role": "assistant", "content": self.text},
+                    "finish_reason": "stop",
+                }],
+                "usage": self.chat_usage(),
+            }),
+            Shape::Messages => json!({
+                "id": "msg_mock",
+                "type": "message",
+                "role": "assistant",
+                "model": self.model,
+                "content": [{"type": "text", "text": self.text}],
+                "stop_reason": "end_turn",
+                "stop_sequence": null,
+                "usage": {"input_tokens": self.input_tokens, "output_tokens": self.output_tokens},
+            }),
+            Shape::Responses => self.response_object("completed", self.text),
+        }
+    }
+
+    /// The streamed form of the completion as SSE frames, one text delta per word.
+    fn events(&self, shape: Shape) -> Vec<String> {
+        match shape {
+            Shape::ChatCompletions => {
+                let chunk = |delta: Value, finish_reason: Value| {
+                    json!({
+                        "id": "chatcmpl-mock",
+                        "object": "chat.completion.chunk",
+                        "created": self.created,
+                        "model": self.model,
+                        "choices": [{"index": 0, "delta": delta, "finish_reason": finish_reason}],
+                    })
+                };
+                let mut last = chunk(json!({}), json!("stop"));
+                last["usage"] = self.chat_usage();
+                std::iter::once(chunk(
+                    json!({"role": "assistant", "content": ""}),
+                    Value::Null,
+                ))
+                .chain(words(self.text).map(|w| chunk(json!({"content": w}), Value::Null)))
+                .chain(std::iter::once(last))
+                .map(|chunk| format!("data: {chunk}\n\n"))
+                .chain(std::iter::once("data: [DONE]\n\n".to_string()))
+                .collect()
+            }
+            Shape::Messages => {
+                let mut message = self.body(Shape::Messages);
+                message["content"] = json!([]);
+                message["stop_reason"] = Value::Null;
+                message["usage"]["output_tokens"] = json!(0);
+                let mut events = vec![
+                    sse(
+                        "message_start",
+                        json!({"type": "message_start", "message": message}),
+                    ),
+                    sse(
+                        "content_block_start",
+                        json!({
+                            "type": "content_block_start",
+                            "index": 0,
+                            "content_block": {"type": "text", "text": ""},
+                        }),
+                    ),
+                ];
+                events.extend(words(self.text).map(|w| {
+                    sse(
+                        "content_block_delta",
+                        json!({
+                            "type": "content_block_delta",
+                            "index": 0,
+                            "delta": {"type": "text_delta", "text": w},
+                        }),
+                    )
+                }));
+                events.extend([
+                    sse(
+                        "content_block_stop",
+                        json!({"type": "content_block_stop", "index": 0}),
+                    ),
+                    sse(
+                        "message_delta",
+                        json!({
+                            "type": "message_delta",
+                            "delta": {"stop_reason": "end_turn", "stop_sequence": null},
+                            "usage": {"output_tokens": self.output_tokens},
+                        }),
+                    ),
+                    sse("message_stop", json!({"type": "message_stop"})),
+                ]);
+                events
+            }
+            Shape::Responses => {
+                let mut events = vec![sse(
+                    "response.created",
+                    json!({
+                        "type": "response.created",
+                        "response": self.response_object("in_progress", ""),
+                    }),
+                )];
+                events.extend(words(self.text).map(|w| {
+                    sse(
+                        "response.output_text.delta",
+                        json!({
+                            "type": "response.output_text.delta",
+                            "item_id": "msg_mock",
+                            "output_index": 0,
+                            "content_index": 0,
+                            "delta": w,
+                        }),
+                    )
+                }));
+                events.push(sse(
+                    "response.completed",
+                    json!({
+                        "type": "response.completed",
+                        "response": self.response_object("completed", self.text),
+                    }),
+                ));
+                events
+            }
+        }
+    }
+
+    fn chat_usage(&self) -> Value {
+        json!({
+            "prompt_tokens": self.input_tokens,
+            "completion_tokens": self.output_tokens,
+            "total_tokens": self.input_tokens + self.output_tokens,
+        })
+    }
+
+    fn response_object(&self, status: &str, text: &str) -> Value {
+        let output = if text.is_empty() {
+            json!([])
+        } else {
+            json!([{
+                "type": "message",
+                "id": "msg_mock",
+                "status": "completed",
+                "role": "assistant",
+                "content": [{"type": "output_text", "text": text, "annotations": []}],
+            }])
+        };
+        json!({
+            "id": "resp_mock",
+            "object": "response",
+            "created_at": self.created,
+            "status": status,
+            "model": self.model,
+            "output": output,
+            "usage": {
+                "input_tokens": self.input_tokens,
+                "output_tokens": self.output_tokens,
+                "total_tokens": self.input_tokens + self.output_tokens,
+            },
+        })
+    }
+}
+
+fn sse(event: &str, data: Value) -> String {
+    format!("event: {event}\ndata: {data}\n\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hermesllm::apis::{AnthropicApi, OpenAIApi};
+    use hyper::header::HeaderValue;
+
+    fn settings(response: &str) -> MockProviderSettings {
+        MockProviderSettings {
+            response: Some(response.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn completions_take_the_client_api_shape() {
+        let settings = settings("Hello from the mock.");
+        let headers = HeaderMap::new();
+
+        let chat = respond(Some(&settings), None, "gpt-4o", b"{}", false, &headers).await;
+        assert_eq!(chat.status(), StatusCode::OK);
+        let chat: Value = chat.json().await.unwrap();
+        assert_eq!(
+            chat["choices"][0]["message"]["content"],
+            "Hello from the mock."
+        );
+        assert_eq!(chat["usage"]["completion_tokens"], 4);
+
+        let anthropic = SupportedAPIsFromClient::AnthropicMessagesAPI(AnthropicApi::Messages);
+        let message: Value = respond(
+            Some(&settings),
+            Some(&anthropic),
+            "claude",
+            b"{}",
+            false,
+            &headers,
+        )
+        .await
+        .json()
+        .await
+        .unwrap();
+        assert_eq!(message["content"][0]["text"], "Hello from the mock.");
+        assert_eq!(message["stop_reason"], "end_turn");
+
+        let responses = SupportedAPIsFromClient::OpenAIResponsesAPI(OpenAIApi::Responses);
+        let response: Value = respond(None, Some(&responses), "gpt-4o", b"{}", false, &headers)
+            .await
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(
+            response["output"][0]["content"][0]["text"],
+            "This is a mock response from gpt-4o."
+        );
+    }
+
+    #[tokio::test]
+    async fn streams_one_delta_per_word() {
+        let settings = settings("one two three");
+        let body = respond(
+            Some(&settings),
+            None,
+            "gpt-4o",
+            b"{}",
+            true,
+            &HeaderMap::new(),
+        )
+        .await
+        .text()
+        .await
+        .unwrap();
+        let deltas: String = body
+            .split("\n\n")
+            .filter_map(|frame| frame.strip_prefix("data: "))
+            .filter_map(|data| serde_json::from_str::<Value>(data).ok())
+            .filter_map(|chunk| {
+                chunk["choices"][0]["delta"]["content"]
+                    .as_str()
+                    .map(String::from)
+            })
+            .collect();
+        assert_eq!(deltas, "one two three");
+        assert!(body.ends_with("data: [DONE]\n\n"));
+
+        let anthropic = SupportedAPIsFromClient::AnthropicMessagesAPI(AnthropicApi::Messages);
+        let events = Completion {
+            model: "claude",
+            text: "one two",
+            input_tokens: 1,
+            output_tokens: 2,
+            created: 0,
+        }
+        .events(Shape::of(Some(&anthropic)));
+        assert_eq!(events.len(), 7);
+        assert!(events[0].starts_with("event: message_start\n"));
+        assert!(events[6].starts_with("event: message_stop\n"));
+    }
+
+    #[tokio::test]
+    async fn injects_errors() {
+        let mut headers = HeaderMap::new();
+        headers.insert(ARCH_MOCK_STATUS_HEADER, HeaderValue::from_static("429"));
+        let response = respond(None, None, "gpt-4o", b"{}", true, &headers).await;
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[RETRY_AFTER], "1");
+
+        let always_failing = MockProviderSettings {
+            error_rate: Some(1.0),
+            error_status: Some(503),
+            ..Default::default()
+        };
+        let response = respond(
+            Some(&always_failing),
+            None,
+            "gpt-4o",
+            b"{}",
+            false,
+            &HeaderMap::new(),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(
+            injected_error(&MockProviderSettings::default(), &HeaderMap::new()),
+            None
+        );
+    }
+}
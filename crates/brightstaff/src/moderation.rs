@@ -0,0 +1,292 @@
+//! Content moderation.
+//!
+//! Moderation requests go to whichever backend their model names: an OpenAI
+//! moderation model or a Bedrock guardrail through the LLM gateway (which
+//! translates to `ApplyGuardrail`), or the `local` classifier, which matches
+//! configured regexes per category without leaving the process. Every backend
+//! answers in the OpenAI categories/scores shape.
+//!
+//! Besides serving `/v1/moderations`, [`screen`] is a pipeline stage for the
+//! other handlers: with `moderation.screen_prompts` set, prompts flagged by
+//! the configured model are refused before routing. Screening fails open; a
+//! prompt goes on when the moderation backend cannot be reached.
+
+use std::collections::BTreeMap;
+
+use common::configuration::{ModerationSettings, TenantConfig};
+use common::consts::{
+    ARCH_IS_STREAMING_HEADER, ARCH_PROVIDER_HINT_HEADER, MODERATIONS_PATH, REQUEST_ID_HEADER,
+    TRACE_PARENT_HEADER,
+};
+use common::errors::BrightStaffError;
+use hermesllm::apis::moderations::{
+    ModerationInput, ModerationRequest, ModerationResponse, ModerationResult,
+};
+use hyper::header::{self, HeaderValue};
+use hyper::HeaderMap;
+use regex::{Regex, RegexBuilder};
+use tracing::{debug, warn};
+
+use crate::app_state::AppState;
+use crate::handlers::llm::resolve_model_alias;
+
+/// Model name of the in-process classifier.
+pub const LOCAL_MODEL: &str = "local";
+const DEFAULT_MODEL: &str = "openai/omni-moderation-latest";
+
+pub struct Moderator {
+    model: String,
+    screen_prompts: bool,
+    local: Option<LocalClassifier>,
+}
+
+/// Flags a category when any of its patterns matches.
+struct LocalClassifier {
+    categories: Vec<(String, Vec<Regex>)>,
+}
+
+impl Moderator {
+    pub fn new(settings: &ModerationSettings) -> Result<Self, regex::Error> {
+        let local = settings
+            .local_categories
+            .as_ref()
+            .map(|categories| {
+                let mut categories = categories
+                    .iter()
+                    .map(|(category, patterns)| {
+                        let patterns = patterns
+                            .iter()
+                            .map(|pattern| {
+                                RegexBuilder::new(pattern).case_insensitive(true).build()
+                            })
+                            .collect::<Result<Vec<_>, _>>()?;
+                        Ok((category.clone(), patterns))
+                    })
+                    .collect::<Result<Vec<_>, regex::Error>>()?;
+                categories.sort_by(|a, b| a.0.cmp(&b.0));
+                Ok::<_, regex::Error>(LocalClassifier { categories })
+            })
+            .transpose()?;
+        let model = settings.model.clone().unwrap_or_else(|| {
+            if local.is_some() {
+                LOCAL_MODEL.to_string()
+            } else {
+                DEFAULT_MODEL.to_string()
+            }
+        });
+        Ok(Moderator {
+            model,
+            screen_prompts: settings.screen_prompts,
+            local,
+        })
+    }
+
+    /// Model for requests that name none.
+    pub fn default_model(&self) -> &str {
+        &self.model
+    }
+
+    pub fn screens_prompts(&self) -> bool {
+        self.screen_prompts
+    }
+
+    fn classify_locally(&self, input: &ModerationInput, id: &str) -> Option<ModerationResponse> {
+        let classifier = self.local.as_ref()?;
+        Some(ModerationResponse {
+            id: format!("modr-{}", id),
+            model: LOCAL_MODEL.to_string(),
+            results: input
+                .texts()
+                .into_iter()
+                .map(|text| classifier.classify(text))
+                .collect(),
+        })
+    }
+}
+
+impl Default for Moderator {
+    fn default() -> Self {
+        Moderator {
+            model: DEFAULT_MODEL.to_string(),
+            screen_prompts: false,
+            local: None,
+        }
+    }
+}
+
+impl LocalClassifier {
+    fn classify(&self, text: &str) -> ModerationResult {
+        let mut result = ModerationResult {
+            category_scores: BTreeMap::new(),
+            ..Default::default()
+        };
+        for (category, patterns) in &self.categories {
+            let flagged = patterns.iter().any(|pattern| pattern.is_match(text));
+            result.record(category, flagged, if flagged { 1.0 } else { 0.0 });
+        }
+        result
+    }
+}
+
+/// Moderate `request` with the model it names, or the configured default.
+/// `headers` are sent on to the gateway, e.g. for the request id and trace
+/// context.
+pub async fn moderate(
+    state: &AppState,
+    mut request: ModerationRequest,
+    mut headers: HeaderMap,
+    tenant: Option<&TenantConfig>,
+    request_id: &str,
+) -> Result<ModerationResponse, BrightStaffError> {
+    let model = request
+        .model
+        .clone()
+        .unwrap_or_else(|| state.moderator.default_model().to_string());
+    if model == LOCAL_MODEL {
+        return state
+            .moderator
+            .classify_locally(&request.input, request_id)
+            .ok_or_else(|| {
+                BrightStaffError::InvalidRequest(
+                    "moderation.local_categories is not configured".to_string(),
+                )
+            });
+    }
+
+    let resolved_model = resolve_model_alias(&model, &state.model_aliases)?;
+    let Some(provider) = state.llm_providers.read().await.get(&resolved_model) else {
+        return Err(BrightStaffError::ModelNotFound(resolved_model));
+    };
+    if let Some(tenant) = tenant {
+        if !tenant.allows_model(&provider.name) {
+            return Err(BrightStaffError::ModelNotAllowedForTenant {
+                model: provider.name.clone(),
+                tenant: tenant.id.clone(),
+            });
+        }
+    }
+    request.model = Some(
+        resolved_model
+            .split_once('/')
+            .map(|(_, model)| model.to_string())
+            .unwrap_or_else(|| resolved_model.clone()),
+    );
+
+    state.access_key_slots.apply(&provider.name, &mut headers);
+    state.vertex_tokens.apply(&provider.name, &mut headers);
+    state
+        .bedrock_credentials
+        .apply(&provider.name, &mut headers);
+    if let Ok(value) = HeaderValue::from_str(&resolved_model) {
+        headers.insert(ARCH_PROVIDER_HINT_HEADER, value);
+    }
+    headers.insert(ARCH_IS_STREAMING_HEADER, HeaderValue::from_static("false"));
+    headers.remove(header::CONTENT_LENGTH);
+
+    debug!(provider_hint = %resolved_model, "moderating input");
+    let body = serde_json::to_vec(&request).map_err(|e| {
+        BrightStaffError::InternalServerError(format!(
+            "failed to serialize moderation request: {e}"
+        ))
+    })?;
+    let response = state
+        .http_client
+        .post(format!("{}{}", state.llm_provider_url, MODERATIONS_PATH))
+        .headers(headers)
+        .body(body)
+        .send()
+        .await
+        .map_err(|e| {
+            BrightStaffError::InternalServerError(format!("failed to send moderation request: {e}"))
+        })?;
+    let status = response.status();
+    let body = response.bytes().await.map_err(|e| {
+        BrightStaffError::InternalServerError(format!("failed to read moderation response: {e}"))
+    })?;
+    if !status.is_success() {
+        return Err(BrightStaffError::ForwardedError {
+            status_code: status,
+            message: String::from_utf8_lossy(&body).into_owned(),
+        });
+    }
+    serde_json::from_slice(&body).map_err(|e| {
+        BrightStaffError::InternalServerError(format!("invalid moderation response: {e}"))
+    })
+}
+
+/// Pipeline stage: refuse `prompt` when the configured moderation model
+/// flags it. Does nothing unless `moderation.screen_prompts` is set.
+pub async fn screen(
+    state: &AppState,
+    prompt: String,
+    headers: &HeaderMap,
+    request_id: &str,
+) -> Result<(), BrightStaffError> {
+    if !state.moderator.screens_prompts() || prompt.trim().is_empty() {
+        return Ok(());
+    }
+    let request = ModerationRequest {
+        input: ModerationInput::Text(prompt),
+        model: None,
+    };
+    let mut forwarded = HeaderMap::new();
+    for name in [REQUEST_ID_HEADER, TRACE_PARENT_HEADER] {
+        if let Some(value) = headers.get(name) {
+            forwarded.insert(name, value.clone());
+        }
+    }
+    match moderate(state, request, forwarded, None, request_id).await {
+        Ok(response) => {
+            let categories: Vec<String> = response
+                .results
+                .iter()
+                .flat_map(ModerationResult::flagged_categories)
+                .collect();
+            if response.results.iter().any(|result| result.flagged) {
+                warn!(categories = ?categories, "refused flagged prompt");
+                return Err(BrightStaffError::ContentFlagged { categories });
+            }
+            Ok(())
+        }
+        Err(error) => {
+            warn!(%error, "prompt moderation failed, forwarding unscreened");
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_local_classifier() {
+        let moderator = Moderator::new(&ModerationSettings {
+            local_categories: Some(HashMap::from([
+                ("violence".to_string(), vec![r"\bkill\b".to_string()]),
+                (
+                    "self-harm".to_string(),
+                    vec![r"\bhurt myself\b".to_string()],
+                ),
+            ])),
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(moderator.default_model(), LOCAL_MODEL);
+
+        let input = ModerationInput::Texts(vec![
+            "How do I KILL a stuck process?".to_string(),
+            "What's the weather?".to_string(),
+        ]);
+        let response = moderator.classify_locally(&input, "1").unwrap();
+        assert_eq!(response.id, "modr-1");
+        assert_eq!(response.results.len(), 2);
+        assert!(response.results[0].flagged);
+        assert_eq!(response.results[0].flagged_categories(), vec!["violence"]);
+        assert_eq!(response.results[0].category_scores["self-harm"], 0.0);
+        assert!(!response.results[1].flagged);
+
+        assert!(Moderator::default().classify_locally(&input, "2").is_none());
+    }
+}
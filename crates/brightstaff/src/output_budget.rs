@@ -0,0 +1,202 @@
+//! Output token budgets for routes and tenants.
+//!
+//! The request's own limit (`max_tokens`, `max_completion_tokens` or
+//! `max_output_tokens`) is clamped to the budget, or the request rejected,
+//! before it goes upstream; a request without a limit gets the budget. As a
+//! backstop against providers that overrun the limit, streamed responses are
+//! cut off once they carry more events than the budget allows.
+
+use bytes::Bytes;
+use common::configuration::{BudgetExceededAction, OutputTokenBudget};
+use common::errors::BrightStaffError;
+use serde_json::Value;
+use tracing::{debug, warn};
+
+use crate::streaming::StreamProcessor;
+
+/// Output limit fields across the client APIs.
+const LIMIT_FIELDS: [&str; 3] = ["max_tokens", "max_completion_tokens", "max_output_tokens"];
+/// Events that carry no tokens (role, finish, usage, lifecycle events) allowed
+/// on top of the budget before a stream is cut off.
+const STREAM_EVENT_SLACK: u64 = 32;
+
+/// Apply `budgets` to a serialized client request. Returns the rewritten body,
+/// `None` when it is unchanged, or the rejection for a `reject` budget.
+pub fn enforce(
+    body: &[u8],
+    budgets: &[&OutputTokenBudget],
+    is_responses_api: bool,
+) -> Result<Option<Bytes>, BrightStaffError> {
+    let Some(limit) = budgets.iter().map(|b| b.max_tokens).min() else {
+        return Ok(None);
+    };
+    let Ok(mut request) = serde_json::from_slice::<Value>(body) else {
+        return Ok(None);
+    };
+    let Some(fields) = request.as_object_mut() else {
+        return Ok(None);
+    };
+
+    let requested = LIMIT_FIELDS
+        .iter()
+        .filter_map(|field| fields.get(*field).and_then(Value::as_u64))
+        .max();
+    match requested {
+        Some(requested) if requested <= u64::from(limit) => return Ok(None),
+        Some(requested) => {
+            if let Some(budget) = budgets.iter().find(|b| {
+                b.on_exceed == BudgetExceededAction::Reject && requested > u64::from(b.max_tokens)
+            }) {
+                return Err(BrightStaffError::OutputTokenBudgetExceeded {
+                    requested,
+                    limit: budget.max_tokens,
+                });
+            }
+            debug!(requested, limit, "clamping output tokens to budget");
+            for field in LIMIT_FIELDS {
+                if fields.get(field).is_some_and(Value::is_number) {
+                    fields.insert(field.to_string(), Value::from(limit));
+                }
+            }
+        }
+        None => {
+            let field = if is_responses_api {
+                "max_output_tokens"
+            } else {
+                "max_tokens"
+            };
+            fields.insert(field.to_string(), Value::from(limit));
+        }
+    }
+    Ok(serde_json::to_vec(&request).ok().map(Bytes::from))
+}
+
+/// Cuts a streamed response off once it runs past its output budget.
+///
+/// Tokens are not counted exactly: every SSE data event carries at most a
+/// few tokens and usually at least one, so the number of events is a cheap
+/// bound that only trips on generations well past the budget.
+pub struct OutputBudgetProcessor<P: StreamProcessor> {
+    inner: P,
+    max_events: u64,
+    events: u64,
+}
+
+impl<P: StreamProcessor> OutputBudgetProcessor<P> {
+    pub fn new(inner: P, max_tokens: u32) -> Self {
+        Self {
+            inner,
+            max_events: u64::from(max_tokens) + STREAM_EVENT_SLACK,
+            events: 0,
+        }
+    }
+}
+
+impl<P: StreamProcessor> StreamProcessor for OutputBudgetProcessor<P> {
+    fn process_chunk(&mut self, chunk: Bytes) -> Result<Option<Bytes>, String> {
+        self.events += chunk
+            .split(|b| *b == b'\n')
+            .filter(|line| line.starts_with(b"data:") && !line.ends_with(b"[DONE]"))
+            .count() as u64;
+        if self.events > self.max_events {
+            warn!(
+                events = self.events,
+                max_events = self.max_events,
+                "streamed output exceeded token budget, cutting off response"
+            );
+            return Err("output token budget exceeded".to_string());
+        }
+        self.inner.process_chunk(chunk)
+    }
+
+    fn on_first_bytes(&mut self) {
+        self.inner.on_first_bytes()
+    }
+
+    fn on_complete(&mut self) {
+        self.inner.on_complete()
+    }
+
+    fn on_error(&mut self, error: &str) {
+        self.inner.on_error(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn budget(max_tokens: u32, on_exceed: BudgetExceededAction) -> OutputTokenBudget {
+        OutputTokenBudget {
+            max_tokens,
+            on_exceed,
+        }
+    }
+
+    fn apply(body: Value, budgets: &[&OutputTokenBudget], responses: bool) -> Option<Value> {
+        enforce(body.to_string().as_bytes(), budgets, responses)
+            .unwrap()
+            .map(|bytes| serde_json::from_slice(&bytes).unwrap())
+    }
+
+    #[test]
+    fn clamps_and_fills_limits() {
+        let route = budget(1024, BudgetExceededAction::Clamp);
+        let tenant = budget(512, BudgetExceededAction::Clamp);
+
+        let out = apply(json!({"max_tokens": 4096}), &[&route, &tenant], false).unwrap();
+        assert_eq!(out["max_tokens"], 512);
+
+        assert!(apply(json!({"max_tokens": 256}), &[&route], false).is_none());
+
+        let out = apply(json!({"model": "gpt-4o"}), &[&route], false).unwrap();
+        assert_eq!(out["max_tokens"], 1024);
+        let out = apply(json!({"model": "gpt-4o"}), &[&route], true).unwrap();
+        assert_eq!(out["max_output_tokens"], 1024);
+
+        assert!(apply(json!({"max_tokens": 4096}), &[], false).is_none());
+    }
+
+    #[test]
+    fn reject_budget_fails_oversized_requests() {
+        let route = budget(1024, BudgetExceededAction::Reject);
+        let err = enforce(
+            json!({"max_completion_tokens": 2048})
+                .to_string()
+                .as_bytes(),
+            &[&route],
+            false,
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            BrightStaffError::OutputTokenBudgetExceeded {
+                requested: 2048,
+                limit: 1024
+            }
+        ));
+    }
+
+    struct Passthrough;
+
+    impl StreamProcessor for Passthrough {
+        fn process_chunk(&mut self, chunk: Bytes) -> Result<Option<Bytes>, String> {
+            Ok(Some(chunk))
+        }
+    }
+
+    #[test]
+    fn cuts_off_runaway_streams() {
+        let mut processor = OutputBudgetProcessor::new(Passthrough, 8);
+        let event =
+            Bytes::from_static(b"data: {\"choices\":[{\"delta\":{\"content\":\"hi\"}}]}\n\n");
+        for _ in 0..40 {
+            assert!(processor.process_chunk(event.clone()).is_ok());
+        }
+        assert!(processor
+            .process_chunk(Bytes::from_static(b"data: [DONE]\n\n"))
+            .is_ok());
+        assert!(processor.process_chunk(event).is_err());
+    }
+}
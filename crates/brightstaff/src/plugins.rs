@@ -0,0 +1,348 @@
+//! WASM plugins with request and response hooks.
+//!
+//! Plugins listed under `plugins` are compiled once at startup and run in a
+//! fresh, fuel- and memory-limited instance per call, so a hook cannot keep
+//! state across requests, stall the gateway or exhaust its memory. A module
+//! has no imports and exports:
+//!
+//! - `memory`
+//! - `alloc(len: i32) -> i32`, returning where the host writes the input
+//! - `on_request(ptr: i32, len: i32) -> i64` and/or `on_response(ptr, len)`
+//!
+//! A hook receives the request JSON (after routing, so the model is already
+//! chosen) or the complete non-streamed response JSON, and returns
+//! `(ptr << 32) | len` of the rewritten JSON in its memory, or 0 to leave it
+//! unchanged. A hook that traps, runs out of fuel, points outside its memory
+//! or returns invalid JSON is logged and skipped.
+
+use std::sync::Arc;
+
+use bytes::Bytes;
+use common::configuration::PluginConfig;
+use serde::de::IgnoredAny;
+use tracing::{debug, warn};
+use wasmi::{Config, Engine, ExternType, Linker, Module, Store, StoreLimits, StoreLimitsBuilder};
+
+/// Instructions a hook call may execute unless the plugin sets `max_fuel`.
+const DEFAULT_MAX_FUEL: u64 = 100_000_000;
+
+/// Size a plugin instance's linear memory may grow to.
+const MAX_MEMORY_BYTES: usize = 64 << 20;
+
+/// Largest body a hook may return.
+const MAX_OUTPUT_BYTES: usize = 16 << 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hook {
+    Request,
+    Response,
+}
+
+impl Hook {
+    fn export(self) -> &'static str {
+        match self {
+            Hook::Request => "on_request",
+            Hook::Response => "on_response",
+        }
+    }
+}
+
+struct Plugin {
+    name: String,
+    module: Module,
+    max_fuel: u64,
+    hooks: Vec<Hook>,
+}
+
+#[derive(Default)]
+pub struct PluginHost {
+    engine: Engine,
+    plugins: Vec<Plugin>,
+}
+
+impl PluginHost {
+    /// Read and compile the configured plugins, failing on the first module
+    /// that is missing or does not follow the plugin ABI.
+    pub fn load(configs: &[PluginConfig]) -> Result<Self, String> {
+        let mut host = Self::new();
+        for config in configs {
+            let wasm = std::fs::read(&config.path).map_err(|e| {
+                format!(
+                    "plugin '{}': failed to read {}: {e}",
+                    config.name, config.path
+                )
+            })?;
+            host.add(
+                &config.name,
+                &wasm,
+                config.max_fuel.unwrap_or(DEFAULT_MAX_FUEL),
+            )?;
+        }
+        Ok(host)
+    }
+
+    fn new() -> Self {
+        let mut config = Config::default();
+        config.consume_fuel(true);
+        Self {
+            engine: Engine::new(&config),
+            plugins: Vec::new(),
+        }
+    }
+
+    fn add(&mut self, name: &str, wasm: &[u8], max_fuel: u64) -> Result<(), String> {
+        let module = Module::new(&self.engine, wasm)
+            .map_err(|e| format!("plugin '{name}': invalid module: {e}"))?;
+        if !matches!(module.get_export("memory"), Some(ExternType::Memory(_))) {
+            return Err(format!("plugin '{name}': module does not export `memory`"));
+        }
+        if !matches!(module.get_export("alloc"), Some(ExternType::Func(_))) {
+            return Err(format!("plugin '{name}': module does not export `alloc`"));
+        }
+        let hooks: Vec<Hook> = [Hook::Request, Hook::Response]
+            .into_iter()
+            .filter(|hook| matches!(module.get_export(hook.export()), Some(ExternType::Func(_))))
+            .collect();
+        if hooks.is_empty() {
+            return Err(format!(
+                "plugin '{name}': module exports neither `on_request` nor `on_response`"
+            ));
+        }
+        debug!(plugin = %name, hooks = ?hooks, "loaded plugin");
+        self.plugins.push(Plugin {
+            name: name.to_string(),
+            module,
+            max_fuel,
+            hooks,
+        });
+        Ok(())
+    }
+
+    pub fn has_hooks(&self, hook: Hook) -> bool {
+        self.plugins.iter().any(|p| p.hooks.contains(&hook))
+    }
+
+    /// `body` passed through every plugin's `hook` in order, off the async
+    /// runtime since hooks are CPU-bound.
+    pub async fn apply(self: Arc<Self>, hook: Hook, body: Bytes) -> Bytes {
+        let unchanged = body.clone();
+        tokio::task::spawn_blocking(move || self.run(hook, body))
+            .await
+            .unwrap_or(unchanged)
+    }
+
+    fn run(&self, hook: Hook, body: Bytes) -> Bytes {
+        if serde_json::from_slice::<IgnoredAny>(&body).is_err() {
+            debug!(hook = hook.export(), "body is not JSON, skipping plugins");
+            return body;
+        }
+        self.plugins
+            .iter()
+            .filter(|p| p.hooks.contains(&hook))
+            .fold(body, |body, plugin| {
+                match plugin.call(&self.engine, hook, &body) {
+                    Ok(Some(rewritten)) => Bytes::from(rewritten),
+                    Ok(None) => body,
+                    Err(error) => {
+                        warn!(
+                            plugin = %plugin.name,
+                            hook = hook.export(),
+                            error = %error,
+                            "plugin hook failed, leaving body unchanged"
+                        );
+                        body
+                    }
+                }
+            })
+    }
+}
+
+impl Plugin {
+    fn call(&self, engine: &Engine, hook: Hook, input: &[u8]) -> Result<Option<Vec<u8>>, String> {
+        let limits = StoreLimitsBuilder::new()
+            .memory_size(MAX_MEMORY_BYTES)
+            .build();
+        let mut store = Store::new(engine, limits);
+        store.limiter(|limits| limits);
+        store.set_fuel(self.max_fuel).map_err(|e| e.to_string())?;
+        let instance = Linker::<StoreLimits>::new(engine)
+            .instantiate(&mut store, &self.module)
+            .and_then(|pre| pre.start(&mut store))
+            .map_err(|e| format!("instantiation failed: {e}"))?;
+        let memory = instance
+            .get_memory(&store, "memory")
+            .ok_or("module does not export `memory`")?;
+        let alloc = instance
+            .get_typed_func::<i32, i32>(&store, "alloc")
+            .map_err(|e| e.to_string())?;
+        let hook_fn = instance
+            .get_typed_func::<(i32, i32), i64>(&store, hook.export())
+            .map_err(|e| e.to_string())?;
+
+        let len = i32::try_from(input.len()).map_err(|_| "input too large")?;
+        let ptr = alloc.call(&mut store, len).map_err(|e| e.to_string())?;
+        memory
+            .write(&mut store, ptr as u32 as usize, input)
+            .map_err(|e| format!("writing input: {e}"))?;
+        let location = hook_fn
+            .call(&mut store, (ptr, len))
+            .map_err(|e| e.to_string())? as u64;
+        if location == 0 {
+            return Ok(None);
+        }
+
+        let offset = (location >> 32) as usize;
+        let len = (location & 0xffff_ffff) as usize;
+        if len > MAX_OUTPUT_BYTES {
+            return Err(format!(
+                "hook returned {len} bytes, more than the {MAX_OUTPUT_BYTES} allowed"
+            ));
+        }
+        // check the range against the memory before copying anything out of it
+        let output = offset
+            .checked_add(len)
+            .and_then(|end| memory.data(&store).get(offset..end))
+            .ok_or_else(|| format!("hook output {offset}+{len} is outside its memory"))?;
+        serde_json::from_slice::<IgnoredAny>(output)
+            .map_err(|e| format!("hook returned invalid JSON: {e}"))?;
+        Ok(Some(output.to_vec()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Rewrites every request to a fixed body and echoes responses back.
+    const REWRITE_PLUGIN: &str = r#"
+        (module
+          (memory (export "memory") 1)
+          (data (i32.const 0) "{\"model\":\"gpt-4o-mini\"}")
+          (func (export "alloc") (param i32) (result i32) (i32.const 1024))
+          (func (export "on_request") (param i32 i32) (result i64) (i64.const 23))
+          (func (export "on_response") (param $ptr i32) (param $len i32) (result i64)
+            (i64.or
+              (i64.shl (i64.extend_i32_u (local.get $ptr)) (i64.const 32))
+              (i64.extend_i32_u (local.get $len)))))
+    "#;
+
+    const SPINNING_PLUGIN: &str = r#"
+        (module
+          (memory (export "memory") 1)
+          (func (export "alloc") (param i32) (result i32) (i32.const 0))
+          (func (export "on_request") (param i32 i32) (result i64)
+            (loop $forever (br $forever))
+            (i64.const 0)))
+    "#;
+
+    fn host_with(wat: &str) -> PluginHost {
+        host(&[("plugin", wat)])
+    }
+
+    fn host(plugins: &[(&str, &str)]) -> PluginHost {
+        let mut host = PluginHost::new();
+        for (name, wat) in plugins {
+            host.add(name, &wat::parse_str(wat).unwrap(), 1_000_000)
+                .unwrap();
+        }
+        host
+    }
+
+    #[test]
+    fn hooks_rewrite_or_pass_through_bodies() {
+        let host = host(&[("rewrite", REWRITE_PLUGIN)]);
+        assert!(host.has_hooks(Hook::Request) && host.has_hooks(Hook::Response));
+
+        let request = Bytes::from_static(br#"{"model":"gpt-4o","messages":[]}"#);
+        assert_eq!(
+            host.run(Hook::Request, request.clone()),
+            Bytes::from_static(br#"{"model":"gpt-4o-mini"}"#)
+        );
+        assert_eq!(host.run(Hook::Response, request.clone()), request);
+
+        let not_json = Bytes::from_static(b"data: [DONE]");
+        assert_eq!(host.run(Hook::Request, not_json.clone()), not_json);
+    }
+
+    #[test]
+    fn runaway_hooks_are_stopped_and_skipped() {
+        let host = host(&[("spin", SPINNING_PLUGIN), ("rewrite", REWRITE_PLUGIN)]);
+        let request = Bytes::from_static(br#"{"model":"gpt-4o"}"#);
+        assert_eq!(
+            host.run(Hook::Request, request),
+            Bytes::from_static(br#"{"model":"gpt-4o-mini"}"#)
+        );
+        assert!(!PluginHost::default().has_hooks(Hook::Response));
+    }
+
+    /// Returns `(offset << 32) | len` taken from its `on_request` parameters,
+    /// so a test can point the output anywhere.
+    const POINTER_PLUGIN: &str = r#"
+        (module
+          (memory (export "memory") 1)
+          (func (export "alloc") (param i32) (result i32) (i32.const 0))
+          (func (export "on_request") (param $ptr i32) (param $len i32) (result i64)
+            (i64.load (i32.const 0))))
+    "#;
+
+    #[test]
+    fn hook_output_and_memory_are_bounded() {
+        let host = host(&[("pointer", POINTER_PLUGIN)]);
+        let plugin = &host.plugins[0];
+        // the module reads its return value from the first 8 input bytes
+        let call = |location: u64| {
+            let mut input = location.to_le_bytes().to_vec();
+            input.extend_from_slice(b"{}");
+            plugin.call(&host.engine, Hook::Request, &input)
+        };
+        let err = call(0xffff_ffff).unwrap_err();
+        assert!(err.contains("more than the"), "{err}");
+        let err = call((65_000 << 32) | 1_000).unwrap_err();
+        assert!(err.contains("outside its memory"), "{err}");
+        let err = call((u64::MAX << 32) | 1).unwrap_err();
+        assert!(err.contains("outside its memory"), "{err}");
+
+        let greedy = host_with(
+            r#"(module
+                 (memory (export "memory") 2000)
+                 (func (export "alloc") (param i32) (result i32) (i32.const 0))
+                 (func (export "on_request") (param i32 i32) (result i64) (i64.const 0)))"#,
+        );
+        let err = greedy.plugins[0]
+            .call(&greedy.engine, Hook::Request, b"{}")
+            .unwrap_err();
+        assert!(err.contains("instantiation failed"), "{err}");
+
+        let growing = host_with(
+            r#"(module
+                 (memory (export "memory") 1)
+                 (func (export "alloc") (param i32) (result i32) (i32.const 0))
+                 (func (export "on_request") (param i32 i32) (result i64)
+                   (i64.extend_i32_s (memory.grow (i32.const 2000)))))"#,
+        );
+        // a refused memory.grow returns -1, read back as an oversized length
+        let err = growing.plugins[0]
+            .call(&growing.engine, Hook::Request, b"{}")
+            .unwrap_err();
+        assert!(err.contains("more than the"), "{err}");
+    }
+
+    #[test]
+    fn rejects_modules_without_the_plugin_abi() {
+        let mut host = PluginHost::new();
+        let no_alloc = wat::parse_str(
+            r#"(module (memory (export "memory") 1)
+                 (func (export "on_request") (param i32 i32) (result i64) (i64.const 0)))"#,
+        )
+        .unwrap();
+        let err = host.add("broken", &no_alloc, 1_000).unwrap_err();
+        assert!(err.contains("does not export `alloc`"), "{err}");
+        assert!(host.add("garbage", b"not wasm", 1_000).is_err());
+        assert!(PluginHost::load(&[PluginConfig {
+            name: "missing".to_string(),
+            path: "/nonexistent/plugin.wasm".to_string(),
+            max_fuel: None,
+        }])
+        .is_err());
+    }
+}
@@ -0,0 +1,327 @@
+//! Prompt compression for long-context routes.
+//!
+//! Routes with `prompt_compression` have their long tool results and older
+//! conversation turns pruned before the request goes upstream. Each segment
+//! is split into sentences, and the sentences whose words recur least within
+//! the segment are dropped until `keep_ratio` of it is left. System prompts,
+//! the latest user message and JSON tool output are never touched.
+
+use std::collections::HashMap;
+
+use bytes::Bytes;
+use common::configuration::{PromptCompressionSettings, PromptSegment};
+use hermesllm::clients::SupportedAPIsFromClient;
+use serde_json::Value;
+use tracing::debug;
+
+const DEFAULT_MIN_CHARS: usize = 4000;
+const DEFAULT_KEEP_RATIO: f64 = 0.5;
+/// Words this short carry too little meaning to score a sentence by.
+const MIN_WORD_CHARS: usize = 3;
+const STOPWORDS: &[&str] = &[
+    "the", "and", "for", "are", "but", "not", "you", "all", "any", "can", "had", "her", "was",
+    "one", "our", "out", "has", "have", "his", "how", "its", "may", "new", "now", "see", "that",
+    "this", "with", "from", "they", "will", "would", "there", "their", "what", "when", "which",
+    "were", "been", "into", "than", "then", "them", "these", "those", "also", "such", "some",
+];
+
+struct Compressor {
+    min_chars: usize,
+    keep_ratio: f64,
+    tool_results: bool,
+    history: bool,
+    saved_chars: usize,
+}
+
+impl Compressor {
+    fn new(settings: &PromptCompressionSettings) -> Self {
+        let segments = settings.segments.as_deref();
+        let enabled = |segment| segments.is_none_or(|s| s.contains(&segment));
+        Self {
+            min_chars: settings.min_chars.unwrap_or(DEFAULT_MIN_CHARS),
+            keep_ratio: settings.keep_ratio.unwrap_or(DEFAULT_KEEP_RATIO),
+            tool_results: enabled(PromptSegment::ToolResults),
+            history: enabled(PromptSegment::History),
+            saved_chars: 0,
+        }
+    }
+
+    fn wants(&self, segment: PromptSegment) -> bool {
+        match segment {
+            PromptSegment::ToolResults => self.tool_results,
+            PromptSegment::History => self.history,
+        }
+    }
+
+    /// Compress a string, or the text parts of a content array, in place.
+    fn compress_content(&mut self, content: &mut Value, segment: PromptSegment) {
+        if !self.wants(segment) {
+            return;
+        }
+        match content {
+            Value::String(text) => self.compress_text(text),
+            Value::Array(parts) => {
+                for part in parts {
+                    match part.get_mut("text") {
+                        Some(Value::String(text)) => self.compress_text(text),
+                        // Anthropic tool results nest their own content blocks.
+                        _ => {
+                            if let Some(nested) = part.get_mut("content") {
+                                self.compress_content(nested, segment);
+                            }
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn compress_text(&mut self, text: &mut String) {
+        if text.len() < self.min_chars || is_json(text) {
+            return;
+        }
+        if let Some(compressed) = prune_sentences(text, self.keep_ratio) {
+            self.saved_chars += text.len() - compressed.len();
+            *text = compressed;
+        }
+    }
+}
+
+/// Compress the segments of a serialized client request. Returns the
+/// rewritten body, or `None` when nothing was long enough to compress.
+pub fn apply(
+    body: &[u8],
+    client_api: &SupportedAPIsFromClient,
+    settings: &PromptCompressionSettings,
+) -> Option<Bytes> {
+    let mut request = serde_json::from_slice::<Value>(body).ok()?;
+    let mut compressor = Compressor::new(settings);
+    let items = match client_api {
+        SupportedAPIsFromClient::OpenAIResponsesAPI(_) => request.get_mut("input")?,
+        _ => request.get_mut("messages")?,
+    };
+    let items = items.as_array_mut()?;
+    let latest_user = items.iter().rposition(|item| item["role"] == "user");
+
+    for (index, item) in items.iter_mut().enumerate() {
+        let is_history = latest_user.is_some_and(|latest| index < latest);
+        match client_api {
+            SupportedAPIsFromClient::OpenAIChatCompletions(_) => {
+                let segment = match item["role"].as_str() {
+                    Some("tool") => PromptSegment::ToolResults,
+                    Some("user" | "assistant") if is_history => PromptSegment::History,
+                    _ => continue,
+                };
+                if let Some(content) = item.get_mut("content") {
+                    compressor.compress_content(content, segment);
+                }
+            }
+            SupportedAPIsFromClient::AnthropicMessagesAPI(_) => match item.get_mut("content") {
+                Some(Value::Array(blocks)) => {
+                    for block in blocks {
+                        if block["type"] == "tool_result" {
+                            if let Some(content) = block.get_mut("content") {
+                                compressor.compress_content(content, PromptSegment::ToolResults);
+                            }
+                        } else if is_history && block["type"] == "text" {
+                            if let Some(Value::String(text)) = block.get_mut("text") {
+                                if compressor.wants(PromptSegment::History) {
+                                    compressor.compress_text(text);
+                                }
+                            }
+                        }
+                    }
+                }
+                Some(content) if is_history => {
+                    compressor.compress_content(content, PromptSegment::History)
+                }
+                _ => {}
+            },
+            SupportedAPIsFromClient::OpenAIResponsesAPI(_) => {
+                let (field, segment) = match item["type"].as_str() {
+                    Some("function_call_output") => ("output", PromptSegment::ToolResults),
+                    Some("message") | None
+                        if is_history
+                            && item["role"] != "system"
+                            && item["role"] != "developer" =>
+                    {
+                        ("content", PromptSegment::History)
+                    }
+                    _ => continue,
+                };
+                if let Some(content) = item.get_mut(field) {
+                    compressor.compress_content(content, segment);
+                }
+            }
+        }
+    }
+
+    if compressor.saved_chars == 0 {
+        return None;
+    }
+    debug!(
+        saved_chars = compressor.saved_chars,
+        "compressed long prompt segments"
+    );
+    serde_json::to_vec(&request).ok().map(Bytes::from)
+}
+
+fn is_json(text: &str) -> bool {
+    let trimmed = text.trim_start();
+    (trimmed.starts_with('{') || trimmed.starts_with('['))
+        && serde_json::from_str::<Value>(text).is_ok()
+}
+
+/// Sentences of `text`, each with the whitespace that follows it, so that
+/// concatenating them gives back `text`.
+fn sentences(text: &str) -> Vec<&str> {
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+    while let Some((index, c)) = chars.next() {
+        let ends_sentence = c == '\n'
+            || (matches!(c, '.' | '!' | '?')
+                && chars.peek().is_some_and(|(_, next)| next.is_whitespace()));
+        if !ends_sentence {
+            continue;
+        }
+        let mut end = index + c.len_utf8();
+        while let Some(&(next_index, next)) = chars.peek() {
+            if !next.is_whitespace() {
+                break;
+            }
+            end = next_index + next.len_utf8();
+            chars.next();
+        }
+        sentences.push(&text[start..end]);
+        start = end;
+    }
+    if start < text.len() {
+        sentences.push(&text[start..]);
+    }
+    sentences
+}
+
+fn words(sentence: &str) -> impl Iterator<Item = String> + '_ {
+    sentence
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| word.chars().count() >= MIN_WORD_CHARS)
+        .map(str::to_lowercase)
+        .filter(|word| !STOPWORDS.contains(&word.as_str()))
+}
+
+/// `text` with its least important sentences dropped until about
+/// `keep_ratio` of it is left, or `None` if nothing could be dropped.
+fn prune_sentences(text: &str, keep_ratio: f64) -> Option<String> {
+    let sentences = sentences(text);
+    if sentences.len() < 2 {
+        return None;
+    }
+    let mut frequency: HashMap<String, usize> = HashMap::new();
+    for word in sentences.iter().flat_map(|s| words(s)) {
+        *frequency.entry(word).or_default() += 1;
+    }
+    // A sentence matters as much as its words recur across the segment.
+    let scores: Vec<f64> = sentences
+        .iter()
+        .map(|sentence| {
+            let counts: Vec<usize> = words(sentence).map(|w| frequency[&w]).collect();
+            if counts.is_empty() {
+                0.0
+            } else {
+                counts.iter().sum::<usize>() as f64 / counts.len() as f64
+            }
+        })
+        .collect();
+
+    let mut ranked: Vec<usize> = (0..sentences.len()).collect();
+    ranked.sort_by(|a, b| scores[*b].total_cmp(&scores[*a]).then(a.cmp(b)));
+    let budget = (text.len() as f64 * keep_ratio) as usize;
+    let mut keep = vec![false; sentences.len()];
+    let mut kept_chars = 0;
+    for index in ranked {
+        if kept_chars >= budget {
+            break;
+        }
+        keep[index] = true;
+        kept_chars += sentences[index].len();
+    }
+    if keep.iter().all(|k| *k) {
+        return None;
+    }
+    Some(
+        sentences
+            .iter()
+            .zip(keep)
+            .filter_map(|(sentence, keep)| keep.then_some(*sentence))
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hermesllm::apis::OpenAIApi;
+    use serde_json::json;
+
+    const RETRIEVED: &str = "Plano routes requests to model providers. \
+        The weather was pleasant that day. \
+        Plano providers are configured in the plano config. \
+        Lunch was served at noon. \
+        Routing in Plano picks providers by preference.";
+
+    fn settings(segments: Option<Vec<PromptSegment>>) -> PromptCompressionSettings {
+        PromptCompressionSettings {
+            min_chars: Some(100),
+            keep_ratio: Some(0.6),
+            segments,
+        }
+    }
+
+    #[test]
+    fn drops_the_least_related_sentences_in_order() {
+        let pruned = prune_sentences(RETRIEVED, 0.6).unwrap();
+        assert!(!pruned.contains("weather"));
+        assert!(!pruned.contains("Lunch"));
+        assert!(pruned.starts_with("Plano routes requests"));
+        assert!(pruned.ends_with("by preference."));
+        assert_eq!(sentences(RETRIEVED).concat(), RETRIEVED);
+    }
+
+    #[test]
+    fn compresses_tool_results_and_history_but_not_the_latest_turn() {
+        let body = json!({
+            "model": "gpt-4o",
+            "messages": [
+                {"role": "system", "content": RETRIEVED},
+                {"role": "user", "content": RETRIEVED},
+                {"role": "tool", "tool_call_id": "call_1", "content": RETRIEVED},
+                {"role": "tool", "tool_call_id": "call_2", "content": json!({"docs": [RETRIEVED]}).to_string()},
+                {"role": "user", "content": RETRIEVED},
+            ]
+        });
+        let chat = SupportedAPIsFromClient::OpenAIChatCompletions(OpenAIApi::ChatCompletions);
+        let compressed = apply(body.to_string().as_bytes(), &chat, &settings(None)).unwrap();
+        let compressed: Value = serde_json::from_slice(&compressed).unwrap();
+        let messages = compressed["messages"].as_array().unwrap();
+        assert_eq!(messages[0]["content"], RETRIEVED);
+        assert_ne!(messages[1]["content"], RETRIEVED);
+        assert_ne!(messages[2]["content"], RETRIEVED);
+        assert_eq!(messages[3], body["messages"][3]);
+        assert_eq!(messages[4]["content"], RETRIEVED);
+
+        let only_tools = settings(Some(vec![PromptSegment::ToolResults]));
+        let compressed = apply(body.to_string().as_bytes(), &chat, &only_tools).unwrap();
+        let compressed: Value = serde_json::from_slice(&compressed).unwrap();
+        assert_eq!(compressed["messages"][1]["content"], RETRIEVED);
+        assert_ne!(compressed["messages"][2]["content"], RETRIEVED);
+    }
+
+    #[test]
+    fn short_requests_are_left_alone() {
+        let body = json!({"messages": [{"role": "tool", "content": "Short. Result."}]});
+        let chat = SupportedAPIsFromClient::OpenAIChatCompletions(OpenAIApi::ChatCompletions);
+        assert!(apply(body.to_string().as_bytes(), &chat, &settings(None)).is_none());
+    }
+}
@@ -0,0 +1,198 @@
+//! Upstream latency per provider, for latency-aware routing.
+//!
+//! Every answered request updates exponentially weighted moving averages of
+//! the provider's time to first token (streaming requests) and total latency.
+//! When a bare model name is served by several providers, the candidates are
+//! tried fastest first, with providers that keep failing moved to the back
+//! until they have had time to recover.
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+/// Weight of the newest sample in the moving averages.
+const EWMA_ALPHA: f64 = 0.2;
+/// Consecutive failures after which a provider is considered unhealthy.
+const UNHEALTHY_AFTER_FAILURES: u32 = 3;
+/// How long an unhealthy provider stays at the back of the order after its
+/// last failure.
+const UNHEALTHY_FOR: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Default)]
+pub struct ProviderHealth {
+    stats: RwLock<HashMap<String, LatencyStats>>,
+}
+
+#[derive(Debug, Default, Clone)]
+struct LatencyStats {
+    ttft_ms: Option<f64>,
+    total_ms: Option<f64>,
+    requests: u64,
+    consecutive_failures: u32,
+    last_failure: Option<Instant>,
+}
+
+impl LatencyStats {
+    fn healthy(&self) -> bool {
+        self.consecutive_failures < UNHEALTHY_AFTER_FAILURES
+            || self
+                .last_failure
+                .is_none_or(|at| at.elapsed() >= UNHEALTHY_FOR)
+    }
+
+    /// Average to rank by: time to first token for streams, else total latency
+    fn latency_ms(&self, streaming: bool) -> Option<f64> {
+        if streaming {
+            self.ttft_ms.or(self.total_ms)
+        } else {
+            self.total_ms
+        }
+    }
+}
+
+/// Latency averages of one provider, as shown by the admin API.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ProviderLatency {
+    pub provider: String,
+    pub ttft_ms: Option<f64>,
+    pub total_ms: Option<f64>,
+    pub requests: u64,
+    pub consecutive_failures: u32,
+    pub healthy: bool,
+}
+
+fn ewma(average: Option<f64>, sample: Duration) -> f64 {
+    let sample = sample.as_secs_f64() * 1000.0;
+    match average {
+        Some(average) => EWMA_ALPHA * sample + (1.0 - EWMA_ALPHA) * average,
+        None => sample,
+    }
+}
+
+impl ProviderHealth {
+    /// Record a completed response from `provider`.
+    pub fn record_success(&self, provider: &str, ttft: Option<Duration>, total: Duration) {
+        let mut stats = self.stats.write().unwrap();
+        let entry = stats.entry(provider.to_string()).or_default();
+        if let Some(ttft) = ttft {
+            entry.ttft_ms = Some(ewma(entry.ttft_ms, ttft));
+        }
+        entry.total_ms = Some(ewma(entry.total_ms, total));
+        entry.requests += 1;
+        entry.consecutive_failures = 0;
+    }
+
+    /// Record a server error or a broken response from `provider`.
+    pub fn record_failure(&self, provider: &str) {
+        let mut stats = self.stats.write().unwrap();
+        let entry = stats.entry(provider.to_string()).or_default();
+        entry.requests += 1;
+        entry.consecutive_failures += 1;
+        entry.last_failure = Some(Instant::now());
+    }
+
+    pub fn is_healthy(&self, provider: &str) -> bool {
+        self.stats
+            .read()
+            .unwrap()
+            .get(provider)
+            .is_none_or(LatencyStats::healthy)
+    }
+
+    /// Order `(model, provider)` candidates healthy first, then by average
+    /// latency. Providers without samples go first so they get measured; ties
+    /// keep their order.
+    pub fn order_fastest_first(&self, candidates: &mut [(String, String)], streaming: bool) {
+        let stats = self.stats.read().unwrap();
+        let key = |provider: &str| {
+            let stats = stats.get(provider);
+            let healthy = stats.is_none_or(LatencyStats::healthy);
+            let latency = stats.and_then(|s| s.latency_ms(streaming)).unwrap_or(0.0);
+            (!healthy, latency)
+        };
+        candidates.sort_by(|(_, a), (_, b)| {
+            let (a, b) = (key(a), key(b));
+            a.0.cmp(&b.0)
+                .then(a.1.partial_cmp(&b.1).unwrap_or(Ordering::Equal))
+        });
+    }
+
+    /// Latency averages of every provider seen so far, sorted by name.
+    pub fn snapshot(&self) -> Vec<ProviderLatency> {
+        let stats = self.stats.read().unwrap();
+        let mut providers: Vec<ProviderLatency> = stats
+            .iter()
+            .map(|(provider, stats)| ProviderLatency {
+                provider: provider.clone(),
+                ttft_ms: stats.ttft_ms,
+                total_ms: stats.total_ms,
+                requests: stats.requests,
+                consecutive_failures: stats.consecutive_failures,
+                healthy: stats.healthy(),
+            })
+            .collect();
+        providers.sort_by(|a, b| a.provider.cmp(&b.provider));
+        providers
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidates(providers: &[&str]) -> Vec<(String, String)> {
+        providers
+            .iter()
+            .map(|p| (p.to_string(), p.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn orders_by_moving_average_and_health() {
+        let health = ProviderHealth::default();
+        health.record_success(
+            "openai/gpt-4o",
+            Some(Duration::from_millis(900)),
+            Duration::from_secs(2),
+        );
+        health.record_success(
+            "azure/gpt-4o",
+            Some(Duration::from_millis(300)),
+            Duration::from_secs(3),
+        );
+        health.record_success(
+            "azure/gpt-4o",
+            Some(Duration::from_millis(800)),
+            Duration::from_secs(3),
+        );
+
+        let latency = health.snapshot();
+        assert_eq!(latency[0].provider, "azure/gpt-4o");
+        assert_eq!(latency[0].ttft_ms, Some(400.0));
+
+        let mut streamed = candidates(&["openai/gpt-4o", "azure/gpt-4o", "groq/gpt-4o"]);
+        health.order_fastest_first(&mut streamed, true);
+        assert_eq!(
+            streamed,
+            candidates(&["groq/gpt-4o", "azure/gpt-4o", "openai/gpt-4o"])
+        );
+
+        let mut complete = candidates(&["azure/gpt-4o", "openai/gpt-4o"]);
+        health.order_fastest_first(&mut complete, false);
+        assert_eq!(complete, candidates(&["openai/gpt-4o", "azure/gpt-4o"]));
+
+        for _ in 0..UNHEALTHY_AFTER_FAILURES {
+            health.record_failure("azure/gpt-4o");
+        }
+        assert!(!health.is_healthy("azure/gpt-4o"));
+        let mut streamed = candidates(&["azure/gpt-4o", "openai/gpt-4o"]);
+        health.order_fastest_first(&mut streamed, true);
+        assert_eq!(streamed, candidates(&["openai/gpt-4o", "azure/gpt-4o"]));
+
+        health.record_success("azure/gpt-4o", None, Duration::from_secs(1));
+        assert!(health.is_healthy("azure/gpt-4o"));
+    }
+}
@@ -0,0 +1,227 @@
+//! Exact-match cache of complete responses, enabled per route.
+//!
+//! A route's `response_cache` keeps successful non-streamed responses for
+//! `ttl`, keyed by tenant, model and the exact upstream request body. With
+//! `stale_while_revalidate`, an entry past its `ttl` is still served for that
+//! much longer while a single background request refreshes it, so clients
+//! never wait on the upstream for a cached question.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
+use std::hash::{Hash, Hasher};
+use std::num::NonZeroUsize;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use bytes::Bytes;
+use common::configuration::ResponseCacheSettings;
+use common::consts::ARCH_CACHE_HEADER;
+use common::utils::parse_duration_ms;
+use http_body_util::combinators::BoxBody;
+use hyper::header::{HeaderValue, CONTENT_TYPE};
+use hyper::{Response, StatusCode};
+use lru::LruCache;
+
+use crate::handlers::full;
+
+/// Entries kept across all routes; the least recently used are evicted first.
+pub const DEFAULT_MAX_ENTRIES: usize = 1024;
+
+/// How long a route's cached responses are fresh, and then served stale.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CachePolicy {
+    pub fresh_for: Duration,
+    pub stale_for: Duration,
+}
+
+impl CachePolicy {
+    /// `None` when the durations don't parse (rejected by config validation).
+    pub fn from_settings(settings: &ResponseCacheSettings) -> Option<Self> {
+        let fresh_for = Duration::from_millis(parse_duration_ms(&settings.ttl)?);
+        let stale_for = match &settings.stale_while_revalidate {
+            Some(window) => Duration::from_millis(parse_duration_ms(window)?),
+            None => Duration::ZERO,
+        };
+        Some(Self {
+            fresh_for,
+            stale_for,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CachedResponse {
+    status: StatusCode,
+    content_type: Option<HeaderValue>,
+    body: Bytes,
+}
+
+impl CachedResponse {
+    pub fn new(status: StatusCode, content_type: Option<HeaderValue>, body: Bytes) -> Self {
+        Self {
+            status,
+            content_type,
+            body,
+        }
+    }
+
+    /// The cached response, marked with how it was served (`hit` or `stale`).
+    pub fn into_response(
+        self,
+        cache_status: &'static str,
+    ) -> Response<BoxBody<Bytes, hyper::Error>> {
+        let mut response = Response::builder()
+            .status(self.status)
+            .header(ARCH_CACHE_HEADER, cache_status);
+        if let Some(content_type) = self.content_type {
+            response = response.header(CONTENT_TYPE, content_type);
+        }
+        response.body(full(self.body)).unwrap()
+    }
+}
+
+#[derive(Debug)]
+pub enum Lookup {
+    Fresh(CachedResponse),
+    /// Past its ttl but within the stale window; serve it and refresh.
+    Stale(CachedResponse),
+    Miss,
+}
+
+struct Entry {
+    response: CachedResponse,
+    stored_at: Instant,
+    policy: CachePolicy,
+}
+
+pub struct ResponseCache {
+    entries: Mutex<LruCache<u64, Entry>>,
+    /// Keys with a background refresh in flight.
+    refreshing: Mutex<HashSet<u64>>,
+}
+
+impl Default for ResponseCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_ENTRIES)
+    }
+}
+
+impl ResponseCache {
+    pub fn new(max_entries: usize) -> Self {
+        let capacity = NonZeroUsize::new(max_entries).unwrap_or(NonZeroUsize::MIN);
+        Self {
+            entries: Mutex::new(LruCache::new(capacity)),
+            refreshing: Mutex::new(HashSet::new()),
+        }
+    }
+
+    pub fn key(tenant: Option<&str>, model: &str, body: &[u8]) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        (tenant, model, body).hash(&mut hasher);
+        hasher.finish()
+    }
+
+    pub fn lookup(&self, key: u64) -> Lookup {
+        let mut entries = self.entries.lock().unwrap();
+        let Some(entry) = entries.get(&key) else {
+            return Lookup::Miss;
+        };
+        let age = entry.stored_at.elapsed();
+        if age < entry.policy.fresh_for {
+            Lookup::Fresh(entry.response.clone())
+        } else if age < entry.policy.fresh_for + entry.policy.stale_for {
+            Lookup::Stale(entry.response.clone())
+        } else {
+            entries.pop(&key);
+            Lookup::Miss
+        }
+    }
+
+    pub fn store(&self, key: u64, response: CachedResponse, policy: CachePolicy) {
+        self.entries.lock().unwrap().put(
+            key,
+            Entry {
+                response,
+                stored_at: Instant::now(),
+                policy,
+            },
+        );
+    }
+
+    /// Claim the background refresh of `key`; false when one is already running.
+    pub fn begin_refresh(&self, key: u64) -> bool {
+        self.refreshing.lock().unwrap().insert(key)
+    }
+
+    pub fn end_refresh(&self, key: u64) {
+        self.refreshing.lock().unwrap().remove(&key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cached(body: &'static str) -> CachedResponse {
+        CachedResponse::new(
+            StatusCode::OK,
+            Some(HeaderValue::from_static("application/json")),
+            Bytes::from_static(body.as_bytes()),
+        )
+    }
+
+    #[test]
+    fn entries_go_from_fresh_to_stale_to_expired() {
+        let cache = ResponseCache::new(8);
+        let key = ResponseCache::key(None, "openai/gpt-4o", b"{}");
+        assert!(matches!(cache.lookup(key), Lookup::Miss));
+
+        let policy = |fresh_ms, stale_ms| CachePolicy {
+            fresh_for: Duration::from_millis(fresh_ms),
+            stale_for: Duration::from_millis(stale_ms),
+        };
+        cache.store(key, cached("{\"id\":1}"), policy(60_000, 0));
+        assert!(matches!(cache.lookup(key), Lookup::Fresh(r) if r.body == "{\"id\":1}"));
+
+        cache.store(key, cached("{\"id\":2}"), policy(0, 60_000));
+        assert!(matches!(cache.lookup(key), Lookup::Stale(r) if r.body == "{\"id\":2}"));
+
+        cache.store(key, cached("{\"id\":3}"), policy(0, 0));
+        assert!(matches!(cache.lookup(key), Lookup::Miss));
+        assert!(matches!(cache.lookup(key), Lookup::Miss));
+    }
+
+    #[test]
+    fn keys_are_scoped_and_refreshes_single_flight() {
+        assert_ne!(
+            ResponseCache::key(Some("search"), "openai/gpt-4o", b"{}"),
+            ResponseCache::key(None, "openai/gpt-4o", b"{}")
+        );
+
+        let cache = ResponseCache::default();
+        assert!(cache.begin_refresh(7));
+        assert!(!cache.begin_refresh(7));
+        cache.end_refresh(7);
+        assert!(cache.begin_refresh(7));
+    }
+
+    #[test]
+    fn policy_from_settings() {
+        let settings = ResponseCacheSettings {
+            ttl: "5m".to_string(),
+            stale_while_revalidate: Some("1h".to_string()),
+        };
+        assert_eq!(
+            CachePolicy::from_settings(&settings),
+            Some(CachePolicy {
+                fresh_for: Duration::from_secs(300),
+                stale_for: Duration::from_secs(3600),
+            })
+        );
+        let settings = ResponseCacheSettings {
+            ttl: "soon".to_string(),
+            stale_while_revalidate: None,
+        };
+        assert_eq!(CachePolicy::from_settings(&settings), None);
+    }
+}
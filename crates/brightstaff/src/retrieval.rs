@@ -0,0 +1,342 @@
+//! Retrieval augmentation before routing.
+//!
+//! With `retrieval` configured, the text of the latest user message is sent
+//! to the retrieval endpoint and the documents it returns are injected as
+//! context right before that message: a system message for chat completions,
+//! a developer message for the Responses API, and a leading text block for
+//! Anthropic messages. Documents are taken in the order returned until
+//! `max_context_tokens` is spent. Retrieval is best effort; when the endpoint
+//! fails or is slow the request goes on without context.
+
+use std::time::Duration;
+
+use bytes::Bytes;
+use common::configuration::RetrievalSettings;
+use common::tokenizer::token_count;
+use hermesllm::clients::SupportedAPIsFromClient;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tracing::{debug, warn};
+
+const DEFAULT_TOP_K: usize = 5;
+const DEFAULT_MAX_CONTEXT_TOKENS: usize = 2000;
+const DEFAULT_TIMEOUT_MS: u64 = 2000;
+const CONTEXT_PREAMBLE: &str =
+    "Use the following retrieved documents as context when they are relevant to the user's request.";
+
+#[derive(Debug, Deserialize)]
+struct RetrievalResponse {
+    #[serde(default)]
+    documents: Vec<Document>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Document {
+    content: String,
+    #[serde(default)]
+    source: Option<String>,
+}
+
+pub struct Retriever {
+    settings: RetrievalSettings,
+    http_client: reqwest::Client,
+}
+
+impl Retriever {
+    pub fn new(settings: RetrievalSettings, http_client: reqwest::Client) -> Self {
+        Self {
+            settings,
+            http_client,
+        }
+    }
+
+    /// Inject retrieved context into a serialized client request. Returns the
+    /// rewritten body, or `None` when there is no user query or nothing was
+    /// retrieved.
+    pub async fn augment(
+        &self,
+        body: &[u8],
+        client_api: &SupportedAPIsFromClient,
+        model: &str,
+    ) -> Option<Bytes> {
+        let mut request = serde_json::from_slice::<Value>(body).ok()?;
+        let (index, query) = latest_user_query(&request, client_api)?;
+        let documents = match self.retrieve(&query).await {
+            Ok(documents) => documents,
+            Err(error) => {
+                warn!(error = %error, "retrieval failed, continuing without context");
+                return None;
+            }
+        };
+        let budget = self
+            .settings
+            .max_context_tokens
+            .unwrap_or(DEFAULT_MAX_CONTEXT_TOKENS);
+        let selected = within_budget(documents, budget, model);
+        if selected.is_empty() {
+            return None;
+        }
+        debug!(documents = selected.len(), "injecting retrieved context");
+        inject(&mut request, client_api, index, &context_text(&selected))?;
+        serde_json::to_vec(&request).ok().map(Bytes::from)
+    }
+
+    async fn retrieve(&self, query: &str) -> Result<Vec<Document>, String> {
+        let mut request = self
+            .http_client
+            .post(&self.settings.endpoint)
+            .timeout(Duration::from_millis(
+                self.settings.timeout_ms.unwrap_or(DEFAULT_TIMEOUT_MS),
+            ))
+            .json(&json!({
+                "query": query,
+                "top_k": self.settings.top_k.unwrap_or(DEFAULT_TOP_K),
+            }));
+        for (name, value) in self.settings.headers.iter().flatten() {
+            request = request.header(name, value);
+        }
+        let response = request.send().await.map_err(|e| e.to_string())?;
+        if !response.status().is_success() {
+            return Err(format!("retrieval endpoint answered {}", response.status()));
+        }
+        response
+            .json::<RetrievalResponse>()
+            .await
+            .map(|r| r.documents)
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// Position and text of the latest user message.
+fn latest_user_query(
+    request: &Value,
+    client_api: &SupportedAPIsFromClient,
+) -> Option<(usize, String)> {
+    let items = match client_api {
+        SupportedAPIsFromClient::OpenAIResponsesAPI(_) => match &request["input"] {
+            Value::String(text) => return Some((0, text.clone())),
+            input => input.as_array()?,
+        },
+        _ => request["messages"].as_array()?,
+    };
+    let index = items.iter().rposition(|item| item["role"] == "user")?;
+    let text = match &items[index]["content"] {
+        Value::String(text) => text.clone(),
+        Value::Array(parts) => parts
+            .iter()
+            .filter_map(|part| part["text"].as_str())
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => return None,
+    };
+    (!text.trim().is_empty()).then_some((index, text))
+}
+
+/// Documents in the order given, skipping those that would overrun `budget`.
+fn within_budget(documents: Vec<Document>, budget: usize, model: &str) -> Vec<Document> {
+    let mut spent = 0;
+    documents
+        .into_iter()
+        .filter(|document| {
+            let tokens =
+                token_count(model, &document.content).unwrap_or(document.content.len() / 4);
+            let fits = spent + tokens <= budget;
+            if fits {
+                spent += tokens;
+            }
+            fits
+        })
+        .collect()
+}
+
+fn context_text(documents: &[Document]) -> String {
+    let mut text = CONTEXT_PREAMBLE.to_string();
+    for (number, document) in documents.iter().enumerate() {
+        text.push_str(&format!("\n\n[{}]", number + 1));
+        if let Some(source) = &document.source {
+            text.push_str(&format!(" (source: {source})"));
+        }
+        text.push('\n');
+        text.push_str(&document.content);
+    }
+    text
+}
+
+fn inject(
+    request: &mut Value,
+    client_api: &SupportedAPIsFromClient,
+    index: usize,
+    context: &str,
+) -> Option<()> {
+    match client_api {
+        SupportedAPIsFromClient::OpenAIChatCompletions(_) => {
+            let messages = request["messages"].as_array_mut()?;
+            messages.insert(index, json!({"role": "system", "content": context}));
+        }
+        SupportedAPIsFromClient::OpenAIResponsesAPI(_) => {
+            let context = json!({"type": "message", "role": "developer", "content": context});
+            if let Value::String(text) = &request["input"] {
+                let user = json!({"type": "message", "role": "user", "content": text});
+                request["input"] = json!([context, user]);
+            } else {
+                request["input"].as_array_mut()?.insert(index, context);
+            }
+        }
+        SupportedAPIsFromClient::AnthropicMessagesAPI(_) => {
+            let message = &mut request["messages"][index];
+            let block = json!({"type": "text", "text": context});
+            match &message["content"] {
+                Value::String(text) => {
+                    message["content"] = json!([block, {"type": "text", "text": text}]);
+                }
+                _ => message["content"].as_array_mut()?.insert(0, block),
+            }
+        }
+    }
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hermesllm::apis::anthropic::AnthropicApi;
+    use hermesllm::apis::OpenAIApi;
+    use hyper::service::service_fn;
+    use hyper_util::rt::TokioIo;
+    use tokio::net::TcpListener;
+
+    fn document(content: &str, source: Option<&str>) -> Document {
+        Document {
+            content: content.to_string(),
+            source: source.map(str::to_string),
+        }
+    }
+
+    /// Retrieval endpoint answering every query with `documents`, and
+    /// reporting the queries it received.
+    async fn endpoint(documents: Value) -> (String, tokio::sync::mpsc::Receiver<Value>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let (queries, received) = tokio::sync::mpsc::channel(4);
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let service = service_fn(move |req: hyper::Request<hyper::body::Incoming>| {
+                let queries = queries.clone();
+                let documents = documents.clone();
+                async move {
+                    let body = http_body_util::BodyExt::collect(req.into_body())
+                        .await
+                        .unwrap()
+                        .to_bytes();
+                    queries
+                        .send(serde_json::from_slice::<Value>(&body).unwrap())
+                        .await
+                        .unwrap();
+                    Ok::<_, hyper::Error>(hyper::Response::new(http_body_util::Full::new(
+                        Bytes::from(json!({ "documents": documents }).to_string()),
+                    )))
+                }
+            });
+            hyper::server::conn::http1::Builder::new()
+                .serve_connection(TokioIo::new(stream), service)
+                .await
+                .unwrap();
+        });
+        (format!("http://{address}/search"), received)
+    }
+
+    fn retriever(endpoint: String) -> Retriever {
+        Retriever::new(
+            RetrievalSettings {
+                endpoint,
+                top_k: Some(2),
+                max_context_tokens: None,
+                timeout_ms: None,
+                headers: None,
+            },
+            reqwest::Client::new(),
+        )
+    }
+
+    #[test]
+    fn documents_past_the_budget_are_skipped() {
+        let documents = vec![
+            document("short answer", None),
+            document(&"long ".repeat(100), None),
+            document("another short one", None),
+        ];
+        let selected = within_budget(documents, 20, "gpt-4o");
+        assert_eq!(selected.len(), 2);
+        assert_eq!(selected[1].content, "another short one");
+    }
+
+    #[tokio::test]
+    async fn context_goes_right_before_the_latest_user_message() {
+        let (url, mut queries) = endpoint(json!([
+            {"content": "Plano routes by preference.", "source": "docs/routing.md"}
+        ]))
+        .await;
+        let body = json!({
+            "model": "gpt-4o",
+            "messages": [
+                {"role": "system", "content": "You are helpful."},
+                {"role": "user", "content": "hi"},
+                {"role": "assistant", "content": "hello"},
+                {"role": "user", "content": [{"type": "text", "text": "How does routing work?"}]},
+            ]
+        });
+        let chat = SupportedAPIsFromClient::OpenAIChatCompletions(OpenAIApi::ChatCompletions);
+        let augmented = retriever(url)
+            .augment(body.to_string().as_bytes(), &chat, "gpt-4o")
+            .await
+            .unwrap();
+
+        let query = queries.recv().await.unwrap();
+        assert_eq!(
+            query,
+            json!({"query": "How does routing work?", "top_k": 2})
+        );
+        let augmented: Value = serde_json::from_slice(&augmented).unwrap();
+        let messages = augmented["messages"].as_array().unwrap();
+        assert_eq!(messages.len(), 5);
+        assert_eq!(messages[3]["role"], "system");
+        let context = messages[3]["content"].as_str().unwrap();
+        assert!(context.contains("[1] (source: docs/routing.md)\nPlano routes by preference."));
+        assert_eq!(messages[4], body["messages"][3]);
+    }
+
+    #[tokio::test]
+    async fn anthropic_context_is_a_leading_text_block() {
+        let (url, _queries) = endpoint(json!([{"content": "Plano is a gateway."}])).await;
+        let body = json!({
+            "model": "claude-sonnet-4",
+            "max_tokens": 100,
+            "messages": [{"role": "user", "content": "What is Plano?"}]
+        });
+        let anthropic = SupportedAPIsFromClient::AnthropicMessagesAPI(AnthropicApi::Messages);
+        let augmented = retriever(url)
+            .augment(body.to_string().as_bytes(), &anthropic, "claude-sonnet-4")
+            .await
+            .unwrap();
+        let augmented: Value = serde_json::from_slice(&augmented).unwrap();
+        let content = augmented["messages"][0]["content"].as_array().unwrap();
+        assert!(content[0]["text"]
+            .as_str()
+            .unwrap()
+            .contains("Plano is a gateway."));
+        assert_eq!(
+            content[1],
+            json!({"type": "text", "text": "What is Plano?"})
+        );
+    }
+
+    #[tokio::test]
+    async fn unreachable_endpoint_leaves_the_request_alone() {
+        let body = json!({"messages": [{"role": "user", "content": "hi"}]});
+        let chat = SupportedAPIsFromClient::OpenAIChatCompletions(OpenAIApi::ChatCompletions);
+        let augmented = retriever("http://127.0.0.1:1/search".to_string())
+            .augment(body.to_string().as_bytes(), &chat, "gpt-4o")
+            .await;
+        assert!(augmented.is_none());
+    }
+}
@@ -5,6 +5,7 @@ use std::time::Duration;
 use common::configuration::{
     CostProvider, LatencyProvider, MetricsSource, SelectionPolicy, SelectionPreference,
 };
+use common::pricing::PricingTable;
 use tokio::sync::RwLock;
 use tracing::{debug, info, warn};
 
@@ -13,10 +14,16 @@ const DO_PRICING_URL: &str = "https://api.digitalocean.com/v2/gen-ai/models/cata
 pub struct ModelMetricsService {
     cost: Arc<RwLock<HashMap<String, f64>>>,
     latency: Arc<RwLock<HashMap<String, f64>>>,
+    /// Fallback for models the cost source has no data for.
+    pricing: Arc<PricingTable>,
 }
 
 impl ModelMetricsService {
-    pub async fn new(sources: &[MetricsSource], client: reqwest::Client) -> Self {
+    pub async fn new(
+        sources: &[MetricsSource],
+        client: reqwest::Client,
+        pricing: Arc<PricingTable>,
+    ) -> Self {
         let cost_data = Arc::new(RwLock::new(HashMap::new()));
         let latency_data = Arc::new(RwLock::new(HashMap::new()));
 
@@ -74,6 +81,7 @@ impl ModelMetricsService {
         ModelMetricsService {
             cost: cost_data,
             latency: latency_data,
+            pricing,
         }
     }
 
@@ -92,12 +100,22 @@ impl ModelMetricsService {
 
         match policy.prefer {
             SelectionPreference::Cheapest => {
+                let mut costs = HashMap::new();
                 for m in models {
-                    if !cost_data.contains_key(m.as_str()) {
-                        warn!(model = %m, "no cost data for model — ranking last (prefer: cheapest)");
+                    let cost = cost_data
+                        .get(m.as_str())
+                        .copied()
+                        .or_else(|| self.pricing.blended_price(m));
+                    match cost {
+                        Some(cost) => {
+                            costs.insert(m.clone(), cost);
+                        }
+                        None => {
+                            warn!(model = %m, "no cost data for model — ranking last (prefer: cheapest)")
+                        }
                     }
                 }
-                rank_by_ascending_metric(models, &cost_data)
+                rank_by_ascending_metric(models, &costs)
             }
             SelectionPreference::Fastest => {
                 for m in models {
@@ -116,6 +134,11 @@ impl ModelMetricsService {
         self.cost.read().await.clone()
     }
 
+    /// Whether `model` can be ranked by cost, from the cost source or the pricing table.
+    pub async fn has_cost_data(&self, model: &str) -> bool {
+        self.cost.read().await.contains_key(model) || self.pricing.price_for(model).is_some()
+    }
+
     /// Returns a snapshot of the current latency data. Used at startup to warn about unmatched models.
     pub async fn latency_snapshot(&self) -> HashMap<String, f64> {
         self.latency.read().await.clone()
@@ -293,6 +316,7 @@ mod tests {
                 m
             })),
             latency: Arc::new(RwLock::new(HashMap::new())),
+            pricing: Arc::default(),
         };
         let models = vec!["gpt-4o".to_string(), "gpt-4o-mini".to_string()];
         let result = service
@@ -311,6 +335,7 @@ mod tests {
                 m.insert("claude-sonnet".to_string(), 120.0);
                 m
             })),
+            pricing: Arc::default(),
         };
         let models = vec!["gpt-4o".to_string(), "claude-sonnet".to_string()];
         let result = service
@@ -324,6 +349,7 @@ mod tests {
         let service = ModelMetricsService {
             cost: Arc::new(RwLock::new(HashMap::new())),
             latency: Arc::new(RwLock::new(HashMap::new())),
+            pricing: Arc::default(),
         };
         let models = vec!["model-a".to_string(), "model-b".to_string()];
         let result = service
@@ -341,6 +367,7 @@ mod tests {
                 m
             })),
             latency: Arc::new(RwLock::new(HashMap::new())),
+            pricing: Arc::default(),
         };
         let models = vec!["gpt-4o-mini".to_string(), "gpt-4o".to_string()];
         let result = service
@@ -349,6 +376,31 @@ mod tests {
         assert_eq!(result, vec!["gpt-4o", "gpt-4o-mini"]);
     }
 
+    #[tokio::test]
+    async fn test_rank_models_cheapest_falls_back_to_pricing_table() {
+        let service = ModelMetricsService {
+            cost: Arc::new(RwLock::new(HashMap::new())),
+            latency: Arc::new(RwLock::new(HashMap::new())),
+            pricing: Arc::default(),
+        };
+        let models = vec![
+            "custom/unpriced".to_string(),
+            "anthropic/claude-sonnet-4-20250514".to_string(),
+            "openai/gpt-4o-mini".to_string(),
+        ];
+        let result = service
+            .rank_models(&models, &make_policy(SelectionPreference::Cheapest))
+            .await;
+        assert_eq!(
+            result,
+            vec![
+                "openai/gpt-4o-mini",
+                "anthropic/claude-sonnet-4-20250514",
+                "custom/unpriced"
+            ]
+        );
+    }
+
     #[tokio::test]
     async fn test_rank_models_none_preserves_order() {
         let service = ModelMetricsService {
@@ -359,6 +411,7 @@ mod tests {
                 m
             })),
             latency: Arc::new(RwLock::new(HashMap::new())),
+            pricing: Arc::default(),
         };
         let models = vec!["gpt-4o".to_string(), "gpt-4o-mini".to_string()];
         let result = service
@@ -110,6 +110,11 @@ impl OrchestratorService {
         }
     }
 
+    /// The configured top-level routing preference named `route_name`.
+    pub fn route_preference(&self, route_name: &str) -> Option<&TopLevelRoutingPreference> {
+        self.top_level_preferences.get(route_name)
+    }
+
     // ---- Session cache methods ----
 
     #[must_use]
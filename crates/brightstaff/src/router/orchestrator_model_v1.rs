@@ -240,6 +240,7 @@ impl OrchestratorModel for OrchestratorModelV1 {
                         name: None,
                         tool_calls: None,
                         tool_call_id: None,
+                        thinking_blocks: None,
                     });
                 }
                 break;
@@ -251,6 +252,7 @@ impl OrchestratorModel for OrchestratorModelV1 {
                 name: None,
                 tool_calls: None,
                 tool_call_id: None,
+                thinking_blocks: None,
             });
         }
 
@@ -263,6 +265,7 @@ impl OrchestratorModel for OrchestratorModelV1 {
                     name: None,
                     tool_calls: None,
                     tool_call_id: None,
+                    thinking_blocks: None,
                 });
             }
         }
@@ -306,6 +309,7 @@ impl OrchestratorModel for OrchestratorModelV1 {
                 name: None,
                 tool_calls: None,
                 tool_call_id: None,
+                thinking_blocks: None,
             }],
             temperature: Some(0.01),
             ..Default::default()
@@ -978,6 +982,7 @@ If no routes are needed, return an empty list for `route`.
             name: None,
             tool_calls: None,
             tool_call_id: None,
+            thinking_blocks: None,
         }];
 
         let req = orchestrator.generate_request(&conversation, &None);
@@ -1066,6 +1071,7 @@ If no routes are needed, return an empty list for `route`.
                 name: None,
                 tool_calls: None,
                 tool_call_id: None,
+                thinking_blocks: None,
             });
         }
 
@@ -0,0 +1,218 @@
+//! Provider keys kept out of the config file.
+//!
+//! An `access_key` or `secondary_access_key` may be written as
+//!
+//! - `file:/run/secrets/openai_key`: contents of the file, trailing newline stripped
+//! - `vault:secret/data/plano#openai`: field of a HashiCorp Vault KV secret
+//!
+//! The reference stays in the rendered config. Brightstaff reads the secrets
+//! at startup and again whenever `model_providers` is applied through the
+//! admin API, keeps them in memory only, and passes the key to the LLM
+//! gateway in [`ARCH_UPSTREAM_ACCESS_KEY_HEADER`] on every request it routes.
+//! Requests sent to the LLM listener directly cannot use such a provider.
+//!
+//! Vault is reached at `VAULT_ADDR` with the token from `VAULT_TOKEN`, or read
+//! from `VAULT_TOKEN_FILE`; `VAULT_NAMESPACE` is sent when set. Both KV v1 and
+//! KV v2 response shapes are accepted.
+//!
+//! [`ARCH_UPSTREAM_ACCESS_KEY_HEADER`]: common::consts::ARCH_UPSTREAM_ACCESS_KEY_HEADER
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use common::configuration::{is_secret_reference, LlmProvider};
+use serde_json::Value;
+
+use crate::access_keys::KeySlot;
+
+const VAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Resolved keys, by provider name and key slot.
+pub type SecretKeys = HashMap<(String, KeySlot), String>;
+
+/// Read every `file:` and `vault:` key of `providers`. Fails on the first
+/// reference that cannot be resolved, so a broken reference is never served
+/// as an empty credential.
+pub async fn resolve(
+    providers: &[LlmProvider],
+    http_client: &reqwest::Client,
+) -> Result<SecretKeys, String> {
+    let mut keys = SecretKeys::new();
+    for provider in providers {
+        for (slot, field, value) in [
+            (KeySlot::Primary, "access_key", &provider.access_key),
+            (
+                KeySlot::Secondary,
+                "secondary_access_key",
+                &provider.secondary_access_key,
+            ),
+        ] {
+            let Some(reference) = value.as_deref().filter(|v| is_secret_reference(v)) else {
+                continue;
+            };
+            let secret = resolve_reference(reference, http_client)
+                .await
+                .map_err(|e| {
+                    format!(
+                        "unable to resolve {} for provider '{}': {}",
+                        field, provider.name, e
+                    )
+                })?;
+            keys.insert((provider.name.clone(), slot), secret);
+        }
+    }
+    Ok(keys)
+}
+
+async fn resolve_reference(
+    reference: &str,
+    http_client: &reqwest::Client,
+) -> Result<String, String> {
+    match reference.strip_prefix("file:") {
+        Some(path) => read_secret_file(path),
+        None => read_vault_secret(reference.trim_start_matches("vault:"), http_client).await,
+    }
+}
+
+fn read_secret_file(path: &str) -> Result<String, String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| format!("unable to read secret file {}: {}", path, e))?;
+    let secret = contents.trim_end_matches(['\r', '\n']);
+    if secret.is_empty() {
+        return Err(format!("secret file {} is empty", path));
+    }
+    Ok(secret.to_string())
+}
+
+/// `vault:<path>#<field>` split into its path and field
+fn parse_vault_reference(reference: &str) -> Option<(&str, &str)> {
+    let (path, field) = reference.split_once('#')?;
+    let path = path.trim_matches('/');
+    (!path.is_empty() && !field.is_empty()).then_some((path, field))
+}
+
+fn vault_token() -> Result<String, String> {
+    if let Ok(token_file) = std::env::var("VAULT_TOKEN_FILE") {
+        return read_secret_file(&token_file);
+    }
+    std::env::var("VAULT_TOKEN")
+        .ok()
+        .filter(|token| !token.is_empty())
+        .ok_or_else(|| "VAULT_TOKEN or VAULT_TOKEN_FILE must be set".to_string())
+}
+
+async fn read_vault_secret(
+    reference: &str,
+    http_client: &reqwest::Client,
+) -> Result<String, String> {
+    let (path, field) = parse_vault_reference(reference).ok_or_else(|| {
+        format!(
+            "invalid vault reference 'vault:{}', expected vault:<path>#<field>",
+            reference
+        )
+    })?;
+    let vault_addr =
+        std::env::var("VAULT_ADDR").map_err(|_| "VAULT_ADDR must be set".to_string())?;
+
+    let mut request = http_client
+        .get(format!("{}/v1/{}", vault_addr.trim_end_matches('/'), path))
+        .header("x-vault-token", vault_token()?)
+        .timeout(VAULT_TIMEOUT);
+    if let Ok(namespace) = std::env::var("VAULT_NAMESPACE") {
+        request = request.header("x-vault-namespace", namespace);
+    }
+    let response = request
+        .send()
+        .await
+        .map_err(|e| format!("unable to reach vault at {}: {}", vault_addr, e))?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(format!(
+            "vault returned {} for secret path '{}'",
+            status, path
+        ));
+    }
+    let body: Value = response
+        .json()
+        .await
+        .map_err(|e| format!("invalid vault response for '{}': {}", path, e))?;
+    secret_field(&body, field)
+        .ok_or_else(|| format!("vault secret '{}' has no field '{}'", path, field))
+}
+
+/// Field of a KV secret response. KV v2 nests the secret under `data.data`,
+/// KV v1 returns it directly under `data`.
+fn secret_field(body: &Value, field: &str) -> Option<String> {
+    let data = &body["data"];
+    let data = if data["data"].is_object() && data.get("metadata").is_some() {
+        &data["data"]
+    } else {
+        data
+    };
+    match data.get(field)? {
+        Value::String(secret) => Some(secret.clone()),
+        Value::Null => None,
+        other => Some(other.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn resolves_file_references_per_slot() {
+        let dir = std::env::temp_dir().join(format!("secret-keys-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let secret_file = dir.join("openai_key");
+        std::fs::write(&secret_file, "sk-from-file\n").unwrap();
+
+        let provider = LlmProvider {
+            name: "openai/gpt-4o".to_string(),
+            access_key: Some("$OPENAI_API_KEY".to_string()),
+            secondary_access_key: Some(format!("file:{}", secret_file.display())),
+            ..Default::default()
+        };
+        let keys = resolve(std::slice::from_ref(&provider), &reqwest::Client::new())
+            .await
+            .unwrap();
+        assert_eq!(
+            keys,
+            SecretKeys::from([(
+                ("openai/gpt-4o".to_string(), KeySlot::Secondary),
+                "sk-from-file".to_string()
+            )])
+        );
+
+        let missing = LlmProvider {
+            access_key: Some(format!("file:{}", dir.join("missing").display())),
+            ..provider
+        };
+        let error = resolve(&[missing], &reqwest::Client::new())
+            .await
+            .unwrap_err();
+        assert!(
+            error.contains("access_key for provider 'openai/gpt-4o'"),
+            "{}",
+            error
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn vault_references_and_kv_shapes() {
+        assert_eq!(
+            parse_vault_reference("/secret/data/plano#openai"),
+            Some(("secret/data/plano", "openai"))
+        );
+        assert_eq!(parse_vault_reference("secret/plano"), None);
+        assert_eq!(parse_vault_reference("#openai"), None);
+
+        let kv2 = json!({"data": {"data": {"openai": "sk-v2"}, "metadata": {}}});
+        assert_eq!(secret_field(&kv2, "openai").as_deref(), Some("sk-v2"));
+        let kv1 = json!({"data": {"openai": "sk-v1"}});
+        assert_eq!(secret_field(&kv1, "openai").as_deref(), Some("sk-v1"));
+        assert_eq!(secret_field(&kv1, "anthropic"), None);
+    }
+}
@@ -1954,6 +1954,7 @@ mod tests {
             name: None,
             tool_calls: None,
             tool_call_id: None,
+            thinking_blocks: None,
         }
     }
 
@@ -2221,6 +2222,7 @@ mod tests {
                 name: None,
                 tool_calls: None,
                 tool_call_id: None,
+                thinking_blocks: None,
             },
             // Assistant message with no text content (e.g. tool call) — filtered out.
             Message {
@@ -2229,6 +2231,7 @@ mod tests {
                 name: None,
                 tool_calls: None,
                 tool_call_id: None,
+                thinking_blocks: None,
             },
             // Tool-role message with no extractable text — filtered out.
             Message {
@@ -2237,6 +2240,7 @@ mod tests {
                 name: None,
                 tool_calls: None,
                 tool_call_id: None,
+                thinking_blocks: None,
             },
             Message {
                 role: Role::Assistant,
@@ -2246,6 +2250,7 @@ mod tests {
                 name: None,
                 tool_calls: None,
                 tool_call_id: None,
+                thinking_blocks: None,
             },
             // Rephrased user turn — original index 4, but after filtering
             // only 3 messages remain in `normalized_messages` before it.
@@ -2257,6 +2262,7 @@ mod tests {
                 name: None,
                 tool_calls: None,
                 tool_call_id: None,
+                thinking_blocks: None,
             },
         ];
 
@@ -2609,6 +2615,7 @@ mod tests {
                         },
                     }]),
                     tool_call_id: None,
+                    thinking_blocks: None,
                 }
             };
 
@@ -2620,6 +2627,7 @@ mod tests {
                 name: None,
                 tool_calls: None,
                 tool_call_id: Some(tool_call_id.to_string()),
+                thinking_blocks: None,
             }
         };
 
@@ -2742,6 +2750,7 @@ mod tests {
                         },
                     }]),
                     tool_call_id: None,
+                    thinking_blocks: None,
                 }
             };
 
@@ -2753,6 +2762,7 @@ mod tests {
                 name: None,
                 tool_calls: None,
                 tool_call_id: Some(tool_call_id.to_string()),
+                thinking_blocks: None,
             }
         };
 
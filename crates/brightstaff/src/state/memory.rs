@@ -110,6 +110,7 @@ mod tests {
             created_at: 1234567890,
             model: "claude-3".to_string(),
             provider: "anthropic".to_string(),
+            response: None,
         }
     }
 
@@ -145,6 +146,7 @@ mod tests {
             created_at: 9999999999,
             model: "gpt-4".to_string(),
             provider: "openai".to_string(),
+            response: None,
         };
         storage.put(state2.clone()).await.unwrap();
 
@@ -302,6 +304,7 @@ mod tests {
             created_at: 1234567890,
             model: "gpt-4".to_string(),
             provider: "openai".to_string(),
+            response: None,
         };
 
         let current_input = vec![InputItem::Message(InputMessage {
@@ -408,6 +411,7 @@ mod tests {
             created_at: 1234567890,
             model: "claude-3".to_string(),
             provider: "anthropic".to_string(),
+            response: None,
         };
 
         // Step 2: Current request includes function call output
@@ -501,6 +505,7 @@ mod tests {
             created_at: 1234567890,
             model: "gpt-4".to_string(),
             provider: "openai".to_string(),
+            response: None,
         };
 
         // Current input: function outputs for both calls
@@ -597,6 +602,7 @@ mod tests {
             created_at: 1234567890,
             model: "claude-3".to_string(),
             provider: "anthropic".to_string(),
+            response: None,
         };
 
         // Turn 3: User asks follow-up question
@@ -635,4 +641,57 @@ mod tests {
             _ => panic!("Expected MessageContent::Items"),
         }
     }
+
+    #[tokio::test]
+    async fn test_merge_replays_function_call_items() {
+        let storage = MemoryConversationalStorage::new();
+        let function_call = || InputItem::FunctionCall {
+            item_type: "function_call".to_string(),
+            name: "get_weather".to_string(),
+            arguments: "{\"location\":\"SF\"}".to_string(),
+            call_id: "call_1".to_string(),
+        };
+        let prev_state = OpenAIConversationState {
+            response_id: "resp_tool_004".to_string(),
+            input_items: vec![
+                InputItem::Message(InputMessage {
+                    role: MessageRole::User,
+                    content: MessageContent::Text("What's the weather in SF?".to_string()),
+                }),
+                function_call(),
+            ],
+            created_at: 1234567890,
+            model: "claude-3".to_string(),
+            provider: "anthropic".to_string(),
+            response: None,
+        };
+        storage.put(prev_state).await.unwrap();
+
+        // The client echoes the call next to its output, as the OpenAI SDKs do.
+        let current_input = vec![
+            function_call(),
+            InputItem::FunctionCallOutput {
+                item_type: "function_call_output".to_string(),
+                call_id: "call_1".to_string(),
+                output: serde_json::Value::String("72F and sunny".to_string()),
+            },
+        ];
+        let merged = storage.merge(&storage.get("resp_tool_004").await.unwrap(), current_input);
+
+        assert_eq!(merged.len(), 3);
+        assert!(
+            matches!(&merged[1], InputItem::FunctionCall { call_id, .. } if call_id == "call_1")
+        );
+        assert!(
+            matches!(&merged[2], InputItem::FunctionCallOutput { call_id, .. } if call_id == "call_1")
+        );
+
+        // Survives the JSON round trip persistent backends go through.
+        let json = serde_json::to_string(&merged).unwrap();
+        let restored: Vec<InputItem> = serde_json::from_str(&json).unwrap();
+        assert!(
+            matches!(&restored[1], InputItem::FunctionCall { name, .. } if name == "get_weather")
+        );
+        assert!(matches!(&restored[2], InputItem::FunctionCallOutput { .. }));
+    }
 }
@@ -1,8 +1,10 @@
 use async_trait::async_trait;
 use hermesllm::apis::openai_responses::{
     InputContent, InputItem, InputMessage, InputParam, MessageContent, MessageRole,
+    ResponsesAPIResponse,
 };
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::error::Error;
 use std::fmt;
 use std::sync::Arc;
@@ -31,6 +33,11 @@ pub struct OpenAIConversationState {
 
     /// Provider that generated this response (e.g., "anthropic", "openai")
     pub provider: String,
+
+    /// The response itself, kept for `background` requests so clients can
+    /// poll it until it completes
+    #[serde(default)]
+    pub response: Option<ResponsesAPIResponse>,
 }
 
 /// Error types for state storage operations
@@ -80,12 +87,24 @@ pub trait StateStorage: Send + Sync {
         prev_state: &OpenAIConversationState,
         current_input: Vec<InputItem>,
     ) -> Vec<InputItem> {
-        // Default implementation: prepend previous input, append current
+        // Default implementation: prepend previous input, append current.
+        // Clients often echo the previous turn's function calls (and outputs)
+        // alongside their new function_call_output; the stored copies win so
+        // each call appears once, ahead of its output.
         let prev_count = prev_state.input_items.len();
         let current_count = current_input.len();
 
+        let stored_tool_items: HashSet<(bool, &str)> = prev_state
+            .input_items
+            .iter()
+            .filter_map(tool_item_key)
+            .collect();
         let mut combined_input = prev_state.input_items.clone();
-        combined_input.extend(current_input);
+        combined_input.extend(
+            current_input
+                .into_iter()
+                .filter(|item| tool_item_key(item).is_none_or(|k| !stored_tool_items.contains(&k))),
+        );
 
         debug!(
             response_id = %prev_state.response_id,
@@ -102,6 +121,15 @@ pub trait StateStorage: Send + Sync {
 
 // === Utility functions for state management ===
 
+/// Identity of a function call (`false`) or function call output (`true`) item.
+fn tool_item_key(item: &InputItem) -> Option<(bool, &str)> {
+    match item {
+        InputItem::FunctionCall { call_id, .. } => Some((false, call_id)),
+        InputItem::FunctionCallOutput { call_id, .. } => Some((true, call_id)),
+        _ => None,
+    }
+}
+
 /// Extract input items from InputParam, converting text to structured format
 pub fn extract_input_items(input: &InputParam) -> Vec<InputItem> {
     match input {
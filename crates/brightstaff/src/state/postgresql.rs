@@ -84,19 +84,28 @@ impl StateStorage for PostgreSQLConversationStorage {
         let input_items_json = serde_json::to_value(&state.input_items).map_err(|e| {
             StateStorageError::StorageError(format!("Failed to serialize input_items: {}", e))
         })?;
+        let response_json = state
+            .response
+            .as_ref()
+            .map(serde_json::to_value)
+            .transpose()
+            .map_err(|e| {
+                StateStorageError::StorageError(format!("Failed to serialize response: {}", e))
+            })?;
 
         // Upsert the conversation state
         self.client
             .execute(
                 r#"
                 INSERT INTO conversation_states
-                    (response_id, input_items, created_at, model, provider, updated_at)
-                VALUES ($1, $2, $3, $4, $5, NOW())
+                    (response_id, input_items, created_at, model, provider, response, updated_at)
+                VALUES ($1, $2, $3, $4, $5, $6, NOW())
                 ON CONFLICT (response_id)
                 DO UPDATE SET
                     input_items = EXCLUDED.input_items,
                     model = EXCLUDED.model,
                     provider = EXCLUDED.provider,
+                    response = EXCLUDED.response,
                     updated_at = NOW()
                 "#,
                 &[
@@ -105,6 +114,7 @@ impl StateStorage for PostgreSQLConversationStorage {
                     &state.created_at,
                     &state.model,
                     &state.provider,
+                    &response_json,
                 ],
             )
             .await
@@ -126,7 +136,7 @@ impl StateStorage for PostgreSQLConversationStorage {
             .client
             .query_opt(
                 r#"
-                SELECT response_id, input_items, created_at, model, provider
+                SELECT response_id, input_items, created_at, model, provider, response
                 FROM conversation_states
                 WHERE response_id = $1
                 "#,
@@ -147,6 +157,7 @@ impl StateStorage for PostgreSQLConversationStorage {
                 let created_at: i64 = row.get("created_at");
                 let model: String = row.get("model");
                 let provider: String = row.get("provider");
+                let response_json: Option<serde_json::Value> = row.get("response");
 
                 // Deserialize input_items from JSONB
                 let input_items = serde_json::from_value(input_items_json).map_err(|e| {
@@ -155,6 +166,15 @@ impl StateStorage for PostgreSQLConversationStorage {
                         e
                     ))
                 })?;
+                let response = response_json
+                    .map(serde_json::from_value)
+                    .transpose()
+                    .map_err(|e| {
+                        StateStorageError::StorageError(format!(
+                            "Failed to deserialize response: {}",
+                            e
+                        ))
+                    })?;
 
                 Ok(OpenAIConversationState {
                     response_id,
@@ -162,6 +182,7 @@ impl StateStorage for PostgreSQLConversationStorage {
                     created_at,
                     model,
                     provider,
+                    response,
                 })
             }
             None => Err(StateStorageError::NotFound(format!(
@@ -245,6 +266,7 @@ mod tests {
             created_at: 1234567890,
             model: "gpt-4".to_string(),
             provider: "openai".to_string(),
+            response: None,
         }
     }
 
@@ -135,9 +135,12 @@ impl<P: StreamProcessor> ResponsesStateProcessor<P> {
             for event in sse_iter {
                 // Only process data lines (skip event-only lines)
                 if let Some(data_str) = &event.data {
-                    // Try to parse as ResponsesAPIStreamEvent and check if it's a ResponseCompleted event
-                    if let Ok(ResponsesAPIStreamEvent::ResponseCompleted { response, .. }) =
-                        serde_json::from_str::<ResponsesAPIStreamEvent>(data_str)
+                    // Try to parse as ResponsesAPIStreamEvent and check if it's the final
+                    // event; responses cut short by the token limit end in response.incomplete
+                    if let Ok(
+                        ResponsesAPIStreamEvent::ResponseCompleted { response, .. }
+                        | ResponsesAPIStreamEvent::ResponseIncomplete { response, .. },
+                    ) = serde_json::from_str::<ResponsesAPIStreamEvent>(data_str)
                     {
                         info!(
                             response_id = %response.id,
@@ -247,6 +250,7 @@ impl<P: StreamProcessor> StreamProcessor for ResponsesStateProcessor<P> {
                     .as_secs() as i64,
                 model: self.model.clone(),
                 provider: self.provider.clone(),
+                response: None,
             };
 
             // Store asynchronously (fire and forget with logging)
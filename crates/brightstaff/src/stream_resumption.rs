@@ -0,0 +1,359 @@
+//! Resumable SSE streams.
+//!
+//! With `stream_resumption` configured, every event of a streamed response
+//! gets an id `<stream-id>:<n>` and is kept in a replay window. The client is
+//! served from that window rather than directly from upstream, so generation
+//! carries on when it disconnects; resending the request with
+//! `Last-Event-ID` replays the events after that id and follows the rest of
+//! the stream instead of starting a new generation.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use bytes::{Bytes, BytesMut};
+use common::configuration::StreamResumptionSettings;
+use futures::Stream;
+use http_body_util::combinators::BoxBody;
+use http_body_util::StreamBody;
+use hyper::body::Frame;
+use hyper::header::{HeaderMap, HeaderValue, CACHE_CONTROL, CONTENT_TYPE};
+use hyper::{Response, StatusCode};
+use tokio::sync::Notify;
+use tokio_stream::StreamExt;
+use tracing::debug;
+use uuid::Uuid;
+
+use crate::streaming::StreamProcessor;
+
+pub const LAST_EVENT_ID_HEADER: &str = "last-event-id";
+const DEFAULT_REPLAY_EVENTS: usize = 512;
+const DEFAULT_RETENTION_SECS: u64 = 60;
+
+/// Replay windows of the streams that can currently be resumed.
+pub struct ReplayBuffers {
+    replay_events: usize,
+    retention: Duration,
+    streams: Mutex<HashMap<String, Arc<ReplayStream>>>,
+}
+
+impl ReplayBuffers {
+    pub fn new(settings: &StreamResumptionSettings) -> Self {
+        Self {
+            replay_events: settings
+                .replay_events
+                .unwrap_or(DEFAULT_REPLAY_EVENTS)
+                .max(1),
+            retention: Duration::from_secs(
+                settings.retention_secs.unwrap_or(DEFAULT_RETENTION_SECS),
+            ),
+            streams: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Start a replay window for a new response. Only requests from `tenant`
+    /// may resume it.
+    pub fn register(&self, tenant: Option<&str>) -> Arc<ReplayStream> {
+        let stream = Arc::new(ReplayStream {
+            id: Uuid::new_v4().simple().to_string(),
+            tenant: tenant.map(str::to_string),
+            capacity: self.replay_events,
+            state: Mutex::new(ReplayState {
+                events: VecDeque::new(),
+                next_position: 1,
+                finished_at: None,
+            }),
+            notify: Notify::new(),
+        });
+        let mut streams = self.streams.lock().unwrap();
+        streams.retain(|_, s| !s.expired(self.retention));
+        streams.insert(stream.id.clone(), Arc::clone(&stream));
+        stream
+    }
+
+    /// The stream named by the request's `Last-Event-ID` and the position
+    /// to resume after, if it can still be resumed by `tenant`.
+    pub fn resume(
+        &self,
+        headers: &HeaderMap,
+        tenant: Option<&str>,
+    ) -> Option<(Arc<ReplayStream>, u64)> {
+        let last_event_id = headers.get(LAST_EVENT_ID_HEADER)?.to_str().ok()?;
+        let (id, position) = last_event_id.trim().rsplit_once(':')?;
+        let position = position.parse().ok()?;
+        let stream = self.streams.lock().unwrap().get(id).cloned()?;
+        (stream.tenant.as_deref() == tenant && !stream.expired(self.retention))
+            .then_some((stream, position))
+    }
+}
+
+struct ReplayState {
+    /// Framed events, each with its position in the stream.
+    events: VecDeque<(u64, Bytes)>,
+    /// Position the next event will get; positions start at 1.
+    next_position: u64,
+    finished_at: Option<Instant>,
+}
+
+impl ReplayState {
+    fn first_position(&self) -> u64 {
+        self.next_position - self.events.len() as u64
+    }
+}
+
+/// Events of one response, shared by the task reading upstream and every
+/// client following the stream.
+pub struct ReplayStream {
+    id: String,
+    tenant: Option<String>,
+    capacity: usize,
+    state: Mutex<ReplayState>,
+    notify: Notify,
+}
+
+impl ReplayStream {
+    fn push(&self, event: impl FnOnce(u64) -> Bytes) {
+        let mut state = self.state.lock().unwrap();
+        let position = state.next_position;
+        state.events.push_back((position, event(position)));
+        state.next_position += 1;
+        if state.events.len() > self.capacity {
+            state.events.pop_front();
+        }
+        drop(state);
+        self.notify.notify_waiters();
+    }
+
+    fn finish(&self) {
+        self.state
+            .lock()
+            .unwrap()
+            .finished_at
+            .get_or_insert_with(Instant::now);
+        self.notify.notify_waiters();
+    }
+
+    fn expired(&self, retention: Duration) -> bool {
+        self.state
+            .lock()
+            .unwrap()
+            .finished_at
+            .is_some_and(|finished| finished.elapsed() > retention)
+    }
+
+    /// The event after `position`, waiting for it if the stream is still
+    /// running. `None` once the stream is over, or when the event has
+    /// already left the replay window.
+    async fn next_after(&self, position: u64) -> Option<(u64, Bytes)> {
+        loop {
+            let notified = self.notify.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+            {
+                let state = self.state.lock().unwrap();
+                let first = state.first_position();
+                if position + 1 < first {
+                    debug!(stream = %self.id, position, "resume position left the replay window");
+                    return None;
+                }
+                if let Some(event) = state.events.get((position + 1 - first) as usize) {
+                    return Some(event.clone());
+                }
+                if state.finished_at.is_some() {
+                    return None;
+                }
+            }
+            notified.await;
+        }
+    }
+
+    /// Events after `position`, followed live until the stream ends.
+    pub fn events_after(self: Arc<Self>, position: u64) -> impl Stream<Item = Bytes> {
+        futures::stream::unfold((self, position), |(stream, position)| async move {
+            let (position, event) = stream.next_after(position).await?;
+            Some((event, (stream, position)))
+        })
+    }
+
+    /// Response body serving the events after `position`.
+    pub fn body(self: Arc<Self>, position: u64) -> BoxBody<Bytes, hyper::Error> {
+        let frames = self
+            .events_after(position)
+            .map(|event| Ok::<_, hyper::Error>(Frame::data(event)));
+        BoxBody::new(StreamBody::new(frames))
+    }
+}
+
+/// Response to a request that resumes `stream` after `position`.
+pub fn resume_response(
+    stream: Arc<ReplayStream>,
+    position: u64,
+) -> Response<BoxBody<Bytes, hyper::Error>> {
+    debug!(stream = %stream.id, position, "resuming stream");
+    let mut response = Response::new(stream.body(position));
+    *response.status_mut() = StatusCode::OK;
+    let headers = response.headers_mut();
+    headers.insert(CONTENT_TYPE, HeaderValue::from_static("text/event-stream"));
+    headers.insert(CACHE_CONTROL, HeaderValue::from_static("no-cache"));
+    response
+}
+
+/// Splits the processed stream into SSE events, gives each an id and hands
+/// them to the replay window instead of the client.
+pub struct ResumableStreamProcessor<P: StreamProcessor> {
+    inner: P,
+    stream: Arc<ReplayStream>,
+    pending: BytesMut,
+}
+
+impl<P: StreamProcessor> ResumableStreamProcessor<P> {
+    pub fn new(inner: P, stream: Arc<ReplayStream>) -> Self {
+        Self {
+            inner,
+            stream,
+            pending: BytesMut::new(),
+        }
+    }
+
+    fn publish_complete_events(&mut self) {
+        while let Some((end, separator)) = event_end(&self.pending) {
+            let event = self.pending.split_to(end + separator);
+            let id = &self.stream.id;
+            self.stream.push(|position| {
+                let mut framed = BytesMut::with_capacity(end + id.len() + 32);
+                framed.extend_from_slice(&event[..end]);
+                framed.extend_from_slice(format!("\nid: {id}:{position}\n\n").as_bytes());
+                framed.freeze()
+            });
+        }
+    }
+}
+
+/// End of the first complete event in `buffer` and the length of the blank
+/// line that terminates it.
+fn event_end(buffer: &[u8]) -> Option<(usize, usize)> {
+    let lf = buffer.windows(2).position(|w| w == b"\n\n").map(|i| (i, 2));
+    let crlf = buffer
+        .windows(4)
+        .position(|w| w == b"\r\n\r\n")
+        .map(|i| (i, 4));
+    match (lf, crlf) {
+        (Some(lf), Some(crlf)) => Some(if crlf.0 < lf.0 { crlf } else { lf }),
+        (lf, crlf) => lf.or(crlf),
+    }
+}
+
+impl<P: StreamProcessor> StreamProcessor for ResumableStreamProcessor<P> {
+    fn process_chunk(&mut self, chunk: Bytes) -> Result<Option<Bytes>, String> {
+        if let Some(processed) = self.inner.process_chunk(chunk)? {
+            self.pending.extend_from_slice(&processed);
+            self.publish_complete_events();
+        }
+        Ok(None)
+    }
+
+    fn on_first_bytes(&mut self) {
+        self.inner.on_first_bytes()
+    }
+
+    fn on_complete(&mut self) {
+        self.inner.on_complete();
+        if !self.pending.is_empty() {
+            let rest = self.pending.split().freeze();
+            self.stream.push(|_| rest);
+        }
+        self.stream.finish();
+    }
+
+    fn on_error(&mut self, error: &str) {
+        self.inner.on_error(error)
+    }
+
+    // The replay window, not the client, consumes this stream, and a
+    // reconnecting client resumes from it.
+    fn outlives_client(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Passthrough;
+
+    impl StreamProcessor for Passthrough {
+        fn process_chunk(&mut self, chunk: Bytes) -> Result<Option<Bytes>, String> {
+            Ok(Some(chunk))
+        }
+    }
+
+    fn settings(replay_events: usize) -> StreamResumptionSettings {
+        StreamResumptionSettings {
+            replay_events: Some(replay_events),
+            retention_secs: None,
+        }
+    }
+
+    fn last_event_id(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(LAST_EVENT_ID_HEADER, HeaderValue::from_str(value).unwrap());
+        headers
+    }
+
+    #[tokio::test]
+    async fn assigns_ids_and_resumes_after_the_last_seen_event() {
+        let buffers = ReplayBuffers::new(&settings(16));
+        let stream = buffers.register(Some("acme"));
+        let mut processor = ResumableStreamProcessor::new(Passthrough, Arc::clone(&stream));
+        assert_eq!(
+            processor
+                .process_chunk(Bytes::from("data: {\"n\":1}\n\ndata: {\"n\""))
+                .unwrap(),
+            None
+        );
+        processor
+            .process_chunk(Bytes::from(":2}\r\n\r\ndata: [DONE]\n\n"))
+            .unwrap();
+        processor.on_complete();
+
+        let id = &stream.id;
+        let events: Vec<Bytes> = Arc::clone(&stream).events_after(0).collect().await;
+        assert_eq!(events[0], format!("data: {{\"n\":1}}\nid: {id}:1\n\n"));
+        assert_eq!(events[1], format!("data: {{\"n\":2}}\nid: {id}:2\n\n"));
+        assert_eq!(events.len(), 3);
+
+        let (resumed, position) = buffers
+            .resume(&last_event_id(&format!("{id}:2")), Some("acme"))
+            .unwrap();
+        let events: Vec<Bytes> = resumed.events_after(position).collect().await;
+        assert_eq!(events, vec![format!("data: [DONE]\nid: {id}:3\n\n")]);
+
+        assert!(buffers
+            .resume(&last_event_id(&format!("{id}:2")), Some("globex"))
+            .is_none());
+        assert!(buffers
+            .resume(&last_event_id("unknown:2"), Some("acme"))
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn followers_wait_for_live_events_and_stop_when_evicted() {
+        let buffers = ReplayBuffers::new(&settings(2));
+        let stream = buffers.register(None);
+        let follower = tokio::spawn(Arc::clone(&stream).events_after(0).collect::<Vec<_>>());
+        let mut processor = ResumableStreamProcessor::new(Passthrough, Arc::clone(&stream));
+        processor.process_chunk(Bytes::from("data: a\n\n")).unwrap();
+        tokio::task::yield_now().await;
+        processor
+            .process_chunk(Bytes::from("data: b\n\ndata: c\n\n"))
+            .unwrap();
+        processor.on_complete();
+        assert_eq!(follower.await.unwrap().len(), 3);
+
+        // Event 1 has left the two-event window.
+        let events: Vec<Bytes> = Arc::clone(&stream).events_after(0).collect().await;
+        assert!(events.is_empty());
+        assert_eq!(stream.events_after(1).collect::<Vec<_>>().await.len(), 2);
+    }
+}
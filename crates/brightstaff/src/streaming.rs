@@ -1,19 +1,24 @@
 use bytes::Bytes;
 use common::configuration::ResolvedFilterChain;
+use common::pricing::PricingTable;
 use http_body_util::combinators::BoxBody;
-use http_body_util::StreamBody;
+use http_body_util::{BodyStream, StreamBody};
 use hyper::body::Frame;
 use hyper::header::HeaderMap;
 use opentelemetry::trace::TraceContextExt;
 use opentelemetry::KeyValue;
-use std::time::Instant;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
 use tokio_stream::wrappers::ReceiverStream;
 use tokio_stream::StreamExt;
 use tracing::{debug, info, warn, Instrument};
 use tracing_opentelemetry::OpenTelemetrySpanExt;
 
+use crate::archive::{ArchiveEntry, ArchiveOutcome, ArchivedUsage, Archiver};
+use crate::event_bus::{CompletionEvent, EventBus};
 use crate::handlers::agents::pipeline::{PipelineError, PipelineProcessor};
+use crate::provider_health::ProviderHealth;
 
 const STREAM_BUFFER_SIZE: usize = 16;
 /// Cap on accumulated response bytes kept for usage extraction.
@@ -22,7 +27,10 @@ const STREAM_BUFFER_SIZE: usize = 16;
 const USAGE_BUFFER_MAX: usize = 2 * 1024 * 1024;
 use crate::signals::{InteractionQuality, SignalAnalyzer, TextBasedSignalAnalyzer, FLAG_MARKER};
 use crate::tracing::{llm, set_service_name, signals as signal_constants};
+use crate::usage_export::UsageLedger;
 use hermesllm::apis::openai::Message;
+use hermesllm::clients::errors::ApiError;
+use hermesllm::clients::SupportedAPIsFromClient;
 
 /// Parsed usage + resolved-model details from a provider response.
 #[derive(Debug, Default, Clone)]
@@ -33,6 +41,8 @@ struct ExtractedUsage {
     cached_input_tokens: Option<i64>,
     cache_creation_tokens: Option<i64>,
     reasoning_tokens: Option<i64>,
+    /// Anthropic's `input_tokens` excludes cache reads; OpenAI's `prompt_tokens` includes them.
+    prompt_excludes_cached: bool,
     /// The model the upstream actually used. For router aliases (e.g.
     /// `router:software-engineering`), this differs from the request model.
     resolved_model: Option<String>,
@@ -46,8 +56,49 @@ impl ExtractedUsage {
             && self.resolved_model.is_none()
     }
 
+    /// Prompt tokens including cache reads, which Anthropic reports apart.
+    fn billed_prompt_tokens(&self) -> Option<i64> {
+        let prompt = self.prompt_tokens?;
+        Some(if self.prompt_excludes_cached {
+            prompt + self.cached_input_tokens.unwrap_or(0)
+        } else {
+            prompt
+        })
+    }
+
+    /// Fold in usage from a later event of the same stream. Counts are
+    /// running totals that some providers split across events (Anthropic puts
+    /// input tokens in `message_start` and output tokens in `message_delta`),
+    /// so later non-zero counts win and missing ones keep the earlier value.
+    fn merge(&mut self, later: ExtractedUsage) {
+        fn pick(earlier: &mut Option<i64>, later: Option<i64>) {
+            if later.is_some_and(|v| v > 0) || earlier.is_none() {
+                *earlier = later.or(*earlier);
+            }
+        }
+        pick(&mut self.prompt_tokens, later.prompt_tokens);
+        pick(&mut self.completion_tokens, later.completion_tokens);
+        pick(&mut self.total_tokens, later.total_tokens);
+        pick(&mut self.cached_input_tokens, later.cached_input_tokens);
+        pick(&mut self.cache_creation_tokens, later.cache_creation_tokens);
+        pick(&mut self.reasoning_tokens, later.reasoning_tokens);
+        self.prompt_excludes_cached |= later.prompt_excludes_cached;
+        if later.resolved_model.is_some() {
+            self.resolved_model = later.resolved_model;
+        }
+        if let (Some(p), Some(c)) = (self.prompt_tokens, self.completion_tokens) {
+            self.total_tokens = Some(self.total_tokens.unwrap_or(0).max(p + c));
+        }
+    }
+
     fn from_json(value: &serde_json::Value) -> Self {
         let mut out = Self::default();
+        // Anthropic `message_start` and Responses API stream events nest the
+        // usage and model in the message or response they carry
+        let value = match value.get("message").or_else(|| value.get("response")) {
+            Some(inner) if inner.is_object() && value.get("usage").is_none() => inner,
+            _ => value,
+        };
         if let Some(model) = value.get("model").and_then(|v| v.as_str()) {
             if !model.is_empty() {
                 out.resolved_model = Some(model.to_string());
@@ -70,6 +121,7 @@ impl ExtractedUsage {
             // Anthropic-shape fallbacks
             if out.prompt_tokens.is_none() {
                 out.prompt_tokens = u.get("input_tokens").and_then(|v| v.as_i64());
+                out.prompt_excludes_cached = u.get("cache_read_input_tokens").is_some();
             }
             if out.completion_tokens.is_none() {
                 out.completion_tokens = u.get("output_tokens").and_then(|v| v.as_i64());
@@ -86,6 +138,12 @@ impl ExtractedUsage {
                 out.cached_input_tokens =
                     u.get("cached_content_token_count").and_then(|v| v.as_i64());
             }
+            if out.cached_input_tokens.is_none() {
+                out.cached_input_tokens = u
+                    .get("input_tokens_details")
+                    .and_then(|d| d.get("cached_tokens"))
+                    .and_then(|v| v.as_i64());
+            }
             out.cache_creation_tokens = u
                 .get("cache_creation_input_tokens")
                 .and_then(|v| v.as_i64());
@@ -98,8 +156,8 @@ impl ExtractedUsage {
 }
 
 /// Try to pull usage out of an accumulated response body.
-/// Handles both a single JSON object (non-streaming) and SSE streams where the
-/// final `data: {...}` event carries the `usage` field.
+/// Handles both a single JSON object (non-streaming) and SSE streams, where
+/// the usage of every `data: {...}` event carrying a `usage` field is combined.
 fn extract_usage_from_bytes(buf: &[u8]) -> ExtractedUsage {
     if buf.is_empty() {
         return ExtractedUsage::default();
@@ -113,12 +171,13 @@ fn extract_usage_from_bytes(buf: &[u8]) -> ExtractedUsage {
         }
     }
 
-    // SSE path: scan from the end for a `data:` line containing a usage object.
+    // SSE path: combine the usage objects of all `data:` lines in order.
     let text = match std::str::from_utf8(buf) {
         Ok(t) => t,
         Err(_) => return ExtractedUsage::default(),
     };
-    for line in text.lines().rev() {
+    let mut usage = ExtractedUsage::default();
+    for line in text.lines() {
         let trimmed = line.trim_start();
         let payload = match trimmed.strip_prefix("data:") {
             Some(p) => p.trim_start(),
@@ -131,14 +190,23 @@ fn extract_usage_from_bytes(buf: &[u8]) -> ExtractedUsage {
             continue;
         }
         if let Ok(value) = serde_json::from_str::<serde_json::Value>(payload) {
-            let u = ExtractedUsage::from_json(&value);
-            if !u.is_empty() {
-                return u;
-            }
+            usage.merge(ExtractedUsage::from_json(&value));
         }
     }
 
-    ExtractedUsage::default()
+    usage
+}
+
+/// USD cost of a complete response body, priced by the model it reports
+/// (else `model`), or `None` without usage or a price.
+pub fn response_cost_usd(pricing: &PricingTable, model: &str, body: &[u8]) -> Option<f64> {
+    let usage = extract_usage_from_bytes(body);
+    pricing.cost_usd(
+        usage.resolved_model.as_deref().unwrap_or(model),
+        usage.billed_prompt_tokens()?,
+        usage.completion_tokens?,
+        usage.cached_input_tokens.unwrap_or(0),
+    )
 }
 
 /// Trait for processing streaming chunks
@@ -155,6 +223,12 @@ pub trait StreamProcessor: Send + 'static {
 
     /// Called when streaming encounters an error
     fn on_error(&mut self, _error: &str) {}
+
+    /// Whether the upstream stream should be read to the end after the client
+    /// hangs up. By default a disconnect aborts the upstream request.
+    fn outlives_client(&self) -> bool {
+        false
+    }
 }
 
 impl StreamProcessor for Box<dyn StreamProcessor> {
@@ -170,6 +244,9 @@ impl StreamProcessor for Box<dyn StreamProcessor> {
     fn on_error(&mut self, error: &str) {
         (**self).on_error(error)
     }
+    fn outlives_client(&self) -> bool {
+        (**self).outlives_client()
+    }
 }
 
 /// A processor that tracks streaming metrics
@@ -185,6 +262,18 @@ pub struct ObservableStreamProcessor {
     /// on `on_complete`. Capped at `USAGE_BUFFER_MAX`; excess chunks are dropped
     /// from the buffer (they still pass through to the client).
     response_buffer: Vec<u8>,
+    /// Prices for the `llm.usage.cost_usd` attribute, with the model to price
+    /// when the response does not name one.
+    pricing: Option<(Arc<PricingTable>, String)>,
+    /// Where the call's usage is totalled for export, under this client key.
+    usage_ledger: Option<(Arc<UsageLedger>, String)>,
+    /// Where the finished exchange is archived, with its request side.
+    archive: Option<(Arc<Archiver>, ArchiveEntry)>,
+    /// Published on the event bus once the response is over.
+    completion_event: Option<(Arc<EventBus>, CompletionEvent)>,
+    /// Latency averages updated for this provider, with whether time to
+    /// first token is measured.
+    provider_health: Option<(Arc<ProviderHealth>, String, bool)>,
 }
 
 impl ObservableStreamProcessor {
@@ -219,8 +308,51 @@ impl ObservableStreamProcessor {
             time_to_first_token: None,
             messages,
             response_buffer: Vec::new(),
+            pricing: None,
+            usage_ledger: None,
+            archive: None,
+            completion_event: None,
+            provider_health: None,
         }
     }
+
+    /// Record the call's cost on the span once usage is known.
+    pub fn with_pricing(mut self, pricing: Arc<PricingTable>, model: impl Into<String>) -> Self {
+        self.pricing = Some((pricing, model.into()));
+        self
+    }
+
+    /// Add the call's usage to `ledger` under `client_key` once it is known.
+    pub fn with_usage_ledger(mut self, ledger: Arc<UsageLedger>, client_key: String) -> Self {
+        self.usage_ledger = Some((ledger, client_key));
+        self
+    }
+
+    /// Archive the exchange, completed with the response, once it is over.
+    pub fn with_archive(mut self, archiver: Arc<Archiver>, entry: ArchiveEntry) -> Self {
+        self.archive = Some((archiver, entry));
+        self
+    }
+
+    /// Publish `event`, completed with latency, usage and quality, once the
+    /// response is over.
+    pub fn with_completion_event(mut self, bus: Arc<EventBus>, event: CompletionEvent) -> Self {
+        self.completion_event = Some((bus, event));
+        self
+    }
+
+    /// Update `provider`'s latency averages once the response is over, or
+    /// count a failure if it breaks off. Time to first token is only recorded
+    /// for `streaming` responses.
+    pub fn with_provider_health(
+        mut self,
+        health: Arc<ProviderHealth>,
+        provider: String,
+        streaming: bool,
+    ) -> Self {
+        self.provider_health = Some((health, provider, streaming));
+        self
+    }
 }
 
 impl StreamProcessor for ObservableStreamProcessor {
@@ -270,6 +402,7 @@ impl StreamProcessor for ObservableStreamProcessor {
         // Best-effort usage extraction + emission (works for both streaming
         // SSE and non-streaming JSON responses that include a `usage` object).
         let usage = extract_usage_from_bytes(&self.response_buffer);
+        let mut cost_usd = None;
         if !usage.is_empty() {
             let span = tracing::Span::current();
             let otel_context = span.context();
@@ -292,6 +425,28 @@ impl StreamProcessor for ObservableStreamProcessor {
             if let Some(v) = usage.reasoning_tokens {
                 otel_span.set_attribute(KeyValue::new(llm::REASONING_TOKENS, v));
             }
+            if let (Some((pricing, model)), Some(prompt), Some(completion)) = (
+                &self.pricing,
+                usage.billed_prompt_tokens(),
+                usage.completion_tokens,
+            ) {
+                let model = usage.resolved_model.as_deref().unwrap_or(model);
+                let cached = usage.cached_input_tokens.unwrap_or(0);
+                let cost = pricing.cost_usd(model, prompt, completion, cached);
+                cost_usd = cost;
+                if let Some(cost) = cost {
+                    otel_span.set_attribute(KeyValue::new(llm::COST_USD, cost));
+                }
+                if let Some((ledger, client_key)) = &self.usage_ledger {
+                    ledger.record(
+                        client_key,
+                        model,
+                        prompt.max(0) as u64,
+                        completion.max(0) as u64,
+                        cost,
+                    );
+                }
+            }
             // Override `llm.model` with the model the upstream actually ran
             // (e.g. `openai-gpt-5.4` resolved from `router:software-engineering`).
             // Cost lookup keys off the real model, not the alias.
@@ -299,11 +454,11 @@ impl StreamProcessor for ObservableStreamProcessor {
                 otel_span.set_attribute(KeyValue::new(llm::MODEL_NAME, resolved));
             }
         }
-        // Release the buffered bytes early; nothing downstream needs them.
-        self.response_buffer.clear();
-        self.response_buffer.shrink_to_fit();
+        // Release the buffered bytes early; only the archive needs them.
+        let response_body = std::mem::take(&mut self.response_buffer);
 
         // Analyze signals if messages are available and record as span attributes
+        let mut signal_report = None;
         if let Some(ref messages) = self.messages {
             let analyzer: Box<dyn SignalAnalyzer> = Box::new(TextBasedSignalAnalyzer::new());
             let report = analyzer.analyze(messages);
@@ -377,6 +532,43 @@ impl StreamProcessor for ObservableStreamProcessor {
             if should_flag {
                 otel_span.update_name(format!("{} {}", self.operation_name, FLAG_MARKER));
             }
+            signal_report = Some(report);
+        }
+
+        if let Some((bus, mut event)) = self.completion_event.take() {
+            event.latency_ms = duration_ms as u64;
+            event.time_to_first_token_ms = self.time_to_first_token.map(|t| t as u64);
+            event.prompt_tokens = usage.prompt_tokens;
+            event.completion_tokens = usage.completion_tokens;
+            event.total_tokens = usage.total_tokens;
+            event.cost_usd = cost_usd;
+            event.quality = signal_report.as_ref().map(|r| r.overall_quality.clone());
+            bus.publish(event);
+        }
+
+        if let Some((health, provider, streaming)) = self.provider_health.take() {
+            let ttft = self
+                .time_to_first_token
+                .filter(|_| streaming)
+                .map(|ms| Duration::from_millis(ms as u64));
+            health.record_success(&provider, ttft, self.start_time.elapsed());
+        }
+
+        if let Some((archiver, entry)) = self.archive.take() {
+            let outcome = ArchiveOutcome {
+                response_truncated: response_body.len() < self.total_bytes,
+                response: response_body,
+                usage: (!usage.is_empty()).then_some(ArchivedUsage {
+                    prompt_tokens: usage.prompt_tokens,
+                    completion_tokens: usage.completion_tokens,
+                    total_tokens: usage.total_tokens,
+                    cost_usd,
+                }),
+                signals: signal_report,
+                duration_ms: duration_ms as u64,
+                time_to_first_token_ms: self.time_to_first_token.map(|t| t as u64),
+            };
+            archiver.submit(entry, outcome);
         }
 
         info!(
@@ -396,9 +588,54 @@ impl StreamProcessor for ObservableStreamProcessor {
             duration_ms = self.start_time.elapsed().as_millis(),
             "stream error"
         );
+        // A broken stream counts against the provider instead of as a sample.
+        if let Some((health, provider, _)) = self.provider_health.take() {
+            health.record_failure(&provider);
+        }
     }
 }
 
+/// Marks the current span as cancelled by a client that hung up mid-stream.
+fn record_client_disconnect() {
+    warn!("client disconnected, aborting upstream stream");
+    let span = tracing::Span::current();
+    let otel_context = span.context();
+    let otel_span = otel_context.span();
+    otel_span.set_attribute(KeyValue::new(llm::CLIENT_DISCONNECTED, true));
+    otel_span.add_event(llm::CLIENT_DISCONNECTED, vec![]);
+}
+
+/// Precede a transport error that cuts an event stream short with an error
+/// event in the client's API shape, so the client learns why the response
+/// stopped instead of seeing a bare disconnect. The error is still yielded
+/// after it.
+pub fn with_stream_error_event<S, E>(
+    byte_stream: S,
+    client_api: SupportedAPIsFromClient,
+) -> impl futures::Stream<Item = Result<Bytes, E>> + Send + Unpin
+where
+    S: futures::Stream<Item = Result<Bytes, E>> + Send + Unpin,
+    E: std::fmt::Display + Send,
+{
+    futures::StreamExt::flat_map(byte_stream, move |item| {
+        let items = match item {
+            Ok(chunk) => vec![Ok(chunk)],
+            Err(err) => {
+                let error = ApiError {
+                    status: 502,
+                    error_type: None,
+                    message: format!("upstream stream ended unexpectedly: {err}"),
+                    code: None,
+                    param: None,
+                };
+                let event = Bytes::from(error.to_stream_events(&client_api));
+                vec![Ok(event), Err(err)]
+            }
+        };
+        futures::stream::iter(items)
+    })
+}
+
 /// Result of creating a streaming response
 pub struct StreamingResponse {
     pub body: BoxBody<Bytes, hyper::Error>,
@@ -420,7 +657,18 @@ where
         async move {
             let mut is_first_chunk = true;
 
-            while let Some(item) = byte_stream.next().await {
+            let outlives_client = processor.outlives_client();
+            loop {
+                // Dropping `byte_stream` on the way out aborts the upstream
+                // request, so a hung-up client stops token generation.
+                let item = tokio::select! {
+                    item = byte_stream.next() => item,
+                    _ = tx.closed(), if !outlives_client => {
+                        record_client_disconnect();
+                        break;
+                    }
+                };
+                let Some(item) = item else { break };
                 let chunk = match item {
                     Ok(chunk) => chunk,
                     Err(err) => {
@@ -441,7 +689,7 @@ where
                 match processor.process_chunk(chunk) {
                     Ok(Some(processed_chunk)) => {
                         if tx.send(processed_chunk).await.is_err() {
-                            warn!("receiver dropped");
+                            record_client_disconnect();
                             break;
                         }
                     }
@@ -495,7 +743,18 @@ where
             let mut pipeline_processor = PipelineProcessor::default();
             let chain = output_chain.to_agent_filter_chain("output_filter");
 
-            while let Some(item) = byte_stream.next().await {
+            let outlives_client = inner_processor.outlives_client();
+            loop {
+                // Dropping `byte_stream` on the way out aborts the upstream
+                // request, so a hung-up client stops token generation.
+                let item = tokio::select! {
+                    item = byte_stream.next() => item,
+                    _ = tx.closed(), if !outlives_client => {
+                        record_client_disconnect();
+                        break;
+                    }
+                };
+                let Some(item) = item else { break };
                 let chunk = match item {
                     Ok(chunk) => chunk,
                     Err(err) => {
@@ -546,7 +805,7 @@ where
                 match inner_processor.process_chunk(processed_chunk) {
                     Ok(Some(final_chunk)) => {
                         if tx.send(final_chunk).await.is_err() {
-                            warn!("receiver dropped");
+                            record_client_disconnect();
                             break;
                         }
                     }
@@ -574,6 +833,36 @@ where
     }
 }
 
+/// SSE comment sent to hold a connection open.
+const KEEP_ALIVE_COMMENT: &[u8] = b": keep-alive\n\n";
+
+/// Wraps an SSE response body so that a `: keep-alive` comment goes out every
+/// `interval` until the body's first frame. Proxies and browsers that close
+/// idle connections then wait out a model that thinks before it answers.
+pub fn with_keep_alive(
+    body: BoxBody<Bytes, hyper::Error>,
+    interval: Duration,
+) -> BoxBody<Bytes, hyper::Error> {
+    let ticker = tokio::time::interval_at(tokio::time::Instant::now() + interval, interval);
+    let frames = futures::stream::unfold(
+        (BodyStream::new(body), Some(ticker)),
+        |(mut frames, mut ticker)| async move {
+            let frame = match ticker.as_mut() {
+                Some(waiting) => tokio::select! {
+                    frame = frames.next() => {
+                        ticker = None;
+                        frame?
+                    }
+                    _ = waiting.tick() => Ok(Frame::data(Bytes::from_static(KEEP_ALIVE_COMMENT))),
+                },
+                None => frames.next().await?,
+            };
+            Some((frame, (frames, ticker)))
+        },
+    );
+    BoxBody::new(StreamBody::new(frames))
+}
+
 /// Truncates a message to the specified maximum length, adding "..." if truncated.
 pub fn truncate_message(message: &str, max_length: usize) -> String {
     if message.chars().count() > max_length {
@@ -625,6 +914,24 @@ data: [DONE]
         assert_eq!(u.total_tokens, Some(10));
     }
 
+    #[test]
+    fn streaming_usage_split_across_events_is_combined() {
+        let sse = b"event: message_start
+data: {\"type\":\"message_start\",\"message\":{\"model\":\"claude-sonnet-4-5\",\"usage\":{\"input_tokens\":25,\"output_tokens\":1,\"cache_read_input_tokens\":100}}}
+
+event: message_delta
+data: {\"type\":\"message_delta\",\"delta\":{\"stop_reason\":\"end_turn\"},\"usage\":{\"output_tokens\":40}}
+
+";
+        let u = extract_usage_from_bytes(sse);
+        assert_eq!(u.resolved_model.as_deref(), Some("claude-sonnet-4-5"));
+        assert_eq!(u.prompt_tokens, Some(25));
+        assert_eq!(u.completion_tokens, Some(40));
+        assert_eq!(u.total_tokens, Some(65));
+        assert_eq!(u.cached_input_tokens, Some(100));
+        assert_eq!(u.billed_prompt_tokens(), Some(125));
+    }
+
     #[test]
     fn empty_returns_default() {
         assert!(extract_usage_from_bytes(b"").is_empty());
@@ -635,3 +942,122 @@ data: [DONE]
         assert!(extract_usage_from_bytes(br#"{"ok":true}"#).is_empty());
     }
 }
+
+#[cfg(test)]
+mod keep_alive_tests {
+    use super::*;
+    use http_body_util::BodyExt;
+
+    #[tokio::test]
+    async fn keep_alive_comments_stop_at_the_first_frame() {
+        let (tx, rx) = mpsc::channel::<Bytes>(2);
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(120)).await;
+            tx.send(Bytes::from_static(b"data: first\n\n"))
+                .await
+                .unwrap();
+            tokio::time::sleep(Duration::from_millis(120)).await;
+            tx.send(Bytes::from_static(b"data: second\n\n"))
+                .await
+                .unwrap();
+        });
+        let body = BoxBody::new(StreamBody::new(
+            ReceiverStream::new(rx).map(|chunk| Ok::<_, hyper::Error>(Frame::data(chunk))),
+        ));
+
+        let output = with_keep_alive(body, Duration::from_millis(25))
+            .collect()
+            .await
+            .unwrap()
+            .to_bytes();
+        let output = String::from_utf8(output.to_vec()).unwrap();
+        let (before, after) = output.split_once("data: first\n\n").unwrap();
+        assert!(before.starts_with(": keep-alive\n\n"));
+        assert_eq!(before.replace(": keep-alive\n\n", ""), "");
+        assert_eq!(after, "data: second\n\n");
+    }
+}
+
+#[cfg(test)]
+mod client_disconnect_tests {
+    use super::*;
+    use http_body_util::BodyExt;
+
+    struct Passthrough;
+
+    impl StreamProcessor for Passthrough {
+        fn process_chunk(&mut self, chunk: Bytes) -> Result<Option<Bytes>, String> {
+            Ok(Some(chunk))
+        }
+    }
+
+    #[tokio::test]
+    async fn dropping_the_body_stops_reading_upstream() {
+        let (upstream, rx) = mpsc::channel::<Result<Bytes, reqwest::Error>>(2);
+        upstream
+            .send(Ok(Bytes::from_static(b"data: first\n\n")))
+            .await
+            .unwrap();
+
+        let StreamingResponse {
+            mut body,
+            processor_handle,
+        } = create_streaming_response(ReceiverStream::new(rx), Passthrough);
+        let first = body.frame().await.unwrap().unwrap().into_data().unwrap();
+        assert_eq!(first, Bytes::from_static(b"data: first\n\n"));
+        drop(body);
+
+        // The upstream never finishes on its own; the task must give up on it.
+        tokio::time::timeout(Duration::from_secs(1), processor_handle)
+            .await
+            .expect("upstream was still being read after the client hung up")
+            .unwrap();
+        assert!(upstream.is_closed());
+    }
+}
+
+#[cfg(test)]
+mod stream_error_event_tests {
+    use super::*;
+    use hermesllm::apis::anthropic::AnthropicApi;
+    use hermesllm::apis::openai::OpenAIApi;
+
+    async fn items(client_api: SupportedAPIsFromClient) -> Vec<Result<Bytes, String>> {
+        let upstream = tokio_stream::iter(vec![
+            Ok(Bytes::from_static(b"data: first\n\n")),
+            Err("connection reset".to_string()),
+        ]);
+        with_stream_error_event(upstream, client_api)
+            .collect()
+            .await
+    }
+
+    #[tokio::test]
+    async fn chat_stream_error_ends_with_error_chunk_and_done() {
+        let items = items(SupportedAPIsFromClient::OpenAIChatCompletions(
+            OpenAIApi::ChatCompletions,
+        ))
+        .await;
+
+        assert_eq!(items.len(), 3);
+        let event = items[1].as_ref().unwrap();
+        let event = std::str::from_utf8(event).unwrap();
+        assert!(event.starts_with("data: {\"error\""));
+        assert!(event.contains("connection reset"));
+        assert!(event.ends_with("data: [DONE]\n\n"));
+        assert_eq!(items[2], Err("connection reset".to_string()));
+    }
+
+    #[tokio::test]
+    async fn messages_stream_error_ends_with_error_event() {
+        let items = items(SupportedAPIsFromClient::AnthropicMessagesAPI(
+            AnthropicApi::Messages,
+        ))
+        .await;
+
+        let event = items[1].as_ref().unwrap();
+        let event = std::str::from_utf8(event).unwrap();
+        assert!(event.starts_with("event: error\ndata: "));
+        assert!(event.contains(r#""type":"api_error""#));
+    }
+}
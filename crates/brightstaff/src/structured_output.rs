@@ -0,0 +1,292 @@
+//! Streaming validation of structured output.
+//!
+//! When a streaming request asks for `json_object` or `json_schema` output,
+//! the text deltas of the response are validated as they pass through. On the
+//! first violation no later delta could repair, the stream ends with an error
+//! event in the client's dialect instead of sending the rest of the invalid
+//! output.
+
+use bytes::Bytes;
+use hermesllm::apis::streaming_shapes::structured_output::{
+    StructuredOutputValidator, StructuredOutputViolation,
+};
+use hermesllm::clients::SupportedAPIsFromClient;
+use serde_json::{json, Value};
+use tracing::warn;
+
+use crate::streaming::StreamProcessor;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Dialect {
+    ChatCompletions,
+    Messages,
+    Responses,
+}
+
+/// What a request's structured output must match, and how to report it.
+#[derive(Debug)]
+pub struct StructuredOutputCheck {
+    validator: StructuredOutputValidator,
+    dialect: Dialect,
+}
+
+impl StructuredOutputCheck {
+    /// The check for a request with a `json_object` or `json_schema` format:
+    /// `response_format` in Chat Completions, `text.format` in Responses and
+    /// `output_format` in Anthropic Messages.
+    pub fn for_request(body: &[u8], client_api: &SupportedAPIsFromClient) -> Option<Self> {
+        let request = serde_json::from_slice::<Value>(body).ok()?;
+        let (dialect, format, schema_pointer) = match client_api {
+            SupportedAPIsFromClient::OpenAIChatCompletions(_) => (
+                Dialect::ChatCompletions,
+                request.get("response_format")?,
+                "/json_schema/schema",
+            ),
+            SupportedAPIsFromClient::OpenAIResponsesAPI(_) => (
+                Dialect::Responses,
+                request.pointer("/text/format")?,
+                "/schema",
+            ),
+            SupportedAPIsFromClient::AnthropicMessagesAPI(_) => {
+                (Dialect::Messages, request.get("output_format")?, "/schema")
+            }
+        };
+        let validator = match format.get("type").and_then(Value::as_str)? {
+            "json_object" => StructuredOutputValidator::json_object(),
+            "json_schema" => match format.pointer(schema_pointer) {
+                Some(schema) => StructuredOutputValidator::new(schema.clone()),
+                None => StructuredOutputValidator::json_object(),
+            },
+            _ => return None,
+        };
+        Some(Self { validator, dialect })
+    }
+
+    /// The output text carried by one SSE event, if any.
+    fn text_delta(&self, event: &Value) -> Option<String> {
+        let text = match self.dialect {
+            Dialect::ChatCompletions => event.pointer("/choices/0/delta/content"),
+            Dialect::Messages => event
+                .get("delta")
+                .filter(|d| d["type"] == "text_delta")
+                .and_then(|d| d.get("text")),
+            Dialect::Responses => event
+                .get("delta")
+                .filter(|_| event["type"] == "response.output_text.delta"),
+        };
+        text.and_then(Value::as_str).map(str::to_string)
+    }
+
+    fn error_event(&self, violation: &StructuredOutputViolation) -> String {
+        let message = format!("response does not match the requested format: {violation}");
+        match self.dialect {
+            Dialect::ChatCompletions => {
+                let error = json!({"error": {
+                    "message": message,
+                    "type": "invalid_response_format",
+                    "code": "structured_output_violation",
+                }});
+                format!("data: {error}\n\n")
+            }
+            Dialect::Messages => {
+                let error =
+                    json!({"type": "error", "error": {"type": "api_error", "message": message}});
+                format!("event: error\ndata: {error}\n\n")
+            }
+            Dialect::Responses => {
+                let error = json!({
+                    "type": "error",
+                    "code": "structured_output_violation",
+                    "message": message,
+                    "param": null,
+                });
+                format!("event: error\ndata: {error}\n\n")
+            }
+        }
+    }
+}
+
+/// Validates streamed output against a [`StructuredOutputCheck`].
+///
+/// Chunks are forwarded a complete SSE event at a time so an invalid event
+/// can be replaced by the error; a partial event waits for its next chunk.
+pub struct StructuredOutputProcessor<P: StreamProcessor> {
+    inner: P,
+    check: StructuredOutputCheck,
+    pending: Vec<u8>,
+    failed: bool,
+}
+
+impl<P: StreamProcessor> StructuredOutputProcessor<P> {
+    pub fn new(inner: P, check: StructuredOutputCheck) -> Self {
+        Self {
+            inner,
+            check,
+            pending: Vec::new(),
+            failed: false,
+        }
+    }
+
+    /// The complete events of `pending` to forward, ending with the error
+    /// event when one of them violates the format.
+    fn take_events(&mut self) -> Vec<u8> {
+        let mut out = Vec::new();
+        while let Some(end) = find_event_end(&self.pending) {
+            let event: Vec<u8> = self.pending.drain(..end).collect();
+            let text = event_data(&event)
+                .and_then(|data| serde_json::from_str::<Value>(&data).ok())
+                .and_then(|value| self.check.text_delta(&value));
+            if let Some(Err(violation)) = text.map(|t| self.check.validator.push(&t)) {
+                warn!(error = %violation, "streamed output violates the requested format, ending stream");
+                out.extend_from_slice(self.check.error_event(&violation).as_bytes());
+                self.failed = true;
+                self.pending.clear();
+                break;
+            }
+            out.extend_from_slice(&event);
+        }
+        out
+    }
+}
+
+/// Index just past the first event terminator (a blank line) in `buffer`.
+fn find_event_end(buffer: &[u8]) -> Option<usize> {
+    buffer
+        .windows(2)
+        .position(|w| w == b"\n\n")
+        .map(|index| index + 2)
+}
+
+/// The joined `data:` lines of one SSE event.
+fn event_data(event: &[u8]) -> Option<String> {
+    let text = std::str::from_utf8(event).ok()?;
+    let data: Vec<&str> = text
+        .lines()
+        .filter_map(|line| line.strip_prefix("data:"))
+        .map(str::trim_start)
+        .collect();
+    (!data.is_empty()).then(|| data.join("\n"))
+}
+
+impl<P: StreamProcessor> StreamProcessor for StructuredOutputProcessor<P> {
+    fn process_chunk(&mut self, chunk: Bytes) -> Result<Option<Bytes>, String> {
+        if self.failed {
+            return Err("structured output violation".to_string());
+        }
+        self.pending.extend_from_slice(&chunk);
+        let events = self.take_events();
+        if events.is_empty() {
+            return Ok(None);
+        }
+        self.inner.process_chunk(Bytes::from(events))
+    }
+
+    fn on_first_bytes(&mut self) {
+        self.inner.on_first_bytes()
+    }
+
+    fn on_complete(&mut self) {
+        self.inner.on_complete()
+    }
+
+    fn on_error(&mut self, error: &str) {
+        self.inner.on_error(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hermesllm::apis::OpenAIApi;
+
+    struct Passthrough;
+
+    impl StreamProcessor for Passthrough {
+        fn process_chunk(&mut self, chunk: Bytes) -> Result<Option<Bytes>, String> {
+            Ok(Some(chunk))
+        }
+    }
+
+    fn chat() -> SupportedAPIsFromClient {
+        SupportedAPIsFromClient::OpenAIChatCompletions(OpenAIApi::ChatCompletions)
+    }
+
+    fn chunk(content: &str) -> String {
+        format!(
+            "data: {}\n\n",
+            json!({"choices": [{"index": 0, "delta": {"content": content}}]})
+        )
+    }
+
+    fn run(processor: &mut impl StreamProcessor, chunks: &[String]) -> (String, bool) {
+        let mut out = String::new();
+        for chunk in chunks {
+            match processor.process_chunk(Bytes::from(chunk.clone())) {
+                Ok(Some(bytes)) => out.push_str(std::str::from_utf8(&bytes).unwrap()),
+                Ok(None) => {}
+                Err(_) => return (out, true),
+            }
+        }
+        (out, false)
+    }
+
+    #[test]
+    fn checks_only_structured_output_requests() {
+        let schema = json!({"response_format": {"type": "json_schema", "json_schema": {"name": "w", "schema": {"type": "object"}}}});
+        assert!(
+            StructuredOutputCheck::for_request(schema.to_string().as_bytes(), &chat()).is_some()
+        );
+        let text = json!({"response_format": {"type": "text"}});
+        assert!(StructuredOutputCheck::for_request(text.to_string().as_bytes(), &chat()).is_none());
+
+        let responses = json!({"text": {"format": {"type": "json_object"}}});
+        assert!(StructuredOutputCheck::for_request(
+            responses.to_string().as_bytes(),
+            &SupportedAPIsFromClient::OpenAIResponsesAPI(OpenAIApi::Responses),
+        )
+        .is_some());
+    }
+
+    #[test]
+    fn valid_output_passes_through_across_split_chunks() {
+        let body = json!({"response_format": {"type": "json_object"}});
+        let check =
+            StructuredOutputCheck::for_request(body.to_string().as_bytes(), &chat()).unwrap();
+        let mut processor = StructuredOutputProcessor::new(Passthrough, check);
+        let first = chunk("{\"answer\": ");
+        let (head, tail) = first.split_at(10);
+        let chunks = vec![
+            head.to_string(),
+            tail.to_string(),
+            chunk("42}"),
+            "data: [DONE]\n\n".to_string(),
+        ];
+        let (out, failed) = run(&mut processor, &chunks);
+        assert!(!failed);
+        assert_eq!(out, chunks.concat());
+    }
+
+    #[test]
+    fn invalid_output_ends_with_an_error_event() {
+        let body = json!({"response_format": {"type": "json_object"}});
+        let check =
+            StructuredOutputCheck::for_request(body.to_string().as_bytes(), &chat()).unwrap();
+        let mut processor = StructuredOutputProcessor::new(Passthrough, check);
+        let chunks = vec![
+            chunk("{\"answer\": 42}"),
+            chunk(" Hope this helps!"),
+            chunk(" More text"),
+        ];
+        let (out, failed) = run(&mut processor, &chunks);
+        assert!(failed);
+        assert!(out.starts_with(&chunks[0]));
+        let error: Value = serde_json::from_str(
+            out[chunks[0].len()..]
+                .strip_prefix("data: ")
+                .unwrap()
+                .trim(),
+        )
+        .unwrap();
+        assert_eq!(error["error"]["code"], "structured_output_violation");
+    }
+}
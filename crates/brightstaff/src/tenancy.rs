@@ -0,0 +1,199 @@
+//! Tenant-scoped views of the gateway.
+//!
+//! The `tenancy` config section names tenants; a request picks one with the
+//! tenant header (`x-arch-tenant` by default). A tenant sees only its allowed
+//! models, has its own concurrency cap and routing preferences, and its
+//! conversation state lives in a separate namespace of the state storage.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use common::configuration::{TenancyConfig, TenantConfig};
+use common::consts::ARCH_TENANT_HEADER;
+use common::errors::BrightStaffError;
+use hyper::HeaderMap;
+
+use crate::state::{OpenAIConversationState, StateStorage, StateStorageError};
+
+#[derive(Debug, Default)]
+pub struct Tenancy {
+    header: String,
+    required: bool,
+    tenants: HashMap<String, Arc<TenantConfig>>,
+}
+
+impl Tenancy {
+    pub fn new(config: Option<&TenancyConfig>) -> Self {
+        let Some(config) = config else {
+            return Self::default();
+        };
+        Self {
+            header: config
+                .header
+                .clone()
+                .unwrap_or_else(|| ARCH_TENANT_HEADER.to_string()),
+            required: config.required,
+            tenants: config
+                .tenants
+                .iter()
+                .map(|tenant| (tenant.id.clone(), Arc::new(tenant.clone())))
+                .collect(),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.tenants.is_empty()
+    }
+
+    /// The tenant a request belongs to; `None` is the unscoped view.
+    pub fn resolve(
+        &self,
+        headers: &HeaderMap,
+    ) -> Result<Option<Arc<TenantConfig>>, BrightStaffError> {
+        if !self.is_enabled() {
+            return Ok(None);
+        }
+        let tenant_id = headers
+            .get(self.header.as_str())
+            .and_then(|v| v.to_str().ok())
+            .map(str::trim)
+            .filter(|v| !v.is_empty());
+        match tenant_id {
+            Some(id) => self
+                .tenants
+                .get(id)
+                .cloned()
+                .map(Some)
+                .ok_or_else(|| BrightStaffError::UnknownTenant(id.to_string())),
+            None if self.required => Err(BrightStaffError::TenantRequired(self.header.clone())),
+            None => Ok(None),
+        }
+    }
+}
+
+/// State storage view that keeps a tenant's responses apart from everyone
+/// else's by prefixing response ids in the underlying store.
+pub struct TenantStateStorage {
+    inner: Arc<dyn StateStorage>,
+    prefix: String,
+}
+
+impl TenantStateStorage {
+    /// `storage` scoped to `tenant`, or unchanged for the unscoped view.
+    pub fn scope(
+        storage: Option<Arc<dyn StateStorage>>,
+        tenant: Option<&TenantConfig>,
+    ) -> Option<Arc<dyn StateStorage>> {
+        match (storage, tenant) {
+            (Some(inner), Some(tenant)) => Some(Arc::new(TenantStateStorage {
+                inner,
+                prefix: format!("tenant:{}:", tenant.id),
+            })),
+            (storage, _) => storage,
+        }
+    }
+
+    fn key(&self, response_id: &str) -> String {
+        format!("{}{}", self.prefix, response_id)
+    }
+}
+
+#[async_trait]
+impl StateStorage for TenantStateStorage {
+    async fn put(&self, mut state: OpenAIConversationState) -> Result<(), StateStorageError> {
+        state.response_id = self.key(&state.response_id);
+        self.inner.put(state).await
+    }
+
+    async fn get(&self, response_id: &str) -> Result<OpenAIConversationState, StateStorageError> {
+        let mut state = self
+            .inner
+            .get(&self.key(response_id))
+            .await
+            .map_err(|e| match e {
+                StateStorageError::NotFound(_) => {
+                    StateStorageError::NotFound(response_id.to_string())
+                }
+                e => e,
+            })?;
+        state.response_id = response_id.to_string();
+        Ok(state)
+    }
+
+    async fn exists(&self, response_id: &str) -> Result<bool, StateStorageError> {
+        self.inner.exists(&self.key(response_id)).await
+    }
+
+    async fn delete(&self, response_id: &str) -> Result<(), StateStorageError> {
+        self.inner.delete(&self.key(response_id)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::memory::MemoryConversationalStorage;
+    use hyper::header::HeaderValue;
+
+    fn tenancy(required: bool) -> Tenancy {
+        Tenancy::new(Some(&TenancyConfig {
+            header: None,
+            required,
+            tenants: vec![TenantConfig {
+                id: "search".to_string(),
+                models: Some(vec!["openai/*".to_string()]),
+                max_concurrent_requests: None,
+                routing_preferences: None,
+                output_token_budget: None,
+            }],
+        }))
+    }
+
+    #[test]
+    fn resolves_tenant_from_header() {
+        let mut headers = HeaderMap::new();
+        assert!(tenancy(false).resolve(&headers).unwrap().is_none());
+        assert!(matches!(
+            tenancy(true).resolve(&headers),
+            Err(BrightStaffError::TenantRequired(_))
+        ));
+
+        headers.insert(ARCH_TENANT_HEADER, HeaderValue::from_static("search"));
+        let tenant = tenancy(true).resolve(&headers).unwrap().unwrap();
+        assert!(tenant.allows_model("openai/gpt-4o"));
+        assert!(!tenant.allows_model("anthropic/claude-sonnet-4"));
+
+        headers.insert(ARCH_TENANT_HEADER, HeaderValue::from_static("ads"));
+        assert!(matches!(
+            tenancy(false).resolve(&headers),
+            Err(BrightStaffError::UnknownTenant(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn tenant_state_is_namespaced() {
+        let shared: Arc<dyn StateStorage> = Arc::new(MemoryConversationalStorage::new());
+        let tenant = tenancy(false).tenants["search"].clone();
+        let scoped = TenantStateStorage::scope(Some(shared.clone()), Some(&tenant)).unwrap();
+
+        scoped
+            .put(OpenAIConversationState {
+                response_id: "resp_1".to_string(),
+                input_items: vec![],
+                created_at: 0,
+                model: "gpt-4o".to_string(),
+                provider: "openai".to_string(),
+                response: None,
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(scoped.get("resp_1").await.unwrap().response_id, "resp_1");
+        assert!(!shared.exists("resp_1").await.unwrap());
+        assert!(matches!(
+            shared.get("resp_1").await,
+            Err(StateStorageError::NotFound(_))
+        ));
+    }
+}
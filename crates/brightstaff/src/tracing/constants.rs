@@ -92,6 +92,9 @@ pub mod llm {
     /// (OpenAI `completion_tokens_details.reasoning_tokens`, Google `thoughts_token_count`)
     pub const REASONING_TOKENS: &str = "llm.usage.reasoning_tokens";
 
+    /// Estimated USD cost of the call from the configured pricing table
+    pub const COST_USD: &str = "llm.usage.cost_usd";
+
     /// Temperature parameter used
     pub const TEMPERATURE: &str = "llm.temperature";
 
@@ -106,6 +109,12 @@ pub mod llm {
 
     /// Preview of the user message (truncated)
     pub const USER_MESSAGE_PREVIEW: &str = "llm.user_message_preview";
+
+    /// Deprecated model the client asked for, when it was rewritten to its replacement
+    pub const DEPRECATED_MODEL: &str = "llm.deprecated_model";
+
+    /// Set when the client hung up mid-stream and the upstream call was aborted
+    pub const CLIENT_DISCONNECTED: &str = "llm.client_disconnected";
 }
 
 // =============================================================================
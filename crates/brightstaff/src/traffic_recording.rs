@@ -0,0 +1,283 @@
+//! Record and replay of upstream traffic.
+//!
+//! In `record` mode every exchange forwarded upstream is saved under
+//! `traffic_recording.directory` as one JSON file: the model, the request body
+//! with `redact_fields` blanked, the response status and content type, and
+//! each response chunk with the time it arrived. Request headers, and with
+//! them credentials, are never saved. In `replay` mode requests are answered
+//! from those files with the original chunking and timing, so transform
+//! changes can be regression-tested against real traffic shapes.
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use base64::Engine as _;
+use bytes::Bytes;
+use common::configuration::{TrafficRecordingMode, TrafficRecordingSettings};
+use futures::StreamExt;
+use hyper::header::CONTENT_TYPE;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use tracing::{debug, warn};
+
+const REDACTED: &str = "[redacted]";
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Recording {
+    model: String,
+    request: Value,
+    status: u16,
+    content_type: Option<String>,
+    chunks: Vec<Chunk>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Chunk {
+    /// Milliseconds after the response headers arrived.
+    after_ms: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    text: Option<String>,
+    /// Set instead of `text` for chunks that are not valid UTF-8.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    base64: Option<String>,
+}
+
+impl Chunk {
+    fn new(after: Duration, data: &Bytes) -> Self {
+        let (text, base64) = match std::str::from_utf8(data) {
+            Ok(text) => (Some(text.to_string()), None),
+            Err(_) => (
+                None,
+                Some(base64::engine::general_purpose::STANDARD.encode(data)),
+            ),
+        };
+        Self {
+            after_ms: after.as_millis() as u64,
+            text,
+            base64,
+        }
+    }
+
+    fn data(&self) -> Bytes {
+        match (&self.text, &self.base64) {
+            (Some(text), _) => Bytes::from(text.clone()),
+            (None, Some(encoded)) => base64::engine::general_purpose::STANDARD
+                .decode(encoded)
+                .map(Bytes::from)
+                .unwrap_or_default(),
+            (None, None) => Bytes::new(),
+        }
+    }
+}
+
+pub struct TrafficRecorder {
+    mode: TrafficRecordingMode,
+    directory: PathBuf,
+    redact_fields: Vec<String>,
+}
+
+impl TrafficRecorder {
+    /// Creates the recording directory in `record` mode.
+    pub fn new(settings: &TrafficRecordingSettings) -> std::io::Result<Self> {
+        let directory = PathBuf::from(&settings.directory);
+        if settings.mode == TrafficRecordingMode::Record {
+            std::fs::create_dir_all(&directory)?;
+        }
+        Ok(Self {
+            mode: settings.mode,
+            directory,
+            redact_fields: settings.redact_fields.clone().unwrap_or_default(),
+        })
+    }
+
+    pub fn mode(&self) -> TrafficRecordingMode {
+        self.mode
+    }
+
+    /// Recordings are named by the model and the exact request body, so a
+    /// replayed request must match the recorded one byte for byte.
+    fn path(&self, model: &str, body: &[u8]) -> PathBuf {
+        let digest = Sha256::new()
+            .chain_update(model.as_bytes())
+            .chain_update([0])
+            .chain_update(body)
+            .finalize();
+        let name: String = digest[..16].iter().map(|b| format!("{b:02x}")).collect();
+        self.directory.join(format!("{name}.json"))
+    }
+
+    fn sanitized_request(&self, body: &[u8]) -> Value {
+        let Ok(mut request) = serde_json::from_slice::<Value>(body) else {
+            return Value::String(String::from_utf8_lossy(body).into_owned());
+        };
+        if let Some(fields) = request.as_object_mut() {
+            for field in &self.redact_fields {
+                if let Some(value) = fields.get_mut(field) {
+                    *value = Value::String(REDACTED.to_string());
+                }
+            }
+        }
+        request
+    }
+
+    /// `response` passed through unchanged, saved once its body has been read
+    /// to the end. Bodies cut short by an error are not saved.
+    pub fn record(
+        &self,
+        model: &str,
+        body: &[u8],
+        response: reqwest::Response,
+    ) -> reqwest::Response {
+        let path = self.path(model, body);
+        let recording = Recording {
+            model: model.to_string(),
+            request: self.sanitized_request(body),
+            status: response.status().as_u16(),
+            content_type: response
+                .headers()
+                .get(CONTENT_TYPE)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string),
+            chunks: Vec::new(),
+        };
+        let mut builder = hyper::http::Response::builder().status(response.status());
+        if let Some(headers) = builder.headers_mut() {
+            *headers = response.headers().clone();
+        }
+
+        let started = Instant::now();
+        let upstream = response.bytes_stream().boxed();
+        let stream =
+            futures::stream::unfold(Some((upstream, recording, path)), move |state| async move {
+                let (mut upstream, mut recording, path) = state?;
+                match upstream.next().await {
+                    Some(Ok(chunk)) => {
+                        recording.chunks.push(Chunk::new(started.elapsed(), &chunk));
+                        Some((Ok(chunk), Some((upstream, recording, path))))
+                    }
+                    Some(Err(error)) => Some((Err(error), None)),
+                    None => {
+                        save(&path, &recording).await;
+                        None
+                    }
+                }
+            });
+        reqwest::Response::from(builder.body(reqwest::Body::wrap_stream(stream)).unwrap())
+    }
+
+    /// The recorded response to this request, replayed with its original
+    /// chunk timing, or `None` when nothing was recorded for it.
+    pub async fn replay(&self, model: &str, body: &[u8]) -> Option<reqwest::Response> {
+        let path = self.path(model, body);
+        let contents = tokio::fs::read(&path).await.ok()?;
+        let recording: Recording = match serde_json::from_slice(&contents) {
+            Ok(recording) => recording,
+            Err(error) => {
+                warn!(path = %path.display(), error = %error, "unreadable traffic recording");
+                return None;
+            }
+        };
+        debug!(path = %path.display(), model = %model, "replaying recorded response");
+
+        let started = tokio::time::Instant::now();
+        let chunks = futures::stream::iter(recording.chunks).then(move |chunk| async move {
+            tokio::time::sleep_until(started + Duration::from_millis(chunk.after_ms)).await;
+            Ok::<_, std::io::Error>(chunk.data())
+        });
+        let mut builder = hyper::http::Response::builder().status(recording.status);
+        if let Some(content_type) = recording.content_type {
+            builder = builder.header(CONTENT_TYPE, content_type);
+        }
+        let response = builder.body(reqwest::Body::wrap_stream(chunks)).ok()?;
+        Some(reqwest::Response::from(response))
+    }
+}
+
+async fn save(path: &Path, recording: &Recording) {
+    let result = match serde_json::to_vec_pretty(recording) {
+        Ok(contents) => tokio::fs::write(path, contents).await,
+        Err(error) => Err(error.into()),
+    };
+    match result {
+        Ok(()) => debug!(path = %path.display(), "recorded upstream exchange"),
+        Err(error) => {
+            warn!(path = %path.display(), error = %error, "failed to save traffic recording")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recorder(mode: TrafficRecordingMode, directory: &std::path::Path) -> TrafficRecorder {
+        TrafficRecorder::new(&TrafficRecordingSettings {
+            mode,
+            directory: directory.to_string_lossy().into_owned(),
+            redact_fields: Some(vec!["user".to_string()]),
+        })
+        .unwrap()
+    }
+
+    fn streamed(chunks: &[&'static str]) -> reqwest::Response {
+        let chunks: Vec<Result<Bytes, std::io::Error>> = chunks
+            .iter()
+            .map(|c| Ok(Bytes::from_static(c.as_bytes())))
+            .collect();
+        let response = hyper::http::Response::builder()
+            .header(CONTENT_TYPE, "text/event-stream")
+            .body(reqwest::Body::wrap_stream(futures::stream::iter(chunks)))
+            .unwrap();
+        reqwest::Response::from(response)
+    }
+
+    #[tokio::test]
+    async fn recorded_exchanges_replay_chunk_for_chunk() {
+        let directory =
+            std::env::temp_dir().join(format!("plano-recording-{}", uuid::Uuid::new_v4()));
+        let body = br#"{"model":"gpt-4o","user":"alice@example.com","stream":true}"#;
+
+        let writer = recorder(TrafficRecordingMode::Record, &directory);
+        let response = writer.record(
+            "openai/gpt-4o",
+            body,
+            streamed(&["data: 1\n\n", "data: 2\n\n"]),
+        );
+        assert_eq!(response.text().await.unwrap(), "data: 1\n\ndata: 2\n\n");
+
+        let saved: Recording =
+            serde_json::from_slice(&std::fs::read(writer.path("openai/gpt-4o", body)).unwrap())
+                .unwrap();
+        assert_eq!(saved.request["user"], REDACTED);
+        assert_eq!(saved.request["model"], "gpt-4o");
+        assert_eq!(saved.chunks.len(), 2);
+
+        let replayer = recorder(TrafficRecordingMode::Replay, &directory);
+        let replayed = replayer.replay("openai/gpt-4o", body).await.unwrap();
+        assert_eq!(replayed.headers()[CONTENT_TYPE], "text/event-stream");
+        let chunks: Vec<Bytes> = replayed
+            .bytes_stream()
+            .map(|chunk| chunk.unwrap())
+            .collect()
+            .await;
+        assert_eq!(chunks, vec!["data: 1\n\n", "data: 2\n\n"]);
+
+        assert!(replayer.replay("openai/gpt-4o-mini", body).await.is_none());
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn binary_chunks_round_trip() {
+        let data = Bytes::from_static(&[0xff, 0x00, 0x9f]);
+        let chunk = Chunk::new(Duration::from_millis(12), &data);
+        assert!(chunk.text.is_none());
+        assert_eq!(chunk.data(), data);
+        assert_eq!(
+            Chunk::new(Duration::ZERO, &Bytes::from("hi"))
+                .text
+                .as_deref(),
+            Some("hi")
+        );
+    }
+}
@@ -0,0 +1,429 @@
+//! Hourly usage export for billing analytics.
+//!
+//! Every completed model call adds its token usage and cost to an in-memory
+//! total for its hour, client key and model. Once an hour is over its totals
+//! are written as one file, `usage-YYYY-MM-DDTHH.csv` (or `.parquet`), to the
+//! configured directory and/or S3 location. Client keys are exported as a
+//! short hash, or as the tenant id when the request belongs to a tenant.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use common::configuration::{UsageExportFormat, UsageExportSettings};
+use hyper::HeaderMap;
+use object_store::aws::AmazonS3Builder;
+use object_store::local::LocalFileSystem;
+use object_store::path::Path;
+use object_store::ObjectStore;
+use parquet::data_type::{ByteArray, ByteArrayType, DoubleType, Int64Type};
+use parquet::file::properties::WriterProperties;
+use parquet::file::writer::SerializedFileWriter;
+use parquet::schema::parser::parse_message_type;
+use sha2::{Digest, Sha256};
+use tracing::{debug, warn};
+
+const DEFAULT_KEY_HEADER: &str = "authorization";
+const ANONYMOUS_KEY: &str = "anonymous";
+/// How often finished hours are looked for.
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+const SECONDS_PER_HOUR: i64 = 3600;
+const CSV_HEADER: &str = "hour,key,model,requests,prompt_tokens,completion_tokens,cost_usd\n";
+const PARQUET_SCHEMA: &str = "
+    message usage {
+        REQUIRED BYTE_ARRAY hour (UTF8);
+        REQUIRED BYTE_ARRAY key (UTF8);
+        REQUIRED BYTE_ARRAY model (UTF8);
+        REQUIRED INT64 requests;
+        REQUIRED INT64 prompt_tokens;
+        REQUIRED INT64 completion_tokens;
+        REQUIRED DOUBLE cost_usd;
+    }
+";
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct UsageTotals {
+    requests: u64,
+    prompt_tokens: u64,
+    completion_tokens: u64,
+    cost_usd: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct UsageRow {
+    key: String,
+    model: String,
+    totals: UsageTotals,
+}
+
+/// Usage totals not yet exported, keyed by hour (as hours since the epoch),
+/// client key and model.
+pub struct UsageLedger {
+    key_header: String,
+    totals: Mutex<HashMap<(i64, String, String), UsageTotals>>,
+}
+
+impl UsageLedger {
+    pub fn new(settings: &UsageExportSettings) -> Self {
+        Self {
+            key_header: settings
+                .key_header
+                .clone()
+                .unwrap_or_else(|| DEFAULT_KEY_HEADER.to_string()),
+            totals: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The key a request's usage is exported under.
+    pub fn client_key(&self, headers: &HeaderMap, tenant: Option<&str>) -> String {
+        if let Some(tenant) = tenant {
+            return tenant.to_string();
+        }
+        let key = headers
+            .get(self.key_header.as_str())
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.strip_prefix("Bearer ").unwrap_or(v))
+            .filter(|v| !v.is_empty());
+        match key {
+            Some(key) => {
+                let digest = Sha256::digest(key.as_bytes());
+                digest[..6].iter().map(|b| format!("{b:02x}")).collect()
+            }
+            None => ANONYMOUS_KEY.to_string(),
+        }
+    }
+
+    pub fn record(
+        &self,
+        key: &str,
+        model: &str,
+        prompt_tokens: u64,
+        completion_tokens: u64,
+        cost_usd: Option<f64>,
+    ) {
+        let hour = Utc::now().timestamp().div_euclid(SECONDS_PER_HOUR);
+        self.add(
+            hour,
+            key,
+            model,
+            UsageTotals {
+                requests: 1,
+                prompt_tokens,
+                completion_tokens,
+                cost_usd: cost_usd.unwrap_or(0.0),
+            },
+        );
+    }
+
+    fn add(&self, hour: i64, key: &str, model: &str, usage: UsageTotals) {
+        let mut totals = self.totals.lock().unwrap();
+        let entry = totals
+            .entry((hour, key.to_string(), model.to_string()))
+            .or_default();
+        entry.requests += usage.requests;
+        entry.prompt_tokens += usage.prompt_tokens;
+        entry.completion_tokens += usage.completion_tokens;
+        entry.cost_usd += usage.cost_usd;
+    }
+
+    /// Remove and return the totals of every hour before `current_hour`.
+    fn take_finished(&self, current_hour: i64) -> BTreeMap<i64, Vec<UsageRow>> {
+        let mut totals = self.totals.lock().unwrap();
+        let finished: Vec<_> = totals
+            .keys()
+            .filter(|(hour, _, _)| *hour < current_hour)
+            .cloned()
+            .collect();
+        let mut hours: BTreeMap<i64, Vec<UsageRow>> = BTreeMap::new();
+        for entry in finished {
+            let usage = totals.remove(&entry).unwrap_or_default();
+            let (hour, key, model) = entry;
+            hours.entry(hour).or_default().push(UsageRow {
+                key,
+                model,
+                totals: usage,
+            });
+        }
+        for rows in hours.values_mut() {
+            rows.sort_by(|a, b| (&a.key, &a.model).cmp(&(&b.key, &b.model)));
+        }
+        hours
+    }
+
+    /// Put back rows whose export failed, to be retried.
+    fn restore(&self, hour: i64, rows: Vec<UsageRow>) {
+        for row in rows {
+            self.add(hour, &row.key, &row.model, row.totals);
+        }
+    }
+}
+
+fn hour_start(hour: i64) -> DateTime<Utc> {
+    DateTime::from_timestamp(hour * SECONDS_PER_HOUR, 0).unwrap_or_default()
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn render_csv(hour: &str, rows: &[UsageRow]) -> Vec<u8> {
+    let mut csv = CSV_HEADER.to_string();
+    for row in rows {
+        csv.push_str(&format!(
+            "{},{},{},{},{},{},{}\n",
+            hour,
+            csv_field(&row.key),
+            csv_field(&row.model),
+            row.totals.requests,
+            row.totals.prompt_tokens,
+            row.totals.completion_tokens,
+            row.totals.cost_usd,
+        ));
+    }
+    csv.into_bytes()
+}
+
+fn render_parquet(hour: &str, rows: &[UsageRow]) -> parquet::errors::Result<Vec<u8>> {
+    let hours: Vec<ByteArray> = rows.iter().map(|_| ByteArray::from(hour)).collect();
+    let keys: Vec<ByteArray> = rows
+        .iter()
+        .map(|r| ByteArray::from(r.key.as_str()))
+        .collect();
+    let models: Vec<ByteArray> = rows
+        .iter()
+        .map(|r| ByteArray::from(r.model.as_str()))
+        .collect();
+    let count = |value: fn(&UsageTotals) -> u64| -> Vec<i64> {
+        rows.iter().map(|row| value(&row.totals) as i64).collect()
+    };
+    let requests = count(|t| t.requests);
+    let prompt_tokens = count(|t| t.prompt_tokens);
+    let completion_tokens = count(|t| t.completion_tokens);
+    let costs: Vec<f64> = rows.iter().map(|row| row.totals.cost_usd).collect();
+
+    let schema = Arc::new(parse_message_type(PARQUET_SCHEMA)?);
+    let properties = Arc::new(WriterProperties::builder().build());
+    let mut buffer = Vec::new();
+    let mut writer = SerializedFileWriter::new(&mut buffer, schema, properties)?;
+    let mut row_group = writer.next_row_group()?;
+    for values in [&hours, &keys, &models] {
+        if let Some(mut column) = row_group.next_column()? {
+            column
+                .typed::<ByteArrayType>()
+                .write_batch(values, None, None)?;
+            column.close()?;
+        }
+    }
+    for values in [&requests, &prompt_tokens, &completion_tokens] {
+        if let Some(mut column) = row_group.next_column()? {
+            column
+                .typed::<Int64Type>()
+                .write_batch(values, None, None)?;
+            column.close()?;
+        }
+    }
+    if let Some(mut column) = row_group.next_column()? {
+        column
+            .typed::<DoubleType>()
+            .write_batch(&costs, None, None)?;
+        column.close()?;
+    }
+    row_group.close()?;
+    writer.close()?;
+    Ok(buffer)
+}
+
+struct Destination {
+    store: Arc<dyn ObjectStore>,
+    prefix: Option<String>,
+}
+
+pub struct UsageExporter {
+    ledger: Arc<UsageLedger>,
+    format: UsageExportFormat,
+    destinations: Vec<Destination>,
+}
+
+impl UsageExporter {
+    /// Creates the export directory, if one is configured.
+    pub fn new(
+        settings: &UsageExportSettings,
+        ledger: Arc<UsageLedger>,
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let mut destinations = Vec::new();
+        if let Some(directory) = &settings.directory {
+            std::fs::create_dir_all(directory)?;
+            destinations.push(Destination {
+                store: Arc::new(LocalFileSystem::new_with_prefix(directory)?),
+                prefix: None,
+            });
+        }
+        if let Some(url) = &settings.s3_url {
+            let location = url.strip_prefix("s3://").unwrap_or(url);
+            let (bucket, prefix) = match location.split_once('/') {
+                Some((bucket, prefix)) => (bucket, Some(prefix.trim_matches('/').to_string())),
+                None => (location, None),
+            };
+            destinations.push(Destination {
+                store: Arc::new(
+                    AmazonS3Builder::from_env()
+                        .with_bucket_name(bucket)
+                        .build()?,
+                ),
+                prefix: prefix.filter(|p| !p.is_empty()),
+            });
+        }
+        Ok(Self {
+            ledger,
+            format: settings.format,
+            destinations,
+        })
+    }
+
+    pub fn spawn(self) {
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(CHECK_INTERVAL).await;
+                let current_hour = Utc::now().timestamp().div_euclid(SECONDS_PER_HOUR);
+                self.export_finished_hours(current_hour).await;
+            }
+        });
+    }
+
+    async fn export_finished_hours(&self, current_hour: i64) {
+        for (hour, rows) in self.ledger.take_finished(current_hour) {
+            let start = hour_start(hour);
+            let hour_label = start.format("%Y-%m-%dT%H:00:00Z").to_string();
+            let (extension, body) = match self.format {
+                UsageExportFormat::Csv => ("csv", Ok(render_csv(&hour_label, &rows))),
+                UsageExportFormat::Parquet => ("parquet", render_parquet(&hour_label, &rows)),
+            };
+            let body = match body {
+                Ok(body) => Bytes::from(body),
+                Err(error) => {
+                    warn!(error = %error, hour = %hour_label, "failed to encode usage export");
+                    continue;
+                }
+            };
+            let name = format!("usage-{}.{}", start.format("%Y-%m-%dT%H"), extension);
+            // Files are named by hour, so a retry rewrites the same file everywhere.
+            if self.write(&name, body).await {
+                debug!(file = %name, rows = rows.len(), "exported usage");
+            } else {
+                self.ledger.restore(hour, rows);
+            }
+        }
+    }
+
+    async fn write(&self, name: &str, body: Bytes) -> bool {
+        let mut written = true;
+        for destination in &self.destinations {
+            let path = match &destination.prefix {
+                Some(prefix) => Path::from(format!("{prefix}/{name}")),
+                None => Path::from(name),
+            };
+            if let Err(error) = destination.store.put(&path, body.clone().into()).await {
+                warn!(error = %error, path = %path, "failed to write usage export, will retry");
+                written = false;
+            }
+        }
+        written
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+
+    fn settings(directory: &std::path::Path, format: UsageExportFormat) -> UsageExportSettings {
+        UsageExportSettings {
+            directory: Some(directory.to_string_lossy().into_owned()),
+            s3_url: None,
+            format,
+            key_header: None,
+        }
+    }
+
+    fn usage(prompt_tokens: u64, completion_tokens: u64, cost_usd: f64) -> UsageTotals {
+        UsageTotals {
+            requests: 1,
+            prompt_tokens,
+            completion_tokens,
+            cost_usd,
+        }
+    }
+
+    #[test]
+    fn client_keys_are_hashed_unless_a_tenant_is_known() {
+        let ledger = UsageLedger::new(&UsageExportSettings {
+            directory: None,
+            s3_url: None,
+            format: UsageExportFormat::Csv,
+            key_header: None,
+        });
+        let mut headers = HeaderMap::new();
+        assert_eq!(ledger.client_key(&headers, None), ANONYMOUS_KEY);
+        headers.insert("authorization", "Bearer sk-secret".parse().unwrap());
+        let hashed = ledger.client_key(&headers, None);
+        assert_eq!(hashed.len(), 12);
+        assert!(!hashed.contains("secret"));
+        assert_eq!(ledger.client_key(&headers, Some("acme")), "acme");
+    }
+
+    #[tokio::test]
+    async fn finished_hours_are_written_once_as_csv() {
+        let directory = std::env::temp_dir().join(format!("plano-usage-{}", uuid::Uuid::new_v4()));
+        let settings = settings(&directory, UsageExportFormat::Csv);
+        let ledger = Arc::new(UsageLedger::new(&settings));
+        let exporter = UsageExporter::new(&settings, Arc::clone(&ledger)).unwrap();
+
+        // 2024-01-01T10:00:00Z
+        let hour = 1_704_103_200 / SECONDS_PER_HOUR;
+        ledger.add(hour, "acme", "openai/gpt-4o", usage(100, 20, 0.5));
+        ledger.add(hour, "acme", "openai/gpt-4o", usage(50, 10, 0.25));
+        ledger.add(hour, "beta", "openai/gpt-4o,mini", usage(5, 1, 0.0));
+        ledger.add(hour + 1, "acme", "openai/gpt-4o", usage(1, 1, 0.0));
+
+        exporter.export_finished_hours(hour + 1).await;
+        let csv = std::fs::read_to_string(directory.join("usage-2024-01-01T10.csv")).unwrap();
+        assert_eq!(
+            csv,
+            format!(
+                "{CSV_HEADER}2024-01-01T10:00:00Z,acme,openai/gpt-4o,2,150,30,0.75\n\
+                 2024-01-01T10:00:00Z,beta,\"openai/gpt-4o,mini\",1,5,1,0\n"
+            )
+        );
+        // The current hour stays in the ledger until it is over.
+        assert_eq!(ledger.take_finished(hour + 1).len(), 0);
+        assert_eq!(ledger.take_finished(hour + 2).len(), 1);
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn parquet_files_hold_every_row() {
+        let rows = vec![
+            UsageRow {
+                key: "acme".to_string(),
+                model: "openai/gpt-4o".to_string(),
+                totals: usage(100, 20, 0.5),
+            },
+            UsageRow {
+                key: "beta".to_string(),
+                model: "anthropic/claude-sonnet-4".to_string(),
+                totals: usage(10, 2, 0.1),
+            },
+        ];
+        let file = render_parquet("2024-01-01T10:00:00Z", &rows).unwrap();
+        let reader = SerializedFileReader::new(Bytes::from(file)).unwrap();
+        let metadata = reader.metadata().file_metadata();
+        assert_eq!(metadata.num_rows(), 2);
+        assert_eq!(metadata.schema_descr().num_columns(), 7);
+    }
+}
@@ -0,0 +1,173 @@
+//! Access tokens for `vertex_ai` providers with a service account key.
+//!
+//! Vertex AI only accepts OAuth2 access tokens, which expire after an hour.
+//! For each provider with `vertex_ai.service_account_key_file`, a background
+//! task mints a token at startup and again shortly before it expires.
+//! Requests carry the current token to the LLM gateway in
+//! [`ARCH_UPSTREAM_TOKEN_HEADER`]; the gateway sends it upstream as a bearer
+//! token in place of the provider's `access_key`.
+
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use common::configuration::{LlmProvider, LlmProviderType};
+use common::consts::ARCH_UPSTREAM_TOKEN_HEADER;
+use hermesllm::clients::gcp_auth::{AccessToken, ServiceAccountKey};
+use tracing::{info, warn};
+
+/// Wait before trying again after the token endpoint failed.
+const RETRY_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Default)]
+pub struct VertexTokens {
+    /// Current access token, by provider name.
+    tokens: RwLock<HashMap<String, String>>,
+}
+
+impl VertexTokens {
+    /// Load the service account key of every `vertex_ai` provider that has
+    /// one and start keeping its access token fresh.
+    pub fn spawn(
+        providers: &[LlmProvider],
+        http_client: reqwest::Client,
+    ) -> Result<Arc<Self>, Box<dyn std::error::Error + Send + Sync>> {
+        let tokens = Arc::new(VertexTokens::default());
+        for provider in providers {
+            if provider.provider_interface != LlmProviderType::VertexAI {
+                continue;
+            }
+            let Some(path) = provider
+                .vertex_ai
+                .as_ref()
+                .and_then(|v| v.service_account_key_file.as_ref())
+            else {
+                continue;
+            };
+            let contents = std::fs::read_to_string(path).map_err(|e| {
+                format!(
+                    "failed to read service account key '{}' for provider '{}': {}",
+                    path, provider.name, e
+                )
+            })?;
+            let key = ServiceAccountKey::from_json(&contents)
+                .map_err(|e| format!("provider '{}': {}", provider.name, e))?;
+            info!(provider = %provider.name, account = %key.client_email, "minting vertex ai access tokens");
+            tokio::spawn(Arc::clone(&tokens).refresh(
+                provider.name.clone(),
+                key,
+                http_client.clone(),
+            ));
+        }
+        Ok(tokens)
+    }
+
+    /// Set the token header for `provider` on an upstream request, replacing
+    /// any value the client sent.
+    pub fn apply(&self, provider: &str, headers: &mut hyper::HeaderMap) {
+        headers.remove(ARCH_UPSTREAM_TOKEN_HEADER);
+        let tokens = self.tokens.read().unwrap();
+        if let Some(value) = tokens
+            .get(provider)
+            .and_then(|token| hyper::header::HeaderValue::from_str(token).ok())
+        {
+            headers.insert(ARCH_UPSTREAM_TOKEN_HEADER, value);
+        }
+    }
+
+    async fn refresh(
+        self: Arc<Self>,
+        provider: String,
+        key: ServiceAccountKey,
+        http_client: reqwest::Client,
+    ) {
+        loop {
+            let wait = match mint(&http_client, &key).await {
+                Ok(token) => {
+                    let wait = Duration::from_secs(token.refresh_in(unix_now()).max(1));
+                    self.tokens
+                        .write()
+                        .unwrap()
+                        .insert(provider.clone(), token.token);
+                    wait
+                }
+                Err(error) => {
+                    warn!(provider = %provider, %error, "failed to mint vertex ai access token");
+                    RETRY_INTERVAL
+                }
+            };
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+async fn mint(
+    http_client: &reqwest::Client,
+    key: &ServiceAccountKey,
+) -> Result<AccessToken, Box<dyn std::error::Error + Send + Sync>> {
+    let now = unix_now();
+    let response = http_client
+        .post(&key.token_uri)
+        .header("content-type", "application/x-www-form-urlencoded")
+        .body(key.token_request_body(now)?)
+        .send()
+        .await?;
+    let status = response.status();
+    let body = response.bytes().await?;
+    if !status.is_success() {
+        return Err(format!(
+            "token endpoint returned {}: {}",
+            status,
+            String::from_utf8_lossy(&body)
+        )
+        .into());
+    }
+    Ok(AccessToken::from_response(&body, now)?)
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn apply_replaces_client_supplied_tokens() {
+        let tokens = VertexTokens::default();
+        tokens.tokens.write().unwrap().insert(
+            "vertex_ai/gemini-2.5-flash".to_string(),
+            "ya29.minted".to_string(),
+        );
+
+        let mut headers = hyper::HeaderMap::new();
+        headers.insert(ARCH_UPSTREAM_TOKEN_HEADER, "ya29.client".parse().unwrap());
+        tokens.apply("vertex_ai/gemini-2.5-flash", &mut headers);
+        assert_eq!(headers[ARCH_UPSTREAM_TOKEN_HEADER], "ya29.minted");
+
+        headers.insert(ARCH_UPSTREAM_TOKEN_HEADER, "ya29.client".parse().unwrap());
+        tokens.apply("openai/gpt-4o", &mut headers);
+        assert!(headers.get(ARCH_UPSTREAM_TOKEN_HEADER).is_none());
+    }
+
+    #[test]
+    fn missing_key_files_fail_startup() {
+        let provider = LlmProvider {
+            name: "vertex_ai/gemini-2.5-flash".to_string(),
+            provider_interface: LlmProviderType::VertexAI,
+            vertex_ai: Some(common::configuration::VertexAiSettings {
+                project_id: "my-project".to_string(),
+                location: "us-central1".to_string(),
+                service_account_key_file: Some("/nonexistent/sa.json".to_string()),
+                publisher: None,
+            }),
+            ..Default::default()
+        };
+        let error = VertexTokens::spawn(&[provider], reqwest::Client::new()).unwrap_err();
+        assert!(error.to_string().contains("/nonexistent/sa.json"));
+    }
+}
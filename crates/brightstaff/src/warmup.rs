@@ -0,0 +1,274 @@
+//! Provider warm-up.
+//!
+//! Upstream connections live in Envoy's pools behind the LLM gateway, so
+//! warm-up goes through the gateway: a `GET` on [`WARMUP_PATH`] makes the
+//! gateway open a connection (TLS included) to the hinted provider. With
+//! `probe_request` set, each provider also gets a one-token completion.
+//! Progress and results are reported by `GET /readyz`.
+
+use std::collections::HashSet;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+use common::configuration::{LlmProvider, LlmProviderType, WarmupSettings};
+use common::consts::{ARCH_PROVIDER_HINT_HEADER, CHAT_COMPLETIONS_PATH, WARMUP_PATH};
+use common::llm_providers::LlmProviders;
+use common::utils::parse_duration_ms;
+use futures::future::join_all;
+use reqwest::StatusCode;
+use serde::Serialize;
+use serde_json::json;
+use tracing::{info, warn};
+
+const DEFAULT_WARMUP_TIMEOUT: Duration = Duration::from_secs(10);
+/// Delay between attempts while the gateway itself is still starting.
+const GATEWAY_RETRY_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Outcome of warming one upstream.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProviderWarmup {
+    pub provider: String,
+    pub ok: bool,
+    pub latency_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct WarmupState {
+    /// Bumped by every run; results of a superseded run are discarded.
+    generation: u64,
+    running: bool,
+    results: Vec<ProviderWarmup>,
+}
+
+pub struct Warmup {
+    settings: Option<WarmupSettings>,
+    state: RwLock<WarmupState>,
+}
+
+impl Warmup {
+    pub fn new(settings: Option<WarmupSettings>) -> Self {
+        Warmup {
+            settings,
+            state: RwLock::new(WarmupState::default()),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.settings.is_some()
+    }
+
+    /// Ready once the latest warm-up run has finished, or always when disabled.
+    pub fn is_ready(&self) -> bool {
+        !self.state.read().unwrap().running
+    }
+
+    /// Body for `GET /readyz`.
+    pub fn readiness(&self) -> serde_json::Value {
+        let state = self.state.read().unwrap();
+        json!({
+            "status": if state.running { "warming_up" } else { "ready" },
+            "warmup": if self.is_enabled() { Some(&state.results) } else { None },
+        })
+    }
+
+    /// Warm every provider in the background. A run already in progress is
+    /// superseded. No-op when warm-up is not configured.
+    pub fn start(
+        self: &Arc<Self>,
+        providers: &LlmProviders,
+        http_client: reqwest::Client,
+        llm_provider_url: String,
+    ) {
+        let Some(settings) = self.settings.clone() else {
+            return;
+        };
+        let targets = warmup_targets(providers);
+        let generation = {
+            let mut state = self.state.write().unwrap();
+            state.generation += 1;
+            state.running = true;
+            state.generation
+        };
+        let warmup = Arc::clone(self);
+        tokio::spawn(async move {
+            let timeout = settings
+                .timeout
+                .as_deref()
+                .and_then(parse_duration_ms)
+                .map_or(DEFAULT_WARMUP_TIMEOUT, Duration::from_millis);
+            let probe = settings.probe_request.unwrap_or(false);
+            let results = join_all(targets.iter().map(|provider| {
+                warm_provider(&http_client, &llm_provider_url, provider, probe, timeout)
+            }))
+            .await;
+
+            let ready = results.iter().filter(|r| r.ok).count();
+            info!(ready, total = results.len(), "provider warm-up finished");
+            for result in results.iter().filter(|r| !r.ok) {
+                warn!(
+                    provider = %result.provider,
+                    status = ?result.status,
+                    error = ?result.error,
+                    "provider warm-up failed"
+                );
+            }
+
+            let mut state = warmup.state.write().unwrap();
+            if state.generation == generation {
+                state.running = false;
+                state.results = results;
+            }
+        });
+    }
+}
+
+/// One provider per upstream connection pool, skipping internal models.
+fn warmup_targets(providers: &LlmProviders) -> Vec<Arc<LlmProvider>> {
+    let mut seen = HashSet::new();
+    let mut targets: Vec<_> = providers
+        .iter()
+        .filter(|(key, provider)| {
+            provider.internal != Some(true)
+                && provider.provider_interface != LlmProviderType::Mock
+                && *key == &provider.name
+        })
+        .map(|(_, provider)| Arc::clone(provider))
+        .collect();
+    targets.sort_by(|a, b| a.name.cmp(&b.name));
+    targets.retain(|provider| seen.insert(upstream_key(provider)));
+    targets
+}
+
+/// The gateway's routing key for a provider, which names its Envoy cluster.
+fn upstream_key(provider: &LlmProvider) -> String {
+    provider
+        .cluster_name
+        .clone()
+        .unwrap_or_else(|| provider.provider_interface.to_string())
+}
+
+async fn warm_provider(
+    http_client: &reqwest::Client,
+    llm_provider_url: &str,
+    provider: &LlmProvider,
+    probe: bool,
+    timeout: Duration,
+) -> ProviderWarmup {
+    let start = Instant::now();
+    let deadline = start + timeout;
+    let mut result = send_until_deadline(deadline, || {
+        http_client
+            .get(format!("{llm_provider_url}{WARMUP_PATH}"))
+            .header(ARCH_PROVIDER_HINT_HEADER, &provider.name)
+    })
+    .await
+    .map(|status| (status, is_connected(status)));
+
+    if probe && matches!(result, Ok((_, true))) {
+        let model = provider.model.as_deref().unwrap_or(&provider.name);
+        let body = json!({
+            "model": model.split_once('/').map_or(model, |(_, m)| m),
+            "messages": [{"role": "user", "content": "ping"}],
+            "max_tokens": 1,
+        });
+        result = send_until_deadline(deadline, || {
+            http_client
+                .post(format!("{llm_provider_url}{CHAT_COMPLETIONS_PATH}"))
+                .header(ARCH_PROVIDER_HINT_HEADER, &provider.name)
+                .json(&body)
+        })
+        .await
+        .map(|status| (status, status.is_success()));
+    }
+
+    let (status, ok, error) = match result {
+        Ok((status, ok)) => (Some(status.as_u16()), ok, None),
+        Err(error) => (None, false, Some(error)),
+    };
+    ProviderWarmup {
+        provider: provider.name.clone(),
+        ok,
+        latency_ms: start.elapsed().as_millis() as u64,
+        status,
+        error,
+    }
+}
+
+/// Send the request, retrying connection failures to the gateway (which may
+/// still be starting alongside brightstaff) until `deadline`.
+async fn send_until_deadline(
+    deadline: Instant,
+    request: impl Fn() -> reqwest::RequestBuilder,
+) -> Result<StatusCode, String> {
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Err("timed out".to_string());
+        }
+        match request().timeout(remaining).send().await {
+            Ok(response) => return Ok(response.status()),
+            Err(e) if e.is_connect() => tokio::time::sleep(GATEWAY_RETRY_INTERVAL).await,
+            Err(e) => return Err(e.to_string()),
+        }
+    }
+}
+
+/// Any answer from the provider proves the connection; Envoy reports its own
+/// failure to connect as 502/503/504.
+fn is_connected(status: StatusCode) -> bool {
+    !matches!(
+        status,
+        StatusCode::BAD_GATEWAY | StatusCode::SERVICE_UNAVAILABLE | StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::configuration::LlmProviderType;
+
+    fn provider(name: &str, interface: LlmProviderType, internal: bool) -> LlmProvider {
+        LlmProvider {
+            name: name.to_string(),
+            model: Some(name.to_string()),
+            provider_interface: interface,
+            internal: internal.then_some(true),
+            default: Some(false),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn one_target_per_upstream_without_internal_models() {
+        let providers = LlmProviders::try_from(vec![
+            provider("openai/gpt-4o", LlmProviderType::OpenAI, false),
+            provider("openai/gpt-4o-mini", LlmProviderType::OpenAI, false),
+            provider(
+                "anthropic/claude-sonnet-4",
+                LlmProviderType::Anthropic,
+                false,
+            ),
+            provider("plano/Arch-Router", LlmProviderType::Plano, true),
+        ])
+        .unwrap();
+        let names: Vec<_> = warmup_targets(&providers)
+            .iter()
+            .map(|p| p.name.clone())
+            .collect();
+        assert_eq!(names, ["anthropic/claude-sonnet-4", "openai/gpt-4o"]);
+    }
+
+    #[test]
+    fn readiness_without_warmup_is_ready() {
+        let warmup = Warmup::new(None);
+        assert!(warmup.is_ready());
+        assert_eq!(
+            warmup.readiness(),
+            json!({"status": "ready", "warmup": null})
+        );
+    }
+}
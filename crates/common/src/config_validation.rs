@@ -0,0 +1,1162 @@
+//! Validation pass over the plano configuration. Problems are reported with
+//! the YAML path and source line they come from, instead of surfacing as a
+//! bare deserialization error or panic.
+
+use std::collections::HashSet;
+use std::fmt;
+
+use serde_yaml::Value;
+
+use crate::configuration::{Configuration, LlmProviderType};
+use crate::model_aliases::{compile_pattern, AliasResolution, ModelAliasTable};
+use crate::utils::parse_duration_ms;
+
+/// Unknown keys this close to a known key are treated as typos and rejected.
+const MAX_TYPO_DISTANCE: usize = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigIssue {
+    pub severity: Severity,
+    /// Path to the offending field, e.g. `model_providers[0].model`.
+    pub path: String,
+    /// 1-based line in the source document, when it could be located.
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.severity)?;
+        if let Some(line) = self.line {
+            write!(f, " at line {}", line)?;
+        }
+        if !self.path.is_empty() {
+            write!(f, ": {}", self.path)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+/// Outcome of [`validate_config`]: the parsed configuration (when it could be
+/// deserialized) and every issue found.
+#[derive(Debug, Default)]
+pub struct ConfigReport {
+    pub config: Option<Configuration>,
+    pub issues: Vec<ConfigIssue>,
+}
+
+impl ConfigReport {
+    pub fn has_errors(&self) -> bool {
+        self.errors().next().is_some()
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &ConfigIssue> {
+        self.issues
+            .iter()
+            .filter(|issue| issue.severity == Severity::Error)
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &ConfigIssue> {
+        self.issues
+            .iter()
+            .filter(|issue| issue.severity == Severity::Warning)
+    }
+
+    /// The parsed configuration, or the errors that prevent using it.
+    pub fn into_result(self) -> Result<Configuration, ConfigErrors> {
+        match self.config {
+            Some(config) if !self.has_errors() => Ok(config),
+            _ => Err(ConfigErrors(
+                self.issues
+                    .into_iter()
+                    .filter(|issue| issue.severity == Severity::Error)
+                    .collect(),
+            )),
+        }
+    }
+}
+
+/// The errors of a rejected configuration, one per line when displayed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigErrors(pub Vec<ConfigIssue>);
+
+impl fmt::Display for ConfigErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_issues(f, &self.0)
+    }
+}
+
+impl std::error::Error for ConfigErrors {}
+
+fn write_issues(f: &mut fmt::Formatter<'_>, issues: &[ConfigIssue]) -> fmt::Result {
+    for (i, issue) in issues.iter().enumerate() {
+        if i > 0 {
+            writeln!(f)?;
+        }
+        write!(f, "{}", issue)?;
+    }
+    Ok(())
+}
+
+impl fmt::Display for ConfigReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_issues(f, &self.issues)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+fn render_path(path: &[Segment]) -> String {
+    let mut rendered = String::new();
+    for segment in path {
+        match segment {
+            Segment::Key(key) => {
+                if !rendered.is_empty() {
+                    rendered.push('.');
+                }
+                rendered.push_str(key);
+            }
+            Segment::Index(index) => rendered.push_str(&format!("[{}]", index)),
+        }
+    }
+    rendered
+}
+
+/// Parse and validate a configuration document.
+///
+/// Checks, in order: YAML syntax, the shape expected by [`Configuration`]
+/// (missing fields, wrong types), unknown keys (rejected when they look like
+/// a typo of a known key, reported as warnings otherwise) and cross-field
+/// rules such as value ranges and model references.
+pub fn validate_config(contents: &str) -> ConfigReport {
+    let mut report = ConfigReport::default();
+
+    let source: Value = match serde_yaml::from_str(contents) {
+        Ok(source) => source,
+        Err(err) => {
+            report.issues.push(ConfigIssue {
+                severity: Severity::Error,
+                path: String::new(),
+                line: err.location().map(|l| l.line()),
+                message: strip_location(&err.to_string()),
+            });
+            return report;
+        }
+    };
+
+    let deserializer = serde_yaml::Deserializer::from_str(contents);
+    let config: Configuration = match serde_path_to_error::deserialize(deserializer) {
+        Ok(config) => config,
+        Err(err) => {
+            let path = err.path().to_string();
+            let err = err.into_inner();
+            report.issues.push(ConfigIssue {
+                severity: Severity::Error,
+                path: if path == "." { String::new() } else { path },
+                line: err.location().map(|l| l.line()),
+                message: strip_location(&err.to_string()),
+            });
+            return report;
+        }
+    };
+
+    let mut issues = Vec::new();
+    if let Ok(known) = serde_yaml::to_value(&config) {
+        check_unknown_keys(&source, &known, &mut Vec::new(), &mut issues);
+    }
+    check_semantics(&config, &mut issues);
+
+    report.issues = issues
+        .into_iter()
+        .map(|(severity, path, message)| ConfigIssue {
+            severity,
+            line: locate_line(contents, &path),
+            path: render_path(&path),
+            message,
+        })
+        .collect();
+    report.config = Some(config);
+    report
+}
+
+/// serde_yaml appends "at line X column Y" to its messages; the line is
+/// reported separately.
+fn strip_location(message: &str) -> String {
+    match message.rfind(" at line ") {
+        Some(idx) => message[..idx].to_string(),
+        None => message.to_string(),
+    }
+}
+
+type RawIssue = (Severity, Vec<Segment>, String);
+
+/// Compare the source document with the re-serialized configuration: keys
+/// that did not survive the round trip were ignored during deserialization.
+fn check_unknown_keys(
+    source: &Value,
+    known: &Value,
+    path: &mut Vec<Segment>,
+    issues: &mut Vec<RawIssue>,
+) {
+    match (source, known) {
+        (Value::Mapping(source), Value::Mapping(known)) => {
+            for (key, value) in source {
+                let Some(key) = key.as_str() else {
+                    continue;
+                };
+                path.push(Segment::Key(key.to_string()));
+                match known.get(key) {
+                    Some(known_value) => check_unknown_keys(value, known_value, path, issues),
+                    None if value.is_null() => {}
+                    None => {
+                        let suggestion = known
+                            .keys()
+                            .filter_map(|k| k.as_str())
+                            .filter(|k| !source.contains_key(*k))
+                            .map(|k| (strsim::levenshtein(key, k), k))
+                            .filter(|(distance, _)| *distance <= MAX_TYPO_DISTANCE)
+                            .min();
+                        match suggestion {
+                            Some((_, candidate)) => issues.push((
+                                Severity::Error,
+                                path.clone(),
+                                format!("unknown key `{}`, did you mean `{}`?", key, candidate),
+                            )),
+                            None => issues.push((
+                                Severity::Warning,
+                                path.clone(),
+                                format!("unknown key `{}` is ignored", key),
+                            )),
+                        }
+                    }
+                }
+                path.pop();
+            }
+        }
+        (Value::Sequence(source), Value::Sequence(known)) if source.len() == known.len() => {
+            for (index, (value, known_value)) in source.iter().zip(known).enumerate() {
+                path.push(Segment::Index(index));
+                check_unknown_keys(value, known_value, path, issues);
+                path.pop();
+            }
+        }
+        _ => {}
+    }
+}
+
+fn check_semantics(config: &Configuration, issues: &mut Vec<RawIssue>) {
+    let key = |k: &str| Segment::Key(k.to_string());
+
+    if config.model_providers.is_empty() {
+        issues.push((
+            Severity::Error,
+            vec![key("model_providers")],
+            "at least one model provider is required".to_string(),
+        ));
+    }
+
+    let mut provider_names = HashSet::new();
+    for (index, provider) in config.model_providers.iter().enumerate() {
+        let path = |field: &str| vec![key("model_providers"), Segment::Index(index), key(field)];
+        if provider.model.is_none() {
+            issues.push((
+                Severity::Error,
+                path("model"),
+                format!(
+                    "provider '{}' is missing `model` (e.g. `openai/gpt-4o`)",
+                    provider.name
+                ),
+            ));
+        }
+        if let Some(custom) = &provider.custom_provider {
+            let registered = config
+                .custom_providers
+                .iter()
+                .flatten()
+                .any(|p| &p.name == custom);
+            if !registered {
+                issues.push((
+                    Severity::Error,
+                    path("custom_provider"),
+                    format!(
+                        "provider '{}' uses custom provider '{}', which is not defined in `custom_providers`",
+                        provider.name, custom
+                    ),
+                ));
+            }
+        }
+        if provider.endpoint.is_some() && provider.port.is_none() {
+            issues.push((
+                Severity::Error,
+                path("port"),
+                format!(
+                    "provider '{}' sets `endpoint` but not `port`",
+                    provider.name
+                ),
+            ));
+        }
+        if let Some(timeout) = &provider.timeout {
+            if parse_duration_ms(timeout).is_none() {
+                issues.push((
+                    Severity::Error,
+                    path("timeout"),
+                    format!(
+                        "provider '{}' has invalid timeout '{}' (expected e.g. `500ms`, `30s`, `2m`)",
+                        provider.name, timeout
+                    ),
+                ));
+            }
+        }
+        if let Some(mock) = &provider.mock {
+            let mock_path = |field: &str| {
+                let mut path = path("mock");
+                path.push(key(field));
+                path
+            };
+            if let Some(delay) = mock
+                .delay
+                .as_ref()
+                .filter(|d| parse_duration_ms(d).is_none())
+            {
+                issues.push((
+                    Severity::Error,
+                    mock_path("delay"),
+                    format!(
+                        "provider '{}' has invalid mock delay '{}' (expected e.g. `500ms`, `2s`)",
+                        provider.name, delay
+                    ),
+                ));
+            }
+            if let Some(rate) = mock.error_rate.filter(|r| !(0.0..=1.0).contains(r)) {
+                issues.push((
+                    Severity::Error,
+                    mock_path("error_rate"),
+                    format!("mock error_rate must be between 0.0 and 1.0, got {}", rate),
+                ));
+            }
+        }
+        if provider.provider_interface == LlmProviderType::VertexAI {
+            if provider.base_url_path_prefix.is_none() {
+                issues.push((
+                    Severity::Error,
+                    path("vertex_ai"),
+                    format!(
+                        "provider '{}' needs `vertex_ai.project_id` and `vertex_ai.location`, or a base_url ending in `/v1/projects/<project>/locations/<location>`",
+                        provider.name
+                    ),
+                ));
+            }
+            let has_key_file = provider
+                .vertex_ai
+                .as_ref()
+                .is_some_and(|v| v.service_account_key_file.is_some());
+            if !has_key_file
+                && provider.access_key.is_none()
+                && provider.passthrough_auth != Some(true)
+            {
+                issues.push((
+                    Severity::Error,
+                    path("vertex_ai"),
+                    format!(
+                        "provider '{}' needs `vertex_ai.service_account_key_file`, an `access_key` token or `passthrough_auth`",
+                        provider.name
+                    ),
+                ));
+            }
+        }
+        let is_wildcard = provider
+            .model
+            .as_deref()
+            .is_some_and(|m| m == "*" || m.ends_with("/*"));
+        if !is_wildcard && !provider_names.insert(provider.name.as_str()) {
+            issues.push((
+                Severity::Error,
+                path("name"),
+                format!("provider name '{}' is not unique", provider.name),
+            ));
+        }
+    }
+
+    if let Some(aliases) = &config.model_aliases {
+        let mut aliases: Vec<_> = aliases.iter().collect();
+        aliases.sort_by_key(|(alias, _)| alias.as_str());
+        for (alias, settings) in aliases {
+            if let Err(error) = compile_pattern(alias) {
+                issues.push((
+                    Severity::Error,
+                    vec![key("model_aliases"), key(alias)],
+                    format!("alias pattern '{}' is not a valid regex: {}", alias, error),
+                ));
+            }
+            let blocked = settings.blocked.unwrap_or(false);
+            match &settings.target {
+                Some(_) if blocked => issues.push((
+                    Severity::Error,
+                    vec![key("model_aliases"), key(alias)],
+                    format!("alias '{}' sets both target and blocked", alias),
+                )),
+                None if !blocked => issues.push((
+                    Severity::Error,
+                    vec![key("model_aliases"), key(alias)],
+                    format!("alias '{}' needs a target or blocked: true", alias),
+                )),
+                Some(target) => {
+                    let declared = config
+                        .model_providers
+                        .iter()
+                        .any(|p| p.name == *target || p.model.as_deref() == Some(target.as_str()));
+                    if !declared {
+                        issues.push((
+                            Severity::Error,
+                            vec![key("model_aliases"), key(alias), key("target")],
+                            format!(
+                                "alias '{}' targets model '{}' which is not declared in model_providers",
+                                alias, target
+                            ),
+                        ));
+                    }
+                }
+                None => {}
+            }
+        }
+    }
+
+    if let Some(deprecations) = &config.model_deprecations {
+        let aliases = ModelAliasTable::new(config.model_aliases.as_ref());
+        let mut deprecations: Vec<_> = deprecations.iter().collect();
+        deprecations.sort_by_key(|(model, _)| model.as_str());
+        for (model, deprecation) in deprecations {
+            let replacement = deprecation.replacement.as_str();
+            let declared = config
+                .model_providers
+                .iter()
+                .any(|p| p.name == replacement || p.model.as_deref() == Some(replacement))
+                || matches!(
+                    aliases.resolve(replacement),
+                    Some(AliasResolution::Target(_))
+                );
+            if !declared {
+                issues.push((
+                    Severity::Error,
+                    vec![key("model_deprecations"), key(model), key("replacement")],
+                    format!(
+                        "deprecated model '{}' is replaced by '{}' which is not declared in model_providers or model_aliases",
+                        model, replacement
+                    ),
+                ));
+            }
+        }
+    }
+
+    if let Some(title) = &config.conversation_title {
+        let declared = config
+            .model_providers
+            .iter()
+            .any(|p| p.name == title.model || p.model.as_deref() == Some(title.model.as_str()))
+            || matches!(
+                ModelAliasTable::new(config.model_aliases.as_ref()).resolve(&title.model),
+                Some(AliasResolution::Target(_))
+            );
+        if !declared {
+            issues.push((
+                Severity::Error,
+                vec![key("conversation_title"), key("model")],
+                format!(
+                    "conversation_title model '{}' is not declared in model_providers or model_aliases",
+                    title.model
+                ),
+            ));
+        }
+    }
+
+    if let Some(recording) = &config.traffic_recording {
+        if recording.directory.trim().is_empty() {
+            issues.push((
+                Severity::Error,
+                vec![key("traffic_recording"), key("directory")],
+                "traffic_recording directory must not be empty".to_string(),
+            ));
+        }
+    }
+
+    if let Some(chaos) = &config.chaos {
+        issues.push((
+            Severity::Warning,
+            vec![key("chaos")],
+            "chaos fault injection is enabled; responses will be delayed, failed or corrupted"
+                .to_string(),
+        ));
+        if chaos.latency_rate.is_some() && chaos.latency.is_none() {
+            issues.push((
+                Severity::Error,
+                vec![key("chaos"), key("latency")],
+                "chaos latency_rate is set but latency is not".to_string(),
+            ));
+        }
+        if let Some(latency) = chaos
+            .latency
+            .as_ref()
+            .filter(|l| parse_duration_ms(l).is_none())
+        {
+            issues.push((
+                Severity::Error,
+                vec![key("chaos"), key("latency")],
+                format!("invalid chaos latency '{latency}' (expected e.g. `500ms`, `2s`)"),
+            ));
+        }
+        let rates = [
+            ("latency_rate", chaos.latency_rate),
+            ("error_rate", chaos.error_rate),
+            ("truncate_rate", chaos.truncate_rate),
+            ("malformed_sse_rate", chaos.malformed_sse_rate),
+        ];
+        for (field, rate) in rates {
+            if let Some(rate) = rate.filter(|r| !(0.0..=1.0).contains(r)) {
+                issues.push((
+                    Severity::Error,
+                    vec![key("chaos"), key(field)],
+                    format!("chaos {field} must be between 0.0 and 1.0, got {rate}"),
+                ));
+            }
+        }
+    }
+
+    if let Some(tracing) = &config.tracing {
+        if let Some(random_sampling) = tracing.random_sampling {
+            if random_sampling > 100 {
+                issues.push((
+                    Severity::Error,
+                    vec![key("tracing"), key("random_sampling")],
+                    format!(
+                        "random_sampling is a percentage between 0 and 100, got {}",
+                        random_sampling
+                    ),
+                ));
+            }
+        }
+        if let Some(sampling_rate) = tracing.sampling_rate {
+            if !(0.0..=1.0).contains(&sampling_rate) {
+                issues.push((
+                    Severity::Error,
+                    vec![key("tracing"), key("sampling_rate")],
+                    format!(
+                        "sampling_rate must be between 0.0 and 1.0, got {}",
+                        sampling_rate
+                    ),
+                ));
+            }
+        }
+    }
+
+    if let Some(pricing) = &config.pricing {
+        let mut pricing: Vec<_> = pricing.iter().collect();
+        pricing.sort_by_key(|(model, _)| model.as_str());
+        for (model, price) in pricing {
+            let rates = [
+                ("input_per_million", Some(price.input_per_million)),
+                ("output_per_million", Some(price.output_per_million)),
+                ("cached_input_per_million", price.cached_input_per_million),
+            ];
+            for (field, rate) in rates {
+                if rate.is_some_and(|r| r < 0.0) {
+                    issues.push((
+                        Severity::Error,
+                        vec![key("pricing"), key(model), key(field)],
+                        format!("price for '{}' cannot be negative", model),
+                    ));
+                }
+            }
+        }
+    }
+
+    if let Some(timeout) = config.warmup.as_ref().and_then(|w| w.timeout.as_ref()) {
+        if parse_duration_ms(timeout).is_none() {
+            issues.push((
+                Severity::Error,
+                vec![key("warmup"), key("timeout")],
+                format!(
+                    "invalid warmup timeout '{}' (expected e.g. `500ms`, `30s`, `2m`)",
+                    timeout
+                ),
+            ));
+        }
+    }
+
+    if let Some(timeout) = config.image_fetch.as_ref().and_then(|i| i.timeout.as_ref()) {
+        if parse_duration_ms(timeout).is_none() {
+            issues.push((
+                Severity::Error,
+                vec![key("image_fetch"), key("timeout")],
+                format!(
+                    "invalid image_fetch timeout '{}' (expected e.g. `500ms`, `30s`, `2m`)",
+                    timeout
+                ),
+            ));
+        }
+    }
+
+    for (index, route) in config.routing_preferences.iter().flatten().enumerate() {
+        let keep_ratio = route.prompt_compression.as_ref().and_then(|c| c.keep_ratio);
+        if let Some(ratio) = keep_ratio.filter(|r| !(*r > 0.0 && *r <= 1.0)) {
+            issues.push((
+                Severity::Error,
+                vec![
+                    key("routing_preferences"),
+                    Segment::Index(index),
+                    key("prompt_compression"),
+                    key("keep_ratio"),
+                ],
+                format!("keep_ratio must be in (0, 1], got {}", ratio),
+            ));
+        }
+        let hedge_delay = route.hedging.as_ref().map(|h| &h.delay);
+        if let Some(delay) = hedge_delay.filter(|d| parse_duration_ms(d).is_none()) {
+            issues.push((
+                Severity::Error,
+                vec![
+                    key("routing_preferences"),
+                    Segment::Index(index),
+                    key("hedging"),
+                    key("delay"),
+                ],
+                format!(
+                    "invalid hedging delay '{}' (expected e.g. `500ms`, `2s`)",
+                    delay
+                ),
+            ));
+        }
+        let Some(cache) = &route.response_cache else {
+            continue;
+        };
+        let durations = [
+            ("ttl", Some(&cache.ttl)),
+            (
+                "stale_while_revalidate",
+                cache.stale_while_revalidate.as_ref(),
+            ),
+        ];
+        for (field, value) in durations {
+            if let Some(value) = value.filter(|v| parse_duration_ms(v).is_none()) {
+                issues.push((
+                    Severity::Error,
+                    vec![
+                        key("routing_preferences"),
+                        Segment::Index(index),
+                        key("response_cache"),
+                        key(field),
+                    ],
+                    format!(
+                        "invalid response_cache {} '{}' (expected e.g. `30s`, `5m`, `1h`)",
+                        field, value
+                    ),
+                ));
+            }
+        }
+    }
+
+    if let Some(export) = &config.usage_export {
+        if export.directory.is_none() && export.s3_url.is_none() {
+            issues.push((
+                Severity::Error,
+                vec![key("usage_export")],
+                "usage_export needs a directory or an s3_url".to_string(),
+            ));
+        }
+        if let Some(url) = export
+            .s3_url
+            .as_ref()
+            .filter(|url| url.strip_prefix("s3://").is_none_or(|rest| rest.is_empty()))
+        {
+            issues.push((
+                Severity::Error,
+                vec![key("usage_export"), key("s3_url")],
+                format!("invalid s3_url '{}' (expected s3://bucket/prefix)", url),
+            ));
+        }
+    }
+
+    if let Some(archive) = &config.archive {
+        let sinks = [
+            archive.s3_url.is_some(),
+            archive.kafka.is_some(),
+            archive.http.is_some(),
+        ];
+        if sinks.iter().filter(|set| **set).count() != 1 {
+            issues.push((
+                Severity::Error,
+                vec![key("archive")],
+                "archive needs exactly one of s3_url, kafka or http".to_string(),
+            ));
+        }
+        if let Some(url) = archive
+            .s3_url
+            .as_ref()
+            .filter(|url| url.strip_prefix("s3://").is_none_or(|rest| rest.is_empty()))
+        {
+            issues.push((
+                Severity::Error,
+                vec![key("archive"), key("s3_url")],
+                format!("invalid s3_url '{}' (expected s3://bucket/prefix)", url),
+            ));
+        }
+    }
+
+    if let Some(retrieval) = &config.retrieval {
+        if !(retrieval.endpoint.starts_with("http://")
+            || retrieval.endpoint.starts_with("https://"))
+        {
+            issues.push((
+                Severity::Error,
+                vec![key("retrieval"), key("endpoint")],
+                format!(
+                    "invalid retrieval endpoint '{}' (expected an http or https url)",
+                    retrieval.endpoint
+                ),
+            ));
+        }
+    }
+
+    if let Some(guardrail) = &config.secret_guardrail {
+        for pattern in guardrail.allow.iter().flatten() {
+            if let Err(error) = regex::Regex::new(pattern) {
+                issues.push((
+                    Severity::Error,
+                    vec![key("secret_guardrail"), key("allow")],
+                    format!(
+                        "allow pattern '{}' is not a valid regex: {}",
+                        pattern, error
+                    ),
+                ));
+            }
+        }
+    }
+
+    if let Some(moderation) = &config.moderation {
+        for (category, patterns) in moderation.local_categories.iter().flatten() {
+            for pattern in patterns {
+                if let Err(error) = regex::Regex::new(pattern) {
+                    issues.push((
+                        Severity::Error,
+                        vec![key("moderation"), key("local_categories"), key(category)],
+                        format!("pattern '{}' is not a valid regex: {}", pattern, error),
+                    ));
+                }
+            }
+        }
+        if moderation.model.as_deref() == Some("local") && moderation.local_categories.is_none() {
+            issues.push((
+                Severity::Error,
+                vec![key("moderation"), key("local_categories")],
+                "the local moderation model needs local_categories".to_string(),
+            ));
+        }
+    }
+
+    if config
+        .event_bus
+        .as_ref()
+        .is_some_and(|bus| bus.nats.is_none() && bus.kafka.is_none())
+    {
+        issues.push((
+            Severity::Error,
+            vec![key("event_bus")],
+            "event_bus needs nats, kafka or both".to_string(),
+        ));
+    }
+
+    if let Some(share) = config
+        .concurrency_limits
+        .as_ref()
+        .and_then(|c| c.batch_share)
+    {
+        if !(share > 0.0 && share <= 1.0) {
+            issues.push((
+                Severity::Error,
+                vec![key("concurrency_limits"), key("batch_share")],
+                format!("batch_share must be in (0, 1], got {}", share),
+            ));
+        }
+    }
+
+    if let Some(threshold) = config
+        .overrides
+        .as_ref()
+        .and_then(|o| o.prompt_target_intent_matching_threshold)
+    {
+        if !(0.0..=1.0).contains(&threshold) {
+            issues.push((
+                Severity::Error,
+                vec![
+                    key("overrides"),
+                    key("prompt_target_intent_matching_threshold"),
+                ],
+                format!("threshold must be between 0.0 and 1.0, got {}", threshold),
+            ));
+        }
+    }
+
+    if let Some(overrides) = config
+        .overrides
+        .as_ref()
+        .filter(|o| o.allow_upstream_endpoint_override == Some(true))
+    {
+        if overrides
+            .upstream_endpoint_allowed_hosts
+            .as_ref()
+            .is_none_or(|hosts| hosts.is_empty())
+        {
+            issues.push((
+                Severity::Error,
+                vec![key("overrides"), key("allow_upstream_endpoint_override")],
+                "allow_upstream_endpoint_override requires upstream_endpoint_allowed_hosts"
+                    .to_string(),
+            ));
+        }
+    }
+}
+
+/// Find the source line of a path by walking the document's indentation.
+/// Handles block-style YAML, which is what configs are written in; flow
+/// style (`{a: 1}`) is not located.
+fn locate_line(contents: &str, path: &[Segment]) -> Option<usize> {
+    enum Token<'a> {
+        Dash(usize),
+        Content(usize, &'a str),
+    }
+
+    let mut tokens: Vec<(usize, Token)> = Vec::new();
+    for (number, line) in contents.lines().enumerate() {
+        let text = line.trim_start();
+        if text.is_empty() || text.starts_with('#') {
+            continue;
+        }
+        let indent = line.len() - text.len();
+        if let Some(rest) = text
+            .strip_prefix("- ")
+            .or(if text == "-" { Some("") } else { None })
+        {
+            tokens.push((number + 1, Token::Dash(indent)));
+            let rest_trimmed = rest.trim_start();
+            if !rest_trimmed.is_empty() {
+                let offset = indent + 1 + (rest.len() - rest_trimmed.len()) + 1;
+                tokens.push((number + 1, Token::Content(offset, rest_trimmed)));
+            }
+        } else {
+            tokens.push((number + 1, Token::Content(indent, text)));
+        }
+    }
+
+    let (mut start, mut end) = (0, tokens.len());
+    let mut parent_indent: Option<usize> = None;
+    let mut line = None;
+    for segment in path {
+        let deeper = |indent: usize| parent_indent.is_none_or(|p| indent > p);
+        match segment {
+            Segment::Key(key) => {
+                let indent = tokens[start..end].iter().find_map(|(_, t)| match t {
+                    Token::Content(indent, _) if deeper(*indent) => Some(*indent),
+                    _ => None,
+                })?;
+                let position = (start..end).find(|&i| match &tokens[i].1 {
+                    Token::Content(i, text) if *i == indent => is_key(text, key),
+                    _ => false,
+                })?;
+                line = Some(tokens[position].0);
+                start = position + 1;
+                end = (start..end)
+                    .find(|&i| match tokens[i].1 {
+                        Token::Content(i, _) => i <= indent,
+                        Token::Dash(i) => i < indent,
+                    })
+                    .unwrap_or(end);
+                parent_indent = Some(indent);
+            }
+            Segment::Index(index) => {
+                // Sequences may sit at the same indentation as their key.
+                let indent = tokens[start..end].iter().find_map(|(_, t)| match t {
+                    Token::Dash(indent) => Some(*indent),
+                    _ => None,
+                })?;
+                let position = (start..end)
+                    .filter(|&i| matches!(tokens[i].1, Token::Dash(i) if i == indent))
+                    .nth(*index)?;
+                line = Some(tokens[position].0);
+                start = position + 1;
+                end = (start..end)
+                    .find(|&i| match tokens[i].1 {
+                        Token::Content(i, _) | Token::Dash(i) => i <= indent,
+                    })
+                    .unwrap_or(end);
+                parent_indent = Some(indent);
+            }
+        }
+    }
+    line
+}
+
+fn is_key(text: &str, key: &str) -> bool {
+    [
+        key.to_string(),
+        format!("\"{}\"", key),
+        format!("'{}'", key),
+    ]
+    .iter()
+    .any(|candidate| {
+        text.strip_prefix(candidate.as_str())
+            .is_some_and(|rest| rest.trim_start().starts_with(':'))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VALID: &str = r#"
+version: v0.3.0
+listeners:
+  - type: model
+    name: llm
+    port: 12000
+model_providers:
+  - name: openai/gpt-4o
+    model: gpt-4o
+    provider_interface: openai
+    access_key: sk-test
+"#;
+
+    #[test]
+    fn valid_config_has_no_issues() {
+        let report = validate_config(VALID);
+        assert!(report.issues.is_empty(), "{}", report);
+        assert!(report.into_result().is_ok());
+    }
+
+    #[test]
+    fn missing_field_reports_path_and_line() {
+        let contents = VALID.replace("    provider_interface: openai\n", "");
+        let report = validate_config(&contents);
+        let issue = report.errors().next().unwrap();
+        assert_eq!(issue.path, "model_providers[0]");
+        assert!(issue.message.contains("missing field `provider_interface`"));
+        assert_eq!(issue.line, Some(8));
+        assert!(report.into_result().is_err());
+    }
+
+    #[test]
+    fn wrong_type_reports_field_path() {
+        let contents = VALID.replace("port: 12000", "port: not-a-port");
+        let issue = validate_config(&contents).issues.remove(0);
+        assert_eq!(issue.path, "listeners[0].port");
+        assert_eq!(issue.line, Some(6));
+    }
+
+    #[test]
+    fn typo_is_an_error_with_suggestion() {
+        let contents = VALID.replace("access_key:", "acess_key:");
+        let report = validate_config(&contents);
+        let issue = report.errors().next().unwrap();
+        assert_eq!(issue.path, "model_providers[0].acess_key");
+        assert_eq!(issue.line, Some(11));
+        assert!(issue.message.contains("did you mean `access_key`"));
+        assert_eq!(
+            issue.to_string(),
+            "error at line 11: model_providers[0].acess_key: unknown key `acess_key`, did you mean `access_key`?"
+        );
+    }
+
+    #[test]
+    fn unrelated_unknown_key_is_a_warning() {
+        let contents = format!("{}    protocol: https\n", VALID);
+        let report = validate_config(&contents);
+        assert!(!report.has_errors());
+        let warning = report.warnings().next().unwrap();
+        assert_eq!(warning.path, "model_providers[0].protocol");
+        assert_eq!(warning.line, Some(12));
+    }
+
+    #[test]
+    fn semantic_checks() {
+        let contents = format!(
+            "{}model_aliases:\n  fast:\n    target: gpt-5\ntracing:\n  random_sampling: 150\n",
+            VALID
+        );
+        let report = validate_config(&contents);
+        let errors: Vec<_> = report.errors().collect();
+        assert_eq!(errors.len(), 2, "{}", report);
+        assert_eq!(errors[0].path, "model_aliases.fast.target");
+        assert_eq!(errors[0].line, Some(14));
+        assert_eq!(errors[1].path, "tracing.random_sampling");
+        assert!(errors[1].message.contains("between 0 and 100"));
+    }
+
+    #[test]
+    fn custom_provider_must_be_defined() {
+        let contents = format!("{}    custom_provider: inference\n", VALID);
+        let report = validate_config(&contents);
+        let errors: Vec<_> = report.errors().collect();
+        assert_eq!(errors.len(), 1, "{}", report);
+        assert_eq!(errors[0].path, "model_providers[0].custom_provider");
+
+        let contents = format!(
+            "{}    custom_provider: inference\ncustom_providers:\n  - name: inference\n    endpoint: /serve/{{model}}/v1/chat\n    auth:\n      type: header\n      name: x-inference-token\n",
+            VALID
+        );
+        let report = validate_config(&contents);
+        assert!(report.issues.is_empty(), "{}", report);
+    }
+
+    #[test]
+    fn upstream_endpoint_override_requires_allowed_hosts() {
+        let contents = format!(
+            "{}overrides:\n  allow_upstream_endpoint_override: true\n",
+            VALID
+        );
+        let report = validate_config(&contents);
+        let errors: Vec<_> = report.errors().collect();
+        assert_eq!(errors.len(), 1, "{}", report);
+        assert_eq!(errors[0].path, "overrides.allow_upstream_endpoint_override");
+
+        let contents = format!(
+            "{}overrides:\n  allow_upstream_endpoint_override: true\n  upstream_endpoint_allowed_hosts:\n    - '*.example.com'\n",
+            VALID
+        );
+        assert!(validate_config(&contents).issues.is_empty());
+    }
+
+    #[test]
+    fn model_alias_patterns() {
+        let contents = format!(
+            "{}model_aliases:\n  gpt-4o*:\n    target: openai/gpt-4o\n  '*-preview':\n    blocked: true\n    priority: 10\n",
+            VALID
+        );
+        assert!(!validate_config(&contents).has_errors());
+
+        let contents = format!(
+            "{}model_aliases:\n  regex:gpt-(4o:\n    target: openai/gpt-4o\n  fast:\n    blocked: true\n    target: openai/gpt-4o\n",
+            VALID
+        );
+        let report = validate_config(&contents);
+        let errors: Vec<_> = report.errors().collect();
+        assert_eq!(errors.len(), 2, "{}", report);
+        assert!(errors[0].message.contains("sets both target and blocked"));
+        assert!(errors[1].message.contains("not a valid regex"));
+    }
+
+    #[test]
+    fn deprecation_replacements_must_be_declared() {
+        let contents = format!(
+            "{}model_deprecations:\n  gpt-4-32k:\n    replacement: gpt-4o\n    sunset: 2025-06-06\n",
+            VALID
+        );
+        assert!(!validate_config(&contents).has_errors());
+
+        let contents = format!(
+            "{}model_deprecations:\n  gpt-4-32k:\n    replacement: gpt-5\n",
+            VALID
+        );
+        let issue = validate_config(&contents).errors().next().cloned().unwrap();
+        assert_eq!(issue.path, "model_deprecations.gpt-4-32k.replacement");
+    }
+
+    #[test]
+    fn usage_export_needs_a_destination() {
+        let contents = format!("{}usage_export:\n  format: parquet\n", VALID);
+        let issue = validate_config(&contents).errors().next().cloned().unwrap();
+        assert_eq!(issue.path, "usage_export");
+
+        let contents = format!("{}usage_export:\n  s3_url: billing/usage\n", VALID);
+        let issue = validate_config(&contents).errors().next().cloned().unwrap();
+        assert_eq!(issue.path, "usage_export.s3_url");
+
+        let contents = format!("{}usage_export:\n  s3_url: s3://billing/usage\n", VALID);
+        assert!(!validate_config(&contents).has_errors());
+    }
+
+    #[test]
+    fn archive_needs_exactly_one_sink() {
+        let contents = format!("{}archive:\n  batch_size: 10\n", VALID);
+        let issue = validate_config(&contents).errors().next().cloned().unwrap();
+        assert_eq!(issue.path, "archive");
+
+        let contents = format!(
+            "{}archive:\n  s3_url: s3://evals\n  http:\n    url: http://collector:9000/ingest\n",
+            VALID
+        );
+        assert!(validate_config(&contents).has_errors());
+
+        let contents = format!(
+            "{}archive:\n  kafka:\n    rest_proxy_url: http://kafka-rest:8082\n    topic: llm-exchanges\n",
+            VALID
+        );
+        assert!(!validate_config(&contents).has_errors());
+    }
+
+    #[test]
+    fn provider_timeout_must_be_a_duration() {
+        let contents = format!("{}    timeout: 30\n", VALID);
+        let issue = validate_config(&contents).errors().next().cloned().unwrap();
+        assert_eq!(issue.path, "model_providers[0].timeout");
+        assert!(issue.message.contains("invalid timeout '30'"));
+
+        let contents = format!("{}    timeout: 90s\n", VALID);
+        assert!(!validate_config(&contents).has_errors());
+    }
+
+    #[test]
+    fn vertex_ai_needs_a_project_and_credentials() {
+        let vertex = VALID
+            .replace(
+                "provider_interface: openai",
+                "provider_interface: vertex_ai",
+            )
+            .replace("    access_key: sk-test\n", "");
+        let report = validate_config(&vertex);
+        let errors: Vec<_> = report.errors().collect();
+        assert_eq!(errors.len(), 2, "{}", report);
+        assert!(errors[0].message.contains("vertex_ai.project_id"));
+        assert!(errors[1].message.contains("service_account_key_file"));
+
+        let contents = format!(
+            "{}    base_url_path_prefix: /v1/projects/my-project/locations/us-central1\n    vertex_ai:\n      project_id: my-project\n      location: us-central1\n      service_account_key_file: /etc/plano/sa.json\n",
+            vertex
+        );
+        assert!(!validate_config(&contents).has_errors());
+    }
+
+    #[test]
+    fn reference_config_has_no_errors() {
+        let contents = std::fs::read_to_string(
+            "../../docs/source/resources/includes/plano_config_full_reference_rendered.yaml",
+        )
+        .expect("reference config file not found");
+        let report = validate_config(&contents);
+        assert!(!report.has_errors(), "{}", report);
+    }
+
+    #[test]
+    fn syntax_error_has_line() {
+        let report = validate_config("version: v0.3.0\nlisteners: [\n");
+        let issue = report.errors().next().unwrap();
+        assert!(issue.line.is_some());
+        assert!(issue.path.is_empty());
+    }
+}
@@ -1,7 +1,14 @@
+use hermesllm::apis::amazon_bedrock::{AmazonBedrockApi, InvokeModelFamily};
+use hermesllm::apis::anthropic::AnthropicApi;
 use hermesllm::apis::openai::{ModelDetail, ModelObject, Models};
+use hermesllm::clients::endpoints::{SupportedAPIsFromClient, SupportedUpstreamAPIs};
+use hermesllm::providers::{
+    custom_provider, register_provider, CustomProvider, ProviderCapabilities,
+};
 use serde::{Deserialize, Deserializer, Serialize};
 use std::collections::HashMap;
 use std::fmt::Display;
+use std::sync::Arc;
 
 use crate::api::open_ai::{
     ChatCompletionTool, FunctionDefinition, FunctionParameter, FunctionParameters, ParameterType,
@@ -37,7 +44,19 @@ pub struct Routing {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModelAlias {
-    pub target: String,
+    pub target: Option<String>,
+    /// Reject requests for matching models instead of rewriting them.
+    pub blocked: Option<bool>,
+    /// Orders overlapping wildcard and regex aliases; higher wins.
+    pub priority: Option<i32>,
+}
+
+/// A retired model name and the model that now serves its requests.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelDeprecation {
+    pub replacement: String,
+    /// When the provider retires the model, e.g. `2025-06-06`; shown in the warning.
+    pub sunset: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -116,6 +135,61 @@ pub struct Listener {
     pub input_filters: Option<Vec<String>>,
     pub output_filters: Option<Vec<String>>,
     pub port: u16,
+    pub tls: Option<ListenerTlsConfig>,
+    /// System prompt enforced on model requests through this listener.
+    pub system_prompt: Option<SystemPromptPolicy>,
+    /// How requests through this listener are converted for upstreams that
+    /// speak another API.
+    #[serde(default)]
+    pub transform_mode: TransformMode,
+}
+
+/// What to do with client input an upstream's API has no counterpart for.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransformMode {
+    /// Drop it and send the rest.
+    #[default]
+    BestEffort,
+    /// Reject the request, listing what would have been dropped.
+    Strict,
+}
+
+/// A system prompt the gateway adds to requests whatever the client sent,
+/// for platform policies such as tone or disclosure.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SystemPromptPolicy {
+    pub content: String,
+    /// How it combines with the client's system prompt. Defaults to `prepend`.
+    #[serde(default)]
+    pub mode: SystemPromptMode,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SystemPromptMode {
+    /// Put it before the client's system prompt.
+    #[default]
+    Prepend,
+    /// Put it after the client's system prompt.
+    Append,
+    /// Drop the client's system prompt and use this one.
+    Replace,
+}
+
+/// TLS terminated by Envoy on a listener. With `require_client_cert` the
+/// listener only accepts clients presenting a certificate signed by
+/// `client_ca_path`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ListenerTlsConfig {
+    pub cert_path: String,
+    pub key_path: String,
+    pub client_ca_path: Option<String>,
+    #[serde(default)]
+    pub require_client_cert: bool,
+    /// ALPN protocols offered to clients, `h2` and `http/1.1` when unset.
+    pub alpn_protocols: Option<Vec<String>>,
+    pub min_version: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -165,6 +239,87 @@ pub struct TopLevelRoutingPreference {
     pub models: Vec<String>,
     #[serde(default)]
     pub selection_policy: SelectionPolicy,
+    /// Model to retry on when the routed model refuses with a content-filter
+    /// result; unset disables the fallback for this route.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_filter_fallback: Option<String>,
+    /// Race a second model when the routed one is slow to start answering.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hedging: Option<HedgingSettings>,
+    /// Cap on the output tokens requests on this route may ask for.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_token_budget: Option<OutputTokenBudget>,
+    /// System prompt enforced on requests routed to this route.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system_prompt: Option<SystemPromptPolicy>,
+    /// Cache of complete responses for repeated identical requests.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_cache: Option<ResponseCacheSettings>,
+    /// Pruning of long tool results and history before forwarding.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt_compression: Option<PromptCompressionSettings>,
+}
+
+/// Hedged requests on a route: after `delay` without a first token, the
+/// request also goes to a second model and the first answer wins.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HedgingSettings {
+    /// How long to wait for the routed model before hedging, e.g. `"800ms"`.
+    pub delay: String,
+    /// Model to hedge with. Defaults to the router's next-ranked model.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+}
+
+/// Sentence pruning of long prompt segments on a route. The sentences whose
+/// words recur least in their segment are dropped first, LLMLingua-style.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PromptCompressionSettings {
+    /// Segments shorter than this many characters are left alone. Defaults to 4000.
+    pub min_chars: Option<usize>,
+    /// Share of a compressed segment's characters to keep. Defaults to 0.5.
+    pub keep_ratio: Option<f64>,
+    /// Segments to compress. Defaults to all of them.
+    pub segments: Option<Vec<PromptSegment>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PromptSegment {
+    /// Tool and function call results, where retrieved context usually lands.
+    ToolResults,
+    /// Text of the conversation before the latest user message.
+    History,
+}
+
+/// Exact-match cache of successful non-streamed responses on a route.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResponseCacheSettings {
+    /// How long a cached response is served as fresh, e.g. `"5m"`.
+    pub ttl: String,
+    /// How long past `ttl` a cached response is still served while it is
+    /// refreshed in the background. Unset serves nothing stale.
+    pub stale_while_revalidate: Option<String>,
+}
+
+/// Cap on the output tokens a request may ask for. Requests that don't set a
+/// limit get `max_tokens`; streamed responses running far past it are cut off.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutputTokenBudget {
+    pub max_tokens: u32,
+    /// What happens to a request asking for more. Defaults to `clamp`.
+    #[serde(default)]
+    pub on_exceed: BudgetExceededAction,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetExceededAction {
+    /// Lower the request's limit to the budget.
+    #[default]
+    Clamp,
+    /// Fail the request with a 400.
+    Reject,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -208,7 +363,11 @@ pub struct Configuration {
     pub version: String,
     pub endpoints: Option<HashMap<String, Endpoint>>,
     pub model_providers: Vec<LlmProvider>,
+    /// Providers outside the built-in set, referenced by `custom_provider`
+    /// on a model provider.
+    pub custom_providers: Option<Vec<CustomProvider>>,
     pub model_aliases: Option<HashMap<String, ModelAlias>>,
+    pub model_deprecations: Option<HashMap<String, ModelDeprecation>>,
     pub overrides: Option<Overrides>,
     pub routing: Option<Routing>,
     pub system_prompt: Option<String>,
@@ -224,6 +383,398 @@ pub struct Configuration {
     pub state_storage: Option<StateStorageConfig>,
     pub routing_preferences: Option<Vec<TopLevelRoutingPreference>>,
     pub model_metrics_sources: Option<Vec<MetricsSource>>,
+    pub concurrency_limits: Option<ConcurrencyLimits>,
+    pub connection_settings: Option<ConnectionSettings>,
+    /// Token prices keyed by model, overriding the built-in defaults.
+    pub pricing: Option<HashMap<String, ModelPricing>>,
+    pub admin: Option<AdminSettings>,
+    pub warmup: Option<WarmupSettings>,
+    pub tenancy: Option<TenancyConfig>,
+    pub image_fetch: Option<ImageFetchSettings>,
+    pub conversation_title: Option<ConversationTitleSettings>,
+    pub plugins: Option<Vec<PluginConfig>>,
+    pub traffic_recording: Option<TrafficRecordingSettings>,
+    pub chaos: Option<ChaosSettings>,
+    pub usage_export: Option<UsageExportSettings>,
+    pub stream_resumption: Option<StreamResumptionSettings>,
+    pub stream_keep_alive: Option<StreamKeepAliveSettings>,
+    pub archive: Option<ArchiveSettings>,
+    pub event_bus: Option<EventBusSettings>,
+    pub retrieval: Option<RetrievalSettings>,
+    pub secret_guardrail: Option<SecretGuardrailSettings>,
+    pub moderation: Option<ModerationSettings>,
+}
+
+impl Configuration {
+    /// Register `custom_providers` so model providers can refer to them
+    pub fn register_custom_providers(&self) {
+        for provider in self.custom_providers.iter().flatten() {
+            register_provider(provider.clone());
+        }
+    }
+}
+
+/// Tenant-scoped views of one gateway, selected per request by a header.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct TenancyConfig {
+    /// Header naming the tenant. Defaults to `x-arch-tenant`.
+    pub header: Option<String>,
+    /// Reject requests that do not name a tenant. When false they get the
+    /// unscoped view.
+    #[serde(default)]
+    pub required: bool,
+    pub tenants: Vec<TenantConfig>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TenantConfig {
+    pub id: String,
+    /// Model providers the tenant may use, by name; `openai/*` matches every
+    /// model of a provider. Unset allows all.
+    pub models: Option<Vec<String>>,
+    /// Maximum in-flight upstream requests for the tenant.
+    pub max_concurrent_requests: Option<usize>,
+    /// Used instead of the top-level `routing_preferences` when the request
+    /// carries none of its own.
+    pub routing_preferences: Option<Vec<TopLevelRoutingPreference>>,
+    /// Cap on the output tokens the tenant's requests may ask for.
+    pub output_token_budget: Option<OutputTokenBudget>,
+}
+
+impl TenantConfig {
+    pub fn allows_model(&self, provider_name: &str) -> bool {
+        let Some(models) = &self.models else {
+            return true;
+        };
+        models
+            .iter()
+            .any(|pattern| match pattern.strip_suffix('*') {
+                Some(prefix) => provider_name.starts_with(prefix),
+                None => pattern == provider_name,
+            })
+    }
+}
+
+/// Connect to every configured provider when brightstaff starts and after
+/// `model_providers` changes, so the first user request finds a warm
+/// connection. Enabled by the presence of the section.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct WarmupSettings {
+    /// Also send each provider a one-token completion. Verifies credentials
+    /// and model access, but is a billed request.
+    pub probe_request: Option<bool>,
+    /// Time allowed per provider, e.g. `"10s"`. Defaults to 10 seconds.
+    pub timeout: Option<String>,
+}
+
+/// Fetch remote `image_url`s and inline them as base64 before sending a
+/// request to providers that reject image URLs. Enabled by the presence of
+/// the section; only hosts in `allowed_hosts` are fetched.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ImageFetchSettings {
+    /// Hosts images may be fetched from; `*.example.com` also matches subdomains.
+    #[serde(default)]
+    pub allowed_hosts: Vec<String>,
+    /// Largest image fetched, in bytes. Defaults to 5 MiB.
+    pub max_bytes: Option<usize>,
+    /// Time allowed per image, e.g. `"5s"`. Defaults to 10 seconds.
+    pub timeout: Option<String>,
+    /// Provider interfaces that get inlined images. Defaults to
+    /// `anthropic` and `amazon_bedrock`.
+    pub providers: Option<Vec<LlmProviderType>>,
+}
+
+/// A WASM module with request and/or response hooks, loaded by brightstaff at
+/// startup. Plugins run in the order they are listed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginConfig {
+    pub name: String,
+    /// Path to the `.wasm` module.
+    pub path: String,
+    /// Instructions a single hook call may execute before it is aborted.
+    /// Defaults to 100 million.
+    pub max_fuel: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TrafficRecordingMode {
+    /// Forward as usual and save each upstream exchange.
+    Record,
+    /// Answer from saved exchanges only; requests without one fail.
+    Replay,
+}
+
+/// Upstream exchanges saved to `directory`, with the timing of every
+/// streamed chunk, or served back from it in place of the upstream.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrafficRecordingSettings {
+    pub mode: TrafficRecordingMode,
+    pub directory: String,
+    /// Top-level request body fields saved as `"[redacted]"`, e.g. `user`.
+    pub redact_fields: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UsageExportFormat {
+    #[default]
+    Csv,
+    Parquet,
+}
+
+/// Hourly usage and cost totals per client key and model, written once the
+/// hour is over to `directory` and/or the S3 location `s3_url`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageExportSettings {
+    pub directory: Option<String>,
+    /// `s3://bucket/prefix`; credentials and region come from the standard
+    /// `AWS_*` environment variables.
+    pub s3_url: Option<String>,
+    #[serde(default)]
+    pub format: UsageExportFormat,
+    /// Header whose value identifies the client key. Defaults to
+    /// `authorization`. Keys are exported hashed, or as the tenant id.
+    pub key_header: Option<String>,
+}
+
+/// Ships every completed exchange (request, response, usage and signal
+/// report) to one sink in the background, for building evaluation datasets.
+/// Exactly one of `s3_url`, `kafka` and `http` is set.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ArchiveSettings {
+    /// `s3://bucket/prefix`; each batch is written as one JSON Lines object.
+    pub s3_url: Option<String>,
+    pub kafka: Option<KafkaRestProxy>,
+    pub http: Option<HttpArchiveSink>,
+    /// JSON fields blanked wherever they appear in request and response bodies.
+    pub redact_fields: Option<Vec<String>>,
+    /// Records shipped together. Defaults to 100.
+    pub batch_size: Option<usize>,
+    /// Longest a record waits for its batch to fill. Defaults to 5000.
+    pub flush_interval_ms: Option<u64>,
+    /// Records waiting to be shipped; further records are dropped. Defaults to 10000.
+    pub queue_size: Option<usize>,
+}
+
+/// Kafka topic written through a Kafka REST Proxy (v2 API).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KafkaRestProxy {
+    pub rest_proxy_url: String,
+    pub topic: String,
+}
+
+/// HTTP collector receiving each batch as a JSON Lines `POST`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HttpArchiveSink {
+    pub url: String,
+    /// Extra headers sent with every batch, e.g. for authentication.
+    pub headers: Option<HashMap<String, String>>,
+}
+
+/// Gateway-managed RAG: before routing, the latest user message is sent to
+/// `endpoint` as `{"query", "top_k"}` and the returned `documents` are put
+/// in front of it as context, within `max_context_tokens`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetrievalSettings {
+    pub endpoint: String,
+    /// Documents requested per query. Defaults to 5.
+    pub top_k: Option<usize>,
+    /// Token budget for the injected documents. Defaults to 2000.
+    pub max_context_tokens: Option<usize>,
+    /// Requests go upstream without context when retrieval takes longer.
+    /// Defaults to 2000.
+    pub timeout_ms: Option<u64>,
+    /// Extra headers sent to the endpoint, e.g. for authentication.
+    pub headers: Option<HashMap<String, String>>,
+}
+
+/// Looks for credentials in outbound prompts: known key formats, private
+/// keys, connection strings with passwords and long high-entropy tokens.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct SecretGuardrailSettings {
+    /// What happens to a prompt carrying a secret. Defaults to `mask`.
+    #[serde(default)]
+    pub action: SecretAction,
+    /// Flag long random-looking tokens that match no known format.
+    /// Defaults to true.
+    pub detect_high_entropy: Option<bool>,
+    /// Bits per character above which a token counts as random. Defaults to 4.3.
+    pub entropy_threshold: Option<f64>,
+    /// Regexes for values that are never flagged, e.g. well-known test keys.
+    pub allow: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SecretAction {
+    /// Replace each secret with `[REDACTED:<kind>]` and forward the request.
+    #[default]
+    Mask,
+    /// Fail the request with a 400.
+    Block,
+}
+
+/// Content moderation for `/v1/moderations` and, optionally, for every
+/// prompt before it is routed.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ModerationSettings {
+    /// Model used when a moderation request names none and for screening
+    /// prompts: an OpenAI moderation model such as
+    /// `openai/omni-moderation-latest`, a Bedrock guardrail as
+    /// `amazon_bedrock/<identifier>:<version>`, or `local`. Defaults to
+    /// `local` when `local_categories` is set, otherwise
+    /// `openai/omni-moderation-latest`.
+    pub model: Option<String>,
+    /// The `local` classifier: regexes per category, matched case-insensitively.
+    pub local_categories: Option<HashMap<String, Vec<String>>>,
+    /// Refuse LLM requests whose prompt is flagged. Defaults to false.
+    #[serde(default)]
+    pub screen_prompts: bool,
+}
+
+/// Publishes a small completion event for every finished request (ids,
+/// model, latency, tokens, cost and quality signal) to NATS and/or Kafka.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct EventBusSettings {
+    pub nats: Option<NatsEventSink>,
+    pub kafka: Option<KafkaRestProxy>,
+    /// Events waiting to be published; further events are dropped. Defaults to 10000.
+    pub queue_size: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NatsEventSink {
+    /// e.g. `nats://nats:4222`.
+    pub url: String,
+    /// Defaults to `plano.completions`.
+    pub subject: Option<String>,
+}
+
+/// Lets clients resume an interrupted SSE stream by resending the request
+/// with `Last-Event-ID`. Streamed events get ids and are kept in a replay
+/// window; generation continues upstream when the client disconnects.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct StreamResumptionSettings {
+    /// Events kept per response for replay. Defaults to 512.
+    pub replay_events: Option<usize>,
+    /// How long a finished response can still be resumed. Defaults to 60.
+    pub retention_secs: Option<u64>,
+}
+
+/// SSE comments sent while a stream waits for its first token, so proxies
+/// and browsers that close idle connections keep it open.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct StreamKeepAliveSettings {
+    /// Seconds between comments. Defaults to 15.
+    pub interval_secs: Option<u64>,
+}
+
+/// Faults injected into responses to exercise retries, fallbacks and stream
+/// recovery. For staging only; every rate is a fraction from 0 to 1.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ChaosSettings {
+    /// Share of responses held back by `latency`.
+    pub latency_rate: Option<f64>,
+    /// Added delay, e.g. `"2s"`.
+    pub latency: Option<String>,
+    /// Share of responses replaced with an `error_status` error.
+    pub error_rate: Option<f64>,
+    /// Status of injected errors. Defaults to 503.
+    pub error_status: Option<u16>,
+    /// Share of streams cut off after their first chunks.
+    pub truncate_rate: Option<f64>,
+    /// Share of streams with an unparseable SSE event spliced in.
+    pub malformed_sse_rate: Option<f64>,
+}
+
+/// `POST /v1/conversations/title`: short conversation titles generated by a
+/// cheap model. The endpoint is disabled unless the section is present.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConversationTitleSettings {
+    /// Model (or alias) that writes the titles.
+    pub model: String,
+    /// Leading messages of the conversation given to the model. Defaults to 4.
+    pub max_turns: Option<usize>,
+}
+
+/// Runtime administration API served by brightstaff under `/admin`. The API
+/// is disabled unless `api_key` is set.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct AdminSettings {
+    /// Bearer token required on every `/admin` request.
+    pub api_key: Option<String>,
+    /// Number of configuration versions kept for rollback. Defaults to 10.
+    pub config_history: Option<usize>,
+}
+
+/// Token prices for one model in USD per million tokens.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ModelPricing {
+    pub input_per_million: f64,
+    pub output_per_million: f64,
+    /// Rate for prompt tokens served from the provider's prompt cache.
+    pub cached_input_per_million: Option<f64>,
+}
+
+/// Caps on in-flight upstream requests. Requests that cannot get a slot within
+/// `queue_timeout_ms` are shed with a 503 instead of opening another connection.
+/// Per-provider caps are set with `max_concurrent_requests` on the provider.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ConcurrencyLimits {
+    /// Maximum in-flight upstream requests across all providers.
+    pub max_concurrent_requests: Option<usize>,
+    /// Maximum in-flight upstream requests per client key.
+    pub max_concurrent_requests_per_key: Option<usize>,
+    /// Header whose value identifies the client key. Defaults to `authorization`.
+    pub key_header: Option<String>,
+    /// How long a request may wait for a free slot. Defaults to 0 (shed immediately).
+    pub queue_timeout_ms: Option<u64>,
+    /// Header selecting the priority class, `interactive` or `batch`.
+    /// Defaults to `x-arch-priority`; requests without it are interactive.
+    pub priority_header: Option<String>,
+    /// Client keys (values of `key_header`, without a `Bearer ` prefix) whose
+    /// requests are batch unless the priority header says otherwise.
+    pub batch_keys: Option<Vec<String>>,
+    /// Share of each limit batch requests may occupy; the remaining slots are
+    /// kept for interactive requests. Defaults to 0.8.
+    pub batch_share: Option<f64>,
+    /// How long a batch request may wait for a slot. Defaults to `queue_timeout_ms`.
+    pub batch_queue_timeout_ms: Option<u64>,
+}
+
+/// Socket and HTTP/1 tuning for the brightstaff listener and its upstream
+/// HTTP client. Unset values keep the hyper/reqwest defaults.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ConnectionSettings {
+    pub listener: Option<ListenerConnectionSettings>,
+    pub upstream: Option<UpstreamConnectionSettings>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ListenerConnectionSettings {
+    pub tcp_nodelay: Option<bool>,
+    /// Idle time before the first TCP keep-alive probe is sent.
+    pub tcp_keepalive_secs: Option<u64>,
+    /// Interval between TCP keep-alive probes.
+    pub tcp_keepalive_interval_secs: Option<u64>,
+    /// Whether to reuse client connections for multiple HTTP/1 requests. Defaults to true.
+    pub http1_keep_alive: Option<bool>,
+    /// Close connections that do not send a full request head within this time.
+    pub http1_header_read_timeout_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct UpstreamConnectionSettings {
+    pub tcp_nodelay: Option<bool>,
+    /// Idle time before the first TCP keep-alive probe is sent.
+    pub tcp_keepalive_secs: Option<u64>,
+    /// Interval between TCP keep-alive probes.
+    pub tcp_keepalive_interval_secs: Option<u64>,
+    /// Maximum time to wait between reads of an upstream response.
+    pub read_timeout_ms: Option<u64>,
+    /// How long idle pooled connections are kept before being closed.
+    pub pool_idle_timeout_secs: Option<u64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -234,6 +785,13 @@ pub struct Overrides {
     pub llm_routing_model: Option<String>,
     pub agent_orchestration_model: Option<String>,
     pub orchestrator_model_context_length: Option<usize>,
+    /// Honor the `x-arch-upstream-endpoint` header to redirect a single request
+    /// to another base URL. Only enable when clients are trusted.
+    pub allow_upstream_endpoint_override: Option<bool>,
+    /// Hosts the override header may target (`*.example.com` matches subdomains).
+    /// Required with `allow_upstream_endpoint_override`; no host is allowed
+    /// when unset.
+    pub upstream_endpoint_allowed_hosts: Option<Vec<String>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -350,6 +908,13 @@ pub struct RatelimitHeader {
     pub value: Option<String>,
 }
 
+/// Whether a credential is a `file:/path` or `vault:<path>#<field>`
+/// reference. References stay in the rendered config; brightstaff reads the
+/// secret and hands it to the LLM gateway per request.
+pub fn is_secret_reference(value: &str) -> bool {
+    value.starts_with("file:") || value.starts_with("vault:")
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 //TODO: use enum for model, but if there is a new model, we need to update the code
 pub struct EmbeddingProviver {
@@ -373,6 +938,8 @@ pub enum LlmProviderType {
     Xiaomi,
     #[serde(rename = "gemini")]
     Gemini,
+    #[serde(rename = "vertex_ai")]
+    VertexAI,
     #[serde(rename = "xai")]
     XAI,
     #[serde(rename = "together_ai")]
@@ -393,6 +960,12 @@ pub enum LlmProviderType {
     Plano,
     #[serde(rename = "digitalocean")]
     DigitalOcean,
+    #[serde(rename = "cohere")]
+    Cohere,
+    #[serde(rename = "deepgram")]
+    Deepgram,
+    #[serde(rename = "mock")]
+    Mock,
 }
 
 impl Display for LlmProviderType {
@@ -402,6 +975,7 @@ impl Display for LlmProviderType {
             LlmProviderType::Deepseek => write!(f, "deepseek"),
             LlmProviderType::Groq => write!(f, "groq"),
             LlmProviderType::Gemini => write!(f, "gemini"),
+            LlmProviderType::VertexAI => write!(f, "vertex_ai"),
             LlmProviderType::Mistral => write!(f, "mistral"),
             LlmProviderType::OpenAI => write!(f, "openai"),
             LlmProviderType::Xiaomi => write!(f, "xiaomi"),
@@ -415,6 +989,9 @@ impl Display for LlmProviderType {
             LlmProviderType::AmazonBedrock => write!(f, "amazon_bedrock"),
             LlmProviderType::Plano => write!(f, "plano"),
             LlmProviderType::DigitalOcean => write!(f, "digitalocean"),
+            LlmProviderType::Cohere => write!(f, "cohere"),
+            LlmProviderType::Deepgram => write!(f, "deepgram"),
+            LlmProviderType::Mock => write!(f, "mock"),
         }
     }
 }
@@ -469,7 +1046,12 @@ impl serde::Serialize for OrchestrationPreference {
 pub struct LlmProvider {
     pub name: String,
     pub provider_interface: LlmProviderType,
+    /// Literal key, or a `file:`/`vault:` reference that brightstaff resolves
+    /// (see [`is_secret_reference`]).
     pub access_key: Option<String>,
+    /// Standby credential used once brightstaff switches the provider to its
+    /// secondary key slot, for rotation without a restart.
+    pub secondary_access_key: Option<String>,
     pub model: Option<String>,
     pub default: Option<bool>,
     pub stream: Option<bool>,
@@ -481,6 +1063,165 @@ pub struct LlmProvider {
     pub base_url_path_prefix: Option<String>,
     pub internal: Option<bool>,
     pub passthrough_auth: Option<bool>,
+    pub max_concurrent_requests: Option<usize>,
+    /// Per-API upstream path overrides keyed by `chat_completions`, `messages`,
+    /// `responses`, `converse`, `converse_stream`, `invoke_model`,
+    /// `invoke_model_with_response_stream`, `generate_content`,
+    /// `stream_generate_content`, `cohere_chat`, `ollama_chat`, `completions`,
+    /// `embeddings`, `images_generations`, `moderations` or
+    /// `audio_transcriptions`, for servers that do not use the standard paths.
+    /// `{model}` is replaced with the model id.
+    pub endpoint_paths: Option<HashMap<String, String>>,
+    /// Name of a registered custom provider serving this model. Its
+    /// endpoint and auth replace the ones of `provider_interface`, which still
+    /// picks the wire API.
+    pub custom_provider: Option<String>,
+    /// Extra or replacement upstream headers, merged over the API defaults
+    /// (e.g. `anthropic-version`, `anthropic-beta`). `{api_key}` is replaced
+    /// with the provider credential; an empty value removes a default header.
+    pub http_headers: Option<HashMap<String, String>>,
+    /// Client certificate for upstreams behind mutual-TLS proxies. Requires an
+    /// `https` base_url; applied to the provider's Envoy cluster.
+    pub tls: Option<UpstreamTlsConfig>,
+    /// HTTP CONNECT proxy (`http://host:port`) for this provider's egress.
+    pub proxy: Option<String>,
+    /// Extra base URLs for the same deployment, used in order when the primary
+    /// endpoint is unhealthy. Applied to the provider's Envoy cluster.
+    pub failover_base_urls: Option<Vec<String>>,
+    pub health_check: Option<ProviderHealthCheck>,
+    /// Upstream request timeout (e.g. `120s`), replacing the route default.
+    pub timeout: Option<String>,
+    /// Retries on connect failures, resets, 429 and 5xx responses.
+    pub max_retries: Option<u32>,
+    /// Upstream request body fields set when the client leaves them out.
+    /// Keys may be dotted paths (e.g. `generationConfig.temperature`).
+    pub default_params: Option<HashMap<String, serde_json::Value>>,
+    /// Upper bounds for numeric upstream request body fields, keyed like
+    /// `default_params`. Larger client values are lowered to the bound.
+    pub param_limits: Option<HashMap<String, f64>>,
+    /// Output limit sent to upstreams that require one (Anthropic) when the
+    /// client sets none. Defaults to 4096.
+    pub default_max_tokens: Option<u32>,
+    /// Behaviour of a `mock` provider; ignored by every other provider.
+    pub mock: Option<MockProviderSettings>,
+    /// Project and credentials of a `vertex_ai` provider; ignored by every
+    /// other provider.
+    pub vertex_ai: Option<VertexAiSettings>,
+    /// Deployment and API version of an `azure_openai` provider; ignored by
+    /// every other provider.
+    pub azure_openai: Option<AzureOpenAiSettings>,
+    /// API and authentication of an `amazon_bedrock` provider; ignored by
+    /// every other provider.
+    pub amazon_bedrock: Option<AmazonBedrockSettings>,
+}
+
+/// Amazon Bedrock settings.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct AmazonBedrockSettings {
+    /// Defaults to `converse`.
+    pub api: Option<AmazonBedrockApiKind>,
+    /// Defaults to `bearer`, which sends `access_key` as a Bedrock API key.
+    pub auth: Option<AmazonBedrockAuth>,
+    /// Signing region. Defaults to the region in the base URL's host.
+    pub region: Option<String>,
+    /// IAM credentials for `sigv4`. Without them brightstaff resolves
+    /// credentials from the default AWS chain.
+    pub access_key_id: Option<String>,
+    pub secret_access_key: Option<String>,
+    pub session_token: Option<String>,
+}
+
+/// How requests to a Bedrock provider are authenticated.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum AmazonBedrockAuth {
+    #[default]
+    Bearer,
+    /// AWS Signature Version 4 with IAM credentials.
+    Sigv4,
+}
+
+/// Bedrock runtime API a provider targets. `invoke_model` sends the model's
+/// native JSON body (Claude Messages or Titan Text) and serves OpenAI chat
+/// completions clients, plus Anthropic Messages clients of Claude models;
+/// other clients keep using Converse.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum AmazonBedrockApiKind {
+    #[default]
+    Converse,
+    InvokeModel,
+}
+
+/// Azure OpenAI settings. Requests go to
+/// `/openai/deployments/{deployment}/...?api-version={api_version}` with the
+/// key in an `api-key` header.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct AzureOpenAiSettings {
+    /// Deployment serving the provider's model. Defaults to the model name.
+    pub deployment: Option<String>,
+    /// Defaults to hermesllm's `AZURE_OPENAI_API_VERSION`.
+    pub api_version: Option<String>,
+}
+
+/// Google Cloud settings for a `vertex_ai` provider. The CLI turns
+/// `project_id` and `location` into the provider's base URL when none is
+/// given.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct VertexAiSettings {
+    pub project_id: String,
+    pub location: String,
+    /// Path to a service account JSON key. Brightstaff mints and refreshes
+    /// OAuth2 access tokens from it; without one, `access_key` must hold a
+    /// valid access token.
+    pub service_account_key_file: Option<String>,
+    /// Publisher of the provider's models. Defaults to `google`.
+    pub publisher: Option<VertexAiPublisher>,
+}
+
+/// Model publisher on Vertex AI, which decides the API. `anthropic` sends
+/// every client API to Claude's Messages API through `rawPredict`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum VertexAiPublisher {
+    #[default]
+    Google,
+    Anthropic,
+}
+
+/// Canned responses from the built-in `mock` provider, which brightstaff
+/// answers itself in the client's API shape, for tests and SDK development.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct MockProviderSettings {
+    /// Reply text. Defaults to a fixed sentence naming the model.
+    pub response: Option<String>,
+    /// Wait before the response starts, e.g. `"250ms"`.
+    pub delay: Option<String>,
+    /// Rate at which streamed words are sent; unset sends them all at once.
+    pub tokens_per_second: Option<f64>,
+    /// Fraction of requests, from 0 to 1, failed with `error_status`.
+    pub error_rate: Option<f64>,
+    /// Status of injected errors. Defaults to 500.
+    pub error_status: Option<u16>,
+}
+
+/// Active HTTP health check for a provider's endpoints. Durations use Envoy's
+/// format (e.g. `10s`).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ProviderHealthCheck {
+    pub path: String,
+    pub interval: Option<String>,
+    pub timeout: Option<String>,
+    pub healthy_threshold: Option<u32>,
+    pub unhealthy_threshold: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct UpstreamTlsConfig {
+    pub client_cert_path: String,
+    pub client_key_path: String,
+    /// CA bundle used to verify the upstream, overriding `upstream_tls_ca_path`.
+    pub ca_cert_path: Option<String>,
 }
 
 pub trait IntoModels {
@@ -497,6 +1238,7 @@ impl IntoModels for Vec<LlmProvider> {
                 object: Some("model".to_string()),
                 created: 0,
                 owned_by: "system".to_string(),
+                capabilities: Some(provider.capabilities()),
             })
             .collect();
 
@@ -524,6 +1266,24 @@ impl Default for LlmProvider {
             base_url_path_prefix: None,
             internal: None,
             passthrough_auth: None,
+            max_concurrent_requests: None,
+            endpoint_paths: None,
+            custom_provider: None,
+            http_headers: None,
+            tls: None,
+            proxy: None,
+            failover_base_urls: None,
+            health_check: None,
+            timeout: None,
+            max_retries: None,
+            default_params: None,
+            param_limits: None,
+            default_max_tokens: None,
+            mock: None,
+            vertex_ai: None,
+            azure_openai: None,
+            amazon_bedrock: None,
+            secondary_access_key: None,
         }
     }
 }
@@ -540,6 +1300,97 @@ impl LlmProvider {
     pub fn to_provider_id(&self) -> hermesllm::ProviderId {
         self.provider_interface.to_provider_id()
     }
+
+    /// The registered custom provider named by `custom_provider`
+    pub fn resolve_custom_provider(&self) -> Option<Arc<CustomProvider>> {
+        self.custom_provider.as_deref().and_then(custom_provider)
+    }
+
+    /// What the provider serves this entry's model with
+    pub fn capabilities(&self) -> ProviderCapabilities {
+        self.to_provider_id()
+            .capabilities(self.model.as_deref().unwrap_or(&self.name))
+    }
+
+    /// Model id sent upstream. For Azure OpenAI this is the configured
+    /// deployment, falling back to the model name.
+    pub fn upstream_model(&self) -> Option<&str> {
+        self.azure_openai
+            .as_ref()
+            .filter(|_| self.provider_interface == LlmProviderType::AzureOpenAI)
+            .and_then(|azure| azure.deployment.as_deref())
+            .or(self.model.as_deref())
+    }
+
+    /// Upstream API for a client API. Same as the provider id's mapping,
+    /// except that Bedrock providers configured for `invoke_model` send chat
+    /// completions, and Messages for Claude models, to InvokeModel instead of
+    /// Converse, and
+    /// Vertex AI providers of Anthropic models use the Messages API.
+    pub fn compatible_api_for_client(
+        &self,
+        client_api: &SupportedAPIsFromClient,
+        is_streaming: bool,
+    ) -> SupportedUpstreamAPIs {
+        if self.provider_interface == LlmProviderType::VertexAI
+            && self
+                .vertex_ai
+                .as_ref()
+                .and_then(|vertex| vertex.publisher)
+                .unwrap_or_default()
+                == VertexAiPublisher::Anthropic
+        {
+            return SupportedUpstreamAPIs::AnthropicMessagesAPI(AnthropicApi::Messages);
+        }
+        let upstream_api = self
+            .to_provider_id()
+            .compatible_api_for_client(client_api, is_streaming);
+        let invoke_model = self.provider_interface == LlmProviderType::AmazonBedrock
+            && self
+                .amazon_bedrock
+                .as_ref()
+                .and_then(|bedrock| bedrock.api)
+                .unwrap_or_default()
+                == AmazonBedrockApiKind::InvokeModel
+            && match client_api {
+                SupportedAPIsFromClient::OpenAIChatCompletions(_) => true,
+                // Only Claude takes a Messages body on InvokeModel
+                SupportedAPIsFromClient::AnthropicMessagesAPI(_) => {
+                    self.model
+                        .as_deref()
+                        .and_then(InvokeModelFamily::from_model_id)
+                        == Some(InvokeModelFamily::Anthropic)
+                }
+                _ => false,
+            };
+        match upstream_api {
+            SupportedUpstreamAPIs::AmazonBedrockConverse(_) if invoke_model => {
+                SupportedUpstreamAPIs::AmazonBedrockInvokeModel(AmazonBedrockApi::InvokeModel)
+            }
+            SupportedUpstreamAPIs::AmazonBedrockConverseStream(_) if invoke_model => {
+                SupportedUpstreamAPIs::AmazonBedrockInvokeModelStream(
+                    AmazonBedrockApi::InvokeModelWithResponseStream,
+                )
+            }
+            upstream_api => upstream_api,
+        }
+    }
+
+    /// Bedrock settings of a provider that signs its requests with SigV4.
+    pub fn bedrock_sigv4(&self) -> Option<&AmazonBedrockSettings> {
+        self.amazon_bedrock
+            .as_ref()
+            .filter(|_| self.provider_interface == LlmProviderType::AmazonBedrock)
+            .filter(|bedrock| bedrock.auth == Some(AmazonBedrockAuth::Sigv4))
+    }
+
+    /// `api-version` for Azure OpenAI requests, when configured.
+    pub fn azure_api_version(&self) -> Option<&str> {
+        self.azure_openai
+            .as_ref()
+            .filter(|_| self.provider_interface == LlmProviderType::AzureOpenAI)
+            .and_then(|azure| azure.api_version.as_deref())
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -639,7 +1490,10 @@ mod test {
     use pretty_assertions::assert_eq;
     use std::fs;
 
-    use super::{IntoModels, LlmProvider, LlmProviderType};
+    use super::{
+        AmazonBedrockApiKind, AmazonBedrockSettings, AzureOpenAiSettings, IntoModels, LlmProvider,
+        LlmProviderType, VertexAiPublisher, VertexAiSettings,
+    };
     use crate::api::open_ai::ToolType;
 
     #[test]
@@ -750,4 +1604,111 @@ mod test {
         assert!(model_ids.contains(&"openai-gpt4".to_string()));
         assert!(!model_ids.contains(&"plano-orchestrator".to_string()));
     }
+
+    #[test]
+    fn test_azure_openai_upstream_model_is_the_deployment() {
+        let mut provider = LlmProvider {
+            name: "azure_openai/gpt-4.1".to_string(),
+            provider_interface: LlmProviderType::AzureOpenAI,
+            model: Some("gpt-4.1".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(provider.upstream_model(), Some("gpt-4.1"));
+        assert_eq!(provider.azure_api_version(), None);
+
+        provider.azure_openai = Some(AzureOpenAiSettings {
+            deployment: Some("prod-gpt41".to_string()),
+            api_version: Some("2024-10-21".to_string()),
+        });
+        assert_eq!(provider.upstream_model(), Some("prod-gpt41"));
+        assert_eq!(provider.azure_api_version(), Some("2024-10-21"));
+
+        // Ignored for other providers
+        provider.provider_interface = LlmProviderType::OpenAI;
+        assert_eq!(provider.upstream_model(), Some("gpt-4.1"));
+        assert_eq!(provider.azure_api_version(), None);
+    }
+
+    #[test]
+    fn test_amazon_bedrock_invoke_model_client_apis() {
+        use hermesllm::apis::anthropic::AnthropicApi;
+        use hermesllm::apis::openai::OpenAIApi;
+        use hermesllm::clients::endpoints::{SupportedAPIsFromClient, SupportedUpstreamAPIs};
+
+        let chat = SupportedAPIsFromClient::OpenAIChatCompletions(OpenAIApi::ChatCompletions);
+        let messages = SupportedAPIsFromClient::AnthropicMessagesAPI(AnthropicApi::Messages);
+        let responses = SupportedAPIsFromClient::OpenAIResponsesAPI(OpenAIApi::Responses);
+        let mut provider = LlmProvider {
+            name: "amazon_bedrock/amazon.titan-text-express-v1".to_string(),
+            provider_interface: LlmProviderType::AmazonBedrock,
+            model: Some("amazon.titan-text-express-v1".to_string()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            provider.compatible_api_for_client(&chat, false),
+            SupportedUpstreamAPIs::AmazonBedrockConverse(_)
+        ));
+
+        provider.amazon_bedrock = Some(AmazonBedrockSettings {
+            api: Some(AmazonBedrockApiKind::InvokeModel),
+            ..Default::default()
+        });
+        assert!(matches!(
+            provider.compatible_api_for_client(&chat, false),
+            SupportedUpstreamAPIs::AmazonBedrockInvokeModel(_)
+        ));
+        assert!(matches!(
+            provider.compatible_api_for_client(&chat, true),
+            SupportedUpstreamAPIs::AmazonBedrockInvokeModelStream(_)
+        ));
+        // Titan has no Messages body, so Messages clients keep using Converse
+        assert!(matches!(
+            provider.compatible_api_for_client(&messages, true),
+            SupportedUpstreamAPIs::AmazonBedrockConverseStream(_)
+        ));
+        assert!(matches!(
+            provider.compatible_api_for_client(&responses, true),
+            SupportedUpstreamAPIs::AmazonBedrockConverseStream(_)
+        ));
+
+        provider.model = Some("us.anthropic.claude-3-5-sonnet-20241022-v2:0".to_string());
+        assert!(matches!(
+            provider.compatible_api_for_client(&messages, true),
+            SupportedUpstreamAPIs::AmazonBedrockInvokeModelStream(_)
+        ));
+        assert!(matches!(
+            provider.compatible_api_for_client(&responses, true),
+            SupportedUpstreamAPIs::AmazonBedrockConverseStream(_)
+        ));
+    }
+
+    #[test]
+    fn test_vertex_ai_anthropic_publisher_uses_messages_api() {
+        use hermesllm::apis::openai::OpenAIApi;
+        use hermesllm::clients::endpoints::{SupportedAPIsFromClient, SupportedUpstreamAPIs};
+
+        let chat = SupportedAPIsFromClient::OpenAIChatCompletions(OpenAIApi::ChatCompletions);
+        let mut provider = LlmProvider {
+            name: "vertex_ai/claude-sonnet-4@20250514".to_string(),
+            provider_interface: LlmProviderType::VertexAI,
+            model: Some("claude-sonnet-4@20250514".to_string()),
+            vertex_ai: Some(VertexAiSettings {
+                project_id: "my-project".to_string(),
+                location: "us-east5".to_string(),
+                service_account_key_file: None,
+                publisher: None,
+            }),
+            ..Default::default()
+        };
+        assert!(matches!(
+            provider.compatible_api_for_client(&chat, false),
+            SupportedUpstreamAPIs::GeminiGenerateContent(_)
+        ));
+
+        provider.vertex_ai.as_mut().unwrap().publisher = Some(VertexAiPublisher::Anthropic);
+        assert!(matches!(
+            provider.compatible_api_for_client(&chat, true),
+            SupportedUpstreamAPIs::AnthropicMessagesAPI(_)
+        ));
+    }
 }
@@ -9,13 +9,54 @@ pub const API_REQUEST_TIMEOUT_MS: u64 = 30000; // 30 seconds
 pub const MODEL_SERVER_REQUEST_TIMEOUT_MS: u64 = 30000; // 30 seconds
 pub const MODEL_SERVER_NAME: &str = "bright_staff";
 pub const ARCH_ROUTING_HEADER: &str = "x-arch-llm-provider";
+pub const ARCH_TENANT_HEADER: &str = "x-arch-tenant";
+/// Set to `secondary` to send the provider's `secondary_access_key` upstream.
+/// Only honored on connections from the local host (brightstaff).
+pub const ARCH_ACCESS_KEY_SLOT_HEADER: &str = "x-arch-access-key-slot";
+/// Access token brightstaff minted for a `vertex_ai` provider with a service account key.
+pub const ARCH_UPSTREAM_TOKEN_HEADER: &str = "x-arch-upstream-token";
+/// Provider key brightstaff read from the `file:` or `vault:` reference in
+/// the provider's `access_key` (or `secondary_access_key`).
+pub const ARCH_UPSTREAM_ACCESS_KEY_HEADER: &str = "x-arch-upstream-access-key";
+/// AWS credentials, as JSON, brightstaff resolved for an `amazon_bedrock`
+/// provider that signs requests with SigV4.
+pub const ARCH_UPSTREAM_AWS_CREDENTIALS_HEADER: &str = "x-arch-upstream-aws-credentials";
+/// Provider that produced the response, set on routes with a content-filter fallback.
+pub const ARCH_SERVED_BY_HEADER: &str = "x-arch-served-by";
+/// Why the response came from a fallback provider, e.g. `content_filter`.
+pub const ARCH_FALLBACK_REASON_HEADER: &str = "x-arch-fallback-reason";
+/// Set on responses from routes with a response cache: `hit`, `stale` or `miss`.
+pub const ARCH_CACHE_HEADER: &str = "x-arch-cache";
+/// Expected USD cost of the request, from its prompt and output limit.
+pub const ARCH_COST_ESTIMATE_HEADER: &str = "x-arch-cost-estimate";
+/// USD cost of a complete response, from the usage the provider reported.
+pub const ARCH_COST_ACTUAL_HEADER: &str = "x-arch-cost-actual";
+/// Forces a `mock` provider to fail the request with this status.
+pub const ARCH_MOCK_STATUS_HEADER: &str = "x-arch-mock-status";
 pub const MESSAGES_KEY: &str = "messages";
 pub const ARCH_PROVIDER_HINT_HEADER: &str = "x-arch-llm-provider-hint";
 pub const ARCH_IS_STREAMING_HEADER: &str = "x-arch-streaming-request";
+/// Set to `true` to reject conversions between APIs that would drop input.
+pub const ARCH_STRICT_TRANSFORM_HEADER: &str = "x-arch-strict-transform";
 pub const CHAT_COMPLETIONS_PATH: &str = "/v1/chat/completions";
+pub const COMPLETIONS_PATH: &str = "/v1/completions";
 pub const OPENAI_RESPONSES_API_PATH: &str = "/v1/responses";
 pub const MESSAGES_PATH: &str = "/v1/messages";
+pub const ANTHROPIC_COUNT_TOKENS_PATH: &str = "/v1/messages/count_tokens";
+pub const ANTHROPIC_MESSAGE_BATCHES_PATH: &str = "/v1/messages/batches";
+pub const EMBEDDINGS_PATH: &str = "/v1/embeddings";
+pub const IMAGES_GENERATIONS_PATH: &str = "/v1/images/generations";
+pub const AUDIO_TRANSCRIPTIONS_PATH: &str = "/v1/audio/transcriptions";
+pub const MODERATIONS_PATH: &str = "/v1/moderations";
+pub const REALTIME_PATH: &str = "/v1/realtime";
+pub const BATCHES_PATH: &str = "/v1/batches";
+pub const FILES_PATH: &str = "/v1/files";
+pub const TOKENIZE_PATH: &str = "/v1/tokenize";
+pub const CONVERSATION_TITLE_PATH: &str = "/v1/conversations/title";
 pub const HEALTHZ_PATH: &str = "/healthz";
+pub const READYZ_PATH: &str = "/readyz";
+/// Gateway path that opens a connection to the hinted provider without calling its API.
+pub const WARMUP_PATH: &str = "/plano/warmup";
 pub const X_ARCH_STATE_HEADER: &str = "x-arch-state";
 pub const X_ARCH_API_RESPONSE: &str = "x-arch-api-response-message";
 pub const X_ARCH_TOOL_CALL: &str = "x-arch-tool-call-message";
@@ -27,11 +68,17 @@ pub const ENVOY_ORIGINAL_PATH_HEADER: &str = "x-envoy-original-path";
 pub const TRACE_PARENT_HEADER: &str = "traceparent";
 pub const ARCH_INTERNAL_CLUSTER_NAME: &str = "arch_internal";
 pub const ARCH_UPSTREAM_HOST_HEADER: &str = "x-arch-upstream";
+pub const ARCH_UPSTREAM_ENDPOINT_HEADER: &str = "x-arch-upstream-endpoint";
+pub const UPSTREAM_OVERRIDE_CLUSTER: &str = "arch_upstream_override";
+pub const UPSTREAM_OVERRIDE_TLS_CLUSTER: &str = "arch_upstream_override_tls";
 pub const ARCH_MODEL_PREFIX: &str = "Arch";
 pub const HALLUCINATION_TEMPLATE: &str =
     "It seems I'm missing some information. Could you provide the following details ";
 pub const OTEL_COLLECTOR_HTTP: &str = "opentelemetry_collector_http";
 pub const LLM_ROUTE_HEADER: &str = "x-arch-llm-route";
 pub const ENVOY_RETRY_HEADER: &str = "x-envoy-max-retries";
+pub const ENVOY_RETRY_ON_HEADER: &str = "x-envoy-retry-on";
+pub const ENVOY_RETRIABLE_STATUS_CODES_HEADER: &str = "x-envoy-retriable-status-codes";
+pub const ENVOY_UPSTREAM_TIMEOUT_HEADER: &str = "x-envoy-upstream-rq-timeout-ms";
 pub const BRIGHT_STAFF_SERVICE_NAME: &str = "brightstaff";
 pub const PLANO_FC_CLUSTER: &str = "plano";
@@ -61,6 +61,9 @@ pub enum BrightStaffError {
     #[error("Conversation state not found for previous_response_id: {0}")]
     ConversationStateNotFound(String),
 
+    #[error("No stored response with id '{0}'")]
+    ResponseNotFound(String),
+
     #[error("Internal server error")]
     InternalServerError(String),
 
@@ -76,6 +79,39 @@ pub enum BrightStaffError {
     #[error("Stream error: {0}")]
     StreamError(String),
 
+    #[error("Too many concurrent requests ({0} limit reached)")]
+    ConcurrencyLimitExceeded(String),
+
+    #[error("Missing tenant header '{0}'")]
+    TenantRequired(String),
+
+    #[error("Unknown tenant '{0}'")]
+    UnknownTenant(String),
+
+    #[error("Model '{model}' is not available to tenant '{tenant}'")]
+    ModelNotAllowedForTenant { model: String, tenant: String },
+
+    #[error("Requested {requested} output tokens, above the budget of {limit}")]
+    OutputTokenBudgetExceeded { requested: u64, limit: u32 },
+
+    #[error("All candidate models are rate limited: {}", models.join(", "))]
+    ProvidersCoolingDown {
+        models: Vec<String>,
+        retry_after_secs: u64,
+    },
+
+    #[error("Model '{model}' is blocked by alias '{alias}'")]
+    ModelBlocked { model: String, alias: String },
+
+    #[error("Request contains credentials ({}); refusing to forward it", kinds.join(", "))]
+    SecretDetected { kinds: Vec<String> },
+
+    #[error("Request was flagged by moderation ({}); refusing to forward it", categories.join(", "))]
+    ContentFlagged { categories: Vec<String> },
+
+    #[error("No recorded response for this request to model '{0}'")]
+    NoRecordedResponse(String),
+
     #[error("Failed to create response: {0}")]
     ResponseCreationFailed(#[from] hyper::http::Error),
 }
@@ -99,6 +135,12 @@ impl BrightStaffError {
                 json!({ "previous_response_id": prev_resp_id }),
             ),
 
+            BrightStaffError::ResponseNotFound(response_id) => (
+                StatusCode::NOT_FOUND,
+                "ResponseNotFound",
+                json!({ "response_id": response_id }),
+            ),
+
             BrightStaffError::InternalServerError(reason) => (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 "InternalServerError",
@@ -123,6 +165,69 @@ impl BrightStaffError {
                 json!({ "reason": reason }),
             ),
 
+            BrightStaffError::ConcurrencyLimitExceeded(scope) => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                "ConcurrencyLimitExceeded",
+                json!({ "scope": scope }),
+            ),
+
+            BrightStaffError::TenantRequired(header) => (
+                StatusCode::BAD_REQUEST,
+                "TenantRequired",
+                json!({ "header": header }),
+            ),
+
+            BrightStaffError::UnknownTenant(tenant) => (
+                StatusCode::FORBIDDEN,
+                "UnknownTenant",
+                json!({ "tenant": tenant }),
+            ),
+
+            BrightStaffError::ModelNotAllowedForTenant { model, tenant } => (
+                StatusCode::FORBIDDEN,
+                "ModelNotAllowedForTenant",
+                json!({ "rejected_model_id": model, "tenant": tenant }),
+            ),
+
+            BrightStaffError::OutputTokenBudgetExceeded { requested, limit } => (
+                StatusCode::BAD_REQUEST,
+                "OutputTokenBudgetExceeded",
+                json!({ "requested": requested, "limit": limit }),
+            ),
+
+            BrightStaffError::ProvidersCoolingDown {
+                models,
+                retry_after_secs,
+            } => (
+                StatusCode::TOO_MANY_REQUESTS,
+                "ProvidersCoolingDown",
+                json!({ "models": models, "retry_after_secs": retry_after_secs }),
+            ),
+
+            BrightStaffError::ModelBlocked { model, alias } => (
+                StatusCode::FORBIDDEN,
+                "ModelBlocked",
+                json!({ "rejected_model_id": model, "alias": alias }),
+            ),
+
+            BrightStaffError::SecretDetected { kinds } => (
+                StatusCode::BAD_REQUEST,
+                "SecretDetected",
+                json!({ "kinds": kinds }),
+            ),
+
+            BrightStaffError::ContentFlagged { categories } => (
+                StatusCode::BAD_REQUEST,
+                "ContentFlagged",
+                json!({ "categories": categories }),
+            ),
+
+            BrightStaffError::NoRecordedResponse(model) => (
+                StatusCode::NOT_FOUND,
+                "NoRecordedResponse",
+                json!({ "model": model }),
+            ),
+
             BrightStaffError::ResponseCreationFailed(reason) => (
                 StatusCode::BAD_REQUEST,
                 "ResponseCreationFailed",
@@ -147,17 +252,22 @@ impl BrightStaffError {
             .map_err(|never| match never {}) // This handles the "Infallible" error type
             .boxed();
 
-        Response::builder()
+        let mut builder = Response::builder()
             .status(status)
-            .header("content-type", "application/json")
-            .body(boxed_body)
-            .unwrap_or_else(|_| {
-                Response::new(
-                    Full::new(Bytes::from("Internal Error"))
-                        .map_err(|never| match never {})
-                        .boxed(),
-                )
-            })
+            .header("content-type", "application/json");
+        if let BrightStaffError::ProvidersCoolingDown {
+            retry_after_secs, ..
+        } = &self
+        {
+            builder = builder.header("retry-after", retry_after_secs.to_string());
+        }
+        builder.body(boxed_body).unwrap_or_else(|_| {
+            Response::new(
+                Full::new(Bytes::from("Internal Error"))
+                    .map_err(|never| match never {})
+                    .boxed(),
+            )
+        })
     }
 }
 
@@ -204,6 +314,17 @@ mod tests {
         assert_eq!(body["error"]["code"], "ForwardedError");
     }
 
+    #[test]
+    fn test_cooling_down_sets_retry_after() {
+        let response = BrightStaffError::ProvidersCoolingDown {
+            models: vec!["openai/gpt-4o".to_string()],
+            retry_after_secs: 12,
+        }
+        .into_response();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()["retry-after"], "12");
+    }
+
     #[tokio::test]
     async fn test_hyper_error_wrapping() {
         // Manually trigger a hyper error by creating an invalid URI/Header
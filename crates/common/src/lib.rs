@@ -1,11 +1,15 @@
 pub mod api;
+pub mod config_validation;
 pub mod configuration;
 pub mod consts;
 pub mod errors;
 pub mod http;
 pub mod llm_providers;
+pub mod model_aliases;
 pub mod path;
 pub mod pii;
+pub mod pricing;
+pub mod provider_params;
 pub mod ratelimit;
 pub mod routing;
 pub mod stats;
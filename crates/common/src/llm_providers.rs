@@ -40,6 +40,7 @@ impl LlmProviders {
                 object: Some("model".to_string()),
                 created: 0,
                 owned_by: provider.to_provider_id().to_string(),
+                capabilities: Some(provider.capabilities()),
             })
             .collect();
 
@@ -80,6 +81,29 @@ impl LlmProviders {
 
         None
     }
+
+    /// Other configured providers serving the model `name` resolves to, when
+    /// `name` is a bare model id or alias rather than a provider name.
+    pub fn serving_same_model(&self, name: &str) -> Vec<Arc<LlmProvider>> {
+        let Some(provider) = self.get(name).filter(|p| p.name != name) else {
+            return Vec::new();
+        };
+        let Some(model) = provider.model.as_deref() else {
+            return Vec::new();
+        };
+        let mut serving: Vec<Arc<LlmProvider>> = self
+            .providers
+            .iter()
+            .filter(|(key, other)| {
+                **key == other.name
+                    && other.name != provider.name
+                    && other.model.as_deref() == Some(model)
+            })
+            .map(|(_, other)| Arc::clone(other))
+            .collect();
+        serving.sort_by(|a, b| a.name.cmp(&b.name));
+        serving
+    }
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -277,6 +301,24 @@ mod tests {
             internal: None,
             stream: None,
             passthrough_auth: None,
+            max_concurrent_requests: None,
+            endpoint_paths: None,
+            custom_provider: None,
+            http_headers: None,
+            tls: None,
+            proxy: None,
+            failover_base_urls: None,
+            health_check: None,
+            timeout: None,
+            max_retries: None,
+            default_params: None,
+            param_limits: None,
+            default_max_tokens: None,
+            mock: None,
+            vertex_ai: None,
+            azure_openai: None,
+            amazon_bedrock: None,
+            secondary_access_key: None,
         }
     }
 
@@ -333,4 +375,26 @@ mod tests {
             .wildcard_providers
             .contains_key("custom-provider"));
     }
+
+    #[test]
+    fn test_providers_serving_same_model() {
+        let providers = vec![
+            create_test_provider("openai/gpt-4o", Some("gpt-4o".to_string())),
+            create_test_provider("azure/gpt-4o", Some("gpt-4o".to_string())),
+            create_test_provider("openai/gpt-4o-mini", Some("gpt-4o-mini".to_string())),
+        ];
+        let llm_providers = LlmProviders::try_from(providers).unwrap();
+
+        let serving: Vec<String> = llm_providers
+            .serving_same_model("gpt-4o")
+            .iter()
+            .map(|p| p.name.clone())
+            .collect();
+        assert_eq!(serving.len(), 1);
+        assert_ne!(serving[0], llm_providers.get("gpt-4o").unwrap().name);
+
+        // A provider name pins the request to that provider
+        assert!(llm_providers.serving_same_model("azure/gpt-4o").is_empty());
+        assert!(llm_providers.serving_same_model("gpt-4o-mini").is_empty());
+    }
 }
@@ -0,0 +1,150 @@
+//! Model alias lookup.
+//!
+//! A `model_aliases` key is an exact model name, a wildcard pattern where `*`
+//! matches any run of characters (`gpt-4o*`), or a regular expression after a
+//! `regex:` prefix (`regex:^claude-3-(opus|sonnet)$`). Patterns must match the
+//! whole requested name. An exact key always wins; among matching patterns the
+//! highest `priority` wins, with ties going to the alphabetically first key.
+
+use std::collections::HashMap;
+
+use regex::Regex;
+
+use crate::configuration::ModelAlias;
+
+const REGEX_PREFIX: &str = "regex:";
+
+/// What a requested model name resolves to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AliasResolution<'a> {
+    Target(&'a str),
+    /// Blocked by the alias with this key.
+    Blocked(&'a str),
+}
+
+/// The matcher for an alias key, or `None` for an exact name.
+pub fn compile_pattern(key: &str) -> Result<Option<Regex>, regex::Error> {
+    if let Some(pattern) = key.strip_prefix(REGEX_PREFIX) {
+        return Regex::new(&format!("^(?:{pattern})$")).map(Some);
+    }
+    if !key.contains('*') {
+        return Ok(None);
+    }
+    let pattern = key
+        .split('*')
+        .map(regex::escape)
+        .collect::<Vec<_>>()
+        .join(".*");
+    Regex::new(&format!("^{pattern}$")).map(Some)
+}
+
+#[derive(Debug, Default)]
+pub struct ModelAliasTable {
+    exact: HashMap<String, ModelAlias>,
+    /// Sorted by descending priority, then key.
+    patterns: Vec<(String, Regex, ModelAlias)>,
+}
+
+impl ModelAliasTable {
+    /// Patterns that fail to compile are skipped; config validation reports them.
+    pub fn new(aliases: Option<&HashMap<String, ModelAlias>>) -> Self {
+        let mut table = Self::default();
+        for (key, alias) in aliases.into_iter().flatten() {
+            match compile_pattern(key) {
+                Ok(None) => {
+                    table.exact.insert(key.clone(), alias.clone());
+                }
+                Ok(Some(regex)) => table.patterns.push((key.clone(), regex, alias.clone())),
+                Err(_) => {}
+            }
+        }
+        table.patterns.sort_by(|(a_key, _, a), (b_key, _, b)| {
+            b.priority
+                .unwrap_or(0)
+                .cmp(&a.priority.unwrap_or(0))
+                .then_with(|| a_key.cmp(b_key))
+        });
+        table
+    }
+
+    /// The alias for `model`, or `None` when no key matches it.
+    pub fn resolve(&self, model: &str) -> Option<AliasResolution<'_>> {
+        let (key, alias) = self
+            .exact
+            .get_key_value(model)
+            .map(|(key, alias)| (key.as_str(), alias))
+            .or_else(|| {
+                self.patterns
+                    .iter()
+                    .find(|(_, regex, _)| regex.is_match(model))
+                    .map(|(key, _, alias)| (key.as_str(), alias))
+            })?;
+        if alias.blocked.unwrap_or(false) {
+            return Some(AliasResolution::Blocked(key));
+        }
+        alias.target.as_deref().map(AliasResolution::Target)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alias(target: Option<&str>, blocked: bool, priority: Option<i32>) -> ModelAlias {
+        ModelAlias {
+            target: target.map(str::to_string),
+            blocked: blocked.then_some(true),
+            priority,
+        }
+    }
+
+    #[test]
+    fn compiles_wildcards_and_regexes() {
+        assert!(compile_pattern("gpt-4o").unwrap().is_none());
+        let wildcard = compile_pattern("gpt-4o*").unwrap().unwrap();
+        assert!(wildcard.is_match("gpt-4o-mini"));
+        assert!(!wildcard.is_match("openai/gpt-4o"));
+        let dotted = compile_pattern("*.preview").unwrap().unwrap();
+        assert!(!dotted.is_match("gpt-5-preview"));
+        let regex = compile_pattern("regex:claude-3-(opus|sonnet)")
+            .unwrap()
+            .unwrap();
+        assert!(regex.is_match("claude-3-opus"));
+        assert!(!regex.is_match("claude-3-opus-latest"));
+        assert!(compile_pattern("regex:(").is_err());
+    }
+
+    #[test]
+    fn exact_keys_win_then_priority_then_key_order() {
+        let aliases = HashMap::from([
+            (
+                "gpt-4o-mini".to_string(),
+                alias(Some("openai/gpt-4o-mini"), false, None),
+            ),
+            (
+                "gpt-4o*".to_string(),
+                alias(Some("openai/gpt-4o"), false, None),
+            ),
+            ("*-preview".to_string(), alias(None, true, Some(10))),
+            (
+                "gpt-*".to_string(),
+                alias(Some("openai/gpt-5"), false, None),
+            ),
+        ]);
+        let table = ModelAliasTable::new(Some(&aliases));
+        assert_eq!(
+            table.resolve("gpt-4o-mini"),
+            Some(AliasResolution::Target("openai/gpt-4o-mini"))
+        );
+        assert_eq!(
+            table.resolve("gpt-4o-preview"),
+            Some(AliasResolution::Blocked("*-preview"))
+        );
+        // Equal priority: "gpt-*" sorts before "gpt-4o*".
+        assert_eq!(
+            table.resolve("gpt-4o-2024"),
+            Some(AliasResolution::Target("openai/gpt-5"))
+        );
+        assert_eq!(table.resolve("claude-sonnet"), None);
+    }
+}
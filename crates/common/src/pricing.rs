@@ -0,0 +1,173 @@
+use std::collections::HashMap;
+
+use crate::configuration::ModelPricing;
+
+/// Built-in list prices in USD per million tokens: `(model, input, output, cached input)`.
+/// Entries in the config `pricing:` section replace these per model.
+const BUILTIN_PRICING: &[(&str, f64, f64, Option<f64>)] = &[
+    // OpenAI
+    ("gpt-5", 1.25, 10.0, Some(0.125)),
+    ("gpt-5-mini", 0.25, 2.0, Some(0.025)),
+    ("gpt-5-nano", 0.05, 0.4, Some(0.005)),
+    ("gpt-4.1", 2.0, 8.0, Some(0.5)),
+    ("gpt-4.1-mini", 0.4, 1.6, Some(0.1)),
+    ("gpt-4.1-nano", 0.1, 0.4, Some(0.025)),
+    ("gpt-4o", 2.5, 10.0, Some(1.25)),
+    ("gpt-4o-mini", 0.15, 0.6, Some(0.075)),
+    ("o3", 2.0, 8.0, Some(0.5)),
+    ("o4-mini", 1.1, 4.4, Some(0.275)),
+    // Anthropic
+    ("claude-opus-4-1", 15.0, 75.0, Some(1.5)),
+    ("claude-opus-4", 15.0, 75.0, Some(1.5)),
+    ("claude-sonnet-4-5", 3.0, 15.0, Some(0.3)),
+    ("claude-sonnet-4", 3.0, 15.0, Some(0.3)),
+    ("claude-3-7-sonnet", 3.0, 15.0, Some(0.3)),
+    ("claude-haiku-4-5", 1.0, 5.0, Some(0.1)),
+    ("claude-3-5-haiku", 0.8, 4.0, Some(0.08)),
+    // Google
+    ("gemini-2.5-pro", 1.25, 10.0, Some(0.31)),
+    ("gemini-2.5-flash", 0.3, 2.5, Some(0.075)),
+    ("gemini-2.0-flash", 0.1, 0.4, Some(0.025)),
+    // DeepSeek
+    ("deepseek-chat", 0.27, 1.1, Some(0.07)),
+    ("deepseek-reasoner", 0.55, 2.19, Some(0.14)),
+    // Mistral
+    ("mistral-large-latest", 2.0, 6.0, None),
+    ("mistral-small-latest", 0.1, 0.3, None),
+    // xAI
+    ("grok-4", 3.0, 15.0, Some(0.75)),
+    ("grok-3", 3.0, 15.0, Some(0.75)),
+    ("grok-3-mini", 0.3, 0.5, Some(0.075)),
+];
+
+const TOKENS_PER_PRICE_UNIT: f64 = 1_000_000.0;
+
+/// Per-model token prices used for span cost attributes and cost-aware routing.
+#[derive(Debug, Clone)]
+pub struct PricingTable {
+    prices: HashMap<String, ModelPricing>,
+}
+
+impl Default for PricingTable {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+impl PricingTable {
+    /// Built-in defaults overlaid with the configured `pricing:` entries.
+    pub fn new(configured: Option<&HashMap<String, ModelPricing>>) -> Self {
+        let mut prices: HashMap<String, ModelPricing> = BUILTIN_PRICING
+            .iter()
+            .map(|(model, input, output, cached)| {
+                (
+                    model.to_string(),
+                    ModelPricing {
+                        input_per_million: *input,
+                        output_per_million: *output,
+                        cached_input_per_million: *cached,
+                    },
+                )
+            })
+            .collect();
+        if let Some(configured) = configured {
+            prices.extend(configured.iter().map(|(k, v)| (k.clone(), v.clone())));
+        }
+        Self { prices }
+    }
+
+    /// Look up a model by its exact name, then without the `provider/`
+    /// prefix, then without a trailing `-YYYYMMDD` snapshot date.
+    pub fn price_for(&self, model: &str) -> Option<&ModelPricing> {
+        if let Some(price) = self.prices.get(model) {
+            return Some(price);
+        }
+        let bare = model.split_once('/').map_or(model, |(_, m)| m);
+        self.prices
+            .get(bare)
+            .or_else(|| self.prices.get(strip_date_suffix(bare)))
+    }
+
+    /// USD cost of a call. Cached input tokens are part of `prompt_tokens`;
+    /// they are billed at the cached rate when the model has one.
+    pub fn cost_usd(
+        &self,
+        model: &str,
+        prompt_tokens: i64,
+        completion_tokens: i64,
+        cached_input_tokens: i64,
+    ) -> Option<f64> {
+        let price = self.price_for(model)?;
+        let (fresh, cached) = match price.cached_input_per_million {
+            Some(cached_rate) => (
+                (prompt_tokens - cached_input_tokens).max(0) as f64 * price.input_per_million,
+                cached_input_tokens.max(0) as f64 * cached_rate,
+            ),
+            None => (prompt_tokens as f64 * price.input_per_million, 0.0),
+        };
+        let output = completion_tokens as f64 * price.output_per_million;
+        Some((fresh + cached + output) / TOKENS_PER_PRICE_UNIT)
+    }
+
+    /// Input plus output price per million tokens, the figure used to rank
+    /// models for `prefer: cheapest`.
+    pub fn blended_price(&self, model: &str) -> Option<f64> {
+        self.price_for(model)
+            .map(|p| p.input_per_million + p.output_per_million)
+    }
+}
+
+fn strip_date_suffix(model: &str) -> &str {
+    match model.rsplit_once('-') {
+        Some((base, date)) if date.len() == 8 && date.bytes().all(|b| b.is_ascii_digit()) => base,
+        _ => model,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lookup_strips_provider_prefix_and_snapshot_date() {
+        let table = PricingTable::default();
+        assert!(table.price_for("openai/gpt-4o").is_some());
+        assert_eq!(
+            table.price_for("anthropic/claude-sonnet-4-20250514"),
+            table.price_for("claude-sonnet-4")
+        );
+        assert!(table.price_for("custom/unknown-model").is_none());
+    }
+
+    #[test]
+    fn configured_prices_override_builtins() {
+        let configured = HashMap::from([(
+            "openai/gpt-4o".to_string(),
+            ModelPricing {
+                input_per_million: 1.0,
+                output_per_million: 2.0,
+                cached_input_per_million: None,
+            },
+        )]);
+        let table = PricingTable::new(Some(&configured));
+        assert_eq!(table.blended_price("openai/gpt-4o"), Some(3.0));
+        // other names for the model still resolve to the built-in entry
+        assert_eq!(table.blended_price("gpt-4o"), Some(12.5));
+    }
+
+    #[test]
+    fn cost_bills_cached_tokens_at_cached_rate() {
+        let table = PricingTable::default();
+        // gpt-4o: 2.5 in, 10 out, 1.25 cached per million
+        let cost = table
+            .cost_usd("gpt-4o", 1_000_000, 100_000, 400_000)
+            .unwrap();
+        assert!((cost - (1.5 + 1.0 + 0.5)).abs() < 1e-9);
+
+        // mistral-small has no cached rate: all prompt tokens at the input rate
+        let cost = table
+            .cost_usd("mistral-small-latest", 1_000_000, 0, 400_000)
+            .unwrap();
+        assert!((cost - 0.1).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,143 @@
+use serde_json::{Map, Number, Value};
+
+use crate::configuration::LlmProvider;
+
+/// Apply a provider's `default_params` and `param_limits` to a serialized
+/// upstream request body. Returns `None` when the provider configures
+/// neither, so the caller can forward the original bytes untouched.
+pub fn apply_provider_params(
+    body: &[u8],
+    provider: &LlmProvider,
+) -> Result<Option<Vec<u8>>, serde_json::Error> {
+    if provider.default_params.is_none() && provider.param_limits.is_none() {
+        return Ok(None);
+    }
+    let mut request: Value = serde_json::from_slice(body)?;
+    let Some(root) = request.as_object_mut() else {
+        return Ok(None);
+    };
+
+    for (path, value) in provider.default_params.iter().flatten() {
+        let (parent, key) = parent_object(root, path);
+        if parent.get(key).is_none_or(Value::is_null) {
+            parent.insert(key.to_string(), value.clone());
+        }
+    }
+    for (path, limit) in provider.param_limits.iter().flatten() {
+        if let Some(current) = path
+            .split('.')
+            .try_fold(&mut request, |value, segment| value.get_mut(segment))
+        {
+            clamp(current, *limit);
+        }
+    }
+
+    serde_json::to_vec(&request).map(Some)
+}
+
+/// Object holding the last segment of a dotted `path`, creating intermediate
+/// objects as needed. A non-object in the way is replaced.
+fn parent_object<'a>(
+    root: &'a mut Map<String, Value>,
+    path: &'a str,
+) -> (&'a mut Map<String, Value>, &'a str) {
+    let mut segments: Vec<&str> = path.split('.').collect();
+    let key = segments.pop().unwrap_or(path);
+    let mut current = root;
+    for segment in segments {
+        let entry = current
+            .entry(segment.to_string())
+            .or_insert_with(|| Value::Object(Map::new()));
+        if !entry.is_object() {
+            *entry = Value::Object(Map::new());
+        }
+        current = entry
+            .as_object_mut()
+            .expect("entry was just made an object");
+    }
+    (current, key)
+}
+
+fn clamp(value: &mut Value, limit: f64) {
+    let Some(current) = value.as_f64() else {
+        return;
+    };
+    if current <= limit {
+        return;
+    }
+    *value = if value.is_f64() || limit.fract() != 0.0 {
+        Number::from_f64(limit).map_or(Value::Null, Value::Number)
+    } else {
+        Value::Number(Number::from(limit as i64))
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    fn provider(defaults: Value, limits: &[(&str, f64)]) -> LlmProvider {
+        LlmProvider {
+            default_params: serde_json::from_value(defaults).ok(),
+            param_limits: Some(
+                limits
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), *v))
+                    .collect::<HashMap<_, _>>(),
+            ),
+            ..Default::default()
+        }
+    }
+
+    fn apply(body: Value, provider: &LlmProvider) -> Value {
+        let bytes = apply_provider_params(&serde_json::to_vec(&body).unwrap(), provider)
+            .unwrap()
+            .unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[test]
+    fn defaults_fill_missing_fields_only() {
+        let provider = provider(json!({"temperature": 0.2, "max_tokens": 1024}), &[]);
+        let out = apply(json!({"model": "gpt-4o", "temperature": 0.9}), &provider);
+        assert_eq!(out["temperature"], json!(0.9));
+        assert_eq!(out["max_tokens"], json!(1024));
+    }
+
+    #[test]
+    fn limits_clamp_numbers_and_keep_integer_type() {
+        let provider = provider(json!({}), &[("temperature", 1.0), ("max_tokens", 4096.0)]);
+        let out = apply(
+            json!({"temperature": 1.7, "max_tokens": 100000, "top_p": 0.5}),
+            &provider,
+        );
+        assert_eq!(out["temperature"], json!(1.0));
+        assert_eq!(out["max_tokens"], json!(4096));
+        assert_eq!(out["top_p"], json!(0.5));
+
+        let out = apply(json!({"max_tokens": 10}), &provider);
+        assert_eq!(out["max_tokens"], json!(10));
+    }
+
+    #[test]
+    fn dotted_paths_reach_nested_config() {
+        let provider = provider(
+            json!({"inferenceConfig.temperature": 0.3}),
+            &[("inferenceConfig.maxTokens", 2048.0)],
+        );
+        let out = apply(json!({"inferenceConfig": {"maxTokens": 8192}}), &provider);
+        assert_eq!(
+            out["inferenceConfig"],
+            json!({"maxTokens": 2048, "temperature": 0.3})
+        );
+    }
+
+    #[test]
+    fn untouched_without_params() {
+        assert!(apply_provider_params(b"{}", &LlmProvider::default())
+            .unwrap()
+            .is_none());
+    }
+}
@@ -5,3 +5,35 @@ pub fn shorten_string(s: &str) -> String {
         s.to_string()
     }
 }
+
+/// Parse an Envoy-style duration (`250ms`, `30s`, `5m`, `1h`) into milliseconds.
+pub fn parse_duration_ms(value: &str) -> Option<u64> {
+    let value = value.trim();
+    let (number, unit_ms) = if let Some(n) = value.strip_suffix("ms") {
+        (n, 1)
+    } else if let Some(n) = value.strip_suffix('s') {
+        (n, 1_000)
+    } else if let Some(n) = value.strip_suffix('m') {
+        (n, 60_000)
+    } else if let Some(n) = value.strip_suffix('h') {
+        (n, 3_600_000)
+    } else {
+        return None;
+    };
+    number.trim().parse::<u64>().ok()?.checked_mul(unit_ms)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_envoy_durations() {
+        assert_eq!(parse_duration_ms("250ms"), Some(250));
+        assert_eq!(parse_duration_ms("30s"), Some(30_000));
+        assert_eq!(parse_duration_ms("5m"), Some(300_000));
+        assert_eq!(parse_duration_ms("1h"), Some(3_600_000));
+        assert_eq!(parse_duration_ms("30"), None);
+        assert_eq!(parse_duration_ms("1.5s"), None);
+    }
+}
@@ -7,17 +7,21 @@ use thiserror::Error;
 
 use super::ApiDefinition;
 use crate::providers::request::{ProviderRequest, ProviderRequestError};
+use crate::providers::response::UsageDetails;
 use crate::providers::streaming_response::ProviderStreamResponse;
 
 // ============================================================================
-// AMAZON BEDROCK CONVERSE API ENUMERATION
+// AMAZON BEDROCK API ENUMERATION
 // ============================================================================
 
-/// Enum for all supported Amazon Bedrock Converse APIs
+/// Enum for all supported Amazon Bedrock APIs
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum AmazonBedrockApi {
     Converse,
     ConverseStream,
+    /// Model-specific request bodies, for models or features Converse lacks
+    InvokeModel,
+    InvokeModelWithResponseStream,
 }
 
 impl ApiDefinition for AmazonBedrockApi {
@@ -25,6 +29,10 @@ impl ApiDefinition for AmazonBedrockApi {
         match self {
             AmazonBedrockApi::Converse => "/model/{modelId}/converse",
             AmazonBedrockApi::ConverseStream => "/model/{modelId}/converse-stream",
+            AmazonBedrockApi::InvokeModel => "/model/{modelId}/invoke",
+            AmazonBedrockApi::InvokeModelWithResponseStream => {
+                "/model/{modelId}/invoke-with-response-stream"
+            }
         }
     }
 
@@ -33,6 +41,10 @@ impl ApiDefinition for AmazonBedrockApi {
             Some(AmazonBedrockApi::Converse)
         } else if endpoint.ends_with("/converse-stream") {
             Some(AmazonBedrockApi::ConverseStream)
+        } else if endpoint.ends_with("/invoke") {
+            Some(AmazonBedrockApi::InvokeModel)
+        } else if endpoint.ends_with("/invoke-with-response-stream") {
+            Some(AmazonBedrockApi::InvokeModelWithResponseStream)
         } else {
             None
         }
@@ -40,13 +52,15 @@ impl ApiDefinition for AmazonBedrockApi {
 
     fn supports_streaming(&self) -> bool {
         match self {
-            AmazonBedrockApi::Converse => false,
-            AmazonBedrockApi::ConverseStream => true,
+            AmazonBedrockApi::Converse | AmazonBedrockApi::InvokeModel => false,
+            AmazonBedrockApi::ConverseStream | AmazonBedrockApi::InvokeModelWithResponseStream => {
+                true
+            }
         }
     }
 
     fn supports_tools(&self) -> bool {
-        // Converse API has native tool support
+        // Converse API has native tool support; InvokeModel only for Claude
         true
     }
 
@@ -56,7 +70,12 @@ impl ApiDefinition for AmazonBedrockApi {
     }
 
     fn all_variants() -> Vec<Self> {
-        vec![AmazonBedrockApi::Converse, AmazonBedrockApi::ConverseStream]
+        vec![
+            AmazonBedrockApi::Converse,
+            AmazonBedrockApi::ConverseStream,
+            AmazonBedrockApi::InvokeModel,
+            AmazonBedrockApi::InvokeModelWithResponseStream,
+        ]
     }
 }
 
@@ -229,6 +248,7 @@ impl ProviderRequest for ConverseRequest {
                         name: None,
                         tool_calls: None,
                         tool_call_id: None,
+                        thinking_blocks: None,
                     });
                 }
             }
@@ -262,6 +282,7 @@ impl ProviderRequest for ConverseRequest {
                     name: None,
                     tool_calls: None,
                     tool_call_id: None,
+                    thinking_blocks: None,
                 });
             }
         }
@@ -1084,6 +1105,37 @@ impl TryFrom<&aws_smithy_eventstream::frame::DecodedFrame> for ConverseStreamEve
     }
 }
 
+impl ConverseStreamEvent {
+    /// The `:message-type` header of the event's frame
+    pub fn frame_message_type(&self) -> &'static str {
+        match self {
+            ConverseStreamEvent::InternalServerException(_)
+            | ConverseStreamEvent::ModelStreamErrorException(_)
+            | ConverseStreamEvent::ServiceUnavailableException(_)
+            | ConverseStreamEvent::ThrottlingException(_)
+            | ConverseStreamEvent::ValidationException(_) => "exception",
+            _ => "event",
+        }
+    }
+
+    /// The `:event-type` header of the event's frame, also sent as `:exception-type` for exceptions
+    pub fn frame_event_type(&self) -> &'static str {
+        match self {
+            ConverseStreamEvent::MessageStart(_) => "messageStart",
+            ConverseStreamEvent::ContentBlockStart(_) => "contentBlockStart",
+            ConverseStreamEvent::ContentBlockDelta(_) => "contentBlockDelta",
+            ConverseStreamEvent::ContentBlockStop(_) => "contentBlockStop",
+            ConverseStreamEvent::MessageStop(_) => "messageStop",
+            ConverseStreamEvent::Metadata(_) => "metadata",
+            ConverseStreamEvent::InternalServerException(_) => "internalServerException",
+            ConverseStreamEvent::ModelStreamErrorException(_) => "modelStreamErrorException",
+            ConverseStreamEvent::ServiceUnavailableException(_) => "serviceUnavailableException",
+            ConverseStreamEvent::ThrottlingException(_) => "throttlingException",
+            ConverseStreamEvent::ValidationException(_) => "validationException",
+        }
+    }
+}
+
 impl From<ConverseStreamEvent> for String {
     fn from(val: ConverseStreamEvent) -> String {
         let transformed_json = serde_json::to_string(&val).unwrap_or_default();
@@ -1147,6 +1199,20 @@ impl ProviderStreamResponse for ConverseStreamEvent {
             ConverseStreamEvent::ValidationException(_) => "validationException",
         })
     }
+
+    fn usage(&self) -> Option<UsageDetails> {
+        match self {
+            ConverseStreamEvent::Metadata(event) => Some(UsageDetails {
+                prompt_tokens: event.usage.input_tokens as usize,
+                completion_tokens: event.usage.output_tokens as usize,
+                total_tokens: event.usage.total_tokens as usize,
+                cached_input_tokens: event.usage.cache_read_input_tokens.map(|t| t as usize),
+                cache_creation_tokens: event.usage.cache_write_input_tokens.map(|t| t as usize),
+                reasoning_tokens: None,
+            }),
+            _ => None,
+        }
+    }
 }
 
 // Add as_str helper for ConversationRole
@@ -1159,6 +1225,496 @@ impl ConversationRole {
     }
 }
 
+// ============================================================================
+// INVOKE MODEL STRUCTURES
+// ============================================================================
+
+/// Model family of an InvokeModel target, which decides the body shape
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InvokeModelFamily {
+    Anthropic,
+    TitanText,
+}
+
+impl InvokeModelFamily {
+    /// Detect the family from a model id, inference profile id or ARN
+    /// (e.g. `us.anthropic.claude-3-5-sonnet-20241022-v2:0`)
+    pub fn from_model_id(model_id: &str) -> Option<Self> {
+        if model_id.contains("anthropic.claude") {
+            Some(InvokeModelFamily::Anthropic)
+        } else if model_id.contains("amazon.titan-text") {
+            Some(InvokeModelFamily::TitanText)
+        } else {
+            None
+        }
+    }
+}
+
+/// Amazon Bedrock InvokeModel request. The model id travels in the path and
+/// streaming is chosen by endpoint, so neither is part of the body.
+#[derive(Debug, Clone)]
+pub struct InvokeModelRequest {
+    pub model_id: String,
+    pub stream: bool,
+    pub body: InvokeModelBody,
+}
+
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone)]
+pub enum InvokeModelBody {
+    /// Claude Messages body, sent with `anthropic_version` and without the
+    /// fields Bedrock rejects
+    Anthropic(crate::apis::anthropic::MessagesRequest),
+    TitanText(TitanTextRequest),
+}
+
+impl InvokeModelRequest {
+    /// Serialize the model-specific body sent to Bedrock
+    pub fn body_json(&self) -> Result<Value, serde_json::Error> {
+        match &self.body {
+            InvokeModelBody::Anthropic(request) => {
+                request.platform_body_json(crate::apis::anthropic::AnthropicPlatform::AmazonBedrock)
+            }
+            InvokeModelBody::TitanText(request) => serde_json::to_value(request),
+        }
+    }
+}
+
+impl ProviderRequest for InvokeModelRequest {
+    fn model(&self) -> &str {
+        &self.model_id
+    }
+
+    fn set_model(&mut self, model: String) {
+        self.model_id = model;
+    }
+
+    fn is_streaming(&self) -> bool {
+        self.stream
+    }
+
+    fn extract_messages_text(&self) -> String {
+        match &self.body {
+            InvokeModelBody::Anthropic(request) => request.extract_messages_text(),
+            InvokeModelBody::TitanText(request) => request.input_text.clone(),
+        }
+    }
+
+    fn get_recent_user_message(&self) -> Option<String> {
+        match &self.body {
+            InvokeModelBody::Anthropic(request) => request.get_recent_user_message(),
+            InvokeModelBody::TitanText(request) => Some(request.input_text.clone()),
+        }
+    }
+
+    fn get_tool_names(&self) -> Option<Vec<String>> {
+        match &self.body {
+            InvokeModelBody::Anthropic(request) => request.get_tool_names(),
+            InvokeModelBody::TitanText(_) => None,
+        }
+    }
+
+    fn to_bytes(&self) -> Result<Vec<u8>, ProviderRequestError> {
+        self.body_json()
+            .and_then(|body| serde_json::to_vec(&body))
+            .map_err(|e| ProviderRequestError {
+                message: format!("Failed to serialize Bedrock InvokeModel request: {}", e),
+                source: Some(Box::new(e)),
+            })
+    }
+
+    fn metadata(&self) -> &Option<HashMap<String, Value>> {
+        const NO_METADATA: Option<HashMap<String, Value>> = None;
+        match &self.body {
+            InvokeModelBody::Anthropic(request) => &request.metadata,
+            InvokeModelBody::TitanText(_) => &NO_METADATA,
+        }
+    }
+
+    fn remove_metadata_key(&mut self, key: &str) -> bool {
+        match &mut self.body {
+            InvokeModelBody::Anthropic(request) => request.remove_metadata_key(key),
+            InvokeModelBody::TitanText(_) => false,
+        }
+    }
+
+    fn get_temperature(&self) -> Option<f32> {
+        match &self.body {
+            InvokeModelBody::Anthropic(request) => request.temperature,
+            InvokeModelBody::TitanText(request) => request
+                .text_generation_config
+                .as_ref()
+                .and_then(|config| config.temperature),
+        }
+    }
+
+    fn get_messages(&self) -> Vec<crate::apis::openai::Message> {
+        use crate::apis::openai::{Message, MessageContent, Role};
+
+        match &self.body {
+            InvokeModelBody::Anthropic(request) => request.get_messages(),
+            InvokeModelBody::TitanText(request) => vec![Message {
+                role: Role::User,
+                content: Some(MessageContent::Text(request.input_text.clone())),
+                name: None,
+                tool_calls: None,
+                tool_call_id: None,
+                thinking_blocks: None,
+            }],
+        }
+    }
+
+    fn set_messages(&mut self, messages: &[crate::apis::openai::Message]) {
+        match &mut self.body {
+            InvokeModelBody::Anthropic(request) => request.set_messages(messages),
+            InvokeModelBody::TitanText(request) => {
+                request.input_text = TitanTextRequest::prompt_from_messages(messages)
+            }
+        }
+    }
+}
+
+/// Titan Text request body
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TitanTextRequest {
+    pub input_text: String,
+    pub text_generation_config: Option<TitanTextGenerationConfig>,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TitanTextGenerationConfig {
+    pub max_token_count: Option<u32>,
+    pub temperature: Option<f32>,
+    pub top_p: Option<f32>,
+    pub stop_sequences: Option<Vec<String>>,
+}
+
+impl TitanTextRequest {
+    /// Flatten a conversation into Titan's `User:`/`Bot:` prompt, ending with
+    /// an open `Bot:` turn for the model to complete
+    pub fn prompt_from_messages(messages: &[crate::apis::openai::Message]) -> String {
+        use crate::apis::openai::Role;
+        use crate::transforms::lib::ExtractText;
+
+        let mut prompt = String::new();
+        for message in messages {
+            let text = message.content.extract_text();
+            match message.role {
+                Role::System | Role::Developer => prompt.push_str(&format!("{}\n\n", text)),
+                Role::User | Role::Tool => prompt.push_str(&format!("User: {}\n", text)),
+                Role::Assistant => prompt.push_str(&format!("Bot: {}\n", text)),
+            }
+        }
+        prompt.push_str("Bot:");
+        prompt
+    }
+}
+
+/// Titan Text response body
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TitanTextResponse {
+    pub input_text_token_count: Option<u32>,
+    pub results: Vec<TitanTextResult>,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TitanTextResult {
+    pub token_count: Option<u32>,
+    pub output_text: String,
+    /// `FINISH`, `LENGTH`, `STOP_CRITERIA_MET`, `CONTENT_FILTERED`, ...
+    pub completion_reason: Option<String>,
+}
+
+/// InvokeModel response body; Claude answers in the Messages shape
+#[allow(clippy::large_enum_variant)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum InvokeModelResponse {
+    Anthropic(crate::apis::anthropic::MessagesResponse),
+    TitanText(TitanTextResponse),
+}
+
+/// Decoded payload of one InvokeModelWithResponseStream `chunk` event. Claude
+/// chunks are Messages stream events.
+#[allow(clippy::large_enum_variant)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum InvokeModelStreamChunk {
+    Anthropic(crate::apis::anthropic::MessagesStreamEvent),
+    TitanText(TitanTextStreamChunk),
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TitanTextStreamChunk {
+    pub output_text: String,
+    pub index: Option<u32>,
+    pub total_output_text_token_count: Option<u32>,
+    /// Set on the last chunk
+    pub completion_reason: Option<String>,
+    pub input_text_token_count: Option<u32>,
+    /// Set on the last chunk
+    #[serde(rename = "amazon-bedrock-invocationMetrics")]
+    pub invocation_metrics: Option<InvocationMetrics>,
+}
+
+/// Token counts Bedrock appends to the last chunk of an InvokeModel stream
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct InvocationMetrics {
+    pub input_token_count: Option<u32>,
+    pub output_token_count: Option<u32>,
+    pub invocation_latency: Option<u64>,
+    pub first_byte_latency: Option<u64>,
+}
+
+/// Envelope of a `chunk` event; `bytes` holds the model's JSON payload
+#[serde_with::serde_as]
+#[derive(Deserialize, Debug)]
+struct InvokeModelPayloadPart {
+    #[serde_as(as = "serde_with::base64::Base64")]
+    bytes: Vec<u8>,
+}
+
+impl TryFrom<&aws_smithy_eventstream::frame::DecodedFrame> for InvokeModelStreamChunk {
+    type Error = BedrockError;
+
+    fn try_from(frame: &aws_smithy_eventstream::frame::DecodedFrame) -> Result<Self, Self::Error> {
+        let message = match frame {
+            aws_smithy_eventstream::frame::DecodedFrame::Complete(msg) => msg,
+            aws_smithy_eventstream::frame::DecodedFrame::Incomplete => {
+                return Err(BedrockError::Validation {
+                    message: "Expected Complete frame, got Incomplete".to_string(),
+                })
+            }
+        };
+        let header = |name: &str| {
+            message
+                .headers()
+                .iter()
+                .find(|h| h.name().as_str() == name)
+                .and_then(|h| h.value().as_string().ok())
+                .map(|value| value.as_str().to_string())
+        };
+        let payload = message.payload();
+
+        match header(":message-type").as_deref() {
+            Some("event") => {
+                let event_type = header(":event-type").unwrap_or_default();
+                if event_type != "chunk" {
+                    return Err(BedrockError::Validation {
+                        message: format!("Unknown event type: {}", event_type),
+                    });
+                }
+                let part: InvokeModelPayloadPart =
+                    serde_json::from_slice(payload).map_err(BedrockError::Serialization)?;
+                serde_json::from_slice(&part.bytes).map_err(BedrockError::Serialization)
+            }
+            Some("exception") => {
+                let exception: BedrockException =
+                    serde_json::from_slice(payload).map_err(BedrockError::Serialization)?;
+                let message = exception.message.unwrap_or_default();
+                Err(match header(":exception-type").as_deref() {
+                    Some("throttlingException") => BedrockError::Throttling { message },
+                    Some("validationException") => BedrockError::Validation { message },
+                    Some("serviceUnavailableException") => {
+                        BedrockError::ServiceUnavailable { message }
+                    }
+                    Some("modelTimeoutException") => BedrockError::ModelTimeout { message },
+                    _ => BedrockError::InternalServer { message },
+                })
+            }
+            other => Err(BedrockError::Validation {
+                message: format!("Unknown message type: {}", other.unwrap_or_default()),
+            }),
+        }
+    }
+}
+
+// ============================================================================
+// TITAN EMBEDDINGS STRUCTURES
+// ============================================================================
+
+/// Amazon Titan text embeddings InvokeModel body. Titan embeds one text per
+/// request.
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TitanEmbedRequest {
+    pub input_text: String,
+    /// 256, 512 or 1024 on Titan Text Embeddings V2
+    pub dimensions: Option<u32>,
+    pub normalize: Option<bool>,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TitanEmbedResponse {
+    pub embedding: Vec<f32>,
+    pub input_text_token_count: Option<u32>,
+}
+
+// ============================================================================
+// TITAN IMAGE GENERATOR STRUCTURES
+// ============================================================================
+
+/// Amazon Titan Image Generator InvokeModel body for text-to-image
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TitanImageRequest {
+    /// Always `TEXT_IMAGE` here
+    pub task_type: String,
+    pub text_to_image_params: TitanTextToImageParams,
+    pub image_generation_config: Option<TitanImageGenerationConfig>,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TitanTextToImageParams {
+    pub text: String,
+    pub negative_text: Option<String>,
+    /// `PRECISE` or `AUTO` on Titan Image Generator v2
+    pub style: Option<String>,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TitanImageGenerationConfig {
+    /// 1 to 5
+    pub number_of_images: Option<u32>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    /// `standard` or `premium`
+    pub quality: Option<String>,
+    pub cfg_scale: Option<f32>,
+    pub seed: Option<u32>,
+}
+
+/// Images come back base64-encoded PNGs
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TitanImageResponse {
+    #[serde(default)]
+    pub images: Vec<String>,
+    pub error: Option<String>,
+}
+
+// ============================================================================
+// APPLY GUARDRAIL STRUCTURES
+// ============================================================================
+
+/// `ApplyGuardrail` body: assess text against a configured guardrail without
+/// invoking a model
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ApplyGuardrailRequest {
+    /// `INPUT` for prompts, `OUTPUT` for model responses
+    pub source: String,
+    pub content: Vec<GuardrailContentBlock>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GuardrailContentBlock {
+    pub text: GuardrailTextBlock,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GuardrailTextBlock {
+    pub text: String,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ApplyGuardrailResponse {
+    /// `GUARDRAIL_INTERVENED` or `NONE`
+    pub action: String,
+    #[serde(default)]
+    pub assessments: Vec<GuardrailAssessment>,
+    pub usage: Option<serde_json::Value>,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct GuardrailAssessment {
+    pub content_policy: Option<GuardrailContentPolicy>,
+    pub topic_policy: Option<GuardrailTopicPolicy>,
+    pub word_policy: Option<GuardrailWordPolicy>,
+    pub sensitive_information_policy: Option<GuardrailSensitiveInformationPolicy>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct GuardrailContentPolicy {
+    #[serde(default)]
+    pub filters: Vec<GuardrailContentFilter>,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct GuardrailContentFilter {
+    /// `HATE`, `INSULTS`, `SEXUAL`, `VIOLENCE`, `MISCONDUCT` or `PROMPT_ATTACK`
+    #[serde(rename = "type")]
+    pub filter_type: String,
+    /// `NONE`, `LOW`, `MEDIUM` or `HIGH`
+    pub confidence: String,
+    /// `BLOCKED` or `NONE`
+    pub action: String,
+    pub detected: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct GuardrailTopicPolicy {
+    #[serde(default)]
+    pub topics: Vec<GuardrailTopic>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct GuardrailTopic {
+    pub name: String,
+    pub action: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct GuardrailWordPolicy {
+    #[serde(default)]
+    pub custom_words: Vec<GuardrailPolicyMatch>,
+    #[serde(default)]
+    pub managed_word_lists: Vec<GuardrailPolicyMatch>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct GuardrailSensitiveInformationPolicy {
+    #[serde(default)]
+    pub pii_entities: Vec<GuardrailPolicyMatch>,
+    #[serde(default)]
+    pub regexes: Vec<GuardrailPolicyMatch>,
+}
+
+/// A word, PII entity or regex match; `action` is `BLOCKED`, `ANONYMIZED` or
+/// `NONE`
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct GuardrailPolicyMatch {
+    #[serde(default)]
+    pub action: String,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
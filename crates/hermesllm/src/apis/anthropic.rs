@@ -1,4 +1,4 @@
-use crate::providers::response::TokenUsage;
+use crate::providers::response::{TokenUsage, UsageDetails};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use serde_with::skip_serializing_none;
@@ -120,6 +120,75 @@ pub struct MessagesRequest {
     pub stop_sequences: Option<Vec<String>>,
     pub tools: Option<Vec<MessagesTool>>,
     pub tool_choice: Option<MessagesToolChoice>,
+
+    /// Cloud platform the request is sent to, which changes the body envelope
+    #[serde(skip)]
+    pub platform: Option<AnthropicPlatform>,
+}
+
+/// Anthropic API version Bedrock expects in Claude request bodies
+pub const BEDROCK_ANTHROPIC_VERSION: &str = "bedrock-2023-05-31";
+/// Anthropic API version Vertex AI expects in Claude request bodies
+pub const VERTEX_ANTHROPIC_VERSION: &str = "vertex-2023-10-16";
+
+/// Cloud platform serving Claude. Platforms take the model id in the path and
+/// `anthropic_version` in the body instead of headers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnthropicPlatform {
+    AmazonBedrock,
+    VertexAI,
+}
+
+impl AnthropicPlatform {
+    pub fn anthropic_version(&self) -> &'static str {
+        match self {
+            AnthropicPlatform::AmazonBedrock => BEDROCK_ANTHROPIC_VERSION,
+            AnthropicPlatform::VertexAI => VERTEX_ANTHROPIC_VERSION,
+        }
+    }
+
+    /// Messages request fields the platform does not accept. Bedrock picks
+    /// streaming by endpoint; Vertex AI still reads `stream` from the body.
+    fn excluded_fields(&self) -> &'static [&'static str] {
+        match self {
+            AnthropicPlatform::AmazonBedrock => &[
+                "model",
+                "stream",
+                "metadata",
+                "container",
+                "mcp_servers",
+                "service_tier",
+            ],
+            AnthropicPlatform::VertexAI => &[
+                "model",
+                "metadata",
+                "container",
+                "mcp_servers",
+                "service_tier",
+            ],
+        }
+    }
+}
+
+impl MessagesRequest {
+    /// Serialize the body `platform` expects: `anthropic_version` added and
+    /// the fields it rejects, including `model`, removed
+    pub fn platform_body_json(
+        &self,
+        platform: AnthropicPlatform,
+    ) -> Result<Value, serde_json::Error> {
+        let mut body = serde_json::to_value(self)?;
+        if let Some(fields) = body.as_object_mut() {
+            for field in platform.excluded_fields() {
+                fields.remove(*field);
+            }
+            fields.insert(
+                "anthropic_version".to_string(),
+                Value::String(platform.anthropic_version().to_string()),
+            );
+        }
+        Ok(body)
+    }
 }
 
 // Messages API specific types
@@ -155,6 +224,60 @@ impl ExtractText for ToolResultContent {
     }
 }
 
+/// Whether the model cites a document in its answer
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CitationsConfig {
+    pub enabled: bool,
+}
+
+/// The part of a source a text block draws on
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+#[serde(tag = "type")]
+pub enum Citation {
+    /// Characters of a plain text document
+    CharLocation {
+        cited_text: String,
+        document_index: u32,
+        document_title: Option<String>,
+        start_char_index: u32,
+        end_char_index: u32,
+    },
+    /// Pages of a PDF document
+    PageLocation {
+        cited_text: String,
+        document_index: u32,
+        document_title: Option<String>,
+        start_page_number: u32,
+        end_page_number: u32,
+    },
+    /// Blocks of a custom content document
+    ContentBlockLocation {
+        cited_text: String,
+        document_index: u32,
+        document_title: Option<String>,
+        start_block_index: u32,
+        end_block_index: u32,
+    },
+    /// A result of the web search server tool
+    WebSearchResultLocation {
+        cited_text: String,
+        url: String,
+        title: Option<String>,
+        encrypted_index: String,
+    },
+    /// Blocks of a search result passed in by the client
+    SearchResultLocation {
+        cited_text: String,
+        search_result_index: u32,
+        source: String,
+        title: Option<String>,
+        start_block_index: u32,
+        end_block_index: u32,
+    },
+}
+
 #[skip_serializing_none]
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "snake_case")]
@@ -162,6 +285,8 @@ impl ExtractText for ToolResultContent {
 pub enum MessagesContentBlock {
     Text {
         text: String,
+        /// Sources backing this text, when the request enabled citations
+        citations: Option<Vec<Citation>>,
         cache_control: Option<MessagesCacheControl>,
     },
     Thinking {
@@ -169,11 +294,18 @@ pub enum MessagesContentBlock {
         signature: Option<String>,
         cache_control: Option<MessagesCacheControl>,
     },
+    /// Thinking flagged by safety systems, encrypted in `data`
+    RedactedThinking {
+        data: String,
+    },
     Image {
         source: MessagesImageSource,
     },
     Document {
         source: MessagesDocumentSource,
+        title: Option<String>,
+        /// `{"enabled": true}` to have the answer cite this document
+        citations: Option<CitationsConfig>,
     },
     ToolUse {
         id: String,
@@ -192,15 +324,18 @@ pub enum MessagesContentBlock {
         name: String,
         input: Value,
     },
+    /// `content` is a list of `web_search_result`s, or an error object
     WebSearchToolResult {
         tool_use_id: String,
         is_error: Option<bool>,
-        content: Vec<MessagesContentBlock>,
+        content: Value,
     },
+    /// `content` is a `code_execution_result` with `stdout`, `stderr` and
+    /// `return_code`, or an error object
     CodeExecutionToolResult {
         tool_use_id: String,
         is_error: Option<bool>,
-        content: Vec<MessagesContentBlock>,
+        content: Value,
     },
     McpToolUse {
         id: String,
@@ -278,12 +413,32 @@ pub struct MessagesMessage {
     pub content: MessagesMessageContent,
 }
 
+/// Anthropic-run web search, standing in for the Responses API `web_search` tool
+pub const WEB_SEARCH_TOOL_TYPE: &str = "web_search_20250305";
+/// Anthropic-run code execution, standing in for `code_interpreter`. Needs the
+/// `code-execution-2025-08-25` beta header.
+pub const CODE_EXECUTION_TOOL_TYPE: &str = "code_execution_20250825";
+
+/// A client tool, or with `kind` set a server tool Anthropic runs itself,
+/// whose settings (e.g. `max_uses`, `allowed_domains`) are kept in `options`.
 #[skip_serializing_none]
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct MessagesTool {
+    #[serde(rename = "type")]
+    pub kind: Option<String>,
     pub name: String,
     pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Value::is_null")]
     pub input_schema: Value,
+    #[serde(flatten)]
+    pub options: serde_json::Map<String, Value>,
+}
+
+impl MessagesTool {
+    /// Server tools carry a versioned type; client tools carry `custom` or none
+    pub fn is_server_tool(&self) -> bool {
+        self.kind.as_deref().is_some_and(|kind| kind != "custom")
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -401,6 +556,9 @@ pub enum MessagesContentDelta {
     ThinkingDelta { thinking: String },
     #[serde(rename = "signature_delta")]
     SignatureDelta { signature: String },
+    /// A citation for the text block being streamed
+    #[serde(rename = "citations_delta")]
+    CitationsDelta { citation: Citation },
 }
 
 #[skip_serializing_none]
@@ -425,21 +583,39 @@ impl TryFrom<&[u8]> for MessagesRequest {
     }
 }
 
+impl TokenUsage for MessagesUsage {
+    fn completion_tokens(&self) -> usize {
+        self.output_tokens as usize
+    }
+    fn prompt_tokens(&self) -> usize {
+        self.input_tokens as usize
+    }
+    fn total_tokens(&self) -> usize {
+        (self.input_tokens + self.output_tokens) as usize
+    }
+    fn cached_input_tokens(&self) -> Option<usize> {
+        self.cache_read_input_tokens.map(|t| t as usize)
+    }
+    fn cache_creation_tokens(&self) -> Option<usize> {
+        self.cache_creation_input_tokens.map(|t| t as usize)
+    }
+}
+
 impl TokenUsage for MessagesResponse {
     fn completion_tokens(&self) -> usize {
-        self.usage.output_tokens as usize
+        self.usage.completion_tokens()
     }
     fn prompt_tokens(&self) -> usize {
-        self.usage.input_tokens as usize
+        self.usage.prompt_tokens()
     }
     fn total_tokens(&self) -> usize {
-        (self.usage.input_tokens + self.usage.output_tokens) as usize
+        self.usage.total_tokens()
     }
     fn cached_input_tokens(&self) -> Option<usize> {
-        self.usage.cache_read_input_tokens.map(|t| t as usize)
+        self.usage.cached_input_tokens()
     }
     fn cache_creation_tokens(&self) -> Option<usize> {
-        self.usage.cache_creation_input_tokens.map(|t| t as usize)
+        self.usage.cache_creation_tokens()
     }
 }
 
@@ -529,7 +705,13 @@ impl ProviderRequest for MessagesRequest {
     }
 
     fn to_bytes(&self) -> Result<Vec<u8>, ProviderRequestError> {
-        serde_json::to_vec(self).map_err(|e| ProviderRequestError {
+        match self.platform {
+            Some(platform) => self
+                .platform_body_json(platform)
+                .and_then(|body| serde_json::to_vec(&body)),
+            None => serde_json::to_vec(self),
+        }
+        .map_err(|e| ProviderRequestError {
             message: format!("Failed to serialize MessagesRequest: {}", e),
             source: Some(Box::new(e)),
         })
@@ -671,6 +853,18 @@ impl ProviderStreamResponse for MessagesStreamEvent {
             MessagesStreamEvent::Ping => "ping",
         })
     }
+
+    fn usage(&self) -> Option<UsageDetails> {
+        match self {
+            MessagesStreamEvent::MessageStart { message } => {
+                Some(UsageDetails::from(&message.usage as &dyn TokenUsage))
+            }
+            MessagesStreamEvent::MessageDelta { usage, .. } => {
+                Some(UsageDetails::from(usage as &dyn TokenUsage))
+            }
+            _ => None,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -1362,6 +1556,7 @@ mod tests {
             if let MessagesContentBlock::Text {
                 text,
                 cache_control,
+                ..
             } = &system_blocks[0]
             {
                 assert_eq!(
@@ -1425,6 +1620,7 @@ mod tests {
             if let MessagesContentBlock::Text {
                 text,
                 cache_control,
+                ..
             } = &content_blocks[2]
             {
                 assert_eq!(text, "try again");
@@ -1541,4 +1737,107 @@ mod tests {
         let serialized_json = serde_json::to_value(&deserialized_request).unwrap();
         assert_eq!(request_json, serialized_json);
     }
+
+    #[test]
+    fn test_platform_body_envelopes() {
+        let request: MessagesRequest = serde_json::from_value(json!({
+            "model": "claude-sonnet-4@20250514",
+            "messages": [{"role": "user", "content": "Hi"}],
+            "max_tokens": 100,
+            "stream": true,
+            "metadata": {"user_id": "u1"}
+        }))
+        .unwrap();
+
+        assert_eq!(
+            request
+                .platform_body_json(AnthropicPlatform::VertexAI)
+                .unwrap(),
+            json!({
+                "anthropic_version": "vertex-2023-10-16",
+                "messages": [{"role": "user", "content": "Hi"}],
+                "max_tokens": 100,
+                "stream": true
+            })
+        );
+        assert_eq!(
+            request
+                .platform_body_json(AnthropicPlatform::AmazonBedrock)
+                .unwrap(),
+            json!({
+                "anthropic_version": "bedrock-2023-05-31",
+                "messages": [{"role": "user", "content": "Hi"}],
+                "max_tokens": 100
+            })
+        );
+    }
+}
+
+// ============================================================================
+// MESSAGE BATCHES STRUCTURES
+// ============================================================================
+
+/// `POST /v1/messages/batches` body
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MessageBatchCreateRequest {
+    pub requests: Vec<MessageBatchRequest>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MessageBatchRequest {
+    pub custom_id: String,
+    pub params: MessagesRequest,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MessageBatchProcessingStatus {
+    InProgress,
+    Canceling,
+    Ended,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct MessageBatchRequestCounts {
+    pub processing: u32,
+    pub succeeded: u32,
+    pub errored: u32,
+    pub canceled: u32,
+    pub expired: u32,
+}
+
+/// A message batch; timestamps are RFC 3339 strings
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MessageBatch {
+    pub id: String,
+    pub processing_status: MessageBatchProcessingStatus,
+    pub request_counts: MessageBatchRequestCounts,
+    pub created_at: String,
+    pub expires_at: Option<String>,
+    pub ended_at: Option<String>,
+    pub cancel_initiated_at: Option<String>,
+    /// Set once the batch has ended; results are JSONL of [`MessageBatchResult`]
+    pub results_url: Option<String>,
+}
+
+/// One line of a batch's results
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MessageBatchResult {
+    pub custom_id: String,
+    pub result: MessageBatchResultKind,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MessageBatchResultKind {
+    Succeeded {
+        message: MessagesResponse,
+    },
+    /// `error` is Anthropic's error response, `{"type": "error", "error": {...}}`
+    Errored {
+        error: Value,
+    },
+    Canceled,
+    Expired,
 }
@@ -0,0 +1,507 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use serde_with::skip_serializing_none;
+
+use crate::apis::deepgram::{DeepgramListenParams, DeepgramListenResponse};
+use crate::apis::multipart::{FormPart, MultipartForm};
+use crate::clients::endpoints::{join_endpoint, AZURE_OPENAI_API_VERSION};
+use crate::providers::request::ProviderRequestError;
+use crate::providers::response::ProviderResponseError;
+use crate::ProviderId;
+
+// ============================================================================
+// TRANSCRIPTION REQUEST
+// ============================================================================
+
+/// OpenAI `/v1/audio/transcriptions` request. It is a multipart form, kept
+/// as sent so OpenAI-compatible providers get every field unchanged.
+#[derive(Debug, Clone)]
+pub struct TranscriptionRequest {
+    pub form: MultipartForm,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TranscriptionFormat {
+    #[default]
+    Json,
+    Text,
+    Srt,
+    VerboseJson,
+    Vtt,
+}
+
+impl TranscriptionFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            TranscriptionFormat::Json | TranscriptionFormat::VerboseJson => "application/json",
+            TranscriptionFormat::Text => "text/plain; charset=utf-8",
+            TranscriptionFormat::Srt => "application/x-subrip",
+            TranscriptionFormat::Vtt => "text/vtt",
+        }
+    }
+}
+
+impl TranscriptionRequest {
+    pub fn from_form(form: MultipartForm) -> Result<Self, String> {
+        if form.part("file").is_none() {
+            return Err("missing required field 'file'".to_string());
+        }
+        if form.text("model").is_none_or(str::is_empty) {
+            return Err("missing required field 'model'".to_string());
+        }
+        let request = TranscriptionRequest { form };
+        request.try_response_format()?;
+        Ok(request)
+    }
+
+    pub fn parse(body: &[u8], boundary: &str) -> Result<Self, String> {
+        Self::from_form(MultipartForm::parse(body, boundary)?)
+    }
+
+    pub fn model(&self) -> &str {
+        self.form.text("model").unwrap_or_default()
+    }
+
+    pub fn set_model(&mut self, model: &str) {
+        self.form.set_text("model", model);
+    }
+
+    pub fn file(&self) -> Option<&FormPart> {
+        self.form.part("file")
+    }
+
+    pub fn language(&self) -> Option<&str> {
+        self.form
+            .text("language")
+            .filter(|language| !language.is_empty())
+    }
+
+    pub fn stream(&self) -> bool {
+        self.form.text("stream") == Some("true")
+    }
+
+    pub fn response_format(&self) -> TranscriptionFormat {
+        self.try_response_format().unwrap_or_default()
+    }
+
+    /// Requested `timestamp_granularities[]`, e.g. `word` and `segment`
+    pub fn timestamp_granularities(&self) -> Vec<&str> {
+        self.form.texts("timestamp_granularities[]")
+    }
+
+    fn try_response_format(&self) -> Result<TranscriptionFormat, String> {
+        match self.form.text("response_format") {
+            None | Some("") => Ok(TranscriptionFormat::default()),
+            Some(format) => serde_json::from_value(Value::String(format.to_string()))
+                .map_err(|_| format!("unsupported response_format '{}'", format)),
+        }
+    }
+}
+
+// ============================================================================
+// TRANSCRIPTION RESPONSE
+// ============================================================================
+
+/// A transcription in the `verbose_json` shape; the other formats are
+/// rendered from it
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct TranscriptionResponse {
+    pub task: Option<String>,
+    pub language: Option<String>,
+    /// Audio length in seconds
+    pub duration: Option<f64>,
+    pub text: String,
+    pub words: Option<Vec<TranscriptionWord>>,
+    pub segments: Option<Vec<TranscriptionSegment>>,
+    pub usage: Option<Value>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TranscriptionWord {
+    pub word: String,
+    pub start: f64,
+    pub end: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TranscriptionSegment {
+    pub id: u32,
+    pub start: f64,
+    pub end: f64,
+    pub text: String,
+}
+
+/// Server-sent events of a streamed transcription
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type")]
+pub enum TranscriptionStreamEvent {
+    #[serde(rename = "transcript.text.delta")]
+    Delta { delta: String },
+    #[serde(rename = "transcript.text.done")]
+    Done {
+        text: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        usage: Option<Value>,
+    },
+}
+
+impl TranscriptionResponse {
+    /// Body of the response in `format`
+    pub fn render(&self, format: TranscriptionFormat) -> Vec<u8> {
+        match format {
+            TranscriptionFormat::Json => {
+                serde_json::to_vec(&serde_json::json!({ "text": self.text })).unwrap_or_default()
+            }
+            TranscriptionFormat::VerboseJson => serde_json::to_vec(self).unwrap_or_default(),
+            TranscriptionFormat::Text => format!("{}\n", self.text).into_bytes(),
+            TranscriptionFormat::Srt => self
+                .cues()
+                .iter()
+                .enumerate()
+                .map(|(index, segment)| {
+                    format!(
+                        "{}\n{} --> {}\n{}\n\n",
+                        index + 1,
+                        timestamp(segment.start, ','),
+                        timestamp(segment.end, ','),
+                        segment.text.trim()
+                    )
+                })
+                .collect::<String>()
+                .into_bytes(),
+            TranscriptionFormat::Vtt => {
+                let cues: String = self
+                    .cues()
+                    .iter()
+                    .map(|segment| {
+                        format!(
+                            "{} --> {}\n{}\n\n",
+                            timestamp(segment.start, '.'),
+                            timestamp(segment.end, '.'),
+                            segment.text.trim()
+                        )
+                    })
+                    .collect();
+                format!("WEBVTT\n\n{}", cues).into_bytes()
+            }
+        }
+    }
+
+    /// The transcription as a server-sent event stream: a delta per segment,
+    /// then the full text
+    pub fn to_sse(&self) -> Vec<u8> {
+        let mut events: Vec<TranscriptionStreamEvent> = self
+            .cues()
+            .into_iter()
+            .enumerate()
+            .map(|(index, segment)| TranscriptionStreamEvent::Delta {
+                delta: if index == 0 {
+                    segment.text.trim().to_string()
+                } else {
+                    format!(" {}", segment.text.trim())
+                },
+            })
+            .collect();
+        events.push(TranscriptionStreamEvent::Done {
+            text: self.text.clone(),
+            usage: self.usage.clone(),
+        });
+        events
+            .iter()
+            .filter_map(|event| serde_json::to_string(event).ok())
+            .map(|event| format!("data: {}\n\n", event))
+            .collect::<String>()
+            .into_bytes()
+    }
+
+    /// Segments to caption with; the whole text is one cue when there are none
+    fn cues(&self) -> Vec<TranscriptionSegment> {
+        match &self.segments {
+            Some(segments) if !segments.is_empty() => segments.clone(),
+            _ => vec![TranscriptionSegment {
+                id: 0,
+                start: 0.0,
+                end: self.duration.unwrap_or_default(),
+                text: self.text.clone(),
+            }],
+        }
+    }
+}
+
+/// `HH:MM:SS,mmm` (SubRip) or `HH:MM:SS.mmm` (WebVTT)
+fn timestamp(seconds: f64, separator: char) -> String {
+    let millis = (seconds.max(0.0) * 1000.0).round() as u64;
+    format!(
+        "{:02}:{:02}:{:02}{}{:03}",
+        millis / 3_600_000,
+        millis / 60_000 % 60,
+        millis / 1000 % 60,
+        separator,
+        millis % 1000
+    )
+}
+
+// ============================================================================
+// UPSTREAM TRANSCRIPTION REQUEST
+// ============================================================================
+
+/// A transcription request translated for the provider serving its model.
+/// Deepgram is sent its native pre-recorded `/v1/listen` API; every other
+/// provider (OpenAI, Groq, Azure OpenAI, ...) takes the OpenAI form as-is.
+#[derive(Debug, Clone)]
+pub struct UpstreamTranscriptionRequest {
+    pub model_id: String,
+    pub response_format: TranscriptionFormat,
+    pub stream: bool,
+    pub body: TranscriptionBody,
+}
+
+#[derive(Debug, Clone)]
+pub enum TranscriptionBody {
+    OpenAI(MultipartForm),
+    Deepgram {
+        params: DeepgramListenParams,
+        audio: FormPart,
+    },
+}
+
+impl UpstreamTranscriptionRequest {
+    pub fn for_provider(
+        request: TranscriptionRequest,
+        provider_id: &ProviderId,
+    ) -> Result<Self, ProviderRequestError> {
+        let model_id = request.model().to_string();
+        let response_format = request.response_format();
+        let stream = request.stream();
+        let body = match provider_id {
+            ProviderId::Deepgram => {
+                let audio = request
+                    .file()
+                    .cloned()
+                    .ok_or_else(|| ProviderRequestError {
+                        message: "Failed to translate transcription request: no audio file"
+                            .to_string(),
+                        source: None,
+                    })?;
+                let granularities = request.timestamp_granularities();
+                let params = DeepgramListenParams {
+                    model: model_id.clone(),
+                    language: request.language().map(str::to_string),
+                    utterances: matches!(
+                        response_format,
+                        TranscriptionFormat::Srt | TranscriptionFormat::Vtt
+                    ) || stream
+                        || (response_format == TranscriptionFormat::VerboseJson
+                            && (granularities.is_empty() || granularities.contains(&"segment"))),
+                };
+                TranscriptionBody::Deepgram { params, audio }
+            }
+            _ => TranscriptionBody::OpenAI(request.form),
+        };
+        Ok(UpstreamTranscriptionRequest {
+            model_id,
+            response_format,
+            stream,
+            body,
+        })
+    }
+
+    /// Whether the provider's native API is used, so its response has to be
+    /// translated back
+    pub fn is_native(&self) -> bool {
+        !matches!(self.body, TranscriptionBody::OpenAI(_))
+    }
+
+    /// Upstream path. For Azure OpenAI, `model_id` is the deployment name and
+    /// `api_version` overrides [`AZURE_OPENAI_API_VERSION`].
+    pub fn path(
+        &self,
+        provider_id: &ProviderId,
+        base_url_path_prefix: Option<&str>,
+        use_unversioned_paths: bool,
+        api_version: Option<&str>,
+    ) -> String {
+        let build_endpoint = |provider_prefix: &str, suffix: &str| {
+            join_endpoint(base_url_path_prefix, provider_prefix, suffix)
+        };
+        match &self.body {
+            TranscriptionBody::Deepgram { params, .. } => {
+                build_endpoint("/v1", &format!("/listen?{}", params.to_query()))
+            }
+            TranscriptionBody::OpenAI(_) => match provider_id {
+                ProviderId::Groq => build_endpoint("/openai/v1", "/audio/transcriptions"),
+                ProviderId::AzureOpenAI => build_endpoint(
+                    "/openai/deployments",
+                    &format!(
+                        "/{}/audio/transcriptions?api-version={}",
+                        self.model_id,
+                        api_version.unwrap_or(AZURE_OPENAI_API_VERSION)
+                    ),
+                ),
+                _ if use_unversioned_paths => build_endpoint("", "/audio/transcriptions"),
+                _ => build_endpoint("/v1", "/audio/transcriptions"),
+            },
+        }
+    }
+
+    /// Request body and its content type. Forms are encoded with `boundary`.
+    pub fn to_bytes(&self, boundary: &str) -> (String, Vec<u8>) {
+        match &self.body {
+            TranscriptionBody::OpenAI(form) => (
+                format!("multipart/form-data; boundary={}", boundary),
+                form.encode(boundary),
+            ),
+            TranscriptionBody::Deepgram { audio, .. } => (
+                audio
+                    .content_type
+                    .clone()
+                    .unwrap_or_else(|| "application/octet-stream".to_string()),
+                audio.data.clone(),
+            ),
+        }
+    }
+
+    /// Content type of the response the client asked for
+    pub fn response_content_type(&self) -> &'static str {
+        if self.stream {
+            "text/event-stream"
+        } else {
+            self.response_format.content_type()
+        }
+    }
+
+    /// Parse a native response and render it as the client asked for
+    pub fn translate_response(&self, body: &[u8]) -> Result<Vec<u8>, ProviderResponseError> {
+        let response = match &self.body {
+            TranscriptionBody::Deepgram { .. } => {
+                serde_json::from_slice::<DeepgramListenResponse>(body)
+                    .map(TranscriptionResponse::from)
+            }
+            TranscriptionBody::OpenAI(_) => serde_json::from_slice::<TranscriptionResponse>(body),
+        }
+        .map_err(|e| ProviderResponseError {
+            message: format!("Failed to parse transcription response: {}", e),
+            source: Some(Box::new(e)),
+        })?;
+
+        if self.stream {
+            Ok(response.to_sse())
+        } else {
+            Ok(response.render(self.response_format))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(fields: &[(&str, &str)]) -> TranscriptionRequest {
+        let mut form = MultipartForm::default();
+        form.parts.push(FormPart {
+            name: "file".to_string(),
+            filename: Some("a.mp3".to_string()),
+            content_type: Some("audio/mpeg".to_string()),
+            data: b"ID3".to_vec(),
+        });
+        for (name, value) in fields {
+            form.set_text(name, value);
+        }
+        TranscriptionRequest::from_form(form).unwrap()
+    }
+
+    #[test]
+    fn test_transcription_request_fields() {
+        let request = request(&[
+            ("model", "whisper-1"),
+            ("response_format", "verbose_json"),
+            ("stream", "true"),
+        ]);
+        assert_eq!(request.model(), "whisper-1");
+        assert_eq!(request.response_format(), TranscriptionFormat::VerboseJson);
+        assert!(request.stream());
+        assert_eq!(request.language(), None);
+
+        let mut form = request.form.clone();
+        form.set_text("response_format", "docx");
+        assert!(TranscriptionRequest::from_form(form).is_err());
+        assert!(TranscriptionRequest::from_form(MultipartForm::default()).is_err());
+    }
+
+    #[test]
+    fn test_transcription_paths() {
+        let openai = UpstreamTranscriptionRequest::for_provider(
+            request(&[("model", "whisper-large-v3")]),
+            &ProviderId::Groq,
+        )
+        .unwrap();
+        assert!(!openai.is_native());
+        assert_eq!(
+            openai.path(&ProviderId::Groq, None, false, None),
+            "/openai/v1/audio/transcriptions"
+        );
+        assert_eq!(
+            openai.path(&ProviderId::OpenAI, None, false, None),
+            "/v1/audio/transcriptions"
+        );
+
+        let deepgram = UpstreamTranscriptionRequest::for_provider(
+            request(&[("model", "nova-3"), ("language", "en")]),
+            &ProviderId::Deepgram,
+        )
+        .unwrap();
+        assert!(deepgram.is_native());
+        assert_eq!(
+            deepgram.path(&ProviderId::Deepgram, None, false, None),
+            "/v1/listen?model=nova-3&smart_format=true&language=en"
+        );
+        let (content_type, body) = deepgram.to_bytes("unused");
+        assert_eq!(content_type, "audio/mpeg");
+        assert_eq!(body, b"ID3");
+    }
+
+    #[test]
+    fn test_render_formats() {
+        let response = TranscriptionResponse {
+            duration: Some(3.5),
+            text: "Hello there. General Kenobi.".to_string(),
+            segments: Some(vec![
+                TranscriptionSegment {
+                    id: 0,
+                    start: 0.0,
+                    end: 1.25,
+                    text: "Hello there.".to_string(),
+                },
+                TranscriptionSegment {
+                    id: 1,
+                    start: 1.5,
+                    end: 3.5,
+                    text: "General Kenobi.".to_string(),
+                },
+            ]),
+            ..Default::default()
+        };
+        assert_eq!(
+            response.render(TranscriptionFormat::Json),
+            br#"{"text":"Hello there. General Kenobi."}"#
+        );
+        assert_eq!(
+            String::from_utf8(response.render(TranscriptionFormat::Srt)).unwrap(),
+            "1\n00:00:00,000 --> 00:00:01,250\nHello there.\n\n2\n00:00:01,500 --> 00:00:03,500\nGeneral Kenobi.\n\n"
+        );
+        assert_eq!(
+            String::from_utf8(response.render(TranscriptionFormat::Vtt)).unwrap(),
+            "WEBVTT\n\n00:00:00.000 --> 00:00:01.250\nHello there.\n\n00:00:01.500 --> 00:00:03.500\nGeneral Kenobi.\n\n"
+        );
+        assert_eq!(
+            String::from_utf8(response.to_sse()).unwrap(),
+            concat!(
+                "data: {\"type\":\"transcript.text.delta\",\"delta\":\"Hello there.\"}\n\n",
+                "data: {\"type\":\"transcript.text.delta\",\"delta\":\" General Kenobi.\"}\n\n",
+                "data: {\"type\":\"transcript.text.done\",\"text\":\"Hello there. General Kenobi.\"}\n\n"
+            )
+        );
+    }
+}
@@ -0,0 +1,138 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use serde_with::skip_serializing_none;
+use std::collections::HashMap;
+
+// ============================================================================
+// BATCH INPUT AND OUTPUT LINES
+// ============================================================================
+
+/// One line of a batch input file: a request to run against `url`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BatchRequestLine {
+    pub custom_id: String,
+    pub method: String,
+    /// Endpoint the request is for, e.g. `/v1/chat/completions`
+    pub url: String,
+    pub body: Value,
+}
+
+/// One line of a batch output or error file
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BatchResponseLine {
+    pub id: String,
+    pub custom_id: String,
+    pub response: Option<BatchLineResponse>,
+    pub error: Option<BatchLineError>,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BatchLineResponse {
+    pub status_code: u16,
+    pub request_id: Option<String>,
+    pub body: Value,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct BatchLineError {
+    pub code: String,
+    pub message: String,
+}
+
+// ============================================================================
+// BATCH OBJECTS
+// ============================================================================
+
+/// `POST /v1/batches` body
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CreateBatchRequest {
+    pub input_file_id: Option<String>,
+    pub endpoint: String,
+    #[serde(default = "default_completion_window")]
+    pub completion_window: String,
+    pub metadata: Option<HashMap<String, String>>,
+}
+
+fn default_completion_window() -> String {
+    "24h".to_string()
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchStatus {
+    Validating,
+    Failed,
+    InProgress,
+    Finalizing,
+    Completed,
+    Expired,
+    Cancelling,
+    Cancelled,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct BatchRequestCounts {
+    pub total: u32,
+    pub completed: u32,
+    pub failed: u32,
+}
+
+/// A batch as `/v1/batches` reports it; timestamps are Unix seconds
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Batch {
+    pub id: String,
+    #[serde(default = "default_batch_object")]
+    pub object: String,
+    pub endpoint: String,
+    pub errors: Option<Value>,
+    pub input_file_id: Option<String>,
+    pub completion_window: String,
+    pub status: BatchStatus,
+    pub output_file_id: Option<String>,
+    pub error_file_id: Option<String>,
+    pub created_at: i64,
+    pub in_progress_at: Option<i64>,
+    pub expires_at: Option<i64>,
+    pub finalizing_at: Option<i64>,
+    pub completed_at: Option<i64>,
+    pub failed_at: Option<i64>,
+    pub expired_at: Option<i64>,
+    pub cancelling_at: Option<i64>,
+    pub cancelled_at: Option<i64>,
+    pub request_counts: Option<BatchRequestCounts>,
+    pub metadata: Option<HashMap<String, String>>,
+}
+
+fn default_batch_object() -> String {
+    "batch".to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_batch_round_trip() {
+        let body = json!({
+            "id": "batch_abc123",
+            "object": "batch",
+            "endpoint": "/v1/chat/completions",
+            "input_file_id": "file-abc123",
+            "completion_window": "24h",
+            "status": "in_progress",
+            "created_at": 1711471533,
+            "in_progress_at": 1711471538,
+            "expires_at": 1711557933,
+            "request_counts": {"total": 100, "completed": 95, "failed": 0},
+            "metadata": {"job": "nightly"}
+        });
+        let batch: Batch = serde_json::from_value(body.clone()).unwrap();
+        assert_eq!(batch.status, BatchStatus::InProgress);
+        assert_eq!(serde_json::to_value(&batch).unwrap(), body);
+    }
+}
@@ -0,0 +1,549 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use serde_with::skip_serializing_none;
+
+use std::collections::HashMap;
+
+use super::ApiDefinition;
+use crate::providers::request::{ProviderRequest, ProviderRequestError};
+
+// ============================================================================
+// COHERE API ENUMERATION
+// ============================================================================
+
+/// Enum for all supported Cohere APIs
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CohereApi {
+    Chat,
+}
+
+impl ApiDefinition for CohereApi {
+    fn endpoint(&self) -> &'static str {
+        match self {
+            CohereApi::Chat => "/v2/chat",
+        }
+    }
+
+    fn from_endpoint(endpoint: &str) -> Option<Self> {
+        let path = endpoint.split('?').next().unwrap_or(endpoint);
+        if path.ends_with("/v2/chat") {
+            Some(CohereApi::Chat)
+        } else {
+            None
+        }
+    }
+
+    fn supports_streaming(&self) -> bool {
+        true
+    }
+
+    fn supports_tools(&self) -> bool {
+        true
+    }
+
+    fn supports_vision(&self) -> bool {
+        true
+    }
+
+    fn all_variants() -> Vec<Self> {
+        vec![CohereApi::Chat]
+    }
+}
+
+// ============================================================================
+// CHAT REQUEST STRUCTURES
+// ============================================================================
+
+/// Cohere v2 `chat` request body. Streaming uses the same endpoint with
+/// `stream: true`.
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct CohereChatRequest {
+    pub model: String,
+    /// The conversation so far, oldest first; the preamble is a `system` message
+    pub messages: Vec<CohereMessage>,
+    pub tools: Option<Vec<CohereTool>>,
+    pub tool_choice: Option<CohereToolChoice>,
+    pub strict_tools: Option<bool>,
+    pub response_format: Option<CohereResponseFormat>,
+    pub max_tokens: Option<u32>,
+    pub stop_sequences: Option<Vec<String>>,
+    pub temperature: Option<f32>,
+    pub seed: Option<i32>,
+    pub frequency_penalty: Option<f32>,
+    pub presence_penalty: Option<f32>,
+    pub k: Option<u32>,
+    pub p: Option<f32>,
+    pub logprobs: Option<bool>,
+    pub stream: Option<bool>,
+    /// Cohere has no metadata field, so metadata never leaves the gateway
+    #[serde(skip)]
+    pub metadata: Option<HashMap<String, Value>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CohereRole {
+    System,
+    User,
+    Assistant,
+    Tool,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CohereMessage {
+    pub role: CohereRole,
+    pub content: Option<CohereContent>,
+    /// Assistant turns only
+    pub tool_calls: Option<Vec<CohereToolCall>>,
+    /// Assistant turns only: the reasoning the model gave before calling tools
+    pub tool_plan: Option<String>,
+    /// Tool turns only
+    pub tool_call_id: Option<String>,
+}
+
+impl CohereMessage {
+    pub fn text(role: CohereRole, text: impl Into<String>) -> Self {
+        CohereMessage {
+            role,
+            content: Some(CohereContent::Text(text.into())),
+            tool_calls: None,
+            tool_plan: None,
+            tool_call_id: None,
+        }
+    }
+}
+
+/// Message content is either a plain string or a list of typed items
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum CohereContent {
+    Text(String),
+    Items(Vec<CohereContentItem>),
+}
+
+impl CohereContent {
+    /// Concatenated text items, excluding thinking
+    pub fn text(&self) -> String {
+        match self {
+            CohereContent::Text(text) => text.clone(),
+            CohereContent::Items(items) => items
+                .iter()
+                .filter_map(|item| match item {
+                    CohereContentItem::Text { text } => Some(text.as_str()),
+                    _ => None,
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CohereContentItem {
+    Text {
+        text: String,
+    },
+    ImageUrl {
+        image_url: CohereImageUrl,
+    },
+    Thinking {
+        thinking: String,
+    },
+    Document {
+        document: Value,
+    },
+    #[serde(other)]
+    Unknown,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CohereImageUrl {
+    pub url: String,
+    pub detail: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CohereTool {
+    #[serde(rename = "type")]
+    pub tool_type: String,
+    pub function: CohereFunction,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CohereFunction {
+    pub name: String,
+    pub description: Option<String>,
+    pub parameters: Value,
+}
+
+/// A tool call made by the model. In stream events only the fields carried
+/// by that event are set.
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct CohereToolCall {
+    pub id: Option<String>,
+    #[serde(rename = "type")]
+    pub call_type: Option<String>,
+    pub function: Option<CohereToolCallFunction>,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct CohereToolCallFunction {
+    pub name: Option<String>,
+    /// JSON-encoded arguments, or a fragment of them in `tool-call-delta`
+    pub arguments: Option<String>,
+}
+
+/// Cohere picks tools on its own when unset
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum CohereToolChoice {
+    Required,
+    None,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CohereResponseFormat {
+    /// `text` or `json_object`
+    #[serde(rename = "type")]
+    pub format_type: String,
+    pub json_schema: Option<Value>,
+}
+
+impl ProviderRequest for CohereChatRequest {
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn set_model(&mut self, model: String) {
+        self.model = model;
+    }
+
+    fn is_streaming(&self) -> bool {
+        self.stream.unwrap_or(false)
+    }
+
+    fn extract_messages_text(&self) -> String {
+        self.messages
+            .iter()
+            .filter_map(|message| message.content.as_ref())
+            .map(CohereContent::text)
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn get_recent_user_message(&self) -> Option<String> {
+        self.messages
+            .iter()
+            .rev()
+            .find(|message| message.role == CohereRole::User)
+            .and_then(|message| message.content.as_ref())
+            .map(CohereContent::text)
+    }
+
+    fn get_tool_names(&self) -> Option<Vec<String>> {
+        self.tools.as_ref().map(|tools| {
+            tools
+                .iter()
+                .map(|tool| tool.function.name.clone())
+                .collect()
+        })
+    }
+
+    fn to_bytes(&self) -> Result<Vec<u8>, ProviderRequestError> {
+        serde_json::to_vec(self).map_err(|e| ProviderRequestError {
+            message: format!("Failed to serialize Cohere request: {}", e),
+            source: Some(Box::new(e)),
+        })
+    }
+
+    fn metadata(&self) -> &Option<HashMap<String, Value>> {
+        &self.metadata
+    }
+
+    fn remove_metadata_key(&mut self, key: &str) -> bool {
+        if let Some(ref mut metadata) = self.metadata {
+            metadata.remove(key).is_some()
+        } else {
+            false
+        }
+    }
+
+    fn get_temperature(&self) -> Option<f32> {
+        self.temperature
+    }
+
+    fn get_messages(&self) -> Vec<crate::apis::openai::Message> {
+        use crate::apis::openai::{Message, MessageContent, Role};
+
+        self.messages
+            .iter()
+            .map(|message| Message {
+                role: match message.role {
+                    CohereRole::System => Role::System,
+                    CohereRole::User => Role::User,
+                    CohereRole::Assistant => Role::Assistant,
+                    CohereRole::Tool => Role::Tool,
+                },
+                content: Some(MessageContent::Text(
+                    message
+                        .content
+                        .as_ref()
+                        .map(CohereContent::text)
+                        .unwrap_or_default(),
+                )),
+                name: None,
+                tool_calls: None,
+                tool_call_id: message.tool_call_id.clone(),
+                thinking_blocks: None,
+            })
+            .collect()
+    }
+
+    fn set_messages(&mut self, messages: &[crate::apis::openai::Message]) {
+        use crate::apis::openai::Role;
+        use crate::transforms::lib::ExtractText;
+
+        self.messages = messages
+            .iter()
+            .filter_map(|msg| {
+                let role = match msg.role {
+                    Role::System | Role::Developer => CohereRole::System,
+                    Role::User => CohereRole::User,
+                    Role::Assistant => CohereRole::Assistant,
+                    Role::Tool => return None,
+                };
+                Some(CohereMessage::text(role, msg.content.extract_text()))
+            })
+            .collect();
+    }
+}
+
+// ============================================================================
+// CHAT RESPONSE STRUCTURES
+// ============================================================================
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CohereChatResponse {
+    pub id: String,
+    pub finish_reason: Option<CohereFinishReason>,
+    pub message: CohereResponseMessage,
+    pub usage: Option<CohereUsage>,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct CohereResponseMessage {
+    pub role: Option<CohereRole>,
+    pub content: Option<Vec<CohereContentItem>>,
+    pub tool_plan: Option<String>,
+    pub tool_calls: Option<Vec<CohereToolCall>>,
+    pub citations: Option<Vec<Value>>,
+}
+
+/// Why the model stopped generating
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum CohereFinishReason {
+    Complete,
+    StopSequence,
+    MaxTokens,
+    ToolCall,
+    Error,
+    Timeout,
+    #[serde(other)]
+    Other,
+}
+
+/// Token counts. `billed_units` excludes tokens Cohere does not charge for,
+/// so `tokens` is what gets reported.
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct CohereUsage {
+    pub billed_units: Option<CohereTokenCounts>,
+    pub tokens: Option<CohereTokenCounts>,
+    pub cached_tokens: Option<f64>,
+}
+
+/// Cohere reports counts as numbers that may carry a fraction
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct CohereTokenCounts {
+    pub input_tokens: Option<f64>,
+    pub output_tokens: Option<f64>,
+}
+
+// ============================================================================
+// STREAMING STRUCTURES
+// ============================================================================
+
+/// One `data:` payload of a streamed chat. The SSE `event:` line repeats
+/// `type`, so it carries nothing extra.
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CohereStreamEvent {
+    #[serde(rename = "type")]
+    pub event_type: CohereStreamEventType,
+    /// Set on `message-start`
+    pub id: Option<String>,
+    /// Position of the content item or tool call the event belongs to
+    pub index: Option<u32>,
+    pub delta: Option<CohereStreamDelta>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum CohereStreamEventType {
+    MessageStart,
+    ContentStart,
+    ContentDelta,
+    ContentEnd,
+    ToolPlanDelta,
+    ToolCallStart,
+    ToolCallDelta,
+    ToolCallEnd,
+    CitationStart,
+    CitationEnd,
+    MessageEnd,
+    Debug,
+    #[serde(other)]
+    Other,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct CohereStreamDelta {
+    pub message: Option<CohereStreamMessage>,
+    /// Set on `message-end`
+    pub finish_reason: Option<CohereFinishReason>,
+    /// Set on `message-end`
+    pub usage: Option<CohereUsage>,
+    pub error: Option<String>,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct CohereStreamMessage {
+    pub role: Option<CohereRole>,
+    pub content: Option<CohereStreamContent>,
+    pub tool_plan: Option<String>,
+    /// A single call: complete id and name on `tool-call-start`, argument
+    /// fragments on `tool-call-delta`
+    pub tool_calls: Option<CohereToolCall>,
+    pub citations: Option<Value>,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct CohereStreamContent {
+    #[serde(rename = "type")]
+    pub content_type: Option<String>,
+    pub text: Option<String>,
+    pub thinking: Option<String>,
+}
+
+// ============================================================================
+// EMBED STRUCTURES
+// ============================================================================
+
+/// Cohere v2 `embed` request body
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct CohereEmbedRequest {
+    pub model: String,
+    pub texts: Vec<String>,
+    /// `search_document`, `search_query`, `classification` or `clustering`;
+    /// required by v3 and later models
+    pub input_type: String,
+    /// Only `float` embeddings are requested
+    pub embedding_types: Vec<String>,
+    pub output_dimension: Option<u32>,
+    /// `NONE`, `START` or `END`
+    pub truncate: Option<String>,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CohereEmbedResponse {
+    pub id: Option<String>,
+    pub embeddings: CohereEmbeddingsByType,
+    /// Carries `billed_units` like a chat response's usage
+    pub meta: Option<CohereUsage>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct CohereEmbeddingsByType {
+    #[serde(default)]
+    pub float: Vec<Vec<f32>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_cohere_api_from_endpoint() {
+        assert_eq!(CohereApi::from_endpoint("/v2/chat"), Some(CohereApi::Chat));
+        assert_eq!(CohereApi::from_endpoint("/v1/chat/completions"), None);
+    }
+
+    #[test]
+    fn test_request_serialization_skips_internal_fields() {
+        let request = CohereChatRequest {
+            model: "command-a-03-2025".to_string(),
+            messages: vec![
+                CohereMessage::text(CohereRole::System, "Be brief"),
+                CohereMessage::text(CohereRole::User, "Hi"),
+            ],
+            tool_choice: Some(CohereToolChoice::Required),
+            metadata: Some(HashMap::from([("trace".to_string(), json!("x"))])),
+            stream: Some(true),
+            ..Default::default()
+        };
+
+        let body = serde_json::to_value(&request).unwrap();
+        assert_eq!(
+            body,
+            json!({
+                "model": "command-a-03-2025",
+                "messages": [
+                    {"role": "system", "content": "Be brief"},
+                    {"role": "user", "content": "Hi"}
+                ],
+                "tool_choice": "REQUIRED",
+                "stream": true
+            })
+        );
+        assert_eq!(request.extract_messages_text(), "Be brief Hi");
+        assert_eq!(request.get_recent_user_message().as_deref(), Some("Hi"));
+    }
+
+    #[test]
+    fn test_stream_event_deserialization() {
+        let event: CohereStreamEvent = serde_json::from_value(json!({
+            "type": "tool-call-start",
+            "index": 0,
+            "delta": {"message": {"tool_calls": {
+                "id": "get_weather_k3x",
+                "type": "function",
+                "function": {"name": "get_weather", "arguments": ""}
+            }}}
+        }))
+        .unwrap();
+        assert_eq!(event.event_type, CohereStreamEventType::ToolCallStart);
+        let call = event.delta.unwrap().message.unwrap().tool_calls.unwrap();
+        assert_eq!(call.id.as_deref(), Some("get_weather_k3x"));
+
+        let event: CohereStreamEvent =
+            serde_json::from_value(json!({"type": "some-future-event"})).unwrap();
+        assert_eq!(event.event_type, CohereStreamEventType::Other);
+    }
+}
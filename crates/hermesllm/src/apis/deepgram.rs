@@ -0,0 +1,125 @@
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+
+// ============================================================================
+// DEEPGRAM PRE-RECORDED AUDIO (`/v1/listen`)
+// ============================================================================
+
+/// Query parameters of a pre-recorded `/v1/listen` request. The audio itself
+/// is the raw request body.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct DeepgramListenParams {
+    pub model: String,
+    /// BCP-47 language; the language is detected when unset
+    pub language: Option<String>,
+    /// Return utterances, which become transcription segments
+    pub utterances: bool,
+}
+
+impl DeepgramListenParams {
+    pub fn to_query(&self) -> String {
+        let mut query = format!("model={}&smart_format=true", self.model);
+        match &self.language {
+            Some(language) => query.push_str(&format!("&language={}", language)),
+            None => query.push_str("&detect_language=true"),
+        }
+        if self.utterances {
+            query.push_str("&utterances=true");
+        }
+        query
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DeepgramListenResponse {
+    pub metadata: Option<DeepgramMetadata>,
+    pub results: DeepgramResults,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct DeepgramMetadata {
+    pub request_id: Option<String>,
+    /// Audio length in seconds
+    pub duration: Option<f64>,
+    pub channels: Option<u32>,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct DeepgramResults {
+    #[serde(default)]
+    pub channels: Vec<DeepgramChannel>,
+    pub utterances: Option<Vec<DeepgramUtterance>>,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct DeepgramChannel {
+    #[serde(default)]
+    pub alternatives: Vec<DeepgramAlternative>,
+    pub detected_language: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct DeepgramAlternative {
+    #[serde(default)]
+    pub transcript: String,
+    #[serde(default)]
+    pub confidence: f64,
+    #[serde(default)]
+    pub words: Vec<DeepgramWord>,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct DeepgramWord {
+    pub word: String,
+    pub start: f64,
+    pub end: f64,
+    #[serde(default)]
+    pub confidence: f64,
+    /// The word as written with smart formatting, e.g. `Yeah,`
+    pub punctuated_word: Option<String>,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct DeepgramUtterance {
+    pub start: f64,
+    pub end: f64,
+    #[serde(default)]
+    pub confidence: f64,
+    #[serde(default)]
+    pub channel: u32,
+    pub transcript: String,
+    pub id: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_listen_query() {
+        let params = DeepgramListenParams {
+            model: "nova-3".to_string(),
+            language: None,
+            utterances: true,
+        };
+        assert_eq!(
+            params.to_query(),
+            "model=nova-3&smart_format=true&detect_language=true&utterances=true"
+        );
+
+        let params = DeepgramListenParams {
+            language: Some("en".to_string()),
+            utterances: false,
+            ..params
+        };
+        assert_eq!(
+            params.to_query(),
+            "model=nova-3&smart_format=true&language=en"
+        );
+    }
+}
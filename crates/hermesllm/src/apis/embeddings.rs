@@ -0,0 +1,434 @@
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+
+use crate::apis::amazon_bedrock::{TitanEmbedRequest, TitanEmbedResponse};
+use crate::apis::cohere::{CohereEmbedRequest, CohereEmbedResponse};
+use crate::apis::gemini::{
+    BatchEmbedContentsRequest, BatchEmbedContentsResponse, EmbedContentRequest,
+    EmbedContentResponse,
+};
+use crate::clients::endpoints::{join_endpoint, AZURE_OPENAI_API_VERSION};
+use crate::providers::request::ProviderRequestError;
+use crate::providers::response::ProviderResponseError;
+use crate::ProviderId;
+
+// ============================================================================
+// EMBEDDINGS REQUEST STRUCTURES
+// ============================================================================
+
+/// OpenAI `/v1/embeddings` request, the shape every client sends whatever
+/// provider serves the model
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct EmbeddingsRequest {
+    #[serde(default)]
+    pub model: String,
+    pub input: EmbeddingInput,
+    pub encoding_format: Option<EncodingFormat>,
+    pub dimensions: Option<u32>,
+    pub user: Option<String>,
+    /// Task hint for Cohere (`input_type`) and Gemini (`taskType`), e.g.
+    /// `search_query`. OpenAI has no such field, so it is dropped for
+    /// OpenAI-compatible providers.
+    pub input_type: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum EmbeddingInput {
+    Text(String),
+    Texts(Vec<String>),
+    Tokens(Vec<u32>),
+    TokenArrays(Vec<Vec<u32>>),
+}
+
+impl EmbeddingInput {
+    /// The texts to embed; `None` for token ids, which only OpenAI-compatible
+    /// providers accept
+    pub fn texts(&self) -> Option<Vec<String>> {
+        match self {
+            EmbeddingInput::Text(text) => Some(vec![text.clone()]),
+            EmbeddingInput::Texts(texts) => Some(texts.clone()),
+            EmbeddingInput::Tokens(_) | EmbeddingInput::TokenArrays(_) => None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum EncodingFormat {
+    #[default]
+    Float,
+    /// Little-endian `f32`s, base64-encoded
+    Base64,
+}
+
+// ============================================================================
+// EMBEDDINGS RESPONSE STRUCTURES
+// ============================================================================
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct EmbeddingsResponse {
+    #[serde(default = "list_object")]
+    pub object: String,
+    pub data: Vec<Embedding>,
+    #[serde(default)]
+    pub model: String,
+    #[serde(default)]
+    pub usage: EmbeddingsUsage,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Embedding {
+    #[serde(default = "embedding_object")]
+    pub object: String,
+    pub index: u32,
+    pub embedding: EmbeddingVector,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum EmbeddingVector {
+    Float(Vec<f32>),
+    Base64(String),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct EmbeddingsUsage {
+    #[serde(default)]
+    pub prompt_tokens: u32,
+    #[serde(default)]
+    pub total_tokens: u32,
+}
+
+fn list_object() -> String {
+    "list".to_string()
+}
+
+fn embedding_object() -> String {
+    "embedding".to_string()
+}
+
+#[serde_with::serde_as]
+#[derive(Serialize)]
+struct Base64Bytes(#[serde_as(as = "serde_with::base64::Base64")] Vec<u8>);
+
+impl EmbeddingsResponse {
+    /// OpenAI response holding `vectors` in input order
+    pub fn from_vectors(model: &str, vectors: Vec<Vec<f32>>, prompt_tokens: u32) -> Self {
+        EmbeddingsResponse {
+            object: list_object(),
+            data: vectors
+                .into_iter()
+                .enumerate()
+                .map(|(index, vector)| Embedding {
+                    object: embedding_object(),
+                    index: index as u32,
+                    embedding: EmbeddingVector::Float(vector),
+                })
+                .collect(),
+            model: model.to_string(),
+            usage: EmbeddingsUsage {
+                prompt_tokens,
+                total_tokens: prompt_tokens,
+            },
+        }
+    }
+
+    /// Re-encode float vectors as base64, as OpenAI does for
+    /// `encoding_format: "base64"`
+    pub fn encode_base64(&mut self) {
+        for embedding in &mut self.data {
+            if let EmbeddingVector::Float(values) = &embedding.embedding {
+                let bytes = values
+                    .iter()
+                    .flat_map(|value| value.to_le_bytes())
+                    .collect();
+                if let Ok(serde_json::Value::String(encoded)) =
+                    serde_json::to_value(Base64Bytes(bytes))
+                {
+                    embedding.embedding = EmbeddingVector::Base64(encoded);
+                }
+            }
+        }
+    }
+}
+
+// ============================================================================
+// UPSTREAM EMBEDDINGS REQUESTS
+// ============================================================================
+
+/// An embeddings request translated for the provider serving its model.
+/// Cohere, Gemini and Amazon Bedrock are sent their native embeddings API;
+/// every other provider takes the OpenAI request as-is.
+#[derive(Debug, Clone)]
+pub struct UpstreamEmbeddingsRequest {
+    pub model_id: String,
+    /// Encoding the client asked for; native APIs only return floats
+    pub encoding_format: Option<EncodingFormat>,
+    pub body: EmbeddingsBody,
+}
+
+#[derive(Debug, Clone)]
+pub enum EmbeddingsBody {
+    OpenAI(EmbeddingsRequest),
+    Cohere(CohereEmbedRequest),
+    GeminiEmbedContent(EmbedContentRequest),
+    GeminiBatchEmbedContents(BatchEmbedContentsRequest),
+    TitanEmbed(TitanEmbedRequest),
+}
+
+impl UpstreamEmbeddingsRequest {
+    pub fn for_provider(
+        request: EmbeddingsRequest,
+        provider_id: &ProviderId,
+    ) -> Result<Self, ProviderRequestError> {
+        let model_id = request.model.clone();
+        let encoding_format = request.encoding_format;
+        let body = match provider_id {
+            ProviderId::Cohere => CohereEmbedRequest::try_from(request).map(EmbeddingsBody::Cohere),
+            ProviderId::Gemini => match request.input.texts() {
+                Some(texts) if texts.len() == 1 => {
+                    EmbedContentRequest::try_from(request).map(EmbeddingsBody::GeminiEmbedContent)
+                }
+                _ => BatchEmbedContentsRequest::try_from(request)
+                    .map(EmbeddingsBody::GeminiBatchEmbedContents),
+            },
+            ProviderId::AmazonBedrock => {
+                TitanEmbedRequest::try_from(request).map(EmbeddingsBody::TitanEmbed)
+            }
+            _ => Ok(EmbeddingsBody::OpenAI(EmbeddingsRequest {
+                input_type: None,
+                ..request
+            })),
+        }
+        .map_err(|e| ProviderRequestError {
+            message: format!("Failed to translate embeddings request: {}", e),
+            source: Some(Box::new(e)),
+        })?;
+        Ok(UpstreamEmbeddingsRequest {
+            model_id,
+            encoding_format,
+            body,
+        })
+    }
+
+    /// Whether the provider's native API is used, so its response has to be
+    /// translated back
+    pub fn is_native(&self) -> bool {
+        !matches!(self.body, EmbeddingsBody::OpenAI(_))
+    }
+
+    /// Upstream path. For Azure OpenAI, `model_id` is the deployment name and
+    /// `api_version` overrides [`AZURE_OPENAI_API_VERSION`].
+    pub fn path(
+        &self,
+        provider_id: &ProviderId,
+        base_url_path_prefix: Option<&str>,
+        use_unversioned_paths: bool,
+        api_version: Option<&str>,
+    ) -> String {
+        let build_endpoint = |provider_prefix: &str, suffix: &str| {
+            join_endpoint(base_url_path_prefix, provider_prefix, suffix)
+        };
+        let model_id = &self.model_id;
+        match &self.body {
+            EmbeddingsBody::Cohere(_) => build_endpoint("/v2", "/embed"),
+            EmbeddingsBody::GeminiEmbedContent(_) => {
+                build_endpoint("/v1beta", &format!("/models/{}:embedContent", model_id))
+            }
+            EmbeddingsBody::GeminiBatchEmbedContents(_) => build_endpoint(
+                "/v1beta",
+                &format!("/models/{}:batchEmbedContents", model_id),
+            ),
+            EmbeddingsBody::TitanEmbed(_) => {
+                build_endpoint("", &format!("/model/{}/invoke", model_id))
+            }
+            EmbeddingsBody::OpenAI(_) => match provider_id {
+                ProviderId::Groq => build_endpoint("/openai/v1", "/embeddings"),
+                ProviderId::Zhipu => build_endpoint("/api/paas/v4", "/embeddings"),
+                ProviderId::Qwen => build_endpoint("/compatible-mode/v1", "/embeddings"),
+                ProviderId::AzureOpenAI => build_endpoint(
+                    "/openai/deployments",
+                    &format!(
+                        "/{}/embeddings?api-version={}",
+                        model_id,
+                        api_version.unwrap_or(AZURE_OPENAI_API_VERSION)
+                    ),
+                ),
+                ProviderId::VertexAI => build_endpoint("", "/endpoints/openapi/embeddings"),
+                _ if use_unversioned_paths => build_endpoint("", "/embeddings"),
+                _ => build_endpoint("/v1", "/embeddings"),
+            },
+        }
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, ProviderRequestError> {
+        match &self.body {
+            EmbeddingsBody::OpenAI(request) => serde_json::to_vec(request),
+            EmbeddingsBody::Cohere(request) => serde_json::to_vec(request),
+            EmbeddingsBody::GeminiEmbedContent(request) => serde_json::to_vec(request),
+            EmbeddingsBody::GeminiBatchEmbedContents(request) => serde_json::to_vec(request),
+            EmbeddingsBody::TitanEmbed(request) => serde_json::to_vec(request),
+        }
+        .map_err(|e| ProviderRequestError {
+            message: format!("Failed to serialize embeddings request: {}", e),
+            source: Some(Box::new(e)),
+        })
+    }
+
+    /// Parse the provider's response into the OpenAI shape
+    pub fn parse_response(&self, body: &[u8]) -> Result<EmbeddingsResponse, ProviderResponseError> {
+        let mut response = match &self.body {
+            EmbeddingsBody::OpenAI(_) => serde_json::from_slice::<EmbeddingsResponse>(body),
+            EmbeddingsBody::Cohere(_) => {
+                serde_json::from_slice::<CohereEmbedResponse>(body).map(EmbeddingsResponse::from)
+            }
+            EmbeddingsBody::GeminiEmbedContent(_) => {
+                serde_json::from_slice::<EmbedContentResponse>(body).map(EmbeddingsResponse::from)
+            }
+            EmbeddingsBody::GeminiBatchEmbedContents(_) => {
+                serde_json::from_slice::<BatchEmbedContentsResponse>(body)
+                    .map(EmbeddingsResponse::from)
+            }
+            EmbeddingsBody::TitanEmbed(_) => {
+                serde_json::from_slice::<TitanEmbedResponse>(body).map(EmbeddingsResponse::from)
+            }
+        }
+        .map_err(|e| ProviderResponseError {
+            message: format!("Failed to parse embeddings response: {}", e),
+            source: Some(Box::new(e)),
+        })?;
+
+        if self.is_native() {
+            response.model = self.model_id.clone();
+            if self.encoding_format == Some(EncodingFormat::Base64) {
+                response.encode_base64();
+            }
+        }
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn request(body: serde_json::Value) -> EmbeddingsRequest {
+        serde_json::from_value(body).unwrap()
+    }
+
+    #[test]
+    fn test_embedding_input_shapes() {
+        let single = request(json!({"model": "m", "input": "hello"}));
+        assert_eq!(single.input.texts(), Some(vec!["hello".to_string()]));
+
+        let batch = request(json!({"model": "m", "input": ["a", "b"]}));
+        assert_eq!(
+            batch.input.texts(),
+            Some(vec!["a".to_string(), "b".to_string()])
+        );
+
+        let tokens = request(json!({"model": "m", "input": [1, 2, 3]}));
+        assert_eq!(tokens.input, EmbeddingInput::Tokens(vec![1, 2, 3]));
+        assert_eq!(tokens.input.texts(), None);
+    }
+
+    #[test]
+    fn test_openai_request_forwarded_without_input_type() {
+        let upstream = UpstreamEmbeddingsRequest::for_provider(
+            request(json!({
+                "model": "text-embedding-3-small",
+                "input": "hello",
+                "dimensions": 256,
+                "input_type": "search_query"
+            })),
+            &ProviderId::OpenAI,
+        )
+        .unwrap();
+        assert!(!upstream.is_native());
+        assert_eq!(
+            upstream.path(&ProviderId::OpenAI, None, false, None),
+            "/v1/embeddings"
+        );
+        let body: serde_json::Value =
+            serde_json::from_slice(&upstream.to_bytes().unwrap()).unwrap();
+        assert_eq!(
+            body,
+            json!({"model": "text-embedding-3-small", "input": "hello", "dimensions": 256})
+        );
+    }
+
+    #[test]
+    fn test_embeddings_paths() {
+        let path = |provider: ProviderId, input: serde_json::Value, model: &str| {
+            UpstreamEmbeddingsRequest::for_provider(
+                request(json!({"model": model, "input": input})),
+                &provider,
+            )
+            .unwrap()
+            .path(&provider, None, false, None)
+        };
+        assert_eq!(
+            path(ProviderId::Cohere, json!("a"), "embed-v4.0"),
+            "/v2/embed"
+        );
+        assert_eq!(
+            path(ProviderId::Gemini, json!("a"), "gemini-embedding-001"),
+            "/v1beta/models/gemini-embedding-001:embedContent"
+        );
+        assert_eq!(
+            path(
+                ProviderId::Gemini,
+                json!(["a", "b"]),
+                "gemini-embedding-001"
+            ),
+            "/v1beta/models/gemini-embedding-001:batchEmbedContents"
+        );
+        assert_eq!(
+            path(
+                ProviderId::AmazonBedrock,
+                json!("a"),
+                "amazon.titan-embed-text-v2:0"
+            ),
+            "/model/amazon.titan-embed-text-v2:0/invoke"
+        );
+        assert_eq!(
+            path(ProviderId::AzureOpenAI, json!("a"), "embeddings-deployment"),
+            format!(
+                "/openai/deployments/embeddings-deployment/embeddings?api-version={}",
+                AZURE_OPENAI_API_VERSION
+            )
+        );
+        assert_eq!(
+            path(ProviderId::Groq, json!("a"), "m"),
+            "/openai/v1/embeddings"
+        );
+    }
+
+    #[test]
+    fn test_native_response_encoded_as_base64() {
+        let upstream = UpstreamEmbeddingsRequest::for_provider(
+            request(json!({
+                "model": "amazon.titan-embed-text-v2:0",
+                "input": "hello",
+                "encoding_format": "base64"
+            })),
+            &ProviderId::AmazonBedrock,
+        )
+        .unwrap();
+        let response = upstream
+            .parse_response(br#"{"embedding": [1.0, -2.0], "inputTextTokenCount": 2}"#)
+            .unwrap();
+        assert_eq!(
+            serde_json::to_value(&response).unwrap(),
+            json!({
+                "object": "list",
+                "data": [{"object": "embedding", "index": 0, "embedding": "AACAPwAAAMA="}],
+                "model": "amazon.titan-embed-text-v2:0",
+                "usage": {"prompt_tokens": 2, "total_tokens": 2}
+            })
+        );
+    }
+}
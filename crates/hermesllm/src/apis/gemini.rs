@@ -0,0 +1,679 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use serde_with::skip_serializing_none;
+
+use std::collections::HashMap;
+
+use super::ApiDefinition;
+use crate::providers::request::{ProviderRequest, ProviderRequestError};
+
+// ============================================================================
+// GOOGLE GEMINI API ENUMERATION
+// ============================================================================
+
+/// Enum for all supported Google Gemini APIs
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GeminiApi {
+    GenerateContent,
+    StreamGenerateContent,
+}
+
+impl ApiDefinition for GeminiApi {
+    fn endpoint(&self) -> &'static str {
+        match self {
+            GeminiApi::GenerateContent => "/v1beta/models/{model}:generateContent",
+            GeminiApi::StreamGenerateContent => "/v1beta/models/{model}:streamGenerateContent",
+        }
+    }
+
+    fn from_endpoint(endpoint: &str) -> Option<Self> {
+        let path = endpoint.split('?').next().unwrap_or(endpoint);
+        if path.ends_with(":generateContent") {
+            Some(GeminiApi::GenerateContent)
+        } else if path.ends_with(":streamGenerateContent") {
+            Some(GeminiApi::StreamGenerateContent)
+        } else {
+            None
+        }
+    }
+
+    fn supports_streaming(&self) -> bool {
+        match self {
+            GeminiApi::GenerateContent => false,
+            GeminiApi::StreamGenerateContent => true,
+        }
+    }
+
+    fn supports_tools(&self) -> bool {
+        true
+    }
+
+    fn supports_vision(&self) -> bool {
+        true
+    }
+
+    fn all_variants() -> Vec<Self> {
+        vec![GeminiApi::GenerateContent, GeminiApi::StreamGenerateContent]
+    }
+}
+
+// ============================================================================
+// GENERATE CONTENT REQUEST STRUCTURES
+// ============================================================================
+
+/// Gemini `generateContent` / `streamGenerateContent` request body
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct GenerateContentRequest {
+    /// The model is part of the request path, not the body
+    #[serde(skip)]
+    pub model: String,
+    /// The conversation so far, oldest first
+    pub contents: Vec<Content>,
+    /// Developer-set system instruction; only text parts are honored
+    pub system_instruction: Option<Content>,
+    pub generation_config: Option<GenerationConfig>,
+    pub tools: Option<Vec<Tool>>,
+    pub tool_config: Option<ToolConfig>,
+    pub safety_settings: Option<Vec<Value>>,
+    pub cached_content: Option<String>,
+    /// Gemini rejects unknown fields, so metadata never leaves the gateway
+    #[serde(skip)]
+    pub metadata: Option<HashMap<String, Value>>,
+    /// Whether this request should use the streaming endpoint (internal field, not serialized)
+    #[serde(skip)]
+    pub stream: bool,
+}
+
+/// Author of a turn; system instructions carry no role
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ContentRole {
+    User,
+    Model,
+}
+
+/// A single turn made of ordered parts
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Content {
+    pub role: Option<ContentRole>,
+    #[serde(default)]
+    pub parts: Vec<Part>,
+}
+
+/// One piece of a turn. Exactly one of the data fields is set.
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Part {
+    pub text: Option<String>,
+    pub inline_data: Option<Blob>,
+    pub file_data: Option<FileData>,
+    pub function_call: Option<FunctionCall>,
+    pub function_response: Option<FunctionResponse>,
+    /// Set on parts that hold the model's reasoning rather than its answer
+    pub thought: Option<bool>,
+    pub thought_signature: Option<String>,
+}
+
+impl Part {
+    pub fn text(text: impl Into<String>) -> Self {
+        Part {
+            text: Some(text.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Answer text, excluding thought summaries
+    pub fn answer_text(&self) -> Option<&str> {
+        if self.thought == Some(true) {
+            return None;
+        }
+        self.text.as_deref()
+    }
+
+    /// Thought summary text, returned with `includeThoughts`
+    pub fn thought_text(&self) -> Option<&str> {
+        if self.thought != Some(true) {
+            return None;
+        }
+        self.text.as_deref()
+    }
+}
+
+/// Base64-encoded media sent inline
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Blob {
+    pub mime_type: String,
+    pub data: String,
+}
+
+/// Media referenced by URI
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FileData {
+    pub mime_type: Option<String>,
+    pub file_uri: String,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FunctionCall {
+    pub id: Option<String>,
+    pub name: String,
+    #[serde(default)]
+    pub args: Value,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FunctionResponse {
+    pub id: Option<String>,
+    pub name: String,
+    /// Must be a JSON object
+    pub response: Value,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct GenerationConfig {
+    pub stop_sequences: Option<Vec<String>>,
+    pub response_mime_type: Option<String>,
+    pub response_json_schema: Option<Value>,
+    pub candidate_count: Option<u32>,
+    pub max_output_tokens: Option<u32>,
+    pub temperature: Option<f32>,
+    pub top_p: Option<f32>,
+    pub top_k: Option<u32>,
+    pub seed: Option<i32>,
+    pub presence_penalty: Option<f32>,
+    pub frequency_penalty: Option<f32>,
+    pub response_logprobs: Option<bool>,
+    pub logprobs: Option<u32>,
+    pub thinking_config: Option<Value>,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Tool {
+    pub function_declarations: Option<Vec<FunctionDeclaration>>,
+    pub google_search: Option<Value>,
+    pub code_execution: Option<Value>,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FunctionDeclaration {
+    pub name: String,
+    pub description: Option<String>,
+    /// Full JSON Schema, as opposed to the OpenAPI subset `parameters` takes
+    pub parameters_json_schema: Option<Value>,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolConfig {
+    pub function_calling_config: Option<FunctionCallingConfig>,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FunctionCallingConfig {
+    pub mode: FunctionCallingMode,
+    pub allowed_function_names: Option<Vec<String>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum FunctionCallingMode {
+    Auto,
+    Any,
+    None,
+}
+
+/// Gemini streaming request (same structure as generateContent)
+pub type StreamGenerateContentRequest = GenerateContentRequest;
+
+impl ProviderRequest for GenerateContentRequest {
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn set_model(&mut self, model: String) {
+        self.model = model;
+    }
+
+    fn is_streaming(&self) -> bool {
+        self.stream
+    }
+
+    fn extract_messages_text(&self) -> String {
+        self.system_instruction
+            .iter()
+            .chain(&self.contents)
+            .flat_map(|content| &content.parts)
+            .filter_map(|part| part.text.as_deref())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn get_recent_user_message(&self) -> Option<String> {
+        self.contents
+            .iter()
+            .rev()
+            .filter(|content| content.role == Some(ContentRole::User))
+            .find_map(|content| content.parts.iter().find_map(|part| part.text.clone()))
+    }
+
+    fn get_tool_names(&self) -> Option<Vec<String>> {
+        let names: Vec<String> = self
+            .tools
+            .iter()
+            .flatten()
+            .flat_map(|tool| tool.function_declarations.iter().flatten())
+            .map(|declaration| declaration.name.clone())
+            .collect();
+        if names.is_empty() {
+            None
+        } else {
+            Some(names)
+        }
+    }
+
+    fn to_bytes(&self) -> Result<Vec<u8>, ProviderRequestError> {
+        serde_json::to_vec(self).map_err(|e| ProviderRequestError {
+            message: format!("Failed to serialize Gemini request: {}", e),
+            source: Some(Box::new(e)),
+        })
+    }
+
+    fn metadata(&self) -> &Option<HashMap<String, Value>> {
+        &self.metadata
+    }
+
+    fn remove_metadata_key(&mut self, key: &str) -> bool {
+        if let Some(ref mut metadata) = self.metadata {
+            metadata.remove(key).is_some()
+        } else {
+            false
+        }
+    }
+
+    fn get_temperature(&self) -> Option<f32> {
+        self.generation_config.as_ref()?.temperature
+    }
+
+    fn get_messages(&self) -> Vec<crate::apis::openai::Message> {
+        use crate::apis::openai::{Message, MessageContent, Role};
+
+        let text_of = |content: &Content| {
+            content
+                .parts
+                .iter()
+                .filter_map(Part::answer_text)
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+
+        let mut openai_messages = Vec::new();
+        if let Some(system) = &self.system_instruction {
+            openai_messages.push(Message {
+                role: Role::System,
+                content: Some(MessageContent::Text(text_of(system))),
+                name: None,
+                tool_calls: None,
+                tool_call_id: None,
+                thinking_blocks: None,
+            });
+        }
+        for content in &self.contents {
+            let role = match content.role {
+                Some(ContentRole::Model) => Role::Assistant,
+                _ => Role::User,
+            };
+            openai_messages.push(Message {
+                role,
+                content: Some(MessageContent::Text(text_of(content))),
+                name: None,
+                tool_calls: None,
+                tool_call_id: None,
+                thinking_blocks: None,
+            });
+        }
+        openai_messages
+    }
+
+    fn set_messages(&mut self, messages: &[crate::apis::openai::Message]) {
+        use crate::apis::openai::Role;
+        use crate::transforms::lib::ExtractText;
+
+        let mut system_parts = Vec::new();
+        let mut contents = Vec::new();
+        for msg in messages {
+            let role = match msg.role {
+                Role::System | Role::Developer => {
+                    system_parts.push(Part::text(msg.content.extract_text()));
+                    continue;
+                }
+                Role::User => ContentRole::User,
+                Role::Assistant => ContentRole::Model,
+                Role::Tool => continue,
+            };
+            contents.push(Content {
+                role: Some(role),
+                parts: vec![Part::text(msg.content.extract_text())],
+            });
+        }
+
+        if !system_parts.is_empty() {
+            self.system_instruction = Some(Content {
+                role: None,
+                parts: system_parts,
+            });
+        }
+        self.contents = contents;
+    }
+}
+
+// ============================================================================
+// GENERATE CONTENT RESPONSE STRUCTURES
+// ============================================================================
+
+/// Gemini `generateContent` response. Each event of a streamed response has
+/// the same shape, carrying only the newly generated parts.
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct GenerateContentResponse {
+    #[serde(default)]
+    pub candidates: Vec<Candidate>,
+    pub prompt_feedback: Option<PromptFeedback>,
+    pub usage_metadata: Option<UsageMetadata>,
+    pub model_version: Option<String>,
+    pub response_id: Option<String>,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Candidate {
+    pub content: Option<Content>,
+    pub finish_reason: Option<FinishReason>,
+    #[serde(default)]
+    pub index: u32,
+    pub safety_ratings: Option<Vec<Value>>,
+    /// Set when the request asked for `responseLogprobs`
+    pub logprobs_result: Option<LogprobsResult>,
+    pub avg_logprobs: Option<f64>,
+}
+
+/// Log probabilities of the generated tokens
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct LogprobsResult {
+    /// The most likely tokens at each step, `logprobs` of them
+    #[serde(default)]
+    pub top_candidates: Vec<TopCandidates>,
+    /// The token chosen at each step
+    #[serde(default)]
+    pub chosen_candidates: Vec<LogprobsCandidate>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TopCandidates {
+    #[serde(default)]
+    pub candidates: Vec<LogprobsCandidate>,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct LogprobsCandidate {
+    pub token: Option<String>,
+    pub token_id: Option<i64>,
+    pub log_probability: Option<f64>,
+}
+
+/// Why the model stopped generating
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum FinishReason {
+    FinishReasonUnspecified,
+    Stop,
+    MaxTokens,
+    Safety,
+    Recitation,
+    Language,
+    Blocklist,
+    ProhibitedContent,
+    Spii,
+    MalformedFunctionCall,
+    ImageSafety,
+    UnexpectedToolCall,
+    TooManyToolCalls,
+    #[serde(other)]
+    Other,
+}
+
+/// Set instead of candidates when the prompt itself was blocked
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptFeedback {
+    pub block_reason: Option<String>,
+    pub safety_ratings: Option<Vec<Value>>,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageMetadata {
+    #[serde(default)]
+    pub prompt_token_count: u32,
+    #[serde(default)]
+    pub candidates_token_count: u32,
+    #[serde(default)]
+    pub total_token_count: u32,
+    pub cached_content_token_count: Option<u32>,
+    pub thoughts_token_count: Option<u32>,
+}
+
+// ============================================================================
+// EMBED CONTENT STRUCTURES
+// ============================================================================
+
+/// Gemini `embedContent` request body. `batchEmbedContents` takes a list of
+/// them, each naming its model.
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct EmbedContentRequest {
+    /// `models/{model}`; only set inside a batch
+    pub model: Option<String>,
+    pub content: Content,
+    /// `RETRIEVAL_QUERY`, `RETRIEVAL_DOCUMENT`, `SEMANTIC_SIMILARITY`,
+    /// `CLASSIFICATION`, `CLUSTERING`, ...
+    pub task_type: Option<String>,
+    pub output_dimensionality: Option<u32>,
+}
+
+/// Gemini `batchEmbedContents` request body
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct BatchEmbedContentsRequest {
+    pub requests: Vec<EmbedContentRequest>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ContentEmbedding {
+    #[serde(default)]
+    pub values: Vec<f32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct EmbedContentResponse {
+    pub embedding: ContentEmbedding,
+}
+
+/// Embeddings in the order of the batch's requests
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BatchEmbedContentsResponse {
+    #[serde(default)]
+    pub embeddings: Vec<ContentEmbedding>,
+}
+
+// ============================================================================
+// IMAGEN PREDICT STRUCTURES (VERTEX AI)
+// ============================================================================
+
+/// Vertex AI Imagen `:predict` request body
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ImagenPredictRequest {
+    pub instances: Vec<ImagenInstance>,
+    pub parameters: ImagenParameters,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ImagenInstance {
+    pub prompt: String,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ImagenParameters {
+    /// 1 to 4
+    pub sample_count: Option<u32>,
+    /// `1:1`, `3:4`, `4:3`, `9:16` or `16:9`
+    pub aspect_ratio: Option<String>,
+    pub negative_prompt: Option<String>,
+    pub person_generation: Option<String>,
+    pub output_options: Option<ImagenOutputOptions>,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ImagenOutputOptions {
+    /// e.g. `image/png` or `image/jpeg`
+    pub mime_type: Option<String>,
+    pub compression_quality: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ImagenPredictResponse {
+    #[serde(default)]
+    pub predictions: Vec<ImagenPrediction>,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ImagenPrediction {
+    /// Missing when the image was filtered out
+    pub bytes_base64_encoded: Option<String>,
+    pub mime_type: Option<String>,
+    /// Reason the image was filtered, e.g. by responsible AI filters
+    pub rai_filtered_reason: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_gemini_api_from_endpoint() {
+        assert_eq!(
+            GeminiApi::from_endpoint("/v1beta/models/gemini-2.5-flash:generateContent"),
+            Some(GeminiApi::GenerateContent)
+        );
+        assert_eq!(
+            GeminiApi::from_endpoint(
+                "/v1beta/models/gemini-2.5-flash:streamGenerateContent?alt=sse"
+            ),
+            Some(GeminiApi::StreamGenerateContent)
+        );
+        assert_eq!(GeminiApi::from_endpoint("/v1/chat/completions"), None);
+    }
+
+    #[test]
+    fn test_request_serializes_in_camel_case_without_internal_fields() {
+        let request = GenerateContentRequest {
+            model: "gemini-2.5-flash".to_string(),
+            contents: vec![Content {
+                role: Some(ContentRole::User),
+                parts: vec![Part::text("Hi")],
+            }],
+            system_instruction: Some(Content {
+                role: None,
+                parts: vec![Part::text("Be brief")],
+            }),
+            generation_config: Some(GenerationConfig {
+                max_output_tokens: Some(64),
+                ..Default::default()
+            }),
+            metadata: Some(HashMap::from([("trace".to_string(), json!("x"))])),
+            stream: true,
+            ..Default::default()
+        };
+
+        let body = serde_json::to_value(&request).unwrap();
+        assert_eq!(
+            body,
+            json!({
+                "contents": [{"role": "user", "parts": [{"text": "Hi"}]}],
+                "systemInstruction": {"parts": [{"text": "Be brief"}]},
+                "generationConfig": {"maxOutputTokens": 64}
+            })
+        );
+        assert_eq!(request.extract_messages_text(), "Be brief Hi");
+        assert_eq!(request.get_recent_user_message().as_deref(), Some("Hi"));
+    }
+
+    #[test]
+    fn test_response_deserialization() {
+        let body = json!({
+            "candidates": [{
+                "content": {
+                    "role": "model",
+                    "parts": [
+                        {"text": "Thinking it over", "thought": true},
+                        {"text": "It is sunny."},
+                        {"functionCall": {"name": "get_weather", "args": {"city": "Paris"}}}
+                    ]
+                },
+                "finishReason": "STOP",
+                "index": 0
+            }],
+            "usageMetadata": {"promptTokenCount": 12, "candidatesTokenCount": 7, "totalTokenCount": 19},
+            "modelVersion": "gemini-2.5-flash",
+            "responseId": "abc"
+        });
+
+        let response: GenerateContentResponse = serde_json::from_value(body).unwrap();
+        let candidate = &response.candidates[0];
+        assert_eq!(candidate.finish_reason, Some(FinishReason::Stop));
+        let parts = &candidate.content.as_ref().unwrap().parts;
+        assert_eq!(parts[0].answer_text(), None);
+        assert_eq!(parts[1].answer_text(), Some("It is sunny."));
+        assert_eq!(
+            parts[2].function_call.as_ref().unwrap().args["city"],
+            "Paris"
+        );
+        assert_eq!(response.usage_metadata.unwrap().total_token_count, 19);
+
+        let unknown: FinishReason = serde_json::from_value(json!("SOMETHING_NEW")).unwrap();
+        assert_eq!(unknown, FinishReason::Other);
+    }
+}
@@ -0,0 +1,302 @@
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+
+use crate::apis::amazon_bedrock::{TitanImageRequest, TitanImageResponse};
+use crate::apis::gemini::{ImagenPredictRequest, ImagenPredictResponse};
+use crate::clients::endpoints::{join_endpoint, AZURE_OPENAI_API_VERSION};
+use crate::providers::request::ProviderRequestError;
+use crate::providers::response::ProviderResponseError;
+use crate::ProviderId;
+
+// ============================================================================
+// IMAGE GENERATION REQUEST STRUCTURES
+// ============================================================================
+
+/// OpenAI `/v1/images/generations` request, the shape every client sends
+/// whatever provider serves the model
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ImageGenerationRequest {
+    #[serde(default)]
+    pub model: String,
+    pub prompt: String,
+    pub n: Option<u32>,
+    /// `standard` or `hd` for DALL·E 3; `low`, `medium`, `high` or `auto`
+    /// for GPT image models
+    pub quality: Option<String>,
+    pub response_format: Option<ImageResponseFormat>,
+    /// `{width}x{height}`, e.g. `1024x1024`
+    pub size: Option<String>,
+    /// `vivid` or `natural`; DALL·E 3 only
+    pub style: Option<String>,
+    /// `png`, `jpeg` or `webp`; GPT image models only
+    pub output_format: Option<String>,
+    pub background: Option<String>,
+    pub user: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ImageResponseFormat {
+    #[default]
+    Url,
+    B64Json,
+}
+
+impl ImageGenerationRequest {
+    /// `size` as width and height in pixels
+    pub fn dimensions(&self) -> Option<(u32, u32)> {
+        let (width, height) = self.size.as_deref()?.split_once('x')?;
+        Some((width.trim().parse().ok()?, height.trim().parse().ok()?))
+    }
+}
+
+// ============================================================================
+// IMAGE GENERATION RESPONSE STRUCTURES
+// ============================================================================
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ImagesResponse {
+    pub created: u64,
+    #[serde(default)]
+    pub data: Vec<ImageData>,
+    pub usage: Option<serde_json::Value>,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ImageData {
+    pub url: Option<String>,
+    pub b64_json: Option<String>,
+    pub revised_prompt: Option<String>,
+}
+
+impl ImagesResponse {
+    /// Return images as the client asked: base64 images become `data:` URLs
+    /// when a URL is wanted, since native APIs only return image bytes
+    pub fn into_format(mut self, format: ImageResponseFormat, mime_type: &str) -> Self {
+        for image in &mut self.data {
+            match format {
+                ImageResponseFormat::Url => {
+                    if let Some(b64) = image.b64_json.take() {
+                        image.url = Some(format!("data:{};base64,{}", mime_type, b64));
+                    }
+                }
+                ImageResponseFormat::B64Json => {
+                    let b64 = image
+                        .url
+                        .as_deref()
+                        .and_then(|url| url.strip_prefix("data:"))
+                        .and_then(|url| url.split_once(";base64,"))
+                        .map(|(_, b64)| b64.to_string());
+                    if let Some(b64) = b64 {
+                        image.url = None;
+                        image.b64_json = Some(b64);
+                    }
+                }
+            }
+        }
+        self
+    }
+}
+
+// ============================================================================
+// UPSTREAM IMAGE GENERATION REQUEST
+// ============================================================================
+
+/// An image generation request translated for the provider serving its
+/// model. Vertex AI and Gemini are sent Imagen's `:predict` API and Amazon
+/// Bedrock the Titan Image Generator body (also taken by Nova Canvas); every
+/// other provider takes the OpenAI request as-is.
+#[derive(Debug, Clone)]
+pub struct UpstreamImagesRequest {
+    pub model_id: String,
+    /// Format the client asked for; native APIs only return base64
+    pub response_format: ImageResponseFormat,
+    pub body: ImagesBody,
+}
+
+#[derive(Debug, Clone)]
+pub enum ImagesBody {
+    OpenAI(ImageGenerationRequest),
+    Imagen(ImagenPredictRequest),
+    TitanImage(TitanImageRequest),
+}
+
+impl UpstreamImagesRequest {
+    pub fn for_provider(
+        request: ImageGenerationRequest,
+        provider_id: &ProviderId,
+    ) -> Result<Self, ProviderRequestError> {
+        let model_id = request.model.clone();
+        let response_format = request.response_format.unwrap_or_default();
+        let body = match provider_id {
+            ProviderId::VertexAI | ProviderId::Gemini => {
+                ImagenPredictRequest::try_from(request).map(ImagesBody::Imagen)
+            }
+            ProviderId::AmazonBedrock => {
+                TitanImageRequest::try_from(request).map(ImagesBody::TitanImage)
+            }
+            _ => Ok(ImagesBody::OpenAI(request)),
+        }
+        .map_err(|e| ProviderRequestError {
+            message: format!("Failed to translate image generation request: {}", e),
+            source: Some(Box::new(e)),
+        })?;
+        Ok(UpstreamImagesRequest {
+            model_id,
+            response_format,
+            body,
+        })
+    }
+
+    /// Whether the provider's native API is used, so its response has to be
+    /// translated back
+    pub fn is_native(&self) -> bool {
+        !matches!(self.body, ImagesBody::OpenAI(_))
+    }
+
+    /// Upstream path. For Azure OpenAI, `model_id` is the deployment name and
+    /// `api_version` overrides [`AZURE_OPENAI_API_VERSION`].
+    pub fn path(
+        &self,
+        provider_id: &ProviderId,
+        base_url_path_prefix: Option<&str>,
+        use_unversioned_paths: bool,
+        api_version: Option<&str>,
+    ) -> String {
+        let build_endpoint = |provider_prefix: &str, suffix: &str| {
+            join_endpoint(base_url_path_prefix, provider_prefix, suffix)
+        };
+        let model_id = &self.model_id;
+        match &self.body {
+            // Vertex AI paths hang off the base_url's
+            // `/v1/projects/{project}/locations/{location}` prefix
+            ImagesBody::Imagen(_) if *provider_id == ProviderId::VertexAI => build_endpoint(
+                "",
+                &format!("/publishers/google/models/{}:predict", model_id),
+            ),
+            ImagesBody::Imagen(_) => {
+                build_endpoint("/v1beta", &format!("/models/{}:predict", model_id))
+            }
+            ImagesBody::TitanImage(_) => build_endpoint("", &format!("/model/{}/invoke", model_id)),
+            ImagesBody::OpenAI(_) => match provider_id {
+                ProviderId::AzureOpenAI => build_endpoint(
+                    "/openai/deployments",
+                    &format!(
+                        "/{}/images/generations?api-version={}",
+                        model_id,
+                        api_version.unwrap_or(AZURE_OPENAI_API_VERSION)
+                    ),
+                ),
+                _ if use_unversioned_paths => build_endpoint("", "/images/generations"),
+                _ => build_endpoint("/v1", "/images/generations"),
+            },
+        }
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, ProviderRequestError> {
+        match &self.body {
+            ImagesBody::OpenAI(request) => serde_json::to_vec(request),
+            ImagesBody::Imagen(request) => serde_json::to_vec(request),
+            ImagesBody::TitanImage(request) => serde_json::to_vec(request),
+        }
+        .map_err(|e| ProviderRequestError {
+            message: format!("Failed to serialize image generation request: {}", e),
+            source: Some(Box::new(e)),
+        })
+    }
+
+    /// Parse the provider's response into the OpenAI shape
+    pub fn parse_response(&self, body: &[u8]) -> Result<ImagesResponse, ProviderResponseError> {
+        match &self.body {
+            ImagesBody::OpenAI(_) => serde_json::from_slice::<ImagesResponse>(body),
+            ImagesBody::Imagen(request) => {
+                let mime_type = request
+                    .parameters
+                    .output_options
+                    .as_ref()
+                    .and_then(|options| options.mime_type.clone())
+                    .unwrap_or_else(|| "image/png".to_string());
+                serde_json::from_slice::<ImagenPredictResponse>(body).map(|response| {
+                    ImagesResponse::from(response).into_format(self.response_format, &mime_type)
+                })
+            }
+            ImagesBody::TitanImage(_) => {
+                serde_json::from_slice::<TitanImageResponse>(body).map(|response| {
+                    ImagesResponse::from(response).into_format(self.response_format, "image/png")
+                })
+            }
+        }
+        .map_err(|e| ProviderResponseError {
+            message: format!("Failed to parse image generation response: {}", e),
+            source: Some(Box::new(e)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn request(body: serde_json::Value) -> ImageGenerationRequest {
+        serde_json::from_value(body).unwrap()
+    }
+
+    #[test]
+    fn test_image_paths() {
+        let body = json!({"model": "imagen-3.0-generate-002", "prompt": "a cat"});
+        let imagen =
+            UpstreamImagesRequest::for_provider(request(body.clone()), &ProviderId::VertexAI)
+                .unwrap();
+        assert!(imagen.is_native());
+        assert_eq!(
+            imagen.path(&ProviderId::VertexAI, None, false, None),
+            "/publishers/google/models/imagen-3.0-generate-002:predict"
+        );
+
+        let titan = UpstreamImagesRequest::for_provider(
+            request(json!({"model": "amazon.titan-image-generator-v2:0", "prompt": "a cat"})),
+            &ProviderId::AmazonBedrock,
+        )
+        .unwrap();
+        assert_eq!(
+            titan.path(&ProviderId::AmazonBedrock, None, false, None),
+            "/model/amazon.titan-image-generator-v2:0/invoke"
+        );
+
+        let openai =
+            UpstreamImagesRequest::for_provider(request(body), &ProviderId::OpenAI).unwrap();
+        assert!(!openai.is_native());
+        assert_eq!(
+            openai.path(&ProviderId::OpenAI, None, false, None),
+            "/v1/images/generations"
+        );
+    }
+
+    #[test]
+    fn test_images_response_formats() {
+        let response = ImagesResponse {
+            created: 1,
+            data: vec![ImageData {
+                b64_json: Some("iVBORw0KGgo=".to_string()),
+                ..Default::default()
+            }],
+            usage: None,
+        };
+        let as_url = response
+            .clone()
+            .into_format(ImageResponseFormat::Url, "image/png");
+        assert_eq!(
+            as_url.data[0].url.as_deref(),
+            Some("data:image/png;base64,iVBORw0KGgo=")
+        );
+        assert_eq!(as_url.data[0].b64_json, None);
+        assert_eq!(
+            as_url.into_format(ImageResponseFormat::B64Json, "image/png"),
+            response
+        );
+    }
+}
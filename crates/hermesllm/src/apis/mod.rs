@@ -1,7 +1,18 @@
 pub mod amazon_bedrock;
 pub mod anthropic;
+pub mod audio;
+pub mod batches;
+pub mod cohere;
+pub mod deepgram;
+pub mod embeddings;
+pub mod gemini;
+pub mod images;
+pub mod moderations;
+pub mod multipart;
+pub mod ollama;
 pub mod openai;
 pub mod openai_responses;
+pub mod realtime;
 pub mod streaming_shapes;
 
 // Explicit exports to avoid naming conflicts
@@ -10,6 +21,10 @@ pub use amazon_bedrock::{
     Message as BedrockMessage, Tool as BedrockTool, ToolChoice as BedrockToolChoice,
 };
 pub use anthropic::{AnthropicApi, MessagesRequest, MessagesResponse, MessagesStreamEvent};
+pub use cohere::{CohereApi, CohereChatRequest, CohereChatResponse, CohereStreamEvent};
+pub use embeddings::{EmbeddingsRequest, EmbeddingsResponse, UpstreamEmbeddingsRequest};
+pub use gemini::{GeminiApi, GenerateContentRequest, GenerateContentResponse};
+pub use ollama::{OllamaApi, OllamaChatRequest, OllamaChatResponse};
 pub use openai::{
     ChatCompletionsRequest, ChatCompletionsResponse, ChatCompletionsStreamResponse, OpenAIApi,
 };
@@ -0,0 +1,288 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+
+use crate::apis::amazon_bedrock::{ApplyGuardrailRequest, ApplyGuardrailResponse};
+use crate::clients::endpoints::join_endpoint;
+use crate::providers::request::ProviderRequestError;
+use crate::providers::response::ProviderResponseError;
+use crate::ProviderId;
+
+/// Categories OpenAI's moderation models report on every result
+pub const MODERATION_CATEGORIES: [&str; 13] = [
+    "harassment",
+    "harassment/threatening",
+    "hate",
+    "hate/threatening",
+    "illicit",
+    "illicit/violent",
+    "self-harm",
+    "self-harm/instructions",
+    "self-harm/intent",
+    "sexual",
+    "sexual/minors",
+    "violence",
+    "violence/graphic",
+];
+
+// ============================================================================
+// MODERATION REQUEST STRUCTURES
+// ============================================================================
+
+/// OpenAI `/v1/moderations` request
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ModerationRequest {
+    pub input: ModerationInput,
+    pub model: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum ModerationInput {
+    Text(String),
+    Texts(Vec<String>),
+    /// Text and image parts, classified together by omni models
+    Parts(Vec<ModerationInputPart>),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ModerationInputPart {
+    Text { text: String },
+    ImageUrl { image_url: ModerationImageUrl },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ModerationImageUrl {
+    pub url: String,
+}
+
+impl ModerationInput {
+    /// Text to classify; image parts are left out
+    pub fn texts(&self) -> Vec<&str> {
+        match self {
+            ModerationInput::Text(text) => vec![text.as_str()],
+            ModerationInput::Texts(texts) => texts.iter().map(String::as_str).collect(),
+            ModerationInput::Parts(parts) => parts
+                .iter()
+                .filter_map(|part| match part {
+                    ModerationInputPart::Text { text } => Some(text.as_str()),
+                    ModerationInputPart::ImageUrl { .. } => None,
+                })
+                .collect(),
+        }
+    }
+
+    pub fn has_images(&self) -> bool {
+        matches!(self, ModerationInput::Parts(parts)
+            if parts.iter().any(|part| matches!(part, ModerationInputPart::ImageUrl { .. })))
+    }
+}
+
+// ============================================================================
+// MODERATION RESPONSE STRUCTURES
+// ============================================================================
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ModerationResponse {
+    pub id: String,
+    pub model: String,
+    pub results: Vec<ModerationResult>,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ModerationResult {
+    pub flagged: bool,
+    pub categories: BTreeMap<String, bool>,
+    pub category_scores: BTreeMap<String, f64>,
+    /// Input types (`text`, `image`) each category was judged on; omni models only
+    pub category_applied_input_types: Option<BTreeMap<String, Vec<String>>>,
+}
+
+impl ModerationResult {
+    /// A result with every standard category present and unflagged
+    pub fn unflagged() -> Self {
+        ModerationResult {
+            flagged: false,
+            categories: MODERATION_CATEGORIES
+                .iter()
+                .map(|category| (category.to_string(), false))
+                .collect(),
+            category_scores: MODERATION_CATEGORIES
+                .iter()
+                .map(|category| (category.to_string(), 0.0))
+                .collect(),
+            category_applied_input_types: None,
+        }
+    }
+
+    /// Record a category's verdict, keeping the higher score when it was
+    /// already reported
+    pub fn record(&mut self, category: &str, flagged: bool, score: f64) {
+        let flag = self.categories.entry(category.to_string()).or_default();
+        *flag |= flagged;
+        let current = self
+            .category_scores
+            .entry(category.to_string())
+            .or_default();
+        *current = current.max(score);
+        self.flagged |= flagged;
+    }
+
+    /// Categories flagged, in name order
+    pub fn flagged_categories(&self) -> Vec<String> {
+        self.categories
+            .iter()
+            .filter(|(_, flagged)| **flagged)
+            .map(|(category, _)| category.clone())
+            .collect()
+    }
+}
+
+// ============================================================================
+// UPSTREAM MODERATION REQUEST
+// ============================================================================
+
+/// A moderation request translated for the provider serving its model.
+/// Amazon Bedrock is sent `ApplyGuardrail`, with the model naming the
+/// guardrail as `{identifier}:{version}`; every other provider takes the
+/// OpenAI request as-is.
+#[derive(Debug, Clone)]
+pub struct UpstreamModerationRequest {
+    pub model_id: String,
+    pub body: ModerationBody,
+}
+
+#[derive(Debug, Clone)]
+pub enum ModerationBody {
+    OpenAI(ModerationRequest),
+    BedrockGuardrail(ApplyGuardrailRequest),
+}
+
+impl UpstreamModerationRequest {
+    pub fn for_provider(
+        request: ModerationRequest,
+        provider_id: &ProviderId,
+    ) -> Result<Self, ProviderRequestError> {
+        let model_id = request.model.clone().unwrap_or_default();
+        let body = match provider_id {
+            ProviderId::AmazonBedrock => ApplyGuardrailRequest::try_from(request)
+                .map(ModerationBody::BedrockGuardrail)
+                .map_err(|e| ProviderRequestError {
+                    message: format!("Failed to translate moderation request: {}", e),
+                    source: Some(Box::new(e)),
+                })?,
+            _ => ModerationBody::OpenAI(request),
+        };
+        Ok(UpstreamModerationRequest { model_id, body })
+    }
+
+    /// Whether the provider's native API is used, so its response has to be
+    /// translated back
+    pub fn is_native(&self) -> bool {
+        !matches!(self.body, ModerationBody::OpenAI(_))
+    }
+
+    pub fn path(&self, base_url_path_prefix: Option<&str>, use_unversioned_paths: bool) -> String {
+        let build_endpoint = |provider_prefix: &str, suffix: &str| {
+            join_endpoint(base_url_path_prefix, provider_prefix, suffix)
+        };
+        match &self.body {
+            ModerationBody::BedrockGuardrail(_) => {
+                let (identifier, version) = self
+                    .model_id
+                    .split_once(':')
+                    .unwrap_or((&self.model_id, "DRAFT"));
+                build_endpoint(
+                    "",
+                    &format!("/guardrail/{}/version/{}/apply", identifier, version),
+                )
+            }
+            ModerationBody::OpenAI(_) if use_unversioned_paths => {
+                build_endpoint("", "/moderations")
+            }
+            ModerationBody::OpenAI(_) => build_endpoint("/v1", "/moderations"),
+        }
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, ProviderRequestError> {
+        match &self.body {
+            ModerationBody::OpenAI(request) => serde_json::to_vec(request),
+            ModerationBody::BedrockGuardrail(request) => serde_json::to_vec(request),
+        }
+        .map_err(|e| ProviderRequestError {
+            message: format!("Failed to serialize moderation request: {}", e),
+            source: Some(Box::new(e)),
+        })
+    }
+
+    /// Parse the provider's response into the OpenAI shape
+    pub fn parse_response(
+        &self,
+        body: &[u8],
+        request_id: &str,
+    ) -> Result<ModerationResponse, ProviderResponseError> {
+        match &self.body {
+            ModerationBody::OpenAI(_) => serde_json::from_slice::<ModerationResponse>(body),
+            ModerationBody::BedrockGuardrail(_) => {
+                serde_json::from_slice::<ApplyGuardrailResponse>(body).map(|response| {
+                    ModerationResponse {
+                        id: format!("modr-{}", request_id),
+                        model: self.model_id.clone(),
+                        results: vec![ModerationResult::from(response)],
+                    }
+                })
+            }
+        }
+        .map_err(|e| ProviderResponseError {
+            message: format!("Failed to parse moderation response: {}", e),
+            source: Some(Box::new(e)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_moderation_inputs() {
+        let request: ModerationRequest = serde_json::from_value(json!({
+            "model": "omni-moderation-latest",
+            "input": [
+                {"type": "text", "text": "first"},
+                {"type": "image_url", "image_url": {"url": "https://example.com/a.png"}}
+            ]
+        }))
+        .unwrap();
+        assert_eq!(request.input.texts(), vec!["first"]);
+        assert!(request.input.has_images());
+
+        let request: ModerationRequest =
+            serde_json::from_value(json!({"input": ["first", "second"]})).unwrap();
+        assert_eq!(request.input.texts(), vec!["first", "second"]);
+        assert!(!request.input.has_images());
+    }
+
+    #[test]
+    fn test_moderation_paths() {
+        let request: ModerationRequest =
+            serde_json::from_value(json!({"model": "gr-abc123:2", "input": "hello"})).unwrap();
+        let guardrail =
+            UpstreamModerationRequest::for_provider(request.clone(), &ProviderId::AmazonBedrock)
+                .unwrap();
+        assert!(guardrail.is_native());
+        assert_eq!(
+            guardrail.path(None, false),
+            "/guardrail/gr-abc123/version/2/apply"
+        );
+
+        let openai = UpstreamModerationRequest::for_provider(request, &ProviderId::OpenAI).unwrap();
+        assert!(!openai.is_native());
+        assert_eq!(openai.path(None, false), "/v1/moderations");
+    }
+}
@@ -0,0 +1,196 @@
+//! `multipart/form-data` bodies, as sent to the OpenAI audio APIs. Only what
+//! those forms use is supported: named fields, with an optional filename and
+//! content type.
+
+/// One field of a form
+#[derive(Debug, Clone, PartialEq)]
+pub struct FormPart {
+    pub name: String,
+    pub filename: Option<String>,
+    pub content_type: Option<String>,
+    pub data: Vec<u8>,
+}
+
+/// A `multipart/form-data` body, fields in the order they were sent
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MultipartForm {
+    pub parts: Vec<FormPart>,
+}
+
+/// Boundary of a `multipart/form-data` content type, e.g.
+/// `multipart/form-data; boundary=abc`. `None` for any other content type.
+pub fn form_boundary(content_type: &str) -> Option<String> {
+    let mut params = content_type.split(';');
+    let mime = params.next()?.trim();
+    if !mime.eq_ignore_ascii_case("multipart/form-data") {
+        return None;
+    }
+    params
+        .filter_map(|param| param.split_once('='))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("boundary"))
+        .map(|(_, value)| value.trim().trim_matches('"').to_string())
+        .filter(|boundary| !boundary.is_empty())
+}
+
+impl MultipartForm {
+    pub fn parse(body: &[u8], boundary: &str) -> Result<Self, String> {
+        let delimiter = format!("--{}", boundary).into_bytes();
+        let mut rest = match find(body, &delimiter) {
+            Some(start) => &body[start + delimiter.len()..],
+            None => return Err("no multipart boundary found in body".to_string()),
+        };
+
+        let mut parts = Vec::new();
+        loop {
+            if rest.starts_with(b"--") {
+                return Ok(MultipartForm { parts });
+            }
+            rest = rest
+                .strip_prefix(b"\r\n")
+                .ok_or("malformed multipart boundary line")?;
+            let headers_end =
+                find(rest, b"\r\n\r\n").ok_or("unterminated multipart part headers")?;
+            let headers = std::str::from_utf8(&rest[..headers_end])
+                .map_err(|_| "multipart part headers are not UTF-8")?;
+            rest = &rest[headers_end + 4..];
+
+            let mut closing = b"\r\n".to_vec();
+            closing.extend_from_slice(&delimiter);
+            let data_end = find(rest, &closing).ok_or("unterminated multipart part")?;
+            parts.push(FormPart::from_headers(headers, rest[..data_end].to_vec())?);
+            rest = &rest[data_end + closing.len()..];
+        }
+    }
+
+    /// Serialize the form with `boundary`, which must not occur in any field
+    pub fn encode(&self, boundary: &str) -> Vec<u8> {
+        let mut body = Vec::new();
+        for part in &self.parts {
+            body.extend_from_slice(format!("--{}\r\n", boundary).as_bytes());
+            let mut disposition = format!("Content-Disposition: form-data; name=\"{}\"", part.name);
+            if let Some(filename) = &part.filename {
+                disposition.push_str(&format!("; filename=\"{}\"", filename));
+            }
+            body.extend_from_slice(disposition.as_bytes());
+            body.extend_from_slice(b"\r\n");
+            if let Some(content_type) = &part.content_type {
+                body.extend_from_slice(format!("Content-Type: {}\r\n", content_type).as_bytes());
+            }
+            body.extend_from_slice(b"\r\n");
+            body.extend_from_slice(&part.data);
+            body.extend_from_slice(b"\r\n");
+        }
+        body.extend_from_slice(format!("--{}--\r\n", boundary).as_bytes());
+        body
+    }
+
+    pub fn part(&self, name: &str) -> Option<&FormPart> {
+        self.parts.iter().find(|part| part.name == name)
+    }
+
+    /// Value of a text field
+    pub fn text(&self, name: &str) -> Option<&str> {
+        self.part(name)
+            .and_then(|part| std::str::from_utf8(&part.data).ok())
+    }
+
+    /// Values of a repeated text field such as `timestamp_granularities[]`
+    pub fn texts(&self, name: &str) -> Vec<&str> {
+        self.parts
+            .iter()
+            .filter(|part| part.name == name)
+            .filter_map(|part| std::str::from_utf8(&part.data).ok())
+            .collect()
+    }
+
+    /// Replace the value of a text field, or add it
+    pub fn set_text(&mut self, name: &str, value: &str) {
+        match self.parts.iter_mut().find(|part| part.name == name) {
+            Some(part) => part.data = value.as_bytes().to_vec(),
+            None => self.parts.push(FormPart {
+                name: name.to_string(),
+                filename: None,
+                content_type: None,
+                data: value.as_bytes().to_vec(),
+            }),
+        }
+    }
+
+    pub fn remove(&mut self, name: &str) {
+        self.parts.retain(|part| part.name != name);
+    }
+}
+
+impl FormPart {
+    fn from_headers(headers: &str, data: Vec<u8>) -> Result<Self, String> {
+        let mut name = None;
+        let mut filename = None;
+        let mut content_type = None;
+        for line in headers.split("\r\n") {
+            let Some((header, value)) = line.split_once(':') else {
+                continue;
+            };
+            if header.trim().eq_ignore_ascii_case("content-type") {
+                content_type = Some(value.trim().to_string());
+            } else if header.trim().eq_ignore_ascii_case("content-disposition") {
+                for param in value.split(';').skip(1) {
+                    match param.trim().split_once('=') {
+                        Some(("name", value)) => name = Some(value.trim_matches('"').to_string()),
+                        Some(("filename", value)) => {
+                            filename = Some(value.trim_matches('"').to_string())
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+        Ok(FormPart {
+            name: name.ok_or("multipart part without a name")?,
+            filename,
+            content_type,
+            data,
+        })
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_form_round_trip() {
+        let body = concat!(
+            "--XyZ\r\n",
+            "Content-Disposition: form-data; name=\"model\"\r\n",
+            "\r\n",
+            "whisper-1\r\n",
+            "--XyZ\r\n",
+            "Content-Disposition: form-data; name=\"file\"; filename=\"a.wav\"\r\n",
+            "Content-Type: audio/wav\r\n",
+            "\r\n",
+            "RIFF\r\n\0data\r\n",
+            "--XyZ--\r\n"
+        );
+        let boundary = form_boundary("multipart/form-data; boundary=\"XyZ\"").unwrap();
+        let mut form = MultipartForm::parse(body.as_bytes(), &boundary).unwrap();
+        assert_eq!(form.text("model"), Some("whisper-1"));
+        let file = form.part("file").unwrap();
+        assert_eq!(file.filename.as_deref(), Some("a.wav"));
+        assert_eq!(file.content_type.as_deref(), Some("audio/wav"));
+        assert_eq!(file.data, b"RIFF\r\n\0data");
+        assert_eq!(form.encode(&boundary), body.as_bytes());
+
+        form.set_text("model", "whisper-large-v3");
+        let reparsed = MultipartForm::parse(&form.encode("b2"), "b2").unwrap();
+        assert_eq!(reparsed.text("model"), Some("whisper-large-v3"));
+
+        assert_eq!(form_boundary("application/json"), None);
+        assert!(MultipartForm::parse(b"not a form", "XyZ").is_err());
+    }
+}
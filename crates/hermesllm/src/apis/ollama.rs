@@ -0,0 +1,318 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use serde_with::skip_serializing_none;
+
+use std::collections::HashMap;
+
+use super::ApiDefinition;
+use crate::providers::request::{ProviderRequest, ProviderRequestError};
+
+// ============================================================================
+// OLLAMA API ENUMERATION
+// ============================================================================
+
+/// Enum for all supported Ollama native APIs
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OllamaApi {
+    Chat,
+}
+
+impl ApiDefinition for OllamaApi {
+    fn endpoint(&self) -> &'static str {
+        match self {
+            OllamaApi::Chat => "/api/chat",
+        }
+    }
+
+    fn from_endpoint(endpoint: &str) -> Option<Self> {
+        let path = endpoint.split('?').next().unwrap_or(endpoint);
+        if path.ends_with("/api/chat") {
+            Some(OllamaApi::Chat)
+        } else {
+            None
+        }
+    }
+
+    fn supports_streaming(&self) -> bool {
+        true
+    }
+
+    fn supports_tools(&self) -> bool {
+        true
+    }
+
+    fn supports_vision(&self) -> bool {
+        true
+    }
+
+    fn all_variants() -> Vec<Self> {
+        vec![OllamaApi::Chat]
+    }
+}
+
+// ============================================================================
+// CHAT REQUEST STRUCTURES
+// ============================================================================
+
+/// Ollama `/api/chat` request body. Streaming responses are newline-delimited
+/// JSON objects shaped like [`OllamaChatResponse`].
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct OllamaChatRequest {
+    pub model: String,
+    pub messages: Vec<OllamaMessage>,
+    pub tools: Option<Vec<OllamaTool>>,
+    /// `"json"` or a JSON schema
+    pub format: Option<Value>,
+    pub options: Option<OllamaOptions>,
+    /// Ollama streams unless told otherwise, so this is always sent
+    #[serde(default)]
+    pub stream: bool,
+    pub keep_alive: Option<Value>,
+    /// Ask thinking models to return their reasoning separately
+    pub think: Option<bool>,
+    /// Ollama has no metadata field, so metadata never leaves the gateway
+    #[serde(skip)]
+    pub metadata: Option<HashMap<String, Value>>,
+}
+
+/// Sampling and runtime parameters, named as in a Modelfile
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct OllamaOptions {
+    pub temperature: Option<f32>,
+    pub top_p: Option<f32>,
+    pub top_k: Option<u32>,
+    pub seed: Option<i32>,
+    /// Maximum tokens to generate
+    pub num_predict: Option<i32>,
+    pub stop: Option<Vec<String>>,
+    pub presence_penalty: Option<f32>,
+    pub frequency_penalty: Option<f32>,
+    /// Context window size
+    pub num_ctx: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum OllamaRole {
+    System,
+    User,
+    Assistant,
+    Tool,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OllamaMessage {
+    pub role: OllamaRole,
+    #[serde(default)]
+    pub content: String,
+    /// Base64-encoded images, without a data URL prefix
+    pub images: Option<Vec<String>>,
+    /// Assistant turns only
+    pub tool_calls: Option<Vec<OllamaToolCall>>,
+    /// Tool turns only: the function whose result this is
+    pub tool_name: Option<String>,
+    /// Reasoning of thinking models
+    pub thinking: Option<String>,
+}
+
+impl OllamaMessage {
+    pub fn text(role: OllamaRole, text: impl Into<String>) -> Self {
+        OllamaMessage {
+            role,
+            content: text.into(),
+            images: None,
+            tool_calls: None,
+            tool_name: None,
+            thinking: None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OllamaTool {
+    #[serde(rename = "type")]
+    pub tool_type: String,
+    pub function: OllamaFunction,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OllamaFunction {
+    pub name: String,
+    pub description: Option<String>,
+    pub parameters: Value,
+}
+
+/// Ollama sends whole tool calls, with arguments as a JSON object and no id
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OllamaToolCall {
+    pub function: OllamaToolCallFunction,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OllamaToolCallFunction {
+    pub index: Option<u32>,
+    pub name: String,
+    #[serde(default)]
+    pub arguments: Value,
+}
+
+impl ProviderRequest for OllamaChatRequest {
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn set_model(&mut self, model: String) {
+        self.model = model;
+    }
+
+    fn is_streaming(&self) -> bool {
+        self.stream
+    }
+
+    fn extract_messages_text(&self) -> String {
+        self.messages
+            .iter()
+            .map(|message| message.content.as_str())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn get_recent_user_message(&self) -> Option<String> {
+        self.messages
+            .iter()
+            .rev()
+            .find(|message| message.role == OllamaRole::User)
+            .map(|message| message.content.clone())
+    }
+
+    fn get_tool_names(&self) -> Option<Vec<String>> {
+        self.tools.as_ref().map(|tools| {
+            tools
+                .iter()
+                .map(|tool| tool.function.name.clone())
+                .collect()
+        })
+    }
+
+    fn to_bytes(&self) -> Result<Vec<u8>, ProviderRequestError> {
+        serde_json::to_vec(self).map_err(|e| ProviderRequestError {
+            message: format!("Failed to serialize Ollama request: {}", e),
+            source: Some(Box::new(e)),
+        })
+    }
+
+    fn metadata(&self) -> &Option<HashMap<String, Value>> {
+        &self.metadata
+    }
+
+    fn remove_metadata_key(&mut self, key: &str) -> bool {
+        if let Some(ref mut metadata) = self.metadata {
+            metadata.remove(key).is_some()
+        } else {
+            false
+        }
+    }
+
+    fn get_temperature(&self) -> Option<f32> {
+        self.options
+            .as_ref()
+            .and_then(|options| options.temperature)
+    }
+
+    fn get_messages(&self) -> Vec<crate::apis::openai::Message> {
+        use crate::apis::openai::{Message, MessageContent, Role};
+
+        self.messages
+            .iter()
+            .map(|message| Message {
+                role: match message.role {
+                    OllamaRole::System => Role::System,
+                    OllamaRole::User => Role::User,
+                    OllamaRole::Assistant => Role::Assistant,
+                    OllamaRole::Tool => Role::Tool,
+                },
+                content: Some(MessageContent::Text(message.content.clone())),
+                name: None,
+                tool_calls: None,
+                tool_call_id: None,
+                thinking_blocks: None,
+            })
+            .collect()
+    }
+
+    fn set_messages(&mut self, messages: &[crate::apis::openai::Message]) {
+        use crate::apis::openai::Role;
+        use crate::transforms::lib::ExtractText;
+
+        self.messages = messages
+            .iter()
+            .filter_map(|msg| {
+                let role = match msg.role {
+                    Role::System | Role::Developer => OllamaRole::System,
+                    Role::User => OllamaRole::User,
+                    Role::Assistant => OllamaRole::Assistant,
+                    Role::Tool => return None,
+                };
+                Some(OllamaMessage::text(role, msg.content.extract_text()))
+            })
+            .collect();
+    }
+}
+
+// ============================================================================
+// CHAT RESPONSE STRUCTURES
+// ============================================================================
+
+/// A whole `/api/chat` response, or one line of a stream. The last line of a
+/// stream has `done: true` with the stop reason and token counts.
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OllamaChatResponse {
+    pub model: String,
+    pub created_at: Option<String>,
+    pub message: Option<OllamaMessage>,
+    #[serde(default)]
+    pub done: bool,
+    /// `stop`, `length`, `load` or `unload`
+    pub done_reason: Option<String>,
+    pub prompt_eval_count: Option<u32>,
+    pub eval_count: Option<u32>,
+    pub total_duration: Option<u64>,
+    pub load_duration: Option<u64>,
+    pub prompt_eval_duration: Option<u64>,
+    pub eval_duration: Option<u64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_ollama_api_from_endpoint() {
+        assert_eq!(OllamaApi::from_endpoint("/api/chat"), Some(OllamaApi::Chat));
+        assert_eq!(OllamaApi::from_endpoint("/v1/chat/completions"), None);
+    }
+
+    #[test]
+    fn test_ollama_request_always_sends_stream() {
+        let request = OllamaChatRequest {
+            model: "llama3.2".to_string(),
+            messages: vec![OllamaMessage::text(OllamaRole::User, "Hi")],
+            ..Default::default()
+        };
+        assert_eq!(
+            serde_json::to_value(&request).unwrap(),
+            json!({
+                "model": "llama3.2",
+                "messages": [{"role": "user", "content": "Hi"}],
+                "stream": false
+            })
+        );
+    }
+}
@@ -5,9 +5,11 @@ use std::collections::HashMap;
 use std::fmt::Display;
 use thiserror::Error;
 
+use super::anthropic::MessagesCacheControl;
 use super::ApiDefinition;
+use crate::providers::capabilities::ProviderCapabilities;
 use crate::providers::request::{ProviderRequest, ProviderRequestError};
-use crate::providers::response::{ProviderResponse, TokenUsage};
+use crate::providers::response::{ProviderResponse, TokenUsage, UsageDetails};
 use crate::providers::streaming_response::ProviderStreamResponse;
 use crate::transforms::lib::ExtractText;
 use crate::{CHAT_COMPLETIONS_PATH, OPENAI_RESPONSES_API_PATH};
@@ -115,18 +117,47 @@ pub struct ChatCompletionsRequest {
     pub stop_token_ids: Option<Vec<u32>>,
     pub continue_final_message: Option<bool>,
     pub add_generation_prompt: Option<bool>,
+
+    // xAI-specific parameters
+    /// Grok live search configuration (`mode`, `sources`, `return_citations`, ...)
+    pub search_parameters: Option<Value>,
+
+    // Anthropic-specific parameters
+    /// Prompt caching (`{"type": "ephemeral"}`): the system prompt, the tool
+    /// definitions and the conversation so far become cache breakpoints.
+    /// Dropped for upstreams that cache on their own.
+    pub cache_control: Option<MessagesCacheControl>,
 }
 
 impl ChatCompletionsRequest {
-    /// Suppress max_tokens if the model is o3, o3-*, openrouter/o3, or openrouter/o3-*
-    pub fn suppress_max_tokens_if_o3(&mut self) {
-        let model = self.model.as_str();
-        let is_o3 = model == "o3"
-            || model.starts_with("o3-")
-            || model == "openrouter/o3"
-            || model.starts_with("openrouter/o3-");
-        if is_o3 {
+    /// Carry the output limit in `max_completion_tokens` when
+    /// `use_max_completion_tokens`, in `max_tokens` otherwise. A request
+    /// setting both keeps `max_completion_tokens`. OpenAI's o-series and
+    /// gpt-5 models reject `max_tokens` whatever the provider.
+    pub fn normalize_max_tokens(&mut self, use_max_completion_tokens: bool) {
+        let model = self.model.rsplit('/').next().unwrap_or_default();
+        let rejects_max_tokens = ["o1", "o3", "o4", "gpt-5"]
+            .iter()
+            .any(|prefix| model.starts_with(prefix));
+        let limit = self.max_completion_tokens.or(self.max_tokens);
+        if use_max_completion_tokens || rejects_max_tokens {
+            self.max_completion_tokens = limit;
             self.max_tokens = None;
+        } else {
+            self.max_tokens = limit;
+            self.max_completion_tokens = None;
+        }
+    }
+
+    /// Drop `reasoning_effort` unless the model is an OpenAI reasoning model
+    /// (o-series, gpt-5, gpt-oss); other models reject it
+    pub fn drop_reasoning_effort_unless_reasoning_model(&mut self) {
+        let model = self.model.rsplit('/').next().unwrap_or_default();
+        let is_reasoning_model = ["o1", "o3", "o4", "gpt-5", "gpt-oss"]
+            .iter()
+            .any(|prefix| model.starts_with(prefix));
+        if !is_reasoning_model {
+            self.reasoning_effort = None;
         }
     }
 
@@ -164,6 +195,25 @@ pub struct Message {
     pub tool_calls: Option<Vec<ToolCall>>,
     /// ID of the tool call that this message is responding to (only present for tool role)
     pub tool_call_id: Option<String>,
+    /// Thinking an assistant message came with, sent back as is
+    pub thinking_blocks: Option<Vec<ThinkingBlock>>,
+}
+
+/// An Anthropic thinking block. Thinking with tool use only continues when
+/// the signed blocks of the last turn are sent back, so they ride along on
+/// assistant messages. Streams carry them piecewise: the thinking text in
+/// pieces, then its signature.
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ThinkingBlock {
+    Thinking {
+        thinking: String,
+        signature: Option<String>,
+    },
+    RedactedThinking {
+        data: String,
+    },
 }
 
 #[skip_serializing_none]
@@ -172,6 +222,10 @@ pub struct ResponseMessage {
     pub role: Role,
     /// The contents of the message (can be null for some cases)
     pub content: Option<String>,
+    /// Reasoning that preceded the answer, as returned by DeepSeek-R1 style models
+    pub reasoning_content: Option<String>,
+    /// Anthropic thinking behind `reasoning_content`, to send back next turn
+    pub thinking_blocks: Option<Vec<ThinkingBlock>>,
     /// The refusal message generated by the model
     pub refusal: Option<String>,
     /// Annotations for the message, when applicable, as when using the web search tool
@@ -189,6 +243,8 @@ impl Default for ResponseMessage {
         ResponseMessage {
             role: Role::Assistant,
             content: None,
+            reasoning_content: None,
+            thinking_blocks: None,
             refusal: None,
             annotations: None,
             audio: None,
@@ -211,6 +267,7 @@ impl ResponseMessage {
             name: None, // Response messages don't have names in the same way request messages do
             tool_calls: self.tool_calls.clone(),
             tool_call_id: None, // Response messages don't have tool_call_id
+            thinking_blocks: self.thinking_blocks.clone(),
         }
     }
 }
@@ -259,7 +316,7 @@ impl Display for MessageContent {
     }
 }
 
-/// Individual content part within a message (text or image)
+/// Individual content part within a message (text, image or file)
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(tag = "type")]
 pub enum ContentPart {
@@ -267,6 +324,31 @@ pub enum ContentPart {
     Text { text: String },
     #[serde(rename = "image_url")]
     ImageUrl { image_url: ImageUrl },
+    #[serde(rename = "file")]
+    File { file: FileContent },
+    #[serde(rename = "input_audio")]
+    InputAudio { input_audio: InputAudio },
+}
+
+/// Base64-encoded audio attached to a message
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct InputAudio {
+    pub data: String,
+    /// Encoding of `data`, e.g. `wav` or `mp3`
+    pub format: String,
+}
+
+/// A document attached to a message, as inline data or an uploaded file
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct FileContent {
+    /// Base64 data URL, e.g. `data:application/pdf;base64,...`
+    pub file_data: Option<String>,
+    pub file_id: Option<String>,
+    pub filename: Option<String>,
+    /// Not part of OpenAI's schema; carries documents referenced by URL to
+    /// upstreams that fetch them
+    pub file_url: Option<String>,
 }
 
 /// Image URL configuration for vision capabilities
@@ -389,6 +471,10 @@ pub struct ChatCompletionsResponse {
     pub usage: Usage,
     pub system_fingerprint: Option<String>,
     pub service_tier: Option<String>,
+    /// Source URLs returned by xAI live search and Perplexity
+    pub citations: Option<Vec<String>>,
+    /// Perplexity search results (`title`, `url`, `date`, ...)
+    pub search_results: Option<Vec<Value>>,
     // This isn't a standard OpenAI field, but we include it for extensibility
     pub metadata: Option<HashMap<String, Value>>,
 }
@@ -440,7 +526,34 @@ pub struct Choice {
     pub index: u32,
     pub message: ResponseMessage,
     pub finish_reason: Option<FinishReason>,
-    pub logprobs: Option<Value>,
+    pub logprobs: Option<ChoiceLogprobs>,
+}
+
+/// Per-token log probabilities of a choice, or of a streamed chunk's delta
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ChoiceLogprobs {
+    pub content: Option<Vec<TokenLogprob>>,
+    pub refusal: Option<Vec<TokenLogprob>>,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TokenLogprob {
+    pub token: String,
+    pub logprob: f64,
+    /// UTF-8 bytes of the token
+    pub bytes: Option<Vec<u8>>,
+    /// The most likely tokens at this position, as many as `top_logprobs` asked for
+    #[serde(default)]
+    pub top_logprobs: Vec<TopLogprob>,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TopLogprob {
+    pub token: String,
+    pub logprob: f64,
+    pub bytes: Option<Vec<u8>>,
 }
 
 // ============================================================================
@@ -460,6 +573,10 @@ pub struct ChatCompletionsStreamResponse {
     pub system_fingerprint: Option<String>,
     /// Specifies the processing type used for serving the request
     pub service_tier: Option<String>,
+    /// Source URLs, as sent by xAI and Perplexity on streamed chunks
+    pub citations: Option<Vec<String>>,
+    /// Perplexity search results
+    pub search_results: Option<Vec<Value>>,
 }
 
 /// A choice in a streaming response
@@ -469,7 +586,7 @@ pub struct StreamChoice {
     pub index: u32,
     pub delta: MessageDelta,
     pub finish_reason: Option<FinishReason>,
-    pub logprobs: Option<Value>,
+    pub logprobs: Option<ChoiceLogprobs>,
 }
 
 /// Message delta for streaming updates
@@ -478,8 +595,14 @@ pub struct StreamChoice {
 pub struct MessageDelta {
     pub role: Option<Role>,
     pub content: Option<String>,
+    /// Reasoning delta, as streamed by DeepSeek-R1 style models
+    pub reasoning_content: Option<String>,
+    /// Anthropic thinking block pieces
+    pub thinking_blocks: Option<Vec<ThinkingBlock>>,
     /// The refusal message generated by the model
     pub refusal: Option<String>,
+    /// Annotations for the content streamed so far, such as citations
+    pub annotations: Option<Vec<Value>>,
     /// Deprecated and replaced by tool_calls. The name and arguments of a function that should be called
     pub function_call: Option<FunctionCall>,
     pub tool_calls: Option<Vec<ToolCallDelta>>,
@@ -511,12 +634,94 @@ pub struct StreamOptions {
     pub include_usage: Option<bool>,
 }
 
+// ============================================================================
+// LEGACY COMPLETIONS API TYPES
+// ============================================================================
+
+/// Legacy `/v1/completions` request
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CompletionsRequest {
+    #[serde(default)]
+    pub model: String,
+    pub prompt: CompletionPrompt,
+    pub best_of: Option<u32>,
+    /// Return the prompt along with the completion
+    pub echo: Option<bool>,
+    pub frequency_penalty: Option<f32>,
+    pub logit_bias: Option<HashMap<String, i32>>,
+    /// Number of most likely tokens to return log probabilities for
+    pub logprobs: Option<u32>,
+    pub max_tokens: Option<u32>,
+    pub n: Option<u32>,
+    pub presence_penalty: Option<f32>,
+    pub seed: Option<i32>,
+    pub stop: Option<StopSequences>,
+    pub stream: Option<bool>,
+    pub stream_options: Option<StreamOptions>,
+    /// Text that comes after the completion, for insertion
+    pub suffix: Option<String>,
+    pub temperature: Option<f32>,
+    pub top_p: Option<f32>,
+    pub user: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum CompletionPrompt {
+    Text(String),
+    Texts(Vec<String>),
+    Tokens(Vec<u32>),
+    TokenArrays(Vec<Vec<u32>>),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum StopSequences {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl From<StopSequences> for Vec<String> {
+    fn from(stop: StopSequences) -> Self {
+        match stop {
+            StopSequences::One(stop) => vec![stop],
+            StopSequences::Many(stops) => stops,
+        }
+    }
+}
+
+/// Legacy `/v1/completions` response, and the shape of each streamed chunk
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CompletionsResponse {
+    pub id: String,
+    pub object: String,
+    pub created: u64,
+    pub model: String,
+    pub choices: Vec<CompletionChoice>,
+    /// Always set on whole responses; only on the final chunk of a stream
+    pub usage: Option<Usage>,
+    pub system_fingerprint: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CompletionChoice {
+    pub text: String,
+    pub index: u32,
+    pub logprobs: Option<Value>,
+    pub finish_reason: Option<FinishReason>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModelDetail {
     pub id: String,
     pub object: Option<String>,
     pub created: usize,
     pub owned_by: String,
+    /// What the provider serves the model with, when the gateway knows
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capabilities: Option<ProviderCapabilities>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -567,8 +772,6 @@ impl TryFrom<&[u8]> for ChatCompletionsRequest {
     fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
         let mut req: ChatCompletionsRequest =
             serde_json::from_slice(bytes).map_err(OpenAIStreamError::from)?;
-        // Use the centralized suppression logic
-        req.suppress_max_tokens_if_o3();
         req.fix_temperature_if_gpt5();
         Ok(req)
     }
@@ -636,6 +839,8 @@ impl ProviderRequest for ChatCompletionsRequest {
                         .map(|part| match part {
                             ContentPart::Text { text } => text.clone(),
                             ContentPart::ImageUrl { .. } => "[Image]".to_string(),
+                            ContentPart::File { .. } => "[File]".to_string(),
+                            ContentPart::InputAudio { .. } => "[Audio]".to_string(),
                         })
                         .collect::<Vec<_>>()
                         .join(" "),
@@ -757,6 +962,12 @@ impl ProviderStreamResponse for ChatCompletionsStreamResponse {
     fn event_type(&self) -> Option<&str> {
         None // OpenAI doesn't use event types in SSE
     }
+
+    fn usage(&self) -> Option<UsageDetails> {
+        self.usage
+            .as_ref()
+            .map(|u| UsageDetails::from(u as &dyn TokenUsage))
+    }
 }
 
 #[cfg(test)]
@@ -1386,4 +1597,84 @@ mod tests {
         assert_eq!(response.service_tier, None); // Should be None when not present
         assert_eq!(response.system_fingerprint, None);
     }
+
+    #[test]
+    fn test_xai_search_parameters_and_citations_round_trip() {
+        let request: ChatCompletionsRequest = serde_json::from_value(serde_json::json!({
+            "model": "grok-3",
+            "messages": [{"role": "user", "content": "What happened today?"}],
+            "search_parameters": {"mode": "auto", "return_citations": true}
+        }))
+        .unwrap();
+        let request_json = serde_json::to_value(&request).unwrap();
+        assert_eq!(
+            request_json["search_parameters"],
+            serde_json::json!({"mode": "auto", "return_citations": true})
+        );
+
+        let json_response = r#"{
+            "id": "chatcmpl-xai",
+            "object": "chat.completion",
+            "created": 1234567890,
+            "model": "grok-3",
+            "choices": [{
+                "index": 0,
+                "message": {"role": "assistant", "content": "Here is the news"},
+                "finish_reason": "stop"
+            }],
+            "usage": {"prompt_tokens": 10, "completion_tokens": 20, "total_tokens": 30},
+            "citations": ["https://x.com/i/status/1", "https://example.com/news"]
+        }"#;
+        let response: ChatCompletionsResponse = serde_json::from_str(json_response).unwrap();
+        assert_eq!(
+            response.citations.as_deref(),
+            Some(
+                &[
+                    "https://x.com/i/status/1".to_string(),
+                    "https://example.com/news".to_string()
+                ][..]
+            )
+        );
+        let response_json = serde_json::to_value(&response).unwrap();
+        assert_eq!(response_json["citations"][1], "https://example.com/news");
+    }
+
+    #[test]
+    fn test_perplexity_citations_and_search_results_preserved() {
+        let json_response = r#"{
+            "id": "pplx-123",
+            "object": "chat.completion",
+            "created": 1234567890,
+            "model": "sonar-pro",
+            "choices": [{
+                "index": 0,
+                "message": {"role": "assistant", "content": "Answer [1]"},
+                "finish_reason": "stop"
+            }],
+            "usage": {"prompt_tokens": 10, "completion_tokens": 20, "total_tokens": 30},
+            "citations": ["https://example.com/a"],
+            "search_results": [{"title": "A", "url": "https://example.com/a", "date": "2025-01-01"}]
+        }"#;
+        let response: ChatCompletionsResponse = serde_json::from_str(json_response).unwrap();
+        let response_json = serde_json::to_value(&response).unwrap();
+        assert_eq!(response_json["citations"][0], "https://example.com/a");
+        assert_eq!(response_json["search_results"][0]["title"], "A");
+
+        let json_chunk = r#"{
+            "id": "pplx-123",
+            "object": "chat.completion.chunk",
+            "created": 1234567890,
+            "model": "sonar-pro",
+            "choices": [{"index": 0, "delta": {"content": "Answer"}, "finish_reason": null}],
+            "citations": ["https://example.com/a"],
+            "search_results": [{"title": "A", "url": "https://example.com/a"}]
+        }"#;
+        let chunk: ChatCompletionsStreamResponse = serde_json::from_str(json_chunk).unwrap();
+        let chunk_json = serde_json::to_value(&chunk).unwrap();
+        assert_eq!(chunk_json["citations"][0], "https://example.com/a");
+        assert_eq!(
+            chunk_json["search_results"][0]["url"],
+            "https://example.com/a"
+        );
+    }
 }
@@ -126,13 +126,9 @@ pub enum InputParam {
 pub enum InputItem {
     /// Input message (role + content)
     Message(InputMessage),
-    /// Item reference
-    ItemReference {
-        #[serde(rename = "type")]
-        item_type: String,
-        id: String,
-    },
-    /// Function call emitted by model in prior turn
+    /// Function call emitted by model in prior turn. Listed before
+    /// `ItemReference` so an echoed call that carries its `id` still
+    /// deserializes as a call.
     FunctionCall {
         #[serde(rename = "type")]
         item_type: String,
@@ -147,6 +143,12 @@ pub enum InputItem {
         call_id: String,
         output: serde_json::Value,
     },
+    /// Item reference
+    ItemReference {
+        #[serde(rename = "type")]
+        item_type: String,
+        id: String,
+    },
 }
 
 /// Input message with role and content
@@ -180,6 +182,7 @@ pub enum MessageRole {
 }
 
 /// Input content types
+#[skip_serializing_none]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum InputContent {
@@ -191,9 +194,14 @@ pub enum InputContent {
         image_url: String,
         detail: Option<String>,
     },
-    /// File input via URL
+    /// File input as a URL, inline base64 data or an uploaded file id
     #[serde(rename = "input_file", alias = "file")]
-    InputFile { file_url: String },
+    InputFile {
+        file_url: Option<String>,
+        file_data: Option<String>,
+        file_id: Option<String>,
+        filename: Option<String>,
+    },
     /// Audio input
     InputAudio {
         data: Option<String>,
@@ -460,6 +468,49 @@ pub struct ResponsesAPIResponse {
     pub max_tool_calls: Option<i32>,
 }
 
+impl ResponsesAPIResponse {
+    /// A response that has just started generating, with default settings.
+    /// Used when a response is built from another API's output.
+    pub fn in_progress(id: String, model: String, created_at: i64) -> Self {
+        ResponsesAPIResponse {
+            id,
+            object: "response".to_string(),
+            created_at,
+            status: ResponseStatus::InProgress,
+            error: None,
+            incomplete_details: None,
+            instructions: None,
+            model,
+            output: vec![],
+            usage: None,
+            parallel_tool_calls: true,
+            conversation: None,
+            previous_response_id: None,
+            tools: vec![],
+            tool_choice: "auto".to_string(),
+            temperature: 1.0,
+            top_p: 1.0,
+            metadata: HashMap::new(),
+            truncation: Some("disabled".to_string()),
+            max_output_tokens: None,
+            reasoning: Some(Reasoning {
+                effort: None,
+                summary: None,
+            }),
+            store: Some(true),
+            text: Some(TextConfig {
+                format: Some(TextFormat::Text),
+            }),
+            audio: None,
+            modalities: None,
+            service_tier: Some("auto".to_string()),
+            background: Some(false),
+            top_logprobs: Some(0),
+            max_tool_calls: None,
+        }
+    }
+}
+
 /// Response status
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "snake_case")]
@@ -557,6 +608,8 @@ pub enum OutputItem {
     WebSearchCall {
         id: String,
         status: OutputItemStatus,
+        /// What the search did, e.g. `{"type": "search", "query": ...}`
+        action: Option<serde_json::Value>,
     },
     /// Code interpreter tool call
     CodeInterpreterCall {
@@ -655,6 +708,19 @@ pub struct LogProb {
     /// Log probability value
     pub logprob: f32,
     /// Token bytes
+    #[serde(default)]
+    pub bytes: Vec<u8>,
+    /// Most likely tokens at this position
+    #[serde(default)]
+    pub top_logprobs: Vec<TopLogProb>,
+}
+
+/// One of the most likely tokens at a position
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TopLogProb {
+    pub token: String,
+    pub logprob: f32,
+    #[serde(default)]
     pub bytes: Vec<u8>,
 }
 
@@ -676,6 +742,8 @@ pub struct FileSearchResult {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum CodeInterpreterOutput {
+    /// Console output of the run
+    Logs { logs: String },
     /// Text output
     Text { text: String },
     /// Image output
@@ -787,6 +855,20 @@ pub enum ResponsesAPIStreamEvent {
         sequence_number: i32,
     },
 
+    /// Response ended early, e.g. on the output token limit
+    #[serde(rename = "response.incomplete")]
+    ResponseIncomplete {
+        response: ResponsesAPIResponse,
+        sequence_number: i32,
+    },
+
+    /// Response failed; `response.error` says why
+    #[serde(rename = "response.failed")]
+    ResponseFailed {
+        response: ResponsesAPIResponse,
+        sequence_number: i32,
+    },
+
     /// Output item added
     #[serde(rename = "response.output_item.added")]
     ResponseOutputItemAdded {
@@ -830,7 +912,7 @@ pub enum ResponsesAPIStreamEvent {
         output_index: i32,
         content_index: i32,
         delta: String,
-        logprobs: Vec<serde_json::Value>,
+        logprobs: Vec<LogProb>,
         obfuscation: Option<String>,
         sequence_number: i32,
     },
@@ -842,7 +924,47 @@ pub enum ResponsesAPIStreamEvent {
         output_index: i32,
         content_index: i32,
         text: String,
-        logprobs: Vec<serde_json::Value>,
+        logprobs: Vec<LogProb>,
+        sequence_number: i32,
+    },
+
+    /// Reasoning summary part added
+    #[serde(rename = "response.reasoning_summary_part.added")]
+    ResponseReasoningSummaryPartAdded {
+        item_id: String,
+        output_index: i32,
+        summary_index: i32,
+        part: serde_json::Value,
+        sequence_number: i32,
+    },
+
+    /// Reasoning summary part done
+    #[serde(rename = "response.reasoning_summary_part.done")]
+    ResponseReasoningSummaryPartDone {
+        item_id: String,
+        output_index: i32,
+        summary_index: i32,
+        part: serde_json::Value,
+        sequence_number: i32,
+    },
+
+    /// Reasoning summary text delta
+    #[serde(rename = "response.reasoning_summary_text.delta")]
+    ResponseReasoningSummaryTextDelta {
+        item_id: String,
+        output_index: i32,
+        summary_index: i32,
+        delta: String,
+        sequence_number: i32,
+    },
+
+    /// Reasoning summary text done (final complete text)
+    #[serde(rename = "response.reasoning_summary_text.done")]
+    ResponseReasoningSummaryTextDone {
+        item_id: String,
+        output_index: i32,
+        summary_index: i32,
+        text: String,
         sequence_number: i32,
     },
 
@@ -972,8 +1094,13 @@ pub enum ResponsesAPIStreamEvent {
         sequence_number: i32,
     },
 
-    /// Done event (end of stream)
-    Done { sequence_number: i32 },
+    /// Done event (end of stream). Transforms from other APIs fill in why
+    /// the upstream stopped and its usage, which no other event carries.
+    Done {
+        sequence_number: i32,
+        incomplete_details: Option<IncompleteDetails>,
+        usage: Option<ResponseUsage>,
+    },
 }
 
 // ============================================================================
@@ -1282,6 +1409,8 @@ impl From<ResponsesAPIStreamEvent> for String {
             ResponsesAPIStreamEvent::ResponseCreated { .. } => "response.created",
             ResponsesAPIStreamEvent::ResponseInProgress { .. } => "response.in_progress",
             ResponsesAPIStreamEvent::ResponseCompleted { .. } => "response.completed",
+            ResponsesAPIStreamEvent::ResponseIncomplete { .. } => "response.incomplete",
+            ResponsesAPIStreamEvent::ResponseFailed { .. } => "response.failed",
             ResponsesAPIStreamEvent::ResponseOutputItemAdded { .. } => "response.output_item.added",
             ResponsesAPIStreamEvent::ResponseOutputItemDone { .. } => "response.output_item.done",
             ResponsesAPIStreamEvent::ResponseContentPartAdded { .. } => {
@@ -1290,6 +1419,18 @@ impl From<ResponsesAPIStreamEvent> for String {
             ResponsesAPIStreamEvent::ResponseContentPartDone { .. } => "response.content_part.done",
             ResponsesAPIStreamEvent::ResponseOutputTextDelta { .. } => "response.output_text.delta",
             ResponsesAPIStreamEvent::ResponseOutputTextDone { .. } => "response.output_text.done",
+            ResponsesAPIStreamEvent::ResponseReasoningSummaryPartAdded { .. } => {
+                "response.reasoning_summary_part.added"
+            }
+            ResponsesAPIStreamEvent::ResponseReasoningSummaryPartDone { .. } => {
+                "response.reasoning_summary_part.done"
+            }
+            ResponsesAPIStreamEvent::ResponseReasoningSummaryTextDelta { .. } => {
+                "response.reasoning_summary_text.delta"
+            }
+            ResponsesAPIStreamEvent::ResponseReasoningSummaryTextDone { .. } => {
+                "response.reasoning_summary_text.done"
+            }
             ResponsesAPIStreamEvent::ResponseAudioDelta { .. } => "response.audio.delta",
             ResponsesAPIStreamEvent::ResponseAudioDone { .. } => "response.audio.done",
             ResponsesAPIStreamEvent::ResponseAudioTranscriptDelta { .. } => {
@@ -1360,6 +1501,8 @@ impl crate::providers::streaming_response::ProviderStreamResponse for ResponsesA
         matches!(
             self,
             ResponsesAPIStreamEvent::ResponseCompleted { .. }
+                | ResponsesAPIStreamEvent::ResponseIncomplete { .. }
+                | ResponsesAPIStreamEvent::ResponseFailed { .. }
                 | ResponsesAPIStreamEvent::Done { .. }
         )
     }
@@ -1379,6 +1522,8 @@ impl crate::providers::streaming_response::ProviderStreamResponse for ResponsesA
             ResponsesAPIStreamEvent::ResponseCreated { .. } => "response.created",
             ResponsesAPIStreamEvent::ResponseInProgress { .. } => "response.in_progress",
             ResponsesAPIStreamEvent::ResponseCompleted { .. } => "response.completed",
+            ResponsesAPIStreamEvent::ResponseIncomplete { .. } => "response.incomplete",
+            ResponsesAPIStreamEvent::ResponseFailed { .. } => "response.failed",
             ResponsesAPIStreamEvent::ResponseOutputItemAdded { .. } => "response.output_item.added",
             ResponsesAPIStreamEvent::ResponseOutputItemDone { .. } => "response.output_item.done",
             ResponsesAPIStreamEvent::ResponseContentPartAdded { .. } => {
@@ -1387,6 +1532,18 @@ impl crate::providers::streaming_response::ProviderStreamResponse for ResponsesA
             ResponsesAPIStreamEvent::ResponseContentPartDone { .. } => "response.content_part.done",
             ResponsesAPIStreamEvent::ResponseOutputTextDelta { .. } => "response.output_text.delta",
             ResponsesAPIStreamEvent::ResponseOutputTextDone { .. } => "response.output_text.done",
+            ResponsesAPIStreamEvent::ResponseReasoningSummaryPartAdded { .. } => {
+                "response.reasoning_summary_part.added"
+            }
+            ResponsesAPIStreamEvent::ResponseReasoningSummaryPartDone { .. } => {
+                "response.reasoning_summary_part.done"
+            }
+            ResponsesAPIStreamEvent::ResponseReasoningSummaryTextDelta { .. } => {
+                "response.reasoning_summary_text.delta"
+            }
+            ResponsesAPIStreamEvent::ResponseReasoningSummaryTextDone { .. } => {
+                "response.reasoning_summary_text.done"
+            }
             ResponsesAPIStreamEvent::ResponseAudioDelta { .. } => "response.audio.delta",
             ResponsesAPIStreamEvent::ResponseAudioDone { .. } => "response.audio.done",
             ResponsesAPIStreamEvent::ResponseAudioTranscriptDelta { .. } => {
@@ -1426,6 +1583,18 @@ impl crate::providers::streaming_response::ProviderStreamResponse for ResponsesA
             ResponsesAPIStreamEvent::Done { .. } => "done",
         })
     }
+
+    fn usage(&self) -> Option<crate::providers::response::UsageDetails> {
+        use crate::providers::response::{TokenUsage, UsageDetails};
+        let usage = match self {
+            ResponsesAPIStreamEvent::ResponseCompleted { response, .. }
+            | ResponsesAPIStreamEvent::ResponseIncomplete { response, .. }
+            | ResponsesAPIStreamEvent::ResponseFailed { response, .. } => response.usage.as_ref(),
+            ResponsesAPIStreamEvent::Done { usage, .. } => usage.as_ref(),
+            _ => None,
+        };
+        usage.map(|u| UsageDetails::from(u as &dyn TokenUsage))
+    }
 }
 
 #[cfg(test)]
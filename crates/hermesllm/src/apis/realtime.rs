@@ -0,0 +1,213 @@
+//! OpenAI Realtime API over WebSocket.
+//!
+//! Events are relayed as the JSON text they arrive in, so event types and
+//! fields this module does not model pass through untouched. Only the events
+//! the gateway acts on are parsed: `session.update` from the client, whose
+//! model may be an alias, and `response.done` and `error` from the server,
+//! which carry usage and failures.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use serde_with::skip_serializing_none;
+
+use crate::clients::endpoints::join_endpoint;
+use crate::ProviderId;
+
+/// Azure OpenAI API version for realtime deployments
+pub const AZURE_REALTIME_API_VERSION: &str = "2025-04-01-preview";
+
+/// Upstream path of a realtime session, or `None` when the provider does
+/// not serve the Realtime API. For Azure OpenAI `model` is the deployment
+/// name and `api_version` overrides [`AZURE_REALTIME_API_VERSION`].
+pub fn realtime_path(
+    provider_id: &ProviderId,
+    model: &str,
+    base_url_path_prefix: Option<&str>,
+    api_version: Option<&str>,
+) -> Option<String> {
+    match provider_id {
+        ProviderId::OpenAI => Some(join_endpoint(
+            base_url_path_prefix,
+            "/v1",
+            &format!("/realtime?model={}", model),
+        )),
+        ProviderId::AzureOpenAI => Some(join_endpoint(
+            base_url_path_prefix,
+            "/openai",
+            &format!(
+                "/realtime?api-version={}&deployment={}",
+                api_version.unwrap_or(AZURE_REALTIME_API_VERSION),
+                model
+            ),
+        )),
+        _ => None,
+    }
+}
+
+// ============================================================================
+// CLIENT EVENTS
+// ============================================================================
+
+/// Pin the model of a client `session.update` event to `model`. A session's
+/// model is fixed when it connects, so an alias sent here would otherwise be
+/// rejected upstream. Returns `None` for every other event and for updates
+/// that already name `model` or none.
+pub fn pin_session_model(event: &str, model: &str) -> Option<String> {
+    let mut event: Value = serde_json::from_str(event).ok()?;
+    if event.get("type")?.as_str()? != "session.update" {
+        return None;
+    }
+    let session_model = event.get_mut("session")?.get_mut("model")?;
+    if session_model.as_str() == Some(model) {
+        return None;
+    }
+    *session_model = Value::String(model.to_string());
+    serde_json::to_string(&event).ok()
+}
+
+// ============================================================================
+// SERVER EVENTS
+// ============================================================================
+
+/// The server events the gateway acts on; everything else is `Other`.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(tag = "type")]
+pub enum RealtimeServerEvent {
+    #[serde(rename = "response.done")]
+    ResponseDone { response: RealtimeResponse },
+    #[serde(rename = "error")]
+    Error { error: RealtimeError },
+    #[serde(other)]
+    Other,
+}
+
+impl RealtimeServerEvent {
+    /// Parse a server event; events that are not JSON count as `Other`.
+    pub fn parse(event: &str) -> Self {
+        serde_json::from_str(event).unwrap_or(RealtimeServerEvent::Other)
+    }
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct RealtimeResponse {
+    pub id: Option<String>,
+    /// `completed`, `cancelled`, `failed` or `incomplete`
+    pub status: Option<String>,
+    pub usage: Option<RealtimeUsage>,
+}
+
+/// Tokens of one response. Input counts the whole conversation so far,
+/// text and audio together.
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct RealtimeUsage {
+    #[serde(default)]
+    pub total_tokens: u32,
+    #[serde(default)]
+    pub input_tokens: u32,
+    #[serde(default)]
+    pub output_tokens: u32,
+    pub input_token_details: Option<RealtimeTokenDetails>,
+    pub output_token_details: Option<RealtimeTokenDetails>,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct RealtimeTokenDetails {
+    pub cached_tokens: Option<u32>,
+    pub text_tokens: Option<u32>,
+    pub audio_tokens: Option<u32>,
+}
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct RealtimeError {
+    #[serde(rename = "type")]
+    pub error_type: Option<String>,
+    pub code: Option<String>,
+    #[serde(default)]
+    pub message: String,
+    pub param: Option<String>,
+    /// The client event that caused the error
+    pub event_id: Option<String>,
+}
+
+/// An `error` event the gateway sends itself, e.g. when the upstream
+/// connection fails.
+pub fn error_event(code: &str, message: &str) -> String {
+    serde_json::json!({
+        "type": "error",
+        "error": {"type": "server_error", "code": code, "message": message}
+    })
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_realtime_paths() {
+        assert_eq!(
+            realtime_path(&ProviderId::OpenAI, "gpt-realtime", None, None).as_deref(),
+            Some("/v1/realtime?model=gpt-realtime")
+        );
+        assert_eq!(
+            realtime_path(&ProviderId::AzureOpenAI, "voice", None, None).as_deref(),
+            Some("/openai/realtime?api-version=2025-04-01-preview&deployment=voice")
+        );
+        assert_eq!(
+            realtime_path(&ProviderId::Anthropic, "claude", None, None),
+            None
+        );
+    }
+
+    #[test]
+    fn test_realtime_events() {
+        let update = json!({
+            "type": "session.update",
+            "session": {"model": "voice-alias", "voice": "alloy"}
+        })
+        .to_string();
+        let pinned: Value =
+            serde_json::from_str(&pin_session_model(&update, "gpt-realtime").unwrap()).unwrap();
+        assert_eq!(pinned["session"]["model"], "gpt-realtime");
+        assert_eq!(pinned["session"]["voice"], "alloy");
+        assert_eq!(
+            pin_session_model(r#"{"type":"input_audio_buffer.commit"}"#, "gpt-realtime"),
+            None
+        );
+
+        let done = json!({
+            "type": "response.done",
+            "event_id": "event_1",
+            "response": {
+                "id": "resp_1",
+                "status": "completed",
+                "output": [],
+                "usage": {
+                    "total_tokens": 253,
+                    "input_tokens": 132,
+                    "output_tokens": 121,
+                    "input_token_details": {"cached_tokens": 0, "text_tokens": 119, "audio_tokens": 13},
+                    "output_token_details": {"text_tokens": 30, "audio_tokens": 91}
+                }
+            }
+        })
+        .to_string();
+        match RealtimeServerEvent::parse(&done) {
+            RealtimeServerEvent::ResponseDone { response } => {
+                let usage = response.usage.unwrap();
+                assert_eq!(usage.input_tokens, 132);
+                assert_eq!(usage.output_token_details.unwrap().audio_tokens, Some(91));
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+        assert!(matches!(
+            RealtimeServerEvent::parse(r#"{"type":"response.audio.delta","delta":"AAA="}"#),
+            RealtimeServerEvent::Other
+        ));
+    }
+}
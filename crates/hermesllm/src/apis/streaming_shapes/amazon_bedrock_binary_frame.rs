@@ -1,7 +1,13 @@
+use aws_smithy_eventstream::frame::write_message_to;
 use aws_smithy_eventstream::frame::DecodedFrame;
 use aws_smithy_eventstream::frame::MessageFrameDecoder;
+use aws_smithy_types::event_stream::{Header, HeaderValue, Message};
 use bytes::Buf;
 
+use crate::apis::amazon_bedrock::{BedrockError, ConverseStreamEvent};
+use crate::providers::streaming_response::ProviderStreamResponseType;
+use crate::transforms::response_streaming::to_bedrock_streaming::MessagesToConverseStream;
+
 /// AWS Event Stream frame decoder wrapper
 pub struct BedrockBinaryFrameDecoder<B>
 where
@@ -46,3 +52,166 @@ where
         self.buffer.has_remaining()
     }
 }
+
+/// AWS Event Stream frame encoder, for serving Bedrock's ConverseStream wire
+/// format to clients. Each event becomes one frame carrying its JSON payload
+/// and the `:message-type`, `:event-type` and `:content-type` headers, with
+/// the prelude and message CRCs computed by the event-stream writer.
+#[derive(Debug, Default)]
+pub struct BedrockBinaryFrameEncoder {
+    messages_to_converse: MessagesToConverseStream,
+}
+
+impl BedrockBinaryFrameEncoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Frames for a stream response. ConverseStream events are framed as-is
+    /// and Anthropic Messages events are converted first; other shapes have
+    /// no ConverseStream mapping.
+    pub fn encode(
+        &mut self,
+        response: &ProviderStreamResponseType,
+    ) -> Result<Vec<u8>, BedrockError> {
+        let events = match response {
+            ProviderStreamResponseType::ConverseStreamEvent(event) => vec![event.clone()],
+            ProviderStreamResponseType::MessagesStreamEvent(event) => {
+                self.messages_to_converse.convert(event.clone())
+            }
+            _ => {
+                return Err(BedrockError::Validation {
+                    message: "Stream response has no ConverseStream mapping".to_string(),
+                })
+            }
+        };
+        let mut frames = Vec::new();
+        for event in &events {
+            frames.extend(Self::encode_event(event)?);
+        }
+        Ok(frames)
+    }
+
+    /// One event-stream frame for a ConverseStream event
+    pub fn encode_event(event: &ConverseStreamEvent) -> Result<Vec<u8>, BedrockError> {
+        let payload = serde_json::to_vec(event)?;
+        let mut message = Message::new(payload)
+            .add_header(Header::new(
+                ":event-type",
+                HeaderValue::String(event.frame_event_type().into()),
+            ))
+            .add_header(Header::new(
+                ":content-type",
+                HeaderValue::String("application/json".into()),
+            ))
+            .add_header(Header::new(
+                ":message-type",
+                HeaderValue::String(event.frame_message_type().into()),
+            ));
+        if event.frame_message_type() == "exception" {
+            message = message.add_header(Header::new(
+                ":exception-type",
+                HeaderValue::String(event.frame_event_type().into()),
+            ));
+        }
+        let mut frame = Vec::new();
+        write_message_to(&message, &mut frame).map_err(|err| BedrockError::InternalServer {
+            message: format!("Failed to write event-stream frame: {}", err),
+        })?;
+        Ok(frame)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::apis::anthropic::MessagesStreamEvent;
+    use serde_json::json;
+
+    fn decode_all(wire: &[u8]) -> Vec<ConverseStreamEvent> {
+        let mut decoder = BedrockBinaryFrameDecoder::from_bytes(wire);
+        let mut events = Vec::new();
+        while let Some(frame) = decoder.decode_frame() {
+            match frame {
+                DecodedFrame::Complete(_) => {
+                    events.push(ConverseStreamEvent::try_from(&frame).unwrap())
+                }
+                DecodedFrame::Incomplete => break,
+            }
+        }
+        events
+    }
+
+    #[test]
+    fn encoded_anthropic_stream_decodes_as_converse_stream() {
+        let anthropic_events: Vec<MessagesStreamEvent> = [
+            json!({"type": "message_start", "message": {"id": "msg_1", "type": "message",
+                "role": "assistant", "content": [], "model": "claude",
+                "usage": {"input_tokens": 12, "output_tokens": 1}}}),
+            json!({"type": "content_block_start", "index": 0,
+                "content_block": {"type": "text", "text": ""}}),
+            json!({"type": "content_block_delta", "index": 0,
+                "delta": {"type": "text_delta", "text": "Hi"}}),
+            json!({"type": "content_block_stop", "index": 0}),
+            json!({"type": "message_delta", "delta": {"stop_reason": "end_turn"},
+                "usage": {"input_tokens": 0, "output_tokens": 3}}),
+            json!({"type": "message_stop"}),
+        ]
+        .into_iter()
+        .map(|event| serde_json::from_value(event).unwrap())
+        .collect();
+
+        let mut encoder = BedrockBinaryFrameEncoder::new();
+        let mut wire = Vec::new();
+        for event in anthropic_events {
+            wire.extend(
+                encoder
+                    .encode(&ProviderStreamResponseType::MessagesStreamEvent(event))
+                    .unwrap(),
+            );
+        }
+
+        let events = decode_all(&wire);
+        let types: Vec<_> = events
+            .iter()
+            .map(|event| event.frame_event_type())
+            .collect();
+        assert_eq!(
+            types,
+            [
+                "messageStart",
+                "contentBlockDelta",
+                "contentBlockStop",
+                "messageStop",
+                "metadata"
+            ]
+        );
+        match &events[4] {
+            ConverseStreamEvent::Metadata(metadata) => {
+                assert_eq!(metadata.usage.input_tokens, 12);
+                assert_eq!(metadata.usage.output_tokens, 3);
+                assert_eq!(metadata.usage.total_tokens, 15);
+            }
+            other => panic!("expected metadata, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn exceptions_are_framed_as_exception_messages() {
+        let event: ConverseStreamEvent = ConverseStreamEvent::ThrottlingException(
+            serde_json::from_value(json!({
+                "message": "slow down"
+            }))
+            .unwrap(),
+        );
+
+        let wire = BedrockBinaryFrameEncoder::encode_event(&event).unwrap();
+
+        match decode_all(&wire).as_slice() {
+            [ConverseStreamEvent::ThrottlingException(exception)] => {
+                assert_eq!(exception.message.as_deref(), Some("slow down"));
+            }
+            other => panic!("expected a throttling exception, got {:?}", other),
+        }
+    }
+}
@@ -1,7 +1,10 @@
 use crate::apis::anthropic::{
-    MessagesMessageDelta, MessagesStopReason, MessagesStreamEvent, MessagesUsage,
+    MessagesContentBlock, MessagesContentDelta, MessagesMessageDelta, MessagesStopReason,
+    MessagesStreamEvent, MessagesUsage,
 };
 use crate::apis::streaming_shapes::sse::{SseEvent, SseStreamBufferTrait};
+use crate::clients::errors::{ApiError, ErrorDialect};
+use crate::providers::response::UsageDetails;
 use crate::providers::streaming_response::ProviderStreamResponseType;
 use log::warn;
 use std::collections::HashSet;
@@ -41,8 +44,17 @@ pub struct AnthropicMessagesStreamBuffer {
     /// Track if we've seen a MessageDelta (so we need to send MessageStop at the end)
     seen_message_delta: bool,
 
+    /// Track if we opened a thinking block for upstream reasoning that is still open
+    thinking_block_open: bool,
+
+    /// Added to upstream block indices once a thinking block has taken index 0
+    block_index_offset: u32,
+
     /// Model name to use when generating message_start events
     model: Option<String>,
+
+    /// Usage reported by the stream so far
+    stream_usage: Option<UsageDetails>,
 }
 
 impl Default for AnthropicMessagesStreamBuffer {
@@ -60,7 +72,10 @@ impl AnthropicMessagesStreamBuffer {
             content_block_start_indices: HashSet::new(),
             needs_content_block_stop: false,
             seen_message_delta: false,
+            thinking_block_open: false,
+            block_index_offset: 0,
             model: None,
+            stream_usage: None,
         }
     }
 
@@ -101,6 +116,8 @@ impl AnthropicMessagesStreamBuffer {
             raw_line: sse_string.clone(),
             sse_transformed_lines: sse_string,
             provider_stream_response: Some(ProviderStreamResponseType::MessagesStreamEvent(event)),
+            id: None,
+            retry: None,
         });
         self.seen_message_delta = true;
     }
@@ -119,6 +136,8 @@ impl AnthropicMessagesStreamBuffer {
             raw_line: sse_string.clone(),
             sse_transformed_lines: sse_string,
             provider_stream_response: None,
+            id: None,
+            retry: None,
         });
         self.message_stopped = true;
         self.seen_message_delta = false;
@@ -134,14 +153,58 @@ impl AnthropicMessagesStreamBuffer {
         self.content_block_start_indices.insert(index);
     }
 
+    /// Close the thinking block opened for upstream reasoning before another
+    /// block starts; the blocks after it move up one index.
+    fn close_thinking_block(&mut self) {
+        if !self.thinking_block_open {
+            return;
+        }
+        self.buffered_events
+            .push(AnthropicMessagesStreamBuffer::create_content_block_stop_event(0));
+        self.thinking_block_open = false;
+        self.needs_content_block_stop = false;
+        self.block_index_offset = 1;
+    }
+
+    /// Re-serialize `event` with its content block index moved by `block_index_offset`
+    fn shift_block_index(&self, mut event: SseEvent) -> SseEvent {
+        if self.block_index_offset == 0 {
+            return event;
+        }
+        if let Some(ProviderStreamResponseType::MessagesStreamEvent(evt)) =
+            &mut event.provider_stream_response
+        {
+            match evt {
+                MessagesStreamEvent::ContentBlockStart { index, .. }
+                | MessagesStreamEvent::ContentBlockDelta { index, .. }
+                | MessagesStreamEvent::ContentBlockStop { index } => {
+                    *index += self.block_index_offset;
+                }
+                _ => return event,
+            }
+            event.sse_transformed_lines = evt.clone().into();
+        }
+        event
+    }
+
     /// Helper to create and format a ContentBlockStart SSE event
-    fn create_content_block_start_event() -> SseEvent {
-        let content_block_start = MessagesStreamEvent::ContentBlockStart {
-            index: 0,
-            content_block: crate::apis::anthropic::MessagesContentBlock::Text {
+    fn create_content_block_start_event(index: u32, thinking: bool) -> SseEvent {
+        let content_block = if thinking {
+            MessagesContentBlock::Thinking {
+                thinking: String::new(),
+                signature: None,
+                cache_control: None,
+            }
+        } else {
+            MessagesContentBlock::Text {
                 text: String::new(),
+                citations: None,
                 cache_control: None,
-            },
+            }
+        };
+        let content_block_start = MessagesStreamEvent::ContentBlockStart {
+            index,
+            content_block,
         };
         let sse_string: String = content_block_start.into();
 
@@ -151,6 +214,8 @@ impl AnthropicMessagesStreamBuffer {
             raw_line: sse_string.clone(),
             sse_transformed_lines: sse_string,
             provider_stream_response: None,
+            id: None,
+            retry: None,
         }
     }
 
@@ -181,12 +246,14 @@ impl AnthropicMessagesStreamBuffer {
             raw_line: sse_string.clone(),
             sse_transformed_lines: sse_string,
             provider_stream_response: None,
+            id: None,
+            retry: None,
         }
     }
 
     /// Helper to create and format a ContentBlockStop SSE event
-    fn create_content_block_stop_event() -> SseEvent {
-        let content_block_stop = MessagesStreamEvent::ContentBlockStop { index: 0 };
+    fn create_content_block_stop_event(index: u32) -> SseEvent {
+        let content_block_stop = MessagesStreamEvent::ContentBlockStop { index };
         let sse_string: String = content_block_stop.into();
 
         SseEvent {
@@ -195,6 +262,8 @@ impl AnthropicMessagesStreamBuffer {
             raw_line: sse_string.clone(),
             sse_transformed_lines: sse_string,
             provider_stream_response: None,
+            id: None,
+            retry: None,
         }
     }
 }
@@ -205,6 +274,7 @@ impl SseStreamBufferTrait for AnthropicMessagesStreamBuffer {
         if event.should_skip() {
             return;
         }
+        event.accumulate_usage(&mut self.stream_usage);
 
         // FIRST: Try to extract model name from the raw event data before transformation
         // The provider_stream_response has already been transformed to Anthropic format,
@@ -251,27 +321,48 @@ impl SseStreamBufferTrait for AnthropicMessagesStreamBuffer {
                         self.buffered_events.push(event);
                         self.message_started = true;
                     }
-                    MessagesStreamEvent::ContentBlockStart { index, .. } => {
-                        let index = *index as i32;
+                    MessagesStreamEvent::ContentBlockStart { .. } => {
                         self.ensure_message_started();
+                        self.close_thinking_block();
+                        let event = self.shift_block_index(event);
+                        let index = match &event.provider_stream_response {
+                            Some(ProviderStreamResponseType::MessagesStreamEvent(
+                                MessagesStreamEvent::ContentBlockStart { index, .. },
+                            )) => *index as i32,
+                            _ => 0,
+                        };
 
                         // Add the content_block_start event (from tool calls or other sources)
                         self.buffered_events.push(event);
                         self.set_content_block_start_sent(index);
                         self.needs_content_block_stop = true;
                     }
-                    MessagesStreamEvent::ContentBlockDelta { index, .. } => {
-                        let index = *index as i32;
+                    MessagesStreamEvent::ContentBlockDelta { delta, .. } => {
+                        let thinking = matches!(delta, MessagesContentDelta::ThinkingDelta { .. });
                         self.ensure_message_started();
+                        if !thinking {
+                            self.close_thinking_block();
+                        }
+                        let event = self.shift_block_index(event);
+                        let index = match &event.provider_stream_response {
+                            Some(ProviderStreamResponseType::MessagesStreamEvent(
+                                MessagesStreamEvent::ContentBlockDelta { index, .. },
+                            )) => *index,
+                            _ => 0,
+                        };
 
                         // Check if ContentBlockStart was sent for this index
-                        if !self.has_content_block_start_been_sent(index) {
-                            // Inject ContentBlockStart before delta
+                        if !self.has_content_block_start_been_sent(index as i32) {
+                            // Inject ContentBlockStart before delta; reasoning converted
+                            // from chat completions opens a thinking block
                             let content_block_start =
-                                AnthropicMessagesStreamBuffer::create_content_block_start_event();
+                                AnthropicMessagesStreamBuffer::create_content_block_start_event(
+                                    index, thinking,
+                                );
                             self.buffered_events.push(content_block_start);
-                            self.set_content_block_start_sent(index);
+                            self.set_content_block_start_sent(index as i32);
                             self.needs_content_block_stop = true;
+                            self.thinking_block_open = thinking;
                         }
 
                         // Content deltas are between ContentBlockStart and ContentBlockStop
@@ -286,9 +377,12 @@ impl SseStreamBufferTrait for AnthropicMessagesStreamBuffer {
                         // Inject ContentBlockStop before message_delta
                         if self.needs_content_block_stop {
                             let content_block_stop =
-                                AnthropicMessagesStreamBuffer::create_content_block_stop_event();
+                                AnthropicMessagesStreamBuffer::create_content_block_stop_event(
+                                    self.block_index_offset,
+                                );
                             self.buffered_events.push(content_block_stop);
                             self.needs_content_block_stop = false;
+                            self.thinking_block_open = false;
                         }
 
                         // Check if the last event was also a MessageDelta - if so, merge them
@@ -320,6 +414,8 @@ impl SseStreamBufferTrait for AnthropicMessagesStreamBuffer {
                         self.ensure_message_started();
                         // Clear the flag so we don't inject another one
                         self.needs_content_block_stop = false;
+                        self.thinking_block_open = false;
+                        let event = self.shift_block_index(event);
                         self.buffered_events.push(event);
                     }
                     MessagesStreamEvent::MessageStop => {
@@ -333,9 +429,12 @@ impl SseStreamBufferTrait for AnthropicMessagesStreamBuffer {
 
                         if self.needs_content_block_stop {
                             let content_block_stop =
-                                AnthropicMessagesStreamBuffer::create_content_block_stop_event();
+                                AnthropicMessagesStreamBuffer::create_content_block_stop_event(
+                                    self.block_index_offset,
+                                );
                             self.buffered_events.push(content_block_stop);
                             self.needs_content_block_stop = false;
+                            self.thinking_block_open = false;
                         }
 
                         // If no message_delta has been emitted yet (empty/filtered upstream
@@ -346,11 +445,13 @@ impl SseStreamBufferTrait for AnthropicMessagesStreamBuffer {
                             if self.content_block_start_indices.is_empty() {
                                 let content_block_start =
                                     AnthropicMessagesStreamBuffer::create_content_block_start_event(
+                                        0, false,
                                     );
                                 self.buffered_events.push(content_block_start);
                                 self.set_content_block_start_sent(0);
                                 let content_block_stop =
                                     AnthropicMessagesStreamBuffer::create_content_block_stop_event(
+                                        0,
                                     );
                                 self.buffered_events.push(content_block_stop);
                             }
@@ -404,6 +505,19 @@ impl SseStreamBufferTrait for AnthropicMessagesStreamBuffer {
         }
         buffer
     }
+
+    fn final_usage(&self) -> Option<UsageDetails> {
+        self.stream_usage
+    }
+
+    fn add_upstream_error(&mut self, error: &ApiError) {
+        // An `error` event ends an Anthropic stream; no message_stop follows
+        self.buffered_events.push(SseEvent::from_wire(
+            error.to_sse_event(ErrorDialect::Anthropic),
+        ));
+        self.message_stopped = true;
+        self.seen_message_delta = false;
+    }
 }
 
 #[cfg(test)]
@@ -867,4 +981,65 @@ data: [DONE]"#;
             "No bytes should be emitted after message_stop, got: {tail:?}"
         );
     }
+
+    #[test]
+    fn test_openai_reasoning_content_to_anthropic_thinking_block() {
+        let client_api = SupportedAPIsFromClient::AnthropicMessagesAPI(AnthropicApi::Messages);
+        let upstream_api = SupportedUpstreamAPIs::OpenAIChatCompletions(OpenAIApi::ChatCompletions);
+        let mut buffer = AnthropicMessagesStreamBuffer::new();
+
+        let raw_input = r#"data: {"id":"c1","object":"chat.completion.chunk","created":1,"model":"deepseek-reasoner","choices":[{"index":0,"delta":{"role":"assistant","reasoning_content":"Think"},"finish_reason":null}]}
+
+data: {"id":"c1","object":"chat.completion.chunk","created":1,"model":"deepseek-reasoner","choices":[{"index":0,"delta":{"reasoning_content":"ing"},"finish_reason":null}]}
+
+data: {"id":"c1","object":"chat.completion.chunk","created":1,"model":"deepseek-reasoner","choices":[{"index":0,"delta":{"content":"Answer"},"finish_reason":null}]}
+
+data: {"id":"c1","object":"chat.completion.chunk","created":1,"model":"deepseek-reasoner","choices":[{"index":0,"delta":{},"finish_reason":"stop"}]}
+
+data: [DONE]"#;
+        for raw in SseStreamIter::try_from(raw_input.as_bytes()).unwrap() {
+            let e = SseEvent::try_from((raw, &client_api, &upstream_api)).unwrap();
+            buffer.add_transformed_event(e);
+        }
+        let out = String::from_utf8(buffer.to_bytes()).unwrap();
+
+        let data: Vec<serde_json::Value> = out
+            .lines()
+            .filter_map(|line| line.strip_prefix("data: "))
+            .map(|data| serde_json::from_str(data).unwrap())
+            .collect();
+        let block_events: Vec<(String, u64, String)> = data
+            .iter()
+            .filter(|event| event["type"].as_str().unwrap().starts_with("content_block"))
+            .map(|event| {
+                let kind = event["content_block"]["type"]
+                    .as_str()
+                    .or(event["delta"]["type"].as_str())
+                    .unwrap_or_default();
+                (
+                    event["type"].as_str().unwrap().to_string(),
+                    event["index"].as_u64().unwrap(),
+                    kind.to_string(),
+                )
+            })
+            .collect();
+        let expected = [
+            ("content_block_start", 0, "thinking"),
+            ("content_block_delta", 0, "thinking_delta"),
+            ("content_block_delta", 0, "thinking_delta"),
+            ("content_block_stop", 0, ""),
+            ("content_block_start", 1, "text"),
+            ("content_block_delta", 1, "text_delta"),
+            ("content_block_stop", 1, ""),
+        ];
+        assert_eq!(
+            block_events,
+            expected
+                .iter()
+                .map(|(t, i, k)| (t.to_string(), *i, k.to_string()))
+                .collect::<Vec<_>>(),
+            "Output:\n{out}"
+        );
+        assert_eq!(out.matches("event: message_stop").count(), 1);
+    }
 }
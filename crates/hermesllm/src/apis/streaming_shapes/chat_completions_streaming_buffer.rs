@@ -1,9 +1,14 @@
 use crate::apis::streaming_shapes::sse::{SseEvent, SseStreamBufferTrait};
+use crate::clients::errors::{ApiError, ErrorDialect};
+use crate::providers::response::UsageDetails;
 
 ///  OpenAI Chat Completions SSE Stream Buffer for when client and upstream APIs match.
 pub struct OpenAIChatCompletionsStreamBuffer {
     /// Buffered SSE events ready to be written to wire
     buffered_events: Vec<SseEvent>,
+
+    /// Usage reported by the stream so far
+    stream_usage: Option<UsageDetails>,
 }
 
 impl Default for OpenAIChatCompletionsStreamBuffer {
@@ -16,6 +21,7 @@ impl OpenAIChatCompletionsStreamBuffer {
     pub fn new() -> Self {
         Self {
             buffered_events: Vec::new(),
+            stream_usage: None,
         }
     }
 }
@@ -26,6 +32,7 @@ impl SseStreamBufferTrait for OpenAIChatCompletionsStreamBuffer {
         if event.should_skip() {
             return;
         }
+        event.accumulate_usage(&mut self.stream_usage);
 
         // For OpenAI Chat Completions, events are already properly transformed
         // Just accumulate them for later wire transmission
@@ -42,4 +49,17 @@ impl SseStreamBufferTrait for OpenAIChatCompletionsStreamBuffer {
         }
         buffer
     }
+
+    fn final_usage(&self) -> Option<UsageDetails> {
+        self.stream_usage
+    }
+
+    fn add_upstream_error(&mut self, error: &ApiError) {
+        // Other upstreams end without [DONE] on error, so it's added here
+        self.buffered_events.push(SseEvent::from_wire(
+            error.to_sse_event(ErrorDialect::OpenAI),
+        ));
+        self.buffered_events
+            .push(SseEvent::from_wire("data: [DONE]\n\n".to_string()));
+    }
 }
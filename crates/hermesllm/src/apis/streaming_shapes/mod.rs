@@ -5,3 +5,5 @@ pub mod passthrough_streaming_buffer;
 pub mod responses_api_streaming_buffer;
 pub mod sse;
 pub mod sse_chunk_processor;
+pub mod structured_output;
+pub mod tool_call_arguments;
@@ -1,9 +1,21 @@
-use crate::apis::streaming_shapes::sse::{SseEvent, SseStreamBufferTrait};
+use crate::apis::streaming_shapes::sse::{SseEvent, SseStreamBufferTrait, SseStreamIter};
+use crate::clients::endpoints::{SupportedAPIsFromClient, SupportedUpstreamAPIs};
+use crate::clients::errors::ApiError;
+use crate::providers::response::UsageDetails;
 
 /// Passthrough SSE Stream Buffer for when client and upstream APIs match.
 pub struct PassthroughStreamBuffer {
     /// Buffered SSE events ready to be written to wire
     buffered_events: Vec<SseEvent>,
+
+    /// Usage reported by the stream so far
+    stream_usage: Option<UsageDetails>,
+
+    /// Tail of a chunk passed to `scan_chunk` whose event isn't complete yet
+    partial_event: Vec<u8>,
+
+    /// Whether `scan_chunk` has seen the end of the stream
+    finished: bool,
 }
 
 impl Default for PassthroughStreamBuffer {
@@ -16,8 +28,72 @@ impl PassthroughStreamBuffer {
     pub fn new() -> Self {
         Self {
             buffered_events: Vec::new(),
+            stream_usage: None,
+            partial_event: Vec::new(),
+            finished: false,
         }
     }
+
+    /// Scan a chunk that is forwarded to the client untouched. Events are
+    /// only parsed when they mention `usage`; the rest are checked for the
+    /// end of the stream by substring, and nothing is re-serialized.
+    /// Returns the number of complete events seen in the chunk.
+    pub fn scan_chunk(
+        &mut self,
+        chunk: &[u8],
+        client_api: &SupportedAPIsFromClient,
+        upstream_api: &SupportedUpstreamAPIs,
+    ) -> usize {
+        self.partial_event.extend_from_slice(chunk);
+        let Some(end) = last_event_end(&self.partial_event) else {
+            return 0;
+        };
+        let complete: Vec<u8> = self.partial_event.drain(..end).collect();
+        let Ok(events) = SseStreamIter::try_from(complete.as_slice()) else {
+            return 0;
+        };
+
+        let mut count = 0;
+        for event in events {
+            count += 1;
+            if event.is_done()
+                || matches!(
+                    event.event.as_deref(),
+                    Some("response.completed" | "response.incomplete" | "response.failed")
+                )
+            {
+                self.finished = true;
+            }
+            let Some(data) = event.data.as_deref() else {
+                continue;
+            };
+            if data.contains("\"finish_reason\":\"") {
+                self.finished = true;
+            }
+            if data.contains("\"usage\"") {
+                if let Ok(event) = SseEvent::try_from((event, client_api, upstream_api)) {
+                    event.accumulate_usage(&mut self.stream_usage);
+                }
+            }
+        }
+        count
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+}
+
+/// End of the last complete event in `bytes`, just past its blank line.
+fn last_event_end(bytes: &[u8]) -> Option<usize> {
+    (1..bytes.len())
+        .rev()
+        .find(|&i| {
+            bytes[i] == b'\n'
+                && (bytes[i - 1] == b'\n'
+                    || (i > 1 && bytes[i - 1] == b'\r' && bytes[i - 2] == b'\n'))
+        })
+        .map(|i| i + 1)
 }
 
 impl SseStreamBufferTrait for PassthroughStreamBuffer {
@@ -26,6 +102,7 @@ impl SseStreamBufferTrait for PassthroughStreamBuffer {
         if event.should_skip() {
             return;
         }
+        event.accumulate_usage(&mut self.stream_usage);
 
         // Skip events with empty transformed lines (e.g., suppressed event-only lines)
         if event.sse_transformed_lines.is_empty() {
@@ -45,6 +122,15 @@ impl SseStreamBufferTrait for PassthroughStreamBuffer {
         }
         buffer
     }
+
+    fn final_usage(&self) -> Option<UsageDetails> {
+        self.stream_usage
+    }
+
+    fn add_upstream_error(&mut self, _error: &ApiError) {
+        // The upstream speaks the client's API, so its own error event has
+        // already been passed through
+    }
 }
 
 #[cfg(test)]
@@ -102,4 +188,64 @@ mod tests {
         println!("✓ All events preserved including [DONE]");
         println!("✓ Function calling events preserved\n");
     }
+
+    #[test]
+    fn test_final_usage_combines_split_anthropic_usage() {
+        use crate::apis::anthropic::AnthropicApi;
+        use crate::apis::streaming_shapes::sse::SseEvent;
+        use crate::clients::{SupportedAPIsFromClient, SupportedUpstreamAPIs};
+
+        let raw_input = r#"event: message_start
+data: {"type":"message_start","message":{"id":"msg_01","type":"message","role":"assistant","content":[],"model":"claude-sonnet-4-5","stop_reason":null,"stop_sequence":null,"usage":{"input_tokens":25,"output_tokens":1,"cache_read_input_tokens":100}}}
+
+event: content_block_delta
+data: {"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"Hi"}}
+
+event: message_delta
+data: {"type":"message_delta","delta":{"stop_reason":"end_turn","stop_sequence":null},"usage":{"input_tokens":25,"output_tokens":40}}
+
+event: message_stop
+data: {"type":"message_stop"}"#;
+
+        let client_api = SupportedAPIsFromClient::AnthropicMessagesAPI(AnthropicApi::Messages);
+        let upstream_api = SupportedUpstreamAPIs::AnthropicMessagesAPI(AnthropicApi::Messages);
+        let mut buffer = PassthroughStreamBuffer::new();
+        assert!(buffer.final_usage().is_none());
+        for raw_event in SseStreamIter::try_from(raw_input.as_bytes()).unwrap() {
+            let event = SseEvent::try_from((raw_event, &client_api, &upstream_api)).unwrap();
+            buffer.add_transformed_event(event);
+        }
+
+        let usage = buffer.final_usage().unwrap();
+        assert_eq!(usage.prompt_tokens, 25);
+        assert_eq!(usage.completion_tokens, 40);
+        assert_eq!(usage.total_tokens, 65);
+        assert_eq!(usage.cached_input_tokens, Some(100));
+    }
+
+    #[test]
+    fn test_scan_chunk_reads_usage_across_split_chunks() {
+        use crate::apis::openai::OpenAIApi;
+        use crate::clients::{SupportedAPIsFromClient, SupportedUpstreamAPIs};
+
+        let raw_input = concat!(
+            "data: {\"id\":\"c1\",\"object\":\"chat.completion.chunk\",\"created\":1,\"model\":\"gpt-4o\",\"choices\":[{\"index\":0,\"delta\":{\"content\":\"Hi\"},\"finish_reason\":null}]}\n\n",
+            "data: {\"id\":\"c1\",\"object\":\"chat.completion.chunk\",\"created\":1,\"model\":\"gpt-4o\",\"choices\":[{\"index\":0,\"delta\":{},\"finish_reason\":\"stop\"}],",
+            "\"usage\":{\"prompt_tokens\":7,\"completion_tokens\":3,\"total_tokens\":10}}\n\n",
+            "data: [DONE]\n\n",
+        );
+        let client_api = SupportedAPIsFromClient::OpenAIChatCompletions(OpenAIApi::ChatCompletions);
+        let upstream_api = SupportedUpstreamAPIs::OpenAIChatCompletions(OpenAIApi::ChatCompletions);
+        let mut buffer = PassthroughStreamBuffer::new();
+
+        let (head, tail) = raw_input.as_bytes().split_at(250);
+        assert_eq!(buffer.scan_chunk(head, &client_api, &upstream_api), 1);
+        assert!(!buffer.is_finished());
+        assert_eq!(buffer.scan_chunk(tail, &client_api, &upstream_api), 2);
+        assert!(buffer.is_finished());
+
+        let usage = buffer.final_usage().unwrap();
+        assert_eq!(usage.prompt_tokens, 7);
+        assert_eq!(usage.completion_tokens, 3);
+    }
 }
@@ -1,35 +1,18 @@
 use crate::apis::openai_responses::{
-    OutputItem, OutputItemStatus, Reasoning, ResponseStatus, ResponsesAPIResponse,
-    ResponsesAPIStreamEvent, TextConfig, TextFormat,
+    IncompleteDetails, LogProb, OutputContent, OutputItem, OutputItemStatus, ResponseStatus,
+    ResponseUsage, ResponsesAPIResponse, ResponsesAPIStreamEvent,
 };
 use crate::apis::streaming_shapes::sse::{SseEvent, SseStreamBufferTrait};
+use crate::clients::errors::ApiError;
+use crate::providers::response::UsageDetails;
+use crate::providers::streaming_response::ProviderStreamResponse;
+use crate::transforms::response::to_openai::fill_server_tool_input;
 use log::debug;
 use std::collections::HashMap;
 
 /// Helper to convert ResponseAPIStreamEvent to SseEvent
 fn event_to_sse(event: ResponsesAPIStreamEvent) -> SseEvent {
-    let event_type = match &event {
-        ResponsesAPIStreamEvent::ResponseCreated { .. } => "response.created",
-        ResponsesAPIStreamEvent::ResponseInProgress { .. } => "response.in_progress",
-        ResponsesAPIStreamEvent::ResponseCompleted { .. } => "response.completed",
-        ResponsesAPIStreamEvent::ResponseOutputItemAdded { .. } => "response.output_item.added",
-        ResponsesAPIStreamEvent::ResponseOutputItemDone { .. } => "response.output_item.done",
-        ResponsesAPIStreamEvent::ResponseOutputTextDelta { .. } => "response.output_text.delta",
-        ResponsesAPIStreamEvent::ResponseOutputTextDone { .. } => "response.output_text.done",
-        ResponsesAPIStreamEvent::ResponseFunctionCallArgumentsDelta { .. } => {
-            "response.function_call_arguments.delta"
-        }
-        ResponsesAPIStreamEvent::ResponseFunctionCallArgumentsDone { .. } => {
-            "response.function_call_arguments.done"
-        }
-        unknown => {
-            debug!(
-                "Unknown ResponsesAPIStreamEvent type encountered: {:?}",
-                unknown
-            );
-            "unknown"
-        }
-    };
+    let event_type = event.event_type().unwrap_or("unknown").to_string();
 
     let json_data = match serde_json::to_string(&event) {
         Ok(data) => data,
@@ -42,49 +25,154 @@ fn event_to_sse(event: ResponsesAPIStreamEvent) -> SseEvent {
 
     SseEvent {
         data: Some(json_data),
-        event: Some(event_type.to_string()),
+        event: Some(event_type),
         raw_line: wire_format.clone(),
         sse_transformed_lines: wire_format,
         provider_stream_response: None,
+        id: None,
+        retry: None,
+    }
+}
+
+/// The kinds of output item a translated stream produces
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum ItemKind {
+    Reasoning,
+    Message,
+    FunctionCall,
+    /// A built-in tool call the upstream ran itself, e.g. a web search
+    BuiltInCall,
+}
+
+/// One output item and everything streamed into it so far
+struct StreamItem {
+    id: String,
+    kind: ItemKind,
+    /// Reasoning summary, message text or function call arguments
+    text: String,
+    /// Messages only: logprobs of the text streamed so far
+    logprobs: Vec<LogProb>,
+    /// Function calls only
+    call_id: String,
+    name: String,
+    /// Built-in calls only: the call as the upstream last reported it
+    built_in: Option<OutputItem>,
+    /// Set once the item's *.done events are out
+    status: Option<OutputItemStatus>,
+}
+
+impl StreamItem {
+    fn summary_part(&self) -> serde_json::Value {
+        serde_json::json!({"type": "summary_text", "text": self.text})
+    }
+
+    fn text_part(&self) -> OutputContent {
+        OutputContent::OutputText {
+            text: self.text.clone(),
+            annotations: vec![],
+            logprobs: (!self.logprobs.is_empty()).then(|| self.logprobs.clone()),
+        }
+    }
+
+    fn output_item(&self, status: OutputItemStatus) -> OutputItem {
+        match self.kind {
+            ItemKind::Reasoning => OutputItem::Reasoning {
+                id: self.id.clone(),
+                summary: if matches!(status, OutputItemStatus::InProgress) {
+                    vec![]
+                } else {
+                    vec![self.summary_part()]
+                },
+            },
+            ItemKind::Message => OutputItem::Message {
+                id: self.id.clone(),
+                content: if matches!(status, OutputItemStatus::InProgress) {
+                    vec![]
+                } else {
+                    vec![self.text_part()]
+                },
+                status,
+                role: "assistant".to_string(),
+            },
+            ItemKind::FunctionCall => OutputItem::FunctionCall {
+                id: self.id.clone(),
+                status,
+                call_id: self.call_id.clone(),
+                name: Some(self.name.clone()),
+                arguments: Some(self.text.clone()),
+            },
+            ItemKind::BuiltInCall => {
+                let mut call = self
+                    .built_in
+                    .clone()
+                    .expect("built-in call items start from the upstream call");
+                if let Ok(input) = serde_json::from_str(&self.text) {
+                    fill_server_tool_input(&mut call, &input);
+                }
+                // A call still running upstream ends with its item
+                if let OutputItem::WebSearchCall {
+                    status: call_status,
+                    ..
+                }
+                | OutputItem::CodeInterpreterCall {
+                    status: call_status,
+                    ..
+                } = &mut call
+                {
+                    if matches!(call_status, OutputItemStatus::InProgress) {
+                        *call_status = status;
+                    }
+                }
+                call
+            }
+        }
     }
 }
 
 /// SSE Stream Buffer for ResponsesAPIStreamEvent with full lifecycle management.
 ///
-/// This buffer manages the wire format for v1/responses streaming, handling
-/// delta events and emitting complete lifecycle events.
+/// This buffer manages the wire format for v1/responses streaming. Upstream
+/// deltas arrive without item ids or sequence numbers; the buffer assigns
+/// them and emits the rest of the typed event sequence around them:
 ///
+/// ```text
+/// response.created, response.in_progress
+///   output_item.added, reasoning_summary_part.added, reasoning_summary_text.delta*, ...done
+///   output_item.added, content_part.added, output_text.delta*, output_text.done, content_part.done, output_item.done
+///   output_item.added, function_call_arguments.delta*, function_call_arguments.done, output_item.done
+/// response.completed (or response.incomplete)
+/// ```
+///
+/// Items are emitted one after another: an item is done once the upstream
+/// moves on to the next one.
 pub struct ResponsesAPIStreamBuffer {
     /// Sequence number for events
     sequence_number: i32,
 
-    /// Track item IDs by output index
-    item_ids: HashMap<i32, String>,
-
     /// Response metadata
     response_id: Option<String>,
     model: Option<String>,
     created_at: Option<i64>,
 
-    /// Full response metadata from upstream (tools, temperature, etc.)
-    /// This is extracted from the first upstream event and used to build
-    /// complete response.created and response.in_progress events
+    /// Response metadata from upstream, used as the template for the
+    /// lifecycle events
     upstream_response_metadata: Option<ResponsesAPIResponse>,
 
     /// Lifecycle state flags
     created_emitted: bool,
-    in_progress_emitted: bool,
     finalized: bool,
 
-    /// Track which output items we've added
-    output_items_added: HashMap<i32, String>, // output_index -> item_id
+    /// Output items in output index order
+    items: Vec<StreamItem>,
+    /// Upstream (kind, index) -> output index
+    slots: HashMap<(ItemKind, i32), usize>,
 
-    /// Accumulated content by item_id
-    text_content: HashMap<String, String>,
-    function_arguments: HashMap<String, String>,
-
-    /// Tool call metadata by output_index
-    tool_call_metadata: HashMap<i32, (String, String)>, // output_index -> (call_id, name)
+    /// Set once the upstream signals the end of the response
+    finish_pending: bool,
+    incomplete_details: Option<IncompleteDetails>,
+    usage: Option<ResponseUsage>,
+    /// Usage reported by the stream so far
+    stream_usage: Option<UsageDetails>,
 
     /// Final completed response (for logging/tracing/persistence)
     completed_response: Option<ResponsesAPIResponse>,
@@ -103,18 +191,18 @@ impl ResponsesAPIStreamBuffer {
     pub fn new() -> Self {
         Self {
             sequence_number: 0,
-            item_ids: HashMap::new(),
             response_id: None,
             model: None,
             created_at: None,
             upstream_response_metadata: None,
             created_emitted: false,
-            in_progress_emitted: false,
             finalized: false,
-            output_items_added: HashMap::new(),
-            text_content: HashMap::new(),
-            function_arguments: HashMap::new(),
-            tool_call_metadata: HashMap::new(),
+            items: Vec::new(),
+            slots: HashMap::new(),
+            finish_pending: false,
+            incomplete_details: None,
+            usage: None,
+            stream_usage: None,
             completed_response: None,
             buffered_events: Vec::new(),
         }
@@ -134,119 +222,210 @@ impl ResponsesAPIStreamBuffer {
         )
     }
 
-    fn get_or_create_item_id(&mut self, output_index: i32, prefix: &str) -> String {
-        if let Some(id) = self.item_ids.get(&output_index) {
-            return id.clone();
-        }
-        let id = ResponsesAPIStreamBuffer::generate_item_id(prefix);
-        self.item_ids.insert(output_index, id.clone());
-        id
+    fn emit(&mut self, event: ResponsesAPIStreamEvent) {
+        self.buffered_events.push(event_to_sse(event));
     }
 
-    /// Create response.created event
-    fn create_response_created_event(&mut self) -> SseEvent {
-        let response = self.build_response(ResponseStatus::InProgress);
-        let event = ResponsesAPIStreamEvent::ResponseCreated {
-            response,
-            sequence_number: self.next_sequence_number(),
-        };
-        event_to_sse(event)
-    }
+    /// Emit response.created and response.in_progress, once
+    fn emit_prelude(&mut self) {
+        if self.created_emitted {
+            return;
+        }
+        self.created_emitted = true;
+        if self.response_id.is_none() {
+            self.response_id = Some(ResponsesAPIStreamBuffer::generate_item_id("resp"));
+            self.created_at = Some(
+                std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap()
+                    .as_secs() as i64,
+            );
+        }
 
-    /// Create response.in_progress event
-    fn create_response_in_progress_event(&mut self) -> SseEvent {
         let response = self.build_response(ResponseStatus::InProgress);
-        let event = ResponsesAPIStreamEvent::ResponseInProgress {
+        let sequence_number = self.next_sequence_number();
+        self.emit(ResponsesAPIStreamEvent::ResponseCreated {
+            response: response.clone(),
+            sequence_number,
+        });
+        let sequence_number = self.next_sequence_number();
+        self.emit(ResponsesAPIStreamEvent::ResponseInProgress {
             response,
-            sequence_number: self.next_sequence_number(),
-        };
-        event_to_sse(event)
+            sequence_number,
+        });
     }
 
-    /// Create output_item.added event for text
-    fn create_output_item_added_event(&mut self, output_index: i32, item_id: &str) -> SseEvent {
-        let event = ResponsesAPIStreamEvent::ResponseOutputItemAdded {
-            output_index,
-            item: OutputItem::Message {
-                id: item_id.to_string(),
-                status: OutputItemStatus::InProgress,
-                role: "assistant".to_string(),
-                content: vec![],
+    /// Output index of the item for an upstream (kind, index), adding the
+    /// item, and finishing the one before it, the first time it is seen
+    fn item_for(
+        &mut self,
+        kind: ItemKind,
+        upstream_index: i32,
+        call: Option<(&str, &str)>,
+        built_in: Option<OutputItem>,
+    ) -> usize {
+        if let Some(index) = self.slots.get(&(kind, upstream_index)) {
+            return *index;
+        }
+        if let Some(previous) = self.items.len().checked_sub(1) {
+            self.finish_item(previous, OutputItemStatus::Completed);
+        }
+
+        let prefix = match kind {
+            ItemKind::Reasoning => "rs",
+            ItemKind::Message => "msg",
+            ItemKind::FunctionCall => "fc",
+            ItemKind::BuiltInCall => "",
+        };
+        let (call_id, name) = call.map_or_else(
+            || {
+                (
+                    format!("call_{}", uuid::Uuid::new_v4()),
+                    "unknown".to_string(),
+                )
             },
-            sequence_number: self.next_sequence_number(),
+            |(call_id, name)| (call_id.to_string(), name.to_string()),
+        );
+        // Built-in calls keep the upstream's id
+        let id = if built_in.is_some() {
+            call_id.clone()
+        } else {
+            ResponsesAPIStreamBuffer::generate_item_id(prefix)
+        };
+        let item = StreamItem {
+            id,
+            kind,
+            text: String::new(),
+            logprobs: Vec::new(),
+            call_id,
+            name,
+            built_in,
+            status: None,
         };
-        event_to_sse(event)
+        let output_index = self.items.len();
+        let item_id = item.id.clone();
+        let added = item.output_item(OutputItemStatus::InProgress);
+        self.items.push(item);
+        self.slots.insert((kind, upstream_index), output_index);
+
+        let sequence_number = self.next_sequence_number();
+        self.emit(ResponsesAPIStreamEvent::ResponseOutputItemAdded {
+            output_index: output_index as i32,
+            item: added,
+            sequence_number,
+        });
+        match kind {
+            ItemKind::Reasoning => {
+                let sequence_number = self.next_sequence_number();
+                self.emit(ResponsesAPIStreamEvent::ResponseReasoningSummaryPartAdded {
+                    item_id,
+                    output_index: output_index as i32,
+                    summary_index: 0,
+                    part: serde_json::json!({"type": "summary_text", "text": ""}),
+                    sequence_number,
+                });
+            }
+            ItemKind::Message => {
+                let sequence_number = self.next_sequence_number();
+                self.emit(ResponsesAPIStreamEvent::ResponseContentPartAdded {
+                    item_id,
+                    output_index: output_index as i32,
+                    content_index: 0,
+                    part: OutputContent::OutputText {
+                        text: String::new(),
+                        annotations: vec![],
+                        logprobs: None,
+                    },
+                    sequence_number,
+                });
+            }
+            ItemKind::FunctionCall | ItemKind::BuiltInCall => {}
+        }
+        output_index
     }
 
-    /// Create output_item.added event for tool call
-    fn create_tool_call_added_event(
-        &mut self,
-        output_index: i32,
-        item_id: &str,
-        call_id: &str,
-        name: &str,
-    ) -> SseEvent {
-        let event = ResponsesAPIStreamEvent::ResponseOutputItemAdded {
+    /// Emit the *.done events of an item
+    fn finish_item(&mut self, output_index: usize, status: OutputItemStatus) {
+        let item = &mut self.items[output_index];
+        if item.status.is_some() {
+            return;
+        }
+        item.status = Some(status.clone());
+        let item_id = item.id.clone();
+        let text = item.text.clone();
+        let kind = item.kind;
+        let output_index = output_index as i32;
+        let mut done_events = Vec::new();
+        match kind {
+            ItemKind::Reasoning => {
+                let part = item.summary_part();
+                done_events.push(ResponsesAPIStreamEvent::ResponseReasoningSummaryTextDone {
+                    item_id: item_id.clone(),
+                    output_index,
+                    summary_index: 0,
+                    text,
+                    sequence_number: 0,
+                });
+                done_events.push(ResponsesAPIStreamEvent::ResponseReasoningSummaryPartDone {
+                    item_id,
+                    output_index,
+                    summary_index: 0,
+                    part,
+                    sequence_number: 0,
+                });
+            }
+            ItemKind::Message => {
+                let part = item.text_part();
+                done_events.push(ResponsesAPIStreamEvent::ResponseOutputTextDone {
+                    item_id: item_id.clone(),
+                    output_index,
+                    content_index: 0,
+                    text,
+                    logprobs: item.logprobs.clone(),
+                    sequence_number: 0,
+                });
+                done_events.push(ResponsesAPIStreamEvent::ResponseContentPartDone {
+                    item_id,
+                    output_index,
+                    content_index: 0,
+                    part,
+                    sequence_number: 0,
+                });
+            }
+            ItemKind::FunctionCall => {
+                done_events.push(ResponsesAPIStreamEvent::ResponseFunctionCallArgumentsDone {
+                    output_index,
+                    item_id,
+                    arguments: text,
+                    sequence_number: 0,
+                });
+            }
+            ItemKind::BuiltInCall => {}
+        }
+        done_events.push(ResponsesAPIStreamEvent::ResponseOutputItemDone {
             output_index,
-            item: OutputItem::FunctionCall {
-                id: item_id.to_string(),
-                status: OutputItemStatus::InProgress,
-                call_id: call_id.to_string(),
-                name: Some(name.to_string()),
-                arguments: Some(String::new()),
-            },
-            sequence_number: self.next_sequence_number(),
-        };
-        event_to_sse(event)
+            item: item.output_item(status),
+            sequence_number: 0,
+        });
+
+        for mut event in done_events {
+            set_sequence_number(&mut event, self.next_sequence_number());
+            self.emit(event);
+        }
     }
 
     /// Build the base response object with current state
     fn build_response(&self, status: ResponseStatus) -> ResponsesAPIResponse {
-        // If we have upstream metadata, use it as a base and update status/output
-        if let Some(upstream) = &self.upstream_response_metadata {
-            let mut response = upstream.clone();
-            response.status = status;
-            // Don't update output here - will be set in finalize()
-            return response;
-        }
-
-        // Fallback: build a minimal response from local state
-        ResponsesAPIResponse {
-            id: self.response_id.clone().unwrap_or_default(),
-            object: "response".to_string(),
-            created_at: self.created_at.unwrap_or(0),
-            status,
-            error: None,
-            incomplete_details: None,
-            instructions: None,
-            model: self.model.clone().unwrap_or_else(|| "unknown".to_string()),
-            output: vec![],
-            usage: None,
-            parallel_tool_calls: true,
-            conversation: None,
-            previous_response_id: None,
-            tools: vec![],
-            tool_choice: "auto".to_string(),
-            temperature: 1.0,
-            top_p: 1.0,
-            metadata: HashMap::new(),
-            truncation: Some("disabled".to_string()),
-            max_output_tokens: None,
-            reasoning: Some(Reasoning {
-                effort: None,
-                summary: None,
-            }),
-            store: Some(true),
-            text: Some(TextConfig {
-                format: Some(TextFormat::Text),
-            }),
-            audio: None,
-            modalities: None,
-            service_tier: Some("auto".to_string()),
-            background: Some(false),
-            top_logprobs: Some(0),
-            max_tool_calls: None,
-        }
+        let mut response = match &self.upstream_response_metadata {
+            Some(upstream) => upstream.clone(),
+            None => ResponsesAPIResponse::in_progress(
+                self.response_id.clone().unwrap_or_default(),
+                self.model.clone().unwrap_or_else(|| "unknown".to_string()),
+                self.created_at.unwrap_or(0),
+            ),
+        };
+        response.status = status;
+        response
     }
 
     /// Get the completed response after finalization (for logging/tracing/persistence)
@@ -254,8 +433,10 @@ impl ResponsesAPIStreamBuffer {
         self.completed_response.as_ref()
     }
 
-    /// Finalize the response by emitting all *.done events and response.completed.
-    /// Call this when the stream is complete (after seeing [DONE] or end_of_stream).
+    /// Finalize the response by emitting the remaining *.done events and
+    /// response.completed, or response.incomplete when the upstream stopped
+    /// early. Call this when the stream is complete (after seeing [DONE] or
+    /// end_of_stream).
     pub fn finalize(&mut self) {
         // Idempotent finalize: avoid duplicate response.completed loops.
         if self.finalized {
@@ -263,184 +444,93 @@ impl ResponsesAPIStreamBuffer {
         }
         self.finalized = true;
 
-        let mut events = Vec::new();
-
         // Ensure lifecycle prelude is emitted even if finalize is triggered
         // by finish_reason before any prior delta was processed.
-        if !self.created_emitted {
-            if self.response_id.is_none() {
-                self.response_id = Some(format!(
-                    "resp_{}",
-                    uuid::Uuid::new_v4().to_string().replace("-", "")
-                ));
-                self.created_at = Some(
-                    std::time::SystemTime::now()
-                        .duration_since(std::time::UNIX_EPOCH)
-                        .unwrap()
-                        .as_secs() as i64,
-                );
-                self.model = Some("unknown".to_string());
-            }
-            events.push(self.create_response_created_event());
-            self.created_emitted = true;
-        }
-        if !self.in_progress_emitted {
-            events.push(self.create_response_in_progress_event());
-            self.in_progress_emitted = true;
-        }
-
-        // Emit done events for all accumulated content
-
-        // Text content done events
-        let text_items: Vec<_> = self
-            .text_content
-            .iter()
-            .map(|(id, content)| (id.clone(), content.clone()))
-            .collect();
-        for (item_id, content) in text_items {
-            let output_index = self
-                .output_items_added
-                .iter()
-                .find(|(_, id)| **id == item_id)
-                .map(|(idx, _)| *idx)
-                .unwrap_or(0);
-
-            let seq1 = self.next_sequence_number();
-            let text_done_event = ResponsesAPIStreamEvent::ResponseOutputTextDone {
-                item_id: item_id.clone(),
-                output_index,
-                content_index: 0,
-                text: content.clone(),
-                logprobs: vec![],
-                sequence_number: seq1,
-            };
-            events.push(event_to_sse(text_done_event));
-
-            let seq2 = self.next_sequence_number();
-            let item_done_event = ResponsesAPIStreamEvent::ResponseOutputItemDone {
-                output_index,
-                item: OutputItem::Message {
-                    id: item_id.clone(),
-                    status: OutputItemStatus::Completed,
-                    role: "assistant".to_string(),
-                    content: vec![],
-                },
-                sequence_number: seq2,
+        self.emit_prelude();
+
+        let incomplete = self.incomplete_details.is_some();
+        if let Some(last) = self.items.len().checked_sub(1) {
+            let status = if incomplete {
+                OutputItemStatus::Incomplete
+            } else {
+                OutputItemStatus::Completed
             };
-            events.push(event_to_sse(item_done_event));
+            self.finish_item(last, status);
         }
 
-        // Function call done events
-        let func_items: Vec<_> = self
-            .function_arguments
+        let mut final_response = self.build_response(if incomplete {
+            ResponseStatus::Incomplete
+        } else {
+            ResponseStatus::Completed
+        });
+        final_response.output = self
+            .items
             .iter()
-            .map(|(id, args)| (id.clone(), args.clone()))
+            .map(|item| {
+                item.output_item(item.status.clone().unwrap_or(OutputItemStatus::Completed))
+            })
             .collect();
-        for (item_id, arguments) in func_items {
-            let output_index = self
-                .output_items_added
-                .iter()
-                .find(|(_, id)| **id == item_id)
-                .map(|(idx, _)| *idx)
-                .unwrap_or(0);
-
-            let seq1 = self.next_sequence_number();
-            let args_done_event = ResponsesAPIStreamEvent::ResponseFunctionCallArgumentsDone {
-                output_index,
-                item_id: item_id.clone(),
-                arguments: arguments.clone(),
-                sequence_number: seq1,
-            };
-            events.push(event_to_sse(args_done_event));
-
-            let (call_id, name) = self
-                .tool_call_metadata
-                .get(&output_index)
-                .cloned()
-                .unwrap_or_else(|| {
-                    (
-                        format!("call_{}", uuid::Uuid::new_v4()),
-                        "unknown".to_string(),
-                    )
-                });
-
-            let seq2 = self.next_sequence_number();
-            let item_done_event = ResponsesAPIStreamEvent::ResponseOutputItemDone {
-                output_index,
-                item: OutputItem::FunctionCall {
-                    id: item_id.clone(),
-                    status: OutputItemStatus::Completed,
-                    call_id,
-                    name: Some(name),
-                    arguments: Some(arguments.clone()),
-                },
-                sequence_number: seq2,
-            };
-            events.push(event_to_sse(item_done_event));
-        }
-
-        // Build final response
-        let mut output_items = Vec::new();
-
-        // Build complete output array by iterating through all output indices in order
-        let max_output_index = self.output_items_added.keys().max().copied().unwrap_or(-1);
-
-        for output_index in 0..=max_output_index {
-            if let Some(item_id) = self.output_items_added.get(&output_index) {
-                // Check if this is a function call
-                if let Some(arguments) = self.function_arguments.get(item_id) {
-                    let (call_id, name) = self
-                        .tool_call_metadata
-                        .get(&output_index)
-                        .cloned()
-                        .unwrap_or_else(|| {
-                            (
-                                format!("call_{}", uuid::Uuid::new_v4()),
-                                "unknown".to_string(),
-                            )
-                        });
-
-                    output_items.push(OutputItem::FunctionCall {
-                        id: item_id.clone(),
-                        status: OutputItemStatus::Completed,
-                        call_id,
-                        name: Some(name),
-                        arguments: Some(arguments.clone()),
-                    });
-                }
-                // Check if this is a text message
-                else if let Some(text) = self.text_content.get(item_id) {
-                    use crate::apis::openai_responses::OutputContent;
-                    output_items.push(OutputItem::Message {
-                        id: item_id.clone(),
-                        status: OutputItemStatus::Completed,
-                        role: "assistant".to_string(),
-                        content: vec![OutputContent::OutputText {
-                            text: text.clone(),
-                            annotations: vec![],
-                            logprobs: None,
-                        }],
-                    });
-                }
-            }
-        }
-
-        let mut final_response = self.build_response(ResponseStatus::Completed);
-        final_response.output = output_items;
+        final_response.incomplete_details = self.incomplete_details.clone();
+        final_response.usage = self.usage.clone();
 
         // Store completed response
         self.completed_response = Some(final_response.clone());
 
-        // Emit response.completed
-        let seq_final = self.next_sequence_number();
-        let completed_event = ResponsesAPIStreamEvent::ResponseCompleted {
-            response: final_response,
-            sequence_number: seq_final,
-        };
-        events.push(event_to_sse(completed_event));
+        let sequence_number = self.next_sequence_number();
+        self.emit(if incomplete {
+            ResponsesAPIStreamEvent::ResponseIncomplete {
+                response: final_response,
+                sequence_number,
+            }
+        } else {
+            ResponsesAPIStreamEvent::ResponseCompleted {
+                response: final_response,
+                sequence_number,
+            }
+        });
+    }
+}
+
+/// Id of a built-in tool call item, `None` for other items
+fn built_in_call_id(item: &OutputItem) -> Option<&str> {
+    match item {
+        OutputItem::WebSearchCall { id, .. } | OutputItem::CodeInterpreterCall { id, .. } => {
+            Some(id)
+        }
+        _ => None,
+    }
+}
 
-        // Add all finalization events to the buffer
-        self.buffered_events.extend(events);
+fn set_sequence_number(event: &mut ResponsesAPIStreamEvent, value: i32) {
+    match event {
+        ResponsesAPIStreamEvent::ResponseReasoningSummaryTextDone {
+            sequence_number, ..
+        }
+        | ResponsesAPIStreamEvent::ResponseReasoningSummaryPartDone {
+            sequence_number, ..
+        }
+        | ResponsesAPIStreamEvent::ResponseOutputTextDone {
+            sequence_number, ..
+        }
+        | ResponsesAPIStreamEvent::ResponseContentPartDone {
+            sequence_number, ..
+        }
+        | ResponsesAPIStreamEvent::ResponseFunctionCallArgumentsDone {
+            sequence_number, ..
+        }
+        | ResponsesAPIStreamEvent::ResponseOutputItemDone {
+            sequence_number, ..
+        }
+        | ResponsesAPIStreamEvent::ResponseOutputTextDelta {
+            sequence_number, ..
+        }
+        | ResponsesAPIStreamEvent::ResponseReasoningSummaryTextDelta {
+            sequence_number, ..
+        }
+        | ResponsesAPIStreamEvent::ResponseFunctionCallArgumentsDelta {
+            sequence_number, ..
+        } => *sequence_number = value,
+        _ => {}
     }
 }
 
@@ -450,6 +540,7 @@ impl SseStreamBufferTrait for ResponsesAPIStreamBuffer {
         if event.should_skip() {
             return;
         }
+        event.accumulate_usage(&mut self.stream_usage);
 
         // Handle [DONE] marker - trigger finalization
         if event.is_done() {
@@ -457,110 +548,94 @@ impl SseStreamBufferTrait for ResponsesAPIStreamBuffer {
             return;
         }
 
+        // Upstream event-only lines (e.g. Anthropic `event:` lines) carry nothing
+        if event.is_event_only() {
+            return;
+        }
+
         // Extract the ResponseAPIStreamEvent from the SseEvent's provider_stream_response
-        let provider_response = match event.provider_stream_response.as_ref() {
-            Some(response) => response,
-            None => {
-                eprintln!("Warning: Event missing provider_stream_response");
+        let stream_event = match event.provider_stream_response {
+            Some(crate::providers::streaming_response::ProviderStreamResponseType::ResponseAPIStreamEvent(evt)) => *evt,
+            Some(_) => {
+                debug!("Expected ResponseAPIStreamEvent in provider_stream_response");
                 return;
             }
-        };
-
-        // Extract ResponseAPIStreamEvent from the enum
-        let stream_event = match provider_response {
-            crate::providers::streaming_response::ProviderStreamResponseType::ResponseAPIStreamEvent(evt) => evt,
-            _ => {
-                eprintln!("Warning: Expected ResponseAPIStreamEvent in provider_stream_response");
+            None => {
+                debug!("Event missing provider_stream_response");
                 return;
             }
         };
 
-        // Explicit completion marker from transform layer.
-        if matches!(stream_event.as_ref(), ResponsesAPIStreamEvent::Done { .. }) {
-            self.finalize();
+        if self.finalized {
             return;
         }
 
-        let mut events = Vec::new();
-
-        // Capture upstream metadata from ResponseCreated or ResponseInProgress if present
-        match stream_event.as_ref() {
+        match stream_event {
+            // Which response and model the stream belongs to
             ResponsesAPIStreamEvent::ResponseCreated { response, .. }
             | ResponsesAPIStreamEvent::ResponseInProgress { response, .. } => {
                 if self.upstream_response_metadata.is_none() {
-                    // Store the full upstream response as our metadata template
-                    self.upstream_response_metadata = Some(response.clone());
-                    // Also extract basic fields
                     self.response_id = Some(response.id.clone());
                     self.model = Some(response.model.clone());
                     self.created_at = Some(response.created_at);
+                    self.upstream_response_metadata = Some(response);
                 }
-                // Don't emit these - we'll generate our own lifecycle events
-                return;
+                self.emit_prelude();
             }
-            _ => {}
-        }
-
-        // Emit lifecycle events if not yet emitted
-        if !self.created_emitted {
-            // Initialize metadata from first event if needed
-            if self.response_id.is_none() {
-                self.response_id = Some(format!(
-                    "resp_{}",
-                    uuid::Uuid::new_v4().to_string().replace("-", "")
-                ));
-                self.created_at = Some(
-                    std::time::SystemTime::now()
-                        .duration_since(std::time::UNIX_EPOCH)
-                        .unwrap()
-                        .as_secs() as i64,
-                );
-                self.model = Some("unknown".to_string()); // Will be set by caller if available
+            // Explicit completion marker from transform layer. Finalization
+            // waits for [DONE] or the end of this chunk, so usage sent in a
+            // trailing chunk still makes it into response.completed.
+            ResponsesAPIStreamEvent::Done {
+                incomplete_details,
+                usage,
+                ..
+            } => {
+                self.finish_pending = true;
+                if incomplete_details.is_some() {
+                    self.incomplete_details = incomplete_details;
+                }
+                if usage.is_some() {
+                    self.usage = usage;
+                }
+            }
+            ResponsesAPIStreamEvent::ResponseReasoningSummaryTextDelta {
+                output_index,
+                delta,
+                ..
+            } => {
+                self.emit_prelude();
+                let index = self.item_for(ItemKind::Reasoning, output_index, None, None);
+                self.items[index].text.push_str(&delta);
+                let event = ResponsesAPIStreamEvent::ResponseReasoningSummaryTextDelta {
+                    item_id: self.items[index].id.clone(),
+                    output_index: index as i32,
+                    summary_index: 0,
+                    delta,
+                    sequence_number: self.next_sequence_number(),
+                };
+                self.emit(event);
             }
-
-            events.push(self.create_response_created_event());
-            self.created_emitted = true;
-        }
-
-        if !self.in_progress_emitted {
-            events.push(self.create_response_in_progress_event());
-            self.in_progress_emitted = true;
-        }
-
-        // Process the delta event
-        match stream_event.as_ref() {
             ResponsesAPIStreamEvent::ResponseOutputTextDelta {
                 output_index,
                 delta,
+                logprobs,
+                obfuscation,
                 ..
             } => {
-                let item_id = self.get_or_create_item_id(*output_index, "msg");
-
-                // Emit output_item.added if this is the first time we see this output index
-                if !self.output_items_added.contains_key(output_index) {
-                    self.output_items_added
-                        .insert(*output_index, item_id.clone());
-                    events.push(self.create_output_item_added_event(*output_index, &item_id));
-                }
-
-                // Accumulate text content
-                self.text_content
-                    .entry(item_id.clone())
-                    .and_modify(|content| content.push_str(delta))
-                    .or_insert_with(|| delta.clone());
-
-                // Emit text delta with filled-in item_id and sequence_number
-                let mut delta_event = stream_event.as_ref().clone();
-                if let ResponsesAPIStreamEvent::ResponseOutputTextDelta {
-                    item_id: ref mut id,
-                    sequence_number: ref mut seq,
-                    ..
-                } = &mut delta_event
-                {
-                    *id = item_id;
-                    *seq = self.next_sequence_number();
-                }
-                events.push(event_to_sse(delta_event));
+                self.emit_prelude();
+                let index = self.item_for(ItemKind::Message, output_index, None, None);
+                self.items[index].text.push_str(&delta);
+                self.items[index].logprobs.extend(logprobs.iter().cloned());
+                let event = ResponsesAPIStreamEvent::ResponseOutputTextDelta {
+                    item_id: self.items[index].id.clone(),
+                    output_index: index as i32,
+                    content_index: 0,
+                    delta,
+                    logprobs,
+                    obfuscation,
+                    sequence_number: self.next_sequence_number(),
+                };
+                self.emit(event);
             }
             ResponsesAPIStreamEvent::ResponseFunctionCallArgumentsDelta {
                 output_index,
@@ -569,76 +644,68 @@ impl SseStreamBufferTrait for ResponsesAPIStreamBuffer {
                 name,
                 ..
             } => {
-                let item_id = self.get_or_create_item_id(*output_index, "fc");
-
-                // Store metadata if provided (from initial tool call event)
-                if let (Some(cid), Some(n)) = (call_id, name) {
-                    self.tool_call_metadata
-                        .insert(*output_index, (cid.clone(), n.clone()));
-                }
-
-                // Emit output_item.added if this is the first time we see this tool call
-                if !self.output_items_added.contains_key(output_index) {
-                    self.output_items_added
-                        .insert(*output_index, item_id.clone());
-
-                    // For tool calls, we need call_id and name from metadata
-                    // These should now be populated from the event itself
-                    let (call_id, name) = self
-                        .tool_call_metadata
-                        .get(output_index)
-                        .cloned()
-                        .unwrap_or_else(|| {
-                            (
-                                format!("call_{}", uuid::Uuid::new_v4()),
-                                "unknown".to_string(),
-                            )
-                        });
-
-                    events.push(self.create_tool_call_added_event(
-                        *output_index,
-                        &item_id,
-                        &call_id,
-                        &name,
-                    ));
-                }
-
-                // Accumulate function arguments
-                self.function_arguments
-                    .entry(item_id.clone())
-                    .and_modify(|args| args.push_str(delta))
-                    .or_insert_with(|| delta.clone());
-
-                // Emit function call arguments delta with filled-in item_id and sequence_number
-                let mut delta_event = stream_event.as_ref().clone();
-                if let ResponsesAPIStreamEvent::ResponseFunctionCallArgumentsDelta {
-                    item_id: ref mut id,
-                    sequence_number: ref mut seq,
-                    ..
-                } = &mut delta_event
-                {
-                    *id = item_id;
-                    *seq = self.next_sequence_number();
+                self.emit_prelude();
+                // Input of a built-in call goes into its item, not the wire
+                if let Some(&index) = self.slots.get(&(ItemKind::BuiltInCall, output_index)) {
+                    self.items[index].text.push_str(&delta);
+                    return;
                 }
-                events.push(event_to_sse(delta_event));
+                // The first delta of a call carries its id and name
+                let call = call_id.as_deref().zip(name.as_deref());
+                let index = self.item_for(ItemKind::FunctionCall, output_index, call, None);
+                self.items[index].text.push_str(&delta);
+                let event = ResponsesAPIStreamEvent::ResponseFunctionCallArgumentsDelta {
+                    output_index: index as i32,
+                    item_id: self.items[index].id.clone(),
+                    delta,
+                    sequence_number: self.next_sequence_number(),
+                    call_id,
+                    name,
+                };
+                self.emit(event);
             }
-            _ => {
-                // For other event types, just pass through with sequence number
-                let other_event = stream_event.as_ref().clone();
-                // TODO: Add sequence number to other event types if needed
-                events.push(event_to_sse(other_event));
+            // Built-in calls: added when the upstream starts one, done with
+            // its result
+            ResponsesAPIStreamEvent::ResponseOutputItemAdded {
+                output_index, item, ..
+            } if built_in_call_id(&item).is_some() => {
+                self.emit_prelude();
+                let id = built_in_call_id(&item).unwrap_or_default().to_string();
+                self.item_for(
+                    ItemKind::BuiltInCall,
+                    output_index,
+                    Some((&id, "")),
+                    Some(item),
+                );
+            }
+            ResponsesAPIStreamEvent::ResponseOutputItemDone { item, .. }
+                if built_in_call_id(&item).is_some() =>
+            {
+                let id = built_in_call_id(&item);
+                let Some(index) = self.items.iter().position(|existing| {
+                    existing.kind == ItemKind::BuiltInCall && Some(existing.id.as_str()) == id
+                }) else {
+                    debug!("Result for unknown built-in call {:?}", id);
+                    return;
+                };
+                self.items[index].built_in = Some(item);
+                self.finish_item(index, OutputItemStatus::Completed);
+            }
+            other => {
+                // For other event types, just pass through
+                self.emit_prelude();
+                self.emit(other);
             }
         }
-
-        // Store all generated events in the buffer
-        self.buffered_events.extend(events);
     }
 
     fn to_bytes(&mut self) -> Vec<u8> {
-        // For Responses API, we need special handling:
-        // - Most events are already in buffered_events from add_transformed_event
-        // - We should NOT finalize here - finalization happens when we detect [DONE] or end of stream
-        // - Just flush the accumulated events and clear the buffer
+        // Finalization normally happens on [DONE]; an upstream that signalled
+        // the end of the response without one is finalized once its chunk
+        // has been processed
+        if self.finish_pending {
+            self.finalize();
+        }
 
         // Convert all accumulated events to bytes and clear buffer
         let mut buffer = Vec::new();
@@ -648,6 +715,39 @@ impl SseStreamBufferTrait for ResponsesAPIStreamBuffer {
         }
         buffer
     }
+
+    fn final_usage(&self) -> Option<UsageDetails> {
+        self.stream_usage
+    }
+
+    fn add_upstream_error(&mut self, error: &ApiError) {
+        if self.finalized {
+            return;
+        }
+        self.finalized = true;
+        self.emit_prelude();
+        if let Some(last) = self.items.len().checked_sub(1) {
+            self.finish_item(last, OutputItemStatus::Incomplete);
+        }
+
+        let mut failed_response = self.build_response(ResponseStatus::Failed);
+        failed_response.output = self
+            .items
+            .iter()
+            .map(|item| {
+                item.output_item(item.status.clone().unwrap_or(OutputItemStatus::Incomplete))
+            })
+            .collect();
+        failed_response.error = Some(error.response_error());
+        failed_response.usage = self.usage.clone();
+        self.completed_response = Some(failed_response.clone());
+
+        let sequence_number = self.next_sequence_number();
+        self.emit(ResponsesAPIStreamEvent::ResponseFailed {
+            response: failed_response,
+            sequence_number,
+        });
+    }
 }
 
 #[cfg(test)]
@@ -853,4 +953,219 @@ data: {"id":"chatcmpl-123","object":"chat.completion.chunk","created":1234567890
             "response.completed should be emitted exactly once"
         );
     }
+
+    #[test]
+    fn test_reasoning_content_becomes_reasoning_item() {
+        let raw_input = r#"data: {"id":"chatcmpl-123","object":"chat.completion.chunk","created":1234567890,"model":"deepseek-reasoner","choices":[{"index":0,"delta":{"role":"assistant","reasoning_content":"Let me think"},"finish_reason":null}]}
+
+data: {"id":"chatcmpl-123","object":"chat.completion.chunk","created":1234567890,"model":"deepseek-reasoner","choices":[{"index":0,"delta":{"content":"Hello"},"finish_reason":null}]}
+
+data: {"id":"chatcmpl-123","object":"chat.completion.chunk","created":1234567890,"model":"deepseek-reasoner","choices":[{"index":0,"delta":{},"finish_reason":"stop"}]}
+
+data: [DONE]"#;
+
+        let client_api = SupportedAPIsFromClient::OpenAIResponsesAPI(OpenAIApi::Responses);
+        let upstream_api = SupportedUpstreamAPIs::OpenAIChatCompletions(OpenAIApi::ChatCompletions);
+
+        let stream_iter = SseStreamIter::try_from(raw_input.as_bytes()).unwrap();
+        let mut buffer = ResponsesAPIStreamBuffer::new();
+
+        for raw_event in stream_iter {
+            let transformed_event =
+                SseEvent::try_from((raw_event, &client_api, &upstream_api)).unwrap();
+            buffer.add_transformed_event(transformed_event);
+        }
+
+        let output = String::from_utf8_lossy(&buffer.to_bytes()).to_string();
+        assert!(output.contains("event: response.reasoning_summary_text.delta"));
+        assert!(output.contains("event: response.reasoning_summary_text.done"));
+        assert!(output.contains(r#""type":"response.output_text.delta","item_id""#));
+
+        let completed = buffer.get_completed_response().unwrap();
+        assert_eq!(completed.output.len(), 2);
+        match &completed.output[0] {
+            OutputItem::Reasoning { id, summary } => {
+                assert!(id.starts_with("rs_"));
+                assert_eq!(summary[0]["text"], "Let me think");
+            }
+            other => panic!("Expected reasoning item first, got {:?}", other),
+        }
+        assert!(matches!(completed.output[1], OutputItem::Message { .. }));
+    }
+
+    #[test]
+    fn test_anthropic_stream_to_responses_event_sequence() {
+        let raw_input = r#"event: message_start
+data: {"type":"message_start","message":{"id":"msg_01","type":"message","role":"assistant","content":[],"model":"claude-sonnet-4-5","stop_reason":null,"stop_sequence":null,"usage":{"input_tokens":25,"output_tokens":1}}}
+
+event: content_block_start
+data: {"type":"content_block_start","index":0,"content_block":{"type":"thinking","thinking":""}}
+
+event: content_block_delta
+data: {"type":"content_block_delta","index":0,"delta":{"type":"thinking_delta","thinking":"Weather lookup"}}
+
+event: content_block_stop
+data: {"type":"content_block_stop","index":0}
+
+event: content_block_start
+data: {"type":"content_block_start","index":1,"content_block":{"type":"text","text":""}}
+
+event: content_block_delta
+data: {"type":"content_block_delta","index":1,"delta":{"type":"text_delta","text":"Checking"}}
+
+event: content_block_start
+data: {"type":"content_block_start","index":2,"content_block":{"type":"tool_use","id":"toolu_01","name":"get_weather","input":{}}}
+
+event: content_block_delta
+data: {"type":"content_block_delta","index":2,"delta":{"type":"input_json_delta","partial_json":"{\"city\":\"Paris\"}"}}
+
+event: message_delta
+data: {"type":"message_delta","delta":{"stop_reason":"max_tokens","stop_sequence":null},"usage":{"input_tokens":25,"output_tokens":40}}
+
+event: message_stop
+data: {"type":"message_stop"}"#;
+
+        let client_api = SupportedAPIsFromClient::OpenAIResponsesAPI(OpenAIApi::Responses);
+        let upstream_api = SupportedUpstreamAPIs::AnthropicMessagesAPI(
+            crate::apis::anthropic::AnthropicApi::Messages,
+        );
+
+        let mut buffer = ResponsesAPIStreamBuffer::new();
+        for raw_event in SseStreamIter::try_from(raw_input.as_bytes()).unwrap() {
+            if let Ok(transformed) = SseEvent::try_from((raw_event, &client_api, &upstream_api)) {
+                buffer.add_transformed_event(transformed);
+            }
+        }
+        let output = String::from_utf8_lossy(&buffer.to_bytes()).to_string();
+
+        let events: Vec<&str> = output
+            .lines()
+            .filter_map(|line| line.strip_prefix("event: "))
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                "response.created",
+                "response.in_progress",
+                "response.output_item.added",
+                "response.reasoning_summary_part.added",
+                "response.reasoning_summary_text.delta",
+                "response.reasoning_summary_text.done",
+                "response.reasoning_summary_part.done",
+                "response.output_item.done",
+                "response.output_item.added",
+                "response.content_part.added",
+                "response.output_text.delta",
+                "response.output_text.done",
+                "response.content_part.done",
+                "response.output_item.done",
+                "response.output_item.added",
+                "response.function_call_arguments.delta",
+                "response.function_call_arguments.delta",
+                "response.function_call_arguments.done",
+                "response.output_item.done",
+                "response.incomplete",
+            ]
+        );
+        let sequence_numbers: Vec<i64> = output
+            .lines()
+            .filter_map(|line| line.strip_prefix("data: "))
+            .map(|data| serde_json::from_str::<serde_json::Value>(data).unwrap())
+            .map(|event| event["sequence_number"].as_i64().unwrap())
+            .collect();
+        assert_eq!(
+            sequence_numbers,
+            (0..events.len() as i64).collect::<Vec<_>>()
+        );
+
+        let response = buffer.get_completed_response().unwrap();
+        assert_eq!(response.model, "claude-sonnet-4-5");
+        assert_eq!(response.status, ResponseStatus::Incomplete);
+        assert_eq!(response.usage.as_ref().unwrap().output_tokens, 40);
+        assert_eq!(response.output.len(), 3);
+        match &response.output[2] {
+            OutputItem::FunctionCall {
+                call_id, arguments, ..
+            } => {
+                assert_eq!(call_id, "toolu_01");
+                assert_eq!(arguments.as_deref(), Some(r#"{"city":"Paris"}"#));
+            }
+            other => panic!("Expected function call last, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_anthropic_server_tool_streams_as_built_in_call() {
+        let raw_input = r#"event: message_start
+data: {"type":"message_start","message":{"id":"msg_02","type":"message","role":"assistant","content":[],"model":"claude-sonnet-4-5","stop_reason":null,"stop_sequence":null,"usage":{"input_tokens":25,"output_tokens":1}}}
+
+event: content_block_start
+data: {"type":"content_block_start","index":0,"content_block":{"type":"server_tool_use","id":"srvtoolu_01","name":"web_search","input":{}}}
+
+event: content_block_delta
+data: {"type":"content_block_delta","index":0,"delta":{"type":"input_json_delta","partial_json":"{\"query\":\"rust news\"}"}}
+
+event: content_block_stop
+data: {"type":"content_block_stop","index":0}
+
+event: content_block_start
+data: {"type":"content_block_start","index":1,"content_block":{"type":"web_search_tool_result","tool_use_id":"srvtoolu_01","content":[{"type":"web_search_result","url":"https://blog.rust-lang.org","title":"Rust Blog","encrypted_content":"abc"}]}}
+
+event: content_block_start
+data: {"type":"content_block_start","index":2,"content_block":{"type":"text","text":""}}
+
+event: content_block_delta
+data: {"type":"content_block_delta","index":2,"delta":{"type":"text_delta","text":"Found it."}}
+
+event: message_delta
+data: {"type":"message_delta","delta":{"stop_reason":"end_turn","stop_sequence":null},"usage":{"input_tokens":25,"output_tokens":12}}
+
+event: message_stop
+data: {"type":"message_stop"}"#;
+
+        let client_api = SupportedAPIsFromClient::OpenAIResponsesAPI(OpenAIApi::Responses);
+        let upstream_api = SupportedUpstreamAPIs::AnthropicMessagesAPI(
+            crate::apis::anthropic::AnthropicApi::Messages,
+        );
+
+        let mut buffer = ResponsesAPIStreamBuffer::new();
+        for raw_event in SseStreamIter::try_from(raw_input.as_bytes()).unwrap() {
+            if let Ok(transformed) = SseEvent::try_from((raw_event, &client_api, &upstream_api)) {
+                buffer.add_transformed_event(transformed);
+            }
+        }
+        let output = String::from_utf8_lossy(&buffer.to_bytes()).to_string();
+
+        let events: Vec<&str> = output
+            .lines()
+            .filter_map(|line| line.strip_prefix("event: "))
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                "response.created",
+                "response.in_progress",
+                "response.output_item.added",
+                "response.output_item.done",
+                "response.output_item.added",
+                "response.content_part.added",
+                "response.output_text.delta",
+                "response.output_text.done",
+                "response.content_part.done",
+                "response.output_item.done",
+                "response.completed",
+            ]
+        );
+
+        let response = buffer.get_completed_response().unwrap();
+        assert_eq!(response.output.len(), 2);
+        match &response.output[0] {
+            OutputItem::WebSearchCall { id, status, action } => {
+                assert_eq!(id, "srvtoolu_01");
+                assert!(matches!(status, OutputItemStatus::Completed));
+                assert_eq!(action.as_ref().unwrap()["query"], "rust news");
+            }
+            other => panic!("Expected web search call first, got {:?}", other),
+        }
+    }
 }
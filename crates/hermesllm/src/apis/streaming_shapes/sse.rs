@@ -2,12 +2,19 @@ use crate::apis::streaming_shapes::anthropic_streaming_buffer::AnthropicMessages
 use crate::apis::streaming_shapes::chat_completions_streaming_buffer::OpenAIChatCompletionsStreamBuffer;
 use crate::apis::streaming_shapes::passthrough_streaming_buffer::PassthroughStreamBuffer;
 use crate::apis::streaming_shapes::responses_api_streaming_buffer::ResponsesAPIStreamBuffer;
+use crate::clients::errors::ApiError;
+use crate::providers::response::UsageDetails;
 use crate::providers::streaming_response::ProviderStreamResponse;
 use crate::providers::streaming_response::ProviderStreamResponseType;
+use bytes::Bytes;
+use futures_core::Stream;
 use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 use std::error::Error;
 use std::fmt;
+use std::pin::Pin;
 use std::str::FromStr;
+use std::task::{Context, Poll};
 
 /// Trait defining the interface for SSE stream buffers.
 ///
@@ -38,6 +45,15 @@ pub trait SseStreamBufferTrait: Send + Sync {
     /// # Returns
     /// Bytes ready for wire transmission (may be empty if no events were accumulated)
     fn to_bytes(&mut self) -> Vec<u8>;
+
+    /// Token usage of the stream so far, combined from every usage report
+    /// among the added events. Complete once the stream has ended; `None` if
+    /// the stream reported no usage.
+    fn final_usage(&self) -> Option<UsageDetails>;
+
+    /// End the stream with an error the upstream reported mid-stream, in the
+    /// client API's shape. Nothing should be added after it.
+    fn add_upstream_error(&mut self, error: &ApiError);
 }
 
 /// Unified SSE Stream Buffer enum that provides a zero-cost abstraction
@@ -66,6 +82,24 @@ impl SseStreamBufferTrait for SseStreamBuffer {
             Self::OpenAIResponses(buffer) => buffer.to_bytes(),
         }
     }
+
+    fn final_usage(&self) -> Option<UsageDetails> {
+        match self {
+            Self::Passthrough(buffer) => buffer.final_usage(),
+            Self::OpenAIChatCompletions(buffer) => buffer.final_usage(),
+            Self::AnthropicMessages(buffer) => buffer.final_usage(),
+            Self::OpenAIResponses(buffer) => buffer.final_usage(),
+        }
+    }
+
+    fn add_upstream_error(&mut self, error: &ApiError) {
+        match self {
+            Self::Passthrough(buffer) => buffer.add_upstream_error(error),
+            Self::OpenAIChatCompletions(buffer) => buffer.add_upstream_error(error),
+            Self::AnthropicMessages(buffer) => buffer.add_upstream_error(error),
+            Self::OpenAIResponses(buffer) => buffer.add_upstream_error(error),
+        }
+    }
 }
 
 // ============================================================================
@@ -81,6 +115,12 @@ pub struct SseEvent {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub event: Option<String>, // Optional event type (e.g., "message_start", "content_block_delta")
 
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>, // Optional event id, echoed back by clients as Last-Event-ID
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry: Option<u64>, // Optional reconnection delay in milliseconds
+
     #[serde(skip_serializing, skip_deserializing)]
     pub raw_line: String, // The complete line as received including "data: " prefix and "\n\n"
 
@@ -101,12 +141,27 @@ impl SseEvent {
         SseEvent {
             data: None,  // Data is embedded in sse_transformed_lines
             event: None, // Event type is embedded in sse_transformed_lines
+            id: None,
+            retry: None,
             raw_line: sse_string.clone(),
             sse_transformed_lines: sse_string,
             provider_stream_response: Some(response),
         }
     }
 
+    /// An event generated by the gateway from its complete wire form.
+    pub fn from_wire(lines: String) -> Self {
+        SseEvent {
+            data: None,
+            event: None,
+            id: None,
+            retry: None,
+            raw_line: lines.clone(),
+            sse_transformed_lines: lines,
+            provider_stream_response: None,
+        }
+    }
+
     /// Check if this event represents the end of the stream
     pub fn is_done(&self) -> bool {
         self.data == Some("[DONE]".into()) || self.event == Some("message_stop".into())
@@ -116,7 +171,7 @@ impl SseEvent {
     /// This includes ping messages and other provider-specific events that don't contain content
     pub fn should_skip(&self) -> bool {
         // Skip ping messages (commonly used by providers for connection keep-alive)
-        self.data == Some(r#"{"type": "ping"}"#.into())
+        self.data == Some(r#"{"type": "ping"}"#.into()) || self.event.as_deref() == Some("ping")
     }
 
     /// Check if this is an event-only SSE event (no data payload)
@@ -124,6 +179,45 @@ impl SseEvent {
         self.event.is_some() && self.data.is_none()
     }
 
+    /// Fold a later field line of the same event into this one. `data` lines
+    /// are joined with newlines; `event`, `id` and `retry` take the last value.
+    fn merge_field(&mut self, field: SseEvent) {
+        if let Some(data) = field.data {
+            match &mut self.data {
+                Some(existing) => {
+                    existing.push('\n');
+                    existing.push_str(&data);
+                }
+                None => self.data = Some(data),
+            }
+        }
+        if field.event.is_some() {
+            self.event = field.event;
+        }
+        if field.id.is_some() {
+            self.id = field.id;
+        }
+        if field.retry.is_some() {
+            self.retry = field.retry;
+        }
+        self.raw_line.push('\n');
+        self.raw_line.push_str(&field.raw_line);
+        self.sse_transformed_lines.push('\n');
+        self.sse_transformed_lines
+            .push_str(&field.sse_transformed_lines);
+    }
+
+    /// Add the usage this event reports, if any, to a stream's running usage
+    pub fn accumulate_usage(&self, stream_usage: &mut Option<UsageDetails>) {
+        if let Some(usage) = self
+            .provider_stream_response
+            .as_ref()
+            .and_then(|resp| resp.usage())
+        {
+            stream_usage.get_or_insert_default().accumulate(&usage);
+        }
+    }
+
     /// Get the parsed provider response if available
     pub fn provider_response(&self) -> Result<&dyn ProviderStreamResponse, std::io::Error> {
         self.provider_stream_response
@@ -138,6 +232,9 @@ impl SseEvent {
 impl FromStr for SseEvent {
     type Err = SseParseError;
 
+    /// Parse a single SSE field line (`data:`, `event:`, `id:` or `retry:`).
+    /// Complete events spanning several lines are assembled by [`SseStreamIter`]
+    /// and [`SseEventStream`].
     fn from_str(line: &str) -> Result<Self, Self::Err> {
         // Trim leading/trailing whitespace for parsing
         let trimmed_line = line.trim();
@@ -149,44 +246,53 @@ impl FromStr for SseEvent {
             });
         }
 
-        if let Some(stripped) = trimmed_line.strip_prefix("data: ") {
-            let data: String = stripped.to_string();
-            if data.trim().is_empty() {
-                return Err(SseParseError {
-                    message: "Empty data field after 'data: ' prefix".to_string(),
-                });
+        let mut event = SseEvent {
+            data: None,
+            event: None,
+            id: None,
+            retry: None,
+            raw_line: line.to_string(),
+            // Preserve original line format for passthrough, use trimmed for transformations
+            sse_transformed_lines: line.to_string(),
+            provider_stream_response: None,
+        };
+
+        // A single space after the colon is part of the separator, not the value
+        let (field, value) = trimmed_line.split_once(':').unwrap_or((trimmed_line, ""));
+        let value = value.strip_prefix(' ').unwrap_or(value);
+        match field {
+            "data" => {
+                if value.trim().is_empty() {
+                    return Err(SseParseError {
+                        message: "Empty data field after 'data: ' prefix".to_string(),
+                    });
+                }
+                event.data = Some(value.to_string());
             }
-            Ok(SseEvent {
-                data: Some(data),
-                event: None,
-                raw_line: line.to_string(),
-                // Preserve original line format for passthrough, use trimmed for transformations
-                sse_transformed_lines: line.to_string(),
-                provider_stream_response: None,
-            })
-        } else if let Some(stripped) = trimmed_line.strip_prefix("event: ") {
-            let event_type = stripped.to_string();
-            if event_type.is_empty() {
+            "event" => {
+                if value.is_empty() {
+                    return Err(SseParseError {
+                        message: "Empty event field is not a valid SSE event".to_string(),
+                    });
+                }
+                event.event = Some(value.to_string());
+            }
+            "id" => event.id = Some(value.to_string()),
+            "retry" => {
+                event.retry = Some(value.parse().map_err(|_| SseParseError {
+                    message: format!("Invalid retry value: {}", value),
+                })?);
+            }
+            _ => {
                 return Err(SseParseError {
-                    message: "Empty event field is not a valid SSE event".to_string(),
-                });
+                    message: format!(
+                        "Line does not start with 'data:', 'event:', 'id:' or 'retry:': {}",
+                        trimmed_line
+                    ),
+                })
             }
-            Ok(SseEvent {
-                data: None,
-                event: Some(event_type),
-                raw_line: line.to_string(),
-                // Preserve original line format for passthrough, use trimmed for transformations
-                sse_transformed_lines: line.to_string(),
-                provider_stream_response: None,
-            })
-        } else {
-            Err(SseParseError {
-                message: format!(
-                    "Line does not start with 'data: ' or 'event: ': {}",
-                    trimmed_line
-                ),
-            })
         }
+        Ok(event)
     }
 }
 
@@ -224,6 +330,38 @@ impl fmt::Display for SseParseError {
 
 impl Error for SseParseError {}
 
+/// Folds SSE lines into complete events. Field lines accumulate until a blank
+/// line ends the event; comments and unknown fields are ignored.
+#[derive(Default)]
+struct SseEventAssembler {
+    current: Option<SseEvent>,
+}
+
+impl SseEventAssembler {
+    /// Feed one line, returning the event it completes, if any.
+    fn push_line(&mut self, line: &str) -> Option<SseEvent> {
+        if line.trim().is_empty() {
+            return self.finish();
+        }
+        let field = line.parse::<SseEvent>().ok()?;
+        match &mut self.current {
+            Some(event) => event.merge_field(field),
+            None => self.current = Some(field),
+        }
+        None
+    }
+
+    /// Flush an event whose terminating blank line never arrived.
+    fn finish(&mut self) -> Option<SseEvent> {
+        self.current.take().filter(|event| !is_filtered(event))
+    }
+}
+
+/// Data events that should be filtered at the transport layer
+fn is_filtered(event: &SseEvent) -> bool {
+    event.data.is_some() && !event.is_done() && event.should_skip()
+}
+
 /// Generic SSE (Server-Sent Events) streaming iterator container
 /// Parses raw SSE lines into SseEvent objects
 pub struct SseStreamIter<I>
@@ -233,6 +371,7 @@ where
 {
     pub lines: I,
     pub done_seen: bool,
+    assembler: SseEventAssembler,
 }
 
 impl<I> SseStreamIter<I>
@@ -244,6 +383,7 @@ where
         Self {
             lines,
             done_seen: false,
+            assembler: SseEventAssembler::default(),
         }
     }
 }
@@ -274,23 +414,160 @@ where
             return None;
         }
 
-        for line in &mut self.lines {
-            let line_str = line.as_ref();
+        let event = loop {
+            match self.lines.next() {
+                Some(line) => {
+                    if let Some(event) = self.assembler.push_line(line.as_ref()) {
+                        break event;
+                    }
+                }
+                None => break self.assembler.finish()?,
+            }
+        };
+        // Return [DONE] for transformation, then terminate the stream
+        if event.data.is_some() && event.is_done() {
+            self.done_seen = true;
+        }
+        Some(event)
+    }
+}
+
+/// Async counterpart of [`SseStreamIter`] that parses events straight off a
+/// byte stream. Lines split across chunks are buffered until they complete,
+/// so callers never need the whole body in one contiguous buffer.
+pub struct SseEventStream<S> {
+    inner: S,
+    partial_line: Vec<u8>,
+    assembler: SseEventAssembler,
+    pending: VecDeque<SseEvent>,
+    inner_done: bool,
+    done_seen: bool,
+}
+
+impl<S> SseEventStream<S>
+where
+    S: Stream<Item = Bytes> + Unpin,
+{
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            partial_line: Vec::new(),
+            assembler: SseEventAssembler::default(),
+            pending: VecDeque::new(),
+            inner_done: false,
+            done_seen: false,
+        }
+    }
+
+    fn push_line(&mut self, line: &[u8]) {
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        if let Some(event) = self.assembler.push_line(&String::from_utf8_lossy(line)) {
+            self.pending.push_back(event);
+        }
+    }
+
+    fn push_chunk(&mut self, chunk: &[u8]) {
+        let mut rest = chunk;
+        while let Some(newline) = rest.iter().position(|&b| b == b'\n') {
+            if self.partial_line.is_empty() {
+                self.push_line(&rest[..newline]);
+            } else {
+                let mut line = std::mem::take(&mut self.partial_line);
+                line.extend_from_slice(&rest[..newline]);
+                self.push_line(&line);
+            }
+            rest = &rest[newline + 1..];
+        }
+        self.partial_line.extend_from_slice(rest);
+    }
+}
 
-            // Try to parse as either data: or event: line
-            if let Ok(event) = line_str.parse::<SseEvent>() {
-                // For data: lines, check if this is the [DONE] marker
+impl<S> Stream for SseEventStream<S>
+where
+    S: Stream<Item = Bytes> + Unpin,
+{
+    type Item = SseEvent;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            if self.done_seen {
+                return Poll::Ready(None);
+            }
+            if let Some(event) = self.pending.pop_front() {
+                // Return [DONE] for transformation, then terminate the stream
                 if event.data.is_some() && event.is_done() {
                     self.done_seen = true;
-                    return Some(event); // Return [DONE] event for transformation
                 }
-                // For data: lines, skip events that should be filtered at the transport layer
-                if event.data.is_some() && event.should_skip() {
-                    continue;
+                return Poll::Ready(Some(event));
+            }
+            if self.inner_done {
+                return Poll::Ready(None);
+            }
+            match Pin::new(&mut self.inner).poll_next(cx) {
+                Poll::Ready(Some(chunk)) => self.push_chunk(&chunk),
+                Poll::Ready(None) => {
+                    // A final line without a trailing newline still counts
+                    self.inner_done = true;
+                    let line = std::mem::take(&mut self.partial_line);
+                    self.push_line(&line);
+                    if let Some(event) = self.assembler.finish() {
+                        self.pending.push_back(event);
+                    }
                 }
-                return Some(event);
+                Poll::Pending => return Poll::Pending,
             }
         }
-        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+    use futures::stream::{self, StreamExt};
+
+    #[test]
+    fn event_stream_reassembles_events_split_across_chunks() {
+        let chunks = [
+            "event: message_start\r\nda",
+            "ta: {\"type\":\"message_start\"}\r\n\r\ndata: {\"type\":",
+            "\"ping\"}\n\ndata: [DONE]\n\ndata: {\"after\":\"done\"}\n\n",
+        ];
+        let events: Vec<SseEvent> = block_on(
+            SseEventStream::new(stream::iter(
+                chunks.map(|chunk| Bytes::from_static(chunk.as_bytes())),
+            ))
+            .collect(),
+        );
+
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[0].event.as_deref(), Some("message_start"));
+        assert_eq!(
+            events[0].data.as_deref(),
+            Some("{\"type\":\"message_start\"}")
+        );
+        assert_eq!(events[1].data.as_deref(), Some("{\"type\":\"ping\"}"));
+        assert!(events[2].is_done());
+    }
+
+    #[test]
+    fn event_stream_flushes_a_final_line_without_newline() {
+        let chunks = [Bytes::from_static(b"data: {\"id\":1}")];
+        let events: Vec<SseEvent> = block_on(SseEventStream::new(stream::iter(chunks)).collect());
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].data.as_deref(), Some("{\"id\":1}"));
+    }
+
+    #[test]
+    fn stream_iter_joins_data_lines_and_keeps_id_and_retry() {
+        let raw = "id: 7\nretry: 3000\nevent: update\ndata: {\ndata:\"a\":1}\n\n: comment\n\n";
+        let events: Vec<SseEvent> = SseStreamIter::try_from(raw.as_bytes()).unwrap().collect();
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].id.as_deref(), Some("7"));
+        assert_eq!(events[0].retry, Some(3000));
+        assert_eq!(events[0].event.as_deref(), Some("update"));
+        assert_eq!(events[0].data.as_deref(), Some("{\n\"a\":1}"));
     }
 }
@@ -1,5 +1,7 @@
 use crate::apis::streaming_shapes::sse::{SseEvent, SseStreamIter};
 use crate::clients::endpoints::{SupportedAPIsFromClient, SupportedUpstreamAPIs};
+use crate::clients::errors::ApiError;
+use crate::providers::streaming_response::needs_buffering;
 
 /// Stateful processor for handling SSE chunks that may contain incomplete events.
 ///
@@ -8,6 +10,10 @@ use crate::clients::endpoints::{SupportedAPIsFromClient, SupportedUpstreamAPIs};
 pub struct SseChunkProcessor {
     /// Buffered bytes from incomplete SSE events across chunks
     incomplete_event_buffer: Vec<u8>,
+    /// Error the upstream reported mid-stream, until taken
+    upstream_error: Option<ApiError>,
+    /// Set once the upstream reported an error; later events are dropped
+    upstream_failed: bool,
 }
 
 impl Default for SseChunkProcessor {
@@ -20,6 +26,8 @@ impl SseChunkProcessor {
     pub fn new() -> Self {
         Self {
             incomplete_event_buffer: Vec::new(),
+            upstream_error: None,
+            upstream_failed: false,
         }
     }
 
@@ -42,10 +50,25 @@ impl SseChunkProcessor {
         client_api: &SupportedAPIsFromClient,
         upstream_api: &SupportedUpstreamAPIs,
     ) -> Result<Vec<SseEvent>, String> {
+        if self.upstream_failed {
+            return Ok(Vec::new());
+        }
+
         // Combine buffered incomplete event with new chunk
         let mut combined_data = std::mem::take(&mut self.incomplete_event_buffer);
         combined_data.extend_from_slice(chunk);
 
+        // Ollama streams newline-delimited JSON; hold back the unfinished last
+        // line and frame the complete ones as SSE data events
+        if matches!(upstream_api, SupportedUpstreamAPIs::OllamaChat(_)) {
+            let complete_len = combined_data
+                .iter()
+                .rposition(|byte| *byte == b'\n')
+                .map_or(0, |newline| newline + 1);
+            self.incomplete_event_buffer = combined_data.split_off(complete_len);
+            combined_data = ndjson_to_sse(&combined_data);
+        }
+
         // Parse using SseStreamIter
         let sse_iter = match SseStreamIter::try_from(combined_data.as_slice()) {
             Ok(iter) => iter,
@@ -56,6 +79,21 @@ impl SseChunkProcessor {
 
         // Process each parsed SSE event
         for sse_event in sse_iter {
+            // An error from an upstream speaking another API can't be
+            // transformed like content; it's kept for the stream buffer to
+            // report in the client's shape
+            if needs_buffering(client_api, upstream_api) {
+                if let Some(error) = sse_event
+                    .data
+                    .as_deref()
+                    .and_then(ApiError::from_stream_event)
+                {
+                    self.upstream_error = Some(error);
+                    self.upstream_failed = true;
+                    break;
+                }
+            }
+
             // Try to transform the event (this is where incomplete JSON fails)
             match SseEvent::try_from((sse_event.clone(), client_api, upstream_api)) {
                 Ok(transformed) => {
@@ -85,6 +123,11 @@ impl SseChunkProcessor {
         Ok(transformed_events)
     }
 
+    /// Take the error the upstream reported mid-stream, if it sent one.
+    pub fn take_upstream_error(&mut self) -> Option<ApiError> {
+        self.upstream_error.take()
+    }
+
     /// Check if there are buffered incomplete bytes
     pub fn has_buffered_data(&self) -> bool {
         !self.incomplete_event_buffer.is_empty()
@@ -96,6 +139,20 @@ impl SseChunkProcessor {
     }
 }
 
+/// Frame each non-empty NDJSON line as an SSE `data:` event
+fn ndjson_to_sse(lines: &[u8]) -> Vec<u8> {
+    let mut framed = Vec::with_capacity(lines.len());
+    for line in lines.split(|byte| *byte == b'\n') {
+        let line = line.trim_ascii();
+        if !line.is_empty() {
+            framed.extend_from_slice(b"data: ");
+            framed.extend_from_slice(line);
+            framed.extend_from_slice(b"\n\n");
+        }
+    }
+    framed
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -295,4 +352,79 @@ data: {"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text
             }
         }
     }
+
+    #[test]
+    fn test_upstream_error_event_is_reported_in_client_shape() {
+        use crate::apis::anthropic::AnthropicApi;
+        use crate::apis::streaming_shapes::sse::{SseStreamBuffer, SseStreamBufferTrait};
+
+        let mut processor = SseChunkProcessor::new();
+        let client_api = SupportedAPIsFromClient::OpenAIResponsesAPI(OpenAIApi::Responses);
+        let upstream_api = SupportedUpstreamAPIs::AnthropicMessagesAPI(AnthropicApi::Messages);
+        let mut buffer = SseStreamBuffer::try_from((&client_api, &upstream_api)).unwrap();
+
+        let chunk = br#"event: content_block_delta
+data: {"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"Hel"}}
+
+event: error
+data: {"type":"error","error":{"type":"overloaded_error","message":"Overloaded"}}
+
+"#;
+        let events = processor
+            .process_chunk(chunk, &client_api, &upstream_api)
+            .unwrap();
+        for event in events {
+            buffer.add_transformed_event(event);
+        }
+        let error = processor.take_upstream_error().unwrap();
+        buffer.add_upstream_error(&error);
+        let output = String::from_utf8(buffer.to_bytes()).unwrap();
+
+        assert!(output.contains("event: response.output_text.delta"));
+        assert!(output.contains("event: response.failed"));
+        assert!(output.contains(r#""message":"Overloaded""#));
+        assert!(!output.contains("response.completed"));
+
+        // Anything the upstream sends after its error is dropped
+        let trailing = b"event: message_stop\ndata: {\"type\":\"message_stop\"}\n\n";
+        assert!(processor
+            .process_chunk(trailing, &client_api, &upstream_api)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_ollama_ndjson_lines_split_across_chunks() {
+        use crate::apis::ollama::OllamaApi;
+
+        let mut processor = SseChunkProcessor::new();
+        let client_api = SupportedAPIsFromClient::OpenAIChatCompletions(OpenAIApi::ChatCompletions);
+        let upstream_api = SupportedUpstreamAPIs::OllamaChat(OllamaApi::Chat);
+
+        let chunk1 = b"{\"model\":\"llama3.2\",\"message\":{\"role\":\"assistant\",\"content\":\"Hel\"},\"done\":false}\n{\"model\":\"llama3.2\",\"message\":{\"role\":\"assi";
+        let events = processor
+            .process_chunk(chunk1, &client_api, &upstream_api)
+            .unwrap();
+        assert_eq!(events.len(), 1);
+        assert!(events[0]
+            .sse_transformed_lines
+            .contains(r#""content":"Hel""#));
+        assert!(processor.has_buffered_data());
+
+        let chunk2 = b"stant\",\"content\":\"lo\"},\"done\":false}\n{\"model\":\"llama3.2\",\"message\":{\"role\":\"assistant\",\"content\":\"\"},\"done\":true,\"done_reason\":\"stop\",\"prompt_eval_count\":5,\"eval_count\":2}\n";
+        let events = processor
+            .process_chunk(chunk2, &client_api, &upstream_api)
+            .unwrap();
+        assert_eq!(events.len(), 2);
+        assert!(events[0]
+            .sse_transformed_lines
+            .contains(r#""content":"lo""#));
+        assert!(events[1]
+            .sse_transformed_lines
+            .contains(r#""finish_reason":"stop""#));
+        assert!(events[1]
+            .sse_transformed_lines
+            .contains(r#""total_tokens":7"#));
+        assert!(!processor.has_buffered_data());
+    }
 }
@@ -0,0 +1,496 @@
+//! Incremental validation of streamed structured output.
+//!
+//! When a client asks for `json_object` or `json_schema` output, the text
+//! deltas of a stream should add up to one JSON value. The validator is fed
+//! those deltas as they arrive and reports the first violation that no later
+//! delta could repair: invalid JSON, content after the value, or a mismatch
+//! with the schema's `type`, `properties`, `required`,
+//! `additionalProperties: false` or `items`. Schema nodes using `$ref` or
+//! combinators (`anyOf`, `oneOf`, `allOf`) are not checked.
+
+use serde_json::{json, Value};
+use thiserror::Error;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum StructuredOutputViolation {
+    #[error("invalid JSON: unexpected '{0}'")]
+    UnexpectedCharacter(char),
+    #[error("invalid JSON literal '{0}'")]
+    InvalidLiteral(String),
+    #[error("unexpected content after the JSON value")]
+    TrailingContent,
+    #[error("expected {expected} at '{path}', got {actual}")]
+    TypeMismatch {
+        path: String,
+        expected: String,
+        actual: &'static str,
+    },
+    #[error("property '{property}' is not allowed at '{path}'")]
+    UnexpectedProperty { path: String, property: String },
+    #[error("missing required property '{property}' at '{path}'")]
+    MissingProperty { path: String, property: String },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Expect {
+    Value,
+    /// After `[`: a value or `]`.
+    ValueOrClose,
+    /// After `{`: a key or `}`.
+    KeyOrClose,
+    Key,
+    Colon,
+    CommaOrClose,
+    Nothing,
+}
+
+#[derive(Debug)]
+enum Lexeme {
+    None,
+    String {
+        is_key: bool,
+        escaped: bool,
+        /// Hex digits still expected after `\u`.
+        unicode_digits: u8,
+    },
+    /// A number, `true`, `false` or `null` being read.
+    Scalar(String),
+}
+
+#[derive(Debug)]
+struct Frame {
+    is_object: bool,
+    schema: Option<Value>,
+    path: String,
+    seen_keys: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct StructuredOutputValidator {
+    expect: Expect,
+    lexeme: Lexeme,
+    key: String,
+    stack: Vec<Frame>,
+    /// Schema and path of the value expected next.
+    value_schema: Option<Value>,
+    value_path: String,
+}
+
+impl StructuredOutputValidator {
+    /// A validator for output matching `schema`.
+    pub fn new(schema: Value) -> Self {
+        Self {
+            expect: Expect::Value,
+            lexeme: Lexeme::None,
+            key: String::new(),
+            stack: Vec::new(),
+            value_schema: Some(schema),
+            value_path: "$".to_string(),
+        }
+    }
+
+    /// `json_object` mode: any JSON object.
+    pub fn json_object() -> Self {
+        Self::new(json!({"type": "object"}))
+    }
+
+    /// Feed the next text delta.
+    pub fn push(&mut self, text: &str) -> Result<(), StructuredOutputViolation> {
+        text.chars().try_for_each(|c| self.step(c))
+    }
+
+    /// Whether a complete JSON value has been read.
+    pub fn is_complete(&self) -> bool {
+        self.expect == Expect::Nothing && matches!(self.lexeme, Lexeme::None)
+    }
+
+    fn step(&mut self, c: char) -> Result<(), StructuredOutputViolation> {
+        match &mut self.lexeme {
+            Lexeme::String {
+                is_key,
+                escaped,
+                unicode_digits,
+            } => {
+                let is_key = *is_key;
+                if *unicode_digits > 0 {
+                    if !c.is_ascii_hexdigit() {
+                        return Err(StructuredOutputViolation::UnexpectedCharacter(c));
+                    }
+                    *unicode_digits -= 1;
+                } else if *escaped {
+                    match c {
+                        'u' => *unicode_digits = 4,
+                        '"' | '\\' | '/' | 'b' | 'f' | 'n' | 'r' | 't' => {}
+                        _ => return Err(StructuredOutputViolation::UnexpectedCharacter(c)),
+                    }
+                    *escaped = false;
+                } else if c == '\\' {
+                    *escaped = true;
+                } else if c == '"' {
+                    self.lexeme = Lexeme::None;
+                    return if is_key {
+                        self.end_key()
+                    } else {
+                        self.end_value();
+                        Ok(())
+                    };
+                } else if c.is_control() {
+                    return Err(StructuredOutputViolation::UnexpectedCharacter(c));
+                }
+                // Escaped key characters are kept without their backslash;
+                // schema property names rarely need escaping.
+                if is_key && !matches!(&self.lexeme, Lexeme::String { escaped: true, .. }) {
+                    self.key.push(c);
+                }
+                Ok(())
+            }
+            Lexeme::Scalar(token) => {
+                if c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.') {
+                    token.push(c);
+                    return Ok(());
+                }
+                let token = std::mem::take(token);
+                self.lexeme = Lexeme::None;
+                self.end_scalar(&token)?;
+                self.structural(c)
+            }
+            Lexeme::None => self.structural(c),
+        }
+    }
+
+    fn structural(&mut self, c: char) -> Result<(), StructuredOutputViolation> {
+        if c.is_whitespace() {
+            return Ok(());
+        }
+        match (self.expect, c) {
+            (Expect::Nothing, _) => Err(StructuredOutputViolation::TrailingContent),
+            (Expect::ValueOrClose, ']') => self.close(false),
+            (Expect::Value | Expect::ValueOrClose, _) => self.begin_value(c),
+            (Expect::KeyOrClose, '}') => self.close(true),
+            (Expect::KeyOrClose | Expect::Key, '"') => {
+                self.key.clear();
+                self.lexeme = Lexeme::String {
+                    is_key: true,
+                    escaped: false,
+                    unicode_digits: 0,
+                };
+                Ok(())
+            }
+            (Expect::Colon, ':') => {
+                self.expect = Expect::Value;
+                Ok(())
+            }
+            (Expect::CommaOrClose, ',') => {
+                let frame = self.stack.last().expect("inside a container");
+                if frame.is_object {
+                    self.expect = Expect::Key;
+                } else {
+                    self.value_schema = item_schema(frame.schema.as_ref());
+                    self.value_path = format!("{}[]", frame.path);
+                    self.expect = Expect::Value;
+                }
+                Ok(())
+            }
+            (Expect::CommaOrClose, '}') if self.in_object() => self.close(true),
+            (Expect::CommaOrClose, ']') if !self.in_object() => self.close(false),
+            _ => Err(StructuredOutputViolation::UnexpectedCharacter(c)),
+        }
+    }
+
+    fn in_object(&self) -> bool {
+        self.stack.last().is_some_and(|f| f.is_object)
+    }
+
+    fn begin_value(&mut self, c: char) -> Result<(), StructuredOutputViolation> {
+        let actual = match c {
+            '{' => "object",
+            '[' => "array",
+            '"' => "string",
+            '-' | '0'..='9' => "number",
+            't' | 'f' => "boolean",
+            'n' => "null",
+            _ => return Err(StructuredOutputViolation::UnexpectedCharacter(c)),
+        };
+        let schema = self.value_schema.take();
+        check_type(schema.as_ref(), actual, &self.value_path)?;
+        match c {
+            '{' | '[' => {
+                let is_object = c == '{';
+                let path = std::mem::take(&mut self.value_path);
+                if !is_object {
+                    self.value_schema = item_schema(schema.as_ref());
+                    self.value_path = format!("{path}[]");
+                }
+                self.stack.push(Frame {
+                    is_object,
+                    schema,
+                    path,
+                    seen_keys: Vec::new(),
+                });
+                self.expect = if is_object {
+                    Expect::KeyOrClose
+                } else {
+                    Expect::ValueOrClose
+                };
+            }
+            '"' => {
+                self.lexeme = Lexeme::String {
+                    is_key: false,
+                    escaped: false,
+                    unicode_digits: 0,
+                }
+            }
+            _ => {
+                // Remember the schema for the integer check when the scalar ends.
+                self.value_schema = schema;
+                self.lexeme = Lexeme::Scalar(c.to_string());
+            }
+        }
+        Ok(())
+    }
+
+    fn end_key(&mut self) -> Result<(), StructuredOutputViolation> {
+        let frame = self.stack.last_mut().expect("keys are read inside objects");
+        let key = std::mem::take(&mut self.key);
+        let path = format!("{}.{key}", frame.path);
+        let schema = match frame.schema.as_ref().filter(|s| is_checkable(s)) {
+            Some(schema) => match schema.get("properties").and_then(|p| p.get(&key)) {
+                Some(property) => Some(property.clone()),
+                None => match schema.get("additionalProperties") {
+                    Some(Value::Bool(false)) => {
+                        return Err(StructuredOutputViolation::UnexpectedProperty {
+                            path: frame.path.clone(),
+                            property: key,
+                        })
+                    }
+                    Some(additional @ Value::Object(_)) => Some(additional.clone()),
+                    _ => None,
+                },
+            },
+            None => None,
+        };
+        frame.seen_keys.push(key);
+        self.value_schema = schema;
+        self.value_path = path;
+        self.expect = Expect::Colon;
+        Ok(())
+    }
+
+    fn end_scalar(&mut self, token: &str) -> Result<(), StructuredOutputViolation> {
+        let value = serde_json::from_str::<Value>(token)
+            .ok()
+            .filter(|v| v.is_number() || v.is_boolean() || v.is_null())
+            .ok_or_else(|| StructuredOutputViolation::InvalidLiteral(token.to_string()))?;
+        let schema = self.value_schema.take();
+        if value.is_number() && !(value.is_i64() || value.is_u64()) {
+            check_type(schema.as_ref(), "non-integer number", &self.value_path)?;
+        }
+        self.end_value();
+        Ok(())
+    }
+
+    fn close(&mut self, is_object: bool) -> Result<(), StructuredOutputViolation> {
+        let frame = self.stack.pop().expect("closing an open container");
+        debug_assert_eq!(frame.is_object, is_object);
+        if let Some(required) = frame
+            .schema
+            .as_ref()
+            .filter(|s| is_checkable(s))
+            .and_then(|s| s.get("required"))
+            .and_then(Value::as_array)
+        {
+            if let Some(missing) = required
+                .iter()
+                .filter_map(Value::as_str)
+                .find(|r| !frame.seen_keys.iter().any(|k| k == r))
+            {
+                return Err(StructuredOutputViolation::MissingProperty {
+                    path: frame.path,
+                    property: missing.to_string(),
+                });
+            }
+        }
+        self.end_value();
+        Ok(())
+    }
+
+    fn end_value(&mut self) {
+        self.value_schema = None;
+        self.expect = if self.stack.is_empty() {
+            Expect::Nothing
+        } else {
+            Expect::CommaOrClose
+        };
+    }
+}
+
+/// Schema nodes built from references or combinators are left unchecked.
+fn is_checkable(schema: &Value) -> bool {
+    ["$ref", "anyOf", "oneOf", "allOf"]
+        .iter()
+        .all(|k| schema.get(k).is_none())
+}
+
+fn item_schema(schema: Option<&Value>) -> Option<Value> {
+    schema
+        .filter(|s| is_checkable(s))
+        .and_then(|s| s.get("items"))
+        .filter(|items| items.is_object())
+        .cloned()
+}
+
+/// Check a value's JSON type against the schema's `type`. A number that
+/// turns out not to be an integer is checked again as "non-integer number".
+fn check_type(
+    schema: Option<&Value>,
+    actual: &'static str,
+    path: &str,
+) -> Result<(), StructuredOutputViolation> {
+    let Some(types) = schema
+        .filter(|s| is_checkable(s))
+        .and_then(|s| s.get("type"))
+    else {
+        return Ok(());
+    };
+    let types: Vec<&str> = match types {
+        Value::String(t) => vec![t.as_str()],
+        Value::Array(ts) => ts.iter().filter_map(Value::as_str).collect(),
+        _ => return Ok(()),
+    };
+    let allowed = types.iter().any(|t| match (*t, actual) {
+        ("integer", "number") | ("number", "non-integer number") => true,
+        (t, actual) => t == actual,
+    });
+    if allowed {
+        Ok(())
+    } else {
+        Err(StructuredOutputViolation::TypeMismatch {
+            path: path.to_string(),
+            expected: types.join(" or "),
+            actual,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn feed(
+        validator: &mut StructuredOutputValidator,
+        deltas: &[&str],
+    ) -> Result<(), StructuredOutputViolation> {
+        deltas.iter().try_for_each(|delta| validator.push(delta))
+    }
+
+    fn weather_schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "city": {"type": "string"},
+                "temperature": {"type": "integer"},
+                "tags": {"type": "array", "items": {"type": "string"}}
+            },
+            "required": ["city", "temperature"],
+            "additionalProperties": false
+        })
+    }
+
+    #[test]
+    fn accepts_valid_output_split_anywhere() {
+        let mut validator = StructuredOutputValidator::new(weather_schema());
+        feed(
+            &mut validator,
+            &[
+                "{\"ci",
+                "ty\": \"Lis\\u00",
+                "e9bon\", \"temp",
+                "erature\": 2",
+                "1, \"tags\": [\"sun",
+                "ny\", \"dry\"]}",
+                "\n",
+            ],
+        )
+        .unwrap();
+        assert!(validator.is_complete());
+
+        let mut validator = StructuredOutputValidator::json_object();
+        feed(&mut validator, &[" {\"a\": [1, 2.5e3, true, null, {}]}"]).unwrap();
+        assert!(validator.is_complete());
+    }
+
+    #[test]
+    fn reports_syntax_violations() {
+        let mut validator = StructuredOutputValidator::json_object();
+        assert_eq!(
+            validator.push("Sure! Here is the JSON"),
+            Err(StructuredOutputViolation::UnexpectedCharacter('S'))
+        );
+
+        let mut validator = StructuredOutputValidator::json_object();
+        assert_eq!(
+            feed(&mut validator, &["{\"a\": tru", "x}"]),
+            Err(StructuredOutputViolation::InvalidLiteral(
+                "trux".to_string()
+            ))
+        );
+
+        let mut validator = StructuredOutputValidator::json_object();
+        assert_eq!(
+            feed(&mut validator, &["{}", " {}"]),
+            Err(StructuredOutputViolation::TrailingContent)
+        );
+
+        let mut validator = StructuredOutputValidator::json_object();
+        assert_eq!(
+            validator.push("[1]"),
+            Err(StructuredOutputViolation::TypeMismatch {
+                path: "$".to_string(),
+                expected: "object".to_string(),
+                actual: "array",
+            })
+        );
+    }
+
+    #[test]
+    fn reports_schema_violations_as_soon_as_they_appear() {
+        let mut validator = StructuredOutputValidator::new(weather_schema());
+        assert_eq!(
+            validator.push("{\"city\": \"Lisbon\", \"country\""),
+            Err(StructuredOutputViolation::UnexpectedProperty {
+                path: "$".to_string(),
+                property: "country".to_string(),
+            })
+        );
+
+        let mut validator = StructuredOutputValidator::new(weather_schema());
+        assert!(matches!(
+            validator.push("{\"city\": 7"),
+            Err(StructuredOutputViolation::TypeMismatch { path, actual: "number", .. }) if path == "$.city"
+        ));
+
+        let mut validator = StructuredOutputValidator::new(weather_schema());
+        assert!(matches!(
+            validator.push("{\"city\": \"Lisbon\", \"temperature\": 21.5,"),
+            Err(StructuredOutputViolation::TypeMismatch {
+                actual: "non-integer number",
+                ..
+            })
+        ));
+
+        let mut validator = StructuredOutputValidator::new(weather_schema());
+        assert!(matches!(
+            validator.push("{\"city\": \"Lisbon\", \"tags\": [\"a\", 1"),
+            Err(StructuredOutputViolation::TypeMismatch { path, .. }) if path == "$.tags[]"
+        ));
+
+        let mut validator = StructuredOutputValidator::new(weather_schema());
+        assert_eq!(
+            validator.push("{\"city\": \"Lisbon\"}"),
+            Err(StructuredOutputViolation::MissingProperty {
+                path: "$".to_string(),
+                property: "temperature".to_string(),
+            })
+        );
+    }
+}
@@ -0,0 +1,333 @@
+//! Incremental assembly of streamed tool-call arguments.
+//!
+//! Tool-call arguments arrive as JSON fragments: `tool_calls[*].function.arguments`
+//! deltas in Chat Completions chunks, `input_json_delta` events in Anthropic
+//! streams. The assembler buffers the fragments of each call and reports the
+//! call as soon as its arguments form a complete JSON object, so consumers can
+//! act on a tool call before the stream ends. Arguments that can no longer
+//! become a valid object are reported as soon as that is known.
+//!
+//! Chat Completions calls are keyed by their `index`; only the first choice is
+//! tracked. Anthropic calls are keyed by the index of their content block.
+
+use std::collections::BTreeMap;
+
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::apis::anthropic::{MessagesContentBlock, MessagesContentDelta, MessagesStreamEvent};
+use crate::apis::openai::ChatCompletionsStreamResponse;
+use crate::apis::streaming_shapes::structured_output::{
+    StructuredOutputValidator, StructuredOutputViolation,
+};
+use crate::providers::streaming_response::ProviderStreamResponseType;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ToolCallArgumentError {
+    #[error("invalid tool call arguments: {0}")]
+    Invalid(StructuredOutputViolation),
+    #[error("tool call arguments ended before the JSON object was complete")]
+    Incomplete,
+}
+
+/// What the assembler learned about a tool call from the latest stream event.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ToolCallUpdate {
+    /// The call's arguments form a complete JSON object.
+    Complete {
+        index: u32,
+        id: Option<String>,
+        name: Option<String>,
+        arguments: Value,
+    },
+    /// The call's arguments can't become a valid JSON object.
+    Invalid {
+        index: u32,
+        id: Option<String>,
+        name: Option<String>,
+        error: ToolCallArgumentError,
+    },
+}
+
+#[derive(Debug)]
+struct PendingToolCall {
+    id: Option<String>,
+    name: Option<String>,
+    arguments: String,
+    validator: StructuredOutputValidator,
+    /// Set once the call has been reported complete or invalid.
+    reported: bool,
+}
+
+impl PendingToolCall {
+    fn new() -> Self {
+        Self {
+            id: None,
+            name: None,
+            arguments: String::new(),
+            validator: StructuredOutputValidator::json_object(),
+            reported: false,
+        }
+    }
+
+    /// Feed an argument fragment; reports the call once it's complete or broken.
+    fn push(&mut self, index: u32, fragment: &str) -> Option<ToolCallUpdate> {
+        if self.reported || fragment.is_empty() {
+            return None;
+        }
+        self.arguments.push_str(fragment);
+        if let Err(violation) = self.validator.push(fragment) {
+            return Some(self.invalid(index, ToolCallArgumentError::Invalid(violation)));
+        }
+        if !self.validator.is_complete() {
+            return None;
+        }
+        self.reported = true;
+        match serde_json::from_str(&self.arguments) {
+            Ok(arguments) => Some(ToolCallUpdate::Complete {
+                index,
+                id: self.id.clone(),
+                name: self.name.clone(),
+                arguments,
+            }),
+            // The validator accepted it, so this only happens for JSON it
+            // doesn't check, such as out-of-range numbers
+            Err(_) => Some(self.invalid(index, ToolCallArgumentError::Incomplete)),
+        }
+    }
+
+    /// Report a call whose arguments stopped arriving. Calls that sent no
+    /// arguments at all take `default` (an empty object for Chat Completions,
+    /// the block's initial input for Anthropic).
+    fn finish(&mut self, index: u32, default: Value) -> Option<ToolCallUpdate> {
+        if self.reported {
+            return None;
+        }
+        self.reported = true;
+        if self.arguments.trim().is_empty() {
+            return Some(ToolCallUpdate::Complete {
+                index,
+                id: self.id.clone(),
+                name: self.name.clone(),
+                arguments: default,
+            });
+        }
+        Some(self.invalid(index, ToolCallArgumentError::Incomplete))
+    }
+
+    fn invalid(&mut self, index: u32, error: ToolCallArgumentError) -> ToolCallUpdate {
+        self.reported = true;
+        ToolCallUpdate::Invalid {
+            index,
+            id: self.id.clone(),
+            name: self.name.clone(),
+            error,
+        }
+    }
+}
+
+/// Buffers streamed tool-call arguments per call and reports each call once.
+#[derive(Debug, Default)]
+pub struct ToolCallArgumentAssembler {
+    calls: BTreeMap<u32, PendingToolCall>,
+    /// Initial `input` of Anthropic tool_use blocks, used when no deltas follow.
+    initial_inputs: BTreeMap<u32, Value>,
+}
+
+impl ToolCallArgumentAssembler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed any parsed stream event. Formats without tool-call deltas are ignored.
+    pub fn push(&mut self, response: &ProviderStreamResponseType) -> Vec<ToolCallUpdate> {
+        match response {
+            ProviderStreamResponseType::ChatCompletionsStreamResponse(chunk) => {
+                self.push_chat_chunk(chunk)
+            }
+            ProviderStreamResponseType::MessagesStreamEvent(event) => {
+                self.push_messages_event(event)
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Feed a Chat Completions chunk. A `finish_reason` closes every open call.
+    pub fn push_chat_chunk(
+        &mut self,
+        chunk: &ChatCompletionsStreamResponse,
+    ) -> Vec<ToolCallUpdate> {
+        let mut updates = Vec::new();
+        let Some(choice) = chunk.choices.iter().find(|choice| choice.index == 0) else {
+            return updates;
+        };
+        for delta in choice.delta.tool_calls.iter().flatten() {
+            let call = self
+                .calls
+                .entry(delta.index)
+                .or_insert_with(PendingToolCall::new);
+            if delta.id.is_some() {
+                call.id.clone_from(&delta.id);
+            }
+            let function = delta.function.as_ref();
+            if let Some(name) = function.and_then(|function| function.name.as_ref()) {
+                call.name = Some(name.clone());
+            }
+            let fragment = function
+                .and_then(|function| function.arguments.as_deref())
+                .unwrap_or_default();
+            updates.extend(call.push(delta.index, fragment));
+        }
+        if choice.finish_reason.is_some() {
+            for (index, call) in &mut self.calls {
+                updates.extend(call.finish(*index, Value::Object(Default::default())));
+            }
+        }
+        updates
+    }
+
+    /// Feed an Anthropic Messages stream event. `content_block_stop` closes
+    /// the block's call.
+    pub fn push_messages_event(&mut self, event: &MessagesStreamEvent) -> Vec<ToolCallUpdate> {
+        match event {
+            MessagesStreamEvent::ContentBlockStart {
+                index,
+                content_block:
+                    MessagesContentBlock::ToolUse {
+                        id, name, input, ..
+                    },
+            } => {
+                let mut call = PendingToolCall::new();
+                call.id = Some(id.clone());
+                call.name = Some(name.clone());
+                self.calls.insert(*index, call);
+                self.initial_inputs.insert(*index, input.clone());
+                Vec::new()
+            }
+            MessagesStreamEvent::ContentBlockDelta {
+                index,
+                delta: MessagesContentDelta::InputJsonDelta { partial_json },
+            } => self
+                .calls
+                .get_mut(index)
+                .and_then(|call| call.push(*index, partial_json))
+                .into_iter()
+                .collect(),
+            MessagesStreamEvent::ContentBlockStop { index } => {
+                let default = self
+                    .initial_inputs
+                    .remove(index)
+                    .unwrap_or_else(|| Value::Object(Default::default()));
+                self.calls
+                    .get_mut(index)
+                    .and_then(|call| call.finish(*index, default))
+                    .into_iter()
+                    .collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn chat_chunk(value: Value) -> ChatCompletionsStreamResponse {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn tool_call_chunk(index: u32, id: Option<&str>, name: Option<&str>, arguments: &str) -> Value {
+        json!({
+            "id": "chatcmpl-1", "object": "chat.completion.chunk", "created": 1, "model": "gpt-4o",
+            "choices": [{"index": 0, "delta": {"tool_calls": [{
+                "index": index, "id": id, "type": id.map(|_| "function"),
+                "function": {"name": name, "arguments": arguments}
+            }]}, "finish_reason": null}]
+        })
+    }
+
+    #[test]
+    fn chat_tool_call_completes_at_the_closing_brace() {
+        let mut assembler = ToolCallArgumentAssembler::new();
+        let chunks = [
+            tool_call_chunk(0, Some("call_1"), Some("get_weather"), ""),
+            tool_call_chunk(0, None, None, "{\"location\":"),
+            tool_call_chunk(0, None, None, "\"Paris\"}"),
+        ];
+
+        let updates: Vec<_> = chunks
+            .into_iter()
+            .map(|chunk| assembler.push_chat_chunk(&chat_chunk(chunk)))
+            .collect();
+
+        assert!(updates[0].is_empty());
+        assert!(updates[1].is_empty());
+        assert_eq!(
+            updates[2],
+            vec![ToolCallUpdate::Complete {
+                index: 0,
+                id: Some("call_1".to_string()),
+                name: Some("get_weather".to_string()),
+                arguments: json!({"location": "Paris"}),
+            }]
+        );
+    }
+
+    #[test]
+    fn chat_tool_call_cut_off_by_finish_reason_is_incomplete() {
+        let mut assembler = ToolCallArgumentAssembler::new();
+        assembler.push_chat_chunk(&chat_chunk(tool_call_chunk(
+            0,
+            Some("call_1"),
+            Some("lookup"),
+            "{\"q\":\"ru",
+        )));
+
+        let updates = assembler.push_chat_chunk(&chat_chunk(json!({
+            "id": "chatcmpl-1", "object": "chat.completion.chunk", "created": 1, "model": "gpt-4o",
+            "choices": [{"index": 0, "delta": {}, "finish_reason": "length"}]
+        })));
+
+        assert!(matches!(
+            updates.as_slice(),
+            [ToolCallUpdate::Invalid {
+                error: ToolCallArgumentError::Incomplete,
+                ..
+            }]
+        ));
+    }
+
+    #[test]
+    fn anthropic_input_json_deltas_assemble_per_block() {
+        let mut assembler = ToolCallArgumentAssembler::new();
+        let events: Vec<MessagesStreamEvent> = [
+            json!({"type": "content_block_start", "index": 1, "content_block": {
+                "type": "tool_use", "id": "toolu_1", "name": "search", "input": {}}}),
+            json!({"type": "content_block_delta", "index": 1, "delta": {
+                "type": "input_json_delta", "partial_json": "{\"query\": [1, "}}),
+            json!({"type": "content_block_delta", "index": 1, "delta": {
+                "type": "input_json_delta", "partial_json": "2]}"}}),
+            json!({"type": "content_block_stop", "index": 1}),
+        ]
+        .into_iter()
+        .map(|event| serde_json::from_value(event).unwrap())
+        .collect();
+
+        let updates: Vec<_> = events
+            .iter()
+            .flat_map(|event| assembler.push_messages_event(event))
+            .collect();
+
+        assert_eq!(
+            updates,
+            vec![ToolCallUpdate::Complete {
+                index: 1,
+                id: Some("toolu_1".to_string()),
+                name: Some("search".to_string()),
+                arguments: json!({"query": [1, 2]}),
+            }]
+        );
+    }
+}
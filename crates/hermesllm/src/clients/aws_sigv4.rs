@@ -0,0 +1,314 @@
+//! AWS Signature Version 4 for Bedrock runtime requests.
+//!
+//! Bedrock accepts either a bearer API key or a SigV4 signature made with IAM
+//! credentials. Signing covers the method, path, query, host, timestamp and a
+//! hash of the final body, so it has to run after every body transformation.
+//! Streaming endpoints (`converse-stream`, `invoke-with-response-stream`) are
+//! signed the same way; only their responses are event streams.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+pub const BEDROCK_SIGNING_SERVICE: &str = "bedrock";
+
+const ALGORITHM: &str = "AWS4-HMAC-SHA256";
+const SHA256_BLOCK_SIZE: usize = 64;
+
+/// IAM credentials. `session_token` is set for temporary credentials from STS,
+/// container roles and instance profiles.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AwsCredentials {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_token: Option<String>,
+}
+
+/// The parts of an HTTP request covered by the signature.
+#[derive(Debug, Clone, Copy)]
+pub struct SignableRequest<'a> {
+    pub method: &'a str,
+    /// Host header sent upstream, e.g. `bedrock-runtime.us-east-1.amazonaws.com`.
+    pub host: &'a str,
+    /// Request path including any query string, as sent upstream.
+    pub path: &'a str,
+    pub body: &'a [u8],
+}
+
+/// Region of a regional AWS endpoint such as
+/// `bedrock-runtime.us-west-2.amazonaws.com`.
+pub fn region_from_host(host: &str) -> Option<&str> {
+    let host = host.split(':').next().unwrap_or(host);
+    let labels: Vec<&str> = host.split('.').collect();
+    match labels.as_slice() {
+        [_, region, "amazonaws", "com"] | [_, region, "amazonaws", "com", "cn"] => Some(region),
+        _ => None,
+    }
+}
+
+/// Headers that sign `request`, to be set on it as-is: `x-amz-date`,
+/// `x-amz-security-token` for temporary credentials, and `authorization`.
+pub fn sign(
+    request: &SignableRequest,
+    credentials: &AwsCredentials,
+    region: &str,
+    service: &str,
+    unix_secs: u64,
+) -> Vec<(&'static str, String)> {
+    let amz_date = format_amz_date(unix_secs);
+    let date = &amz_date[..8];
+
+    let mut headers = vec![
+        ("host", request.host.trim().to_ascii_lowercase()),
+        ("x-amz-date", amz_date.clone()),
+    ];
+    if let Some(token) = &credentials.session_token {
+        headers.push(("x-amz-security-token", token.clone()));
+    }
+    let signed_headers = headers
+        .iter()
+        .map(|(name, _)| *name)
+        .collect::<Vec<_>>()
+        .join(";");
+    let canonical_headers: String = headers
+        .iter()
+        .map(|(name, value)| format!("{}:{}\n", name, value))
+        .collect();
+
+    let (path, query) = request.path.split_once('?').unwrap_or((request.path, ""));
+    let canonical_request = format!(
+        "{}\n{}\n{}\n{}\n{}\n{}",
+        request.method.to_ascii_uppercase(),
+        canonical_uri(path),
+        canonical_query(query),
+        canonical_headers,
+        signed_headers,
+        hex(&Sha256::digest(request.body))
+    );
+
+    let scope = format!("{}/{}/{}/aws4_request", date, region, service);
+    let string_to_sign = format!(
+        "{}\n{}\n{}\n{}",
+        ALGORITHM,
+        amz_date,
+        scope,
+        hex(&Sha256::digest(canonical_request.as_bytes()))
+    );
+
+    let secret = format!("AWS4{}", credentials.secret_access_key);
+    let k_date = hmac_sha256(secret.as_bytes(), date.as_bytes());
+    let k_region = hmac_sha256(&k_date, region.as_bytes());
+    let k_service = hmac_sha256(&k_region, service.as_bytes());
+    let k_signing = hmac_sha256(&k_service, b"aws4_request");
+    let signature = hex(&hmac_sha256(&k_signing, string_to_sign.as_bytes()));
+
+    let mut signing_headers: Vec<(&'static str, String)> = headers
+        .into_iter()
+        .filter(|(name, _)| *name != "host")
+        .collect();
+    signing_headers.push((
+        "authorization",
+        format!(
+            "{} Credential={}/{}, SignedHeaders={}, Signature={}",
+            ALGORITHM, credentials.access_key_id, scope, signed_headers, signature
+        ),
+    ));
+    signing_headers
+}
+
+/// Each path segment is URI-encoded again, as AWS does for every service but S3.
+fn canonical_uri(path: &str) -> String {
+    if path.is_empty() {
+        return "/".to_string();
+    }
+    path.split('/')
+        .map(uri_encode)
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Query parameters sorted by name, then value. Parameters are expected to be
+/// percent-encoded already.
+fn canonical_query(query: &str) -> String {
+    let mut params: Vec<(&str, &str)> = query
+        .split('&')
+        .filter(|param| !param.is_empty())
+        .map(|param| param.split_once('=').unwrap_or((param, "")))
+        .collect();
+    params.sort();
+    params
+        .iter()
+        .map(|(name, value)| format!("{}={}", name, value))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+fn uri_encode(segment: &str) -> String {
+    segment
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+fn hmac_sha256(key: &[u8], message: &[u8]) -> [u8; 32] {
+    let mut block = [0u8; SHA256_BLOCK_SIZE];
+    if key.len() > SHA256_BLOCK_SIZE {
+        block[..32].copy_from_slice(&Sha256::digest(key));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+    let inner_pad: Vec<u8> = block.iter().map(|byte| byte ^ 0x36).collect();
+    let outer_pad: Vec<u8> = block.iter().map(|byte| byte ^ 0x5c).collect();
+
+    let inner = Sha256::new()
+        .chain_update(&inner_pad)
+        .chain_update(message)
+        .finalize();
+    Sha256::new()
+        .chain_update(&outer_pad)
+        .chain_update(inner)
+        .finalize()
+        .into()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// `YYYYMMDD'T'HHMMSS'Z'` in UTC.
+fn format_amz_date(unix_secs: u64) -> String {
+    let days = (unix_secs / 86_400) as i64;
+    let secs_of_day = unix_secs % 86_400;
+
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}{:02}{:02}T{:02}{:02}{:02}Z",
+        year,
+        month,
+        day,
+        secs_of_day / 3_600,
+        secs_of_day % 3_600 / 60,
+        secs_of_day % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example_credentials() -> AwsCredentials {
+        AwsCredentials {
+            access_key_id: "AKIDEXAMPLE".to_string(),
+            secret_access_key: "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY".to_string(),
+            session_token: None,
+        }
+    }
+
+    // 2015-08-30T12:36:00Z, the timestamp of the AWS SigV4 test suite
+    const TEST_SUITE_TIME: u64 = 1_440_938_160;
+
+    #[test]
+    fn test_sign_matches_aws_test_suite_get_vanilla() {
+        let request = SignableRequest {
+            method: "GET",
+            host: "example.amazonaws.com",
+            path: "/",
+            body: b"",
+        };
+        let headers = sign(
+            &request,
+            &example_credentials(),
+            "us-east-1",
+            "service",
+            TEST_SUITE_TIME,
+        );
+        assert_eq!(
+            headers,
+            vec![
+                ("x-amz-date", "20150830T123600Z".to_string()),
+                (
+                    "authorization",
+                    "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/service/aws4_request, SignedHeaders=host;x-amz-date, Signature=5fa00fa31553b73ebf1942676e86291e8372ff2a2260956d9b8aae1d763fbf31".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sign_bedrock_request_with_session_token() {
+        let credentials = AwsCredentials {
+            session_token: Some("session".to_string()),
+            ..example_credentials()
+        };
+        let request = SignableRequest {
+            method: "POST",
+            host: "bedrock-runtime.us-west-2.amazonaws.com",
+            path: "/model/anthropic.claude-3-haiku-20240307-v1:0/converse-stream",
+            body: br#"{"messages":[]}"#,
+        };
+        let headers = sign(
+            &request,
+            &credentials,
+            "us-west-2",
+            BEDROCK_SIGNING_SERVICE,
+            TEST_SUITE_TIME,
+        );
+        assert_eq!(headers[1], ("x-amz-security-token", "session".to_string()));
+        let authorization = &headers[2].1;
+        assert!(authorization.starts_with(
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-west-2/bedrock/aws4_request, SignedHeaders=host;x-amz-date;x-amz-security-token, Signature="
+        ));
+    }
+
+    #[test]
+    fn test_canonical_uri_double_encodes_segments() {
+        assert_eq!(
+            canonical_uri("/model/anthropic.claude-v2:1/invoke"),
+            "/model/anthropic.claude-v2%3A1/invoke"
+        );
+        assert_eq!(
+            canonical_uri("/model/anthropic.claude-v2%3A1/invoke"),
+            "/model/anthropic.claude-v2%253A1/invoke"
+        );
+    }
+
+    #[test]
+    fn test_region_from_host() {
+        assert_eq!(
+            region_from_host("bedrock-runtime.us-west-2.amazonaws.com"),
+            Some("us-west-2")
+        );
+        assert_eq!(
+            region_from_host("bedrock-runtime.cn-north-1.amazonaws.com.cn:443"),
+            Some("cn-north-1")
+        );
+        assert_eq!(region_from_host("localhost"), None);
+    }
+
+    #[test]
+    fn test_format_amz_date() {
+        assert_eq!(format_amz_date(0), "19700101T000000Z");
+        assert_eq!(format_amz_date(TEST_SUITE_TIME), "20150830T123600Z");
+        assert_eq!(format_amz_date(1_709_251_199), "20240229T235959Z");
+    }
+}
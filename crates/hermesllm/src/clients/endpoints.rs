@@ -1,7 +1,21 @@
-use crate::apis::{AmazonBedrockApi, AnthropicApi, ApiDefinition, OpenAIApi};
+use crate::apis::{
+    AmazonBedrockApi, AnthropicApi, ApiDefinition, CohereApi, GeminiApi, OllamaApi, OpenAIApi,
+};
 use crate::ProviderId;
+use std::collections::HashMap;
 use std::fmt;
 
+/// Placeholder substituted with the provider credential in header templates.
+pub const API_KEY_PLACEHOLDER: &str = "{api_key}";
+/// Anthropic API version sent when the provider config does not override it.
+pub const ANTHROPIC_API_VERSION: &str = "2023-06-01";
+/// Azure OpenAI `api-version` query parameter used when the provider config
+/// does not set one.
+pub const AZURE_OPENAI_API_VERSION: &str = "2025-01-01-preview";
+/// Credential headers a client may send; they are stripped before the
+/// upstream's own auth headers are applied.
+pub const CLIENT_AUTH_HEADERS: [&str; 2] = ["authorization", "x-api-key"];
+
 /// Unified enum representing all supported API endpoints across providers
 #[derive(Debug, Clone, PartialEq)]
 pub enum SupportedAPIsFromClient {
@@ -16,6 +30,12 @@ pub enum SupportedUpstreamAPIs {
     AnthropicMessagesAPI(AnthropicApi),
     AmazonBedrockConverse(AmazonBedrockApi),
     AmazonBedrockConverseStream(AmazonBedrockApi),
+    AmazonBedrockInvokeModel(AmazonBedrockApi),
+    AmazonBedrockInvokeModelStream(AmazonBedrockApi),
+    GeminiGenerateContent(GeminiApi),
+    GeminiStreamGenerateContent(GeminiApi),
+    CohereChat(CohereApi),
+    OllamaChat(OllamaApi),
     OpenAIResponsesAPI(OpenAIApi),
 }
 
@@ -47,9 +67,21 @@ impl fmt::Display for SupportedUpstreamAPIs {
             SupportedUpstreamAPIs::AmazonBedrockConverse(api) => {
                 write!(f, "Amazon Bedrock ({})", api.endpoint())
             }
-            SupportedUpstreamAPIs::AmazonBedrockConverseStream(api) => {
+            SupportedUpstreamAPIs::AmazonBedrockConverseStream(api)
+            | SupportedUpstreamAPIs::AmazonBedrockInvokeModel(api)
+            | SupportedUpstreamAPIs::AmazonBedrockInvokeModelStream(api) => {
                 write!(f, "Amazon Bedrock ({})", api.endpoint())
             }
+            SupportedUpstreamAPIs::GeminiGenerateContent(api)
+            | SupportedUpstreamAPIs::GeminiStreamGenerateContent(api) => {
+                write!(f, "Gemini ({})", api.endpoint())
+            }
+            SupportedUpstreamAPIs::CohereChat(api) => {
+                write!(f, "Cohere ({})", api.endpoint())
+            }
+            SupportedUpstreamAPIs::OllamaChat(api) => {
+                write!(f, "Ollama ({})", api.endpoint())
+            }
             SupportedUpstreamAPIs::OpenAIResponsesAPI(api) => {
                 write!(f, "OpenAI Responses ({})", api.endpoint())
             }
@@ -85,6 +117,11 @@ impl SupportedAPIsFromClient {
         }
     }
 
+    /// Build the upstream path for a request to `provider_id`.
+    ///
+    /// For Azure OpenAI, `model_id` is the deployment name and `api_version`
+    /// overrides [`AZURE_OPENAI_API_VERSION`]; other providers ignore it.
+    #[allow(clippy::too_many_arguments)]
     pub fn target_endpoint_for_provider(
         &self,
         provider_id: &ProviderId,
@@ -93,20 +130,11 @@ impl SupportedAPIsFromClient {
         is_streaming: bool,
         base_url_path_prefix: Option<&str>,
         use_unversioned_paths: bool,
+        api_version: Option<&str>,
     ) -> String {
         // Helper function to build endpoint with optional prefix override
         let build_endpoint = |provider_prefix: &str, suffix: &str| -> String {
-            let prefix = base_url_path_prefix
-                .map(|p| p.trim_matches('/'))
-                .filter(|p| !p.is_empty())
-                .unwrap_or(provider_prefix.trim_matches('/'));
-
-            let suffix = suffix.trim_start_matches('/');
-            if prefix.is_empty() {
-                format!("/{}", suffix)
-            } else {
-                format!("/{}/{}", prefix, suffix)
-            }
+            join_endpoint(base_url_path_prefix, provider_prefix, suffix)
         };
 
         // Helper function to route based on provider with a specific endpoint suffix
@@ -138,7 +166,12 @@ impl SupportedAPIsFromClient {
                         let suffix = endpoint_suffix.trim_start_matches('/');
                         build_endpoint(
                             "/openai/deployments",
-                            &format!("/{}/{}?api-version=2025-01-01-preview", model_id, suffix),
+                            &format!(
+                                "/{}/{}?api-version={}",
+                                model_id,
+                                suffix,
+                                api_version.unwrap_or(AZURE_OPENAI_API_VERSION)
+                            ),
                         )
                     } else {
                         build_endpoint("/v1", endpoint_suffix)
@@ -151,6 +184,10 @@ impl SupportedAPIsFromClient {
                         build_endpoint("/v1", endpoint_suffix)
                     }
                 }
+                ProviderId::Cohere => build_endpoint("/compatibility/v1", endpoint_suffix),
+                // Vertex AI paths hang off the base_url's
+                // `/v1/projects/{project}/locations/{location}` prefix
+                ProviderId::VertexAI => build_endpoint("", "/endpoints/openapi/chat/completions"),
                 ProviderId::AmazonBedrock => {
                     if request_path.starts_with("/v1/") {
                         if !is_streaming {
@@ -185,6 +222,10 @@ impl SupportedAPIsFromClient {
                             build_endpoint("/v1", "/chat/completions")
                         }
                     }
+                    ProviderId::VertexAI => {
+                        build_endpoint("", "/endpoints/openapi/chat/completions")
+                    }
+                    ProviderId::Cohere => build_endpoint("/compatibility/v1", "/chat/completions"),
                     _ => build_endpoint("/v1", "/chat/completions"),
                 }
             }
@@ -193,18 +234,92 @@ impl SupportedAPIsFromClient {
                 match provider_id {
                     // Providers that support /v1/responses natively
                     ProviderId::OpenAI | ProviderId::XAI => route_by_provider("/responses"),
+                    // Anthropic: translate to Messages
+                    ProviderId::Anthropic => build_endpoint("/v1", "/messages"),
                     // All other providers: translate to /chat/completions
                     _ => route_by_provider("/chat/completions"),
                 }
             }
-            SupportedAPIsFromClient::OpenAIChatCompletions(_) => {
+            SupportedAPIsFromClient::OpenAIChatCompletions(_) => match provider_id {
+                // Gemini is reached through its native generateContent API
+                ProviderId::Gemini if request_path.starts_with("/v1/") => {
+                    if is_streaming {
+                        build_endpoint(
+                            "/v1beta",
+                            &format!("/models/{}:streamGenerateContent?alt=sse", model_id),
+                        )
+                    } else {
+                        build_endpoint("/v1beta", &format!("/models/{}:generateContent", model_id))
+                    }
+                }
+                ProviderId::VertexAI if request_path.starts_with("/v1/") => {
+                    // Publisher-qualified ids (`google/gemini-...`) are what
+                    // the OpenAI-compatible endpoint takes; accept them here too
+                    let model_id = model_id.strip_prefix("google/").unwrap_or(model_id);
+                    let method = if is_streaming {
+                        "streamGenerateContent?alt=sse"
+                    } else {
+                        "generateContent"
+                    };
+                    build_endpoint(
+                        "",
+                        &format!("/publishers/google/models/{}:{}", model_id, method),
+                    )
+                }
+                // Cohere is reached through its native v2 chat API, which
+                // streams from the same path
+                ProviderId::Cohere if request_path.starts_with("/v1/") => {
+                    build_endpoint("/v2", "/chat")
+                }
+                // Ollama is reached through its native chat API, which streams
+                // newline-delimited JSON from the same path
+                ProviderId::Ollama if request_path.starts_with("/v1/") => {
+                    build_endpoint("", "/api/chat")
+                }
                 // For Chat Completions API, use the standard chat/completions path
-                route_by_provider("/chat/completions")
-            }
+                _ => route_by_provider("/chat/completions"),
+            },
         }
     }
 }
 
+/// `suffix` under the configured `base_url_path_prefix`, or under the
+/// provider's own `provider_prefix` when none is configured.
+pub(crate) fn join_endpoint(
+    base_url_path_prefix: Option<&str>,
+    provider_prefix: &str,
+    suffix: &str,
+) -> String {
+    let prefix = base_url_path_prefix
+        .map(|p| p.trim_matches('/'))
+        .filter(|p| !p.is_empty())
+        .unwrap_or(provider_prefix.trim_matches('/'));
+
+    let suffix = suffix.trim_start_matches('/');
+    if prefix.is_empty() {
+        format!("/{}", suffix)
+    } else {
+        format!("/{}/{}", prefix, suffix)
+    }
+}
+
+/// Whether `provider_id` still serves the legacy `/v1/completions` text
+/// completions API. Legacy requests for any other provider are translated to
+/// chat completions.
+pub fn serves_legacy_completions(provider_id: &ProviderId) -> bool {
+    matches!(provider_id, ProviderId::OpenAI | ProviderId::TogetherAI)
+}
+
+/// Upstream path of the legacy completions API on a provider that
+/// [serves it](serves_legacy_completions).
+pub fn legacy_completions_endpoint(
+    base_url_path_prefix: Option<&str>,
+    use_unversioned_paths: bool,
+) -> String {
+    let provider_prefix = if use_unversioned_paths { "" } else { "/v1" };
+    join_endpoint(base_url_path_prefix, provider_prefix, "/completions")
+}
+
 impl SupportedUpstreamAPIs {
     /// Create a SupportedUpstreamApi from an endpoint path
     pub fn from_endpoint(endpoint: &str) -> Option<Self> {
@@ -231,11 +346,223 @@ impl SupportedUpstreamAPIs {
                         bedrock_api,
                     ))
                 }
+                AmazonBedrockApi::InvokeModel => {
+                    return Some(SupportedUpstreamAPIs::AmazonBedrockInvokeModel(bedrock_api))
+                }
+                AmazonBedrockApi::InvokeModelWithResponseStream => {
+                    return Some(SupportedUpstreamAPIs::AmazonBedrockInvokeModelStream(
+                        bedrock_api,
+                    ))
+                }
+            }
+        }
+
+        if let Some(gemini_api) = GeminiApi::from_endpoint(endpoint) {
+            match gemini_api {
+                GeminiApi::GenerateContent => {
+                    return Some(SupportedUpstreamAPIs::GeminiGenerateContent(gemini_api))
+                }
+                GeminiApi::StreamGenerateContent => {
+                    return Some(SupportedUpstreamAPIs::GeminiStreamGenerateContent(
+                        gemini_api,
+                    ))
+                }
             }
         }
 
+        if let Some(cohere_api) = CohereApi::from_endpoint(endpoint) {
+            return Some(SupportedUpstreamAPIs::CohereChat(cohere_api));
+        }
+
+        if let Some(ollama_api) = OllamaApi::from_endpoint(endpoint) {
+            return Some(SupportedUpstreamAPIs::OllamaChat(ollama_api));
+        }
+
         None
     }
+
+    /// Header templates every request to this API needs. Values may contain
+    /// [`API_KEY_PLACEHOLDER`].
+    pub fn default_header_templates(&self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            SupportedUpstreamAPIs::AnthropicMessagesAPI(_) => &[
+                ("x-api-key", API_KEY_PLACEHOLDER),
+                ("anthropic-version", ANTHROPIC_API_VERSION),
+            ],
+            SupportedUpstreamAPIs::GeminiGenerateContent(_)
+            | SupportedUpstreamAPIs::GeminiStreamGenerateContent(_) => {
+                &[("x-goog-api-key", API_KEY_PLACEHOLDER)]
+            }
+            SupportedUpstreamAPIs::OpenAIChatCompletions(_)
+            | SupportedUpstreamAPIs::AmazonBedrockConverse(_)
+            | SupportedUpstreamAPIs::AmazonBedrockConverseStream(_)
+            | SupportedUpstreamAPIs::AmazonBedrockInvokeModel(_)
+            | SupportedUpstreamAPIs::AmazonBedrockInvokeModelStream(_)
+            | SupportedUpstreamAPIs::CohereChat(_)
+            | SupportedUpstreamAPIs::OllamaChat(_)
+            | SupportedUpstreamAPIs::OpenAIResponsesAPI(_) => {
+                &[("authorization", "Bearer {api_key}")]
+            }
+        }
+    }
+
+    /// Resolve the headers to set on an upstream request.
+    ///
+    /// Provider `overrides` are merged over the defaults by case-insensitive
+    /// name; an empty override value drops that header. The credential is
+    /// substituted for [`API_KEY_PLACEHOLDER`] in every value. Vertex AI
+    /// takes an OAuth2 bearer token whatever the API, Azure OpenAI takes its
+    /// key in `api-key`, and Deepgram as `Authorization: Token`.
+    pub fn upstream_headers(
+        &self,
+        provider_id: &ProviderId,
+        credential: &str,
+        overrides: Option<&HashMap<String, String>>,
+    ) -> Vec<(String, String)> {
+        let defaults = match provider_id {
+            ProviderId::VertexAI => &[("authorization", "Bearer {api_key}")],
+            ProviderId::AzureOpenAI => &[("api-key", API_KEY_PLACEHOLDER)],
+            ProviderId::Deepgram => &[("authorization", "Token {api_key}")],
+            _ => self.default_header_templates(),
+        };
+        let headers = defaults
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        render_header_templates(headers, credential, overrides)
+    }
+
+    /// Key used to look up a per-provider path override for this API in
+    /// provider configuration (`endpoint_paths`).
+    pub fn path_key(&self) -> &'static str {
+        match self {
+            SupportedUpstreamAPIs::OpenAIChatCompletions(_) => "chat_completions",
+            SupportedUpstreamAPIs::AnthropicMessagesAPI(_) => "messages",
+            SupportedUpstreamAPIs::AmazonBedrockConverse(_) => "converse",
+            SupportedUpstreamAPIs::AmazonBedrockConverseStream(_) => "converse_stream",
+            SupportedUpstreamAPIs::AmazonBedrockInvokeModel(_) => "invoke_model",
+            SupportedUpstreamAPIs::AmazonBedrockInvokeModelStream(_) => {
+                "invoke_model_with_response_stream"
+            }
+            SupportedUpstreamAPIs::GeminiGenerateContent(_) => "generate_content",
+            SupportedUpstreamAPIs::GeminiStreamGenerateContent(_) => "stream_generate_content",
+            SupportedUpstreamAPIs::CohereChat(_) => "cohere_chat",
+            SupportedUpstreamAPIs::OllamaChat(_) => "ollama_chat",
+            SupportedUpstreamAPIs::OpenAIResponsesAPI(_) => "responses",
+        }
+    }
+
+    /// Path of a Bedrock InvokeModel request, which depends on the API rather
+    /// than the client endpoint. `None` for every other API.
+    pub fn invoke_model_path(
+        &self,
+        model_id: &str,
+        base_url_path_prefix: Option<&str>,
+    ) -> Option<String> {
+        let action = match self {
+            SupportedUpstreamAPIs::AmazonBedrockInvokeModel(_) => "invoke",
+            SupportedUpstreamAPIs::AmazonBedrockInvokeModelStream(_) => {
+                "invoke-with-response-stream"
+            }
+            _ => return None,
+        };
+        let prefix = base_url_path_prefix
+            .map(|p| p.trim_matches('/'))
+            .filter(|p| !p.is_empty())
+            .map(|p| format!("/{}", p))
+            .unwrap_or_default();
+        Some(format!("{}/model/{}/{}", prefix, model_id, action))
+    }
+
+    /// Path of a Claude request to Vertex AI, under the base URL's
+    /// `/v1/projects/{project}/locations/{location}` prefix. `None` unless
+    /// the provider is Vertex AI and this is the Anthropic Messages API.
+    pub fn vertex_anthropic_path(
+        &self,
+        provider_id: &ProviderId,
+        model_id: &str,
+        is_streaming: bool,
+        base_url_path_prefix: Option<&str>,
+    ) -> Option<String> {
+        if *provider_id != ProviderId::VertexAI
+            || !matches!(self, SupportedUpstreamAPIs::AnthropicMessagesAPI(_))
+        {
+            return None;
+        }
+        let model_id = model_id.strip_prefix("anthropic/").unwrap_or(model_id);
+        let method = if is_streaming {
+            "streamRawPredict"
+        } else {
+            "rawPredict"
+        };
+        let prefix = base_url_path_prefix
+            .map(|p| p.trim_matches('/'))
+            .filter(|p| !p.is_empty())
+            .map(|p| format!("/{}", p))
+            .unwrap_or_default();
+        Some(format!(
+            "{}/publishers/anthropic/models/{}:{}",
+            prefix, model_id, method
+        ))
+    }
+
+    /// Resolve a configured path override for this API, if one exists.
+    ///
+    /// Overrides are full request paths (e.g. `/openai/v1/chat/completions`);
+    /// a `{model}` placeholder is replaced with the upstream model id.
+    pub fn path_override(
+        &self,
+        endpoint_paths: &HashMap<String, String>,
+        model_id: &str,
+    ) -> Option<String> {
+        endpoint_path_override(endpoint_paths, self.path_key(), model_id)
+    }
+}
+
+/// Resolve the configured path for `key` in a provider's `endpoint_paths`.
+/// Besides the [`SupportedUpstreamAPIs::path_key`] keys, `completions`,
+/// `embeddings`, `images_generations`, `moderations` and
+/// `audio_transcriptions` override the paths of those endpoints.
+pub fn endpoint_path_override(
+    endpoint_paths: &HashMap<String, String>,
+    key: &str,
+    model_id: &str,
+) -> Option<String> {
+    let path = endpoint_paths.get(key)?.trim();
+    if path.is_empty() {
+        return None;
+    }
+    let path = path.replace("{model}", model_id);
+    if path.starts_with('/') {
+        Some(path)
+    } else {
+        Some(format!("/{}", path))
+    }
+}
+
+/// Merge provider `overrides` over the `headers` templates and substitute the
+/// credential for [`API_KEY_PLACEHOLDER`].
+pub(crate) fn render_header_templates(
+    mut headers: Vec<(String, String)>,
+    credential: &str,
+    overrides: Option<&HashMap<String, String>>,
+) -> Vec<(String, String)> {
+    if let Some(overrides) = overrides {
+        let mut overrides: Vec<_> = overrides.iter().collect();
+        overrides.sort();
+        for (name, value) in overrides {
+            let name = name.to_ascii_lowercase();
+            headers.retain(|(existing, _)| *existing != name);
+            if !value.is_empty() {
+                headers.push((name, value.clone()));
+            }
+        }
+    }
+
+    headers
+        .into_iter()
+        .map(|(name, value)| (name, value.replace(API_KEY_PLACEHOLDER, credential)))
+        .collect()
 }
 
 /// Get all supported endpoint paths
@@ -285,6 +612,18 @@ mod tests {
         assert!(SupportedAPIsFromClient::from_endpoint("").is_none());
     }
 
+    #[test]
+    fn test_legacy_completions_endpoint() {
+        assert!(serves_legacy_completions(&ProviderId::OpenAI));
+        assert!(!serves_legacy_completions(&ProviderId::Anthropic));
+        assert_eq!(legacy_completions_endpoint(None, false), "/v1/completions");
+        assert_eq!(
+            legacy_completions_endpoint(Some("/api/v2"), false),
+            "/api/v2/completions"
+        );
+        assert_eq!(legacy_completions_endpoint(None, true), "/completions");
+    }
+
     #[test]
     fn test_supported_endpoints() {
         let endpoints = supported_endpoints();
@@ -294,6 +633,111 @@ mod tests {
         assert!(endpoints.contains(&"/v1/responses"));
     }
 
+    #[test]
+    fn test_upstream_headers_defaults() {
+        let messages = SupportedUpstreamAPIs::AnthropicMessagesAPI(AnthropicApi::Messages);
+        assert_eq!(
+            messages.upstream_headers(&ProviderId::Anthropic, "sk-ant", None),
+            vec![
+                ("x-api-key".to_string(), "sk-ant".to_string()),
+                (
+                    "anthropic-version".to_string(),
+                    ANTHROPIC_API_VERSION.to_string()
+                ),
+            ]
+        );
+
+        let chat = SupportedUpstreamAPIs::OpenAIChatCompletions(OpenAIApi::ChatCompletions);
+        assert_eq!(
+            chat.upstream_headers(&ProviderId::OpenAI, "sk-oai", None),
+            vec![("authorization".to_string(), "Bearer sk-oai".to_string())]
+        );
+
+        let gemini = SupportedUpstreamAPIs::GeminiGenerateContent(GeminiApi::GenerateContent);
+        assert_eq!(
+            gemini.upstream_headers(&ProviderId::Gemini, "AIza", None),
+            vec![("x-goog-api-key".to_string(), "AIza".to_string())]
+        );
+        assert_eq!(
+            gemini.upstream_headers(&ProviderId::VertexAI, "ya29.token", None),
+            vec![("authorization".to_string(), "Bearer ya29.token".to_string())]
+        );
+        assert_eq!(
+            chat.upstream_headers(&ProviderId::AzureOpenAI, "azure-key", None),
+            vec![("api-key".to_string(), "azure-key".to_string())]
+        );
+        assert_eq!(
+            chat.upstream_headers(&ProviderId::Deepgram, "dg-key", None),
+            vec![("authorization".to_string(), "Token dg-key".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_upstream_headers_overrides() {
+        let mut overrides = HashMap::new();
+        overrides.insert("Anthropic-Version".to_string(), "2024-10-22".to_string());
+        overrides.insert(
+            "anthropic-beta".to_string(),
+            "prompt-caching-2024-07-31".to_string(),
+        );
+        let messages = SupportedUpstreamAPIs::AnthropicMessagesAPI(AnthropicApi::Messages);
+        let headers = messages.upstream_headers(&ProviderId::Anthropic, "sk-ant", Some(&overrides));
+        assert!(headers.contains(&("anthropic-version".to_string(), "2024-10-22".to_string())));
+        assert!(headers.contains(&(
+            "anthropic-beta".to_string(),
+            "prompt-caching-2024-07-31".to_string()
+        )));
+        assert!(headers.contains(&("x-api-key".to_string(), "sk-ant".to_string())));
+
+        // An OpenAI-compatible server that wants the key in a custom header.
+        let mut overrides = HashMap::new();
+        overrides.insert("authorization".to_string(), String::new());
+        overrides.insert("api-key".to_string(), "{api_key}".to_string());
+        let chat = SupportedUpstreamAPIs::OpenAIChatCompletions(OpenAIApi::ChatCompletions);
+        assert_eq!(
+            chat.upstream_headers(&ProviderId::AzureOpenAI, "sk-azure", Some(&overrides)),
+            vec![("api-key".to_string(), "sk-azure".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_upstream_path_override() {
+        let mut endpoint_paths = HashMap::new();
+        endpoint_paths.insert(
+            "chat_completions".to_string(),
+            "/openai/v1/chat/completions".to_string(),
+        );
+        endpoint_paths.insert(
+            "converse".to_string(),
+            "api/model/{model}/converse".to_string(),
+        );
+
+        let chat = SupportedUpstreamAPIs::OpenAIChatCompletions(OpenAIApi::ChatCompletions);
+        assert_eq!(
+            chat.path_override(&endpoint_paths, "gpt-4o"),
+            Some("/openai/v1/chat/completions".to_string())
+        );
+
+        let converse = SupportedUpstreamAPIs::AmazonBedrockConverse(AmazonBedrockApi::Converse);
+        assert_eq!(
+            converse.path_override(&endpoint_paths, "claude-3"),
+            Some("/api/model/claude-3/converse".to_string())
+        );
+
+        let messages = SupportedUpstreamAPIs::AnthropicMessagesAPI(AnthropicApi::Messages);
+        assert_eq!(messages.path_override(&endpoint_paths, "claude-3"), None);
+
+        endpoint_paths.insert("embeddings".to_string(), "/api/v2/embed".to_string());
+        assert_eq!(
+            endpoint_path_override(&endpoint_paths, "embeddings", "e5-large"),
+            Some("/api/v2/embed".to_string())
+        );
+        assert_eq!(
+            endpoint_path_override(&endpoint_paths, "moderations", "e5-large"),
+            None
+        );
+    }
+
     #[test]
     fn test_identify_provider() {
         assert_eq!(identify_provider("/v1/chat/completions"), Some("openai"));
@@ -351,7 +795,8 @@ mod tests {
                 "gpt-4",
                 false,
                 None,
-                false
+                false,
+                None
             ),
             "/v1/chat/completions"
         );
@@ -364,7 +809,8 @@ mod tests {
                 "llama2",
                 false,
                 None,
-                false
+                false,
+                None
             ),
             "/openai/v1/chat/completions"
         );
@@ -377,7 +823,8 @@ mod tests {
                 "chatglm",
                 false,
                 None,
-                false
+                false,
+                None
             ),
             "/api/paas/v4/chat/completions"
         );
@@ -390,7 +837,8 @@ mod tests {
                 "qwen-turbo",
                 false,
                 None,
-                false
+                false,
+                None
             ),
             "/compatible-mode/v1/chat/completions"
         );
@@ -403,7 +851,8 @@ mod tests {
                 "gpt-4",
                 false,
                 None,
-                false
+                false,
+                None
             ),
             "/openai/deployments/gpt-4/chat/completions?api-version=2025-01-01-preview"
         );
@@ -416,9 +865,22 @@ mod tests {
                 "gemini-pro",
                 false,
                 None,
-                false
+                false,
+                None
+            ),
+            "/v1beta/models/gemini-pro:generateContent"
+        );
+        assert_eq!(
+            api.target_endpoint_for_provider(
+                &ProviderId::Gemini,
+                "/v1/chat/completions",
+                "gemini-pro",
+                true,
+                None,
+                false,
+                None
             ),
-            "/v1beta/openai/chat/completions"
+            "/v1beta/models/gemini-pro:streamGenerateContent?alt=sse"
         );
     }
 
@@ -433,7 +895,8 @@ mod tests {
                 "sonar-pro",
                 false,
                 None,
-                true
+                true,
+                None
             ),
             "/chat/completions"
         );
@@ -451,7 +914,8 @@ mod tests {
                 "chatglm",
                 false,
                 Some("/api/coding/paas/v4"),
-                false
+                false,
+                None
             ),
             "/api/coding/paas/v4/chat/completions"
         );
@@ -464,7 +928,8 @@ mod tests {
                 "chatglm",
                 false,
                 Some("api/coding/paas/v4"),
-                false
+                false,
+                None
             ),
             "/api/coding/paas/v4/chat/completions"
         );
@@ -477,7 +942,8 @@ mod tests {
                 "chatglm",
                 false,
                 Some("/api/coding/paas/v4/"),
-                false
+                false,
+                None
             ),
             "/api/coding/paas/v4/chat/completions"
         );
@@ -490,7 +956,8 @@ mod tests {
                 "gpt-4",
                 false,
                 Some("/custom/api/v2"),
-                false
+                false,
+                None
             ),
             "/custom/api/v2/chat/completions"
         );
@@ -503,7 +970,8 @@ mod tests {
                 "llama2",
                 false,
                 Some("/api/v2"),
-                false
+                false,
+                None
             ),
             "/api/v2/v1/chat/completions"
         );
@@ -521,7 +989,8 @@ mod tests {
                 "chatglm",
                 false,
                 Some("/"),
-                false
+                false,
+                None
             ),
             "/api/paas/v4/chat/completions"
         );
@@ -534,7 +1003,8 @@ mod tests {
                 "chatglm",
                 false,
                 None,
-                false
+                false,
+                None
             ),
             "/api/paas/v4/chat/completions"
         );
@@ -552,7 +1022,8 @@ mod tests {
                 "us.amazon.nova-pro-v1:0",
                 false,
                 None,
-                false
+                false,
+                None
             ),
             "/model/us.amazon.nova-pro-v1:0/converse"
         );
@@ -565,7 +1036,8 @@ mod tests {
                 "us.amazon.nova-pro-v1:0",
                 true,
                 None,
-                false
+                false,
+                None
             ),
             "/model/us.amazon.nova-pro-v1:0/converse-stream"
         );
@@ -578,7 +1050,8 @@ mod tests {
                 "us.amazon.nova-pro-v1:0",
                 false,
                 Some("/custom/path"),
-                false
+                false,
+                None
             ),
             "/custom/path/model/us.amazon.nova-pro-v1:0/converse"
         );
@@ -591,12 +1064,132 @@ mod tests {
                 "us.amazon.nova-pro-v1:0",
                 true,
                 Some("/custom/path"),
-                false
+                false,
+                None
             ),
             "/custom/path/model/us.amazon.nova-pro-v1:0/converse-stream"
         );
     }
 
+    #[test]
+    fn test_vertex_ai_endpoints() {
+        let prefix = Some("/v1/projects/my-project/locations/us-central1");
+        let chat = SupportedAPIsFromClient::OpenAIChatCompletions(OpenAIApi::ChatCompletions);
+        assert_eq!(
+            chat.target_endpoint_for_provider(
+                &ProviderId::VertexAI,
+                "/v1/chat/completions",
+                "gemini-2.5-flash",
+                false,
+                prefix,
+                false,
+                None
+            ),
+            "/v1/projects/my-project/locations/us-central1/publishers/google/models/gemini-2.5-flash:generateContent"
+        );
+        assert_eq!(
+            chat.target_endpoint_for_provider(
+                &ProviderId::VertexAI,
+                "/v1/chat/completions",
+                "google/gemini-2.5-flash",
+                true,
+                prefix,
+                false,
+                None
+            ),
+            "/v1/projects/my-project/locations/us-central1/publishers/google/models/gemini-2.5-flash:streamGenerateContent?alt=sse"
+        );
+
+        let messages = SupportedAPIsFromClient::AnthropicMessagesAPI(AnthropicApi::Messages);
+        assert_eq!(
+            messages.target_endpoint_for_provider(
+                &ProviderId::VertexAI,
+                "/v1/messages",
+                "google/gemini-2.5-flash",
+                true,
+                prefix,
+                false,
+                None
+            ),
+            "/v1/projects/my-project/locations/us-central1/endpoints/openapi/chat/completions"
+        );
+    }
+
+    #[test]
+    fn test_cohere_endpoints() {
+        let chat = SupportedAPIsFromClient::OpenAIChatCompletions(OpenAIApi::ChatCompletions);
+        for is_streaming in [false, true] {
+            assert_eq!(
+                chat.target_endpoint_for_provider(
+                    &ProviderId::Cohere,
+                    "/v1/chat/completions",
+                    "command-a-03-2025",
+                    is_streaming,
+                    None,
+                    false,
+                    None
+                ),
+                "/v2/chat"
+            );
+        }
+
+        let messages = SupportedAPIsFromClient::AnthropicMessagesAPI(AnthropicApi::Messages);
+        assert_eq!(
+            messages.target_endpoint_for_provider(
+                &ProviderId::Cohere,
+                "/v1/messages",
+                "command-a-03-2025",
+                false,
+                None,
+                false,
+                None
+            ),
+            "/compatibility/v1/chat/completions"
+        );
+        assert_eq!(
+            SupportedUpstreamAPIs::from_endpoint("/v2/chat"),
+            Some(SupportedUpstreamAPIs::CohereChat(CohereApi::Chat))
+        );
+    }
+
+    #[test]
+    fn test_ollama_endpoints() {
+        let chat = SupportedAPIsFromClient::OpenAIChatCompletions(OpenAIApi::ChatCompletions);
+        for is_streaming in [false, true] {
+            assert_eq!(
+                chat.target_endpoint_for_provider(
+                    &ProviderId::Ollama,
+                    "/v1/chat/completions",
+                    "llama3.2",
+                    is_streaming,
+                    None,
+                    false,
+                    None
+                ),
+                "/api/chat"
+            );
+        }
+
+        // Messages clients keep using the OpenAI-compatible endpoint
+        let messages = SupportedAPIsFromClient::AnthropicMessagesAPI(AnthropicApi::Messages);
+        assert_eq!(
+            messages.target_endpoint_for_provider(
+                &ProviderId::Ollama,
+                "/v1/messages",
+                "llama3.2",
+                false,
+                None,
+                false,
+                None
+            ),
+            "/v1/chat/completions"
+        );
+        assert_eq!(
+            SupportedUpstreamAPIs::from_endpoint("/api/chat"),
+            Some(SupportedUpstreamAPIs::OllamaChat(OllamaApi::Chat))
+        );
+    }
+
     #[test]
     fn test_anthropic_messages_endpoint() {
         let api = SupportedAPIsFromClient::AnthropicMessagesAPI(AnthropicApi::Messages);
@@ -609,7 +1202,8 @@ mod tests {
                 "claude-3-opus",
                 false,
                 None,
-                false
+                false,
+                None
             ),
             "/v1/messages"
         );
@@ -622,7 +1216,8 @@ mod tests {
                 "claude-3-opus",
                 false,
                 Some("/api/v2"),
-                false
+                false,
+                None
             ),
             "/api/v2/messages"
         );
@@ -640,7 +1235,8 @@ mod tests {
                 "llama2",
                 false,
                 None,
-                false
+                false,
+                None
             ),
             "/v1/chat/completions"
         );
@@ -653,7 +1249,8 @@ mod tests {
                 "chatglm",
                 false,
                 None,
-                false
+                false,
+                None
             ),
             "/v1/chat/completions"
         );
@@ -666,7 +1263,8 @@ mod tests {
                 "chatglm",
                 false,
                 Some("/api/v2"),
-                false
+                false,
+                None
             ),
             "/api/v2/chat/completions"
         );
@@ -684,7 +1282,8 @@ mod tests {
                 "gpt-4-deployment",
                 false,
                 None,
-                false
+                false,
+                None
             ),
             "/openai/deployments/gpt-4-deployment/chat/completions?api-version=2025-01-01-preview"
         );
@@ -697,10 +1296,25 @@ mod tests {
                 "gpt-4-deployment",
                 false,
                 Some("/custom/azure/path"),
-                false
+                false,
+                None
             ),
             "/custom/azure/path/gpt-4-deployment/chat/completions?api-version=2025-01-01-preview"
         );
+
+        // Configured api-version replaces the default
+        assert_eq!(
+            api.target_endpoint_for_provider(
+                &ProviderId::AzureOpenAI,
+                "/v1/chat/completions",
+                "prod-gpt-4o",
+                true,
+                None,
+                false,
+                Some("2024-10-21")
+            ),
+            "/openai/deployments/prod-gpt-4o/chat/completions?api-version=2024-10-21"
+        );
     }
 
     #[test]
@@ -713,9 +1327,72 @@ mod tests {
                 "grok-4-1-fast-reasoning",
                 false,
                 None,
-                false
+                false,
+                None
             ),
             "/v1/responses"
         );
     }
+
+    #[test]
+    fn test_vertex_anthropic_paths() {
+        let messages = SupportedUpstreamAPIs::AnthropicMessagesAPI(AnthropicApi::Messages);
+        assert_eq!(
+            messages.vertex_anthropic_path(
+                &ProviderId::VertexAI,
+                "claude-sonnet-4@20250514",
+                false,
+                Some("/v1/projects/my-project/locations/us-east5"),
+            ),
+            Some(
+                "/v1/projects/my-project/locations/us-east5/publishers/anthropic/models/claude-sonnet-4@20250514:rawPredict"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            messages.vertex_anthropic_path(
+                &ProviderId::VertexAI,
+                "anthropic/claude-sonnet-4@20250514",
+                true,
+                None
+            ),
+            Some(
+                "/publishers/anthropic/models/claude-sonnet-4@20250514:streamRawPredict"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            messages.vertex_anthropic_path(&ProviderId::Anthropic, "claude-sonnet-4", false, None),
+            None
+        );
+    }
+
+    #[test]
+    fn test_bedrock_invoke_model_paths() {
+        let invoke = SupportedUpstreamAPIs::AmazonBedrockInvokeModel(AmazonBedrockApi::InvokeModel);
+        assert_eq!(
+            invoke.invoke_model_path("amazon.titan-text-express-v1", None),
+            Some("/model/amazon.titan-text-express-v1/invoke".to_string())
+        );
+        let invoke_stream = SupportedUpstreamAPIs::AmazonBedrockInvokeModelStream(
+            AmazonBedrockApi::InvokeModelWithResponseStream,
+        );
+        assert_eq!(
+            invoke_stream
+                .invoke_model_path("anthropic.claude-3-haiku-20240307-v1:0", Some("/bedrock/")),
+            Some(
+                "/bedrock/model/anthropic.claude-3-haiku-20240307-v1:0/invoke-with-response-stream"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            SupportedUpstreamAPIs::from_endpoint("/model/amazon.titan-text-express-v1/invoke"),
+            Some(invoke)
+        );
+        assert_eq!(
+            SupportedUpstreamAPIs::AmazonBedrockConverse(AmazonBedrockApi::Converse)
+                .invoke_model_path("amazon.titan-text-express-v1", None),
+            None
+        );
+    }
 }
@@ -0,0 +1,445 @@
+//! Translation of upstream error payloads into the error shape of the API
+//! dialect the client spoke, so an Anthropic SDK never sees an OpenAI error
+//! body (and vice versa).
+
+use serde_json::{json, Value};
+
+use super::endpoints::{SupportedAPIsFromClient, SupportedUpstreamAPIs};
+use crate::apis::openai_responses::{
+    ResponseError, ResponseErrorCode, ResponseStatus, ResponsesAPIResponse, ResponsesAPIStreamEvent,
+};
+
+/// Error wire formats understood by the gateway.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorDialect {
+    /// `{"error": {"message", "type", "param", "code"}}`
+    OpenAI,
+    /// `{"type": "error", "error": {"type", "message"}}`
+    Anthropic,
+    /// `{"message": "..."}`
+    AmazonBedrock,
+    /// `{"error": {"code", "message", "status"}}`
+    Gemini,
+    /// `{"id", "message"}`
+    Cohere,
+    /// `{"error": "..."}`
+    Ollama,
+}
+
+impl From<&SupportedAPIsFromClient> for ErrorDialect {
+    fn from(api: &SupportedAPIsFromClient) -> Self {
+        match api {
+            SupportedAPIsFromClient::AnthropicMessagesAPI(_) => ErrorDialect::Anthropic,
+            SupportedAPIsFromClient::OpenAIChatCompletions(_)
+            | SupportedAPIsFromClient::OpenAIResponsesAPI(_) => ErrorDialect::OpenAI,
+        }
+    }
+}
+
+impl From<&SupportedUpstreamAPIs> for ErrorDialect {
+    fn from(api: &SupportedUpstreamAPIs) -> Self {
+        match api {
+            SupportedUpstreamAPIs::AnthropicMessagesAPI(_) => ErrorDialect::Anthropic,
+            SupportedUpstreamAPIs::OpenAIChatCompletions(_)
+            | SupportedUpstreamAPIs::OpenAIResponsesAPI(_) => ErrorDialect::OpenAI,
+            SupportedUpstreamAPIs::AmazonBedrockConverse(_)
+            | SupportedUpstreamAPIs::AmazonBedrockConverseStream(_)
+            | SupportedUpstreamAPIs::AmazonBedrockInvokeModel(_)
+            | SupportedUpstreamAPIs::AmazonBedrockInvokeModelStream(_) => {
+                ErrorDialect::AmazonBedrock
+            }
+            SupportedUpstreamAPIs::GeminiGenerateContent(_)
+            | SupportedUpstreamAPIs::GeminiStreamGenerateContent(_) => ErrorDialect::Gemini,
+            SupportedUpstreamAPIs::CohereChat(_) => ErrorDialect::Cohere,
+            SupportedUpstreamAPIs::OllamaChat(_) => ErrorDialect::Ollama,
+        }
+    }
+}
+
+const ANTHROPIC_ERROR_TYPES: &[&str] = &[
+    "invalid_request_error",
+    "authentication_error",
+    "permission_error",
+    "not_found_error",
+    "request_too_large",
+    "rate_limit_error",
+    "api_error",
+    "overloaded_error",
+];
+
+const OPENAI_ERROR_TYPES: &[&str] = &[
+    "invalid_request_error",
+    "authentication_error",
+    "permission_error",
+    "not_found_error",
+    "rate_limit_error",
+    "insufficient_quota",
+    "server_error",
+];
+
+/// A provider-neutral view of an upstream error.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApiError {
+    pub status: u16,
+    pub error_type: Option<String>,
+    pub message: String,
+    pub code: Option<String>,
+    pub param: Option<String>,
+}
+
+impl ApiError {
+    /// Parse an upstream error body. Accepts the OpenAI, Anthropic and Gemini
+    /// `error` object, a bare `{"error": "..."}`, Bedrock's `{"message": ...}`,
+    /// and falls back to the raw body text.
+    pub fn parse(body: &[u8], status: u16) -> Self {
+        let mut error = ApiError {
+            status,
+            error_type: None,
+            message: String::new(),
+            code: None,
+            param: None,
+        };
+
+        let value: Value = match serde_json::from_slice(body) {
+            Ok(value) => value,
+            Err(_) => {
+                error.message = String::from_utf8_lossy(body).trim().to_string();
+                return error;
+            }
+        };
+
+        match value.get("error") {
+            Some(object @ Value::Object(_)) => error.read_error_object(object),
+            Some(Value::String(message)) => error.message = message.clone(),
+            _ => {
+                error.message = as_string(value.get("message").or_else(|| value.get("Message")))
+                    .unwrap_or_else(|| value.to_string());
+                error.error_type = as_string(value.get("__type"));
+            }
+        }
+        error
+    }
+
+    /// Parse the data of an SSE event that reports an error instead of
+    /// content: an Anthropic `error` event, a Chat Completions chunk holding
+    /// an `error` object, or a Responses `error` / `response.failed` event.
+    /// Returns `None` for any other event. Streams carry no status code, so
+    /// one is derived from the error type.
+    pub fn from_stream_event(data: &str) -> Option<Self> {
+        let value: Value = serde_json::from_str(data).ok()?;
+        let object = match value.get("type").and_then(Value::as_str) {
+            Some("response.failed") => value.pointer("/response/error")?,
+            // Responses `error` events carry the fields at the top level
+            Some("error") if !value["error"].is_object() => &value,
+            _ => value.get("error").filter(|error| error.is_object())?,
+        };
+        let mut error = ApiError {
+            status: 500,
+            error_type: None,
+            message: String::new(),
+            code: None,
+            param: None,
+        };
+        error.read_error_object(object);
+        error.status = match object.get("code").and_then(Value::as_u64) {
+            Some(status @ 400..=599) => status as u16,
+            _ => [&error.error_type, &error.code]
+                .into_iter()
+                .flatten()
+                .map(String::as_str)
+                .find_map(stream_error_status)
+                .unwrap_or(500),
+        };
+        Some(error)
+    }
+
+    /// Fill in the fields of an OpenAI, Anthropic or Gemini `error` object.
+    fn read_error_object(&mut self, object: &Value) {
+        self.message = as_string(object.get("message")).unwrap_or_default();
+        // Gemini names its error class `status`
+        self.error_type = as_string(object.get("type")).or_else(|| as_string(object.get("status")));
+        self.code = as_string(object.get("code"));
+        self.param = as_string(object.get("param"));
+    }
+
+    /// Error type to report in `dialect`: the upstream type when it is
+    /// meaningful there, otherwise one derived from the status code.
+    fn error_type_for(&self, dialect: ErrorDialect) -> String {
+        let known: &[&str] = match dialect {
+            ErrorDialect::Anthropic => ANTHROPIC_ERROR_TYPES,
+            ErrorDialect::OpenAI => OPENAI_ERROR_TYPES,
+            ErrorDialect::AmazonBedrock
+            | ErrorDialect::Gemini
+            | ErrorDialect::Cohere
+            | ErrorDialect::Ollama => &[],
+        };
+        if let Some(error_type) = &self.error_type {
+            if known.contains(&error_type.as_str()) {
+                return error_type.clone();
+            }
+        }
+        let error_type = match (dialect, self.status) {
+            (_, 401) => "authentication_error",
+            (_, 403) => "permission_error",
+            (ErrorDialect::Anthropic, 404) => "not_found_error",
+            (ErrorDialect::Anthropic, 413) => "request_too_large",
+            (_, 429) => "rate_limit_error",
+            (ErrorDialect::Anthropic, 503 | 529) => "overloaded_error",
+            (ErrorDialect::Anthropic, s) if s >= 500 => "api_error",
+            (_, s) if s >= 500 => "server_error",
+            _ => "invalid_request_error",
+        };
+        error_type.to_string()
+    }
+
+    /// Render this error as a JSON body in `dialect`.
+    pub fn to_json(&self, dialect: ErrorDialect) -> Value {
+        let error_type = self.error_type_for(dialect);
+        match dialect {
+            ErrorDialect::OpenAI => json!({
+                "error": {
+                    "message": self.message,
+                    "type": error_type,
+                    "param": self.param,
+                    "code": self.code,
+                }
+            }),
+            ErrorDialect::Anthropic => json!({
+                "type": "error",
+                "error": {
+                    "type": error_type,
+                    "message": self.message,
+                }
+            }),
+            ErrorDialect::AmazonBedrock | ErrorDialect::Cohere => {
+                json!({ "message": self.message })
+            }
+            ErrorDialect::Ollama => json!({ "error": self.message }),
+            ErrorDialect::Gemini => json!({
+                "error": {
+                    "code": self.status,
+                    "message": self.message,
+                    "status": self.error_type.as_deref().unwrap_or("UNKNOWN"),
+                }
+            }),
+        }
+    }
+
+    /// Render this error as an SSE event in `dialect`, for errors that
+    /// surface after a stream has started.
+    pub fn to_sse_event(&self, dialect: ErrorDialect) -> String {
+        let data = self.to_json(dialect);
+        match dialect {
+            ErrorDialect::Anthropic => format!("event: error\ndata: {}\n\n", data),
+            ErrorDialect::OpenAI
+            | ErrorDialect::AmazonBedrock
+            | ErrorDialect::Gemini
+            | ErrorDialect::Cohere
+            | ErrorDialect::Ollama => format!("data: {}\n\n", data),
+        }
+    }
+
+    /// The `error` of a failed Responses API response.
+    pub fn response_error(&self) -> ResponseError {
+        ResponseError {
+            code: match self.status {
+                429 => ResponseErrorCode::RateLimitExceeded,
+                _ => ResponseErrorCode::ServerError,
+            },
+            message: self.message.clone(),
+        }
+    }
+
+    /// The events that end a stream with this error for `client_api`: an
+    /// `error` event for Anthropic Messages, an error chunk followed by
+    /// `[DONE]` for Chat Completions, and `response.failed` for Responses.
+    pub fn to_stream_events(&self, client_api: &SupportedAPIsFromClient) -> String {
+        match client_api {
+            SupportedAPIsFromClient::AnthropicMessagesAPI(_) => {
+                self.to_sse_event(ErrorDialect::Anthropic)
+            }
+            SupportedAPIsFromClient::OpenAIChatCompletions(_) => {
+                format!(
+                    "{}data: [DONE]\n\n",
+                    self.to_sse_event(ErrorDialect::OpenAI)
+                )
+            }
+            SupportedAPIsFromClient::OpenAIResponsesAPI(_) => {
+                let mut response =
+                    ResponsesAPIResponse::in_progress(String::new(), "unknown".to_string(), 0);
+                response.status = ResponseStatus::Failed;
+                response.error = Some(self.response_error());
+                ResponsesAPIStreamEvent::ResponseFailed {
+                    response,
+                    sequence_number: 0,
+                }
+                .into()
+            }
+        }
+    }
+}
+
+/// A JSON field as text; non-string values keep their JSON form.
+fn as_string(value: Option<&Value>) -> Option<String> {
+    match value? {
+        Value::String(s) => Some(s.clone()),
+        Value::Null => None,
+        other => Some(other.to_string()),
+    }
+}
+
+/// Status code implied by the type or code of an error reported mid-stream.
+fn stream_error_status(name: &str) -> Option<u16> {
+    Some(match name {
+        "invalid_request_error" => 400,
+        "authentication_error" => 401,
+        "permission_error" => 403,
+        "not_found_error" => 404,
+        "request_too_large" => 413,
+        "rate_limit_error" | "rate_limit_exceeded" | "RESOURCE_EXHAUSTED" => 429,
+        "overloaded_error" => 529,
+        _ => return None,
+    })
+}
+
+/// Reshape an upstream error body into the client's dialect.
+///
+/// Returns `None` when the upstream already speaks the client's dialect and
+/// the body should be forwarded unchanged.
+pub fn translate_error_body(
+    body: &[u8],
+    status: u16,
+    upstream_api: &SupportedUpstreamAPIs,
+    client_api: &SupportedAPIsFromClient,
+) -> Option<Vec<u8>> {
+    let upstream_dialect = ErrorDialect::from(upstream_api);
+    let client_dialect = ErrorDialect::from(client_api);
+    if upstream_dialect == client_dialect {
+        return None;
+    }
+    let error = ApiError::parse(body, status);
+    serde_json::to_vec(&error.to_json(client_dialect)).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::apis::{AmazonBedrockApi, AnthropicApi, GeminiApi, OpenAIApi};
+
+    fn messages_client() -> SupportedAPIsFromClient {
+        SupportedAPIsFromClient::AnthropicMessagesAPI(AnthropicApi::Messages)
+    }
+
+    fn chat_client() -> SupportedAPIsFromClient {
+        SupportedAPIsFromClient::OpenAIChatCompletions(OpenAIApi::ChatCompletions)
+    }
+
+    #[test]
+    fn openai_error_to_anthropic() {
+        let body = br#"{"error":{"message":"Rate limit reached","type":"requests","param":null,"code":"rate_limit_exceeded"}}"#;
+        let upstream = SupportedUpstreamAPIs::OpenAIChatCompletions(OpenAIApi::ChatCompletions);
+        let translated = translate_error_body(body, 429, &upstream, &messages_client()).unwrap();
+        let value: Value = serde_json::from_slice(&translated).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "type": "error",
+                "error": {"type": "rate_limit_error", "message": "Rate limit reached"}
+            })
+        );
+    }
+
+    #[test]
+    fn anthropic_error_to_openai() {
+        let body =
+            br#"{"type":"error","error":{"type":"overloaded_error","message":"Overloaded"}}"#;
+        let upstream = SupportedUpstreamAPIs::AnthropicMessagesAPI(AnthropicApi::Messages);
+        let translated = translate_error_body(body, 529, &upstream, &chat_client()).unwrap();
+        let value: Value = serde_json::from_slice(&translated).unwrap();
+        assert_eq!(value["error"]["message"], "Overloaded");
+        assert_eq!(value["error"]["type"], "server_error");
+    }
+
+    #[test]
+    fn bedrock_and_plain_text_errors() {
+        let upstream = SupportedUpstreamAPIs::AmazonBedrockConverse(AmazonBedrockApi::Converse);
+        let translated = translate_error_body(
+            br#"{"message":"The security token included in the request is invalid."}"#,
+            403,
+            &upstream,
+            &messages_client(),
+        )
+        .unwrap();
+        let value: Value = serde_json::from_slice(&translated).unwrap();
+        assert_eq!(value["error"]["type"], "permission_error");
+        assert_eq!(
+            value["error"]["message"],
+            "The security token included in the request is invalid."
+        );
+
+        let error = ApiError::parse(b"upstream connect error", 503);
+        assert_eq!(error.message, "upstream connect error");
+        assert_eq!(
+            error.to_json(ErrorDialect::Anthropic)["error"]["type"],
+            "overloaded_error"
+        );
+    }
+
+    #[test]
+    fn same_dialect_is_passthrough() {
+        let upstream = SupportedUpstreamAPIs::OpenAIChatCompletions(OpenAIApi::ChatCompletions);
+        assert!(translate_error_body(b"{}", 400, &upstream, &chat_client()).is_none());
+        let responses = SupportedAPIsFromClient::OpenAIResponsesAPI(OpenAIApi::Responses);
+        assert!(translate_error_body(b"{}", 400, &upstream, &responses).is_none());
+    }
+
+    #[test]
+    fn sse_error_event_shapes() {
+        let error = ApiError::parse(br#"{"error":{"message":"boom"}}"#, 500);
+        let anthropic = error.to_sse_event(ErrorDialect::Anthropic);
+        assert!(anthropic.starts_with("event: error\ndata: "));
+        assert!(anthropic.contains(r#""type":"api_error""#));
+        let openai = error.to_sse_event(ErrorDialect::OpenAI);
+        assert!(openai.starts_with("data: {\"error\""));
+        assert!(openai.ends_with("\n\n"));
+    }
+
+    #[test]
+    fn stream_error_events_are_recognized() {
+        let anthropic = ApiError::from_stream_event(
+            r#"{"type":"error","error":{"type":"overloaded_error","message":"Overloaded"}}"#,
+        )
+        .unwrap();
+        assert_eq!(anthropic.status, 529);
+        assert_eq!(anthropic.message, "Overloaded");
+
+        let responses = ApiError::from_stream_event(
+            r#"{"type":"error","code":"rate_limit_exceeded","message":"Slow down","sequence_number":3}"#,
+        )
+        .unwrap();
+        assert_eq!(responses.status, 429);
+
+        let failed = ApiError::from_stream_event(
+            r#"{"type":"response.failed","response":{"status":"failed","error":{"code":"server_error","message":"boom"}}}"#,
+        )
+        .unwrap();
+        assert_eq!(failed.message, "boom");
+        assert_eq!(failed.status, 500);
+
+        assert!(ApiError::from_stream_event(
+            r#"{"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"hi"}}"#
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn gemini_error_to_openai() {
+        let body =
+            br#"{"error":{"code":429,"message":"Quota exceeded","status":"RESOURCE_EXHAUSTED"}}"#;
+        let upstream = SupportedUpstreamAPIs::GeminiGenerateContent(GeminiApi::GenerateContent);
+        let translated = translate_error_body(body, 429, &upstream, &chat_client()).unwrap();
+        let value: Value = serde_json::from_slice(&translated).unwrap();
+        assert_eq!(value["error"]["type"], "rate_limit_error");
+        assert_eq!(value["error"]["message"], "Quota exceeded");
+    }
+}
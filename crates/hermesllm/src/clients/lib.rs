@@ -19,6 +19,8 @@ pub enum TransformError {
     MissingField(String),
     #[error("Unsupported conversion: {0}")]
     UnsupportedConversion(String),
+    #[error("Conversion would drop: {}", dropped.join(", "))]
+    LossyConversion { dropped: Vec<String> },
 }
 
 #[cfg(test)]
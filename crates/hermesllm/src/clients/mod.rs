@@ -1,8 +1,14 @@
+#[cfg(feature = "aws-sigv4")]
+pub mod aws_sigv4;
 pub mod endpoints;
+pub mod errors;
+#[cfg(feature = "gcp-auth")]
+pub mod gcp_auth;
 pub mod lib;
 
 // Re-export the main items for easier access
 pub use endpoints::*;
+pub use errors::*;
 pub use lib::*;
 
 // Note: transformer module contains TryFrom trait implementations that are automatically available
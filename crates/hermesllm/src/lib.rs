@@ -7,7 +7,7 @@ pub mod providers;
 pub mod transforms;
 // Re-export important types and traits
 pub use apis::streaming_shapes::amazon_bedrock_binary_frame::BedrockBinaryFrameDecoder;
-pub use apis::streaming_shapes::sse::{SseEvent, SseStreamIter};
+pub use apis::streaming_shapes::sse::{SseEvent, SseEventStream, SseStreamIter};
 pub use aws_smithy_eventstream::frame::DecodedFrame;
 pub use providers::id::ProviderId;
 pub use providers::request::{ProviderRequest, ProviderRequestError, ProviderRequestType};
@@ -18,8 +18,14 @@ pub use providers::streaming_response::{ProviderStreamResponse, ProviderStreamRe
 
 //TODO: Refactor such that commons doesn't depend on Hermes. For now this will clean up strings
 pub const CHAT_COMPLETIONS_PATH: &str = "/v1/chat/completions";
+pub const COMPLETIONS_PATH: &str = "/v1/completions";
 pub const OPENAI_RESPONSES_API_PATH: &str = "/v1/responses";
 pub const MESSAGES_PATH: &str = "/v1/messages";
+pub const EMBEDDINGS_PATH: &str = "/v1/embeddings";
+pub const IMAGES_GENERATIONS_PATH: &str = "/v1/images/generations";
+pub const AUDIO_TRANSCRIPTIONS_PATH: &str = "/v1/audio/transcriptions";
+pub const MODERATIONS_PATH: &str = "/v1/moderations";
+pub const REALTIME_PATH: &str = "/v1/realtime";
 
 #[cfg(test)]
 mod tests {
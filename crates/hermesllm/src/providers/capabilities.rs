@@ -0,0 +1,424 @@
+//! What each upstream and model accepts.
+//!
+//! Upstreams answer out-of-range sampling parameters with a 400, and clients
+//! written against one provider routinely send values another rejects, e.g. a
+//! temperature of 1.5 to Anthropic, which takes 0–1. [`SamplingLimits`] holds
+//! what an upstream accepts; [`ProviderRequestType::clamp_sampling_params`]
+//! brings a request within it before it goes out.
+//!
+//! [`ProviderCapabilities`] describes what a provider serves a model with,
+//! for routers picking a model that can take a request and for model
+//! listings.
+
+use std::ops::RangeInclusive;
+
+use serde::{Deserialize, Serialize};
+
+use crate::apis::amazon_bedrock::{InvokeModelBody, InvokeModelFamily, InvokeModelRequest};
+use crate::apis::openai::{ChatCompletionsRequest, ContentPart, MessageContent, OpenAIApi};
+use crate::clients::endpoints::{SupportedAPIsFromClient, SupportedUpstreamAPIs};
+use crate::providers::id::ProviderId;
+use crate::providers::request::ProviderRequestType;
+
+/// Context windows of model families, matched in order against model names
+const CONTEXT_WINDOWS: &[(&str, u32)] = &[
+    ("gpt-4.1", 1_047_576),
+    ("gpt-5", 400_000),
+    ("gpt-4o", 128_000),
+    ("gpt-4-turbo", 128_000),
+    ("gpt-3.5-turbo", 16_385),
+    ("claude", 200_000),
+    ("gemini", 1_048_576),
+    ("grok-4", 256_000),
+    ("grok-3", 131_072),
+    ("deepseek", 128_000),
+    ("mistral-large", 128_000),
+    ("mistral-medium", 128_000),
+    ("command-a", 256_000),
+    ("command-r", 128_000),
+    ("nova-premier", 1_000_000),
+    ("nova-pro", 300_000),
+    ("nova-lite", 300_000),
+    ("nova-micro", 128_000),
+];
+
+/// Model families that take image input
+const VISION_FAMILIES: &[&str] = &[
+    "gpt-4o",
+    "gpt-4.1",
+    "gpt-4-turbo",
+    "gpt-5",
+    "claude",
+    "gemini",
+    "grok-4",
+    "pixtral",
+    "mistral-medium",
+    "mistral-small",
+    "llama-4",
+    "llava",
+    "vision",
+    "nova-premier",
+    "nova-pro",
+    "nova-lite",
+    "qwen-vl",
+    "glm-4.5v",
+];
+
+/// Model families that can't call tools
+const NO_TOOL_FAMILIES: &[&str] = &["o1-mini", "gemma", "titan", "-tts", "embed"];
+
+/// Sampling parameter ranges and limits of an upstream
+#[derive(Debug, Clone, PartialEq)]
+pub struct SamplingLimits {
+    /// Accepted temperatures; `None` when the model takes none
+    pub temperature: Option<RangeInclusive<f32>>,
+    /// Accepted top_p values; `None` when the model takes none
+    pub top_p: Option<RangeInclusive<f32>>,
+    /// Most stop sequences a request may carry
+    pub max_stop_sequences: Option<usize>,
+    /// Whether temperature and top_p may be set together
+    pub temperature_with_top_p: bool,
+}
+
+impl SamplingLimits {
+    const OPENAI: Self = Self {
+        temperature: Some(0.0..=2.0),
+        top_p: Some(0.0..=1.0),
+        max_stop_sequences: None,
+        temperature_with_top_p: true,
+    };
+
+    /// What `provider_id` accepts for `model` on `upstream_api`
+    pub fn for_upstream(
+        provider_id: ProviderId,
+        upstream_api: &SupportedUpstreamAPIs,
+        model: &str,
+    ) -> Self {
+        let unit_temperature = Self {
+            temperature: Some(0.0..=1.0),
+            ..Self::OPENAI
+        };
+        match upstream_api {
+            // Anthropic advises against setting both, and its newer models
+            // reject it
+            SupportedUpstreamAPIs::AnthropicMessagesAPI(_) => Self {
+                temperature_with_top_p: false,
+                ..unit_temperature
+            },
+            SupportedUpstreamAPIs::AmazonBedrockInvokeModel(_)
+            | SupportedUpstreamAPIs::AmazonBedrockInvokeModelStream(_) => {
+                match InvokeModelFamily::from_model_id(model) {
+                    Some(InvokeModelFamily::Anthropic) => Self {
+                        temperature_with_top_p: false,
+                        ..unit_temperature
+                    },
+                    _ => unit_temperature,
+                }
+            }
+            SupportedUpstreamAPIs::AmazonBedrockConverse(_)
+            | SupportedUpstreamAPIs::AmazonBedrockConverseStream(_) => Self {
+                max_stop_sequences: Some(4),
+                ..unit_temperature
+            },
+            SupportedUpstreamAPIs::GeminiGenerateContent(_)
+            | SupportedUpstreamAPIs::GeminiStreamGenerateContent(_) => Self {
+                max_stop_sequences: Some(5),
+                ..Self::OPENAI
+            },
+            SupportedUpstreamAPIs::CohereChat(_) => Self {
+                top_p: Some(0.01..=0.99),
+                max_stop_sequences: Some(5),
+                ..unit_temperature
+            },
+            SupportedUpstreamAPIs::OllamaChat(_) => Self {
+                temperature: Some(0.0..=f32::MAX),
+                ..Self::OPENAI
+            },
+            SupportedUpstreamAPIs::OpenAIChatCompletions(_)
+            | SupportedUpstreamAPIs::OpenAIResponsesAPI(_) => {
+                let limits = match provider_id {
+                    ProviderId::OpenAI
+                    | ProviderId::AzureOpenAI
+                    | ProviderId::GitHub
+                    | ProviderId::Groq => Self {
+                        max_stop_sequences: Some(4),
+                        ..Self::OPENAI
+                    },
+                    ProviderId::Moonshotai | ProviderId::Zhipu => unit_temperature,
+                    _ => Self::OPENAI,
+                };
+                // o-series models sample at fixed settings
+                if is_o_series(model) {
+                    return Self {
+                        temperature: None,
+                        top_p: None,
+                        ..limits
+                    };
+                }
+                limits
+            }
+        }
+    }
+}
+
+/// What a provider serves a model with
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProviderCapabilities {
+    /// Context window in tokens, when known
+    pub max_context_tokens: Option<u32>,
+    pub supports_tools: bool,
+    pub supports_vision: bool,
+    pub supports_streaming: bool,
+    /// Whether `response_format` reaches the model
+    pub supports_json_mode: bool,
+    /// Most stop sequences a request may carry
+    pub max_stop_sequences: Option<usize>,
+}
+
+impl ProviderCapabilities {
+    /// What `provider_id` serves `model` with, going by the model's family
+    pub fn for_model(provider_id: ProviderId, model: &str) -> Self {
+        let chat_api = provider_id.compatible_api_for_client(
+            &SupportedAPIsFromClient::OpenAIChatCompletions(OpenAIApi::ChatCompletions),
+            false,
+        );
+        let name = model.rsplit('/').next().unwrap_or_default().to_lowercase();
+        let in_family = |families: &[&str]| families.iter().any(|f| name.contains(f));
+        // Deepgram only does speech
+        let chat = provider_id != ProviderId::Deepgram;
+        Self {
+            max_context_tokens: if is_o_series(&name) {
+                Some(200_000)
+            } else {
+                CONTEXT_WINDOWS
+                    .iter()
+                    .find(|(family, _)| name.contains(family))
+                    .map(|(_, window)| *window)
+            },
+            supports_tools: chat && !in_family(NO_TOOL_FAMILIES),
+            supports_vision: chat
+                && (in_family(VISION_FAMILIES) || (is_o_series(&name) && !name.contains("mini"))),
+            supports_streaming: chat,
+            // Anthropic and Bedrock have no JSON mode to map `response_format` to
+            supports_json_mode: chat
+                && !matches!(
+                    provider_id,
+                    ProviderId::Anthropic | ProviderId::AmazonBedrock
+                ),
+            max_stop_sequences: SamplingLimits::for_upstream(provider_id, &chat_api, model)
+                .max_stop_sequences,
+        }
+    }
+
+    /// Whether the model can take `request`: its tools, images and
+    /// `response_format`
+    pub fn can_serve(&self, request: &ChatCompletionsRequest) -> bool {
+        let needs_tools = request
+            .tools
+            .as_ref()
+            .is_some_and(|tools| !tools.is_empty());
+        let needs_vision = request.messages.iter().any(|message| {
+            matches!(&message.content, Some(MessageContent::Parts(parts))
+                if parts.iter().any(|part| matches!(part, ContentPart::ImageUrl { .. })))
+        });
+        let needs_json_mode = request
+            .response_format
+            .as_ref()
+            .is_some_and(|format| format.get("type").and_then(|t| t.as_str()) != Some("text"));
+        (!needs_tools || self.supports_tools)
+            && (!needs_vision || self.supports_vision)
+            && (!needs_json_mode || self.supports_json_mode)
+    }
+}
+
+impl ProviderId {
+    /// What this provider serves `model` with
+    pub fn capabilities(&self, model: &str) -> ProviderCapabilities {
+        ProviderCapabilities::for_model(*self, model)
+    }
+}
+
+impl ProviderRequestType {
+    /// Bring temperature, top_p and stop sequences within `limits`, dropping
+    /// what the upstream doesn't take. Returns a note for each change.
+    pub fn clamp_sampling_params(&mut self, limits: &SamplingLimits) -> Vec<String> {
+        let Some((temperature, top_p, stop)) = self.sampling_params_mut() else {
+            return Vec::new();
+        };
+        let mut changes = Vec::new();
+        clamp_param(
+            "temperature",
+            temperature,
+            &limits.temperature,
+            &mut changes,
+        );
+        clamp_param("top_p", top_p, &limits.top_p, &mut changes);
+        if !limits.temperature_with_top_p && temperature.is_some() && top_p.take().is_some() {
+            changes.push("dropped top_p, which can't be set with temperature".to_string());
+        }
+        if let (Some(stop), Some(max)) = (stop, limits.max_stop_sequences) {
+            if stop.len() > max {
+                changes.push(format!(
+                    "kept the first {} of {} stop sequences",
+                    max,
+                    stop.len()
+                ));
+                stop.truncate(max);
+            }
+        }
+        changes
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn sampling_params_mut(
+        &mut self,
+    ) -> Option<(&mut Option<f32>, &mut Option<f32>, Option<&mut Vec<String>>)> {
+        match self {
+            Self::ChatCompletionsRequest(r) => {
+                Some((&mut r.temperature, &mut r.top_p, r.stop.as_mut()))
+            }
+            Self::MessagesRequest(r) => {
+                Some((&mut r.temperature, &mut r.top_p, r.stop_sequences.as_mut()))
+            }
+            Self::ResponsesAPIRequest(r) => Some((&mut r.temperature, &mut r.top_p, None)),
+            Self::BedrockConverse(r) | Self::BedrockConverseStream(r) => {
+                let c = r.inference_config.as_mut()?;
+                Some((&mut c.temperature, &mut c.top_p, c.stop_sequences.as_mut()))
+            }
+            Self::BedrockInvokeModel(InvokeModelRequest { body, .. }) => match body {
+                InvokeModelBody::Anthropic(r) => {
+                    Some((&mut r.temperature, &mut r.top_p, r.stop_sequences.as_mut()))
+                }
+                InvokeModelBody::TitanText(r) => {
+                    let c = r.text_generation_config.as_mut()?;
+                    Some((&mut c.temperature, &mut c.top_p, c.stop_sequences.as_mut()))
+                }
+            },
+            Self::GeminiGenerateContent(r) => {
+                let c = r.generation_config.as_mut()?;
+                Some((&mut c.temperature, &mut c.top_p, c.stop_sequences.as_mut()))
+            }
+            Self::CohereChat(r) => Some((&mut r.temperature, &mut r.p, r.stop_sequences.as_mut())),
+            Self::OllamaChat(r) => {
+                let o = r.options.as_mut()?;
+                Some((&mut o.temperature, &mut o.top_p, o.stop.as_mut()))
+            }
+        }
+    }
+}
+
+fn is_o_series(model: &str) -> bool {
+    let model = model.rsplit('/').next().unwrap_or_default();
+    ["o1", "o3", "o4"].iter().any(|p| model.starts_with(p))
+}
+
+fn clamp_param(
+    name: &str,
+    value: &mut Option<f32>,
+    range: &Option<RangeInclusive<f32>>,
+    changes: &mut Vec<String>,
+) {
+    let Some(current) = *value else {
+        return;
+    };
+    match range {
+        None => {
+            *value = None;
+            changes.push(format!("dropped {}, which the model doesn't take", name));
+        }
+        Some(range) if !range.contains(&current) => {
+            let clamped = current.clamp(*range.start(), *range.end());
+            *value = Some(clamped);
+            changes.push(format!("{} {} clamped to {}", name, current, clamped));
+        }
+        Some(_) => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::apis::anthropic::AnthropicApi;
+    use crate::apis::openai::{ChatCompletionsRequest, OpenAIApi};
+    use serde_json::json;
+
+    fn chat_request(body: serde_json::Value) -> ProviderRequestType {
+        let req: ChatCompletionsRequest = serde_json::from_value(body).unwrap();
+        ProviderRequestType::ChatCompletionsRequest(req)
+    }
+
+    #[test]
+    fn clamps_to_anthropic_ranges() {
+        let mut request = chat_request(json!({
+            "model": "claude-sonnet-4-5",
+            "messages": [{"role": "user", "content": "hi"}],
+            "temperature": 1.5,
+            "top_p": 0.9
+        }));
+        let limits = SamplingLimits::for_upstream(
+            ProviderId::Anthropic,
+            &SupportedUpstreamAPIs::AnthropicMessagesAPI(AnthropicApi::Messages),
+            "claude-sonnet-4-5",
+        );
+
+        let changes = request.clamp_sampling_params(&limits);
+        assert_eq!(changes.len(), 2);
+        let ProviderRequestType::ChatCompletionsRequest(req) = request else {
+            panic!("expected chat request");
+        };
+        assert_eq!(req.temperature, Some(1.0));
+        assert_eq!(req.top_p, None);
+    }
+
+    #[test]
+    fn truncates_stop_sequences_and_drops_unsupported_params() {
+        let mut request = chat_request(json!({
+            "model": "o3-mini",
+            "messages": [{"role": "user", "content": "hi"}],
+            "temperature": 0.2,
+            "stop": ["a", "b", "c", "d", "e"]
+        }));
+        let limits = SamplingLimits::for_upstream(
+            ProviderId::OpenAI,
+            &SupportedUpstreamAPIs::OpenAIChatCompletions(OpenAIApi::ChatCompletions),
+            "o3-mini",
+        );
+
+        request.clamp_sampling_params(&limits);
+        let ProviderRequestType::ChatCompletionsRequest(req) = request else {
+            panic!("expected chat request");
+        };
+        assert_eq!(req.temperature, None);
+        assert_eq!(req.stop.unwrap(), vec!["a", "b", "c", "d"]);
+    }
+
+    #[test]
+    fn capabilities_follow_the_model_family() {
+        let claude = ProviderId::Anthropic.capabilities("claude-sonnet-4-5");
+        assert_eq!(claude.max_context_tokens, Some(200_000));
+        assert!(claude.supports_tools && claude.supports_vision);
+        assert!(!claude.supports_json_mode);
+
+        let mini = ProviderId::OpenAI.capabilities("openai/o1-mini");
+        assert!(!mini.supports_tools && !mini.supports_vision);
+        assert_eq!(mini.max_stop_sequences, Some(4));
+
+        let gemma = ProviderId::Gemini.capabilities("gemma-3-27b-it");
+        assert_eq!(gemma.max_stop_sequences, Some(5));
+        assert!(!gemma.supports_tools);
+
+        let image_request: ChatCompletionsRequest = serde_json::from_value(json!({
+            "model": "deepseek-chat",
+            "messages": [{"role": "user", "content": [
+                {"type": "image_url", "image_url": {"url": "https://example.com/a.png"}}
+            ]}]
+        }))
+        .unwrap();
+        assert!(!ProviderId::Deepseek
+            .capabilities("deepseek-chat")
+            .can_serve(&image_request));
+        assert!(ProviderId::OpenAI
+            .capabilities("gpt-4o")
+            .can_serve(&image_request));
+    }
+}
@@ -1,4 +1,4 @@
-use crate::apis::{AmazonBedrockApi, AnthropicApi, OpenAIApi};
+use crate::apis::{AmazonBedrockApi, AnthropicApi, CohereApi, GeminiApi, OllamaApi, OpenAIApi};
 use crate::clients::endpoints::{SupportedAPIsFromClient, SupportedUpstreamAPIs};
 use serde::Deserialize;
 use std::collections::HashMap;
@@ -33,6 +33,8 @@ pub enum ProviderId {
     Deepseek,
     Groq,
     Gemini,
+    /// Gemini models on Google Cloud, authenticated with OAuth2 access tokens.
+    VertexAI,
     Anthropic,
     GitHub,
     Plano,
@@ -45,6 +47,11 @@ pub enum ProviderId {
     Qwen,
     AmazonBedrock,
     DigitalOcean,
+    Cohere,
+    /// Speech-to-text only, through its pre-recorded `/v1/listen` API.
+    Deepgram,
+    /// Canned responses served by the gateway itself, for tests.
+    Mock,
 }
 
 impl TryFrom<&str> for ProviderId {
@@ -59,6 +66,8 @@ impl TryFrom<&str> for ProviderId {
             "groq" => Ok(ProviderId::Groq),
             "gemini" => Ok(ProviderId::Gemini),
             "google" => Ok(ProviderId::Gemini), // alias
+            "vertex_ai" => Ok(ProviderId::VertexAI),
+            "vertex" => Ok(ProviderId::VertexAI), // alias
             "anthropic" => Ok(ProviderId::Anthropic),
             "github" => Ok(ProviderId::GitHub),
             "plano" => Ok(ProviderId::Plano),
@@ -75,6 +84,9 @@ impl TryFrom<&str> for ProviderId {
             "digitalocean" => Ok(ProviderId::DigitalOcean),
             "do" => Ok(ProviderId::DigitalOcean),    // alias
             "do_ai" => Ok(ProviderId::DigitalOcean), // alias
+            "cohere" => Ok(ProviderId::Cohere),
+            "deepgram" => Ok(ProviderId::Deepgram),
+            "mock" => Ok(ProviderId::Mock),
             _ => Err(format!("Unknown provider: {}", value)),
         }
     }
@@ -88,7 +100,7 @@ impl ProviderId {
             ProviderId::AmazonBedrock => "amazon",
             ProviderId::AzureOpenAI => "openai",
             ProviderId::TogetherAI => "together",
-            ProviderId::Gemini => "google",
+            ProviderId::Gemini | ProviderId::VertexAI => "google",
             ProviderId::OpenAI => "openai",
             ProviderId::Xiaomi => "xiaomi",
             ProviderId::Anthropic => "anthropic",
@@ -124,6 +136,17 @@ impl ProviderId {
         is_streaming: bool,
     ) -> SupportedUpstreamAPIs {
         match (self, client_api) {
+            // The mock provider answers in whatever API the client speaks
+            (ProviderId::Mock, SupportedAPIsFromClient::OpenAIChatCompletions(_)) => {
+                SupportedUpstreamAPIs::OpenAIChatCompletions(OpenAIApi::ChatCompletions)
+            }
+            (ProviderId::Mock, SupportedAPIsFromClient::AnthropicMessagesAPI(_)) => {
+                SupportedUpstreamAPIs::AnthropicMessagesAPI(AnthropicApi::Messages)
+            }
+            (ProviderId::Mock, SupportedAPIsFromClient::OpenAIResponsesAPI(_)) => {
+                SupportedUpstreamAPIs::OpenAIResponsesAPI(OpenAIApi::Responses)
+            }
+
             // Claude/Anthropic providers natively support Anthropic APIs
             (ProviderId::Anthropic, SupportedAPIsFromClient::AnthropicMessagesAPI(_)) => {
                 SupportedUpstreamAPIs::AnthropicMessagesAPI(AnthropicApi::Messages)
@@ -132,9 +155,10 @@ impl ProviderId {
                 SupportedUpstreamAPIs::OpenAIChatCompletions(OpenAIApi::ChatCompletions)
             }
 
-            // Anthropic doesn't support Responses API, fall back to chat completions
+            // Anthropic doesn't support Responses API; translate to Messages so
+            // thinking and server tools survive the round trip
             (ProviderId::Anthropic, SupportedAPIsFromClient::OpenAIResponsesAPI(_)) => {
-                SupportedUpstreamAPIs::OpenAIChatCompletions(OpenAIApi::ChatCompletions)
+                SupportedUpstreamAPIs::AnthropicMessagesAPI(AnthropicApi::Messages)
             }
 
             // OpenAI-compatible providers only support OpenAI chat completions
@@ -146,6 +170,7 @@ impl ProviderId {
                 | ProviderId::Deepseek
                 | ProviderId::Plano
                 | ProviderId::Gemini
+                | ProviderId::VertexAI
                 | ProviderId::GitHub
                 | ProviderId::AzureOpenAI
                 | ProviderId::XAI
@@ -154,7 +179,9 @@ impl ProviderId {
                 | ProviderId::Moonshotai
                 | ProviderId::Zhipu
                 | ProviderId::Qwen
-                | ProviderId::DigitalOcean,
+                | ProviderId::DigitalOcean
+                | ProviderId::Cohere
+                | ProviderId::Deepgram,
                 SupportedAPIsFromClient::AnthropicMessagesAPI(_),
             ) => SupportedUpstreamAPIs::OpenAIChatCompletions(OpenAIApi::ChatCompletions),
 
@@ -165,19 +192,45 @@ impl ProviderId {
                 | ProviderId::Mistral
                 | ProviderId::Deepseek
                 | ProviderId::Plano
-                | ProviderId::Gemini
                 | ProviderId::GitHub
                 | ProviderId::AzureOpenAI
                 | ProviderId::XAI
                 | ProviderId::TogetherAI
-                | ProviderId::Ollama
                 | ProviderId::Moonshotai
                 | ProviderId::Zhipu
                 | ProviderId::Qwen
-                | ProviderId::DigitalOcean,
+                | ProviderId::DigitalOcean
+                | ProviderId::Deepgram,
                 SupportedAPIsFromClient::OpenAIChatCompletions(_),
             ) => SupportedUpstreamAPIs::OpenAIChatCompletions(OpenAIApi::ChatCompletions),
 
+            // Gemini and Vertex AI chat clients use the native generateContent
+            // API; other client APIs keep going through the OpenAI-compatible surface
+            (
+                ProviderId::Gemini | ProviderId::VertexAI,
+                SupportedAPIsFromClient::OpenAIChatCompletions(_),
+            ) => {
+                if is_streaming {
+                    SupportedUpstreamAPIs::GeminiStreamGenerateContent(
+                        GeminiApi::StreamGenerateContent,
+                    )
+                } else {
+                    SupportedUpstreamAPIs::GeminiGenerateContent(GeminiApi::GenerateContent)
+                }
+            }
+
+            // Cohere chat clients use the native v2 chat API; other client
+            // APIs go through its OpenAI compatibility API
+            (ProviderId::Cohere, SupportedAPIsFromClient::OpenAIChatCompletions(_)) => {
+                SupportedUpstreamAPIs::CohereChat(CohereApi::Chat)
+            }
+
+            // Ollama chat clients use the native chat API; other client APIs
+            // go through its OpenAI compatibility API
+            (ProviderId::Ollama, SupportedAPIsFromClient::OpenAIChatCompletions(_)) => {
+                SupportedUpstreamAPIs::OllamaChat(OllamaApi::Chat)
+            }
+
             // OpenAI Responses API - OpenAI and xAI support this natively
             (
                 ProviderId::OpenAI | ProviderId::XAI,
@@ -219,6 +272,19 @@ impl ProviderId {
             }
         }
     }
+
+    /// Whether the provider's ChatCompletions API reads the output limit from
+    /// `max_completion_tokens`; the others only read the deprecated `max_tokens`
+    pub fn takes_max_completion_tokens(&self) -> bool {
+        matches!(
+            self,
+            ProviderId::OpenAI
+                | ProviderId::AzureOpenAI
+                | ProviderId::GitHub
+                | ProviderId::Groq
+                | ProviderId::XAI
+        )
+    }
 }
 
 impl Display for ProviderId {
@@ -230,6 +296,7 @@ impl Display for ProviderId {
             ProviderId::Deepseek => write!(f, "Deepseek"),
             ProviderId::Groq => write!(f, "Groq"),
             ProviderId::Gemini => write!(f, "Gemini"),
+            ProviderId::VertexAI => write!(f, "vertex_ai"),
             ProviderId::Anthropic => write!(f, "Anthropic"),
             ProviderId::GitHub => write!(f, "GitHub"),
             ProviderId::Plano => write!(f, "Plano"),
@@ -242,6 +309,9 @@ impl Display for ProviderId {
             ProviderId::Qwen => write!(f, "qwen"),
             ProviderId::AmazonBedrock => write!(f, "amazon_bedrock"),
             ProviderId::DigitalOcean => write!(f, "digitalocean"),
+            ProviderId::Cohere => write!(f, "cohere"),
+            ProviderId::Deepgram => write!(f, "deepgram"),
+            ProviderId::Mock => write!(f, "mock"),
         }
     }
 }
@@ -355,4 +425,61 @@ mod tests {
             SupportedUpstreamAPIs::OpenAIResponsesAPI(OpenAIApi::Responses)
         ));
     }
+
+    #[test]
+    fn test_gemini_uses_generate_content_for_chat_clients() {
+        let chat = SupportedAPIsFromClient::OpenAIChatCompletions(OpenAIApi::ChatCompletions);
+        assert_eq!(
+            ProviderId::Gemini.compatible_api_for_client(&chat, false),
+            SupportedUpstreamAPIs::GeminiGenerateContent(GeminiApi::GenerateContent)
+        );
+        assert_eq!(
+            ProviderId::Gemini.compatible_api_for_client(&chat, true),
+            SupportedUpstreamAPIs::GeminiStreamGenerateContent(GeminiApi::StreamGenerateContent)
+        );
+
+        let messages = SupportedAPIsFromClient::AnthropicMessagesAPI(AnthropicApi::Messages);
+        assert_eq!(
+            ProviderId::Gemini.compatible_api_for_client(&messages, true),
+            SupportedUpstreamAPIs::OpenAIChatCompletions(OpenAIApi::ChatCompletions)
+        );
+        assert_eq!(
+            ProviderId::VertexAI.compatible_api_for_client(&chat, true),
+            SupportedUpstreamAPIs::GeminiStreamGenerateContent(GeminiApi::StreamGenerateContent)
+        );
+        assert_eq!(
+            ProviderId::VertexAI.compatible_api_for_client(&messages, false),
+            SupportedUpstreamAPIs::OpenAIChatCompletions(OpenAIApi::ChatCompletions)
+        );
+    }
+    #[test]
+    fn test_cohere_uses_native_chat_for_chat_clients() {
+        let chat = SupportedAPIsFromClient::OpenAIChatCompletions(OpenAIApi::ChatCompletions);
+        assert_eq!(ProviderId::try_from("cohere").unwrap(), ProviderId::Cohere);
+        assert_eq!(
+            ProviderId::Cohere.compatible_api_for_client(&chat, true),
+            SupportedUpstreamAPIs::CohereChat(CohereApi::Chat)
+        );
+
+        let responses = SupportedAPIsFromClient::OpenAIResponsesAPI(OpenAIApi::Responses);
+        assert_eq!(
+            ProviderId::Cohere.compatible_api_for_client(&responses, false),
+            SupportedUpstreamAPIs::OpenAIChatCompletions(OpenAIApi::ChatCompletions)
+        );
+    }
+
+    #[test]
+    fn test_ollama_uses_native_chat_for_chat_clients() {
+        let chat = SupportedAPIsFromClient::OpenAIChatCompletions(OpenAIApi::ChatCompletions);
+        assert_eq!(
+            ProviderId::Ollama.compatible_api_for_client(&chat, true),
+            SupportedUpstreamAPIs::OllamaChat(OllamaApi::Chat)
+        );
+
+        let messages = SupportedAPIsFromClient::AnthropicMessagesAPI(AnthropicApi::Messages);
+        assert_eq!(
+            ProviderId::Ollama.compatible_api_for_client(&messages, false),
+            SupportedUpstreamAPIs::OpenAIChatCompletions(OpenAIApi::ChatCompletions)
+        );
+    }
 }
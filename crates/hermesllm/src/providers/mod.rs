@@ -3,12 +3,17 @@
 //! This module contains provider-specific implementations that handle
 //! request/response conversion for different LLM service APIs.
 //!
+pub mod capabilities;
 pub mod id;
+pub mod registry;
 pub mod request;
 pub mod response;
+pub mod stream_fixture;
 pub mod streaming_response;
 
+pub use capabilities::{ProviderCapabilities, SamplingLimits};
 pub use id::ProviderId;
+pub use registry::{custom_provider, register_provider, AuthStyle, CustomProvider};
 pub use request::{ProviderRequest, ProviderRequestError, ProviderRequestType};
 pub use response::{ProviderResponse, ProviderResponseType, TokenUsage};
 pub use streaming_response::{ProviderStreamResponse, ProviderStreamResponseType};
@@ -0,0 +1,262 @@
+//! Providers registered at startup, outside the built-in [`ProviderId`] set.
+//!
+//! A [`CustomProvider`] speaks the wire API of a built-in provider (e.g.
+//! OpenAI chat completions) but is served from its own endpoint with its own
+//! auth header. Gateways register the `custom_providers` from their config;
+//! embedders can attach request/response hooks to the same entry through a
+//! [`TransformPipeline`], which the gateway runs through
+//! [`CustomProvider::transform_request`] and
+//! [`CustomProvider::transform_response`] (non-streaming responses only).
+//!
+//! ```ignore
+//! register_provider(CustomProvider {
+//!     name: "inference".to_string(),
+//!     endpoint: Some("/serve/{model}/v1/chat".to_string()),
+//!     auth: AuthStyle::Header {
+//!         name: "x-inference-token".to_string(),
+//!     },
+//!     pipeline: TransformPipeline::new().with_request_hook(|upstream| { /* ... */ }),
+//! });
+//! let provider = custom_provider("inference").unwrap();
+//! ```
+//!
+//! [`ProviderId`]: crate::ProviderId
+
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock, RwLock};
+
+use serde::{Deserialize, Serialize};
+
+use crate::clients::endpoints::{
+    render_header_templates, SupportedAPIsFromClient, SupportedUpstreamAPIs, API_KEY_PLACEHOLDER,
+};
+use crate::providers::request::{ProviderRequestError, ProviderRequestType};
+use crate::providers::response::ProviderResponseType;
+use crate::transforms::lossy::TransformOptions;
+use crate::transforms::pipeline::{TransformPipeline, UpstreamRequest};
+
+/// How a custom provider expects the credential
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AuthStyle {
+    /// `Authorization: Bearer <key>`
+    #[default]
+    Bearer,
+    /// The bare key in the named header
+    Header { name: String },
+    /// No credential is sent
+    None,
+}
+
+/// A provider registered by name at startup
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CustomProvider {
+    pub name: String,
+    /// Upstream path for every request to this provider. `{model}` is
+    /// replaced with the upstream model id.
+    pub endpoint: Option<String>,
+    #[serde(default)]
+    pub auth: AuthStyle,
+    /// Hooks run by [`Self::transform_request`] and
+    /// [`Self::transform_response`]. Only embedders can set these.
+    #[serde(skip)]
+    pub pipeline: TransformPipeline,
+}
+
+impl CustomProvider {
+    /// Upstream path for `model_id`, if the provider has an endpoint template
+    pub fn endpoint_for(&self, model_id: &str) -> Option<String> {
+        let path = self.endpoint.as_deref()?.trim();
+        if path.is_empty() {
+            return None;
+        }
+        let path = path.replace("{model}", model_id);
+        if path.starts_with('/') {
+            Some(path)
+        } else {
+            Some(format!("/{}", path))
+        }
+    }
+
+    /// Headers carrying `credential` the way this provider expects it, with
+    /// `overrides` merged over them as for built-in providers.
+    pub fn upstream_headers(
+        &self,
+        credential: &str,
+        overrides: Option<&HashMap<String, String>>,
+    ) -> Vec<(String, String)> {
+        let headers = match &self.auth {
+            AuthStyle::Bearer => vec![(
+                "authorization".to_string(),
+                format!("Bearer {}", API_KEY_PLACEHOLDER),
+            )],
+            AuthStyle::Header { name } => {
+                vec![(name.to_ascii_lowercase(), API_KEY_PLACEHOLDER.to_string())]
+            }
+            AuthStyle::None => Vec::new(),
+        };
+        render_header_templates(headers, credential, overrides)
+    }
+
+    pub fn requires_credential(&self) -> bool {
+        self.auth != AuthStyle::None
+    }
+
+    /// Convert `request` for `upstream_api` and run this provider's request
+    /// hooks. Headers the hooks add go on the outgoing request.
+    pub fn transform_request(
+        &self,
+        request: ProviderRequestType,
+        upstream_api: &SupportedUpstreamAPIs,
+        options: TransformOptions,
+    ) -> Result<UpstreamRequest, ProviderRequestError> {
+        self.pipeline
+            .clone()
+            .with_options(options)
+            .transform_request(request, upstream_api)
+    }
+
+    /// Parse a non-streaming upstream response into the client's shape and
+    /// run this provider's response hooks.
+    pub fn transform_response(
+        &self,
+        body: &[u8],
+        client_api: &SupportedAPIsFromClient,
+        upstream_api: &SupportedUpstreamAPIs,
+        options: TransformOptions,
+    ) -> Result<ProviderResponseType, std::io::Error> {
+        self.pipeline
+            .clone()
+            .with_options(options)
+            .transform_response(body, client_api, upstream_api)
+    }
+}
+
+fn registry() -> &'static RwLock<HashMap<String, Arc<CustomProvider>>> {
+    static REGISTRY: OnceLock<RwLock<HashMap<String, Arc<CustomProvider>>>> = OnceLock::new();
+    REGISTRY.get_or_init(Default::default)
+}
+
+/// Register `provider` under its name, replacing any earlier entry. Hooks
+/// already registered under that name are kept when `provider` brings none,
+/// so config loaded after an embedder's registration doesn't drop them.
+pub fn register_provider(mut provider: CustomProvider) {
+    let mut providers = registry().write().unwrap_or_else(|err| err.into_inner());
+    if provider.pipeline.is_empty() {
+        if let Some(existing) = providers.get(&provider.name) {
+            provider.pipeline = existing.pipeline.clone();
+        }
+    }
+    providers.insert(provider.name.clone(), Arc::new(provider));
+}
+
+/// The provider registered under `name`
+pub fn custom_provider(name: &str) -> Option<Arc<CustomProvider>> {
+    registry()
+        .read()
+        .unwrap_or_else(|err| err.into_inner())
+        .get(name)
+        .cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::apis::openai::{ChatCompletionsRequest, OpenAIApi};
+    use serde_json::json;
+
+    #[test]
+    fn registered_provider_resolves_endpoint_and_auth() {
+        let provider: CustomProvider = serde_yaml::from_str(
+            r#"
+name: registry-test
+endpoint: serve/{model}/v1/chat
+auth:
+  type: header
+  name: X-Inference-Token
+"#,
+        )
+        .unwrap();
+        register_provider(CustomProvider {
+            pipeline: TransformPipeline::new().with_request_hook(|_| {}),
+            ..provider.clone()
+        });
+        register_provider(provider);
+
+        let provider = custom_provider("registry-test").unwrap();
+        assert!(!provider.pipeline.is_empty());
+        assert_eq!(
+            provider.endpoint_for("llama-3").as_deref(),
+            Some("/serve/llama-3/v1/chat")
+        );
+        assert_eq!(
+            provider.upstream_headers("secret", None),
+            vec![("x-inference-token".to_string(), "secret".to_string())]
+        );
+        assert!(custom_provider("unregistered").is_none());
+    }
+
+    #[test]
+    fn registered_hooks_run_on_conversions() {
+        register_provider(CustomProvider {
+            name: "hooks-test".to_string(),
+            pipeline: TransformPipeline::new()
+                .with_request_hook(|upstream| {
+                    upstream
+                        .headers
+                        .push(("x-org-id".to_string(), "acme".to_string()));
+                })
+                .with_response_hook(|response, _| {
+                    if let ProviderResponseType::ChatCompletionsResponse(resp) = response {
+                        resp.system_fingerprint = Some("inference".to_string());
+                    }
+                }),
+            ..Default::default()
+        });
+        let provider = custom_provider("hooks-test").unwrap();
+        let chat = SupportedUpstreamAPIs::OpenAIChatCompletions(OpenAIApi::ChatCompletions);
+
+        let request: ChatCompletionsRequest = serde_json::from_value(json!({
+            "model": "llama-3",
+            "messages": [{"role": "user", "content": "Hi"}]
+        }))
+        .unwrap();
+        let upstream = provider
+            .transform_request(
+                ProviderRequestType::ChatCompletionsRequest(request),
+                &chat,
+                TransformOptions::default(),
+            )
+            .unwrap();
+        assert_eq!(
+            upstream.headers,
+            vec![("x-org-id".to_string(), "acme".to_string())]
+        );
+
+        let body = json!({
+            "id": "chatcmpl-1",
+            "object": "chat.completion",
+            "created": 1,
+            "model": "llama-3",
+            "choices": [{
+                "index": 0,
+                "message": {"role": "assistant", "content": "Hello"},
+                "finish_reason": "stop"
+            }],
+            "usage": {"prompt_tokens": 3, "completion_tokens": 1, "total_tokens": 4}
+        })
+        .to_string();
+        let response = provider
+            .transform_response(
+                body.as_bytes(),
+                &SupportedAPIsFromClient::OpenAIChatCompletions(OpenAIApi::ChatCompletions),
+                &chat,
+                TransformOptions::default(),
+            )
+            .unwrap();
+        let ProviderResponseType::ChatCompletionsResponse(resp) = response else {
+            panic!("expected a ChatCompletions response");
+        };
+        assert_eq!(resp.system_fingerprint.as_deref(), Some("inference"));
+    }
+}
@@ -1,12 +1,22 @@
-use crate::apis::anthropic::MessagesRequest;
+use crate::apis::anthropic::{AnthropicPlatform, MessagesRequest};
 use crate::apis::openai::ChatCompletionsRequest;
 
-use crate::apis::amazon_bedrock::{ConverseRequest, ConverseStreamRequest};
+use crate::apis::amazon_bedrock::{
+    ConverseRequest, ConverseStreamRequest, InvokeModelBody, InvokeModelRequest,
+};
+use crate::apis::cohere::CohereChatRequest;
+use crate::apis::gemini::GenerateContentRequest;
+use crate::apis::ollama::OllamaChatRequest;
 use crate::apis::openai_responses::ResponsesAPIRequest;
 use crate::clients::endpoints::SupportedAPIsFromClient;
 use crate::clients::endpoints::SupportedUpstreamAPIs;
+use crate::providers::capabilities::SamplingLimits;
+use crate::transforms::lossy::{dropped_request_input, TransformOptions};
+use crate::transforms::request::from_openai::take_anthropic_server_tools;
+use crate::transforms::tool_call_ids::{ToolCallIdFormat, ToolCallIdMap};
 use crate::ProviderId;
 
+use log::warn;
 use serde_json::Value;
 use std::collections::HashMap;
 use std::error::Error;
@@ -18,6 +28,10 @@ pub enum ProviderRequestType {
     MessagesRequest(MessagesRequest),
     BedrockConverse(ConverseRequest),
     BedrockConverseStream(ConverseStreamRequest),
+    BedrockInvokeModel(InvokeModelRequest),
+    GeminiGenerateContent(GenerateContentRequest),
+    CohereChat(CohereChatRequest),
+    OllamaChat(OllamaChatRequest),
     ResponsesAPIRequest(ResponsesAPIRequest),
     //add more request types here
 }
@@ -68,6 +82,10 @@ impl ProviderRequestType {
             Self::MessagesRequest(r) => r.set_messages(messages),
             Self::BedrockConverse(r) => r.set_messages(messages),
             Self::BedrockConverseStream(r) => r.set_messages(messages),
+            Self::BedrockInvokeModel(r) => r.set_messages(messages),
+            Self::GeminiGenerateContent(r) => r.set_messages(messages),
+            Self::CohereChat(r) => r.set_messages(messages),
+            Self::OllamaChat(r) => r.set_messages(messages),
             Self::ResponsesAPIRequest(r) => r.set_messages(messages),
         }
     }
@@ -89,6 +107,66 @@ impl ProviderRequestType {
                 req.web_search_options = None;
             }
         }
+
+        // Prompt caching hints become breakpoints on the way to the Messages
+        // API; ChatCompletions upstreams would reject them
+        if matches!(
+            upstream_api,
+            SupportedUpstreamAPIs::OpenAIChatCompletions(_)
+        ) {
+            if let Self::ChatCompletionsRequest(req) = self {
+                req.cache_control = None;
+                req.normalize_max_tokens(provider_id.takes_max_completion_tokens());
+            }
+        }
+
+        let limits = SamplingLimits::for_upstream(provider_id, upstream_api, self.model());
+        for change in self.clamp_sampling_params(&limits) {
+            warn!("{:?} request adjusted: {}", provider_id, change);
+        }
+
+        if let Some(format) = ToolCallIdFormat::for_upstream(provider_id, upstream_api) {
+            self.adapt_tool_call_ids(&mut ToolCallIdMap::new(format));
+        }
+
+        // Vertex AI only reaches the Messages API for its Claude models
+        if provider_id == ProviderId::VertexAI
+            && matches!(upstream_api, SupportedUpstreamAPIs::AnthropicMessagesAPI(_))
+        {
+            if let Self::MessagesRequest(req) = self {
+                req.platform = Some(AnthropicPlatform::VertexAI);
+            }
+        }
+    }
+
+    /// The output limit the client set, if any
+    fn requested_max_tokens(&self) -> Option<u32> {
+        match self {
+            Self::ChatCompletionsRequest(r) => r.max_completion_tokens.or(r.max_tokens),
+            Self::MessagesRequest(r) => Some(r.max_tokens),
+            Self::ResponsesAPIRequest(r) => r.max_output_tokens.map(|t| t.max(0) as u32),
+            _ => None,
+        }
+    }
+
+    /// Give a request for an upstream that requires an output limit
+    /// `default_max_tokens` of room for the answer, on top of any thinking
+    /// budget
+    fn set_default_max_tokens(&mut self, default_max_tokens: u32) {
+        let messages_req = match self {
+            Self::MessagesRequest(r) => r,
+            Self::BedrockInvokeModel(InvokeModelRequest {
+                body: InvokeModelBody::Anthropic(r),
+                ..
+            }) => r,
+            _ => return,
+        };
+        let budget = messages_req
+            .thinking
+            .as_ref()
+            .and_then(|thinking| thinking.budget_tokens)
+            .unwrap_or(0);
+        messages_req.max_tokens = budget + default_max_tokens;
     }
 }
 
@@ -99,6 +177,10 @@ impl ProviderRequest for ProviderRequestType {
             Self::MessagesRequest(r) => r.model(),
             Self::BedrockConverse(r) => r.model(),
             Self::BedrockConverseStream(r) => r.model(),
+            Self::BedrockInvokeModel(r) => r.model(),
+            Self::GeminiGenerateContent(r) => r.model(),
+            Self::CohereChat(r) => r.model(),
+            Self::OllamaChat(r) => r.model(),
             Self::ResponsesAPIRequest(r) => r.model(),
         }
     }
@@ -109,6 +191,10 @@ impl ProviderRequest for ProviderRequestType {
             Self::MessagesRequest(r) => r.set_model(model),
             Self::BedrockConverse(r) => r.set_model(model),
             Self::BedrockConverseStream(r) => r.set_model(model),
+            Self::BedrockInvokeModel(r) => r.set_model(model),
+            Self::GeminiGenerateContent(r) => r.set_model(model),
+            Self::CohereChat(r) => r.set_model(model),
+            Self::OllamaChat(r) => r.set_model(model),
             Self::ResponsesAPIRequest(r) => r.set_model(model),
         }
     }
@@ -119,6 +205,10 @@ impl ProviderRequest for ProviderRequestType {
             Self::MessagesRequest(r) => r.is_streaming(),
             Self::BedrockConverse(_) => false,
             Self::BedrockConverseStream(_) => true,
+            Self::BedrockInvokeModel(r) => r.is_streaming(),
+            Self::GeminiGenerateContent(r) => r.is_streaming(),
+            Self::CohereChat(r) => r.is_streaming(),
+            Self::OllamaChat(r) => r.is_streaming(),
             Self::ResponsesAPIRequest(r) => r.is_streaming(),
         }
     }
@@ -129,6 +219,10 @@ impl ProviderRequest for ProviderRequestType {
             Self::MessagesRequest(r) => r.extract_messages_text(),
             Self::BedrockConverse(r) => r.extract_messages_text(),
             Self::BedrockConverseStream(r) => r.extract_messages_text(),
+            Self::BedrockInvokeModel(r) => r.extract_messages_text(),
+            Self::GeminiGenerateContent(r) => r.extract_messages_text(),
+            Self::CohereChat(r) => r.extract_messages_text(),
+            Self::OllamaChat(r) => r.extract_messages_text(),
             Self::ResponsesAPIRequest(r) => r.extract_messages_text(),
         }
     }
@@ -139,6 +233,10 @@ impl ProviderRequest for ProviderRequestType {
             Self::MessagesRequest(r) => r.get_recent_user_message(),
             Self::BedrockConverse(r) => r.get_recent_user_message(),
             Self::BedrockConverseStream(r) => r.get_recent_user_message(),
+            Self::BedrockInvokeModel(r) => r.get_recent_user_message(),
+            Self::GeminiGenerateContent(r) => r.get_recent_user_message(),
+            Self::CohereChat(r) => r.get_recent_user_message(),
+            Self::OllamaChat(r) => r.get_recent_user_message(),
             Self::ResponsesAPIRequest(r) => r.get_recent_user_message(),
         }
     }
@@ -149,6 +247,10 @@ impl ProviderRequest for ProviderRequestType {
             Self::MessagesRequest(r) => r.get_tool_names(),
             Self::BedrockConverse(r) => r.get_tool_names(),
             Self::BedrockConverseStream(r) => r.get_tool_names(),
+            Self::BedrockInvokeModel(r) => r.get_tool_names(),
+            Self::GeminiGenerateContent(r) => r.get_tool_names(),
+            Self::CohereChat(r) => r.get_tool_names(),
+            Self::OllamaChat(r) => r.get_tool_names(),
             Self::ResponsesAPIRequest(r) => r.get_tool_names(),
         }
     }
@@ -159,6 +261,10 @@ impl ProviderRequest for ProviderRequestType {
             Self::MessagesRequest(r) => r.to_bytes(),
             Self::BedrockConverse(r) => r.to_bytes(),
             Self::BedrockConverseStream(r) => r.to_bytes(),
+            Self::BedrockInvokeModel(r) => r.to_bytes(),
+            Self::GeminiGenerateContent(r) => r.to_bytes(),
+            Self::CohereChat(r) => r.to_bytes(),
+            Self::OllamaChat(r) => r.to_bytes(),
             Self::ResponsesAPIRequest(r) => r.to_bytes(),
         }
     }
@@ -169,6 +275,10 @@ impl ProviderRequest for ProviderRequestType {
             Self::MessagesRequest(r) => r.metadata(),
             Self::BedrockConverse(r) => r.metadata(),
             Self::BedrockConverseStream(r) => r.metadata(),
+            Self::BedrockInvokeModel(r) => r.metadata(),
+            Self::GeminiGenerateContent(r) => r.metadata(),
+            Self::CohereChat(r) => r.metadata(),
+            Self::OllamaChat(r) => r.metadata(),
             Self::ResponsesAPIRequest(r) => r.metadata(),
         }
     }
@@ -179,6 +289,10 @@ impl ProviderRequest for ProviderRequestType {
             Self::MessagesRequest(r) => r.remove_metadata_key(key),
             Self::BedrockConverse(r) => r.remove_metadata_key(key),
             Self::BedrockConverseStream(r) => r.remove_metadata_key(key),
+            Self::BedrockInvokeModel(r) => r.remove_metadata_key(key),
+            Self::GeminiGenerateContent(r) => r.remove_metadata_key(key),
+            Self::CohereChat(r) => r.remove_metadata_key(key),
+            Self::OllamaChat(r) => r.remove_metadata_key(key),
             Self::ResponsesAPIRequest(r) => r.remove_metadata_key(key),
         }
     }
@@ -189,6 +303,10 @@ impl ProviderRequest for ProviderRequestType {
             Self::MessagesRequest(r) => r.get_temperature(),
             Self::BedrockConverse(r) => r.get_temperature(),
             Self::BedrockConverseStream(r) => r.get_temperature(),
+            Self::BedrockInvokeModel(r) => r.get_temperature(),
+            Self::GeminiGenerateContent(r) => r.get_temperature(),
+            Self::CohereChat(r) => r.get_temperature(),
+            Self::OllamaChat(r) => r.get_temperature(),
             Self::ResponsesAPIRequest(r) => r.get_temperature(),
         }
     }
@@ -199,6 +317,10 @@ impl ProviderRequest for ProviderRequestType {
             Self::MessagesRequest(r) => r.get_messages(),
             Self::BedrockConverse(r) => r.get_messages(),
             Self::BedrockConverseStream(r) => r.get_messages(),
+            Self::BedrockInvokeModel(r) => r.get_messages(),
+            Self::GeminiGenerateContent(r) => r.get_messages(),
+            Self::CohereChat(r) => r.get_messages(),
+            Self::OllamaChat(r) => r.get_messages(),
             Self::ResponsesAPIRequest(r) => r.get_messages(),
         }
     }
@@ -209,6 +331,10 @@ impl ProviderRequest for ProviderRequestType {
             Self::MessagesRequest(r) => r.set_messages(messages),
             Self::BedrockConverse(r) => r.set_messages(messages),
             Self::BedrockConverseStream(r) => r.set_messages(messages),
+            Self::BedrockInvokeModel(r) => r.set_messages(messages),
+            Self::GeminiGenerateContent(r) => r.set_messages(messages),
+            Self::CohereChat(r) => r.set_messages(messages),
+            Self::OllamaChat(r) => r.set_messages(messages),
             Self::ResponsesAPIRequest(r) => r.set_messages(messages),
         }
     }
@@ -300,6 +426,68 @@ impl TryFrom<(ProviderRequestType, &SupportedUpstreamAPIs)> for ProviderRequestT
                     })?;
                 Ok(ProviderRequestType::BedrockConverseStream(bedrock_req))
             }
+            (
+                ProviderRequestType::ChatCompletionsRequest(chat_req),
+                SupportedUpstreamAPIs::AmazonBedrockInvokeModel(_)
+                | SupportedUpstreamAPIs::AmazonBedrockInvokeModelStream(_),
+            ) => {
+                let invoke_req = InvokeModelRequest::try_from(chat_req).map_err(|e| {
+                    ProviderRequestError {
+                        message: format!(
+                            "Failed to convert ChatCompletionsRequest to Amazon Bedrock InvokeModel request: {}",
+                            e
+                        ),
+                        source: Some(Box::new(e)),
+                    }
+                })?;
+                Ok(ProviderRequestType::BedrockInvokeModel(invoke_req))
+            }
+            (
+                ProviderRequestType::ChatCompletionsRequest(chat_req),
+                SupportedUpstreamAPIs::GeminiGenerateContent(_)
+                | SupportedUpstreamAPIs::GeminiStreamGenerateContent(_),
+            ) => {
+                let gemini_req = GenerateContentRequest::try_from(chat_req).map_err(|e| {
+                    ProviderRequestError {
+                        message: format!(
+                            "Failed to convert ChatCompletionsRequest to Gemini request: {}",
+                            e
+                        ),
+                        source: Some(Box::new(e)),
+                    }
+                })?;
+                Ok(ProviderRequestType::GeminiGenerateContent(gemini_req))
+            }
+            (
+                ProviderRequestType::ChatCompletionsRequest(chat_req),
+                SupportedUpstreamAPIs::CohereChat(_),
+            ) => {
+                let cohere_req = CohereChatRequest::try_from(chat_req).map_err(|e| {
+                    ProviderRequestError {
+                        message: format!(
+                            "Failed to convert ChatCompletionsRequest to Cohere request: {}",
+                            e
+                        ),
+                        source: Some(Box::new(e)),
+                    }
+                })?;
+                Ok(ProviderRequestType::CohereChat(cohere_req))
+            }
+            (
+                ProviderRequestType::ChatCompletionsRequest(chat_req),
+                SupportedUpstreamAPIs::OllamaChat(_),
+            ) => {
+                let ollama_req = OllamaChatRequest::try_from(chat_req).map_err(|e| {
+                    ProviderRequestError {
+                        message: format!(
+                            "Failed to convert ChatCompletionsRequest to Ollama request: {}",
+                            e
+                        ),
+                        source: Some(Box::new(e)),
+                    }
+                })?;
+                Ok(ProviderRequestType::OllamaChat(ollama_req))
+            }
             (
                 ProviderRequestType::ChatCompletionsRequest(_),
                 SupportedUpstreamAPIs::OpenAIResponsesAPI(_),
@@ -321,7 +509,7 @@ impl TryFrom<(ProviderRequestType, &SupportedUpstreamAPIs)> for ProviderRequestT
                 ProviderRequestType::MessagesRequest(messages_req),
                 SupportedUpstreamAPIs::OpenAIChatCompletions(_),
             ) => {
-                let chat_req = ChatCompletionsRequest::try_from(messages_req).map_err(|e| {
+                let mut chat_req = ChatCompletionsRequest::try_from(messages_req).map_err(|e| {
                     ProviderRequestError {
                         message: format!(
                             "Failed to convert MessagesRequest to ChatCompletionsRequest: {}",
@@ -330,6 +518,9 @@ impl TryFrom<(ProviderRequestType, &SupportedUpstreamAPIs)> for ProviderRequestT
                         source: Some(Box::new(e)),
                     }
                 })?;
+                // Thinking is a per-request switch on Anthropic, but only
+                // reasoning models take an effort
+                chat_req.drop_reasoning_effort_unless_reasoning_model();
                 Ok(ProviderRequestType::ChatCompletionsRequest(chat_req))
             }
             (
@@ -361,6 +552,82 @@ impl TryFrom<(ProviderRequestType, &SupportedUpstreamAPIs)> for ProviderRequestT
                 })?;
                 Ok(ProviderRequestType::BedrockConverseStream(bedrock_req))
             }
+            (
+                ProviderRequestType::MessagesRequest(messages_req),
+                SupportedUpstreamAPIs::GeminiGenerateContent(_)
+                | SupportedUpstreamAPIs::GeminiStreamGenerateContent(_),
+            ) => {
+                // Chain: MessagesRequest -> ChatCompletions -> GenerateContentRequest
+                let chat_req = ChatCompletionsRequest::try_from(messages_req).map_err(|e| {
+                    ProviderRequestError {
+                        message: format!(
+                            "Failed to convert MessagesRequest to ChatCompletionsRequest: {}",
+                            e
+                        ),
+                        source: Some(Box::new(e)),
+                    }
+                })?;
+                let gemini_req = GenerateContentRequest::try_from(chat_req).map_err(|e| {
+                    ProviderRequestError {
+                        message: format!(
+                            "Failed to convert ChatCompletionsRequest to Gemini request: {}",
+                            e
+                        ),
+                        source: Some(Box::new(e)),
+                    }
+                })?;
+                Ok(ProviderRequestType::GeminiGenerateContent(gemini_req))
+            }
+            (
+                ProviderRequestType::MessagesRequest(messages_req),
+                SupportedUpstreamAPIs::CohereChat(_),
+            ) => {
+                // Chain: MessagesRequest -> ChatCompletions -> CohereChatRequest
+                let chat_req = ChatCompletionsRequest::try_from(messages_req).map_err(|e| {
+                    ProviderRequestError {
+                        message: format!(
+                            "Failed to convert MessagesRequest to ChatCompletionsRequest: {}",
+                            e
+                        ),
+                        source: Some(Box::new(e)),
+                    }
+                })?;
+                let cohere_req = CohereChatRequest::try_from(chat_req).map_err(|e| {
+                    ProviderRequestError {
+                        message: format!(
+                            "Failed to convert ChatCompletionsRequest to Cohere request: {}",
+                            e
+                        ),
+                        source: Some(Box::new(e)),
+                    }
+                })?;
+                Ok(ProviderRequestType::CohereChat(cohere_req))
+            }
+            (
+                ProviderRequestType::MessagesRequest(messages_req),
+                SupportedUpstreamAPIs::OllamaChat(_),
+            ) => {
+                // Chain: MessagesRequest -> ChatCompletions -> OllamaChatRequest
+                let chat_req = ChatCompletionsRequest::try_from(messages_req).map_err(|e| {
+                    ProviderRequestError {
+                        message: format!(
+                            "Failed to convert MessagesRequest to ChatCompletionsRequest: {}",
+                            e
+                        ),
+                        source: Some(Box::new(e)),
+                    }
+                })?;
+                let ollama_req = OllamaChatRequest::try_from(chat_req).map_err(|e| {
+                    ProviderRequestError {
+                        message: format!(
+                            "Failed to convert ChatCompletionsRequest to Ollama request: {}",
+                            e
+                        ),
+                        source: Some(Box::new(e)),
+                    }
+                })?;
+                Ok(ProviderRequestType::OllamaChat(ollama_req))
+            }
             (
                 ProviderRequestType::MessagesRequest(_),
                 SupportedUpstreamAPIs::OpenAIResponsesAPI(_),
@@ -401,7 +668,10 @@ impl TryFrom<(ProviderRequestType, &SupportedUpstreamAPIs)> for ProviderRequestT
                 ProviderRequestType::ResponsesAPIRequest(responses_req),
                 SupportedUpstreamAPIs::AnthropicMessagesAPI(_),
             ) => {
-                // Chain: ResponsesAPI -> ChatCompletions -> MessagesRequest
+                // Chain: ResponsesAPI -> ChatCompletions -> MessagesRequest, with
+                // built-in tools mapped straight to Anthropic server tools
+                let mut responses_req = responses_req;
+                let server_tools = take_anthropic_server_tools(&mut responses_req.tools);
                 let chat_req = ChatCompletionsRequest::try_from(responses_req).map_err(|e| {
                     ProviderRequestError {
                         message: format!(
@@ -412,7 +682,7 @@ impl TryFrom<(ProviderRequestType, &SupportedUpstreamAPIs)> for ProviderRequestT
                     }
                 })?;
 
-                let messages_req = MessagesRequest::try_from(chat_req).map_err(|e| {
+                let mut messages_req = MessagesRequest::try_from(chat_req).map_err(|e| {
                     ProviderRequestError {
                         message: format!(
                             "Failed to convert ChatCompletionsRequest to MessagesRequest: {}",
@@ -421,6 +691,12 @@ impl TryFrom<(ProviderRequestType, &SupportedUpstreamAPIs)> for ProviderRequestT
                         source: Some(Box::new(e)),
                     }
                 })?;
+                if !server_tools.is_empty() {
+                    messages_req
+                        .tools
+                        .get_or_insert_with(Vec::new)
+                        .extend(server_tools);
+                }
                 Ok(ProviderRequestType::MessagesRequest(messages_req))
             }
 
@@ -480,6 +756,115 @@ impl TryFrom<(ProviderRequestType, &SupportedUpstreamAPIs)> for ProviderRequestT
                 Ok(ProviderRequestType::BedrockConverseStream(bedrock_req))
             }
 
+            // ResponsesAPI -> Gemini (via ChatCompletions)
+            (
+                ProviderRequestType::ResponsesAPIRequest(responses_req),
+                SupportedUpstreamAPIs::GeminiGenerateContent(_)
+                | SupportedUpstreamAPIs::GeminiStreamGenerateContent(_),
+            ) => {
+                // Chain: ResponsesAPI -> ChatCompletions -> GenerateContentRequest
+                let chat_req = ChatCompletionsRequest::try_from(responses_req).map_err(|e| {
+                    ProviderRequestError {
+                        message: format!(
+                            "Failed to convert ResponsesAPIRequest to ChatCompletionsRequest: {}",
+                            e
+                        ),
+                        source: Some(Box::new(e)),
+                    }
+                })?;
+                let gemini_req = GenerateContentRequest::try_from(chat_req).map_err(|e| {
+                    ProviderRequestError {
+                        message: format!(
+                            "Failed to convert ChatCompletionsRequest to Gemini request: {}",
+                            e
+                        ),
+                        source: Some(Box::new(e)),
+                    }
+                })?;
+                Ok(ProviderRequestType::GeminiGenerateContent(gemini_req))
+            }
+
+            // ResponsesAPI -> Cohere (via ChatCompletions)
+            (
+                ProviderRequestType::ResponsesAPIRequest(responses_req),
+                SupportedUpstreamAPIs::CohereChat(_),
+            ) => {
+                // Chain: ResponsesAPI -> ChatCompletions -> CohereChatRequest
+                let chat_req = ChatCompletionsRequest::try_from(responses_req).map_err(|e| {
+                    ProviderRequestError {
+                        message: format!(
+                            "Failed to convert ResponsesAPIRequest to ChatCompletionsRequest: {}",
+                            e
+                        ),
+                        source: Some(Box::new(e)),
+                    }
+                })?;
+                let cohere_req = CohereChatRequest::try_from(chat_req).map_err(|e| {
+                    ProviderRequestError {
+                        message: format!(
+                            "Failed to convert ChatCompletionsRequest to Cohere request: {}",
+                            e
+                        ),
+                        source: Some(Box::new(e)),
+                    }
+                })?;
+                Ok(ProviderRequestType::CohereChat(cohere_req))
+            }
+
+            // ResponsesAPI -> Ollama (via ChatCompletions)
+            (
+                ProviderRequestType::ResponsesAPIRequest(responses_req),
+                SupportedUpstreamAPIs::OllamaChat(_),
+            ) => {
+                // Chain: ResponsesAPIRequest -> ChatCompletions -> OllamaChatRequest
+                let chat_req = ChatCompletionsRequest::try_from(responses_req).map_err(|e| {
+                    ProviderRequestError {
+                        message: format!(
+                            "Failed to convert ResponsesAPIRequest to ChatCompletionsRequest: {}",
+                            e
+                        ),
+                        source: Some(Box::new(e)),
+                    }
+                })?;
+                let ollama_req = OllamaChatRequest::try_from(chat_req).map_err(|e| {
+                    ProviderRequestError {
+                        message: format!(
+                            "Failed to convert ChatCompletionsRequest to Ollama request: {}",
+                            e
+                        ),
+                        source: Some(Box::new(e)),
+                    }
+                })?;
+                Ok(ProviderRequestType::OllamaChat(ollama_req))
+            }
+
+            (
+                ProviderRequestType::MessagesRequest(messages_req),
+                SupportedUpstreamAPIs::AmazonBedrockInvokeModel(_)
+                | SupportedUpstreamAPIs::AmazonBedrockInvokeModelStream(_),
+            ) => {
+                let invoke_req = InvokeModelRequest::try_from(messages_req).map_err(|e| {
+                    ProviderRequestError {
+                        message: format!(
+                            "Failed to convert MessagesRequest to Amazon Bedrock InvokeModel request: {}",
+                            e
+                        ),
+                        source: Some(Box::new(e)),
+                    }
+                })?;
+                Ok(ProviderRequestType::BedrockInvokeModel(invoke_req))
+            }
+
+            // InvokeModel is never chosen for Responses API clients
+            (
+                ProviderRequestType::ResponsesAPIRequest(_),
+                SupportedUpstreamAPIs::AmazonBedrockInvokeModel(_)
+                | SupportedUpstreamAPIs::AmazonBedrockInvokeModelStream(_),
+            ) => Err(ProviderRequestError {
+                message: "Amazon Bedrock InvokeModel upstreams only serve OpenAI ChatCompletions and Anthropic Messages clients; use the Converse API for Responses API clients.".to_string(),
+                source: None,
+            }),
+
             // ============================================================================
             // Amazon Bedrock conversions (not supported as client API)
             // ============================================================================
@@ -497,7 +882,67 @@ impl TryFrom<(ProviderRequestType, &SupportedUpstreamAPIs)> for ProviderRequestT
                     source: None,
                 })
             }
+
+            (ProviderRequestType::BedrockInvokeModel(_), _) => {
+                Err(ProviderRequestError {
+                    message: "Amazon Bedrock InvokeModel is not supported as a client API. Only OpenAI ChatCompletions, Anthropic Messages, and OpenAI Responses APIs are supported as client APIs.".to_string(),
+                    source: None,
+                })
+            }
+
+            (ProviderRequestType::GeminiGenerateContent(_), _) => {
+                Err(ProviderRequestError {
+                    message: "Gemini generateContent is not supported as a client API. Only OpenAI ChatCompletions, Anthropic Messages, and OpenAI Responses APIs are supported as client APIs.".to_string(),
+                    source: None,
+                })
+            }
+
+            (ProviderRequestType::CohereChat(_), _) => {
+                Err(ProviderRequestError {
+                    message: "Cohere chat is not supported as a client API. Only OpenAI ChatCompletions, Anthropic Messages, and OpenAI Responses APIs are supported as client APIs.".to_string(),
+                    source: None,
+                })
+            }
+            (ProviderRequestType::OllamaChat(_), _) => {
+                Err(ProviderRequestError {
+                    message: "Ollama chat is not supported as a client API. Only OpenAI ChatCompletions, Anthropic Messages, and OpenAI Responses APIs are supported as client APIs.".to_string(),
+                    source: None,
+                })
+            }
+        }
+    }
+}
+
+impl
+    TryFrom<(
+        ProviderRequestType,
+        &SupportedUpstreamAPIs,
+        TransformOptions,
+    )> for ProviderRequestType
+{
+    type Error = ProviderRequestError;
+
+    /// Convert for `upstream_api`, failing in strict mode rather than
+    /// dropping input the upstream API can't express.
+    fn try_from(
+        (client_request, upstream_api, options): (
+            ProviderRequestType,
+            &SupportedUpstreamAPIs,
+            TransformOptions,
+        ),
+    ) -> Result<Self, Self::Error> {
+        options
+            .check(|| dropped_request_input(&client_request, upstream_api))
+            .map_err(|e| ProviderRequestError {
+                message: format!("Strict transform rejected the request: {}", e),
+                source: Some(Box::new(e)),
+            })?;
+        let requested_max_tokens = client_request.requested_max_tokens();
+        let mut request = ProviderRequestType::try_from((client_request, upstream_api))?;
+        if requested_max_tokens.is_none() {
+            request.set_default_max_tokens(options.default_max_tokens);
         }
+        Ok(request)
     }
 }
 
@@ -650,6 +1095,7 @@ mod tests {
             tools: None,
             tool_choice: None,
             metadata: None,
+            platform: None,
         };
 
         let openai_req = ChatCompletionsRequest::try_from(anthropic_req.clone())
@@ -697,6 +1143,7 @@ mod tests {
                     name: None,
                     tool_calls: None,
                     tool_call_id: None,
+                    thinking_blocks: None,
                 },
                 Message {
                     role: Role::User,
@@ -704,6 +1151,7 @@ mod tests {
                     name: None,
                     tool_calls: None,
                     tool_call_id: None,
+                    thinking_blocks: None,
                 },
             ],
             temperature: Some(0.7),
@@ -819,6 +1267,7 @@ mod tests {
                 name: None,
                 tool_calls: None,
                 tool_call_id: None,
+                thinking_blocks: None,
             }],
             web_search_options: Some(serde_json::json!({"search_context_size":"medium"})),
             ..Default::default()
@@ -835,6 +1284,87 @@ mod tests {
         assert!(req.web_search_options.is_none());
     }
 
+    #[test]
+    fn test_normalize_for_upstream_picks_max_tokens_field() {
+        let upstream = SupportedUpstreamAPIs::OpenAIChatCompletions(ChatCompletions);
+        let normalized = |provider_id: ProviderId, body: serde_json::Value| {
+            let mut request =
+                ProviderRequestType::ChatCompletionsRequest(serde_json::from_value(body).unwrap());
+            request.normalize_for_upstream(provider_id, &upstream);
+            let ProviderRequestType::ChatCompletionsRequest(req) = request else {
+                panic!("expected chat request");
+            };
+            (req.max_tokens, req.max_completion_tokens)
+        };
+        let messages = json!([{"role": "user", "content": "hi"}]);
+
+        assert_eq!(
+            normalized(
+                ProviderId::OpenAI,
+                json!({"model": "gpt-4o", "messages": messages, "max_tokens": 100})
+            ),
+            (None, Some(100))
+        );
+        assert_eq!(
+            normalized(
+                ProviderId::Mistral,
+                json!({"model": "mistral-large", "messages": messages, "max_completion_tokens": 100})
+            ),
+            (Some(100), None)
+        );
+        // Reasoning models take only the new field, whoever serves them
+        assert_eq!(
+            normalized(
+                ProviderId::TogetherAI,
+                json!({"model": "openai/o3-mini", "messages": messages, "max_tokens": 100})
+            ),
+            (None, Some(100))
+        );
+    }
+
+    #[test]
+    fn test_default_max_tokens_fills_anthropic_limit() {
+        let chat_req: ChatCompletionsRequest = serde_json::from_value(json!({
+            "model": "claude-sonnet-4-5",
+            "messages": [{"role": "user", "content": "hi"}],
+            "reasoning_effort": "low"
+        }))
+        .unwrap();
+        let options = TransformOptions {
+            default_max_tokens: 2000,
+            ..Default::default()
+        };
+        let upstream = SupportedUpstreamAPIs::AnthropicMessagesAPI(Messages);
+
+        let request = ProviderRequestType::try_from((
+            ProviderRequestType::ChatCompletionsRequest(chat_req.clone()),
+            &upstream,
+            options,
+        ))
+        .unwrap();
+        let ProviderRequestType::MessagesRequest(messages_req) = request else {
+            panic!("expected messages request");
+        };
+        assert_eq!(
+            messages_req.max_tokens,
+            crate::transforms::LOW_REASONING_BUDGET + 2000
+        );
+
+        // A limit from the client is kept
+        let mut limited = chat_req;
+        limited.max_tokens = Some(3000);
+        let request = ProviderRequestType::try_from((
+            ProviderRequestType::ChatCompletionsRequest(limited),
+            &upstream,
+            options,
+        ))
+        .unwrap();
+        let ProviderRequestType::MessagesRequest(messages_req) = request else {
+            panic!("expected messages request");
+        };
+        assert_eq!(messages_req.max_tokens, 3000);
+    }
+
     #[test]
     fn test_normalize_for_upstream_non_xai_keeps_chat_web_search_options() {
         use crate::apis::openai::{Message, MessageContent, OpenAIApi, Role};
@@ -847,6 +1377,7 @@ mod tests {
                 name: None,
                 tool_calls: None,
                 tool_call_id: None,
+                thinking_blocks: None,
             }],
             web_search_options: Some(serde_json::json!({"search_context_size":"medium"})),
             ..Default::default()
@@ -982,6 +1513,7 @@ mod tests {
                 name: None,
                 tool_calls: None,
                 tool_call_id: None,
+                thinking_blocks: None,
             }],
             ..Default::default()
         };
@@ -1026,6 +1558,7 @@ mod tests {
             tools: None,
             tool_choice: None,
             metadata: None,
+            platform: None,
         };
 
         let upstream_api = SupportedUpstreamAPIs::OpenAIResponsesAPI(Responses);
@@ -1075,6 +1608,7 @@ mod tests {
                     name: None,
                     tool_calls: None,
                     tool_call_id: None,
+                    thinking_blocks: None,
                 },
                 Message {
                     role: Role::User,
@@ -1082,6 +1616,7 @@ mod tests {
                     name: None,
                     tool_calls: None,
                     tool_call_id: None,
+                    thinking_blocks: None,
                 },
             ],
             ..Default::default()
@@ -1122,6 +1657,7 @@ mod tests {
             stop_sequences: None,
             tools: None,
             tool_choice: None,
+            platform: None,
         };
 
         let provider_req = ProviderRequestType::MessagesRequest(anthropic_req);
@@ -1,11 +1,17 @@
-use crate::apis::amazon_bedrock::ConverseResponse;
-use crate::apis::anthropic::MessagesResponse;
-use crate::apis::openai::ChatCompletionsResponse;
-use crate::apis::openai_responses::ResponsesAPIResponse;
+use crate::apis::amazon_bedrock::{ConverseResponse, InvokeModelResponse};
+use crate::apis::anthropic::{MessagesResponse, MessagesUsage};
+use crate::apis::cohere::CohereChatResponse;
+use crate::apis::gemini::GenerateContentResponse;
+use crate::apis::ollama::OllamaChatResponse;
+use crate::apis::openai::{ChatCompletionsResponse, Usage};
+use crate::apis::openai_responses::{ResponseUsage, ResponsesAPIResponse};
 use crate::clients::endpoints::SupportedAPIsFromClient;
 use crate::clients::endpoints::SupportedUpstreamAPIs;
 use crate::providers::id::ProviderId;
-use serde::Serialize;
+use crate::transforms::lossy::{dropped_response_content, TransformOptions};
+use crate::transforms::response::to_openai::restore_server_tool_calls;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
 use std::convert::TryFrom;
 use std::error::Error;
 use std::fmt;
@@ -50,6 +56,61 @@ pub struct UsageDetails {
     pub reasoning_tokens: Option<usize>,
 }
 
+impl From<&dyn TokenUsage> for UsageDetails {
+    fn from(u: &dyn TokenUsage) -> Self {
+        UsageDetails {
+            prompt_tokens: u.prompt_tokens(),
+            completion_tokens: u.completion_tokens(),
+            total_tokens: u.total_tokens(),
+            cached_input_tokens: u.cached_input_tokens(),
+            cache_creation_tokens: u.cache_creation_tokens(),
+            reasoning_tokens: u.reasoning_tokens(),
+        }
+    }
+}
+
+impl UsageDetails {
+    /// Read only the `usage` of a response body already in the client's
+    /// shape, for responses that are passed through without transformation.
+    /// The rest of the body is skipped rather than deserialized.
+    pub fn from_response_body(body: &[u8], client_api: &SupportedAPIsFromClient) -> Option<Self> {
+        #[derive(Deserialize)]
+        struct UsageOnly<T> {
+            usage: Option<T>,
+        }
+
+        fn read<T: TokenUsage + DeserializeOwned>(body: &[u8]) -> Option<UsageDetails> {
+            let usage = serde_json::from_slice::<UsageOnly<T>>(body).ok()?.usage?;
+            Some(UsageDetails::from(&usage as &dyn TokenUsage))
+        }
+
+        match client_api {
+            SupportedAPIsFromClient::OpenAIChatCompletions(_) => read::<Usage>(body),
+            SupportedAPIsFromClient::AnthropicMessagesAPI(_) => read::<MessagesUsage>(body),
+            SupportedAPIsFromClient::OpenAIResponsesAPI(_) => read::<ResponseUsage>(body),
+        }
+    }
+
+    /// Fold in usage reported later in the same stream. Streams report running
+    /// totals, sometimes split across events (Anthropic sends the input tokens
+    /// with `message_start` and the output tokens with `message_delta`), so a
+    /// later count replaces an earlier one unless it is missing or zero.
+    pub fn accumulate(&mut self, later: &UsageDetails) {
+        if later.prompt_tokens > 0 {
+            self.prompt_tokens = later.prompt_tokens;
+        }
+        if later.completion_tokens > 0 {
+            self.completion_tokens = later.completion_tokens;
+        }
+        self.cached_input_tokens = later.cached_input_tokens.or(self.cached_input_tokens);
+        self.cache_creation_tokens = later.cache_creation_tokens.or(self.cache_creation_tokens);
+        self.reasoning_tokens = later.reasoning_tokens.or(self.reasoning_tokens);
+        self.total_tokens = later
+            .total_tokens
+            .max(self.prompt_tokens + self.completion_tokens);
+    }
+}
+
 pub trait ProviderResponse: Send + Sync {
     /// Get usage information if available - returns dynamic trait object
     fn usage(&self) -> Option<&dyn TokenUsage>;
@@ -62,14 +123,7 @@ pub trait ProviderResponse: Send + Sync {
 
     /// Extract a rich usage breakdown including cached/cache-creation/reasoning tokens.
     fn extract_usage_details(&self) -> Option<UsageDetails> {
-        self.usage().map(|u| UsageDetails {
-            prompt_tokens: u.prompt_tokens(),
-            completion_tokens: u.completion_tokens(),
-            total_tokens: u.total_tokens(),
-            cached_input_tokens: u.cached_input_tokens(),
-            cache_creation_tokens: u.cache_creation_tokens(),
-            reasoning_tokens: u.reasoning_tokens(),
-        })
+        self.usage().map(UsageDetails::from)
     }
 }
 
@@ -107,7 +161,48 @@ impl TryFrom<(&[u8], &SupportedAPIsFromClient, &ProviderId)> for ProviderRespons
         (bytes, client_api, provider_id): (&[u8], &SupportedAPIsFromClient, &ProviderId),
     ) -> Result<Self, Self::Error> {
         let upstream_api = provider_id.compatible_api_for_client(client_api, false);
-        match (&upstream_api, client_api) {
+        ProviderResponseType::try_from((bytes, client_api, &upstream_api))
+    }
+}
+
+impl
+    TryFrom<(
+        &[u8],
+        &SupportedAPIsFromClient,
+        &SupportedUpstreamAPIs,
+        TransformOptions,
+    )> for ProviderResponseType
+{
+    type Error = std::io::Error;
+
+    /// Convert for `client_api`, failing in strict mode rather than dropping
+    /// content the client's API can't express.
+    fn try_from(
+        (bytes, client_api, upstream_api, options): (
+            &[u8],
+            &SupportedAPIsFromClient,
+            &SupportedUpstreamAPIs,
+            TransformOptions,
+        ),
+    ) -> Result<Self, Self::Error> {
+        options
+            .check(|| dropped_response_content(bytes, client_api, upstream_api))
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        ProviderResponseType::try_from((bytes, client_api, upstream_api))
+    }
+}
+
+impl TryFrom<(&[u8], &SupportedAPIsFromClient, &SupportedUpstreamAPIs)> for ProviderResponseType {
+    type Error = std::io::Error;
+
+    fn try_from(
+        (bytes, client_api, upstream_api): (
+            &[u8],
+            &SupportedAPIsFromClient,
+            &SupportedUpstreamAPIs,
+        ),
+    ) -> Result<Self, Self::Error> {
+        match (upstream_api, client_api) {
             (
                 SupportedUpstreamAPIs::OpenAIChatCompletions(_),
                 SupportedAPIsFromClient::OpenAIChatCompletions(_),
@@ -225,6 +320,7 @@ impl TryFrom<(&[u8], &SupportedAPIsFromClient, &ProviderId)> for ProviderRespons
                 //Chain transform: Anthropic Messages -> OpenAI ChatCompletions -> ResponsesAPI
                 let anthropic_resp: MessagesResponse = serde_json::from_slice(bytes)
                     .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+                let content = anthropic_resp.content.clone();
 
                 // Transform to ChatCompletions format using the transformer
                 let chat_resp: ChatCompletionsResponse =
@@ -235,12 +331,13 @@ impl TryFrom<(&[u8], &SupportedAPIsFromClient, &ProviderId)> for ProviderRespons
                         )
                     })?;
 
-                let response_api: ResponsesAPIResponse = chat_resp.try_into().map_err(|e| {
+                let mut response_api: ResponsesAPIResponse = chat_resp.try_into().map_err(|e| {
                     std::io::Error::new(
                         std::io::ErrorKind::InvalidData,
                         format!("Transformation error: {}", e),
                     )
                 })?;
+                restore_server_tool_calls(&mut response_api, &content);
                 Ok(ProviderResponseType::ResponsesAPIResponse(Box::new(
                     response_api,
                 )))
@@ -275,6 +372,127 @@ impl TryFrom<(&[u8], &SupportedAPIsFromClient, &ProviderId)> for ProviderRespons
                     response_api,
                 )))
             }
+            (
+                SupportedUpstreamAPIs::AmazonBedrockInvokeModel(_),
+                SupportedAPIsFromClient::OpenAIChatCompletions(_),
+            ) => {
+                let invoke_resp: InvokeModelResponse = serde_json::from_slice(bytes)
+                    .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+
+                let chat_resp: ChatCompletionsResponse = invoke_resp.try_into().map_err(|e| {
+                    std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!("Transformation error: {}", e),
+                    )
+                })?;
+                Ok(ProviderResponseType::ChatCompletionsResponse(chat_resp))
+            }
+            (
+                SupportedUpstreamAPIs::AmazonBedrockInvokeModel(_),
+                SupportedAPIsFromClient::AnthropicMessagesAPI(_),
+            ) => {
+                // Claude answers InvokeModel in the Messages shape already
+                let messages_resp: MessagesResponse = serde_json::from_slice(bytes)
+                    .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+                Ok(ProviderResponseType::MessagesResponse(messages_resp))
+            }
+            // Gemini transformations
+            (
+                SupportedUpstreamAPIs::GeminiGenerateContent(_),
+                SupportedAPIsFromClient::OpenAIChatCompletions(_),
+            ) => {
+                let gemini_resp: GenerateContentResponse = serde_json::from_slice(bytes)
+                    .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+
+                let chat_resp: ChatCompletionsResponse = gemini_resp.try_into().map_err(|e| {
+                    std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!("Transformation error: {}", e),
+                    )
+                })?;
+                Ok(ProviderResponseType::ChatCompletionsResponse(chat_resp))
+            }
+            (
+                SupportedUpstreamAPIs::GeminiGenerateContent(_),
+                SupportedAPIsFromClient::AnthropicMessagesAPI(_),
+            ) => {
+                // Chain transform: Gemini -> ChatCompletions -> Anthropic Messages
+                let gemini_resp: GenerateContentResponse = serde_json::from_slice(bytes)
+                    .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+
+                let chat_resp: ChatCompletionsResponse = gemini_resp.try_into().map_err(|e| {
+                    std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!("Gemini to ChatCompletions transformation error: {}", e),
+                    )
+                })?;
+                let messages_resp: MessagesResponse = chat_resp.try_into().map_err(|e| {
+                    std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!("Transformation error: {}", e),
+                    )
+                })?;
+                Ok(ProviderResponseType::MessagesResponse(messages_resp))
+            }
+            (
+                SupportedUpstreamAPIs::GeminiGenerateContent(_),
+                SupportedAPIsFromClient::OpenAIResponsesAPI(_),
+            ) => {
+                // Chain transform: Gemini -> ChatCompletions -> ResponsesAPI
+                let gemini_resp: GenerateContentResponse = serde_json::from_slice(bytes)
+                    .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+
+                let chat_resp: ChatCompletionsResponse = gemini_resp.try_into().map_err(|e| {
+                    std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!("Gemini to ChatCompletions transformation error: {}", e),
+                    )
+                })?;
+                let response_api: ResponsesAPIResponse = chat_resp.try_into().map_err(|e| {
+                    std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!(
+                            "ChatCompletions to ResponsesAPI transformation error: {}",
+                            e
+                        ),
+                    )
+                })?;
+                Ok(ProviderResponseType::ResponsesAPIResponse(Box::new(
+                    response_api,
+                )))
+            }
+            // Cohere transformations
+            (
+                SupportedUpstreamAPIs::CohereChat(_),
+                SupportedAPIsFromClient::OpenAIChatCompletions(_),
+            ) => {
+                let cohere_resp: CohereChatResponse = serde_json::from_slice(bytes)
+                    .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+
+                let chat_resp: ChatCompletionsResponse = cohere_resp.try_into().map_err(|e| {
+                    std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!("Transformation error: {}", e),
+                    )
+                })?;
+                Ok(ProviderResponseType::ChatCompletionsResponse(chat_resp))
+            }
+            // Ollama transformations
+            (
+                SupportedUpstreamAPIs::OllamaChat(_),
+                SupportedAPIsFromClient::OpenAIChatCompletions(_),
+            ) => {
+                let ollama_resp: OllamaChatResponse = serde_json::from_slice(bytes)
+                    .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+
+                let chat_resp: ChatCompletionsResponse = ollama_resp.try_into().map_err(|e| {
+                    std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!("Transformation error: {}", e),
+                    )
+                })?;
+                Ok(ProviderResponseType::ChatCompletionsResponse(chat_resp))
+            }
             _ => Err(std::io::Error::new(
                 std::io::ErrorKind::InvalidData,
                 "Unsupported API combination for response transformation",
@@ -312,6 +530,32 @@ mod tests {
     use crate::providers::id::ProviderId;
     use serde_json::json;
 
+    #[test]
+    fn test_usage_details_from_response_body() {
+        let body = json!({
+            "id": "msg_01",
+            "type": "message",
+            "role": "assistant",
+            "content": [{"type": "text", "text": "Hi"}],
+            "usage": {"input_tokens": 12, "output_tokens": 4, "cache_read_input_tokens": 8}
+        });
+        let usage = UsageDetails::from_response_body(
+            body.to_string().as_bytes(),
+            &SupportedAPIsFromClient::AnthropicMessagesAPI(AnthropicApi::Messages),
+        )
+        .unwrap();
+        assert_eq!(usage.prompt_tokens, 12);
+        assert_eq!(usage.completion_tokens, 4);
+        assert_eq!(usage.cached_input_tokens, Some(8));
+
+        let without_usage = json!({"id": "chatcmpl-1", "choices": []});
+        assert!(UsageDetails::from_response_body(
+            without_usage.to_string().as_bytes(),
+            &SupportedAPIsFromClient::OpenAIChatCompletions(OpenAIApi::ChatCompletions),
+        )
+        .is_none());
+    }
+
     #[test]
     fn test_openai_response_from_bytes() {
         let resp = json!({
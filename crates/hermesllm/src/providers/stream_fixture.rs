@@ -0,0 +1,350 @@
+//! Record-and-replay fixtures for provider streams.
+//!
+//! [`ProviderStreamRecorder`] captures the raw chunks of an upstream stream,
+//! SSE text or Bedrock event-stream frames, with the time each one arrived.
+//! The resulting [`StreamFixture`] is saved to a file and loaded back in
+//! tests, where [`ProviderStreamReplay`] plays the chunks back with their
+//! original timing and [`StreamFixture::replay_responses`] parses them into
+//! the stream responses a client would receive. Transforms and handlers can
+//! then be tested against real provider output deterministically.
+//!
+//! A fixture file is JSON Lines with one chunk per line, either
+//! `{"offset_ms": 12, "text": "data: {...}\n\n"}` or, for chunks that are not
+//! UTF-8, `{"offset_ms": 12, "binary": "<base64>"}`.
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use aws_smithy_eventstream::frame::DecodedFrame;
+use bytes::Bytes;
+use futures_core::Stream;
+use serde::{Deserialize, Serialize};
+use serde_with::{base64::Base64, serde_as, skip_serializing_none};
+
+use crate::apis::streaming_shapes::amazon_bedrock_binary_frame::BedrockBinaryFrameDecoder;
+use crate::apis::streaming_shapes::sse_chunk_processor::SseChunkProcessor;
+use crate::clients::endpoints::{SupportedAPIsFromClient, SupportedUpstreamAPIs};
+use crate::providers::streaming_response::ProviderStreamResponseType;
+
+/// One upstream chunk and when it arrived, relative to the start of recording.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordedChunk {
+    pub offset: Duration,
+    pub bytes: Bytes,
+}
+
+/// A fixture file line.
+#[serde_as]
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize)]
+struct ChunkLine {
+    offset_ms: u64,
+    text: Option<String>,
+    #[serde_as(as = "Option<Base64>")]
+    #[serde(default)]
+    binary: Option<Vec<u8>>,
+}
+
+impl From<&RecordedChunk> for ChunkLine {
+    fn from(chunk: &RecordedChunk) -> Self {
+        let (text, binary) = match std::str::from_utf8(&chunk.bytes) {
+            Ok(text) => (Some(text.to_string()), None),
+            Err(_) => (None, Some(chunk.bytes.to_vec())),
+        };
+        ChunkLine {
+            offset_ms: chunk.offset.as_millis() as u64,
+            text,
+            binary,
+        }
+    }
+}
+
+impl From<ChunkLine> for RecordedChunk {
+    fn from(line: ChunkLine) -> Self {
+        let bytes = match (line.text, line.binary) {
+            (Some(text), _) => Bytes::from(text),
+            (None, Some(binary)) => Bytes::from(binary),
+            (None, None) => Bytes::new(),
+        };
+        RecordedChunk {
+            offset: Duration::from_millis(line.offset_ms),
+            bytes,
+        }
+    }
+}
+
+/// Captures the chunks of an upstream stream as they arrive.
+#[derive(Debug)]
+pub struct ProviderStreamRecorder {
+    started: Instant,
+    chunks: Vec<RecordedChunk>,
+}
+
+impl Default for ProviderStreamRecorder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProviderStreamRecorder {
+    /// Start recording. Offsets are measured from here, so create the
+    /// recorder when the request is sent to keep the time to first byte.
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            chunks: Vec::new(),
+        }
+    }
+
+    /// Record a chunk as received from the upstream.
+    pub fn record(&mut self, chunk: &[u8]) {
+        self.chunks.push(RecordedChunk {
+            offset: self.started.elapsed(),
+            bytes: Bytes::copy_from_slice(chunk),
+        });
+    }
+
+    pub fn into_fixture(self) -> StreamFixture {
+        StreamFixture {
+            chunks: self.chunks,
+        }
+    }
+}
+
+/// A recorded upstream stream.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StreamFixture {
+    pub chunks: Vec<RecordedChunk>,
+}
+
+impl StreamFixture {
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::from_reader(BufReader::new(File::open(path)?))
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.to_writer(&mut writer)?;
+        writer.flush()
+    }
+
+    /// Read a fixture from JSON Lines; blank lines are skipped.
+    pub fn from_reader(reader: impl BufRead) -> io::Result<Self> {
+        let mut chunks = Vec::new();
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let line: ChunkLine = serde_json::from_str(&line)?;
+            chunks.push(line.into());
+        }
+        Ok(Self { chunks })
+    }
+
+    pub fn to_writer(&self, mut writer: impl Write) -> io::Result<()> {
+        for chunk in &self.chunks {
+            serde_json::to_writer(&mut writer, &ChunkLine::from(chunk))?;
+            writer.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+
+    /// Play the chunks back as a byte stream with their original timing.
+    pub fn replay(&self) -> ProviderStreamReplay {
+        ProviderStreamReplay {
+            chunks: self.chunks.clone(),
+            next: 0,
+            started: None,
+            timer_armed: false,
+            with_delays: true,
+        }
+    }
+
+    /// Parse the chunks into the stream responses a `client_api` client gets
+    /// from `upstream_api`, the way the gateway does: Bedrock event-stream
+    /// frames are decoded, everything else goes through the SSE processor.
+    pub fn replay_responses(
+        &self,
+        client_api: &SupportedAPIsFromClient,
+        upstream_api: &SupportedUpstreamAPIs,
+    ) -> Result<Vec<ProviderStreamResponseType>, String> {
+        let mut responses = Vec::new();
+        if matches!(
+            upstream_api,
+            SupportedUpstreamAPIs::AmazonBedrockConverseStream(_)
+                | SupportedUpstreamAPIs::AmazonBedrockInvokeModelStream(_)
+        ) {
+            let mut decoder = BedrockBinaryFrameDecoder::from_bytes(&[]);
+            for chunk in &self.chunks {
+                decoder.buffer_mut().extend_from_slice(&chunk.bytes);
+                while let Some(frame @ DecodedFrame::Complete(_)) = decoder.decode_frame() {
+                    let response =
+                        ProviderStreamResponseType::try_from((&frame, client_api, upstream_api))
+                            .map_err(|e| e.to_string())?;
+                    responses.push(response);
+                }
+            }
+        } else {
+            let mut processor = SseChunkProcessor::new();
+            for chunk in &self.chunks {
+                let events = processor.process_chunk(&chunk.bytes, client_api, upstream_api)?;
+                responses.extend(
+                    events
+                        .into_iter()
+                        .filter_map(|event| event.provider_stream_response),
+                );
+            }
+        }
+        Ok(responses)
+    }
+}
+
+/// A recorded stream played back as bytes. Each chunk is yielded once its
+/// recorded offset has passed since the first poll; waiting doesn't depend
+/// on an async runtime.
+pub struct ProviderStreamReplay {
+    chunks: Vec<RecordedChunk>,
+    next: usize,
+    started: Option<Instant>,
+    timer_armed: bool,
+    with_delays: bool,
+}
+
+impl ProviderStreamReplay {
+    /// Yield every chunk as soon as it's polled.
+    pub fn without_delays(mut self) -> Self {
+        self.with_delays = false;
+        self
+    }
+}
+
+impl Stream for ProviderStreamReplay {
+    type Item = Bytes;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Bytes>> {
+        let started = *self.started.get_or_insert_with(Instant::now);
+        let Some(chunk) = self.chunks.get(self.next) else {
+            return Poll::Ready(None);
+        };
+        let wait = chunk.offset.saturating_sub(started.elapsed());
+        if self.with_delays && !wait.is_zero() {
+            if !self.timer_armed {
+                self.timer_armed = true;
+                let waker = cx.waker().clone();
+                std::thread::spawn(move || {
+                    std::thread::sleep(wait);
+                    waker.wake();
+                });
+            }
+            return Poll::Pending;
+        }
+        let bytes = chunk.bytes.clone();
+        self.next += 1;
+        self.timer_armed = false;
+        Poll::Ready(Some(bytes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::apis::amazon_bedrock::ConverseStreamEvent;
+    use crate::apis::amazon_bedrock::{ContentBlockDelta, ContentBlockDeltaEvent};
+    use crate::apis::streaming_shapes::amazon_bedrock_binary_frame::BedrockBinaryFrameEncoder;
+    use crate::apis::{AmazonBedrockApi, AnthropicApi, OpenAIApi};
+    use futures::executor::block_on;
+    use futures::StreamExt;
+
+    fn chunk(offset_ms: u64, bytes: &[u8]) -> RecordedChunk {
+        RecordedChunk {
+            offset: Duration::from_millis(offset_ms),
+            bytes: Bytes::copy_from_slice(bytes),
+        }
+    }
+
+    #[test]
+    fn fixture_round_trips_text_and_binary_chunks() {
+        let fixture = StreamFixture {
+            chunks: vec![
+                chunk(0, b"data: {\"a\":1}\n\n"),
+                chunk(15, &[0x00, 0x00, 0x01, 0xff, 0xfe]),
+            ],
+        };
+
+        let mut file = Vec::new();
+        fixture.to_writer(&mut file).unwrap();
+        let text = String::from_utf8(file.clone()).unwrap();
+
+        assert!(text.starts_with(r#"{"offset_ms":0,"text":"data: {\"a\":1}\n\n"}"#));
+        assert!(text.contains(r#""binary":"#));
+        assert_eq!(
+            StreamFixture::from_reader(file.as_slice()).unwrap(),
+            fixture
+        );
+    }
+
+    #[test]
+    fn replay_keeps_the_recorded_timing() {
+        let fixture = StreamFixture {
+            chunks: vec![chunk(0, b"first"), chunk(40, b"second")],
+        };
+
+        let started = Instant::now();
+        let replayed: Vec<Bytes> = block_on(fixture.replay().collect());
+        assert_eq!(replayed, vec![Bytes::from("first"), Bytes::from("second")]);
+        assert!(started.elapsed() >= Duration::from_millis(40));
+
+        let replayed: Vec<Bytes> = block_on(fixture.replay().without_delays().collect());
+        assert_eq!(replayed.len(), 2);
+    }
+
+    #[test]
+    fn replay_responses_parses_sse_and_bedrock_fixtures() {
+        let sse = StreamFixture {
+            chunks: vec![
+                chunk(0, b"event: content_block_delta\ndata: {\"type\":\"content_block_delta\",\"index\":0,"),
+                chunk(5, b"\"delta\":{\"type\":\"text_delta\",\"text\":\"Hi\"}}\n\n"),
+            ],
+        };
+        let client = SupportedAPIsFromClient::OpenAIChatCompletions(OpenAIApi::ChatCompletions);
+        let responses = sse
+            .replay_responses(
+                &client,
+                &SupportedUpstreamAPIs::AnthropicMessagesAPI(AnthropicApi::Messages),
+            )
+            .unwrap();
+        assert_eq!(responses.len(), 1);
+        assert!(matches!(
+            &responses[0],
+            ProviderStreamResponseType::ChatCompletionsStreamResponse(_)
+        ));
+
+        let frame = BedrockBinaryFrameEncoder::encode_event(
+            &ConverseStreamEvent::ContentBlockDelta(ContentBlockDeltaEvent {
+                content_block_index: 0,
+                delta: ContentBlockDelta::Text {
+                    text: "Hi".to_string(),
+                },
+            }),
+        )
+        .unwrap();
+        let (head, tail) = frame.split_at(10);
+        let bedrock = StreamFixture {
+            chunks: vec![chunk(0, head), chunk(5, tail)],
+        };
+        let responses = bedrock
+            .replay_responses(
+                &SupportedAPIsFromClient::AnthropicMessagesAPI(AnthropicApi::Messages),
+                &SupportedUpstreamAPIs::AmazonBedrockConverseStream(
+                    AmazonBedrockApi::ConverseStream,
+                ),
+            )
+            .unwrap();
+        assert_eq!(responses.len(), 1);
+    }
+}
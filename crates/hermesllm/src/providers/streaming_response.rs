@@ -1,4 +1,5 @@
 use serde::Serialize;
+use std::borrow::Cow;
 use std::convert::TryFrom;
 
 use crate::apis::amazon_bedrock::ConverseStreamEvent;
@@ -15,6 +16,7 @@ use crate::apis::streaming_shapes::{
 
 use crate::clients::endpoints::SupportedAPIsFromClient;
 use crate::clients::endpoints::SupportedUpstreamAPIs;
+use crate::providers::response::UsageDetails;
 
 // ============================================================================
 // SSE STREAM BUFFER FACTORY
@@ -113,6 +115,12 @@ pub trait ProviderStreamResponse: Send + Sync {
 
     /// Get event type for SSE streaming (used by Anthropic)
     fn event_type(&self) -> Option<&str>;
+
+    /// Usage reported by this chunk. Streams report running totals, so the
+    /// stream's usage is the last report, see `SseStreamBufferTrait::final_usage`.
+    fn usage(&self) -> Option<UsageDetails> {
+        None
+    }
 }
 
 impl ProviderStreamResponse for ProviderStreamResponseType {
@@ -151,6 +159,15 @@ impl ProviderStreamResponse for ProviderStreamResponseType {
             ProviderStreamResponseType::ResponseAPIStreamEvent(resp) => resp.event_type(),
         }
     }
+
+    fn usage(&self) -> Option<UsageDetails> {
+        match self {
+            ProviderStreamResponseType::ChatCompletionsStreamResponse(resp) => resp.usage(),
+            ProviderStreamResponseType::MessagesStreamEvent(resp) => resp.usage(),
+            ProviderStreamResponseType::ConverseStreamEvent(resp) => resp.usage(),
+            ProviderStreamResponseType::ResponseAPIStreamEvent(resp) => resp.usage(),
+        }
+    }
 }
 
 impl From<ProviderStreamResponseType> for String {
@@ -261,6 +278,17 @@ impl TryFrom<(&[u8], &SupportedAPIsFromClient, &SupportedUpstreamAPIs)>
                     openai_resp,
                 ))
             }
+            (
+                SupportedUpstreamAPIs::AnthropicMessagesAPI(_),
+                SupportedAPIsFromClient::OpenAIResponsesAPI(_),
+            ) => {
+                let anthropic_resp: crate::apis::anthropic::MessagesStreamEvent =
+                    serde_json::from_slice(bytes)?;
+                let responses_resp: ResponsesAPIStreamEvent = anthropic_resp.try_into()?;
+                Ok(ProviderStreamResponseType::ResponseAPIStreamEvent(
+                    Box::new(responses_resp),
+                ))
+            }
 
             // Amazon Bedrock ConverseStream upstream
             (
@@ -288,6 +316,45 @@ impl TryFrom<(&[u8], &SupportedAPIsFromClient, &SupportedUpstreamAPIs)>
                     Box::new(responses_resp),
                 ))
             }
+
+            // Gemini streamGenerateContent upstream
+            (
+                SupportedUpstreamAPIs::GeminiStreamGenerateContent(_),
+                SupportedAPIsFromClient::OpenAIChatCompletions(_),
+            ) => {
+                let gemini_resp: crate::apis::gemini::GenerateContentResponse =
+                    serde_json::from_slice(bytes)?;
+                let openai_resp = gemini_resp.try_into()?;
+                Ok(ProviderStreamResponseType::ChatCompletionsStreamResponse(
+                    openai_resp,
+                ))
+            }
+
+            // Cohere chat upstream
+            (
+                SupportedUpstreamAPIs::CohereChat(_),
+                SupportedAPIsFromClient::OpenAIChatCompletions(_),
+            ) => {
+                let cohere_resp: crate::apis::cohere::CohereStreamEvent =
+                    serde_json::from_slice(bytes)?;
+                let openai_resp = cohere_resp.try_into()?;
+                Ok(ProviderStreamResponseType::ChatCompletionsStreamResponse(
+                    openai_resp,
+                ))
+            }
+
+            // Ollama chat upstream; NDJSON lines are reframed as SSE data upstream of here
+            (
+                SupportedUpstreamAPIs::OllamaChat(_),
+                SupportedAPIsFromClient::OpenAIChatCompletions(_),
+            ) => {
+                let ollama_resp: crate::apis::ollama::OllamaChatResponse =
+                    serde_json::from_slice(bytes)?;
+                let openai_resp = ollama_resp.try_into()?;
+                Ok(ProviderStreamResponseType::ChatCompletionsStreamResponse(
+                    openai_resp,
+                ))
+            }
             _ => Err(std::io::Error::new(
                 std::io::ErrorKind::InvalidData,
                 "Unsupported API combination for response transformation",
@@ -297,6 +364,36 @@ impl TryFrom<(&[u8], &SupportedAPIsFromClient, &SupportedUpstreamAPIs)>
     }
 }
 
+/// Anthropic and Responses API streams name each event on its `event:` line
+/// and repeat the name as the payload's `type`, which selects the variant the
+/// payload parses into. When the payload leaves `type` out, take it from the
+/// event name instead.
+fn typed_event_payload<'a>(
+    data: &'a str,
+    event: Option<&str>,
+    upstream_api: &SupportedUpstreamAPIs,
+) -> Cow<'a, str> {
+    let Some(event) = event else {
+        return Cow::Borrowed(data);
+    };
+    if !matches!(
+        upstream_api,
+        SupportedUpstreamAPIs::AnthropicMessagesAPI(_)
+            | SupportedUpstreamAPIs::OpenAIResponsesAPI(_)
+    ) || data.trim_start().starts_with(r#"{"type""#)
+    {
+        // Providers put `type` first, so this skips the reparse on the hot path
+        return Cow::Borrowed(data);
+    }
+    match serde_json::from_str::<serde_json::Value>(data) {
+        Ok(serde_json::Value::Object(mut payload)) if !payload.contains_key("type") => {
+            payload.insert("type".to_string(), event.into());
+            Cow::Owned(serde_json::Value::Object(payload).to_string())
+        }
+        _ => Cow::Borrowed(data),
+    }
+}
+
 // TryFrom implementation to convert raw bytes to SseEvent with parsed provider response
 impl TryFrom<(SseEvent, &SupportedAPIsFromClient, &SupportedUpstreamAPIs)> for SseEvent {
     type Error = Box<dyn std::error::Error + Send + Sync>;
@@ -328,9 +425,14 @@ impl TryFrom<(SseEvent, &SupportedAPIsFromClient, &SupportedUpstreamAPIs)> for S
 
         // If has data, parse the data as a provider stream response (business logic layer)
         if let Some(data_str) = &transformed_event.data {
-            let data_bytes = data_str.as_bytes();
+            let payload =
+                typed_event_payload(data_str, transformed_event.event.as_deref(), upstream_api);
             let transformed_response: ProviderStreamResponseType =
-                ProviderStreamResponseType::try_from((data_bytes, client_api, upstream_api))?;
+                ProviderStreamResponseType::try_from((
+                    payload.as_bytes(),
+                    client_api,
+                    upstream_api,
+                ))?;
 
             // Convert to SSE string explicitly to avoid type ambiguity
             let sse_string: String = transformed_response.clone().into();
@@ -344,14 +446,14 @@ impl TryFrom<(SseEvent, &SupportedAPIsFromClient, &SupportedUpstreamAPIs)> for S
         if needs_buffering(client_api, upstream_api) {
             match (client_api, upstream_api) {
                 (
-                    SupportedAPIsFromClient::OpenAIChatCompletions(_),
-                    SupportedUpstreamAPIs::AnthropicMessagesAPI(_),
-                ) => {
+                    SupportedAPIsFromClient::OpenAIChatCompletions(_)
+                    | SupportedAPIsFromClient::OpenAIResponsesAPI(_),
+                    SupportedUpstreamAPIs::AnthropicMessagesAPI(_)
+                    | SupportedUpstreamAPIs::CohereChat(_),
+                ) if transformed_event.is_event_only() && transformed_event.event.is_some() => {
                     // OpenAI clients don't expect separate event: lines
-                    // Suppress upstream Anthropic event-only lines
-                    if transformed_event.is_event_only() && transformed_event.event.is_some() {
-                        transformed_event.sse_transformed_lines = "\n".to_string();
-                    }
+                    // Suppress upstream Anthropic and Cohere event-only lines
+                    transformed_event.sse_transformed_lines = "\n".to_string();
                 }
                 _ => {
                     // Other cross-API combinations can be handled here as needed
@@ -371,12 +473,10 @@ impl TryFrom<(SseEvent, &SupportedAPIsFromClient, &SupportedUpstreamAPIs)> for S
                 | (
                     SupportedAPIsFromClient::OpenAIResponsesAPI(_),
                     SupportedUpstreamAPIs::OpenAIResponsesAPI(_),
-                ) => {
-                    if transformed_event.is_event_only() && transformed_event.event.is_some() {
-                        // Mark as should-skip by clearing sse_transformed_lines
-                        // The event line is already included when the data line is transformed
-                        transformed_event.sse_transformed_lines = String::new();
-                    }
+                ) if transformed_event.is_event_only() && transformed_event.event.is_some() => {
+                    // Mark as should-skip by clearing sse_transformed_lines
+                    // The event line is already included when the data line is transformed
+                    transformed_event.sse_transformed_lines = String::new();
                 }
                 _ => {
                     // Other passthrough combinations (OpenAI ChatCompletions, etc.) don't have this issue
@@ -454,6 +554,35 @@ impl
                             Box::new(openai_responses_api_event),
                         ))
                     }
+                    (
+                        SupportedUpstreamAPIs::AmazonBedrockInvokeModelStream(_),
+                        SupportedAPIsFromClient::OpenAIChatCompletions(_),
+                    ) => {
+                        // Unwrap the base64 `chunk` payload into the model-specific event
+                        let invoke_chunk =
+                            crate::apis::amazon_bedrock::InvokeModelStreamChunk::try_from(frame)?;
+                        let openai_event: crate::apis::openai::ChatCompletionsStreamResponse =
+                            invoke_chunk.try_into()?;
+                        Ok(ProviderStreamResponseType::ChatCompletionsStreamResponse(
+                            openai_event,
+                        ))
+                    }
+                    (
+                        SupportedUpstreamAPIs::AmazonBedrockInvokeModelStream(_),
+                        SupportedAPIsFromClient::AnthropicMessagesAPI(_),
+                    ) => {
+                        // Only Claude serves Messages clients, and its chunks
+                        // are Messages stream events
+                        match crate::apis::amazon_bedrock::InvokeModelStreamChunk::try_from(frame)? {
+                            crate::apis::amazon_bedrock::InvokeModelStreamChunk::Anthropic(
+                                event,
+                            ) => Ok(ProviderStreamResponseType::MessagesStreamEvent(event)),
+                            crate::apis::amazon_bedrock::InvokeModelStreamChunk::TitanText(_) => {
+                                Err("Titan Text chunks cannot be sent to Anthropic Messages clients"
+                                    .into())
+                            }
+                        }
+                    }
                     _ => Err("Unsupported API combination for event-stream decoding".into()),
                 }
             }
@@ -530,6 +659,8 @@ mod tests {
         "#
             .to_string(),
             provider_stream_response: None,
+            id: None,
+            retry: None,
         };
 
         // Test JSON serialization - raw_line should be skipped
@@ -556,6 +687,8 @@ mod tests {
             raw_line: r#"data: {"type": "ping"}"#.to_string(),
             sse_transformed_lines: r#"data: {"type": "ping"}"#.to_string(),
             provider_stream_response: None,
+            id: None,
+            retry: None,
         };
         assert!(ping_event.should_skip());
         assert!(!ping_event.is_done());
@@ -568,6 +701,8 @@ mod tests {
             sse_transformed_lines: r#"data: {"id": "test", "object": "chat.completion.chunk"}"#
                 .to_string(),
             provider_stream_response: None,
+            id: None,
+            retry: None,
         };
         assert!(!normal_event.should_skip());
         assert!(!normal_event.is_done());
@@ -579,6 +714,8 @@ mod tests {
             raw_line: "data: [DONE]".to_string(),
             sse_transformed_lines: "data: [DONE]".to_string(),
             provider_stream_response: None,
+            id: None,
+            retry: None,
         };
         assert!(!done_event.should_skip());
         assert!(done_event.is_done());
@@ -589,10 +726,14 @@ mod tests {
         // Create test data with ping messages mixed in
         let test_lines = vec![
             "data: {\"id\": \"msg1\", \"object\": \"chat.completion.chunk\"}".to_string(),
+            "".to_string(),
             "data: {\"type\": \"ping\"}".to_string(), // This should be filtered out
+            "".to_string(),
             "data: {\"id\": \"msg2\", \"object\": \"chat.completion.chunk\"}".to_string(),
+            "".to_string(),
             "data: {\"type\": \"ping\"}".to_string(), // This should be filtered out
-            "data: [DONE]".to_string(),               // This should end the stream
+            "".to_string(),
+            "data: [DONE]".to_string(), // This should end the stream
         ];
 
         let mut iter = SseStreamIter::new(test_lines.into_iter());
@@ -621,36 +762,26 @@ mod tests {
         let test_lines = vec![
             "event: message_start".to_string(),
             "data: {\"type\":\"message_start\",\"message\":{\"id\":\"msg_123\"}}".to_string(),
+            "".to_string(),
             "event: content_block_delta".to_string(),
             "data: {\"type\":\"content_block_delta\",\"delta\":{\"text\":\"Hello\"}}".to_string(),
+            "".to_string(),
             "data: [DONE]".to_string(),
         ];
 
         let mut iter = SseStreamIter::new(test_lines.into_iter());
 
-        // First event should be the event: line
+        // The event: line and its data: line form a single event
         let event1 = iter.next().unwrap();
-        assert!(event1.is_event_only());
+        assert!(!event1.is_event_only());
         assert_eq!(event1.event, Some("message_start".to_string()));
-        assert_eq!(event1.data, None);
+        assert!(event1.data.as_ref().unwrap().contains("msg_123"));
 
-        // Second event should be the data: line
         let event2 = iter.next().unwrap();
-        assert!(!event2.is_event_only());
-        assert_eq!(event2.event, None);
-        assert!(event2.data.as_ref().unwrap().contains("message_start"));
-
-        // Third event should be another event: line
-        let event3 = iter.next().unwrap();
-        assert!(event3.is_event_only());
-        assert_eq!(event3.event, Some("content_block_delta".to_string()));
+        assert_eq!(event2.event, Some("content_block_delta".to_string()));
+        assert!(event2.data.as_ref().unwrap().contains("Hello"));
 
-        // Fourth event should be the content delta data
-        let event4 = iter.next().unwrap();
-        assert!(!event4.is_event_only());
-        assert!(event4.data.as_ref().unwrap().contains("Hello"));
-
-        // Fifth event should be [DONE]
+        // Third event should be [DONE]
         let done_event = iter.next().unwrap();
         assert!(done_event.is_done());
 
@@ -683,6 +814,8 @@ mod tests {
             usage: None,
             system_fingerprint: None,
             service_tier: None,
+            citations: None,
+            search_results: None,
         };
         let provider_type = ProviderStreamResponseType::ChatCompletionsStreamResponse(openai_event);
         assert_eq!(provider_type.event_type(), None);
@@ -1076,6 +1209,67 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_bedrock_invoke_model_stream_frames_to_openai_chunks() {
+        use aws_smithy_eventstream::frame::{write_message_to, DecodedFrame};
+        use aws_smithy_types::event_stream::{Header, HeaderValue, Message};
+        use base64::Engine;
+        use bytes::BytesMut;
+
+        let chunk_frame = |payload: serde_json::Value| {
+            let bytes = base64::engine::general_purpose::STANDARD
+                .encode(serde_json::to_vec(&payload).unwrap());
+            let message = Message::new(serde_json::to_vec(&json!({ "bytes": bytes })).unwrap())
+                .add_header(Header::new(
+                    ":event-type",
+                    HeaderValue::String("chunk".into()),
+                ))
+                .add_header(Header::new(
+                    ":message-type",
+                    HeaderValue::String("event".into()),
+                ));
+            let mut out = Vec::new();
+            write_message_to(&message, &mut out).unwrap();
+            out
+        };
+
+        let mut wire = chunk_frame(json!({"outputText": "Hello", "index": 0}));
+        wire.extend(chunk_frame(json!({
+            "outputText": " there",
+            "index": 0,
+            "completionReason": "FINISH",
+            "amazon-bedrock-invocationMetrics": {"inputTokenCount": 4, "outputTokenCount": 2}
+        })));
+        let mut buffer = BytesMut::from(&wire[..]);
+        let mut decoder = BedrockBinaryFrameDecoder::new(&mut buffer);
+
+        let client_api = SupportedAPIsFromClient::OpenAIChatCompletions(
+            crate::apis::openai::OpenAIApi::ChatCompletions,
+        );
+        let upstream_api = SupportedUpstreamAPIs::AmazonBedrockInvokeModelStream(
+            crate::apis::amazon_bedrock::AmazonBedrockApi::InvokeModelWithResponseStream,
+        );
+
+        let mut chunks = Vec::new();
+        while let Some(frame @ DecodedFrame::Complete(_)) = decoder.decode_frame() {
+            match ProviderStreamResponseType::try_from((&frame, &client_api, &upstream_api)) {
+                Ok(ProviderStreamResponseType::ChatCompletionsStreamResponse(chunk)) => {
+                    chunks.push(chunk)
+                }
+                other => panic!("unexpected conversion result: {:?}", other),
+            }
+        }
+
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].choices[0].delta.content.as_deref(), Some("Hello"));
+        assert_eq!(chunks[0].choices[0].finish_reason, None);
+        assert_eq!(
+            chunks[1].choices[0].finish_reason,
+            Some(crate::apis::openai::FinishReason::Stop)
+        );
+        assert_eq!(chunks[1].usage.as_ref().unwrap().total_tokens, 6);
+    }
+
     #[test]
     fn test_sse_event_transformation_openai_to_anthropic_message_delta() {
         use crate::apis::anthropic::AnthropicApi;
@@ -1106,6 +1300,8 @@ mod tests {
             raw_line: format!("data: {}", openai_stream_chunk),
             sse_transformed_lines: format!("data: {}", openai_stream_chunk),
             provider_stream_response: None,
+            id: None,
+            retry: None,
         };
 
         let client_api = SupportedAPIsFromClient::AnthropicMessagesAPI(AnthropicApi::Messages);
@@ -1162,6 +1358,8 @@ mod tests {
             raw_line: format!("data: {}", openai_stream_chunk),
             sse_transformed_lines: format!("data: {}", openai_stream_chunk),
             provider_stream_response: None,
+            id: None,
+            retry: None,
         };
 
         let client_api = SupportedAPIsFromClient::AnthropicMessagesAPI(AnthropicApi::Messages);
@@ -1204,6 +1402,8 @@ mod tests {
             raw_line: "event: message_start".to_string(),
             sse_transformed_lines: "event: message_start".to_string(),
             provider_stream_response: None,
+            id: None,
+            retry: None,
         };
 
         let client_api = SupportedAPIsFromClient::OpenAIChatCompletions(OpenAIApi::ChatCompletions);
@@ -1251,6 +1451,8 @@ mod tests {
             raw_line: format!("data: {}", anthropic_event),
             sse_transformed_lines: format!("data: {}", anthropic_event),
             provider_stream_response: None,
+            id: None,
+            retry: None,
         };
 
         let client_api = SupportedAPIsFromClient::OpenAIChatCompletions(OpenAIApi::ChatCompletions);
@@ -1274,6 +1476,29 @@ mod tests {
         assert!(transformed.provider_stream_response.is_some());
     }
 
+    #[test]
+    fn test_sse_event_transformation_takes_type_from_event_name() {
+        use crate::apis::anthropic::AnthropicApi;
+
+        // The payload leaves out `type`; the event: line names the variant
+        let raw = "event: content_block_stop\ndata: {\"index\":0}\n\n";
+        let sse_event = SseStreamIter::try_from(raw.as_bytes())
+            .unwrap()
+            .next()
+            .unwrap();
+
+        let client_api = SupportedAPIsFromClient::AnthropicMessagesAPI(AnthropicApi::Messages);
+        let upstream_api = SupportedUpstreamAPIs::AnthropicMessagesAPI(AnthropicApi::Messages);
+        let transformed = SseEvent::try_from((sse_event, &client_api, &upstream_api)).unwrap();
+
+        assert!(matches!(
+            transformed.provider_stream_response,
+            Some(ProviderStreamResponseType::MessagesStreamEvent(
+                MessagesStreamEvent::ContentBlockStop { index: 0 }
+            ))
+        ));
+    }
+
     #[test]
     fn test_sse_event_transformation_no_change_for_matching_apis() {
         use crate::apis::openai::OpenAIApi;
@@ -1298,6 +1523,8 @@ mod tests {
             raw_line: format!("data: {}", original_data),
             sse_transformed_lines: format!("data: {}\n\n", original_data),
             provider_stream_response: None,
+            id: None,
+            retry: None,
         };
 
         let client_api = SupportedAPIsFromClient::OpenAIChatCompletions(OpenAIApi::ChatCompletions);
@@ -1342,6 +1569,8 @@ mod tests {
             raw_line: format!("data: {}", openai_stream_chunk),
             sse_transformed_lines: format!("data: {}", openai_stream_chunk),
             provider_stream_response: None,
+            id: None,
+            retry: None,
         };
 
         let client_api = SupportedAPIsFromClient::AnthropicMessagesAPI(AnthropicApi::Messages);
@@ -1370,4 +1599,105 @@ mod tests {
         let content = provider_response.unwrap().content_delta();
         assert_eq!(content, Some("Test"), "Should preserve content delta");
     }
+
+    #[test]
+    fn test_gemini_stream_events_to_openai_chunks() {
+        use crate::apis::gemini::GeminiApi;
+        use crate::apis::openai::{FinishReason, OpenAIApi};
+        use crate::apis::streaming_shapes::sse_chunk_processor::SseChunkProcessor;
+
+        let client_api = SupportedAPIsFromClient::OpenAIChatCompletions(OpenAIApi::ChatCompletions);
+        let upstream_api =
+            SupportedUpstreamAPIs::GeminiStreamGenerateContent(GeminiApi::StreamGenerateContent);
+
+        let stream = concat!(
+            "data: {\"candidates\": [{\"content\": {\"role\": \"model\", \"parts\": [{\"text\": \"Hel\"}]}, \"index\": 0}], ",
+            "\"usageMetadata\": {\"promptTokenCount\": 5, \"totalTokenCount\": 5}, \"modelVersion\": \"gemini-2.5-flash\"}\r\n\r\n",
+            "data: {\"candidates\": [{\"content\": {\"role\": \"model\", \"parts\": [{\"text\": \"lo\"}]}, \"finishReason\": \"STOP\", \"index\": 0}], ",
+            "\"usageMetadata\": {\"promptTokenCount\": 5, \"candidatesTokenCount\": 2, \"totalTokenCount\": 7}, \"modelVersion\": \"gemini-2.5-flash\"}\r\n\r\n"
+        );
+
+        let mut processor = SseChunkProcessor::new();
+        let events = processor
+            .process_chunk(stream.as_bytes(), &client_api, &upstream_api)
+            .unwrap();
+        let chunks: Vec<ChatCompletionsStreamResponse> = events
+            .into_iter()
+            .filter_map(|event| match event.provider_stream_response {
+                Some(ProviderStreamResponseType::ChatCompletionsStreamResponse(chunk)) => {
+                    Some(chunk)
+                }
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].choices[0].delta.content.as_deref(), Some("Hel"));
+        assert!(chunks[0].usage.is_none());
+        assert_eq!(chunks[1].choices[0].delta.content.as_deref(), Some("lo"));
+        assert_eq!(chunks[1].choices[0].finish_reason, Some(FinishReason::Stop));
+        assert_eq!(chunks[1].usage.as_ref().unwrap().total_tokens, 7);
+        assert_eq!(chunks[1].model, "gemini-2.5-flash");
+    }
+
+    #[test]
+    fn test_cohere_stream_events_to_openai_chunks() {
+        use crate::apis::cohere::CohereApi;
+        use crate::apis::openai::{FinishReason, OpenAIApi};
+        use crate::apis::streaming_shapes::sse_chunk_processor::SseChunkProcessor;
+
+        let client_api = SupportedAPIsFromClient::OpenAIChatCompletions(OpenAIApi::ChatCompletions);
+        let upstream_api = SupportedUpstreamAPIs::CohereChat(CohereApi::Chat);
+
+        let stream = concat!(
+            "event: message-start\n",
+            "data: {\"id\": \"msg-1\", \"type\": \"message-start\", \"delta\": {\"message\": {\"role\": \"assistant\"}}}\n\n",
+            "event: tool-call-start\n",
+            "data: {\"type\": \"tool-call-start\", \"index\": 0, \"delta\": {\"message\": {\"tool_calls\": ",
+            "{\"id\": \"get_weather_k3x\", \"type\": \"function\", \"function\": {\"name\": \"get_weather\", \"arguments\": \"\"}}}}}\n\n",
+            "event: tool-call-delta\n",
+            "data: {\"type\": \"tool-call-delta\", \"index\": 0, \"delta\": {\"message\": {\"tool_calls\": ",
+            "{\"function\": {\"arguments\": \"{\\\"city\\\": \\\"Paris\\\"}\"}}}}}\n\n",
+            "event: message-end\n",
+            "data: {\"type\": \"message-end\", \"delta\": {\"finish_reason\": \"TOOL_CALL\", ",
+            "\"usage\": {\"tokens\": {\"input_tokens\": 20, \"output_tokens\": 8}}}}\n\n"
+        );
+
+        let mut processor = SseChunkProcessor::new();
+        let events = processor
+            .process_chunk(stream.as_bytes(), &client_api, &upstream_api)
+            .unwrap();
+        assert!(events
+            .iter()
+            .filter(|event| event.is_event_only())
+            .all(|event| event.sse_transformed_lines.trim().is_empty()));
+        let chunks: Vec<ChatCompletionsStreamResponse> = events
+            .into_iter()
+            .filter_map(|event| match event.provider_stream_response {
+                Some(ProviderStreamResponseType::ChatCompletionsStreamResponse(chunk)) => {
+                    Some(chunk)
+                }
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(chunks.len(), 4);
+        assert_eq!(chunks[0].id, "msg-1");
+        let start = &chunks[1].choices[0].delta.tool_calls.as_ref().unwrap()[0];
+        assert_eq!(start.id.as_deref(), Some("get_weather_k3x"));
+        assert_eq!(
+            start.function.as_ref().unwrap().name.as_deref(),
+            Some("get_weather")
+        );
+        let delta = &chunks[2].choices[0].delta.tool_calls.as_ref().unwrap()[0];
+        assert_eq!(
+            delta.function.as_ref().unwrap().arguments.as_deref(),
+            Some(r#"{"city": "Paris"}"#)
+        );
+        assert_eq!(
+            chunks[3].choices[0].finish_reason,
+            Some(FinishReason::ToolCalls)
+        );
+        assert_eq!(chunks[3].usage.as_ref().unwrap().total_tokens, 28);
+    }
 }
@@ -0,0 +1,100 @@
+//! Translations between the OpenAI audio transcription API and Deepgram's
+//! pre-recorded `/v1/listen` API.
+
+use crate::apis::audio::{TranscriptionResponse, TranscriptionSegment, TranscriptionWord};
+use crate::apis::deepgram::DeepgramListenResponse;
+
+impl From<DeepgramListenResponse> for TranscriptionResponse {
+    fn from(response: DeepgramListenResponse) -> Self {
+        // Only the first channel and its best alternative are transcribed, as
+        // OpenAI returns a single transcript
+        let channel = response.results.channels.into_iter().next();
+        let language = channel
+            .as_ref()
+            .and_then(|channel| channel.detected_language.clone());
+        let alternative = channel.and_then(|channel| channel.alternatives.into_iter().next());
+
+        let (text, words) = match alternative {
+            Some(alternative) => {
+                let words = alternative
+                    .words
+                    .into_iter()
+                    .map(|word| TranscriptionWord {
+                        word: word.word,
+                        start: word.start,
+                        end: word.end,
+                    })
+                    .collect();
+                (alternative.transcript, Some(words))
+            }
+            None => (String::new(), None),
+        };
+        let segments = response.results.utterances.map(|utterances| {
+            utterances
+                .into_iter()
+                .filter(|utterance| utterance.channel == 0)
+                .enumerate()
+                .map(|(id, utterance)| TranscriptionSegment {
+                    id: id as u32,
+                    start: utterance.start,
+                    end: utterance.end,
+                    text: utterance.transcript,
+                })
+                .collect()
+        });
+
+        TranscriptionResponse {
+            task: Some("transcribe".to_string()),
+            language,
+            duration: response.metadata.and_then(|metadata| metadata.duration),
+            text,
+            words,
+            segments,
+            usage: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_deepgram_response_to_transcription() {
+        let response: DeepgramListenResponse = serde_json::from_value(json!({
+            "metadata": {"request_id": "req-1", "duration": 2.5, "channels": 1},
+            "results": {
+                "channels": [{
+                    "detected_language": "en",
+                    "alternatives": [{
+                        "transcript": "Yeah, as much as it's worth.",
+                        "confidence": 0.99,
+                        "words": [
+                            {"word": "yeah", "start": 0.08, "end": 0.32, "confidence": 0.99, "punctuated_word": "Yeah,"},
+                            {"word": "as", "start": 0.32, "end": 0.48, "confidence": 0.98}
+                        ]
+                    }]
+                }],
+                "utterances": [
+                    {"start": 0.08, "end": 2.4, "confidence": 0.98, "channel": 0, "transcript": "Yeah, as much as it's worth.", "id": "u1"}
+                ]
+            }
+        }))
+        .unwrap();
+        let transcription = TranscriptionResponse::from(response);
+        assert_eq!(transcription.text, "Yeah, as much as it's worth.");
+        assert_eq!(transcription.language.as_deref(), Some("en"));
+        assert_eq!(transcription.duration, Some(2.5));
+        assert_eq!(transcription.words.as_ref().unwrap().len(), 2);
+        assert_eq!(
+            transcription.segments,
+            Some(vec![TranscriptionSegment {
+                id: 0,
+                start: 0.08,
+                end: 2.4,
+                text: "Yeah, as much as it's worth.".to_string()
+            }])
+        );
+    }
+}
@@ -0,0 +1,301 @@
+//! Translations between the OpenAI Batch API and Anthropic's Message Batches
+//! API. Each line item goes through the same chat completions transforms as a
+//! single request.
+
+use crate::apis::anthropic::{
+    MessageBatch, MessageBatchProcessingStatus, MessageBatchRequest, MessageBatchResult,
+    MessageBatchResultKind, MessagesRequest,
+};
+use crate::apis::batches::{
+    Batch, BatchLineError, BatchLineResponse, BatchRequestCounts, BatchRequestLine,
+    BatchResponseLine, BatchStatus,
+};
+use crate::apis::openai::{ChatCompletionsRequest, ChatCompletionsResponse};
+use crate::clients::TransformError;
+use crate::CHAT_COMPLETIONS_PATH;
+
+impl TryFrom<BatchRequestLine> for MessageBatchRequest {
+    type Error = TransformError;
+
+    fn try_from(line: BatchRequestLine) -> Result<Self, Self::Error> {
+        if line.url != CHAT_COMPLETIONS_PATH {
+            return Err(TransformError::UnsupportedConversion(format!(
+                "batch request '{}' targets {}; only {} can be sent to Anthropic",
+                line.custom_id, line.url, CHAT_COMPLETIONS_PATH
+            )));
+        }
+        let request: ChatCompletionsRequest = serde_json::from_value(line.body)?;
+        Ok(MessageBatchRequest {
+            custom_id: line.custom_id,
+            params: MessagesRequest::try_from(request)?,
+        })
+    }
+}
+
+impl From<MessageBatch> for Batch {
+    fn from(batch: MessageBatch) -> Self {
+        let counts = &batch.request_counts;
+        let status = match batch.processing_status {
+            MessageBatchProcessingStatus::InProgress => BatchStatus::InProgress,
+            MessageBatchProcessingStatus::Canceling => BatchStatus::Cancelling,
+            MessageBatchProcessingStatus::Ended if batch.cancel_initiated_at.is_some() => {
+                BatchStatus::Cancelled
+            }
+            MessageBatchProcessingStatus::Ended
+                if counts.expired > 0 && counts.succeeded + counts.errored == 0 =>
+            {
+                BatchStatus::Expired
+            }
+            MessageBatchProcessingStatus::Ended => BatchStatus::Completed,
+        };
+        let created_at = unix_seconds(&batch.created_at).unwrap_or_default();
+        let ended_at = batch.ended_at.as_deref().and_then(unix_seconds);
+        let ended_as = |wanted: BatchStatus| ended_at.filter(|_| status == wanted);
+
+        Batch {
+            // Results are fetched by batch, so the batch id names the output file
+            output_file_id: batch.results_url.as_ref().map(|_| batch.id.clone()),
+            id: batch.id,
+            object: "batch".to_string(),
+            endpoint: CHAT_COMPLETIONS_PATH.to_string(),
+            errors: None,
+            input_file_id: None,
+            completion_window: "24h".to_string(),
+            status,
+            error_file_id: None,
+            created_at,
+            in_progress_at: Some(created_at),
+            expires_at: batch.expires_at.as_deref().and_then(unix_seconds),
+            finalizing_at: None,
+            completed_at: ended_as(BatchStatus::Completed),
+            failed_at: None,
+            expired_at: ended_as(BatchStatus::Expired),
+            cancelling_at: batch.cancel_initiated_at.as_deref().and_then(unix_seconds),
+            cancelled_at: ended_as(BatchStatus::Cancelled),
+            request_counts: Some(BatchRequestCounts {
+                total: counts.processing
+                    + counts.succeeded
+                    + counts.errored
+                    + counts.canceled
+                    + counts.expired,
+                completed: counts.succeeded,
+                failed: counts.errored + counts.canceled + counts.expired,
+            }),
+            metadata: None,
+        }
+    }
+}
+
+impl TryFrom<MessageBatchResult> for BatchResponseLine {
+    type Error = TransformError;
+
+    fn try_from(result: MessageBatchResult) -> Result<Self, Self::Error> {
+        let (response, error) = match result.result {
+            MessageBatchResultKind::Succeeded { message } => {
+                let request_id = message.id.clone();
+                let completion = ChatCompletionsResponse::try_from(message)?;
+                let response = BatchLineResponse {
+                    status_code: 200,
+                    request_id: Some(request_id),
+                    body: serde_json::to_value(completion)?,
+                };
+                (Some(response), None)
+            }
+            MessageBatchResultKind::Errored { error } => {
+                let error = &error["error"];
+                let error = BatchLineError {
+                    code: error["type"].as_str().unwrap_or("api_error").to_string(),
+                    message: error["message"].as_str().unwrap_or_default().to_string(),
+                };
+                (None, Some(error))
+            }
+            MessageBatchResultKind::Canceled => (
+                None,
+                Some(BatchLineError {
+                    code: "batch_cancelled".to_string(),
+                    message: "The batch was cancelled before this request ran".to_string(),
+                }),
+            ),
+            MessageBatchResultKind::Expired => (
+                None,
+                Some(BatchLineError {
+                    code: "batch_expired".to_string(),
+                    message: "The batch expired before this request ran".to_string(),
+                }),
+            ),
+        };
+        Ok(BatchResponseLine {
+            id: format!("batch_req_{}", result.custom_id),
+            custom_id: result.custom_id,
+            response,
+            error,
+        })
+    }
+}
+
+/// Unix seconds of an RFC 3339 timestamp such as `2024-08-20T18:37:24.100435Z`
+fn unix_seconds(timestamp: &str) -> Option<i64> {
+    let (date, time) = timestamp.split_once(['T', 't', ' '])?;
+    let mut date_parts = date.splitn(3, '-').map(|part| part.parse::<i64>().ok());
+    let (year, month, day) = (
+        date_parts.next()??,
+        date_parts.next()??,
+        date_parts.next()??,
+    );
+
+    let (clock, offset_secs) = if let Some(clock) = time.strip_suffix(['Z', 'z']) {
+        (clock, 0)
+    } else {
+        let sign_at = time.rfind(['+', '-'])?;
+        let (clock, offset) = time.split_at(sign_at);
+        let (hours, minutes) = offset[1..].split_once(':')?;
+        let offset = hours.parse::<i64>().ok()? * 3_600 + minutes.parse::<i64>().ok()? * 60;
+        let offset = if time[sign_at..].starts_with('-') {
+            -offset
+        } else {
+            offset
+        };
+        (clock, offset)
+    };
+    let clock = clock.split('.').next()?;
+    let mut clock_parts = clock.splitn(3, ':').map(|part| part.parse::<i64>().ok());
+    let (hour, minute, second) = (
+        clock_parts.next()??,
+        clock_parts.next()??,
+        clock_parts.next()??,
+    );
+
+    // Days since 1970-01-01 from a civil date (Howard Hinnant's algorithm)
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146_097 + day_of_era - 719_468;
+
+    Some(days * 86_400 + hour * 3_600 + minute * 60 + second - offset_secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_batch_line_to_message_batch_request() {
+        let line: BatchRequestLine = serde_json::from_value(json!({
+            "custom_id": "request-1",
+            "method": "POST",
+            "url": "/v1/chat/completions",
+            "body": {
+                "model": "claude-sonnet-4",
+                "max_tokens": 256,
+                "messages": [
+                    {"role": "system", "content": "Be brief."},
+                    {"role": "user", "content": "Hello"}
+                ]
+            }
+        }))
+        .unwrap();
+        let request = MessageBatchRequest::try_from(line.clone()).unwrap();
+        assert_eq!(request.custom_id, "request-1");
+        assert_eq!(request.params.model, "claude-sonnet-4");
+        assert_eq!(request.params.max_tokens, 256);
+        assert_eq!(request.params.messages.len(), 1);
+        assert!(request.params.system.is_some());
+
+        let embeddings = BatchRequestLine {
+            url: "/v1/embeddings".to_string(),
+            ..line
+        };
+        assert!(MessageBatchRequest::try_from(embeddings).is_err());
+    }
+
+    #[test]
+    fn test_message_batch_to_batch() {
+        let batch: MessageBatch = serde_json::from_value(json!({
+            "id": "msgbatch_013Zva2CMHLNnXjNJJKqJ2EF",
+            "type": "message_batch",
+            "processing_status": "ended",
+            "request_counts": {"processing": 0, "succeeded": 2, "errored": 1, "canceled": 0, "expired": 0},
+            "ended_at": "2024-08-20T18:37:24.100435Z",
+            "created_at": "2024-08-20T18:37:24.100435Z",
+            "expires_at": "2024-08-21T18:37:24.100435Z",
+            "cancel_initiated_at": null,
+            "results_url": "https://api.anthropic.com/v1/messages/batches/msgbatch_013Zva2CMHLNnXjNJJKqJ2EF/results"
+        }))
+        .unwrap();
+        let batch = Batch::from(batch);
+        assert_eq!(batch.status, BatchStatus::Completed);
+        assert_eq!(batch.created_at, 1_724_179_044);
+        assert_eq!(batch.completed_at, Some(1_724_179_044));
+        assert_eq!(batch.expires_at, Some(1_724_265_444));
+        assert_eq!(
+            batch.output_file_id.as_deref(),
+            Some("msgbatch_013Zva2CMHLNnXjNJJKqJ2EF")
+        );
+        assert_eq!(
+            batch.request_counts,
+            Some(BatchRequestCounts {
+                total: 3,
+                completed: 2,
+                failed: 1
+            })
+        );
+    }
+
+    #[test]
+    fn test_message_batch_results_to_response_lines() {
+        let succeeded: MessageBatchResult = serde_json::from_value(json!({
+            "custom_id": "request-1",
+            "result": {
+                "type": "succeeded",
+                "message": {
+                    "id": "msg_01",
+                    "type": "message",
+                    "role": "assistant",
+                    "model": "claude-sonnet-4",
+                    "content": [{"type": "text", "text": "Hi!"}],
+                    "stop_reason": "end_turn",
+                    "stop_sequence": null,
+                    "usage": {"input_tokens": 10, "output_tokens": 3}
+                }
+            }
+        }))
+        .unwrap();
+        let line = BatchResponseLine::try_from(succeeded).unwrap();
+        let response = line.response.unwrap();
+        assert_eq!(response.status_code, 200);
+        assert_eq!(response.body["choices"][0]["message"]["content"], "Hi!");
+        assert_eq!(response.body["usage"]["total_tokens"], 13);
+
+        let errored: MessageBatchResult = serde_json::from_value(json!({
+            "custom_id": "request-2",
+            "result": {
+                "type": "errored",
+                "error": {"type": "error", "error": {"type": "invalid_request_error", "message": "max_tokens: Field required"}}
+            }
+        }))
+        .unwrap();
+        let line = BatchResponseLine::try_from(errored).unwrap();
+        assert!(line.response.is_none());
+        assert_eq!(
+            line.error,
+            Some(BatchLineError {
+                code: "invalid_request_error".to_string(),
+                message: "max_tokens: Field required".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_unix_seconds() {
+        assert_eq!(unix_seconds("1970-01-01T00:00:00Z"), Some(0));
+        assert_eq!(unix_seconds("2024-02-29T23:59:59Z"), Some(1_709_251_199));
+        assert_eq!(
+            unix_seconds("2024-03-01T01:59:59+02:00"),
+            Some(1_709_251_199)
+        );
+        assert_eq!(unix_seconds("not a timestamp"), None);
+    }
+}
@@ -0,0 +1,242 @@
+//! The legacy `/v1/completions` API served through chat completions, for
+//! providers that no longer offer it. The prompt becomes a single user
+//! message, and choices come back as plain text.
+
+use crate::apis::openai::{
+    ChatCompletionsRequest, ChatCompletionsResponse, ChatCompletionsStreamResponse,
+    CompletionChoice, CompletionPrompt, CompletionsRequest, CompletionsResponse, Message,
+    MessageContent, Role,
+};
+use crate::clients::TransformError;
+
+/// `max_tokens` of the legacy API when the request sets none; chat
+/// completions would otherwise generate until the model stops
+pub const LEGACY_COMPLETIONS_DEFAULT_MAX_TOKENS: u32 = 16;
+
+const TEXT_COMPLETION_OBJECT: &str = "text_completion";
+
+impl TryFrom<CompletionsRequest> for ChatCompletionsRequest {
+    type Error = TransformError;
+
+    fn try_from(request: CompletionsRequest) -> Result<Self, Self::Error> {
+        let prompt = match request.prompt {
+            CompletionPrompt::Text(text) => text,
+            CompletionPrompt::Texts(mut texts) if texts.len() == 1 => texts.remove(0),
+            CompletionPrompt::Texts(texts) => {
+                return Err(TransformError::UnsupportedConversion(format!(
+                    "chat completions take one prompt per request, got {}",
+                    texts.len()
+                )))
+            }
+            CompletionPrompt::Tokens(_) | CompletionPrompt::TokenArrays(_) => {
+                return Err(TransformError::UnsupportedContent(
+                    "token ids as a prompt; send text instead".to_string(),
+                ))
+            }
+        };
+        if request.echo == Some(true) {
+            return Err(TransformError::UnsupportedConversion(
+                "echo needs a provider that serves the legacy completions API".to_string(),
+            ));
+        }
+        if request.suffix.is_some() {
+            return Err(TransformError::UnsupportedConversion(
+                "suffix needs a provider that serves the legacy completions API".to_string(),
+            ));
+        }
+        if request.best_of.is_some_and(|best_of| best_of > 1) {
+            return Err(TransformError::UnsupportedConversion(
+                "best_of needs a provider that serves the legacy completions API".to_string(),
+            ));
+        }
+
+        // Legacy logprobs are per text offset and have no chat equivalent, so
+        // they are not requested
+        Ok(ChatCompletionsRequest {
+            model: request.model,
+            messages: vec![Message {
+                role: Role::User,
+                content: Some(MessageContent::Text(prompt)),
+                name: None,
+                tool_calls: None,
+                tool_call_id: None,
+                thinking_blocks: None,
+            }],
+            frequency_penalty: request.frequency_penalty,
+            logit_bias: request.logit_bias,
+            max_tokens: Some(
+                request
+                    .max_tokens
+                    .unwrap_or(LEGACY_COMPLETIONS_DEFAULT_MAX_TOKENS),
+            ),
+            n: request.n,
+            presence_penalty: request.presence_penalty,
+            seed: request.seed,
+            stop: request.stop.map(Into::into),
+            stream: request.stream,
+            stream_options: request.stream_options,
+            temperature: request.temperature,
+            top_p: request.top_p,
+            user: request.user,
+            ..Default::default()
+        })
+    }
+}
+
+impl From<ChatCompletionsResponse> for CompletionsResponse {
+    fn from(response: ChatCompletionsResponse) -> Self {
+        CompletionsResponse {
+            id: response.id,
+            object: TEXT_COMPLETION_OBJECT.to_string(),
+            created: response.created,
+            model: response.model,
+            choices: response
+                .choices
+                .into_iter()
+                .map(|choice| CompletionChoice {
+                    text: choice.message.content.unwrap_or_default(),
+                    index: choice.index,
+                    logprobs: None,
+                    finish_reason: choice.finish_reason,
+                })
+                .collect(),
+            usage: Some(response.usage),
+            system_fingerprint: response.system_fingerprint,
+        }
+    }
+}
+
+impl From<ChatCompletionsStreamResponse> for CompletionsResponse {
+    fn from(chunk: ChatCompletionsStreamResponse) -> Self {
+        CompletionsResponse {
+            id: chunk.id,
+            object: TEXT_COMPLETION_OBJECT.to_string(),
+            created: chunk.created,
+            model: chunk.model,
+            choices: chunk
+                .choices
+                .into_iter()
+                .map(|choice| CompletionChoice {
+                    text: choice.delta.content.unwrap_or_default(),
+                    index: choice.index,
+                    logprobs: None,
+                    finish_reason: choice.finish_reason,
+                })
+                .collect(),
+            usage: chunk.usage,
+            system_fingerprint: chunk.system_fingerprint,
+        }
+    }
+}
+
+/// Rewrite the `data:` lines of a chat completions SSE stream as legacy
+/// completions chunks. `[DONE]` and other lines pass through unchanged.
+pub fn chat_stream_to_completions(sse: &[u8]) -> Vec<u8> {
+    let text = String::from_utf8_lossy(sse);
+    let mut converted = String::with_capacity(text.len());
+    for line in text.split_inclusive('\n') {
+        let chunk = line
+            .strip_prefix("data: ")
+            .and_then(|data| serde_json::from_str::<ChatCompletionsStreamResponse>(data).ok())
+            .and_then(|chunk| serde_json::to_string(&CompletionsResponse::from(chunk)).ok());
+        match chunk {
+            Some(chunk) => {
+                converted.push_str("data: ");
+                converted.push_str(&chunk);
+                if line.ends_with('\n') {
+                    converted.push('\n');
+                }
+            }
+            None => converted.push_str(line),
+        }
+    }
+    converted.into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn request(body: serde_json::Value) -> CompletionsRequest {
+        serde_json::from_value(body).unwrap()
+    }
+
+    #[test]
+    fn test_completions_request_to_chat() {
+        let chat = ChatCompletionsRequest::try_from(request(json!({
+            "model": "claude-sonnet-4",
+            "prompt": ["Say this is a test"],
+            "stop": "\n",
+            "temperature": 0.5
+        })))
+        .unwrap();
+        assert_eq!(
+            serde_json::to_value(&chat).unwrap(),
+            json!({
+                "model": "claude-sonnet-4",
+                "messages": [{"role": "user", "content": "Say this is a test"}],
+                "max_tokens": 16,
+                "stop": ["\n"],
+                "temperature": 0.5
+            })
+        );
+
+        for unsupported in [
+            json!({"model": "m", "prompt": ["a", "b"]}),
+            json!({"model": "m", "prompt": [1, 2, 3]}),
+            json!({"model": "m", "prompt": "a", "echo": true}),
+            json!({"model": "m", "prompt": "a", "suffix": "b"}),
+        ] {
+            assert!(ChatCompletionsRequest::try_from(request(unsupported)).is_err());
+        }
+    }
+
+    #[test]
+    fn test_chat_response_to_completions() {
+        let chat: ChatCompletionsResponse = serde_json::from_value(json!({
+            "id": "chatcmpl-1",
+            "object": "chat.completion",
+            "created": 1700000000,
+            "model": "claude-sonnet-4",
+            "choices": [{
+                "index": 0,
+                "message": {"role": "assistant", "content": "This is a test."},
+                "finish_reason": "stop"
+            }],
+            "usage": {"prompt_tokens": 5, "completion_tokens": 5, "total_tokens": 10}
+        }))
+        .unwrap();
+        assert_eq!(
+            serde_json::to_value(CompletionsResponse::from(chat)).unwrap(),
+            json!({
+                "id": "chatcmpl-1",
+                "object": "text_completion",
+                "created": 1700000000,
+                "model": "claude-sonnet-4",
+                "choices": [{"text": "This is a test.", "index": 0, "logprobs": null, "finish_reason": "stop"}],
+                "usage": {"prompt_tokens": 5, "completion_tokens": 5, "total_tokens": 10}
+            })
+        );
+    }
+
+    #[test]
+    fn test_chat_stream_to_completions() {
+        let sse = concat!(
+            "data: {\"id\":\"chatcmpl-1\",\"object\":\"chat.completion.chunk\",\"created\":1700000000,\"model\":\"m\",\"choices\":[{\"index\":0,\"delta\":{\"content\":\"Hi\"}}]}\n",
+            "\n",
+            "data: [DONE]\n",
+            "\n"
+        );
+        let converted = String::from_utf8(chat_stream_to_completions(sse.as_bytes())).unwrap();
+        assert_eq!(
+            converted,
+            concat!(
+                "data: {\"id\":\"chatcmpl-1\",\"object\":\"text_completion\",\"created\":1700000000,\"model\":\"m\",\"choices\":[{\"text\":\"Hi\",\"index\":0,\"logprobs\":null,\"finish_reason\":null}]}\n",
+                "\n",
+                "data: [DONE]\n",
+                "\n"
+            )
+        );
+    }
+}
@@ -0,0 +1,280 @@
+//! Translations between the OpenAI embeddings API and the native embeddings
+//! APIs of Cohere, Gemini and Amazon Bedrock (Titan).
+
+use crate::apis::amazon_bedrock::{TitanEmbedRequest, TitanEmbedResponse};
+use crate::apis::cohere::{CohereEmbedRequest, CohereEmbedResponse};
+use crate::apis::embeddings::{EmbeddingsRequest, EmbeddingsResponse};
+use crate::apis::gemini::{
+    BatchEmbedContentsRequest, BatchEmbedContentsResponse, Content, EmbedContentRequest,
+    EmbedContentResponse, Part,
+};
+use crate::clients::TransformError;
+
+/// Cohere's `input_type` when the client gives none; OpenAI embeddings are
+/// typically stored and searched, so inputs are treated as documents
+pub const COHERE_DEFAULT_INPUT_TYPE: &str = "search_document";
+
+fn texts_of(request: &EmbeddingsRequest) -> Result<Vec<String>, TransformError> {
+    request.input.texts().ok_or_else(|| {
+        TransformError::UnsupportedContent(
+            "token ids as embeddings input; send text instead".to_string(),
+        )
+    })
+}
+
+/// Gemini task type for a Cohere-style `input_type`; anything else is
+/// assumed to be a Gemini task type already
+fn gemini_task_type(input_type: &str) -> String {
+    match input_type {
+        "search_document" => "RETRIEVAL_DOCUMENT".to_string(),
+        "search_query" => "RETRIEVAL_QUERY".to_string(),
+        other => other.to_ascii_uppercase(),
+    }
+}
+
+impl TryFrom<EmbeddingsRequest> for CohereEmbedRequest {
+    type Error = TransformError;
+
+    fn try_from(request: EmbeddingsRequest) -> Result<Self, Self::Error> {
+        Ok(CohereEmbedRequest {
+            texts: texts_of(&request)?,
+            model: request.model,
+            input_type: request
+                .input_type
+                .unwrap_or_else(|| COHERE_DEFAULT_INPUT_TYPE.to_string()),
+            embedding_types: vec!["float".to_string()],
+            output_dimension: request.dimensions,
+            truncate: None,
+        })
+    }
+}
+
+impl TryFrom<EmbeddingsRequest> for EmbedContentRequest {
+    type Error = TransformError;
+
+    fn try_from(request: EmbeddingsRequest) -> Result<Self, Self::Error> {
+        let mut texts = texts_of(&request)?;
+        if texts.len() != 1 {
+            return Err(TransformError::UnsupportedConversion(format!(
+                "embedContent takes one input, got {}",
+                texts.len()
+            )));
+        }
+        Ok(EmbedContentRequest {
+            model: None,
+            content: Content {
+                role: None,
+                parts: vec![Part::text(texts.remove(0))],
+            },
+            task_type: request.input_type.as_deref().map(gemini_task_type),
+            output_dimensionality: request.dimensions,
+        })
+    }
+}
+
+impl TryFrom<EmbeddingsRequest> for BatchEmbedContentsRequest {
+    type Error = TransformError;
+
+    fn try_from(request: EmbeddingsRequest) -> Result<Self, Self::Error> {
+        let model = format!("models/{}", request.model);
+        let task_type = request.input_type.as_deref().map(gemini_task_type);
+        Ok(BatchEmbedContentsRequest {
+            requests: texts_of(&request)?
+                .into_iter()
+                .map(|text| EmbedContentRequest {
+                    model: Some(model.clone()),
+                    content: Content {
+                        role: None,
+                        parts: vec![Part::text(text)],
+                    },
+                    task_type: task_type.clone(),
+                    output_dimensionality: request.dimensions,
+                })
+                .collect(),
+        })
+    }
+}
+
+impl TryFrom<EmbeddingsRequest> for TitanEmbedRequest {
+    type Error = TransformError;
+
+    fn try_from(request: EmbeddingsRequest) -> Result<Self, Self::Error> {
+        if !request.model.contains("amazon.titan-embed") {
+            return Err(TransformError::UnsupportedConversion(format!(
+                "Bedrock embeddings support Amazon Titan embedding models, got '{}'",
+                request.model
+            )));
+        }
+        let mut texts = texts_of(&request)?;
+        if texts.len() != 1 {
+            return Err(TransformError::UnsupportedConversion(format!(
+                "Titan embeds one input per request, got {}",
+                texts.len()
+            )));
+        }
+        Ok(TitanEmbedRequest {
+            input_text: texts.remove(0),
+            dimensions: request.dimensions,
+            normalize: None,
+        })
+    }
+}
+
+// The model is filled in by the caller: native responses do not echo it
+
+impl From<CohereEmbedResponse> for EmbeddingsResponse {
+    fn from(response: CohereEmbedResponse) -> Self {
+        let prompt_tokens = response
+            .meta
+            .and_then(|meta| meta.tokens.or(meta.billed_units))
+            .and_then(|counts| counts.input_tokens)
+            .unwrap_or_default();
+        EmbeddingsResponse::from_vectors("", response.embeddings.float, prompt_tokens as u32)
+    }
+}
+
+impl From<EmbedContentResponse> for EmbeddingsResponse {
+    fn from(response: EmbedContentResponse) -> Self {
+        // Gemini does not report token counts for embeddings
+        EmbeddingsResponse::from_vectors("", vec![response.embedding.values], 0)
+    }
+}
+
+impl From<BatchEmbedContentsResponse> for EmbeddingsResponse {
+    fn from(response: BatchEmbedContentsResponse) -> Self {
+        let vectors = response
+            .embeddings
+            .into_iter()
+            .map(|embedding| embedding.values)
+            .collect();
+        EmbeddingsResponse::from_vectors("", vectors, 0)
+    }
+}
+
+impl From<TitanEmbedResponse> for EmbeddingsResponse {
+    fn from(response: TitanEmbedResponse) -> Self {
+        EmbeddingsResponse::from_vectors(
+            "",
+            vec![response.embedding],
+            response.input_text_token_count.unwrap_or_default(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::apis::embeddings::EmbeddingVector;
+    use serde_json::json;
+
+    fn request(body: serde_json::Value) -> EmbeddingsRequest {
+        serde_json::from_value(body).unwrap()
+    }
+
+    #[test]
+    fn test_cohere_embed_round_trip() {
+        let cohere = CohereEmbedRequest::try_from(request(json!({
+            "model": "embed-v4.0",
+            "input": ["first", "second"],
+            "dimensions": 512
+        })))
+        .unwrap();
+        assert_eq!(
+            serde_json::to_value(&cohere).unwrap(),
+            json!({
+                "model": "embed-v4.0",
+                "texts": ["first", "second"],
+                "input_type": "search_document",
+                "embedding_types": ["float"],
+                "output_dimension": 512
+            })
+        );
+
+        let response: CohereEmbedResponse = serde_json::from_value(json!({
+            "id": "emb_1",
+            "embeddings": {"float": [[0.1, 0.2], [0.3, 0.4]]},
+            "texts": ["first", "second"],
+            "meta": {"billed_units": {"input_tokens": 4}},
+            "response_type": "embeddings_by_type"
+        }))
+        .unwrap();
+        let response = EmbeddingsResponse::from(response);
+        assert_eq!(response.data.len(), 2);
+        assert_eq!(response.data[1].index, 1);
+        assert_eq!(
+            response.data[1].embedding,
+            EmbeddingVector::Float(vec![0.3, 0.4])
+        );
+        assert_eq!(response.usage.prompt_tokens, 4);
+    }
+
+    #[test]
+    fn test_gemini_embed_requests() {
+        let single = EmbedContentRequest::try_from(request(json!({
+            "model": "gemini-embedding-001",
+            "input": "hello",
+            "input_type": "search_query",
+            "dimensions": 768
+        })))
+        .unwrap();
+        assert_eq!(
+            serde_json::to_value(&single).unwrap(),
+            json!({
+                "content": {"parts": [{"text": "hello"}]},
+                "taskType": "RETRIEVAL_QUERY",
+                "outputDimensionality": 768
+            })
+        );
+
+        let batch = BatchEmbedContentsRequest::try_from(request(json!({
+            "model": "gemini-embedding-001",
+            "input": ["a", "b"]
+        })))
+        .unwrap();
+        assert_eq!(
+            serde_json::to_value(&batch).unwrap(),
+            json!({
+                "requests": [
+                    {"model": "models/gemini-embedding-001", "content": {"parts": [{"text": "a"}]}},
+                    {"model": "models/gemini-embedding-001", "content": {"parts": [{"text": "b"}]}}
+                ]
+            })
+        );
+
+        let response: BatchEmbedContentsResponse = serde_json::from_value(json!({
+            "embeddings": [{"values": [0.5]}, {"values": [0.25]}]
+        }))
+        .unwrap();
+        assert_eq!(EmbeddingsResponse::from(response).data.len(), 2);
+    }
+
+    #[test]
+    fn test_titan_embed_takes_one_text_input() {
+        let titan = TitanEmbedRequest::try_from(request(json!({
+            "model": "amazon.titan-embed-text-v2:0",
+            "input": "hello",
+            "dimensions": 256
+        })))
+        .unwrap();
+        assert_eq!(
+            serde_json::to_value(&titan).unwrap(),
+            json!({"inputText": "hello", "dimensions": 256})
+        );
+
+        assert!(TitanEmbedRequest::try_from(request(json!({
+            "model": "amazon.titan-embed-text-v2:0",
+            "input": ["a", "b"]
+        })))
+        .is_err());
+        assert!(TitanEmbedRequest::try_from(request(json!({
+            "model": "amazon.titan-embed-text-v2:0",
+            "input": [1, 2, 3]
+        })))
+        .is_err());
+        assert!(TitanEmbedRequest::try_from(request(json!({
+            "model": "cohere.embed-english-v3",
+            "input": "hello"
+        })))
+        .is_err());
+    }
+}
@@ -0,0 +1,218 @@
+//! Translations between the OpenAI image generation API and the native image
+//! APIs of Vertex AI / Gemini (Imagen) and Amazon Bedrock (Titan Image
+//! Generator, Nova Canvas).
+
+use crate::apis::amazon_bedrock::{
+    TitanImageGenerationConfig, TitanImageRequest, TitanImageResponse, TitanTextToImageParams,
+};
+use crate::apis::gemini::{
+    ImagenInstance, ImagenOutputOptions, ImagenParameters, ImagenPredictRequest,
+    ImagenPredictResponse,
+};
+use crate::apis::images::{ImageData, ImageGenerationRequest, ImagesResponse};
+use crate::clients::TransformError;
+use crate::transforms::lib::current_timestamp;
+
+/// Aspect ratios Imagen generates
+const IMAGEN_ASPECT_RATIOS: [(&str, f64); 5] = [
+    ("1:1", 1.0),
+    ("3:4", 0.75),
+    ("4:3", 4.0 / 3.0),
+    ("9:16", 9.0 / 16.0),
+    ("16:9", 16.0 / 9.0),
+];
+
+fn require_prompt(request: &ImageGenerationRequest) -> Result<(), TransformError> {
+    if request.prompt.trim().is_empty() {
+        return Err(TransformError::MissingField("prompt".to_string()));
+    }
+    Ok(())
+}
+
+impl TryFrom<ImageGenerationRequest> for ImagenPredictRequest {
+    type Error = TransformError;
+
+    fn try_from(request: ImageGenerationRequest) -> Result<Self, Self::Error> {
+        require_prompt(&request)?;
+        // Imagen takes an aspect ratio rather than a size; use the closest one
+        let aspect_ratio = request.dimensions().map(|(width, height)| {
+            let ratio = width as f64 / height.max(1) as f64;
+            IMAGEN_ASPECT_RATIOS
+                .iter()
+                .min_by(|a, b| (a.1 - ratio).abs().total_cmp(&(b.1 - ratio).abs()))
+                .map(|(name, _)| name.to_string())
+                .unwrap_or_default()
+        });
+        let output_options = match request.output_format.as_deref() {
+            Some("jpeg") | Some("jpg") => Some(ImagenOutputOptions {
+                mime_type: Some("image/jpeg".to_string()),
+                compression_quality: None,
+            }),
+            Some("png") => Some(ImagenOutputOptions {
+                mime_type: Some("image/png".to_string()),
+                compression_quality: None,
+            }),
+            Some(format) => {
+                return Err(TransformError::UnsupportedConversion(format!(
+                    "Imagen does not generate '{}' images",
+                    format
+                )))
+            }
+            None => None,
+        };
+
+        Ok(ImagenPredictRequest {
+            instances: vec![ImagenInstance {
+                prompt: request.prompt,
+            }],
+            parameters: ImagenParameters {
+                sample_count: request.n,
+                aspect_ratio,
+                output_options,
+                ..Default::default()
+            },
+        })
+    }
+}
+
+impl TryFrom<ImageGenerationRequest> for TitanImageRequest {
+    type Error = TransformError;
+
+    fn try_from(request: ImageGenerationRequest) -> Result<Self, Self::Error> {
+        require_prompt(&request)?;
+        let (width, height) = request.dimensions().unzip();
+        let quality = request.quality.as_deref().map(|quality| match quality {
+            "hd" | "high" => "premium".to_string(),
+            _ => "standard".to_string(),
+        });
+
+        Ok(TitanImageRequest {
+            task_type: "TEXT_IMAGE".to_string(),
+            text_to_image_params: TitanTextToImageParams {
+                text: request.prompt,
+                ..Default::default()
+            },
+            image_generation_config: Some(TitanImageGenerationConfig {
+                number_of_images: request.n,
+                width,
+                height,
+                quality,
+                ..Default::default()
+            }),
+        })
+    }
+}
+
+impl From<ImagenPredictResponse> for ImagesResponse {
+    fn from(response: ImagenPredictResponse) -> Self {
+        ImagesResponse {
+            created: current_timestamp(),
+            // Filtered images have no bytes and are left out, as OpenAI would
+            // not have returned them either
+            data: response
+                .predictions
+                .into_iter()
+                .filter_map(|prediction| prediction.bytes_base64_encoded)
+                .map(|b64| ImageData {
+                    b64_json: Some(b64),
+                    ..Default::default()
+                })
+                .collect(),
+            usage: None,
+        }
+    }
+}
+
+impl From<TitanImageResponse> for ImagesResponse {
+    fn from(response: TitanImageResponse) -> Self {
+        ImagesResponse {
+            created: current_timestamp(),
+            data: response
+                .images
+                .into_iter()
+                .map(|b64| ImageData {
+                    b64_json: Some(b64),
+                    ..Default::default()
+                })
+                .collect(),
+            usage: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn request(body: serde_json::Value) -> ImageGenerationRequest {
+        serde_json::from_value(body).unwrap()
+    }
+
+    #[test]
+    fn test_image_request_to_imagen() {
+        let imagen = ImagenPredictRequest::try_from(request(json!({
+            "model": "imagen-3.0-generate-002",
+            "prompt": "a lighthouse at dusk",
+            "n": 2,
+            "size": "1792x1024"
+        })))
+        .unwrap();
+        assert_eq!(
+            serde_json::to_value(&imagen).unwrap(),
+            json!({
+                "instances": [{"prompt": "a lighthouse at dusk"}],
+                "parameters": {"sampleCount": 2, "aspectRatio": "16:9"}
+            })
+        );
+
+        assert!(ImagenPredictRequest::try_from(request(json!({
+            "model": "imagen-3.0-generate-002",
+            "prompt": "a lighthouse",
+            "output_format": "webp"
+        })))
+        .is_err());
+    }
+
+    #[test]
+    fn test_image_request_to_titan() {
+        let titan = TitanImageRequest::try_from(request(json!({
+            "model": "amazon.titan-image-generator-v2:0",
+            "prompt": "a lighthouse at dusk",
+            "size": "512x512",
+            "quality": "hd"
+        })))
+        .unwrap();
+        assert_eq!(
+            serde_json::to_value(&titan).unwrap(),
+            json!({
+                "taskType": "TEXT_IMAGE",
+                "textToImageParams": {"text": "a lighthouse at dusk"},
+                "imageGenerationConfig": {"width": 512, "height": 512, "quality": "premium"}
+            })
+        );
+
+        assert!(
+            TitanImageRequest::try_from(request(json!({"model": "m", "prompt": " "}))).is_err()
+        );
+    }
+
+    #[test]
+    fn test_native_image_responses() {
+        let imagen: ImagenPredictResponse = serde_json::from_value(json!({
+            "predictions": [
+                {"bytesBase64Encoded": "aW1hZ2Ux", "mimeType": "image/png"},
+                {"raiFilteredReason": "filtered"}
+            ]
+        }))
+        .unwrap();
+        let response = ImagesResponse::from(imagen);
+        assert_eq!(response.data.len(), 1);
+        assert_eq!(response.data[0].b64_json.as_deref(), Some("aW1hZ2Ux"));
+
+        let titan: TitanImageResponse =
+            serde_json::from_value(json!({"images": ["aW1hZ2Ux", "aW1hZ2Uy"], "error": null}))
+                .unwrap();
+        assert_eq!(ImagesResponse::from(titan).data.len(), 2);
+    }
+}
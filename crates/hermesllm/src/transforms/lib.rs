@@ -1,7 +1,16 @@
-use crate::apis::anthropic::{MessagesContentBlock, MessagesImageSource};
-use crate::apis::openai::{ContentPart, FunctionCall, ImageUrl, Message, MessageContent, ToolCall};
+use crate::apis::anthropic::{
+    Citation, MessagesContentBlock, MessagesDocumentSource, MessagesImageSource, ToolResultContent,
+};
+use crate::apis::openai::{
+    ContentPart, FileContent, FunctionCall, ImageUrl, Message, MessageContent, ThinkingBlock,
+    ToolCall,
+};
 use crate::clients::TransformError;
-use serde_json::Value;
+use crate::transforms::request::from_openai::parse_data_url;
+use crate::transforms::{
+    HIGH_REASONING_BUDGET, LOW_REASONING_BUDGET, MAX_DOCUMENT_BYTES, MEDIUM_REASONING_BUDGET,
+};
+use serde_json::{json, Value};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 pub trait ExtractText {
@@ -94,13 +103,19 @@ impl ContentUtils<ToolCall> for Vec<MessagesContentBlock> {
                         },
                     });
                 }
+                MessagesContentBlock::Document { source, .. } => {
+                    content_parts.push(ContentPart::File {
+                        file: convert_document_source_to_file(source)?,
+                    });
+                }
                 MessagesContentBlock::ToolResult {
                     tool_use_id,
                     content,
                     is_error,
                     ..
                 } => {
-                    let result_text = content.extract_text();
+                    let (result_text, images) = split_tool_result(tool_use_id, content);
+                    content_parts.extend(images);
                     tool_results.push((
                         tool_use_id.clone(),
                         result_text,
@@ -116,8 +131,14 @@ impl ContentUtils<ToolCall> for Vec<MessagesContentBlock> {
                     tool_use_id,
                     content,
                     is_error,
+                } => {
+                    tool_results.push((
+                        tool_use_id.clone(),
+                        content.to_string(),
+                        is_error.unwrap_or(false),
+                    ));
                 }
-                | MessagesContentBlock::McpToolResult {
+                MessagesContentBlock::McpToolResult {
                     tool_use_id,
                     content,
                     is_error,
@@ -140,6 +161,45 @@ impl ContentUtils<ToolCall> for Vec<MessagesContentBlock> {
     }
 }
 
+/// Split a tool result into its text and its images as OpenAI content parts.
+///
+/// OpenAI tool messages only carry text, so images travel in the message that
+/// follows the tool messages, introduced by the tool call they came from, and
+/// the tool text points to them.
+fn split_tool_result(tool_use_id: &str, content: &ToolResultContent) -> (String, Vec<ContentPart>) {
+    let ToolResultContent::Blocks(blocks) = content else {
+        return (content.extract_text(), Vec::new());
+    };
+    let images: Vec<ContentPart> = blocks
+        .iter()
+        .filter_map(|block| match block {
+            MessagesContentBlock::Image { source } => Some(ContentPart::ImageUrl {
+                image_url: ImageUrl {
+                    url: convert_image_source_to_url(source),
+                    detail: Some("auto".to_string()),
+                },
+            }),
+            _ => None,
+        })
+        .collect();
+    let mut text = content.extract_text();
+    if images.is_empty() {
+        return (text, images);
+    }
+    if !text.is_empty() {
+        text.push('\n');
+    }
+    text.push_str(&format!(
+        "[{} image(s) attached in the next message]",
+        images.len()
+    ));
+    let mut parts = vec![ContentPart::Text {
+        text: format!("Images returned by tool call {tool_use_id}:"),
+    }];
+    parts.extend(images);
+    (text, parts)
+}
+
 /// Convert image source to URL
 pub fn convert_image_source_to_url(source: &MessagesImageSource) -> String {
     match source {
@@ -180,21 +240,93 @@ fn convert_image_url_to_source(image_url: &ImageUrl) -> MessagesImageSource {
     }
 }
 
+/// Media type and base64 data of a file part's inline document, if it has
+/// one.
+pub fn inline_document(file: &FileContent) -> Result<Option<(String, String)>, TransformError> {
+    let Some(file_data) = &file.file_data else {
+        return Ok(None);
+    };
+    let (media_type, data) = parse_data_url(file_data).ok_or_else(|| {
+        TransformError::UnsupportedContent("file_data must be a base64 data URL".to_string())
+    })?;
+    check_document_size(&data)?;
+    Ok(Some((media_type, data)))
+}
+
+/// Reject base64 documents over [`MAX_DOCUMENT_BYTES`] once decoded
+pub fn check_document_size(data: &str) -> Result<(), TransformError> {
+    let size = data.len() / 4 * 3;
+    if size > MAX_DOCUMENT_BYTES {
+        return Err(TransformError::UnsupportedContent(format!(
+            "document of {} bytes exceeds the {} byte limit",
+            size, MAX_DOCUMENT_BYTES
+        )));
+    }
+    Ok(())
+}
+
+/// Convert an OpenAI file part to an Anthropic document source
+pub fn convert_file_to_document_source(
+    file: &FileContent,
+) -> Result<MessagesDocumentSource, TransformError> {
+    if let Some((media_type, data)) = inline_document(file)? {
+        return Ok(MessagesDocumentSource::Base64 { media_type, data });
+    }
+    if let Some(file_id) = &file.file_id {
+        return Ok(MessagesDocumentSource::File {
+            file_id: file_id.clone(),
+        });
+    }
+    if let Some(url) = &file.file_url {
+        return Ok(MessagesDocumentSource::Url { url: url.clone() });
+    }
+    Err(TransformError::MissingField(
+        "file part needs file_data, file_id or file_url".to_string(),
+    ))
+}
+
+/// Convert an Anthropic document source to an OpenAI file part
+pub fn convert_document_source_to_file(
+    source: &MessagesDocumentSource,
+) -> Result<FileContent, TransformError> {
+    Ok(match source {
+        MessagesDocumentSource::Base64 { media_type, data } => {
+            check_document_size(data)?;
+            FileContent {
+                file_data: Some(format!("data:{};base64,{}", media_type, data)),
+                ..Default::default()
+            }
+        }
+        MessagesDocumentSource::Url { url } => FileContent {
+            file_url: Some(url.clone()),
+            ..Default::default()
+        },
+        MessagesDocumentSource::File { file_id } => FileContent {
+            file_id: Some(file_id.clone()),
+            ..Default::default()
+        },
+    })
+}
+
 /// Convert OpenAI message to Anthropic content blocks
 pub fn convert_openai_message_to_anthropic_content(
     message: &Message,
 ) -> Result<Vec<MessagesContentBlock>, TransformError> {
-    let mut blocks = Vec::new();
+    // Thinking has to lead the assistant turn it belongs to
+    let mut blocks = message
+        .thinking_blocks
+        .as_deref()
+        .map(convert_thinking_blocks_to_anthropic)
+        .unwrap_or_default();
 
     // Handle regular content
     match &message.content {
-        Some(MessageContent::Text(text)) => {
-            if !text.is_empty() {
-                blocks.push(MessagesContentBlock::Text {
-                    text: text.clone(),
-                    cache_control: None,
-                });
-            }
+        Some(MessageContent::Text(text)) if !text.is_empty() => {
+            blocks.push(MessagesContentBlock::Text {
+                text: text.clone(),
+                citations: None,
+                cache_control: None,
+            });
         }
         Some(MessageContent::Parts(parts)) => {
             for part in parts {
@@ -202,6 +334,7 @@ pub fn convert_openai_message_to_anthropic_content(
                     ContentPart::Text { text } => {
                         blocks.push(MessagesContentBlock::Text {
                             text: text.clone(),
+                            citations: None,
                             cache_control: None,
                         });
                     }
@@ -209,10 +342,23 @@ pub fn convert_openai_message_to_anthropic_content(
                         let source = convert_image_url_to_source(image_url);
                         blocks.push(MessagesContentBlock::Image { source });
                     }
+                    ContentPart::File { file } => {
+                        let source = convert_file_to_document_source(file)?;
+                        blocks.push(MessagesContentBlock::Document {
+                            source,
+                            title: None,
+                            citations: None,
+                        });
+                    }
+                    ContentPart::InputAudio { .. } => {
+                        return Err(TransformError::UnsupportedContent(
+                            "Anthropic does not support audio input".to_string(),
+                        ));
+                    }
                 }
             }
         }
-        None => {}
+        Some(MessageContent::Text(_)) | None => {}
     }
 
     // Handle tool calls
@@ -230,3 +376,95 @@ pub fn convert_openai_message_to_anthropic_content(
 
     Ok(blocks)
 }
+
+/// Convert OpenAI-side thinking blocks back to Anthropic ones. Streamed
+/// pieces are joined until the signature that closes them; thinking that
+/// never got a signature is dropped, as Anthropic would reject it.
+pub fn convert_thinking_blocks_to_anthropic(
+    thinking_blocks: &[ThinkingBlock],
+) -> Vec<MessagesContentBlock> {
+    let mut blocks: Vec<MessagesContentBlock> = Vec::new();
+    for block in thinking_blocks {
+        match block {
+            ThinkingBlock::Thinking {
+                thinking,
+                signature,
+            } => {
+                if let Some(MessagesContentBlock::Thinking {
+                    thinking: open_thinking,
+                    signature: open_signature @ None,
+                    ..
+                }) = blocks.last_mut()
+                {
+                    open_thinking.push_str(thinking);
+                    *open_signature = signature.clone();
+                } else {
+                    blocks.push(MessagesContentBlock::Thinking {
+                        thinking: thinking.clone(),
+                        signature: signature.clone(),
+                        cache_control: None,
+                    });
+                }
+            }
+            ThinkingBlock::RedactedThinking { data } => {
+                blocks.push(MessagesContentBlock::RedactedThinking { data: data.clone() });
+            }
+        }
+    }
+    blocks.retain(|block| {
+        !matches!(
+            block,
+            MessagesContentBlock::Thinking {
+                signature: None,
+                ..
+            }
+        )
+    });
+    blocks
+}
+
+// Citation Utilities
+
+/// The OpenAI message annotation for an Anthropic citation, over the content
+/// characters `start..end` when they are known. Web search results become
+/// `url_citation`s; other sources keep their Anthropic shape as a `citation`.
+pub fn citation_annotation(citation: &Citation, span: Option<(usize, usize)>) -> Value {
+    let (kind, mut body) = match citation {
+        Citation::WebSearchResultLocation { url, title, .. } => {
+            ("url_citation", json!({"url": url, "title": title}))
+        }
+        _ => ("citation", json!(citation)),
+    };
+    if let (Some((start, end)), Some(fields)) = (span, body.as_object_mut()) {
+        fields.insert("start_index".to_string(), start.into());
+        fields.insert("end_index".to_string(), end.into());
+    }
+    json!({"type": kind, kind: body})
+}
+
+// Reasoning Utilities
+
+/// Thinking token budget for an OpenAI `reasoning_effort`. `Some(0)` turns
+/// thinking off; unknown efforts give `None`, leaving the provider default.
+pub fn reasoning_effort_budget(effort: &str) -> Option<u32> {
+    match effort {
+        "none" => Some(0),
+        "minimal" | "low" => Some(LOW_REASONING_BUDGET),
+        "medium" => Some(MEDIUM_REASONING_BUDGET),
+        "high" => Some(HIGH_REASONING_BUDGET),
+        _ => None,
+    }
+}
+
+/// The OpenAI `reasoning_effort` closest to a thinking token budget
+pub fn budget_reasoning_effort(budget: u32) -> &'static str {
+    if budget == 0 {
+        "none"
+    } else if budget <= LOW_REASONING_BUDGET {
+        "low"
+    } else if budget <= MEDIUM_REASONING_BUDGET {
+        "medium"
+    } else {
+        "high"
+    }
+}
@@ -0,0 +1,376 @@
+//! Detection of client input a transform drops, for strict transform mode.
+//!
+//! Converting between APIs is best-effort by default: parameters and content
+//! the upstream API has no counterpart for are left out. With
+//! [`TransformOptions::strict`] the conversion fails with
+//! [`TransformError::LossyConversion`] naming what would have been dropped.
+//! Only input that changes what the model generates or returns is reported;
+//! bookkeeping such as `metadata` or `service_tier` is not.
+//!
+//! [`TransformOptions`] also carries the output limit a conversion fills in
+//! for upstreams that require one when the client set none.
+
+use crate::apis::amazon_bedrock::InvokeModelFamily;
+use crate::apis::anthropic::{
+    MessagesContentBlock, MessagesMessageContent, MessagesRequest, MessagesResponse,
+};
+use crate::apis::openai::ChatCompletionsRequest;
+use crate::clients::endpoints::{SupportedAPIsFromClient, SupportedUpstreamAPIs};
+use crate::clients::TransformError;
+use crate::providers::request::ProviderRequestType;
+use crate::transforms::DEFAULT_MAX_TOKENS;
+
+/// How a conversion between APIs treats input the target can't express.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransformOptions {
+    /// Fail instead of silently dropping fields and content blocks
+    pub strict: bool,
+    /// Output limit for upstreams that require one (Anthropic) when the
+    /// client sets none; thinking budgets come on top of it
+    pub default_max_tokens: u32,
+}
+
+impl Default for TransformOptions {
+    fn default() -> Self {
+        Self {
+            strict: false,
+            default_max_tokens: DEFAULT_MAX_TOKENS,
+        }
+    }
+}
+
+impl TransformOptions {
+    pub const STRICT: Self = Self {
+        strict: true,
+        default_max_tokens: DEFAULT_MAX_TOKENS,
+    };
+
+    /// `Err(LossyConversion)` in strict mode when `dropped` finds anything;
+    /// best-effort mode doesn't look.
+    pub fn check(&self, dropped: impl FnOnce() -> Vec<String>) -> Result<(), TransformError> {
+        if !self.strict {
+            return Ok(());
+        }
+        let dropped = dropped();
+        if dropped.is_empty() {
+            return Ok(());
+        }
+        Err(TransformError::LossyConversion { dropped })
+    }
+}
+
+/// What converting `request` for `upstream_api` drops. Responses API
+/// requests are not inspected.
+pub fn dropped_request_input(
+    request: &ProviderRequestType,
+    upstream_api: &SupportedUpstreamAPIs,
+) -> Vec<String> {
+    match request {
+        ProviderRequestType::ChatCompletionsRequest(req) => dropped_chat_fields(req, upstream_api),
+        ProviderRequestType::MessagesRequest(req) => dropped_messages_input(req, upstream_api),
+        _ => Vec::new(),
+    }
+}
+
+/// What converting an upstream response body for `client_api` drops. Only
+/// Anthropic responses are inspected, as their content blocks are the ones
+/// other APIs have no room for.
+pub fn dropped_response_content(
+    body: &[u8],
+    client_api: &SupportedAPIsFromClient,
+    upstream_api: &SupportedUpstreamAPIs,
+) -> Vec<String> {
+    if !matches!(upstream_api, SupportedUpstreamAPIs::AnthropicMessagesAPI(_)) {
+        return Vec::new();
+    }
+    let Ok(response) = serde_json::from_slice::<MessagesResponse>(body) else {
+        return Vec::new();
+    };
+    let kept: &[&str] = match client_api {
+        SupportedAPIsFromClient::AnthropicMessagesAPI(_) => return Vec::new(),
+        SupportedAPIsFromClient::OpenAIChatCompletions(_) => &[
+            "text",
+            "thinking",
+            "redacted_thinking",
+            "tool_use",
+            "server_tool_use",
+            "mcp_tool_use",
+        ],
+        // Server tool results are restored as built-in tool calls
+        SupportedAPIsFromClient::OpenAIResponsesAPI(_) => &[
+            "text",
+            "thinking",
+            "redacted_thinking",
+            "tool_use",
+            "server_tool_use",
+            "mcp_tool_use",
+            "web_search_tool_result",
+            "code_execution_tool_result",
+        ],
+    };
+    dropped_blocks(response.content.iter(), kept)
+}
+
+/// Chat Completions parameters and whether the request sets them.
+fn chat_fields(req: &ChatCompletionsRequest) -> [(&'static str, bool); 12] {
+    [
+        ("logit_bias", req.logit_bias.is_some()),
+        ("logprobs", req.logprobs == Some(true)),
+        ("top_logprobs", req.top_logprobs.is_some()),
+        ("n", req.n.is_some_and(|n| n > 1)),
+        ("presence_penalty", req.presence_penalty.is_some()),
+        ("frequency_penalty", req.frequency_penalty.is_some()),
+        ("seed", req.seed.is_some()),
+        ("response_format", req.response_format.is_some()),
+        ("top_k", req.top_k.is_some()),
+        ("reasoning_effort", req.reasoning_effort.is_some()),
+        ("modalities", req.modalities.is_some()),
+        ("web_search_options", req.web_search_options.is_some()),
+    ]
+}
+
+fn dropped_chat_fields(
+    req: &ChatCompletionsRequest,
+    upstream_api: &SupportedUpstreamAPIs,
+) -> Vec<String> {
+    const ANTHROPIC: &[&str] = &["reasoning_effort"];
+    let kept: &[&str] = match upstream_api {
+        SupportedUpstreamAPIs::OpenAIChatCompletions(_)
+        | SupportedUpstreamAPIs::OpenAIResponsesAPI(_) => return Vec::new(),
+        SupportedUpstreamAPIs::AnthropicMessagesAPI(_) => ANTHROPIC,
+        SupportedUpstreamAPIs::AmazonBedrockConverse(_)
+        | SupportedUpstreamAPIs::AmazonBedrockConverseStream(_) => &[],
+        SupportedUpstreamAPIs::AmazonBedrockInvokeModel(_)
+        | SupportedUpstreamAPIs::AmazonBedrockInvokeModelStream(_) => {
+            match InvokeModelFamily::from_model_id(&req.model) {
+                Some(InvokeModelFamily::Anthropic) => ANTHROPIC,
+                _ => &[],
+            }
+        }
+        SupportedUpstreamAPIs::GeminiGenerateContent(_)
+        | SupportedUpstreamAPIs::GeminiStreamGenerateContent(_) => &[
+            "logprobs",
+            "top_logprobs",
+            "n",
+            "presence_penalty",
+            "frequency_penalty",
+            "seed",
+            "response_format",
+            "top_k",
+            "reasoning_effort",
+        ],
+        SupportedUpstreamAPIs::CohereChat(_) => &[
+            "logprobs",
+            "presence_penalty",
+            "frequency_penalty",
+            "seed",
+            "response_format",
+            "top_k",
+        ],
+        SupportedUpstreamAPIs::OllamaChat(_) => &[
+            "presence_penalty",
+            "frequency_penalty",
+            "seed",
+            "response_format",
+            "top_k",
+            "reasoning_effort",
+        ],
+    };
+    chat_fields(req)
+        .into_iter()
+        .filter(|(name, set)| *set && !kept.contains(name))
+        .map(|(name, _)| name.to_string())
+        .collect()
+}
+
+fn dropped_messages_input(
+    req: &MessagesRequest,
+    upstream_api: &SupportedUpstreamAPIs,
+) -> Vec<String> {
+    // Bedrock Converse is converted directly; the other APIs go through
+    // Chat Completions, which keeps images, documents, tool results and
+    // thinking settings as `reasoning_effort`
+    let (kept_fields, kept_blocks): (&[&str], &[&str]) = match upstream_api {
+        SupportedUpstreamAPIs::AnthropicMessagesAPI(_) => return Vec::new(),
+        SupportedUpstreamAPIs::AmazonBedrockInvokeModel(_)
+        | SupportedUpstreamAPIs::AmazonBedrockInvokeModelStream(_)
+            if InvokeModelFamily::from_model_id(&req.model)
+                == Some(InvokeModelFamily::Anthropic) =>
+        {
+            return Vec::new()
+        }
+        SupportedUpstreamAPIs::AmazonBedrockConverse(_)
+        | SupportedUpstreamAPIs::AmazonBedrockConverseStream(_) => (
+            &[],
+            &["text", "image", "document", "tool_use", "tool_result"],
+        ),
+        _ => (
+            &["thinking"],
+            &[
+                "text",
+                "image",
+                "document",
+                "tool_use",
+                "server_tool_use",
+                "mcp_tool_use",
+                "tool_result",
+                "web_search_tool_result",
+                "code_execution_tool_result",
+                "mcp_tool_result",
+            ],
+        ),
+    };
+
+    let fields = [
+        ("top_k", req.top_k.is_some()),
+        ("thinking", req.thinking.is_some()),
+        ("container", req.container.is_some()),
+        ("mcp_servers", req.mcp_servers.is_some()),
+    ];
+    let mut dropped: Vec<String> = fields
+        .into_iter()
+        .filter(|(name, set)| *set && !kept_fields.contains(name))
+        .map(|(name, _)| name.to_string())
+        .collect();
+
+    let blocks = req
+        .messages
+        .iter()
+        .flat_map(|message| match &message.content {
+            MessagesMessageContent::Blocks(blocks) => blocks.as_slice(),
+            MessagesMessageContent::Single(_) => &[],
+        });
+    dropped.extend(dropped_blocks(blocks, kept_blocks));
+    dropped
+}
+
+/// Each content block type outside `kept`, once, as `<type> content`.
+fn dropped_blocks<'a>(
+    blocks: impl Iterator<Item = &'a MessagesContentBlock>,
+    kept: &[&str],
+) -> Vec<String> {
+    let mut dropped = Vec::new();
+    for block in blocks {
+        let block_type = block_type(block);
+        let name = format!("{} content", block_type);
+        if !kept.contains(&block_type) && !dropped.contains(&name) {
+            dropped.push(name);
+        }
+    }
+    dropped
+}
+
+fn block_type(block: &MessagesContentBlock) -> &'static str {
+    match block {
+        MessagesContentBlock::Text { .. } => "text",
+        MessagesContentBlock::Thinking { .. } => "thinking",
+        MessagesContentBlock::RedactedThinking { .. } => "redacted_thinking",
+        MessagesContentBlock::Image { .. } => "image",
+        MessagesContentBlock::Document { .. } => "document",
+        MessagesContentBlock::ToolUse { .. } => "tool_use",
+        MessagesContentBlock::ToolResult { .. } => "tool_result",
+        MessagesContentBlock::ServerToolUse { .. } => "server_tool_use",
+        MessagesContentBlock::WebSearchToolResult { .. } => "web_search_tool_result",
+        MessagesContentBlock::CodeExecutionToolResult { .. } => "code_execution_tool_result",
+        MessagesContentBlock::McpToolUse { .. } => "mcp_tool_use",
+        MessagesContentBlock::McpToolResult { .. } => "mcp_tool_result",
+        MessagesContentBlock::ContainerUpload { .. } => "container_upload",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::apis::anthropic::AnthropicApi;
+    use crate::apis::openai::OpenAIApi;
+    use crate::apis::GeminiApi;
+    use serde_json::json;
+
+    #[test]
+    fn strict_mode_reports_dropped_chat_fields() {
+        let request: ChatCompletionsRequest = serde_json::from_value(json!({
+            "model": "claude-sonnet-4-5",
+            "messages": [{"role": "user", "content": "Hi"}],
+            "logit_bias": {"1234": -100},
+            "seed": 7,
+            "reasoning_effort": "low"
+        }))
+        .unwrap();
+        let request = ProviderRequestType::ChatCompletionsRequest(request);
+
+        let dropped = dropped_request_input(
+            &request,
+            &SupportedUpstreamAPIs::AnthropicMessagesAPI(AnthropicApi::Messages),
+        );
+        assert_eq!(dropped, vec!["logit_bias", "seed"]);
+        assert!(matches!(
+            TransformOptions::STRICT.check(|| dropped.clone()),
+            Err(TransformError::LossyConversion { .. })
+        ));
+        assert!(TransformOptions::default().check(|| dropped).is_ok());
+
+        let dropped = dropped_request_input(
+            &request,
+            &SupportedUpstreamAPIs::GeminiGenerateContent(GeminiApi::GenerateContent),
+        );
+        assert_eq!(dropped, vec!["logit_bias"]);
+        assert!(dropped_request_input(
+            &request,
+            &SupportedUpstreamAPIs::OpenAIChatCompletions(OpenAIApi::ChatCompletions),
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn strict_mode_reports_dropped_content_blocks() {
+        let request: MessagesRequest = serde_json::from_value(json!({
+            "model": "claude-sonnet-4-5",
+            "max_tokens": 100,
+            "messages": [
+                {"role": "user", "content": "Hi"},
+                {"role": "assistant", "content": [
+                    {"type": "thinking", "thinking": "The user greets me", "signature": "sig"},
+                    {"type": "text", "text": "Hello"}
+                ]}
+            ]
+        }))
+        .unwrap();
+
+        let dropped = dropped_request_input(
+            &ProviderRequestType::MessagesRequest(request),
+            &SupportedUpstreamAPIs::OpenAIChatCompletions(OpenAIApi::ChatCompletions),
+        );
+        assert_eq!(dropped, vec!["thinking content"]);
+
+        let response = json!({
+            "id": "msg_01",
+            "type": "message",
+            "role": "assistant",
+            "model": "claude-sonnet-4-5",
+            "content": [
+                {"type": "server_tool_use", "id": "srv_1", "name": "web_search", "input": {"query": "rust"}},
+                {"type": "web_search_tool_result", "tool_use_id": "srv_1", "content": []},
+                {"type": "text", "text": "Found it"}
+            ],
+            "stop_reason": "end_turn",
+            "stop_sequence": null,
+            "usage": {"input_tokens": 10, "output_tokens": 5}
+        })
+        .to_string();
+        let upstream = SupportedUpstreamAPIs::AnthropicMessagesAPI(AnthropicApi::Messages);
+        assert_eq!(
+            dropped_response_content(
+                response.as_bytes(),
+                &SupportedAPIsFromClient::OpenAIChatCompletions(OpenAIApi::ChatCompletions),
+                &upstream,
+            ),
+            vec!["web_search_tool_result content"]
+        );
+        assert!(dropped_response_content(
+            response.as_bytes(),
+            &SupportedAPIsFromClient::OpenAIResponsesAPI(OpenAIApi::Responses),
+            &upstream,
+        )
+        .is_empty());
+    }
+}
@@ -8,16 +8,28 @@
 //! by the gateway, but the external API surface remains these two standard formats.
 //! The transformations are split into logical modules for maintainability.
 
+pub mod audio;
+pub mod batches;
+pub mod completions;
+pub mod embeddings;
+pub mod images;
 pub mod lib;
+pub mod lossy;
+pub mod moderations;
+pub mod pipeline;
 pub mod request;
 pub mod response;
 pub mod response_streaming;
+pub mod tool_call_ids;
 
 // Re-export commonly used items for convenience
 pub use lib::*;
+pub use lossy::TransformOptions;
+pub use pipeline::{TransformPipeline, UpstreamRequest};
 pub use request::*;
 pub use response::*;
 pub use response_streaming::*;
+pub use tool_call_ids::{ToolCallIdFormat, ToolCallIdMap};
 
 // ============================================================================
 // CONSTANTS
@@ -25,3 +37,13 @@ pub use response_streaming::*;
 
 /// Default maximum tokens when converting from OpenAI to Anthropic and no max_tokens is specified
 pub const DEFAULT_MAX_TOKENS: u32 = 4096;
+
+/// Thinking token budgets standing in for OpenAI `reasoning_effort` levels on
+/// providers that take a budget instead (Anthropic, Gemini)
+pub const LOW_REASONING_BUDGET: u32 = 1024;
+pub const MEDIUM_REASONING_BUDGET: u32 = 8192;
+pub const HIGH_REASONING_BUDGET: u32 = 24576;
+
+/// Largest inline document carried across formats, Anthropic's request size
+/// limit for PDFs
+pub const MAX_DOCUMENT_BYTES: usize = 32 * 1024 * 1024;
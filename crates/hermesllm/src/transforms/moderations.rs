@@ -0,0 +1,159 @@
+//! Translations between the OpenAI moderation API and Amazon Bedrock's
+//! `ApplyGuardrail`.
+
+use crate::apis::amazon_bedrock::{
+    ApplyGuardrailRequest, ApplyGuardrailResponse, GuardrailContentBlock, GuardrailTextBlock,
+};
+use crate::apis::moderations::{ModerationRequest, ModerationResult};
+use crate::clients::TransformError;
+
+/// Guardrail content filters and the OpenAI category each stands for.
+/// `PROMPT_ATTACK` has no OpenAI counterpart and keeps a category of its own.
+const CONTENT_FILTER_CATEGORIES: [(&str, &str); 6] = [
+    ("HATE", "hate"),
+    ("INSULTS", "harassment"),
+    ("SEXUAL", "sexual"),
+    ("VIOLENCE", "violence"),
+    ("MISCONDUCT", "illicit"),
+    ("PROMPT_ATTACK", "prompt_attack"),
+];
+
+/// Guardrails grade confidence rather than score it
+fn confidence_score(confidence: &str) -> f64 {
+    match confidence {
+        "LOW" => 0.3,
+        "MEDIUM" => 0.6,
+        "HIGH" => 0.9,
+        _ => 0.0,
+    }
+}
+
+impl TryFrom<ModerationRequest> for ApplyGuardrailRequest {
+    type Error = TransformError;
+
+    fn try_from(request: ModerationRequest) -> Result<Self, Self::Error> {
+        if request.input.has_images() {
+            return Err(TransformError::UnsupportedConversion(
+                "Bedrock guardrails only moderate text".to_string(),
+            ));
+        }
+        let content: Vec<GuardrailContentBlock> = request
+            .input
+            .texts()
+            .into_iter()
+            .map(|text| GuardrailContentBlock {
+                text: GuardrailTextBlock {
+                    text: text.to_string(),
+                },
+            })
+            .collect();
+        if content.is_empty() {
+            return Err(TransformError::MissingField("input".to_string()));
+        }
+        Ok(ApplyGuardrailRequest {
+            source: "INPUT".to_string(),
+            content,
+        })
+    }
+}
+
+impl From<ApplyGuardrailResponse> for ModerationResult {
+    /// A guardrail assesses every input at once, so the whole request gets a
+    /// single result. Denied topics, blocked words and sensitive information
+    /// are reported as `denied_topic`, `blocked_words` and
+    /// `sensitive_information`.
+    fn from(response: ApplyGuardrailResponse) -> Self {
+        let mut result = ModerationResult::unflagged();
+        for assessment in response.assessments {
+            for filter in assessment
+                .content_policy
+                .map(|policy| policy.filters)
+                .unwrap_or_default()
+            {
+                let category = CONTENT_FILTER_CATEGORIES
+                    .iter()
+                    .find(|(filter_type, _)| *filter_type == filter.filter_type)
+                    .map_or_else(
+                        || filter.filter_type.to_ascii_lowercase(),
+                        |(_, category)| category.to_string(),
+                    );
+                result.record(
+                    &category,
+                    filter.action == "BLOCKED",
+                    confidence_score(&filter.confidence),
+                );
+            }
+            if let Some(policy) = assessment.topic_policy {
+                let blocked = policy.topics.iter().any(|topic| topic.action == "BLOCKED");
+                result.record("denied_topic", blocked, if blocked { 1.0 } else { 0.0 });
+            }
+            if let Some(policy) = assessment.word_policy {
+                let blocked = policy
+                    .custom_words
+                    .iter()
+                    .chain(&policy.managed_word_lists)
+                    .any(|word| word.action == "BLOCKED");
+                result.record("blocked_words", blocked, if blocked { 1.0 } else { 0.0 });
+            }
+            if let Some(policy) = assessment.sensitive_information_policy {
+                let blocked = policy
+                    .pii_entities
+                    .iter()
+                    .chain(&policy.regexes)
+                    .any(|entity| entity.action == "BLOCKED");
+                result.record(
+                    "sensitive_information",
+                    blocked,
+                    if blocked { 1.0 } else { 0.0 },
+                );
+            }
+        }
+        // An intervention with no blocking assessment still counts as flagged
+        result.flagged |= response.action == "GUARDRAIL_INTERVENED";
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_moderation_request_to_guardrail() {
+        let request: ModerationRequest =
+            serde_json::from_value(json!({"model": "gr-1:1", "input": ["one", "two"]})).unwrap();
+        assert_eq!(
+            serde_json::to_value(ApplyGuardrailRequest::try_from(request).unwrap()).unwrap(),
+            json!({
+                "source": "INPUT",
+                "content": [{"text": {"text": "one"}}, {"text": {"text": "two"}}]
+            })
+        );
+    }
+
+    #[test]
+    fn test_guardrail_response_to_moderation_result() {
+        let response: ApplyGuardrailResponse = serde_json::from_value(json!({
+            "action": "GUARDRAIL_INTERVENED",
+            "outputs": [{"text": "Sorry, I can't help with that."}],
+            "assessments": [{
+                "contentPolicy": {"filters": [
+                    {"type": "VIOLENCE", "confidence": "HIGH", "filterStrength": "HIGH", "action": "BLOCKED", "detected": true},
+                    {"type": "INSULTS", "confidence": "LOW", "action": "NONE"}
+                ]},
+                "topicPolicy": {"topics": [{"name": "Investing", "type": "DENY", "action": "BLOCKED"}]}
+            }]
+        }))
+        .unwrap();
+        let result = ModerationResult::from(response);
+        assert!(result.flagged);
+        assert_eq!(
+            result.flagged_categories(),
+            vec!["denied_topic", "violence"]
+        );
+        assert_eq!(result.category_scores["violence"], 0.9);
+        assert_eq!(result.category_scores["harassment"], 0.3);
+        assert!(!result.categories["hate"]);
+    }
+}
@@ -0,0 +1,216 @@
+//! Conversion between APIs with embedder hooks.
+//!
+//! [`TransformPipeline`] runs the same request and response conversions as
+//! the `TryFrom` impls on [`ProviderRequestType`] and
+//! [`ProviderResponseType`], then hands the converted value to registered
+//! hooks before it is serialized. Hooks can set org-specific metadata, add
+//! headers for the outgoing request or strip fields an upstream doesn't
+//! accept yet, without changing the conversions themselves.
+//!
+//! ```ignore
+//! let pipeline = TransformPipeline::new()
+//!     .with_request_hook(|upstream| {
+//!         upstream.headers.push(("x-org-id".to_string(), "acme".to_string()));
+//!     })
+//!     .with_response_hook(|response, _client_api| { /* ... */ });
+//! let upstream = pipeline.transform_request(client_request, &upstream_api)?;
+//! ```
+
+use std::fmt;
+use std::sync::Arc;
+
+use crate::clients::endpoints::{SupportedAPIsFromClient, SupportedUpstreamAPIs};
+use crate::providers::request::{ProviderRequest, ProviderRequestError, ProviderRequestType};
+use crate::providers::response::ProviderResponseType;
+use crate::transforms::lossy::TransformOptions;
+
+/// A request converted for its upstream, as request hooks see it.
+#[derive(Debug, Clone)]
+pub struct UpstreamRequest {
+    pub request: ProviderRequestType,
+    pub upstream_api: SupportedUpstreamAPIs,
+    /// Headers to add to the outgoing HTTP request
+    pub headers: Vec<(String, String)>,
+}
+
+impl UpstreamRequest {
+    pub fn to_bytes(&self) -> Result<Vec<u8>, ProviderRequestError> {
+        self.request.to_bytes()
+    }
+}
+
+type RequestHook = Arc<dyn Fn(&mut UpstreamRequest) + Send + Sync>;
+type ResponseHook = Arc<dyn Fn(&mut ProviderResponseType, &SupportedAPIsFromClient) + Send + Sync>;
+
+/// Request and response conversions followed by hooks, run in the order
+/// they were registered.
+#[derive(Clone, Default)]
+pub struct TransformPipeline {
+    options: TransformOptions,
+    request_hooks: Vec<RequestHook>,
+    response_hooks: Vec<ResponseHook>,
+}
+
+impl fmt::Debug for TransformPipeline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TransformPipeline")
+            .field("options", &self.options)
+            .field("request_hooks", &self.request_hooks.len())
+            .field("response_hooks", &self.response_hooks.len())
+            .finish()
+    }
+}
+
+impl TransformPipeline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether any hooks are registered
+    pub fn is_empty(&self) -> bool {
+        self.request_hooks.is_empty() && self.response_hooks.is_empty()
+    }
+
+    pub fn with_options(mut self, options: TransformOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Run `hook` on every request once it's converted for the upstream.
+    pub fn with_request_hook(
+        mut self,
+        hook: impl Fn(&mut UpstreamRequest) + Send + Sync + 'static,
+    ) -> Self {
+        self.request_hooks.push(Arc::new(hook));
+        self
+    }
+
+    /// Run `hook` on every non-streaming response once it's converted for
+    /// the client.
+    pub fn with_response_hook(
+        mut self,
+        hook: impl Fn(&mut ProviderResponseType, &SupportedAPIsFromClient) + Send + Sync + 'static,
+    ) -> Self {
+        self.response_hooks.push(Arc::new(hook));
+        self
+    }
+
+    /// Convert `request` for `upstream_api` and run the request hooks.
+    pub fn transform_request(
+        &self,
+        request: ProviderRequestType,
+        upstream_api: &SupportedUpstreamAPIs,
+    ) -> Result<UpstreamRequest, ProviderRequestError> {
+        let request = ProviderRequestType::try_from((request, upstream_api, self.options))?;
+        let mut upstream = UpstreamRequest {
+            request,
+            upstream_api: upstream_api.clone(),
+            headers: Vec::new(),
+        };
+        for hook in &self.request_hooks {
+            hook(&mut upstream);
+        }
+        Ok(upstream)
+    }
+
+    /// Parse an upstream response body into the client's shape and run the
+    /// response hooks.
+    pub fn transform_response(
+        &self,
+        body: &[u8],
+        client_api: &SupportedAPIsFromClient,
+        upstream_api: &SupportedUpstreamAPIs,
+    ) -> Result<ProviderResponseType, std::io::Error> {
+        let mut response =
+            ProviderResponseType::try_from((body, client_api, upstream_api, self.options))?;
+        for hook in &self.response_hooks {
+            hook(&mut response, client_api);
+        }
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::apis::anthropic::AnthropicApi;
+    use crate::apis::openai::{ChatCompletionsRequest, OpenAIApi};
+    use serde_json::{json, Value};
+
+    fn chat_request() -> ProviderRequestType {
+        let request: ChatCompletionsRequest = serde_json::from_value(json!({
+            "model": "claude-sonnet-4-5",
+            "messages": [{"role": "user", "content": "Hi"}],
+            "temperature": 0.2
+        }))
+        .unwrap();
+        ProviderRequestType::ChatCompletionsRequest(request)
+    }
+
+    #[test]
+    fn request_hooks_run_in_order_after_conversion() {
+        let pipeline = TransformPipeline::new()
+            .with_request_hook(|upstream| {
+                if let ProviderRequestType::MessagesRequest(req) = &mut upstream.request {
+                    req.temperature = None;
+                    req.metadata = Some([("org".to_string(), json!("acme"))].into());
+                }
+                upstream
+                    .headers
+                    .push(("x-org-id".to_string(), "acme".to_string()));
+            })
+            .with_request_hook(|upstream| {
+                assert_eq!(upstream.headers.len(), 1);
+            });
+
+        let upstream = pipeline
+            .transform_request(
+                chat_request(),
+                &SupportedUpstreamAPIs::AnthropicMessagesAPI(AnthropicApi::Messages),
+            )
+            .unwrap();
+        assert_eq!(
+            upstream.headers,
+            vec![("x-org-id".to_string(), "acme".to_string())]
+        );
+        let body: Value = serde_json::from_slice(&upstream.to_bytes().unwrap()).unwrap();
+        assert!(body.get("temperature").is_none());
+        assert_eq!(body["metadata"]["org"], "acme");
+    }
+
+    #[test]
+    fn response_hooks_see_the_client_shape() {
+        let pipeline = TransformPipeline::new().with_response_hook(|response, client_api| {
+            assert!(matches!(
+                client_api,
+                SupportedAPIsFromClient::OpenAIChatCompletions(_)
+            ));
+            if let ProviderResponseType::ChatCompletionsResponse(resp) = response {
+                resp.system_fingerprint = Some("org-proxy".to_string());
+            }
+        });
+        let body = json!({
+            "id": "msg_01",
+            "type": "message",
+            "role": "assistant",
+            "model": "claude-sonnet-4-5",
+            "content": [{"type": "text", "text": "Hello"}],
+            "stop_reason": "end_turn",
+            "stop_sequence": null,
+            "usage": {"input_tokens": 3, "output_tokens": 1}
+        })
+        .to_string();
+
+        let response = pipeline
+            .transform_response(
+                body.as_bytes(),
+                &SupportedAPIsFromClient::OpenAIChatCompletions(OpenAIApi::ChatCompletions),
+                &SupportedUpstreamAPIs::AnthropicMessagesAPI(AnthropicApi::Messages),
+            )
+            .unwrap();
+        let ProviderResponseType::ChatCompletionsResponse(resp) = response else {
+            panic!("expected a ChatCompletions response");
+        };
+        assert_eq!(resp.system_fingerprint.as_deref(), Some("org-proxy"));
+    }
+}
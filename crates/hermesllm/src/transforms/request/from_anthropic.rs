@@ -1,6 +1,7 @@
 use crate::apis::amazon_bedrock::{
-    AnyChoice, AutoChoice, ContentBlock, ConversationRole, ConverseRequest, ImageBlock,
-    ImageSource, InferenceConfiguration, Message as BedrockMessage, SystemContentBlock,
+    AnyChoice, AutoChoice, ContentBlock, ConversationRole, ConverseRequest, DocumentBlock,
+    DocumentSource, ImageBlock, ImageSource, InferenceConfiguration, InvokeModelBody,
+    InvokeModelFamily, InvokeModelRequest, Message as BedrockMessage, SystemContentBlock,
     Tool as BedrockTool, ToolChoice as BedrockToolChoice, ToolChoiceSpec, ToolConfiguration,
     ToolInputSchema, ToolResultBlock, ToolResultContentBlock, ToolResultStatus, ToolSpecDefinition,
     ToolUseBlock,
@@ -53,9 +54,13 @@ impl TryFrom<AnthropicMessagesRequest> for ChatCompletionsRequest {
             tools: openai_tools,
             tool_choice: openai_tool_choice,
             parallel_tool_calls,
+            reasoning_effort: req
+                .thinking
+                .filter(|thinking| thinking.thinking_type == "enabled")
+                .and_then(|thinking| thinking.budget_tokens)
+                .map(|budget| budget_reasoning_effort(budget).to_string()),
             ..Default::default()
         };
-        _chat_completions_req.suppress_max_tokens_if_o3();
         _chat_completions_req.fix_temperature_if_gpt5();
         Ok(_chat_completions_req)
     }
@@ -98,7 +103,12 @@ impl TryFrom<AnthropicMessagesRequest> for ConverseRequest {
 
         // Convert tools and tool choice to ToolConfiguration
         // Only include toolConfig if we have actual tools (Bedrock requires at least 1 tool)
+        // Server tools run at Anthropic only
         let tool_config = req.tools.and_then(|anthropic_tools| {
+            let anthropic_tools: Vec<MessagesTool> = anthropic_tools
+                .into_iter()
+                .filter(|tool| !tool.is_server_tool())
+                .collect();
             if anthropic_tools.is_empty() {
                 return None;
             }
@@ -164,6 +174,26 @@ impl TryFrom<AnthropicMessagesRequest> for ConverseRequest {
 }
 
 // Message Conversions
+impl TryFrom<AnthropicMessagesRequest> for InvokeModelRequest {
+    type Error = TransformError;
+
+    /// Claude models on Bedrock take the Messages body as-is, wrapped in
+    /// Bedrock's envelope when serialized
+    fn try_from(req: AnthropicMessagesRequest) -> Result<Self, Self::Error> {
+        if InvokeModelFamily::from_model_id(&req.model) != Some(InvokeModelFamily::Anthropic) {
+            return Err(TransformError::UnsupportedConversion(format!(
+                "model '{}' is not a Claude model; Anthropic Messages clients can only use InvokeModel with Claude",
+                req.model
+            )));
+        }
+        Ok(InvokeModelRequest {
+            model_id: req.model.clone(),
+            stream: req.stream.unwrap_or(false),
+            body: InvokeModelBody::Anthropic(req),
+        })
+    }
+}
+
 impl TryFrom<MessagesMessage> for Vec<Message> {
     type Error = TransformError;
 
@@ -178,6 +208,7 @@ impl TryFrom<MessagesMessage> for Vec<Message> {
                     name: None,
                     tool_calls: None,
                     tool_call_id: None,
+                    thinking_blocks: None,
                 });
             }
             MessagesMessageContent::Blocks(blocks) => {
@@ -190,6 +221,7 @@ impl TryFrom<MessagesMessage> for Vec<Message> {
                         name: None,
                         tool_calls: None,
                         tool_call_id: Some(tool_use_id),
+                        thinking_blocks: None,
                     });
                 }
 
@@ -207,6 +239,7 @@ impl TryFrom<MessagesMessage> for Vec<Message> {
                             Some(tool_calls)
                         },
                         tool_call_id: None,
+                        thinking_blocks: None,
                     };
                     result.push(main_message);
                 }
@@ -264,15 +297,18 @@ impl From<MessagesSystemPrompt> for Message {
             name: None,
             tool_calls: None,
             tool_call_id: None,
+            thinking_blocks: None,
         }
     }
 }
 
 //Utility Functions
 /// Convert Anthropic tools to OpenAI format
+/// Server tools run at Anthropic and have no function to stand in for them.
 fn convert_anthropic_tools(tools: Vec<MessagesTool>) -> Vec<Tool> {
     tools
         .into_iter()
+        .filter(|tool| !tool.is_server_tool())
         .map(|tool| Tool {
             tool_type: "function".to_string(),
             function: Function {
@@ -354,10 +390,10 @@ impl TryFrom<MessagesMessage> for BedrockMessage {
             MessagesMessageContent::Blocks(blocks) => {
                 for block in blocks {
                     match block {
-                        crate::apis::anthropic::MessagesContentBlock::Text { text, .. } => {
-                            if !text.is_empty() {
-                                content_blocks.push(ContentBlock::Text { text });
-                            }
+                        crate::apis::anthropic::MessagesContentBlock::Text { text, .. }
+                            if !text.is_empty() =>
+                        {
+                            content_blocks.push(ContentBlock::Text { text });
                         }
                         crate::apis::anthropic::MessagesContentBlock::ToolUse {
                             id,
@@ -437,7 +473,27 @@ impl TryFrom<MessagesMessage> for BedrockMessage {
                                 }
                             }
                         }
-                        // Skip other content types for now (Thinking, Document, etc.)
+                        crate::apis::anthropic::MessagesContentBlock::Document {
+                            source, ..
+                        } => {
+                            let crate::apis::anthropic::MessagesDocumentSource::Base64 {
+                                media_type,
+                                data,
+                            } = source
+                            else {
+                                return Err(TransformError::UnsupportedContent(
+                                    "Bedrock only accepts documents as base64 data".to_string(),
+                                ));
+                            };
+                            check_document_size(&data)?;
+                            content_blocks.push(ContentBlock::Document {
+                                document: DocumentBlock {
+                                    source: DocumentSource::Base64 { media_type, data },
+                                    name: Some("document".to_string()),
+                                },
+                            });
+                        }
+                        // Skip other content types for now (Thinking, etc.)
                         _ => {}
                     }
                 }
@@ -466,10 +522,12 @@ mod tests {
         ToolChoice as BedrockToolChoice,
     };
     use crate::apis::anthropic::{
-        MessagesMessage, MessagesMessageContent, MessagesRequest, MessagesRole,
-        MessagesSystemPrompt, MessagesTool, MessagesToolChoice, MessagesToolChoiceType,
+        MessagesContentBlock, MessagesMessage, MessagesMessageContent, MessagesRequest,
+        MessagesRole, MessagesSystemPrompt, MessagesTool, MessagesToolChoice,
+        MessagesToolChoiceType,
     };
-    use serde_json::json;
+    use crate::transforms::MAX_DOCUMENT_BYTES;
+    use serde_json::{json, Value};
 
     #[test]
     fn test_anthropic_to_bedrock_basic_request() {
@@ -495,6 +553,7 @@ mod tests {
             stop_sequences: Some(vec!["STOP".to_string()]),
             tools: None,
             tool_choice: None,
+            platform: None,
         };
 
         let bedrock_request: ConverseRequest = anthropic_request.try_into().unwrap();
@@ -544,6 +603,7 @@ mod tests {
             stream: None,
             stop_sequences: None,
             tools: Some(vec![MessagesTool {
+                kind: None,
                 name: "get_weather".to_string(),
                 description: Some("Get current weather information".to_string()),
                 input_schema: json!({
@@ -556,12 +616,14 @@ mod tests {
                     },
                     "required": ["location"]
                 }),
+                options: Default::default(),
             }]),
             tool_choice: Some(MessagesToolChoice {
                 kind: MessagesToolChoiceType::Tool,
                 name: Some("get_weather".to_string()),
                 disable_parallel_tool_use: None,
             }),
+            platform: None,
         };
 
         let bedrock_request: ConverseRequest = anthropic_request.try_into().unwrap();
@@ -608,18 +670,21 @@ mod tests {
             stream: None,
             stop_sequences: None,
             tools: Some(vec![MessagesTool {
+                kind: None,
                 name: "help_tool".to_string(),
                 description: Some("A helpful tool".to_string()),
                 input_schema: json!({
                     "type": "object",
                     "properties": {}
                 }),
+                options: Default::default(),
             }]),
             tool_choice: Some(MessagesToolChoice {
                 kind: MessagesToolChoiceType::Auto,
                 name: None,
                 disable_parallel_tool_use: None,
             }),
+            platform: None,
         };
 
         let bedrock_request: ConverseRequest = anthropic_request.try_into().unwrap();
@@ -666,6 +731,7 @@ mod tests {
             stop_sequences: None,
             tools: None,
             tool_choice: None,
+            platform: None,
         };
 
         let bedrock_request: ConverseRequest = anthropic_request.try_into().unwrap();
@@ -704,4 +770,136 @@ mod tests {
             panic!("Expected text content block");
         }
     }
+
+    #[test]
+    fn test_tool_result_images_follow_the_tool_message() {
+        let message: MessagesMessage = serde_json::from_value(json!({
+            "role": "user",
+            "content": [{
+                "type": "tool_result",
+                "tool_use_id": "toolu_1",
+                "content": [
+                    {"type": "text", "text": "Screenshot taken"},
+                    {"type": "image", "source": {"type": "base64", "media_type": "image/png", "data": "iVBORw0"}}
+                ]
+            }]
+        }))
+        .unwrap();
+
+        let messages: Vec<Message> = message.try_into().unwrap();
+        assert_eq!(messages.len(), 2);
+
+        assert_eq!(messages[0].role, Role::Tool);
+        assert_eq!(messages[0].tool_call_id.as_deref(), Some("toolu_1"));
+        let Some(MessageContent::Text(text)) = &messages[0].content else {
+            panic!("Expected text tool message");
+        };
+        assert_eq!(
+            text,
+            "Screenshot taken\n[1 image(s) attached in the next message]"
+        );
+
+        assert_eq!(messages[1].role, Role::User);
+        let Some(MessageContent::Parts(parts)) = &messages[1].content else {
+            panic!("Expected content parts");
+        };
+        assert!(
+            matches!(&parts[0], ContentPart::Text { text } if text == "Images returned by tool call toolu_1:")
+        );
+        assert!(
+            matches!(&parts[1], ContentPart::ImageUrl { image_url } if image_url.url == "data:image/png;base64,iVBORw0")
+        );
+    }
+
+    #[test]
+    fn test_thinking_budget_maps_to_reasoning_effort() {
+        let req: MessagesRequest = serde_json::from_value(json!({
+            "model": "o4-mini",
+            "max_tokens": 16000,
+            "thinking": {"type": "enabled", "budget_tokens": 10000},
+            "messages": [{"role": "user", "content": "Prove it"}]
+        }))
+        .unwrap();
+        let chat_req = ChatCompletionsRequest::try_from(req).unwrap();
+        assert_eq!(chat_req.reasoning_effort.as_deref(), Some("high"));
+
+        let mut chat_req = chat_req;
+        chat_req.model = "gpt-4o".to_string();
+        chat_req.drop_reasoning_effort_unless_reasoning_model();
+        assert_eq!(chat_req.reasoning_effort, None);
+    }
+
+    #[test]
+    fn test_documents_round_trip_between_anthropic_and_openai() {
+        let req: MessagesRequest = serde_json::from_value(json!({
+            "model": "claude-sonnet-4-5",
+            "max_tokens": 1000,
+            "messages": [{"role": "user", "content": [
+                {"type": "document", "source": {"type": "base64", "media_type": "application/pdf", "data": "JVBERi0x"}},
+                {"type": "document", "source": {"type": "url", "url": "https://example.com/report.pdf"}},
+                {"type": "document", "source": {"type": "file", "file_id": "file_01"}},
+                {"type": "text", "text": "Compare these"}
+            ]}]
+        }))
+        .unwrap();
+
+        let chat_req = ChatCompletionsRequest::try_from(req).unwrap();
+        let Some(MessageContent::Parts(parts)) = &chat_req.messages[0].content else {
+            panic!("Expected content parts");
+        };
+        assert!(matches!(&parts[0], ContentPart::File { file }
+            if file.file_data.as_deref() == Some("data:application/pdf;base64,JVBERi0x")));
+        assert!(matches!(&parts[1], ContentPart::File { file }
+            if file.file_url.as_deref() == Some("https://example.com/report.pdf")));
+        assert!(matches!(&parts[2], ContentPart::File { file }
+            if file.file_id.as_deref() == Some("file_01")));
+
+        let back = MessagesRequest::try_from(chat_req).unwrap();
+        let MessagesMessageContent::Blocks(blocks) = &back.messages[0].content else {
+            panic!("Expected content blocks");
+        };
+        let sources: Vec<Value> = blocks
+            .iter()
+            .filter_map(|block| match block {
+                MessagesContentBlock::Document { source, .. } => {
+                    Some(serde_json::to_value(source).unwrap())
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            sources,
+            vec![
+                json!({"type": "base64", "media_type": "application/pdf", "data": "JVBERi0x"}),
+                json!({"type": "url", "url": "https://example.com/report.pdf"}),
+                json!({"type": "file", "file_id": "file_01"}),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_documents_rejected_where_unsupported() {
+        let url_document: MessagesRequest = serde_json::from_value(json!({
+            "model": "claude-sonnet-4-5",
+            "max_tokens": 1000,
+            "messages": [{"role": "user", "content": [
+                {"type": "document", "source": {"type": "url", "url": "https://example.com/report.pdf"}}
+            ]}]
+        }))
+        .unwrap();
+        assert!(matches!(
+            ConverseRequest::try_from(url_document),
+            Err(TransformError::UnsupportedContent(_))
+        ));
+
+        let oversized = "A".repeat(MAX_DOCUMENT_BYTES / 3 * 4 + 8);
+        let oversized = crate::apis::openai::FileContent {
+            file_data: Some(format!("data:application/pdf;base64,{}", oversized)),
+            ..Default::default()
+        };
+        assert!(matches!(
+            convert_file_to_document_source(&oversized),
+            Err(TransformError::UnsupportedContent(message)) if message.contains("exceeds")
+        ));
+    }
 }
@@ -1,17 +1,34 @@
 use crate::apis::amazon_bedrock::{
-    AnyChoice, AutoChoice, ContentBlock, ConversationRole, ConverseRequest, InferenceConfiguration,
-    Message as BedrockMessage, SystemContentBlock, Tool as BedrockTool,
-    ToolChoice as BedrockToolChoice, ToolChoiceSpec, ToolConfiguration, ToolInputSchema,
-    ToolSpecDefinition,
+    AnyChoice, AutoChoice, ContentBlock, ConversationRole, ConverseRequest, DocumentBlock,
+    DocumentSource, InferenceConfiguration, InvokeModelBody, InvokeModelFamily, InvokeModelRequest,
+    Message as BedrockMessage, SystemContentBlock, TitanTextGenerationConfig, TitanTextRequest,
+    Tool as BedrockTool, ToolChoice as BedrockToolChoice, ToolChoiceSpec, ToolConfiguration,
+    ToolInputSchema, ToolSpecDefinition,
 };
 use crate::apis::anthropic::{
-    MessagesContentBlock, MessagesMessage, MessagesMessageContent, MessagesRequest, MessagesRole,
-    MessagesSystemPrompt, MessagesTool, MessagesToolChoice, MessagesToolChoiceType,
-    ToolResultContent,
+    MessagesCacheControl, MessagesContentBlock, MessagesMessage, MessagesMessageContent,
+    MessagesRequest, MessagesRole, MessagesSystemPrompt, MessagesTool, MessagesToolChoice,
+    MessagesToolChoiceType, ThinkingConfig, ToolResultContent, CODE_EXECUTION_TOOL_TYPE,
+    WEB_SEARCH_TOOL_TYPE,
+};
+use crate::apis::cohere::{
+    CohereChatRequest, CohereContent, CohereContentItem, CohereFunction, CohereImageUrl,
+    CohereMessage, CohereResponseFormat, CohereRole, CohereTool, CohereToolCall,
+    CohereToolCallFunction, CohereToolChoice,
+};
+use crate::apis::gemini::{
+    Blob, Content as GeminiContent, ContentRole as GeminiRole, FileData,
+    FunctionCall as GeminiFunctionCall, FunctionCallingConfig, FunctionCallingMode,
+    FunctionDeclaration, FunctionResponse, GenerateContentRequest, GenerationConfig,
+    Part as GeminiPart, Tool as GeminiTool, ToolConfig as GeminiToolConfig,
+};
+use crate::apis::ollama::{
+    OllamaChatRequest, OllamaFunction, OllamaMessage, OllamaOptions, OllamaRole, OllamaTool,
+    OllamaToolCall, OllamaToolCallFunction,
 };
 use crate::apis::openai::{
-    ChatCompletionsRequest, FunctionCall as OpenAIFunctionCall, Message, MessageContent, Role,
-    Tool, ToolCall as OpenAIToolCall, ToolChoice, ToolChoiceType,
+    ChatCompletionsRequest, FileContent, FunctionCall as OpenAIFunctionCall, Message,
+    MessageContent, Role, Tool, ToolCall as OpenAIToolCall, ToolChoice, ToolChoiceType,
 };
 
 use crate::apis::openai_responses::{
@@ -52,6 +69,7 @@ impl TryFrom<ResponsesInputConverter> for Vec<Message> {
                         name: None,
                         tool_call_id: None,
                         tool_calls: None,
+                        thinking_blocks: None,
                     });
                 }
 
@@ -62,6 +80,7 @@ impl TryFrom<ResponsesInputConverter> for Vec<Message> {
                     name: None,
                     tool_call_id: None,
                     tool_calls: None,
+                    thinking_blocks: None,
                 });
 
                 Ok(messages)
@@ -86,6 +105,7 @@ impl TryFrom<ResponsesInputConverter> for Vec<Message> {
                         name: None,
                         tool_call_id: None,
                         tool_calls: None,
+                        thinking_blocks: None,
                     });
                 }
 
@@ -134,8 +154,22 @@ impl TryFrom<ResponsesInputConverter> for Vec<Message> {
                                                                 },
                                                             })
                                                         }
-                                                        InputContent::InputFile { .. } => None, // Skip files for now
-                                                        InputContent::InputAudio { .. } => None, // Skip audio for now
+                                                        InputContent::InputFile {
+                                                        file_url,
+                                                        file_data,
+                                                        file_id,
+                                                        filename,
+                                                    } => Some(crate::apis::openai::ContentPart::File {
+                                                        file: FileContent {
+                                                            file_data: file_data.clone(),
+                                                            file_id: file_id.clone(),
+                                                            filename: filename.clone(),
+                                                            file_url: file_url.clone(),
+                                                        },
+                                                    }),
+                                                        InputContent::InputAudio { data, format } => {
+                                                            input_audio_part(data, format)
+                                                        }
                                                     })
                                                     .collect(),
                                             )
@@ -159,8 +193,22 @@ impl TryFrom<ResponsesInputConverter> for Vec<Message> {
                                                             },
                                                         })
                                                     }
-                                                    InputContent::InputFile { .. } => None, // Skip files for now
-                                                    InputContent::InputAudio { .. } => None, // Skip audio for now
+                                                    InputContent::InputFile {
+                                                        file_url,
+                                                        file_data,
+                                                        file_id,
+                                                        filename,
+                                                    } => Some(crate::apis::openai::ContentPart::File {
+                                                        file: FileContent {
+                                                            file_data: file_data.clone(),
+                                                            file_id: file_id.clone(),
+                                                            filename: filename.clone(),
+                                                            file_url: file_url.clone(),
+                                                        },
+                                                    }),
+                                                    InputContent::InputAudio { data, format } => {
+                                                        input_audio_part(data, format)
+                                                    }
                                                 })
                                                 .collect(),
                                         )
@@ -174,6 +222,7 @@ impl TryFrom<ResponsesInputConverter> for Vec<Message> {
                                 name: None,
                                 tool_call_id: None,
                                 tool_calls: None,
+                                thinking_blocks: None,
                             });
                         }
                         InputItem::FunctionCallOutput {
@@ -192,6 +241,7 @@ impl TryFrom<ResponsesInputConverter> for Vec<Message> {
                                 name: None,
                                 tool_call_id: Some(call_id),
                                 tool_calls: None,
+                                thinking_blocks: None,
                             });
                         }
                         InputItem::FunctionCall {
@@ -224,6 +274,7 @@ impl TryFrom<ResponsesInputConverter> for Vec<Message> {
                                 name: None,
                                 tool_call_id: None,
                                 tool_calls: Some(vec![tool_call]),
+                                thinking_blocks: None,
                             });
                         }
                         InputItem::ItemReference { .. } => {
@@ -274,6 +325,7 @@ impl TryFrom<Message> for MessagesMessage {
                             is_error: None,
                             content: ToolResultContent::Blocks(vec![MessagesContentBlock::Text {
                                 text: content_text,
+                                citations: None,
                                 cache_control: None,
                             }]),
                             cache_control: None,
@@ -317,10 +369,8 @@ impl TryFrom<Message> for BedrockMessage {
             Role::User => {
                 // Convert user message content to content blocks
                 match message.content {
-                    Some(MessageContent::Text(text)) => {
-                        if !text.is_empty() {
-                            content_blocks.push(ContentBlock::Text { text });
-                        }
+                    Some(MessageContent::Text(text)) if !text.is_empty() => {
+                        content_blocks.push(ContentBlock::Text { text });
                     }
                     Some(MessageContent::Parts(parts)) => {
                         // Convert OpenAI content parts to Bedrock ContentBlocks
@@ -359,10 +409,33 @@ impl TryFrom<Message> for BedrockMessage {
                                         ));
                                     }
                                 }
+                                crate::apis::openai::ContentPart::File { file } => {
+                                    let (media_type, data) =
+                                        inline_document(&file)?.ok_or_else(|| {
+                                            TransformError::UnsupportedContent(
+                                                "Bedrock only accepts documents as base64 file_data"
+                                                    .to_string(),
+                                            )
+                                        })?;
+                                    content_blocks.push(ContentBlock::Document {
+                                        document: DocumentBlock {
+                                            source: DocumentSource::Base64 { media_type, data },
+                                            name: Some(
+                                                file.filename
+                                                    .unwrap_or_else(|| "document".to_string()),
+                                            ),
+                                        },
+                                    });
+                                }
+                                crate::apis::openai::ContentPart::InputAudio { .. } => {
+                                    return Err(TransformError::UnsupportedContent(
+                                        "Bedrock Converse does not support audio input".to_string(),
+                                    ));
+                                }
                             }
                         }
                     }
-                    None => {
+                    Some(MessageContent::Text(_)) | None => {
                         // Empty content for user - shouldn't happen but handle gracefully
                     }
                 }
@@ -711,37 +784,74 @@ impl TryFrom<ChatCompletionsRequest> for AnthropicMessagesRequest {
                 }
                 _ => {
                     let anthropic_message: MessagesMessage = message.try_into()?;
-                    messages.push(anthropic_message);
+                    push_anthropic_message(&mut messages, anthropic_message);
                 }
             }
         }
 
-        // Convert tools and tool choice
+        // Convert tools and tool choice. Anthropic only takes the parallel
+        // tool use switch on a tool choice, so turning it off implies auto.
+        let tool_choice = req.tool_choice.or_else(|| {
+            (req.parallel_tool_calls == Some(false) && req.tools.is_some())
+                .then_some(ToolChoice::Type(ToolChoiceType::Auto))
+        });
         let anthropic_tools = req.tools.map(convert_openai_tools);
         let anthropic_tool_choice =
-            convert_openai_tool_choice(req.tool_choice, req.parallel_tool_calls);
+            convert_openai_tool_choice(tool_choice, req.parallel_tool_calls);
+
+        // Anthropic counts thinking against max_tokens, as OpenAI counts
+        // reasoning against max_completion_tokens. Without a limit from the
+        // client the answer keeps the default room on top of the budget.
+        let requested_max_tokens = req.max_completion_tokens.or(req.max_tokens);
+        let budget = req
+            .reasoning_effort
+            .as_deref()
+            .and_then(reasoning_effort_budget)
+            .filter(|budget| *budget > 0);
+        let (max_tokens, budget) = match (budget, requested_max_tokens) {
+            (Some(budget), None) => (budget + DEFAULT_MAX_TOKENS, Some(budget)),
+            (Some(budget), Some(max_tokens)) => {
+                (max_tokens, Some(budget.min(max_tokens.saturating_sub(1))))
+            }
+            (None, max_tokens) => (max_tokens.unwrap_or(DEFAULT_MAX_TOKENS), None),
+        };
+        // The low budget is also Anthropic's minimum
+        let thinking = budget
+            .filter(|budget| *budget >= LOW_REASONING_BUDGET)
+            .map(|budget| ThinkingConfig {
+                thinking_type: "enabled".to_string(),
+                budget_tokens: Some(budget),
+            });
+        // Thinking only runs at the default temperature and a wide top_p
+        let (temperature, top_p) = if thinking.is_some() {
+            (None, req.top_p.filter(|top_p| *top_p >= 0.95))
+        } else {
+            (req.temperature, req.top_p)
+        };
 
-        Ok(AnthropicMessagesRequest {
+        let mut anthropic_req = AnthropicMessagesRequest {
             model: req.model,
             system: system_prompt,
             messages,
-            max_tokens: req
-                .max_completion_tokens
-                .or(req.max_tokens)
-                .unwrap_or(DEFAULT_MAX_TOKENS),
+            max_tokens,
             container: None,
             mcp_servers: None,
             service_tier: None,
-            thinking: None,
-            temperature: req.temperature,
-            top_p: req.top_p,
+            thinking,
+            temperature,
+            top_p,
             top_k: None, // OpenAI doesn't have top_k
             stream: req.stream,
             stop_sequences: req.stop,
             tools: anthropic_tools,
             tool_choice: anthropic_tool_choice,
             metadata: None,
-        })
+            platform: None,
+        };
+        if let Some(cache_control) = req.cache_control {
+            add_cache_breakpoints(&mut anthropic_req, cache_control);
+        }
+        Ok(anthropic_req)
     }
 }
 
@@ -761,7 +871,7 @@ impl TryFrom<ChatCompletionsRequest> for ConverseRequest {
                 }
                 _ => {
                     let bedrock_message: BedrockMessage = message.try_into()?;
-                    conversation_messages.push(bedrock_message);
+                    push_bedrock_message(&mut conversation_messages, bedrock_message);
                 }
             }
         }
@@ -870,18 +980,757 @@ impl TryFrom<ChatCompletionsRequest> for ConverseRequest {
     }
 }
 
+impl TryFrom<ChatCompletionsRequest> for GenerateContentRequest {
+    type Error = TransformError;
+
+    fn try_from(req: ChatCompletionsRequest) -> Result<Self, Self::Error> {
+        let mut system_parts = Vec::new();
+        let mut contents: Vec<GeminiContent> = Vec::new();
+        // Gemini answers a function call by name, OpenAI by call id
+        let mut tool_names: std::collections::HashMap<String, String> =
+            std::collections::HashMap::new();
+
+        for message in req.messages {
+            match message.role {
+                Role::System | Role::Developer => {
+                    system_parts.push(GeminiPart::text(message.content.extract_text()));
+                }
+                Role::User => contents.push(GeminiContent {
+                    role: Some(GeminiRole::User),
+                    parts: convert_openai_content_to_gemini_parts(message.content)?,
+                }),
+                Role::Assistant => {
+                    let mut parts = Vec::new();
+                    let text = message.content.extract_text();
+                    if !text.is_empty() {
+                        parts.push(GeminiPart::text(text));
+                    }
+                    for tool_call in message.tool_calls.unwrap_or_default() {
+                        let args: serde_json::Value =
+                            serde_json::from_str(&tool_call.function.arguments).map_err(|e| {
+                                TransformError::UnsupportedConversion(format!(
+                                    "Failed to parse tool arguments as JSON: {}. Arguments: {}",
+                                    e, tool_call.function.arguments
+                                ))
+                            })?;
+                        tool_names.insert(tool_call.id, tool_call.function.name.clone());
+                        parts.push(GeminiPart {
+                            function_call: Some(GeminiFunctionCall {
+                                id: None,
+                                name: tool_call.function.name,
+                                args,
+                            }),
+                            ..Default::default()
+                        });
+                    }
+                    if parts.is_empty() {
+                        parts.push(GeminiPart::text(" "));
+                    }
+                    contents.push(GeminiContent {
+                        role: Some(GeminiRole::Model),
+                        parts,
+                    });
+                }
+                Role::Tool => {
+                    let tool_call_id = message.tool_call_id.ok_or_else(|| {
+                        TransformError::MissingField(
+                            "tool_call_id required for Tool messages".to_string(),
+                        )
+                    })?;
+                    let name = tool_names.get(&tool_call_id).cloned().ok_or_else(|| {
+                        TransformError::UnsupportedConversion(format!(
+                            "Tool result {} does not answer any earlier tool call",
+                            tool_call_id
+                        ))
+                    })?;
+                    let output = message.content.extract_text();
+                    // The response must be an object; wrap anything else
+                    let response = match serde_json::from_str::<serde_json::Value>(&output) {
+                        Ok(value @ serde_json::Value::Object(_)) => value,
+                        _ => serde_json::json!({ "result": output }),
+                    };
+                    let part = GeminiPart {
+                        function_response: Some(FunctionResponse {
+                            id: None,
+                            name,
+                            response,
+                        }),
+                        ..Default::default()
+                    };
+                    // Results for one turn's parallel calls go back in a single content
+                    match contents.last_mut() {
+                        Some(last)
+                            if last.role == Some(GeminiRole::User)
+                                && last.parts.iter().all(|p| p.function_response.is_some()) =>
+                        {
+                            last.parts.push(part)
+                        }
+                        _ => contents.push(GeminiContent {
+                            role: Some(GeminiRole::User),
+                            parts: vec![part],
+                        }),
+                    }
+                }
+            }
+        }
+
+        let system_instruction = if system_parts.is_empty() {
+            None
+        } else {
+            Some(GeminiContent {
+                role: None,
+                parts: system_parts,
+            })
+        };
+
+        let (response_mime_type, response_json_schema) =
+            convert_response_format_to_gemini(req.response_format.as_ref());
+        let generation_config = GenerationConfig {
+            stop_sequences: req.stop,
+            response_mime_type,
+            response_json_schema,
+            candidate_count: req.n,
+            max_output_tokens: req.max_completion_tokens.or(req.max_tokens),
+            temperature: req.temperature,
+            top_p: req.top_p,
+            top_k: req.top_k,
+            seed: req.seed,
+            presence_penalty: req.presence_penalty,
+            frequency_penalty: req.frequency_penalty,
+            response_logprobs: req.logprobs,
+            // Gemini only accepts `logprobs` alongside `responseLogprobs`
+            logprobs: req.top_logprobs.filter(|_| req.logprobs == Some(true)),
+            thinking_config: req
+                .reasoning_effort
+                .as_deref()
+                .and_then(reasoning_effort_budget)
+                .map(|budget| {
+                    serde_json::json!({
+                        "thinkingBudget": budget,
+                        "includeThoughts": budget > 0,
+                    })
+                }),
+        };
+        let generation_config =
+            (generation_config != GenerationConfig::default()).then_some(generation_config);
+
+        let tools = req.tools.map(|openai_tools| {
+            vec![GeminiTool {
+                function_declarations: Some(
+                    openai_tools
+                        .into_iter()
+                        .map(|tool| FunctionDeclaration {
+                            name: tool.function.name,
+                            description: tool.function.description,
+                            parameters_json_schema: Some(tool.function.parameters),
+                        })
+                        .collect(),
+                ),
+                ..Default::default()
+            }]
+        });
+
+        let tool_config = req.tool_choice.map(|choice| {
+            let (mode, allowed_function_names) = match choice {
+                ToolChoice::Type(ToolChoiceType::Auto) => (FunctionCallingMode::Auto, None),
+                ToolChoice::Type(ToolChoiceType::Required) => (FunctionCallingMode::Any, None),
+                ToolChoice::Type(ToolChoiceType::None) => (FunctionCallingMode::None, None),
+                ToolChoice::Function { function, .. } => {
+                    (FunctionCallingMode::Any, Some(vec![function.name]))
+                }
+            };
+            GeminiToolConfig {
+                function_calling_config: Some(FunctionCallingConfig {
+                    mode,
+                    allowed_function_names,
+                }),
+            }
+        });
+
+        Ok(GenerateContentRequest {
+            model: req.model,
+            contents,
+            system_instruction,
+            generation_config,
+            tools,
+            tool_config,
+            safety_settings: None,
+            cached_content: None,
+            metadata: req.metadata,
+            stream: req.stream.unwrap_or(false),
+        })
+    }
+}
+
+/// Convert OpenAI user content to Gemini parts
+fn convert_openai_content_to_gemini_parts(
+    content: Option<MessageContent>,
+) -> Result<Vec<GeminiPart>, TransformError> {
+    let mut parts = Vec::new();
+    match content {
+        Some(MessageContent::Text(text)) => parts.push(GeminiPart::text(text)),
+        Some(MessageContent::Parts(content_parts)) => {
+            for part in content_parts {
+                match part {
+                    crate::apis::openai::ContentPart::Text { text } => {
+                        parts.push(GeminiPart::text(text))
+                    }
+                    crate::apis::openai::ContentPart::ImageUrl { image_url } => {
+                        let (mime_type, data) =
+                            parse_data_url(&image_url.url).ok_or_else(|| {
+                                TransformError::UnsupportedConversion(
+                                    "Only base64 data URLs are supported for images in Gemini"
+                                        .to_string(),
+                                )
+                            })?;
+                        parts.push(GeminiPart {
+                            inline_data: Some(Blob { mime_type, data }),
+                            ..Default::default()
+                        });
+                    }
+                    crate::apis::openai::ContentPart::File { file } => {
+                        if let Some((mime_type, data)) = inline_document(&file)? {
+                            parts.push(GeminiPart {
+                                inline_data: Some(Blob { mime_type, data }),
+                                ..Default::default()
+                            });
+                        } else if let Some(file_uri) = file.file_url {
+                            parts.push(GeminiPart {
+                                file_data: Some(FileData {
+                                    mime_type: None,
+                                    file_uri,
+                                }),
+                                ..Default::default()
+                            });
+                        } else {
+                            return Err(TransformError::UnsupportedContent(
+                                "Gemini takes documents as base64 file_data or a file URL, not a file id"
+                                    .to_string(),
+                            ));
+                        }
+                    }
+                    crate::apis::openai::ContentPart::InputAudio { input_audio } => {
+                        parts.push(GeminiPart {
+                            inline_data: Some(Blob {
+                                mime_type: format!("audio/{}", input_audio.format),
+                                data: input_audio.data,
+                            }),
+                            ..Default::default()
+                        });
+                    }
+                }
+            }
+        }
+        None => {}
+    }
+    // Gemini rejects a turn without parts
+    if parts.is_empty() {
+        parts.push(GeminiPart::text(" "));
+    }
+    Ok(parts)
+}
+
+/// Map an OpenAI `response_format` to Gemini's response MIME type and schema
+fn convert_response_format_to_gemini(
+    response_format: Option<&serde_json::Value>,
+) -> (Option<String>, Option<serde_json::Value>) {
+    let Some(format) = response_format else {
+        return (None, None);
+    };
+    match format.get("type").and_then(|t| t.as_str()) {
+        Some("json_object") => (Some("application/json".to_string()), None),
+        Some("json_schema") => (
+            Some("application/json".to_string()),
+            format
+                .get("json_schema")
+                .and_then(|schema| schema.get("schema"))
+                .cloned(),
+        ),
+        _ => (None, None),
+    }
+}
+
+impl TryFrom<ChatCompletionsRequest> for CohereChatRequest {
+    type Error = TransformError;
+
+    fn try_from(req: ChatCompletionsRequest) -> Result<Self, Self::Error> {
+        let messages = req
+            .messages
+            .into_iter()
+            .map(|message| {
+                Ok(match message.role {
+                    Role::System | Role::Developer => {
+                        CohereMessage::text(CohereRole::System, message.content.extract_text())
+                    }
+                    Role::User => CohereMessage {
+                        content: Some(convert_openai_content_to_cohere(message.content)?),
+                        ..CohereMessage::text(CohereRole::User, "")
+                    },
+                    Role::Assistant => {
+                        let text = message.content.extract_text();
+                        let tool_calls = message.tool_calls.map(|calls| {
+                            calls
+                                .into_iter()
+                                .map(|call| CohereToolCall {
+                                    id: Some(call.id),
+                                    call_type: Some("function".to_string()),
+                                    function: Some(CohereToolCallFunction {
+                                        name: Some(call.function.name),
+                                        arguments: Some(call.function.arguments),
+                                    }),
+                                })
+                                .collect()
+                        });
+                        // Text next to tool calls is the model's plan for them
+                        let (content, tool_plan) = match (&tool_calls, text.is_empty()) {
+                            (None, _) => (Some(CohereContent::Text(text)), None),
+                            (Some(_), true) => (None, None),
+                            (Some(_), false) => (None, Some(text)),
+                        };
+                        CohereMessage {
+                            role: CohereRole::Assistant,
+                            content,
+                            tool_calls,
+                            tool_plan,
+                            tool_call_id: None,
+                        }
+                    }
+                    Role::Tool => CohereMessage {
+                        tool_call_id: message.tool_call_id,
+                        ..CohereMessage::text(CohereRole::Tool, message.content.extract_text())
+                    },
+                })
+            })
+            .collect::<Result<_, TransformError>>()?;
+
+        // Cohere cannot force one named tool; offer only that tool and require a call
+        let mut tools = req.tools;
+        let tool_choice = req.tool_choice.and_then(|choice| match choice {
+            ToolChoice::Type(ToolChoiceType::Auto) => None,
+            ToolChoice::Type(ToolChoiceType::Required) => Some(CohereToolChoice::Required),
+            ToolChoice::Type(ToolChoiceType::None) => Some(CohereToolChoice::None),
+            ToolChoice::Function { function, .. } => {
+                if let Some(tools) = tools.as_mut() {
+                    tools.retain(|tool| tool.function.name == function.name);
+                }
+                Some(CohereToolChoice::Required)
+            }
+        });
+        let tools = tools.map(|openai_tools| {
+            openai_tools
+                .into_iter()
+                .map(|tool| CohereTool {
+                    tool_type: "function".to_string(),
+                    function: CohereFunction {
+                        name: tool.function.name,
+                        description: tool.function.description,
+                        parameters: tool.function.parameters,
+                    },
+                })
+                .collect()
+        });
+
+        Ok(CohereChatRequest {
+            model: req.model,
+            messages,
+            tools,
+            tool_choice,
+            strict_tools: None,
+            response_format: convert_response_format_to_cohere(req.response_format.as_ref()),
+            max_tokens: req.max_completion_tokens.or(req.max_tokens),
+            stop_sequences: req.stop,
+            temperature: req.temperature,
+            seed: req.seed,
+            frequency_penalty: req.frequency_penalty,
+            presence_penalty: req.presence_penalty,
+            k: req.top_k,
+            p: req.top_p,
+            logprobs: req.logprobs,
+            stream: req.stream,
+            metadata: req.metadata,
+        })
+    }
+}
+
+/// Convert OpenAI user content to Cohere content, keeping images as URLs
+fn convert_openai_content_to_cohere(
+    content: Option<MessageContent>,
+) -> Result<CohereContent, TransformError> {
+    Ok(match content {
+        Some(MessageContent::Parts(parts)) => CohereContent::Items(
+            parts
+                .into_iter()
+                .map(|part| match part {
+                    crate::apis::openai::ContentPart::Text { text } => {
+                        Ok(CohereContentItem::Text { text })
+                    }
+                    crate::apis::openai::ContentPart::ImageUrl { image_url } => {
+                        Ok(CohereContentItem::ImageUrl {
+                            image_url: CohereImageUrl {
+                                url: image_url.url,
+                                detail: image_url.detail,
+                            },
+                        })
+                    }
+                    crate::apis::openai::ContentPart::File { .. } => {
+                        Err(TransformError::UnsupportedContent(
+                            "Cohere chat does not support document inputs".to_string(),
+                        ))
+                    }
+                    crate::apis::openai::ContentPart::InputAudio { .. } => {
+                        Err(TransformError::UnsupportedContent(
+                            "Cohere chat does not support audio input".to_string(),
+                        ))
+                    }
+                })
+                .collect::<Result<_, _>>()?,
+        ),
+        Some(MessageContent::Text(text)) => CohereContent::Text(text),
+        None => CohereContent::Text(String::new()),
+    })
+}
+
+/// Map an OpenAI `response_format` to Cohere's, which folds the schema into `json_object`
+fn convert_response_format_to_cohere(
+    response_format: Option<&serde_json::Value>,
+) -> Option<CohereResponseFormat> {
+    let format = response_format?;
+    match format.get("type").and_then(|t| t.as_str()) {
+        Some("json_object") => Some(CohereResponseFormat {
+            format_type: "json_object".to_string(),
+            json_schema: None,
+        }),
+        Some("json_schema") => Some(CohereResponseFormat {
+            format_type: "json_object".to_string(),
+            json_schema: format
+                .get("json_schema")
+                .and_then(|schema| schema.get("schema"))
+                .cloned(),
+        }),
+        _ => None,
+    }
+}
+
+impl TryFrom<ChatCompletionsRequest> for OllamaChatRequest {
+    type Error = TransformError;
+
+    fn try_from(req: ChatCompletionsRequest) -> Result<Self, Self::Error> {
+        // Ollama tool results carry the function name instead of a call id
+        let mut tool_names: std::collections::HashMap<String, String> =
+            std::collections::HashMap::new();
+        let mut messages = Vec::with_capacity(req.messages.len());
+        for message in req.messages {
+            let ollama_message = match message.role {
+                Role::System | Role::Developer => {
+                    OllamaMessage::text(OllamaRole::System, message.content.extract_text())
+                }
+                Role::User => {
+                    let (content, images) = convert_openai_content_to_ollama(message.content)?;
+                    OllamaMessage {
+                        images,
+                        ..OllamaMessage::text(OllamaRole::User, content)
+                    }
+                }
+                Role::Assistant => {
+                    let tool_calls = message.tool_calls.map(|calls| {
+                        calls
+                            .into_iter()
+                            .map(|call| {
+                                tool_names.insert(call.id, call.function.name.clone());
+                                OllamaToolCall {
+                                    function: OllamaToolCallFunction {
+                                        index: None,
+                                        name: call.function.name,
+                                        arguments: serde_json::from_str(&call.function.arguments)
+                                            .unwrap_or_else(|_| serde_json::json!({})),
+                                    },
+                                }
+                            })
+                            .collect()
+                    });
+                    OllamaMessage {
+                        tool_calls,
+                        ..OllamaMessage::text(OllamaRole::Assistant, message.content.extract_text())
+                    }
+                }
+                Role::Tool => OllamaMessage {
+                    tool_name: message
+                        .tool_call_id
+                        .as_ref()
+                        .and_then(|id| tool_names.get(id))
+                        .cloned(),
+                    ..OllamaMessage::text(OllamaRole::Tool, message.content.extract_text())
+                },
+            };
+            messages.push(ollama_message);
+        }
+
+        // Ollama has no tool_choice; narrow or drop the tools instead
+        let mut tools = req.tools;
+        match req.tool_choice {
+            Some(ToolChoice::Type(ToolChoiceType::None)) => tools = None,
+            Some(ToolChoice::Function { function, .. }) => {
+                if let Some(tools) = tools.as_mut() {
+                    tools.retain(|tool| tool.function.name == function.name);
+                }
+            }
+            _ => {}
+        }
+        let tools = tools.map(|openai_tools| {
+            openai_tools
+                .into_iter()
+                .map(|tool| OllamaTool {
+                    tool_type: "function".to_string(),
+                    function: OllamaFunction {
+                        name: tool.function.name,
+                        description: tool.function.description,
+                        parameters: tool.function.parameters,
+                    },
+                })
+                .collect()
+        });
+
+        let options = OllamaOptions {
+            temperature: req.temperature,
+            top_p: req.top_p,
+            top_k: req.top_k,
+            seed: req.seed,
+            num_predict: req
+                .max_completion_tokens
+                .or(req.max_tokens)
+                .map(|tokens| i32::try_from(tokens).unwrap_or(i32::MAX)),
+            stop: req.stop,
+            presence_penalty: req.presence_penalty,
+            frequency_penalty: req.frequency_penalty,
+            num_ctx: None,
+        };
+
+        Ok(OllamaChatRequest {
+            model: req.model,
+            messages,
+            tools,
+            format: convert_response_format_to_ollama(req.response_format.as_ref()),
+            options: (options != OllamaOptions::default()).then_some(options),
+            stream: req.stream.unwrap_or(false),
+            keep_alive: None,
+            think: req
+                .reasoning_effort
+                .as_deref()
+                .map(|effort| effort != "none"),
+            metadata: req.metadata,
+        })
+    }
+}
+
+/// Split OpenAI user content into Ollama's text and base64 images
+fn convert_openai_content_to_ollama(
+    content: Option<MessageContent>,
+) -> Result<(String, Option<Vec<String>>), TransformError> {
+    let parts = match content {
+        Some(MessageContent::Parts(parts)) => parts,
+        content => return Ok((content.extract_text(), None)),
+    };
+    let mut texts = Vec::new();
+    let mut images = Vec::new();
+    for part in parts {
+        match part {
+            crate::apis::openai::ContentPart::Text { text } => texts.push(text),
+            crate::apis::openai::ContentPart::ImageUrl { image_url } => {
+                let (_, data) = parse_data_url(&image_url.url).ok_or_else(|| {
+                    TransformError::UnsupportedConversion(
+                        "Only base64 data URLs are supported for images in Ollama".to_string(),
+                    )
+                })?;
+                images.push(data);
+            }
+            crate::apis::openai::ContentPart::File { .. } => {
+                return Err(TransformError::UnsupportedContent(
+                    "Ollama does not support document inputs".to_string(),
+                ));
+            }
+            crate::apis::openai::ContentPart::InputAudio { .. } => {
+                return Err(TransformError::UnsupportedContent(
+                    "Ollama does not support audio input".to_string(),
+                ));
+            }
+        }
+    }
+    Ok((texts.join("\n"), (!images.is_empty()).then_some(images)))
+}
+
+/// Map an OpenAI `response_format` to Ollama's `format`: `"json"` or a JSON schema
+fn convert_response_format_to_ollama(
+    response_format: Option<&serde_json::Value>,
+) -> Option<serde_json::Value> {
+    let format = response_format?;
+    match format.get("type").and_then(|t| t.as_str()) {
+        Some("json_object") => Some(serde_json::Value::String("json".to_string())),
+        Some("json_schema") => format
+            .get("json_schema")
+            .and_then(|schema| schema.get("schema"))
+            .cloned(),
+        _ => None,
+    }
+}
+
+impl TryFrom<ChatCompletionsRequest> for InvokeModelRequest {
+    type Error = TransformError;
+
+    /// Build the model-specific InvokeModel body: Claude models take the
+    /// Messages shape, Titan Text models a flattened prompt
+    fn try_from(req: ChatCompletionsRequest) -> Result<Self, Self::Error> {
+        let model_id = req.model.clone();
+        let stream = req.stream.unwrap_or(false);
+        let body = match InvokeModelFamily::from_model_id(&model_id) {
+            Some(InvokeModelFamily::Anthropic) => {
+                InvokeModelBody::Anthropic(AnthropicMessagesRequest::try_from(req)?)
+            }
+            Some(InvokeModelFamily::TitanText) => {
+                if req.tools.as_ref().is_some_and(|tools| !tools.is_empty()) {
+                    return Err(TransformError::UnsupportedConversion(
+                        "Titan Text models do not support tools".to_string(),
+                    ));
+                }
+                InvokeModelBody::TitanText(TitanTextRequest {
+                    input_text: TitanTextRequest::prompt_from_messages(&req.messages),
+                    text_generation_config: Some(TitanTextGenerationConfig {
+                        max_token_count: req.max_completion_tokens.or(req.max_tokens),
+                        temperature: req.temperature,
+                        top_p: req.top_p,
+                        stop_sequences: req.stop,
+                    }),
+                })
+            }
+            None => {
+                return Err(TransformError::UnsupportedConversion(format!(
+                    "model '{}' has no known InvokeModel body format; use the Converse API",
+                    model_id
+                )))
+            }
+        };
+
+        Ok(InvokeModelRequest {
+            model_id,
+            stream,
+            body,
+        })
+    }
+}
+
+/// Take the Responses API built-in tools Anthropic runs itself out of
+/// `tools`, returning the server tools that stand in for them: `web_search`
+/// becomes the `web_search` server tool and `code_interpreter` becomes
+/// `code_execution`. The rest are left for the ChatCompletions conversion.
+pub fn take_anthropic_server_tools(tools: &mut Option<Vec<ResponsesTool>>) -> Vec<MessagesTool> {
+    let Some(declared) = tools.take() else {
+        return Vec::new();
+    };
+    let mut server_tools = Vec::new();
+    let mut remaining = Vec::new();
+    for tool in declared {
+        match tool {
+            ResponsesTool::WebSearchPreview {
+                domains,
+                user_location,
+                ..
+            } => {
+                let mut options = serde_json::Map::new();
+                if let Some(domains) = domains.filter(|d| !d.is_empty()) {
+                    options.insert("allowed_domains".to_string(), serde_json::json!(domains));
+                }
+                if let Some(location) = user_location {
+                    let mut approximate = serde_json::Map::new();
+                    approximate.insert("type".to_string(), "approximate".into());
+                    for (key, value) in [
+                        ("city", location.city),
+                        ("region", location.region),
+                        ("country", location.country),
+                        ("timezone", location.timezone),
+                    ] {
+                        if let Some(value) = value {
+                            approximate.insert(key.to_string(), value.into());
+                        }
+                    }
+                    options.insert("user_location".to_string(), approximate.into());
+                }
+                server_tools.push(MessagesTool {
+                    kind: Some(WEB_SEARCH_TOOL_TYPE.to_string()),
+                    name: "web_search".to_string(),
+                    description: None,
+                    input_schema: serde_json::Value::Null,
+                    options,
+                });
+            }
+            ResponsesTool::CodeInterpreter => server_tools.push(MessagesTool {
+                kind: Some(CODE_EXECUTION_TOOL_TYPE.to_string()),
+                name: "code_execution".to_string(),
+                description: None,
+                input_schema: serde_json::Value::Null,
+                options: serde_json::Map::new(),
+            }),
+            other => remaining.push(other),
+        }
+    }
+    if !remaining.is_empty() {
+        *tools = Some(remaining);
+    }
+    server_tools
+}
+
 /// Convert OpenAI tools to Anthropic format
 fn convert_openai_tools(tools: Vec<Tool>) -> Vec<MessagesTool> {
     tools
         .into_iter()
         .map(|tool| MessagesTool {
+            kind: None,
             name: tool.function.name,
             description: tool.function.description,
             input_schema: tool.function.parameters,
+            options: Default::default(),
         })
         .collect()
 }
 
+/// Append `message`, folding tool results into the previous message when it
+/// holds only tool results too: the results for one turn's parallel calls
+/// go back in a single user message.
+fn push_anthropic_message(messages: &mut Vec<MessagesMessage>, message: MessagesMessage) {
+    let is_tool_results = |message: &MessagesMessage| {
+        message.role == MessagesRole::User
+            && matches!(&message.content, MessagesMessageContent::Blocks(blocks)
+                if blocks.iter().all(|b| matches!(b, MessagesContentBlock::ToolResult { .. })))
+    };
+    match messages.last_mut() {
+        Some(last) if is_tool_results(last) && is_tool_results(&message) => {
+            if let (
+                MessagesMessageContent::Blocks(blocks),
+                MessagesMessageContent::Blocks(results),
+            ) = (&mut last.content, message.content)
+            {
+                blocks.extend(results);
+            }
+        }
+        _ => messages.push(message),
+    }
+}
+
+/// Bedrock counterpart of [`push_anthropic_message`]
+fn push_bedrock_message(messages: &mut Vec<BedrockMessage>, message: BedrockMessage) {
+    let is_tool_results = |message: &BedrockMessage| {
+        message.role == ConversationRole::User
+            && message
+                .content
+                .iter()
+                .all(|b| matches!(b, ContentBlock::ToolResult { .. }))
+    };
+    match messages.last_mut() {
+        Some(last) if is_tool_results(last) && is_tool_results(&message) => {
+            last.content.extend(message.content)
+        }
+        _ => messages.push(message),
+    }
+}
+
 /// Convert OpenAI tool choice to Anthropic format
 fn convert_openai_tool_choice(
     tool_choice: Option<ToolChoice>,
@@ -913,6 +1762,58 @@ fn convert_openai_tool_choice(
     })
 }
 
+/// Mark the end of the system prompt, the tool definitions and the last
+/// message as prompt cache breakpoints
+fn add_cache_breakpoints(req: &mut AnthropicMessagesRequest, cache_control: MessagesCacheControl) {
+    if let Some(MessagesSystemPrompt::Single(text)) = &req.system {
+        req.system = Some(MessagesSystemPrompt::Blocks(vec![
+            MessagesContentBlock::Text {
+                text: text.clone(),
+                citations: None,
+                cache_control: None,
+            },
+        ]));
+    }
+    if let Some(MessagesSystemPrompt::Blocks(blocks)) = &mut req.system {
+        mark_last_block(blocks, &cache_control);
+    }
+    if let Some(tool) = req.tools.as_mut().and_then(|tools| tools.last_mut()) {
+        if let Ok(value) = serde_json::to_value(&cache_control) {
+            tool.options.insert("cache_control".to_string(), value);
+        }
+    }
+    if let Some(message) = req.messages.last_mut() {
+        if let MessagesMessageContent::Single(text) = &message.content {
+            message.content = MessagesMessageContent::Blocks(vec![MessagesContentBlock::Text {
+                text: text.clone(),
+                citations: None,
+                cache_control: None,
+            }]);
+        }
+        if let MessagesMessageContent::Blocks(blocks) = &mut message.content {
+            mark_last_block(blocks, &cache_control);
+        }
+    }
+}
+
+/// Set `cache_control` on the last block that takes one. Empty text blocks
+/// cannot be cached.
+fn mark_last_block(blocks: &mut [MessagesContentBlock], cache_control: &MessagesCacheControl) {
+    let marker = blocks.iter_mut().rev().find_map(|block| match block {
+        MessagesContentBlock::Text {
+            text,
+            cache_control,
+            ..
+        } if !text.is_empty() => Some(cache_control),
+        MessagesContentBlock::ToolUse { cache_control, .. }
+        | MessagesContentBlock::ToolResult { cache_control, .. } => Some(cache_control),
+        _ => None,
+    });
+    if let Some(marker) = marker {
+        *marker = Some(cache_control.clone());
+    }
+}
+
 /// Build Anthropic message content from content blocks
 fn build_anthropic_content(content_blocks: Vec<MessagesContentBlock>) -> MessagesMessageContent {
     if content_blocks.len() == 1 {
@@ -929,7 +1830,7 @@ fn build_anthropic_content(content_blocks: Vec<MessagesContentBlock>) -> Message
 
 /// Parse a data URL into media type and base64 data
 /// Supports format: data:image/jpeg;base64,<data>
-fn parse_data_url(url: &str) -> Option<(String, String)> {
+pub(crate) fn parse_data_url(url: &str) -> Option<(String, String)> {
     if !url.starts_with("data:") {
         return None;
     }
@@ -961,6 +1862,20 @@ fn parse_data_url(url: &str) -> Option<(String, String)> {
     }
 }
 
+/// Map a Responses API audio input to a chat content part; audio without
+/// data or a format can't be forwarded and is dropped
+fn input_audio_part(
+    data: &Option<String>,
+    format: &Option<String>,
+) -> Option<crate::apis::openai::ContentPart> {
+    Some(crate::apis::openai::ContentPart::InputAudio {
+        input_audio: crate::apis::openai::InputAudio {
+            data: data.clone()?,
+            format: format.clone()?,
+        },
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -987,6 +1902,7 @@ mod tests {
                     name: None,
                     tool_call_id: None,
                     tool_calls: None,
+                    thinking_blocks: None,
                 },
                 Message {
                     role: Role::User,
@@ -994,6 +1910,7 @@ mod tests {
                     name: None,
                     tool_call_id: None,
                     tool_calls: None,
+                    thinking_blocks: None,
                 },
             ],
             temperature: Some(0.7),
@@ -1049,6 +1966,7 @@ mod tests {
                 name: None,
                 tool_call_id: None,
                 tool_calls: None,
+                thinking_blocks: None,
             }],
             temperature: None,
             top_p: None,
@@ -1116,6 +2034,7 @@ mod tests {
                 name: None,
                 tool_call_id: None,
                 tool_calls: None,
+                thinking_blocks: None,
             }],
             temperature: None,
             top_p: None,
@@ -1159,6 +2078,7 @@ mod tests {
                     name: None,
                     tool_call_id: None,
                     tool_calls: None,
+                    thinking_blocks: None,
                 },
                 Message {
                     role: Role::User,
@@ -1166,6 +2086,7 @@ mod tests {
                     name: None,
                     tool_call_id: None,
                     tool_calls: None,
+                    thinking_blocks: None,
                 },
                 Message {
                     role: Role::Assistant,
@@ -1175,6 +2096,7 @@ mod tests {
                     name: None,
                     tool_call_id: None,
                     tool_calls: None,
+                    thinking_blocks: None,
                 },
                 Message {
                     role: Role::User,
@@ -1182,6 +2104,7 @@ mod tests {
                     name: None,
                     tool_call_id: None,
                     tool_calls: None,
+                    thinking_blocks: None,
                 },
             ],
             temperature: Some(0.5),
@@ -1220,6 +2143,7 @@ mod tests {
             name: None,
             tool_call_id: None,
             tool_calls: None,
+            thinking_blocks: None,
         };
 
         let bedrock_message: BedrockMessage = openai_message.try_into().unwrap();
@@ -1336,6 +2260,158 @@ mod tests {
         assert!(converted.web_search_options.is_some());
     }
 
+    #[test]
+    fn test_reasoning_effort_maps_to_thinking_budgets() {
+        let req: ChatCompletionsRequest = serde_json::from_value(json!({
+            "model": "claude-sonnet-4-5",
+            "reasoning_effort": "medium",
+            "temperature": 0.2,
+            "messages": [{"role": "user", "content": "Plan the migration"}]
+        }))
+        .unwrap();
+
+        let anthropic_req = AnthropicMessagesRequest::try_from(req.clone()).unwrap();
+        let thinking = anthropic_req.thinking.unwrap();
+        assert_eq!(thinking.thinking_type, "enabled");
+        assert_eq!(thinking.budget_tokens, Some(MEDIUM_REASONING_BUDGET));
+        assert_eq!(
+            anthropic_req.max_tokens,
+            MEDIUM_REASONING_BUDGET + DEFAULT_MAX_TOKENS
+        );
+        assert_eq!(anthropic_req.temperature, None);
+
+        let gemini_req = GenerateContentRequest::try_from(req).unwrap();
+        assert_eq!(
+            gemini_req.generation_config.unwrap().thinking_config,
+            Some(json!({"thinkingBudget": MEDIUM_REASONING_BUDGET, "includeThoughts": true}))
+        );
+
+        // A limit too small for Anthropic's minimum budget leaves thinking off
+        let small: ChatCompletionsRequest = serde_json::from_value(json!({
+            "model": "claude-sonnet-4-5",
+            "reasoning_effort": "high",
+            "max_completion_tokens": 512,
+            "messages": [{"role": "user", "content": "Hi"}]
+        }))
+        .unwrap();
+        let anthropic_req = AnthropicMessagesRequest::try_from(small).unwrap();
+        assert!(anthropic_req.thinking.is_none());
+        assert_eq!(anthropic_req.max_tokens, 512);
+    }
+
+    #[test]
+    fn test_cache_control_hint_marks_anthropic_cache_breakpoints() {
+        let req: ChatCompletionsRequest = serde_json::from_value(json!({
+            "model": "claude-sonnet-4-5",
+            "cache_control": {"type": "ephemeral"},
+            "messages": [
+                {"role": "system", "content": "You are a careful reviewer."},
+                {"role": "user", "content": "Review this diff"}
+            ],
+            "tools": [
+                {"type": "function", "function": {"name": "read_file", "parameters": {"type": "object"}}},
+                {"type": "function", "function": {"name": "run_tests", "parameters": {"type": "object"}}}
+            ]
+        }))
+        .unwrap();
+        let anthropic_req: AnthropicMessagesRequest = req.try_into().unwrap();
+        let body = serde_json::to_value(&anthropic_req).unwrap();
+
+        let ephemeral = json!({"type": "ephemeral"});
+        assert_eq!(body["system"][0]["cache_control"], ephemeral);
+        assert!(body["tools"][0].get("cache_control").is_none());
+        assert_eq!(body["tools"][1]["cache_control"], ephemeral);
+        assert_eq!(
+            body["messages"][0]["content"][0]["cache_control"],
+            ephemeral
+        );
+    }
+
+    #[test]
+    fn test_thinking_blocks_round_trip_to_anthropic() {
+        use crate::apis::anthropic::MessagesResponse;
+        use crate::apis::openai::ChatCompletionsResponse;
+
+        let upstream: MessagesResponse = serde_json::from_value(json!({
+            "id": "msg_1",
+            "type": "message",
+            "role": "assistant",
+            "model": "claude-sonnet-4-5",
+            "content": [
+                {"type": "thinking", "thinking": "Check the weather first", "signature": "sig_abc"},
+                {"type": "redacted_thinking", "data": "enc_xyz"},
+                {"type": "tool_use", "id": "toolu_1", "name": "get_weather", "input": {"city": "Paris"}}
+            ],
+            "stop_reason": "tool_use",
+            "usage": {"input_tokens": 10, "output_tokens": 20}
+        }))
+        .unwrap();
+        let response = ChatCompletionsResponse::try_from(upstream).unwrap();
+        let assistant = response.choices[0].message.to_message();
+        assert_eq!(
+            assistant.thinking_blocks.as_ref().map(Vec::len),
+            Some(2),
+            "both thinking blocks should reach the client"
+        );
+
+        // Streamed pieces, accumulated as received, come back as one block
+        let mut streamed = assistant.clone();
+        streamed.thinking_blocks = Some(
+            serde_json::from_value(json!([
+                {"type": "thinking", "thinking": "Check the "},
+                {"type": "thinking", "thinking": "weather first"},
+                {"type": "thinking", "thinking": "", "signature": "sig_abc"},
+                {"type": "redacted_thinking", "data": "enc_xyz"}
+            ]))
+            .unwrap(),
+        );
+
+        for message in [assistant, streamed] {
+            let anthropic_message = MessagesMessage::try_from(message).unwrap();
+            let body = serde_json::to_value(&anthropic_message).unwrap();
+            assert_eq!(
+                body["content"][0],
+                json!({"type": "thinking", "thinking": "Check the weather first", "signature": "sig_abc"})
+            );
+            assert_eq!(
+                body["content"][1],
+                json!({"type": "redacted_thinking", "data": "enc_xyz"})
+            );
+            assert_eq!(body["content"][2]["type"], "tool_use");
+        }
+    }
+
+    #[test]
+    fn test_responses_built_in_tools_map_to_anthropic_server_tools() {
+        use crate::apis::openai_responses::Tool as ResponsesTool;
+
+        let mut tools: Option<Vec<ResponsesTool>> = Some(
+            serde_json::from_value(json!([
+                {"type": "web_search", "domains": ["docs.rs"],
+                 "user_location": {"type": "approximate", "country": "US"}},
+                {"type": "code_interpreter", "container": {"type": "auto"}},
+                {"type": "function", "name": "lookup", "parameters": {"type": "object"}}
+            ]))
+            .unwrap(),
+        );
+        let server_tools = take_anthropic_server_tools(&mut tools);
+
+        assert!(matches!(
+            tools.as_deref(),
+            Some([ResponsesTool::Function { name, .. }]) if name == "lookup"
+        ));
+        let server_tools = serde_json::to_value(&server_tools).unwrap();
+        assert_eq!(
+            server_tools,
+            json!([
+                {"type": "web_search_20250305", "name": "web_search",
+                 "allowed_domains": ["docs.rs"],
+                 "user_location": {"type": "approximate", "country": "US"}},
+                {"type": "code_execution_20250825", "name": "code_execution"}
+            ])
+        );
+    }
+
     #[test]
     fn test_responses_function_call_output_maps_to_tool_message() {
         use crate::apis::openai_responses::{
@@ -1464,4 +2540,308 @@ mod tests {
             Some("toolu_abc123")
         );
     }
+
+    #[test]
+    fn test_openai_to_gemini_request() {
+        let openai_request: ChatCompletionsRequest = serde_json::from_value(json!({
+            "model": "gemini-2.5-flash",
+            "messages": [
+                {"role": "system", "content": "You are a helpful assistant."},
+                {"role": "user", "content": "Weather in Paris and Rome?"},
+                {"role": "assistant", "content": null, "tool_calls": [
+                    {"id": "call_1", "type": "function", "function": {"name": "get_weather", "arguments": "{\"city\":\"Paris\"}"}},
+                    {"id": "call_2", "type": "function", "function": {"name": "get_weather", "arguments": "{\"city\":\"Rome\"}"}}
+                ]},
+                {"role": "tool", "tool_call_id": "call_1", "content": "{\"temp\": 21}"},
+                {"role": "tool", "tool_call_id": "call_2", "content": "sunny"}
+            ],
+            "max_tokens": 256,
+            "temperature": 0.5,
+            "response_format": {"type": "json_schema", "json_schema": {"name": "w", "schema": {"type": "object"}}},
+            "tools": [{"type": "function", "function": {"name": "get_weather", "parameters": {"type": "object"}}}],
+            "tool_choice": {"type": "function", "function": {"name": "get_weather"}},
+            "stream": true
+        }))
+        .unwrap();
+
+        let gemini_request: GenerateContentRequest = openai_request.try_into().unwrap();
+        assert_eq!(gemini_request.model, "gemini-2.5-flash");
+        assert!(gemini_request.stream);
+
+        let body = serde_json::to_value(&gemini_request).unwrap();
+        assert_eq!(
+            body["systemInstruction"],
+            json!({"parts": [{"text": "You are a helpful assistant."}]})
+        );
+        assert_eq!(body["contents"].as_array().unwrap().len(), 3);
+        assert_eq!(body["contents"][1]["role"], "model");
+        assert_eq!(
+            body["contents"][1]["parts"][1]["functionCall"],
+            json!({"name": "get_weather", "args": {"city": "Rome"}})
+        );
+        assert_eq!(
+            body["contents"][2],
+            json!({"role": "user", "parts": [
+                {"functionResponse": {"name": "get_weather", "response": {"temp": 21}}},
+                {"functionResponse": {"name": "get_weather", "response": {"result": "sunny"}}}
+            ]})
+        );
+        assert_eq!(
+            body["generationConfig"],
+            json!({
+                "maxOutputTokens": 256,
+                "temperature": 0.5,
+                "responseMimeType": "application/json",
+                "responseJsonSchema": {"type": "object"}
+            })
+        );
+        assert_eq!(
+            body["tools"][0]["functionDeclarations"][0]["parametersJsonSchema"],
+            json!({"type": "object"})
+        );
+        assert_eq!(
+            body["toolConfig"]["functionCallingConfig"],
+            json!({"mode": "ANY", "allowedFunctionNames": ["get_weather"]})
+        );
+    }
+
+    #[test]
+    fn test_openai_to_gemini_rejects_orphan_tool_result() {
+        let openai_request: ChatCompletionsRequest = serde_json::from_value(json!({
+            "model": "gemini-2.5-flash",
+            "messages": [
+                {"role": "user", "content": "Hi"},
+                {"role": "tool", "tool_call_id": "call_9", "content": "ok"}
+            ]
+        }))
+        .unwrap();
+
+        let result: Result<GenerateContentRequest, _> = openai_request.try_into();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_input_audio_maps_to_gemini_and_is_rejected_by_anthropic() {
+        let openai_request: ChatCompletionsRequest = serde_json::from_value(json!({
+            "model": "gemini-2.5-flash",
+            "messages": [{"role": "user", "content": [
+                {"type": "text", "text": "Transcribe this"},
+                {"type": "input_audio", "input_audio": {"data": "UklGRg==", "format": "wav"}}
+            ]}]
+        }))
+        .unwrap();
+
+        let gemini_request: GenerateContentRequest = openai_request.clone().try_into().unwrap();
+        let body = serde_json::to_value(&gemini_request).unwrap();
+        assert_eq!(
+            body["contents"][0]["parts"][1],
+            json!({"inlineData": {"mimeType": "audio/wav", "data": "UklGRg=="}})
+        );
+
+        let result: Result<AnthropicMessagesRequest, _> = openai_request.try_into();
+        assert!(matches!(
+            result,
+            Err(TransformError::UnsupportedContent(msg)) if msg.contains("audio")
+        ));
+    }
+
+    #[test]
+    fn test_openai_to_cohere_request() {
+        let openai_request: ChatCompletionsRequest = serde_json::from_value(json!({
+            "model": "command-a-03-2025",
+            "messages": [
+                {"role": "system", "content": "You are a helpful assistant."},
+                {"role": "user", "content": [
+                    {"type": "text", "text": "Weather here?"},
+                    {"type": "image_url", "image_url": {"url": "https://example.com/sky.png"}}
+                ]},
+                {"role": "assistant", "content": "Checking Paris.", "tool_calls": [
+                    {"id": "call_1", "type": "function", "function": {"name": "get_weather", "arguments": "{\"city\":\"Paris\"}"}}
+                ]},
+                {"role": "tool", "tool_call_id": "call_1", "content": "sunny"}
+            ],
+            "max_completion_tokens": 256,
+            "top_p": 0.5,
+            "stop": ["END"],
+            "response_format": {"type": "json_schema", "json_schema": {"name": "w", "schema": {"type": "object"}}},
+            "tools": [
+                {"type": "function", "function": {"name": "get_weather", "parameters": {"type": "object"}}},
+                {"type": "function", "function": {"name": "get_time", "parameters": {"type": "object"}}}
+            ],
+            "tool_choice": {"type": "function", "function": {"name": "get_weather"}},
+            "stream": true
+        }))
+        .unwrap();
+
+        let cohere_request: CohereChatRequest = openai_request.try_into().unwrap();
+        let body = serde_json::to_value(&cohere_request).unwrap();
+        assert_eq!(
+            body,
+            json!({
+                "model": "command-a-03-2025",
+                "messages": [
+                    {"role": "system", "content": "You are a helpful assistant."},
+                    {"role": "user", "content": [
+                        {"type": "text", "text": "Weather here?"},
+                        {"type": "image_url", "image_url": {"url": "https://example.com/sky.png"}}
+                    ]},
+                    {"role": "assistant", "tool_plan": "Checking Paris.", "tool_calls": [
+                        {"id": "call_1", "type": "function", "function": {"name": "get_weather", "arguments": "{\"city\":\"Paris\"}"}}
+                    ]},
+                    {"role": "tool", "tool_call_id": "call_1", "content": "sunny"}
+                ],
+                "tools": [{"type": "function", "function": {"name": "get_weather", "parameters": {"type": "object"}}}],
+                "tool_choice": "REQUIRED",
+                "response_format": {"type": "json_object", "json_schema": {"type": "object"}},
+                "max_tokens": 256,
+                "stop_sequences": ["END"],
+                "p": 0.5,
+                "stream": true
+            })
+        );
+    }
+
+    #[test]
+    fn test_openai_to_ollama_request() {
+        let openai_request: ChatCompletionsRequest = serde_json::from_value(json!({
+            "model": "llama3.2",
+            "messages": [
+                {"role": "system", "content": "You are a helpful assistant."},
+                {"role": "user", "content": [
+                    {"type": "text", "text": "Weather here?"},
+                    {"type": "image_url", "image_url": {"url": "data:image/png;base64,iVBORw0KGgo="}}
+                ]},
+                {"role": "assistant", "content": null, "tool_calls": [
+                    {"id": "call_1", "type": "function", "function": {"name": "get_weather", "arguments": "{\"city\":\"Paris\"}"}}
+                ]},
+                {"role": "tool", "tool_call_id": "call_1", "content": "sunny"}
+            ],
+            "max_tokens": 256,
+            "temperature": 0.5,
+            "stop": ["END"],
+            "response_format": {"type": "json_object"},
+            "tools": [
+                {"type": "function", "function": {"name": "get_weather", "parameters": {"type": "object"}}}
+            ],
+            "stream": true
+        }))
+        .unwrap();
+
+        let ollama_request: OllamaChatRequest = openai_request.try_into().unwrap();
+        let body = serde_json::to_value(&ollama_request).unwrap();
+        assert_eq!(
+            body,
+            json!({
+                "model": "llama3.2",
+                "messages": [
+                    {"role": "system", "content": "You are a helpful assistant."},
+                    {"role": "user", "content": "Weather here?", "images": ["iVBORw0KGgo="]},
+                    {"role": "assistant", "content": "", "tool_calls": [
+                        {"function": {"name": "get_weather", "arguments": {"city": "Paris"}}}
+                    ]},
+                    {"role": "tool", "content": "sunny", "tool_name": "get_weather"}
+                ],
+                "tools": [{"type": "function", "function": {"name": "get_weather", "parameters": {"type": "object"}}}],
+                "format": "json",
+                "options": {"temperature": 0.5, "num_predict": 256, "stop": ["END"]},
+                "stream": true
+            })
+        );
+    }
+
+    #[test]
+    fn test_openai_to_bedrock_invoke_model_request() {
+        let claude_request: ChatCompletionsRequest = serde_json::from_value(json!({
+            "model": "anthropic.claude-3-5-haiku-20241022-v1:0",
+            "messages": [
+                {"role": "system", "content": "Be brief."},
+                {"role": "user", "content": "Hi"}
+            ],
+            "max_tokens": 128,
+            "stream": true
+        }))
+        .unwrap();
+
+        let invoke: InvokeModelRequest = claude_request.try_into().unwrap();
+        assert!(invoke.stream);
+        let body = invoke.body_json().unwrap();
+        assert_eq!(body["anthropic_version"], "bedrock-2023-05-31");
+        assert_eq!(body["max_tokens"], 128);
+        assert!(body.get("model").is_none());
+        assert!(body.get("stream").is_none());
+
+        let titan_request: ChatCompletionsRequest = serde_json::from_value(json!({
+            "model": "amazon.titan-text-express-v1",
+            "messages": [
+                {"role": "user", "content": "Hi"},
+                {"role": "assistant", "content": "Hello!"},
+                {"role": "user", "content": "Tell me a joke"}
+            ],
+            "max_completion_tokens": 64,
+            "stop": ["User:"]
+        }))
+        .unwrap();
+
+        let invoke: InvokeModelRequest = titan_request.try_into().unwrap();
+        assert!(!invoke.stream);
+        assert_eq!(
+            invoke.body_json().unwrap(),
+            json!({
+                "inputText": "User: Hi\nBot: Hello!\nUser: Tell me a joke\nBot:",
+                "textGenerationConfig": {"maxTokenCount": 64, "stopSequences": ["User:"]}
+            })
+        );
+
+        let unknown_request: ChatCompletionsRequest = serde_json::from_value(json!({
+            "model": "meta.llama3-8b-instruct-v1:0",
+            "messages": [{"role": "user", "content": "Hi"}]
+        }))
+        .unwrap();
+        let result: Result<InvokeModelRequest, _> = unknown_request.try_into();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_parallel_tool_calls_round_trip_to_anthropic() {
+        let request: ChatCompletionsRequest = serde_json::from_value(json!({
+            "model": "claude-sonnet-4-5",
+            "messages": [
+                {"role": "user", "content": "Weather in Paris and Rome?"},
+                {"role": "assistant", "tool_calls": [
+                    {"id": "call_1", "type": "function",
+                     "function": {"name": "get_weather", "arguments": "{\"city\":\"Paris\"}"}},
+                    {"id": "call_2", "type": "function",
+                     "function": {"name": "get_weather", "arguments": "{\"city\":\"Rome\"}"}}
+                ]},
+                {"role": "tool", "tool_call_id": "call_1", "content": "rainy"},
+                {"role": "tool", "tool_call_id": "call_2", "content": "sunny"}
+            ],
+            "tools": [{"type": "function", "function": {"name": "get_weather", "parameters": {}}}],
+            "parallel_tool_calls": false
+        }))
+        .unwrap();
+
+        let anthropic = MessagesRequest::try_from(request.clone()).unwrap();
+        let tool_choice = anthropic.tool_choice.as_ref().unwrap();
+        assert_eq!(tool_choice.kind, MessagesToolChoiceType::Auto);
+        assert_eq!(tool_choice.disable_parallel_tool_use, Some(true));
+        assert_eq!(anthropic.messages.len(), 3);
+        let body = serde_json::to_value(&anthropic.messages[2]).unwrap();
+        assert_eq!(body["content"][0]["tool_use_id"], "call_1");
+        assert_eq!(body["content"][1]["tool_use_id"], "call_2");
+
+        let back = ChatCompletionsRequest::try_from(anthropic).unwrap();
+        assert_eq!(back.parallel_tool_calls, Some(false));
+        assert_eq!(back.messages[1].tool_calls.as_ref().map(Vec::len), Some(2));
+        let results: Vec<_> = back.messages[2..]
+            .iter()
+            .map(|m| m.tool_call_id.as_deref())
+            .collect();
+        assert_eq!(results, vec![Some("call_1"), Some("call_2")]);
+
+        let converse = ConverseRequest::try_from(request).unwrap();
+        let messages = converse.messages.unwrap();
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[2].content.len(), 2);
+    }
 }
@@ -6,6 +6,7 @@
 
 use crate::apis::openai_responses::{
     InputContent, InputItem, InputMessage, MessageContent, MessageRole, OutputContent, OutputItem,
+    OutputItemStatus,
 };
 
 /// Converts an OutputItem from a response into an InputItem for the next request
@@ -46,25 +47,27 @@ pub fn convert_responses_output_to_input_items(output: &OutputItem) -> Option<In
                 content: MessageContent::Items(input_content),
             }))
         }
-        // For function calls, we'll create an assistant message with the tool call info
-        // This matches how conversation history is typically built
+        // Function calls and their outputs are replayed as-is so the next turn
+        // can pair the call_id with the client's function_call_output.
         OutputItem::FunctionCall {
-            name, arguments, ..
-        } => {
-            let tool_call_text = if let (Some(n), Some(args)) = (name, arguments) {
-                format!("Called function: {} with arguments: {}", n, args)
-            } else {
-                "Called a function".to_string()
-            };
-
-            Some(InputItem::Message(InputMessage {
-                role: MessageRole::Assistant,
-                content: MessageContent::Items(vec![InputContent::InputText {
-                    text: tool_call_text,
-                }]),
-            }))
-        }
-        // Skip other output types (tool outputs, etc.) as they don't convert to input
+            call_id,
+            name,
+            arguments,
+            ..
+        } => Some(InputItem::FunctionCall {
+            item_type: "function_call".to_string(),
+            name: name.clone().unwrap_or_default(),
+            arguments: arguments.clone().unwrap_or_else(|| "{}".to_string()),
+            call_id: call_id.clone(),
+        }),
+        OutputItem::FunctionCallOutput {
+            call_id, output, ..
+        } => Some(InputItem::FunctionCallOutput {
+            item_type: "function_call_output".to_string(),
+            call_id: call_id.clone(),
+            output: serde_json::Value::String(output.clone()),
+        }),
+        // Skip other output types (built-in tool calls, etc.) as they don't convert to input
         _ => None,
     }
 }
@@ -77,6 +80,65 @@ pub fn outputs_to_inputs(outputs: &[OutputItem]) -> Vec<InputItem> {
         .collect()
 }
 
+/// Rebuilds the output of the turn that ended `items`: the assistant
+/// messages and function calls after the last user message or function call
+/// output. This is the inverse of [`outputs_to_inputs`] for a stored
+/// conversation, minus the item ids and any output that did not convert.
+pub fn last_turn_outputs(items: &[InputItem]) -> Vec<OutputItem> {
+    let turn_start = items
+        .iter()
+        .rposition(|item| match item {
+            InputItem::Message(message) => !matches!(message.role, MessageRole::Assistant),
+            InputItem::FunctionCallOutput { .. } | InputItem::ItemReference { .. } => true,
+            InputItem::FunctionCall { .. } => false,
+        })
+        .map_or(0, |index| index + 1);
+    items[turn_start..]
+        .iter()
+        .enumerate()
+        .filter_map(|(index, item)| match item {
+            InputItem::Message(message) => {
+                let content = match &message.content {
+                    MessageContent::Text(text) => vec![text.clone()],
+                    MessageContent::Items(items) => items
+                        .iter()
+                        .filter_map(|c| match c {
+                            InputContent::InputText { text } => Some(text.clone()),
+                            _ => None,
+                        })
+                        .collect(),
+                };
+                Some(OutputItem::Message {
+                    id: format!("msg_{}", index),
+                    status: OutputItemStatus::Completed,
+                    role: "assistant".to_string(),
+                    content: content
+                        .into_iter()
+                        .map(|text| OutputContent::OutputText {
+                            text,
+                            annotations: vec![],
+                            logprobs: None,
+                        })
+                        .collect(),
+                })
+            }
+            InputItem::FunctionCall {
+                name,
+                arguments,
+                call_id,
+                ..
+            } => Some(OutputItem::FunctionCall {
+                id: format!("fc_{}", call_id),
+                status: OutputItemStatus::Completed,
+                call_id: call_id.clone(),
+                name: Some(name.clone()),
+                arguments: Some(arguments.clone()),
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -128,22 +190,64 @@ mod tests {
         let input = convert_responses_output_to_input_items(&output).unwrap();
 
         match input {
-            InputItem::Message(msg) => {
-                assert!(matches!(msg.role, MessageRole::Assistant));
-                match &msg.content {
-                    MessageContent::Items(items) => match &items[0] {
-                        InputContent::InputText { text } => {
-                            assert!(text.contains("get_weather"));
-                        }
-                        _ => panic!("Expected InputText"),
-                    },
-                    _ => panic!("Expected MessageContent::Items"),
-                }
+            InputItem::FunctionCall {
+                item_type,
+                name,
+                arguments,
+                call_id,
+            } => {
+                assert_eq!(item_type, "function_call");
+                assert_eq!(name, "get_weather");
+                assert_eq!(arguments, r#"{"location":"SF"}"#);
+                assert_eq!(call_id, "call_123");
             }
-            _ => panic!("Expected Message variant"),
+            _ => panic!("Expected FunctionCall variant"),
+        }
+    }
+
+    #[test]
+    fn test_function_call_output_to_input() {
+        let output = OutputItem::FunctionCallOutput {
+            id: "fco_123".to_string(),
+            call_id: "call_123".to_string(),
+            output: "72F and sunny".to_string(),
+            status: None,
+        };
+
+        match convert_responses_output_to_input_items(&output).unwrap() {
+            InputItem::FunctionCallOutput {
+                item_type,
+                call_id,
+                output,
+            } => {
+                assert_eq!(item_type, "function_call_output");
+                assert_eq!(call_id, "call_123");
+                assert_eq!(output, "72F and sunny");
+            }
+            _ => panic!("Expected FunctionCallOutput variant"),
         }
     }
 
+    #[test]
+    fn test_echoed_function_call_with_id_is_not_an_item_reference() {
+        let item: InputItem = serde_json::from_value(serde_json::json!({
+            "type": "function_call",
+            "id": "fc_123",
+            "call_id": "call_123",
+            "name": "get_weather",
+            "arguments": "{}"
+        }))
+        .unwrap();
+        assert!(matches!(item, InputItem::FunctionCall { .. }));
+
+        let item: InputItem = serde_json::from_value(serde_json::json!({
+            "type": "item_reference",
+            "id": "msg_123"
+        }))
+        .unwrap();
+        assert!(matches!(item, InputItem::ItemReference { .. }));
+    }
+
     #[test]
     fn test_outputs_to_inputs() {
         let outputs = vec![
@@ -169,4 +273,33 @@ mod tests {
         let inputs = outputs_to_inputs(&outputs);
         assert_eq!(inputs.len(), 2);
     }
+    #[test]
+    fn test_last_turn_outputs() {
+        let items: Vec<InputItem> = serde_json::from_value(serde_json::json!([
+            {"role": "user", "content": "Weather in Paris?"},
+            {"type": "function_call", "name": "weather", "arguments": "{}", "call_id": "call_1"},
+            {"type": "function_call_output", "call_id": "call_1", "output": "18C"},
+            {"role": "assistant", "content": [{"type": "output_text", "text": "It's 18C."}]},
+            {"type": "function_call", "name": "forecast", "arguments": "{\"days\":2}", "call_id": "call_2"}
+        ]))
+        .unwrap();
+
+        let outputs = last_turn_outputs(&items);
+        assert_eq!(outputs.len(), 2);
+        match &outputs[0] {
+            OutputItem::Message { role, content, .. } => {
+                assert_eq!(role, "assistant");
+                assert!(
+                    matches!(&content[0], OutputContent::OutputText { text, .. } if text == "It's 18C.")
+                );
+            }
+            other => panic!("Expected Message, got {:?}", other),
+        }
+        assert!(matches!(
+            &outputs[1],
+            OutputItem::FunctionCall { call_id, name: Some(name), .. }
+                if call_id == "call_2" && name == "forecast"
+        ));
+        assert!(last_turn_outputs(&items[..3]).is_empty());
+    }
 }
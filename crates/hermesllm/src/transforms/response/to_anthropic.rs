@@ -20,17 +20,50 @@ impl TryFrom<ChatCompletionsResponse> for MessagesResponse {
             .next()
             .ok_or_else(|| TransformError::MissingField("choices".to_string()))?;
 
-        let content = convert_openai_message_to_anthropic_content(&choice.message.to_message())?;
+        let mut content =
+            convert_openai_message_to_anthropic_content(&choice.message.to_message())?;
+        // Reasoning precedes the answer, as a thinking block would, unless
+        // the signed thinking blocks already came through
+        let has_thinking = content.iter().any(|block| {
+            matches!(
+                block,
+                MessagesContentBlock::Thinking { .. }
+                    | MessagesContentBlock::RedactedThinking { .. }
+            )
+        });
+        if let Some(reasoning) = choice
+            .message
+            .reasoning_content
+            .filter(|r| !r.is_empty() && !has_thinking)
+        {
+            content.insert(
+                0,
+                MessagesContentBlock::Thinking {
+                    thinking: reasoning,
+                    signature: None,
+                    cache_control: None,
+                },
+            );
+        }
         let stop_reason = choice
             .finish_reason
             .map(|fr| fr.into())
             .unwrap_or(MessagesStopReason::EndTurn);
 
+        // Anthropic's input_tokens leaves out cache reads
+        let cached_tokens = resp
+            .usage
+            .prompt_tokens_details
+            .as_ref()
+            .and_then(|details| details.cached_tokens);
         let usage = MessagesUsage {
-            input_tokens: resp.usage.prompt_tokens,
+            input_tokens: resp
+                .usage
+                .prompt_tokens
+                .saturating_sub(cached_tokens.unwrap_or(0)),
             output_tokens: resp.usage.completion_tokens,
             cache_creation_input_tokens: None,
-            cache_read_input_tokens: None,
+            cache_read_input_tokens: cached_tokens,
         };
 
         Ok(MessagesResponse {
@@ -140,6 +173,7 @@ fn convert_bedrock_message_to_anthropic_content(
             ContentBlock::Text { text } => {
                 content_blocks.push(MessagesContentBlock::Text {
                     text: text.clone(),
+                    citations: None,
                     cache_control: None,
                 });
             }
@@ -159,6 +193,7 @@ fn convert_bedrock_message_to_anthropic_content(
                         crate::apis::amazon_bedrock::ToolResultContentBlock::Text { text } => {
                             tool_result_blocks.push(MessagesContentBlock::Text {
                                 text: text.clone(),
+                                citations: None,
                                 cache_control: None,
                             });
                         }
@@ -184,6 +219,7 @@ fn convert_bedrock_message_to_anthropic_content(
                             // Convert JSON content to text representation
                             tool_result_blocks.push(MessagesContentBlock::Text {
                                 text: serde_json::to_string(&json).unwrap_or_default(),
+                                citations: None,
                                 cache_control: None,
                             });
                         }
@@ -215,8 +251,8 @@ fn convert_bedrock_message_to_anthropic_content(
                 }
             }
             ContentBlock::Document { document } => {
-                // Convert Bedrock DocumentSource to Anthropic format
-                // Note: Bedrock's 'name' field is lost in conversion as Anthropic doesn't support it
+                // Convert Bedrock DocumentSource to Anthropic format; the
+                // document's name becomes its title
                 match &document.source {
                     crate::apis::amazon_bedrock::DocumentSource::Base64 { media_type, data } => {
                         content_blocks.push(MessagesContentBlock::Document {
@@ -224,6 +260,8 @@ fn convert_bedrock_message_to_anthropic_content(
                                 media_type: media_type.clone(),
                                 data: data.clone(),
                             },
+                            title: document.name.clone(),
+                            citations: None,
                         });
                     } // Note: S3Location would require async handling if implemented
                 }
@@ -233,6 +271,7 @@ fn convert_bedrock_message_to_anthropic_content(
                 if let Some(guard_text) = &guard_content.text {
                     content_blocks.push(MessagesContentBlock::Text {
                         text: guard_text.text.clone(),
+                        citations: None,
                         cache_control: None,
                     });
                 }
@@ -758,4 +797,36 @@ mod tests {
         // Should use fallback model name
         assert_eq!(anthropic_response_fallback.model, "bedrock-model");
     }
+
+    #[test]
+    fn test_openai_reasoning_content_to_anthropic_thinking() {
+        let openai_response: ChatCompletionsResponse = serde_json::from_value(json!({
+            "id": "chatcmpl-123",
+            "object": "chat.completion",
+            "created": 1234567890,
+            "model": "deepseek-reasoner",
+            "choices": [{
+                "index": 0,
+                "message": {
+                    "role": "assistant",
+                    "content": "4",
+                    "reasoning_content": "2 + 2 is 4"
+                },
+                "finish_reason": "stop"
+            }],
+            "usage": {"prompt_tokens": 10, "completion_tokens": 5, "total_tokens": 15}
+        }))
+        .unwrap();
+
+        let anthropic_response = MessagesResponse::try_from(openai_response).unwrap();
+        assert_eq!(anthropic_response.content.len(), 2);
+        match &anthropic_response.content[0] {
+            MessagesContentBlock::Thinking { thinking, .. } => assert_eq!(thinking, "2 + 2 is 4"),
+            other => panic!("Expected thinking block first, got {:?}", other),
+        }
+        match &anthropic_response.content[1] {
+            MessagesContentBlock::Text { text, .. } => assert_eq!(text, "4"),
+            other => panic!("Expected text block, got {:?}", other),
+        }
+    }
 }
@@ -1,9 +1,24 @@
-use crate::apis::amazon_bedrock::{ConverseOutput, ConverseResponse, StopReason};
+use crate::apis::amazon_bedrock::{
+    ConverseOutput, ConverseResponse, InvokeModelResponse, StopReason, TitanTextResponse,
+};
 use crate::apis::anthropic::{MessagesContentBlock, MessagesResponse, MessagesUsage};
+use crate::apis::cohere::{
+    CohereChatResponse, CohereContentItem, CohereFinishReason, CohereToolCall, CohereUsage,
+};
+use crate::apis::gemini::{
+    FinishReason as GeminiFinishReason, GenerateContentResponse, LogprobsCandidate, LogprobsResult,
+    Part as GeminiPart, UsageMetadata,
+};
+use crate::apis::ollama::{OllamaChatResponse, OllamaToolCall};
 use crate::apis::openai::{
-    ChatCompletionsResponse, Choice, FinishReason, MessageContent, ResponseMessage, Role, Usage,
+    ChatCompletionsResponse, Choice, ChoiceLogprobs, CompletionTokensDetails, FinishReason,
+    FunctionCall, MessageContent, PromptTokensDetails, ResponseMessage, Role, ThinkingBlock,
+    TokenLogprob, ToolCall, TopLogprob, Usage,
+};
+use crate::apis::openai_responses::{
+    CodeInterpreterOutput, LogProb, OutputItem, OutputItemStatus, OutputTokenDetails,
+    ResponseUsage, ResponsesAPIResponse, TokenDetails, TopLogProb,
 };
-use crate::apis::openai_responses::ResponsesAPIResponse;
 use crate::clients::TransformError;
 use crate::transforms::lib::*;
 
@@ -12,40 +27,154 @@ use crate::transforms::lib::*;
 // ============================================================================
 
 // Usage Conversions
+/// Anthropic counts cache reads and writes apart from `input_tokens`; OpenAI's
+/// `prompt_tokens` includes them, with the reads as `cached_tokens`.
 impl From<MessagesUsage> for Usage {
     fn from(val: MessagesUsage) -> Self {
+        let cache_read = val.cache_read_input_tokens.unwrap_or(0);
+        let prompt_tokens =
+            val.input_tokens + cache_read + val.cache_creation_input_tokens.unwrap_or(0);
         Usage {
-            prompt_tokens: val.input_tokens,
+            prompt_tokens,
             completion_tokens: val.output_tokens,
-            total_tokens: val.input_tokens + val.output_tokens,
-            prompt_tokens_details: None,
+            total_tokens: prompt_tokens + val.output_tokens,
+            prompt_tokens_details: val.cache_read_input_tokens.map(|cached_tokens| {
+                PromptTokensDetails {
+                    cached_tokens: Some(cached_tokens),
+                    audio_tokens: None,
+                }
+            }),
             completion_tokens_details: None,
         }
     }
 }
 
+impl From<Usage> for ResponseUsage {
+    fn from(usage: Usage) -> Self {
+        ResponseUsage {
+            input_tokens: usage.prompt_tokens as i32,
+            output_tokens: usage.completion_tokens as i32,
+            total_tokens: usage.total_tokens as i32,
+            input_tokens_details: usage.prompt_tokens_details.map(|details| TokenDetails {
+                cached_tokens: details.cached_tokens.unwrap_or(0) as i32,
+            }),
+            output_tokens_details: usage.completion_tokens_details.map(|details| {
+                OutputTokenDetails {
+                    reasoning_tokens: details.reasoning_tokens.unwrap_or(0) as i32,
+                }
+            }),
+        }
+    }
+}
+
+// Logprob Conversions
+impl From<TokenLogprob> for LogProb {
+    fn from(token: TokenLogprob) -> Self {
+        LogProb {
+            bytes: token
+                .bytes
+                .unwrap_or_else(|| token.token.clone().into_bytes()),
+            token: token.token,
+            logprob: token.logprob as f32,
+            top_logprobs: token
+                .top_logprobs
+                .into_iter()
+                .map(|top| TopLogProb {
+                    bytes: top.bytes.unwrap_or_else(|| top.token.clone().into_bytes()),
+                    token: top.token,
+                    logprob: top.logprob as f32,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// The content logprobs of a chat choice, in the Responses API's shape
+pub(crate) fn responses_logprobs(logprobs: Option<&ChoiceLogprobs>) -> Vec<LogProb> {
+    logprobs
+        .and_then(|logprobs| logprobs.content.clone())
+        .unwrap_or_default()
+        .into_iter()
+        .map(LogProb::from)
+        .collect()
+}
+
+/// Gemini reports the chosen token and the top candidates of each step in
+/// two parallel lists.
+impl From<LogprobsResult> for ChoiceLogprobs {
+    fn from(result: LogprobsResult) -> Self {
+        fn top_logprob(candidate: LogprobsCandidate) -> TopLogprob {
+            let token = candidate.token.unwrap_or_default();
+            TopLogprob {
+                bytes: Some(token.clone().into_bytes()),
+                token,
+                logprob: candidate.log_probability.unwrap_or(0.0),
+            }
+        }
+
+        let mut top_candidates = result.top_candidates.into_iter();
+        let content = result
+            .chosen_candidates
+            .into_iter()
+            .map(|chosen| {
+                let chosen = top_logprob(chosen);
+                TokenLogprob {
+                    token: chosen.token,
+                    logprob: chosen.logprob,
+                    bytes: chosen.bytes,
+                    top_logprobs: top_candidates
+                        .next()
+                        .map(|top| top.candidates.into_iter().map(top_logprob).collect())
+                        .unwrap_or_default(),
+                }
+            })
+            .collect();
+        ChoiceLogprobs {
+            content: Some(content),
+            refusal: None,
+        }
+    }
+}
+
 impl TryFrom<ChatCompletionsResponse> for ResponsesAPIResponse {
     type Error = TransformError;
 
     fn try_from(resp: ChatCompletionsResponse) -> Result<Self, Self::Error> {
         use crate::apis::openai_responses::{
             IncompleteDetails, IncompleteReason, OutputContent, OutputItem, OutputItemStatus,
-            ResponseStatus, ResponseUsage, ResponsesAPIResponse,
+            ResponseStatus,
         };
 
         // Convert the first choice's message to output items
         let output = if let Some(choice) = resp.choices.first() {
             let mut items = Vec::new();
 
+            // Reasoning returned by DeepSeek-R1 style models becomes a reasoning item
+            if let Some(reasoning) = choice
+                .message
+                .reasoning_content
+                .as_ref()
+                .filter(|r| !r.is_empty())
+            {
+                items.push(OutputItem::Reasoning {
+                    id: format!("rs_{}", uuid::Uuid::new_v4().to_string().replace("-", "")),
+                    summary: vec![serde_json::json!({
+                        "type": "summary_text",
+                        "text": reasoning,
+                    })],
+                });
+            }
+
             // Create a message output item from the response message
             let mut content = Vec::new();
 
             // Add text content if present
             if let Some(text) = &choice.message.content {
+                let logprobs = responses_logprobs(choice.logprobs.as_ref());
                 content.push(OutputContent::OutputText {
                     text: text.clone(),
                     annotations: vec![],
-                    logprobs: None,
+                    logprobs: (!logprobs.is_empty()).then_some(logprobs),
                 });
             }
 
@@ -132,22 +261,7 @@ impl TryFrom<ChatCompletionsResponse> for ResponsesAPIResponse {
             ResponseStatus::Completed
         };
 
-        // Convert usage
-        let usage = ResponseUsage {
-            input_tokens: resp.usage.prompt_tokens as i32,
-            output_tokens: resp.usage.completion_tokens as i32,
-            total_tokens: resp.usage.total_tokens as i32,
-            input_tokens_details: resp.usage.prompt_tokens_details.map(|details| {
-                crate::apis::openai_responses::TokenDetails {
-                    cached_tokens: details.cached_tokens.unwrap_or(0) as i32,
-                }
-            }),
-            output_tokens_details: resp.usage.completion_tokens_details.map(|details| {
-                crate::apis::openai_responses::OutputTokenDetails {
-                    reasoning_tokens: details.reasoning_tokens.unwrap_or(0) as i32,
-                }
-            }),
-        };
+        let usage = ResponseUsage::from(resp.usage);
 
         // Set incomplete_details if status is incomplete
         let incomplete_details = if matches!(status, ResponseStatus::Incomplete) {
@@ -204,7 +318,7 @@ impl TryFrom<MessagesResponse> for ChatCompletionsResponse {
     type Error = TransformError;
 
     fn try_from(resp: MessagesResponse) -> Result<Self, Self::Error> {
-        let content = convert_anthropic_content_to_openai(&resp.content)?;
+        let (content, annotations) = convert_anthropic_content_to_openai(&resp.content)?;
         let finish_reason: FinishReason = resp.stop_reason.into();
         let tool_calls = resp.content.extract_tool_calls()?;
 
@@ -221,11 +335,42 @@ impl TryFrom<MessagesResponse> for ChatCompletionsResponse {
             }
         };
 
+        let thinking: Vec<&str> = resp
+            .content
+            .iter()
+            .filter_map(|block| match block {
+                MessagesContentBlock::Thinking { thinking, .. } if !thinking.is_empty() => {
+                    Some(thinking.as_str())
+                }
+                _ => None,
+            })
+            .collect();
+        let thinking_blocks: Vec<ThinkingBlock> = resp
+            .content
+            .iter()
+            .filter_map(|block| match block {
+                MessagesContentBlock::Thinking {
+                    thinking,
+                    signature,
+                    ..
+                } => Some(ThinkingBlock::Thinking {
+                    thinking: thinking.clone(),
+                    signature: signature.clone(),
+                }),
+                MessagesContentBlock::RedactedThinking { data } => {
+                    Some(ThinkingBlock::RedactedThinking { data: data.clone() })
+                }
+                _ => None,
+            })
+            .collect();
+
         let message = ResponseMessage {
             role: Role::Assistant,
             content: content_string,
+            reasoning_content: (!thinking.is_empty()).then(|| thinking.join("\n")),
+            thinking_blocks: (!thinking_blocks.is_empty()).then_some(thinking_blocks),
             refusal: None,
-            annotations: None,
+            annotations: (!annotations.is_empty()).then_some(annotations),
             audio: None,
             function_call: None,
             tool_calls,
@@ -238,13 +383,7 @@ impl TryFrom<MessagesResponse> for ChatCompletionsResponse {
             logprobs: None,
         };
 
-        let usage = Usage {
-            prompt_tokens: resp.usage.input_tokens,
-            completion_tokens: resp.usage.output_tokens,
-            total_tokens: resp.usage.input_tokens + resp.usage.output_tokens,
-            prompt_tokens_details: None,
-            completion_tokens_details: None,
-        };
+        let usage = Usage::from(resp.usage);
 
         Ok(ChatCompletionsResponse {
             id: resp.id,
@@ -290,6 +429,8 @@ impl TryFrom<ConverseResponse> for ChatCompletionsResponse {
         let response_message = ResponseMessage {
             role,
             content,
+            reasoning_content: None,
+            thinking_blocks: None,
             refusal: None,
             annotations: None,
             audio: None,
@@ -343,13 +484,387 @@ impl TryFrom<ConverseResponse> for ChatCompletionsResponse {
     }
 }
 
+impl From<UsageMetadata> for Usage {
+    fn from(val: UsageMetadata) -> Self {
+        // Gemini counts thinking separately from the answer; OpenAI bills both as completion
+        let reasoning_tokens = val.thoughts_token_count.unwrap_or(0);
+        Usage {
+            prompt_tokens: val.prompt_token_count,
+            completion_tokens: val.candidates_token_count + reasoning_tokens,
+            total_tokens: val.total_token_count,
+            prompt_tokens_details: val.cached_content_token_count.map(|cached| {
+                PromptTokensDetails {
+                    cached_tokens: Some(cached),
+                    audio_tokens: None,
+                }
+            }),
+            completion_tokens_details: val.thoughts_token_count.map(|reasoning| {
+                CompletionTokensDetails {
+                    reasoning_tokens: Some(reasoning),
+                    audio_tokens: None,
+                    accepted_prediction_tokens: None,
+                    rejected_prediction_tokens: None,
+                }
+            }),
+        }
+    }
+}
+
+impl From<GeminiFinishReason> for FinishReason {
+    fn from(val: GeminiFinishReason) -> Self {
+        match val {
+            GeminiFinishReason::MaxTokens => FinishReason::Length,
+            GeminiFinishReason::Safety
+            | GeminiFinishReason::Recitation
+            | GeminiFinishReason::Blocklist
+            | GeminiFinishReason::ProhibitedContent
+            | GeminiFinishReason::Spii
+            | GeminiFinishReason::ImageSafety => FinishReason::ContentFilter,
+            _ => FinishReason::Stop,
+        }
+    }
+}
+
+impl TryFrom<GenerateContentResponse> for ChatCompletionsResponse {
+    type Error = TransformError;
+
+    fn try_from(resp: GenerateContentResponse) -> Result<Self, Self::Error> {
+        let choices = resp
+            .candidates
+            .into_iter()
+            .map(|candidate| {
+                let parts = candidate.content.map(|c| c.parts).unwrap_or_default();
+                let (content, tool_calls) = convert_gemini_parts_to_openai(&parts);
+                let finish_reason = candidate.finish_reason.map(|reason| {
+                    if tool_calls.is_some() {
+                        FinishReason::ToolCalls
+                    } else {
+                        reason.into()
+                    }
+                });
+                Choice {
+                    index: candidate.index,
+                    message: ResponseMessage {
+                        content,
+                        reasoning_content: gemini_thoughts(&parts),
+                        tool_calls,
+                        ..Default::default()
+                    },
+                    finish_reason,
+                    logprobs: candidate.logprobs_result.map(ChoiceLogprobs::from),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        // A blocked prompt comes back with feedback instead of candidates
+        let choices = if choices.is_empty() && resp.prompt_feedback.is_some() {
+            vec![Choice {
+                index: 0,
+                message: ResponseMessage::default(),
+                finish_reason: Some(FinishReason::ContentFilter),
+                logprobs: None,
+            }]
+        } else {
+            choices
+        };
+
+        Ok(ChatCompletionsResponse {
+            id: resp
+                .response_id
+                .unwrap_or_else(|| format!("gemini-{}", uuid::Uuid::new_v4().simple())),
+            object: Some("chat.completion".to_string()),
+            created: current_timestamp(),
+            model: resp
+                .model_version
+                .unwrap_or_else(|| "gemini-model".to_string()),
+            choices,
+            usage: resp.usage_metadata.map(Usage::from).unwrap_or_default(),
+            ..Default::default()
+        })
+    }
+}
+
+/// Gemini thought summaries, which OpenAI carries as `reasoning_content`
+pub(crate) fn gemini_thoughts(parts: &[GeminiPart]) -> Option<String> {
+    let thoughts: String = parts.iter().filter_map(GeminiPart::thought_text).collect();
+    (!thoughts.is_empty()).then_some(thoughts)
+}
+
+/// Split Gemini parts into OpenAI answer text and tool calls. Thought
+/// summaries are left out and calls without an id get a generated one.
+pub(crate) fn convert_gemini_parts_to_openai(
+    parts: &[GeminiPart],
+) -> (Option<String>, Option<Vec<ToolCall>>) {
+    let text: String = parts.iter().filter_map(GeminiPart::answer_text).collect();
+    let tool_calls: Vec<ToolCall> = parts
+        .iter()
+        .filter_map(|part| part.function_call.as_ref())
+        .map(|call| ToolCall {
+            id: call
+                .id
+                .clone()
+                .unwrap_or_else(|| format!("call_{}", uuid::Uuid::new_v4().simple())),
+            call_type: "function".to_string(),
+            function: FunctionCall {
+                name: call.name.clone(),
+                arguments: serde_json::to_string(&call.args).unwrap_or_default(),
+            },
+        })
+        .collect();
+
+    (
+        (!text.is_empty()).then_some(text),
+        (!tool_calls.is_empty()).then_some(tool_calls),
+    )
+}
+
+impl From<CohereUsage> for Usage {
+    fn from(val: CohereUsage) -> Self {
+        let counts = val.tokens.or(val.billed_units).unwrap_or_default();
+        let prompt_tokens = counts.input_tokens.unwrap_or(0.0) as u32;
+        let completion_tokens = counts.output_tokens.unwrap_or(0.0) as u32;
+        Usage {
+            prompt_tokens,
+            completion_tokens,
+            total_tokens: prompt_tokens + completion_tokens,
+            prompt_tokens_details: val.cached_tokens.map(|cached| PromptTokensDetails {
+                cached_tokens: Some(cached as u32),
+                audio_tokens: None,
+            }),
+            completion_tokens_details: None,
+        }
+    }
+}
+
+impl From<CohereFinishReason> for FinishReason {
+    fn from(val: CohereFinishReason) -> Self {
+        match val {
+            CohereFinishReason::MaxTokens => FinishReason::Length,
+            CohereFinishReason::ToolCall => FinishReason::ToolCalls,
+            _ => FinishReason::Stop,
+        }
+    }
+}
+
+impl TryFrom<CohereChatResponse> for ChatCompletionsResponse {
+    type Error = TransformError;
+
+    fn try_from(resp: CohereChatResponse) -> Result<Self, Self::Error> {
+        let text: String = resp
+            .message
+            .content
+            .iter()
+            .flatten()
+            .filter_map(|item| match item {
+                CohereContentItem::Text { text } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect();
+        let tool_calls = resp.message.tool_calls.map(|calls| {
+            calls
+                .into_iter()
+                .map(convert_cohere_tool_call_to_openai)
+                .collect::<Vec<_>>()
+        });
+        // Without answer text, the plan is the closest thing to assistant content
+        let content = if text.is_empty() {
+            resp.message.tool_plan.filter(|_| tool_calls.is_some())
+        } else {
+            Some(text)
+        };
+
+        Ok(ChatCompletionsResponse {
+            id: resp.id,
+            object: Some("chat.completion".to_string()),
+            created: current_timestamp(),
+            // Cohere does not echo the model back
+            model: "cohere-model".to_string(),
+            choices: vec![Choice {
+                index: 0,
+                message: ResponseMessage {
+                    content,
+                    tool_calls,
+                    ..Default::default()
+                },
+                finish_reason: resp.finish_reason.map(FinishReason::from),
+                logprobs: None,
+            }],
+            usage: resp.usage.map(Usage::from).unwrap_or_default(),
+            ..Default::default()
+        })
+    }
+}
+
+pub(crate) fn convert_cohere_tool_call_to_openai(call: CohereToolCall) -> ToolCall {
+    let function = call.function.unwrap_or_default();
+    ToolCall {
+        id: call
+            .id
+            .unwrap_or_else(|| format!("call_{}", uuid::Uuid::new_v4().simple())),
+        call_type: "function".to_string(),
+        function: FunctionCall {
+            name: function.name.unwrap_or_default(),
+            arguments: function.arguments.unwrap_or_default(),
+        },
+    }
+}
+
+/// Usage from Ollama's prompt and generated token counts
+pub(crate) fn ollama_usage_to_openai(resp: &OllamaChatResponse) -> Usage {
+    let prompt_tokens = resp.prompt_eval_count.unwrap_or(0);
+    let completion_tokens = resp.eval_count.unwrap_or(0);
+    Usage {
+        prompt_tokens,
+        completion_tokens,
+        total_tokens: prompt_tokens + completion_tokens,
+        prompt_tokens_details: None,
+        completion_tokens_details: None,
+    }
+}
+
+/// Map an Ollama `done_reason` onto the OpenAI finish reason. Ollama reports
+/// `stop` after tool calls too.
+pub(crate) fn ollama_done_reason_to_openai(
+    done_reason: Option<&str>,
+    has_tool_calls: bool,
+) -> FinishReason {
+    match done_reason {
+        Some("length") => FinishReason::Length,
+        _ if has_tool_calls => FinishReason::ToolCalls,
+        _ => FinishReason::Stop,
+    }
+}
+
+/// Ollama tool calls have no id and take arguments as an object
+pub(crate) fn convert_ollama_tool_call_to_openai(call: OllamaToolCall) -> ToolCall {
+    let arguments = match call.function.arguments {
+        serde_json::Value::Null => "{}".to_string(),
+        serde_json::Value::String(arguments) => arguments,
+        arguments => arguments.to_string(),
+    };
+    ToolCall {
+        id: format!("call_{}", uuid::Uuid::new_v4().simple()),
+        call_type: "function".to_string(),
+        function: FunctionCall {
+            name: call.function.name,
+            arguments,
+        },
+    }
+}
+
+impl TryFrom<OllamaChatResponse> for ChatCompletionsResponse {
+    type Error = TransformError;
+
+    fn try_from(resp: OllamaChatResponse) -> Result<Self, Self::Error> {
+        let usage = ollama_usage_to_openai(&resp);
+        let message = resp
+            .message
+            .ok_or_else(|| TransformError::MissingField("message".to_string()))?;
+        let tool_calls = message
+            .tool_calls
+            .filter(|calls| !calls.is_empty())
+            .map(|calls| {
+                calls
+                    .into_iter()
+                    .map(convert_ollama_tool_call_to_openai)
+                    .collect::<Vec<_>>()
+            });
+        let finish_reason =
+            ollama_done_reason_to_openai(resp.done_reason.as_deref(), tool_calls.is_some());
+
+        Ok(ChatCompletionsResponse {
+            id: format!("ollama-{}", uuid::Uuid::new_v4().simple()),
+            object: Some("chat.completion".to_string()),
+            created: current_timestamp(),
+            model: resp.model,
+            choices: vec![Choice {
+                index: 0,
+                message: ResponseMessage {
+                    content: Some(message.content).filter(|content| !content.is_empty()),
+                    reasoning_content: message.thinking.filter(|thinking| !thinking.is_empty()),
+                    tool_calls,
+                    ..Default::default()
+                },
+                finish_reason: Some(finish_reason),
+                logprobs: None,
+            }],
+            usage,
+            ..Default::default()
+        })
+    }
+}
+
+/// Map a Titan Text `completionReason` onto the OpenAI finish reason
+pub(crate) fn titan_completion_reason_to_openai(reason: &str) -> FinishReason {
+    match reason {
+        "LENGTH" => FinishReason::Length,
+        "CONTENT_FILTERED" => FinishReason::ContentFilter,
+        _ => FinishReason::Stop,
+    }
+}
+
+impl TryFrom<TitanTextResponse> for ChatCompletionsResponse {
+    type Error = TransformError;
+
+    fn try_from(resp: TitanTextResponse) -> Result<Self, Self::Error> {
+        let result = resp
+            .results
+            .into_iter()
+            .next()
+            .ok_or_else(|| TransformError::MissingField("results".to_string()))?;
+        let prompt_tokens = resp.input_text_token_count.unwrap_or(0);
+        let completion_tokens = result.token_count.unwrap_or(0);
+
+        Ok(ChatCompletionsResponse {
+            id: format!("bedrock-{}", uuid::Uuid::new_v4().simple()),
+            object: Some("chat.completion".to_string()),
+            created: current_timestamp(),
+            // InvokeModel does not echo the model back
+            model: "bedrock-model".to_string(),
+            choices: vec![Choice {
+                index: 0,
+                message: ResponseMessage {
+                    content: Some(result.output_text),
+                    ..Default::default()
+                },
+                finish_reason: Some(
+                    result
+                        .completion_reason
+                        .as_deref()
+                        .map(titan_completion_reason_to_openai)
+                        .unwrap_or(FinishReason::Stop),
+                ),
+                logprobs: None,
+            }],
+            usage: Usage {
+                prompt_tokens,
+                completion_tokens,
+                total_tokens: prompt_tokens + completion_tokens,
+                prompt_tokens_details: None,
+                completion_tokens_details: None,
+            },
+            ..Default::default()
+        })
+    }
+}
+
+impl TryFrom<InvokeModelResponse> for ChatCompletionsResponse {
+    type Error = TransformError;
+
+    fn try_from(resp: InvokeModelResponse) -> Result<Self, Self::Error> {
+        match resp {
+            InvokeModelResponse::Anthropic(messages) => messages.try_into(),
+            InvokeModelResponse::TitanText(titan) => titan.try_into(),
+        }
+    }
+}
+
 /// Convert Bedrock Message to OpenAI content and tool calls
 /// This function extracts text content and tool calls from a Bedrock message
 fn convert_bedrock_message_to_openai(
     message: &crate::apis::amazon_bedrock::Message,
 ) -> Result<(Option<String>, Option<Vec<crate::apis::openai::ToolCall>>), TransformError> {
     use crate::apis::amazon_bedrock::ContentBlock;
-    use crate::apis::openai::{FunctionCall, ToolCall};
 
     let mut text_content = String::new();
     let mut tool_calls = Vec::new();
@@ -387,28 +902,172 @@ fn convert_bedrock_message_to_openai(
     Ok((content, tool_calls))
 }
 
-/// Convert Anthropic content blocks to OpenAI message content
+/// Convert Anthropic content blocks to OpenAI message content, along with
+/// annotations for the citations on it
 fn convert_anthropic_content_to_openai(
     content: &[MessagesContentBlock],
-) -> Result<MessageContent, TransformError> {
-    let mut text_parts = Vec::new();
+) -> Result<(MessageContent, Vec<serde_json::Value>), TransformError> {
+    let mut text = String::new();
+    let mut annotations = Vec::new();
+    let mut chars = 0;
+    let mut previous_cited = None;
 
     for block in content {
-        match block {
-            MessagesContentBlock::Text { text, .. } => {
-                text_parts.push(text.clone());
-            }
-            MessagesContentBlock::Thinking { thinking, .. } => {
-                text_parts.push(format!("thinking: {}", thinking));
-            }
-            _ => {
-                // Skip other content types for basic text conversion
-                continue;
+        let MessagesContentBlock::Text {
+            text: part,
+            citations,
+            ..
+        } = block
+        else {
+            // Skip other content types for basic text conversion
+            continue;
+        };
+        // A cited answer comes split into blocks around the cited spans,
+        // which only read right joined as they are
+        let cited = citations.as_ref().is_some_and(|c| !c.is_empty());
+        if previous_cited == Some(false) && !cited {
+            text.push('\n');
+            chars += 1;
+        }
+        let start = chars;
+        text.push_str(part);
+        chars += part.chars().count();
+        for citation in citations.iter().flatten() {
+            annotations.push(citation_annotation(citation, Some((start, chars))));
+        }
+        previous_cited = Some(cited);
+    }
+
+    Ok((MessageContent::Text(text), annotations))
+}
+
+/// The Responses API built-in tool call an Anthropic server tool use stands
+/// for, completed with its result block from `content` when there is one.
+/// Server tools without a built-in counterpart give `None`.
+pub fn server_tool_call(
+    id: &str,
+    name: &str,
+    input: &serde_json::Value,
+    content: &[MessagesContentBlock],
+) -> Option<OutputItem> {
+    let result = content.iter().find_map(|block| match block {
+        MessagesContentBlock::WebSearchToolResult {
+            tool_use_id,
+            content,
+            is_error,
+        }
+        | MessagesContentBlock::CodeExecutionToolResult {
+            tool_use_id,
+            content,
+            is_error,
+        } if tool_use_id == id => Some((content, is_error.unwrap_or(false))),
+        _ => None,
+    });
+    // Errors come back as a result whose content is an `*_tool_result_error`
+    let status = match result {
+        Some((content, is_error)) => {
+            let failed = is_error
+                || content
+                    .get("type")
+                    .and_then(|t| t.as_str())
+                    .is_some_and(|t| t.ends_with("_error"));
+            if failed {
+                OutputItemStatus::Incomplete
+            } else {
+                OutputItemStatus::Completed
             }
         }
+        None => OutputItemStatus::InProgress,
+    };
+    let mut call = match name {
+        "web_search" => OutputItem::WebSearchCall {
+            id: id.to_string(),
+            status,
+            action: None,
+        },
+        "code_execution" | "bash_code_execution" => OutputItem::CodeInterpreterCall {
+            id: id.to_string(),
+            status,
+            code: None,
+            outputs: result.map(|(content, _)| {
+                ["stdout", "stderr"]
+                    .iter()
+                    .filter_map(|key| content.get(*key).and_then(|v| v.as_str()))
+                    .filter(|logs| !logs.is_empty())
+                    .map(|logs| CodeInterpreterOutput::Logs {
+                        logs: logs.to_string(),
+                    })
+                    .collect()
+            }),
+        },
+        _ => return None,
+    };
+    fill_server_tool_input(&mut call, input);
+    Some(call)
+}
+
+/// Fill in the search query or code of a built-in call from the input of the
+/// server tool use behind it, which streams after the call starts.
+pub fn fill_server_tool_input(call: &mut OutputItem, input: &serde_json::Value) {
+    match call {
+        OutputItem::WebSearchCall { action, .. } => {
+            *action = Some(serde_json::json!({
+                "type": "search",
+                "query": input.get("query"),
+            }));
+        }
+        OutputItem::CodeInterpreterCall { code, .. } => {
+            *code = ["code", "command"]
+                .iter()
+                .find_map(|key| input.get(*key).and_then(|v| v.as_str()))
+                .map(str::to_string);
+        }
+        _ => {}
     }
+}
 
-    Ok(MessageContent::Text(text_parts.join("\n")))
+/// The built-in tool call a server tool result block completes, without the
+/// input of the call, which streams separately.
+pub fn server_tool_result_call(block: &MessagesContentBlock) -> Option<OutputItem> {
+    let (tool_use_id, name) = match block {
+        MessagesContentBlock::WebSearchToolResult { tool_use_id, .. } => {
+            (tool_use_id, "web_search")
+        }
+        MessagesContentBlock::CodeExecutionToolResult { tool_use_id, .. } => {
+            (tool_use_id, "code_execution")
+        }
+        _ => return None,
+    };
+    server_tool_call(
+        tool_use_id,
+        name,
+        &serde_json::Value::Null,
+        std::slice::from_ref(block),
+    )
+}
+
+/// Put back the built-in tool calls behind the function calls that Anthropic
+/// server tool uses in `content` became on the way through ChatCompletions,
+/// so clients are not asked to run tools that already ran.
+pub fn restore_server_tool_calls(
+    response: &mut ResponsesAPIResponse,
+    content: &[MessagesContentBlock],
+) {
+    for block in content {
+        let MessagesContentBlock::ServerToolUse { id, name, input } = block else {
+            continue;
+        };
+        let Some(call) = server_tool_call(id, name, input, content) else {
+            continue;
+        };
+        if let Some(item) = response
+            .output
+            .iter_mut()
+            .find(|item| matches!(item, OutputItem::FunctionCall { call_id, .. } if call_id == id))
+        {
+            *item = call;
+        }
+    }
 }
 
 #[cfg(test)]
@@ -965,11 +1624,13 @@ mod tests {
                 message: crate::apis::openai::ResponseMessage {
                     role: Role::Assistant,
                     content: Some("Hello! How can I help you?".to_string()),
+                    reasoning_content: None,
                     refusal: None,
                     annotations: None,
                     audio: None,
                     function_call: None,
                     tool_calls: None,
+                    thinking_blocks: None,
                 },
                 finish_reason: Some(FinishReason::Stop),
                 logprobs: None,
@@ -983,6 +1644,8 @@ mod tests {
             },
             system_fingerprint: None,
             service_tier: Some("default".to_string()),
+            citations: None,
+            search_results: None,
             metadata: None,
         };
 
@@ -1039,6 +1702,7 @@ mod tests {
                 message: crate::apis::openai::ResponseMessage {
                     role: Role::Assistant,
                     content: Some("Let me check the weather.".to_string()),
+                    reasoning_content: None,
                     refusal: None,
                     annotations: None,
                     audio: None,
@@ -1051,6 +1715,7 @@ mod tests {
                             arguments: r#"{"location":"San Francisco"}"#.to_string(),
                         },
                     }]),
+                    thinking_blocks: None,
                 },
                 finish_reason: Some(FinishReason::ToolCalls),
                 logprobs: None,
@@ -1064,6 +1729,8 @@ mod tests {
             },
             system_fingerprint: None,
             service_tier: None,
+            citations: None,
+            search_results: None,
             metadata: None,
         };
 
@@ -1112,6 +1779,7 @@ mod tests {
                 message: crate::apis::openai::ResponseMessage {
                     role: Role::Assistant,
                     content: None, // No text content, only tool calls
+                    reasoning_content: None,
                     refusal: None,
                     annotations: None,
                     audio: None,
@@ -1124,6 +1792,7 @@ mod tests {
                             arguments: r#"{"location":"San Francisco, CA"}"#.to_string(),
                         },
                     }]),
+                    thinking_blocks: None,
                 },
                 finish_reason: Some(FinishReason::ToolCalls),
                 logprobs: None,
@@ -1137,6 +1806,8 @@ mod tests {
             },
             system_fingerprint: Some("fp_7eeb46f068".to_string()),
             service_tier: Some("default".to_string()),
+            citations: None,
+            search_results: None,
             metadata: None,
         };
 
@@ -1166,4 +1837,387 @@ mod tests {
             crate::apis::openai_responses::ResponseStatus::Completed
         ));
     }
+
+    #[test]
+    fn test_gemini_to_openai_response() {
+        let gemini_response: crate::apis::gemini::GenerateContentResponse =
+            serde_json::from_value(json!({
+                "candidates": [{
+                    "content": {"role": "model", "parts": [
+                        {"text": "Let me think", "thought": true},
+                        {"text": "Checking the weather."},
+                        {"functionCall": {"name": "get_weather", "args": {"city": "Paris"}}}
+                    ]},
+                    "finishReason": "STOP"
+                }],
+                "usageMetadata": {
+                    "promptTokenCount": 10,
+                    "candidatesTokenCount": 6,
+                    "thoughtsTokenCount": 4,
+                    "totalTokenCount": 20
+                },
+                "modelVersion": "gemini-2.5-flash",
+                "responseId": "resp-1"
+            }))
+            .unwrap();
+
+        let openai_response: ChatCompletionsResponse = gemini_response.try_into().unwrap();
+        assert_eq!(openai_response.id, "resp-1");
+        assert_eq!(openai_response.model, "gemini-2.5-flash");
+        let choice = &openai_response.choices[0];
+        assert_eq!(choice.message.role, Role::Assistant);
+        assert_eq!(
+            choice.message.content.as_deref(),
+            Some("Checking the weather.")
+        );
+        assert_eq!(
+            choice.message.reasoning_content.as_deref(),
+            Some("Let me think")
+        );
+        assert_eq!(choice.finish_reason, Some(FinishReason::ToolCalls));
+        let tool_call = &choice.message.tool_calls.as_ref().unwrap()[0];
+        assert_eq!(tool_call.function.name, "get_weather");
+        assert_eq!(tool_call.function.arguments, r#"{"city":"Paris"}"#);
+        assert!(tool_call.id.starts_with("call_"));
+
+        assert_eq!(openai_response.usage.prompt_tokens, 10);
+        assert_eq!(openai_response.usage.completion_tokens, 10);
+        assert_eq!(openai_response.usage.total_tokens, 20);
+    }
+
+    #[test]
+    fn test_gemini_logprobs_map_to_openai_and_responses() {
+        let gemini_response: crate::apis::gemini::GenerateContentResponse =
+            serde_json::from_value(json!({
+                "candidates": [{
+                    "content": {"role": "model", "parts": [{"text": "Hi there"}]},
+                    "finishReason": "STOP",
+                    "avgLogprobs": -0.3,
+                    "logprobsResult": {
+                        "topCandidates": [
+                            {"candidates": [
+                                {"token": "Hi", "tokenId": 1, "logProbability": -0.1},
+                                {"token": "Hello", "tokenId": 2, "logProbability": -2.5}
+                            ]},
+                            {"candidates": [{"token": " there", "tokenId": 3, "logProbability": -0.5}]}
+                        ],
+                        "chosenCandidates": [
+                            {"token": "Hi", "tokenId": 1, "logProbability": -0.1},
+                            {"token": " there", "tokenId": 3, "logProbability": -0.5}
+                        ]
+                    }
+                }],
+                "modelVersion": "gemini-2.5-flash"
+            }))
+            .unwrap();
+
+        let openai_response: ChatCompletionsResponse = gemini_response.try_into().unwrap();
+        let logprobs = openai_response.choices[0].logprobs.clone().unwrap();
+        let tokens = logprobs.content.unwrap();
+        assert_eq!(tokens.len(), 2);
+        assert_eq!(tokens[0].token, "Hi");
+        assert_eq!(tokens[0].logprob, -0.1);
+        assert_eq!(tokens[0].bytes.as_deref(), Some("Hi".as_bytes()));
+        assert_eq!(tokens[0].top_logprobs.len(), 2);
+        assert_eq!(tokens[0].top_logprobs[1].token, "Hello");
+        assert_eq!(tokens[1].top_logprobs[0].logprob, -0.5);
+
+        let responses_api: ResponsesAPIResponse = openai_response.try_into().unwrap();
+        let OutputItem::Message { content, .. } = &responses_api.output[0] else {
+            panic!("expected a message output item");
+        };
+        let crate::apis::openai_responses::OutputContent::OutputText { logprobs, .. } = &content[0]
+        else {
+            panic!("expected output text");
+        };
+        let logprobs = logprobs.as_ref().unwrap();
+        assert_eq!(logprobs[1].token, " there");
+        assert_eq!(logprobs[0].top_logprobs[1].token, "Hello");
+    }
+
+    #[test]
+    fn test_gemini_blocked_prompt_maps_to_content_filter() {
+        let gemini_response: crate::apis::gemini::GenerateContentResponse =
+            serde_json::from_value(json!({
+                "promptFeedback": {"blockReason": "SAFETY"},
+                "usageMetadata": {"promptTokenCount": 8, "totalTokenCount": 8}
+            }))
+            .unwrap();
+
+        let openai_response: ChatCompletionsResponse = gemini_response.try_into().unwrap();
+        assert_eq!(openai_response.choices.len(), 1);
+        assert_eq!(
+            openai_response.choices[0].finish_reason,
+            Some(FinishReason::ContentFilter)
+        );
+        assert_eq!(openai_response.choices[0].message.content, None);
+    }
+
+    #[test]
+    fn test_cohere_to_openai_response() {
+        let cohere_response: crate::apis::cohere::CohereChatResponse =
+            serde_json::from_value(json!({
+                "id": "c14c80c3-18eb-4519-9460-6c92edd8cfb4",
+                "finish_reason": "TOOL_CALL",
+                "message": {
+                    "role": "assistant",
+                    "tool_plan": "I will look up the weather in Paris.",
+                    "tool_calls": [{
+                        "id": "get_weather_1byjy32y4hvq",
+                        "type": "function",
+                        "function": {"name": "get_weather", "arguments": "{\"city\":\"Paris\"}"}
+                    }]
+                },
+                "usage": {
+                    "billed_units": {"input_tokens": 9, "output_tokens": 11},
+                    "tokens": {"input_tokens": 1024, "output_tokens": 52}
+                }
+            }))
+            .unwrap();
+
+        let openai_response: ChatCompletionsResponse = cohere_response.try_into().unwrap();
+        assert_eq!(openai_response.id, "c14c80c3-18eb-4519-9460-6c92edd8cfb4");
+        let choice = &openai_response.choices[0];
+        assert_eq!(
+            choice.message.content.as_deref(),
+            Some("I will look up the weather in Paris.")
+        );
+        assert_eq!(choice.finish_reason, Some(FinishReason::ToolCalls));
+        let tool_call = &choice.message.tool_calls.as_ref().unwrap()[0];
+        assert_eq!(tool_call.id, "get_weather_1byjy32y4hvq");
+        assert_eq!(tool_call.function.arguments, r#"{"city":"Paris"}"#);
+
+        assert_eq!(openai_response.usage.prompt_tokens, 1024);
+        assert_eq!(openai_response.usage.completion_tokens, 52);
+        assert_eq!(openai_response.usage.total_tokens, 1076);
+    }
+
+    #[test]
+    fn test_ollama_to_openai_response() {
+        let ollama_response: crate::apis::ollama::OllamaChatResponse =
+            serde_json::from_value(json!({
+                "model": "llama3.2",
+                "created_at": "2025-01-01T00:00:00Z",
+                "message": {
+                    "role": "assistant",
+                    "content": "",
+                    "tool_calls": [{
+                        "function": {"name": "get_weather", "arguments": {"city": "Paris"}}
+                    }]
+                },
+                "done": true,
+                "done_reason": "stop",
+                "prompt_eval_count": 26,
+                "eval_count": 14
+            }))
+            .unwrap();
+
+        let openai_response: ChatCompletionsResponse = ollama_response.try_into().unwrap();
+        assert_eq!(openai_response.model, "llama3.2");
+        let choice = &openai_response.choices[0];
+        assert_eq!(choice.message.content, None);
+        assert_eq!(choice.finish_reason, Some(FinishReason::ToolCalls));
+        let tool_call = &choice.message.tool_calls.as_ref().unwrap()[0];
+        assert!(tool_call.id.starts_with("call_"));
+        assert_eq!(tool_call.function.name, "get_weather");
+        assert_eq!(tool_call.function.arguments, r#"{"city":"Paris"}"#);
+        assert_eq!(openai_response.usage.total_tokens, 40);
+    }
+
+    #[test]
+    fn test_bedrock_invoke_model_to_openai_response() {
+        let titan: crate::apis::amazon_bedrock::InvokeModelResponse =
+            serde_json::from_value(json!({
+                "inputTextTokenCount": 5,
+                "results": [{
+                    "tokenCount": 64,
+                    "outputText": "Why did the chicken cross the road?",
+                    "completionReason": "LENGTH"
+                }]
+            }))
+            .unwrap();
+        let openai_response: ChatCompletionsResponse = titan.try_into().unwrap();
+        let choice = &openai_response.choices[0];
+        assert_eq!(
+            choice.message.content.as_deref(),
+            Some("Why did the chicken cross the road?")
+        );
+        assert_eq!(choice.finish_reason, Some(FinishReason::Length));
+        assert_eq!(openai_response.usage.total_tokens, 69);
+
+        let claude: crate::apis::amazon_bedrock::InvokeModelResponse =
+            serde_json::from_value(json!({
+                "id": "msg_bdrk_01",
+                "type": "message",
+                "role": "assistant",
+                "model": "claude-3-5-haiku-20241022",
+                "content": [{"type": "text", "text": "Hello!"}],
+                "stop_reason": "end_turn",
+                "stop_sequence": null,
+                "usage": {"input_tokens": 10, "output_tokens": 3}
+            }))
+            .unwrap();
+        let openai_response: ChatCompletionsResponse = claude.try_into().unwrap();
+        assert_eq!(openai_response.id, "msg_bdrk_01");
+        assert_eq!(
+            openai_response.choices[0].message.content.as_deref(),
+            Some("Hello!")
+        );
+        assert_eq!(openai_response.usage.prompt_tokens, 10);
+    }
+
+    #[test]
+    fn test_anthropic_cache_usage_to_openai() {
+        use crate::providers::response::TokenUsage;
+
+        let anthropic: MessagesResponse = serde_json::from_value(json!({
+            "id": "msg_01",
+            "type": "message",
+            "role": "assistant",
+            "model": "claude-sonnet-4-5",
+            "content": [{"type": "text", "text": "Hi"}],
+            "stop_reason": "end_turn",
+            "stop_sequence": null,
+            "usage": {
+                "input_tokens": 20,
+                "output_tokens": 10,
+                "cache_creation_input_tokens": 100,
+                "cache_read_input_tokens": 900
+            }
+        }))
+        .unwrap();
+        let usage = ChatCompletionsResponse::try_from(anthropic).unwrap().usage;
+        assert_eq!(usage.prompt_tokens, 1020);
+        assert_eq!(usage.total_tokens, 1030);
+        assert_eq!(usage.cached_input_tokens(), Some(900));
+    }
+
+    #[test]
+    fn test_anthropic_server_tools_restore_built_in_calls() {
+        let anthropic: MessagesResponse = serde_json::from_value(json!({
+            "id": "msg_01",
+            "type": "message",
+            "role": "assistant",
+            "model": "claude-sonnet-4-5",
+            "content": [
+                {"type": "server_tool_use", "id": "srvtoolu_1", "name": "web_search",
+                 "input": {"query": "rust 2024 edition"}},
+                {"type": "web_search_tool_result", "tool_use_id": "srvtoolu_1",
+                 "content": [{"type": "web_search_result", "url": "https://doc.rust-lang.org",
+                              "title": "Rust", "encrypted_content": "abc"}]},
+                {"type": "server_tool_use", "id": "srvtoolu_2", "name": "code_execution",
+                 "input": {"code": "print(2 + 2)"}},
+                {"type": "code_execution_tool_result", "tool_use_id": "srvtoolu_2",
+                 "content": {"type": "code_execution_result", "stdout": "4\n",
+                             "stderr": "", "return_code": 0, "content": []}},
+                {"type": "text", "text": "Done."}
+            ],
+            "stop_reason": "end_turn",
+            "stop_sequence": null,
+            "usage": {"input_tokens": 10, "output_tokens": 5}
+        }))
+        .unwrap();
+        let content = anthropic.content.clone();
+        let chat: ChatCompletionsResponse = anthropic.try_into().unwrap();
+        let mut response: ResponsesAPIResponse = chat.try_into().unwrap();
+        restore_server_tool_calls(&mut response, &content);
+
+        assert!(!response
+            .output
+            .iter()
+            .any(|item| matches!(item, OutputItem::FunctionCall { .. })));
+        assert!(response.output.iter().any(|item| matches!(
+            item,
+            OutputItem::WebSearchCall { id, status: OutputItemStatus::Completed, action: Some(action) }
+                if id == "srvtoolu_1" && action["query"] == "rust 2024 edition"
+        )));
+        let code_call = response
+            .output
+            .iter()
+            .find(|item| matches!(item, OutputItem::CodeInterpreterCall { .. }))
+            .unwrap();
+        let OutputItem::CodeInterpreterCall { code, outputs, .. } = code_call else {
+            unreachable!()
+        };
+        assert_eq!(code.as_deref(), Some("print(2 + 2)"));
+        assert!(matches!(
+            outputs.as_deref(),
+            Some([CodeInterpreterOutput::Logs { logs }]) if logs == "4\n"
+        ));
+    }
+
+    #[test]
+    fn test_anthropic_citations_become_annotations() {
+        use crate::apis::anthropic::MessagesStreamEvent;
+        use crate::apis::openai::ChatCompletionsStreamResponse;
+
+        let web_citation = json!({
+            "type": "web_search_result_location",
+            "cited_text": "The sky is blue.",
+            "url": "https://example.com/sky",
+            "title": "Sky",
+            "encrypted_index": "enc_1"
+        });
+        let anthropic: MessagesResponse = serde_json::from_value(json!({
+            "id": "msg_01",
+            "type": "message",
+            "role": "assistant",
+            "model": "claude-sonnet-4-5",
+            "content": [
+                {"type": "text", "text": "The sky is "},
+                {"type": "text", "text": "blue", "citations": [web_citation]},
+                {"type": "text", "text": " and grass is "},
+                {"type": "text", "text": "green", "citations": [{
+                    "type": "char_location",
+                    "cited_text": "Grass is green.",
+                    "document_index": 0,
+                    "document_title": "Plants",
+                    "start_char_index": 0,
+                    "end_char_index": 15
+                }]}
+            ],
+            "stop_reason": "end_turn",
+            "stop_sequence": null,
+            "usage": {"input_tokens": 20, "output_tokens": 10}
+        }))
+        .unwrap();
+
+        let message = ChatCompletionsResponse::try_from(anthropic)
+            .unwrap()
+            .choices[0]
+            .message
+            .clone();
+        assert_eq!(
+            message.content.as_deref(),
+            Some("The sky is blue and grass is green")
+        );
+        let annotations = message.annotations.unwrap();
+        assert_eq!(
+            annotations[0],
+            json!({"type": "url_citation", "url_citation": {
+                "url": "https://example.com/sky",
+                "title": "Sky",
+                "start_index": 11,
+                "end_index": 15
+            }})
+        );
+        assert_eq!(annotations[1]["type"], "citation");
+        assert_eq!(annotations[1]["citation"]["type"], "char_location");
+        assert_eq!(annotations[1]["citation"]["document_title"], "Plants");
+        assert_eq!(annotations[1]["citation"]["start_index"], 29);
+        assert_eq!(annotations[1]["citation"]["end_index"], 34);
+
+        let event: MessagesStreamEvent = serde_json::from_value(json!({
+            "type": "content_block_delta",
+            "index": 1,
+            "delta": {"type": "citations_delta", "citation": web_citation}
+        }))
+        .unwrap();
+        let chunk = ChatCompletionsStreamResponse::try_from(event).unwrap();
+        let annotations = chunk.choices[0].delta.annotations.as_ref().unwrap();
+        assert_eq!(
+            annotations[0]["url_citation"]["url"],
+            "https://example.com/sky"
+        );
+    }
 }
@@ -1,2 +1,3 @@
 pub mod to_anthropic_streaming;
+pub mod to_bedrock_streaming;
 pub mod to_openai_streaming;
@@ -38,6 +38,18 @@ impl TryFrom<ChatCompletionsStreamResponse> for MessagesStreamEvent {
         // sends both role and content in the same chunk - we can only return one event here,
         // so we prioritize the content and let the buffer handle lifecycle events.
 
+        // Handle reasoning delta; the buffer opens a thinking block for it
+        if let Some(reasoning) = &choice.delta.reasoning_content {
+            if !reasoning.is_empty() {
+                return Ok(MessagesStreamEvent::ContentBlockDelta {
+                    index: 0,
+                    delta: MessagesContentDelta::ThinkingDelta {
+                        thinking: reasoning.clone(),
+                    },
+                });
+            }
+        }
+
         // Handle content delta (even if role is present in the same chunk)
         if let Some(content) = &choice.delta.content {
             if !content.is_empty() {
@@ -0,0 +1,119 @@
+use crate::apis::amazon_bedrock::{
+    BedrockTokenUsage, ContentBlockDelta, ContentBlockDeltaEvent, ContentBlockStart,
+    ContentBlockStartEvent, ContentBlockStopEvent, ConversationRole, ConverseStreamEvent,
+    ConverseStreamMetadataEvent, MessageStartEvent, MessageStopEvent, StopReason, ToolUseDelta,
+    ToolUseStart,
+};
+use crate::apis::anthropic::{
+    MessagesContentBlock, MessagesContentDelta, MessagesRole, MessagesStopReason,
+    MessagesStreamEvent,
+};
+
+impl From<MessagesStopReason> for StopReason {
+    fn from(reason: MessagesStopReason) -> Self {
+        match reason {
+            MessagesStopReason::EndTurn | MessagesStopReason::PauseTurn => StopReason::EndTurn,
+            MessagesStopReason::MaxTokens => StopReason::MaxTokens,
+            MessagesStopReason::StopSequence => StopReason::StopSequence,
+            MessagesStopReason::ToolUse => StopReason::ToolUse,
+            MessagesStopReason::Refusal => StopReason::ContentFiltered,
+        }
+    }
+}
+
+/// Converts an Anthropic Messages stream into ConverseStream events.
+///
+/// Anthropic reports input tokens on `message_start` and output tokens on
+/// `message_delta`, while ConverseStream reports both on its closing
+/// `metadata` event, so the input side is carried across events.
+#[derive(Debug, Default)]
+pub struct MessagesToConverseStream {
+    usage: BedrockTokenUsage,
+}
+
+impl MessagesToConverseStream {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The ConverseStream events for one Messages event. Text and thinking
+    /// block starts, thinking deltas, `message_stop` and pings have no
+    /// ConverseStream counterpart and produce nothing.
+    pub fn convert(&mut self, event: MessagesStreamEvent) -> Vec<ConverseStreamEvent> {
+        match event {
+            MessagesStreamEvent::MessageStart { message } => {
+                self.usage.input_tokens = message.usage.input_tokens;
+                self.usage.cache_read_input_tokens = message.usage.cache_read_input_tokens;
+                self.usage.cache_write_input_tokens = message.usage.cache_creation_input_tokens;
+                let role = match message.role {
+                    MessagesRole::User => ConversationRole::User,
+                    MessagesRole::Assistant => ConversationRole::Assistant,
+                };
+                vec![ConverseStreamEvent::MessageStart(MessageStartEvent {
+                    role,
+                })]
+            }
+            MessagesStreamEvent::ContentBlockStart {
+                index,
+                content_block: MessagesContentBlock::ToolUse { id, name, .. },
+            } => vec![ConverseStreamEvent::ContentBlockStart(
+                ContentBlockStartEvent {
+                    content_block_index: index as i32,
+                    start: ContentBlockStart::ToolUse {
+                        tool_use: ToolUseStart {
+                            tool_use_id: id,
+                            name,
+                        },
+                    },
+                },
+            )],
+            MessagesStreamEvent::ContentBlockDelta { index, delta } => {
+                let delta = match delta {
+                    MessagesContentDelta::TextDelta { text } => ContentBlockDelta::Text { text },
+                    MessagesContentDelta::InputJsonDelta { partial_json } => {
+                        ContentBlockDelta::ToolUse {
+                            tool_use: ToolUseDelta {
+                                input: partial_json,
+                            },
+                        }
+                    }
+                    _ => return Vec::new(),
+                };
+                vec![ConverseStreamEvent::ContentBlockDelta(
+                    ContentBlockDeltaEvent {
+                        content_block_index: index as i32,
+                        delta,
+                    },
+                )]
+            }
+            MessagesStreamEvent::ContentBlockStop { index } => {
+                vec![ConverseStreamEvent::ContentBlockStop(
+                    ContentBlockStopEvent {
+                        content_block_index: index as i32,
+                    },
+                )]
+            }
+            MessagesStreamEvent::MessageDelta { delta, usage } => {
+                // message_delta may repeat the input side; prefer it when present
+                if usage.input_tokens > 0 {
+                    self.usage.input_tokens = usage.input_tokens;
+                }
+                self.usage.output_tokens = usage.output_tokens;
+                self.usage.total_tokens = self.usage.input_tokens + self.usage.output_tokens;
+                vec![
+                    ConverseStreamEvent::MessageStop(MessageStopEvent {
+                        stop_reason: delta.stop_reason.into(),
+                        additional_model_response_fields: None,
+                    }),
+                    ConverseStreamEvent::Metadata(Box::new(ConverseStreamMetadataEvent {
+                        usage: self.usage.clone(),
+                        metrics: None,
+                        trace: None,
+                        performance_config: None,
+                    })),
+                ]
+            }
+            _ => Vec::new(),
+        }
+    }
+}
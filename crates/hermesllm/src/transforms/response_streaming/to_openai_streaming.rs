@@ -1,15 +1,25 @@
-use crate::apis::amazon_bedrock::{ConverseStreamEvent, StopReason};
+use crate::apis::amazon_bedrock::{ConverseStreamEvent, InvokeModelStreamChunk, StopReason};
 use crate::apis::anthropic::{
     MessagesContentBlock, MessagesContentDelta, MessagesStopReason, MessagesStreamEvent,
 };
+use crate::apis::cohere::{CohereStreamEvent, CohereStreamEventType};
+use crate::apis::gemini::GenerateContentResponse;
+use crate::apis::ollama::OllamaChatResponse;
 use crate::apis::openai::{
-    ChatCompletionsStreamResponse, FinishReason, FunctionCallDelta, MessageDelta, Role,
-    StreamChoice, ToolCallDelta, Usage,
+    ChatCompletionsStreamResponse, ChoiceLogprobs, FinishReason, FunctionCallDelta, MessageDelta,
+    Role, StreamChoice, ThinkingBlock, ToolCallDelta, Usage,
+};
+use crate::apis::openai_responses::{
+    IncompleteDetails, IncompleteReason, ResponseUsage, ResponsesAPIResponse,
+    ResponsesAPIStreamEvent,
 };
-use crate::apis::openai_responses::ResponsesAPIStreamEvent;
 
 use crate::clients::TransformError;
 use crate::transforms::lib::*;
+use crate::transforms::response::to_openai::{
+    convert_ollama_tool_call_to_openai, ollama_done_reason_to_openai, ollama_usage_to_openai,
+    server_tool_call, server_tool_result_call, titan_completion_reason_to_openai,
+};
 
 // ============================================================================
 // PROVIDER STREAMING TRANSFORMATIONS TO OPENAI FORMAT
@@ -52,9 +62,12 @@ impl TryFrom<MessagesStreamEvent> for ChatCompletionsStreamResponse {
                 MessageDelta {
                     role: Some(Role::Assistant),
                     content: None,
+                    reasoning_content: None,
                     refusal: None,
+                    annotations: None,
                     function_call: None,
                     tool_calls: None,
+                    thinking_blocks: None,
                 },
                 None,
                 None,
@@ -81,9 +94,12 @@ impl TryFrom<MessagesStreamEvent> for ChatCompletionsStreamResponse {
                     MessageDelta {
                         role: None,
                         content: None,
+                        reasoning_content: None,
                         refusal: None,
+                        annotations: None,
                         function_call: None,
                         tool_calls: None,
+                        thinking_blocks: None,
                     },
                     finish_reason,
                     openai_usage,
@@ -96,9 +112,12 @@ impl TryFrom<MessagesStreamEvent> for ChatCompletionsStreamResponse {
                 MessageDelta {
                     role: None,
                     content: None,
+                    reasoning_content: None,
                     refusal: None,
+                    annotations: None,
                     function_call: None,
                     tool_calls: None,
+                    thinking_blocks: None,
                 },
                 Some(FinishReason::Stop),
                 None,
@@ -113,6 +132,8 @@ impl TryFrom<MessagesStreamEvent> for ChatCompletionsStreamResponse {
                 usage: None,
                 system_fingerprint: None,
                 service_tier: None,
+                citations: None,
+                search_results: None,
             }),
         }
     }
@@ -135,9 +156,12 @@ impl TryFrom<ConverseStreamEvent> for ChatCompletionsStreamResponse {
                     MessageDelta {
                         role: Some(role),
                         content: None,
+                        reasoning_content: None,
                         refusal: None,
+                        annotations: None,
                         function_call: None,
                         tool_calls: None,
+                        thinking_blocks: None,
                     },
                     None,
                     None,
@@ -154,7 +178,9 @@ impl TryFrom<ConverseStreamEvent> for ChatCompletionsStreamResponse {
                         MessageDelta {
                             role: None,
                             content: None,
+                            reasoning_content: None,
                             refusal: None,
+                            annotations: None,
                             function_call: None,
                             tool_calls: Some(vec![ToolCallDelta {
                                 index: start_event.content_block_index as u32,
@@ -165,6 +191,7 @@ impl TryFrom<ConverseStreamEvent> for ChatCompletionsStreamResponse {
                                     arguments: Some("".to_string()),
                                 }),
                             }]),
+                            thinking_blocks: None,
                         },
                         None,
                         None,
@@ -182,9 +209,12 @@ impl TryFrom<ConverseStreamEvent> for ChatCompletionsStreamResponse {
                         MessageDelta {
                             role: None,
                             content: Some(text),
+                            reasoning_content: None,
                             refusal: None,
+                            annotations: None,
                             function_call: None,
                             tool_calls: None,
+                            thinking_blocks: None,
                         },
                         None,
                         None,
@@ -195,7 +225,9 @@ impl TryFrom<ConverseStreamEvent> for ChatCompletionsStreamResponse {
                         MessageDelta {
                             role: None,
                             content: None,
+                            reasoning_content: None,
                             refusal: None,
+                            annotations: None,
                             function_call: None,
                             tool_calls: Some(vec![ToolCallDelta {
                                 index: delta_event.content_block_index as u32,
@@ -206,6 +238,7 @@ impl TryFrom<ConverseStreamEvent> for ChatCompletionsStreamResponse {
                                     arguments: Some(tool_use.input),
                                 }),
                             }]),
+                            thinking_blocks: None,
                         },
                         None,
                         None,
@@ -231,9 +264,12 @@ impl TryFrom<ConverseStreamEvent> for ChatCompletionsStreamResponse {
                     MessageDelta {
                         role: None,
                         content: None,
+                        reasoning_content: None,
                         refusal: None,
+                        annotations: None,
                         function_call: None,
                         tool_calls: None,
+                        thinking_blocks: None,
                     },
                     Some(finish_reason),
                     None,
@@ -255,9 +291,12 @@ impl TryFrom<ConverseStreamEvent> for ChatCompletionsStreamResponse {
                     MessageDelta {
                         role: None,
                         content: None,
+                        reasoning_content: None,
                         refusal: None,
+                        annotations: None,
                         function_call: None,
                         tool_calls: None,
+                        thinking_blocks: None,
                     },
                     None,
                     Some(usage),
@@ -274,6 +313,270 @@ impl TryFrom<ConverseStreamEvent> for ChatCompletionsStreamResponse {
     }
 }
 
+impl TryFrom<GenerateContentResponse> for ChatCompletionsStreamResponse {
+    type Error = TransformError;
+
+    /// Each streamed Gemini event carries only new parts, so it maps onto a
+    /// single chunk. Function calls always arrive whole, with their arguments.
+    fn try_from(event: GenerateContentResponse) -> Result<Self, Self::Error> {
+        let mut finished = false;
+        let choices = event
+            .candidates
+            .into_iter()
+            .map(|candidate| {
+                let parts = candidate.content.map(|c| c.parts).unwrap_or_default();
+                let (content, tool_calls) =
+                    crate::transforms::response::to_openai::convert_gemini_parts_to_openai(&parts);
+                let finish_reason = candidate.finish_reason.map(|reason| {
+                    if tool_calls.is_some() {
+                        FinishReason::ToolCalls
+                    } else {
+                        reason.into()
+                    }
+                });
+                finished |= finish_reason.is_some();
+                StreamChoice {
+                    index: candidate.index,
+                    delta: MessageDelta {
+                        role: Some(Role::Assistant),
+                        content,
+                        reasoning_content: crate::transforms::response::to_openai::gemini_thoughts(
+                            &parts,
+                        ),
+                        refusal: None,
+                        annotations: None,
+                        function_call: None,
+                        tool_calls: tool_calls.map(|calls| {
+                            calls
+                                .into_iter()
+                                .enumerate()
+                                .map(|(index, call)| ToolCallDelta {
+                                    index: index as u32,
+                                    id: Some(call.id),
+                                    call_type: Some(call.call_type),
+                                    function: Some(FunctionCallDelta {
+                                        name: Some(call.function.name),
+                                        arguments: Some(call.function.arguments),
+                                    }),
+                                })
+                                .collect()
+                        }),
+                        thinking_blocks: None,
+                    },
+                    finish_reason,
+                    logprobs: candidate.logprobs_result.map(ChoiceLogprobs::from),
+                }
+            })
+            .collect();
+
+        Ok(ChatCompletionsStreamResponse {
+            id: event.response_id.unwrap_or_else(|| "stream".to_string()),
+            object: Some("chat.completion.chunk".to_string()),
+            created: current_timestamp(),
+            model: event.model_version.unwrap_or_else(|| "unknown".to_string()),
+            choices,
+            // Every event repeats the running usage; report it once, at the end
+            usage: event.usage_metadata.filter(|_| finished).map(Usage::from),
+            system_fingerprint: None,
+            service_tier: None,
+            citations: None,
+            search_results: None,
+        })
+    }
+}
+
+impl TryFrom<CohereStreamEvent> for ChatCompletionsStreamResponse {
+    type Error = TransformError;
+
+    /// Content and tool-plan deltas become text, tool calls stream as a start
+    /// event with the id and name followed by argument fragments, and
+    /// `message-end` carries the finish reason and usage. Other events only
+    /// mark boundaries and map to empty chunks.
+    fn try_from(event: CohereStreamEvent) -> Result<Self, Self::Error> {
+        let index = event.index.unwrap_or(0);
+        let delta = event.delta.unwrap_or_default();
+        let message = delta.message.unwrap_or_default();
+        let text_delta = |text: Option<String>| MessageDelta {
+            role: None,
+            content: text,
+            reasoning_content: None,
+            refusal: None,
+            annotations: None,
+            function_call: None,
+            tool_calls: None,
+            thinking_blocks: None,
+        };
+
+        match event.event_type {
+            CohereStreamEventType::MessageStart => Ok(create_openai_chunk(
+                event.id.as_deref().unwrap_or("stream"),
+                "unknown",
+                MessageDelta {
+                    role: Some(Role::Assistant),
+                    ..text_delta(None)
+                },
+                None,
+                None,
+            )),
+            CohereStreamEventType::ContentDelta => Ok(create_openai_chunk(
+                "stream",
+                "unknown",
+                text_delta(message.content.and_then(|content| content.text)),
+                None,
+                None,
+            )),
+            CohereStreamEventType::ToolPlanDelta => Ok(create_openai_chunk(
+                "stream",
+                "unknown",
+                text_delta(message.tool_plan),
+                None,
+                None,
+            )),
+            CohereStreamEventType::ToolCallStart | CohereStreamEventType::ToolCallDelta => {
+                let call = message.tool_calls.unwrap_or_default();
+                let function = call.function.unwrap_or_default();
+                let starting = event.event_type == CohereStreamEventType::ToolCallStart;
+                Ok(create_openai_chunk(
+                    "stream",
+                    "unknown",
+                    MessageDelta {
+                        tool_calls: Some(vec![ToolCallDelta {
+                            index,
+                            id: call.id,
+                            call_type: starting.then(|| "function".to_string()),
+                            function: Some(FunctionCallDelta {
+                                name: function.name,
+                                arguments: function.arguments,
+                            }),
+                        }]),
+                        ..text_delta(None)
+                    },
+                    None,
+                    None,
+                ))
+            }
+            CohereStreamEventType::MessageEnd => Ok(create_openai_chunk(
+                "stream",
+                "unknown",
+                text_delta(None),
+                Some(
+                    delta
+                        .finish_reason
+                        .map(FinishReason::from)
+                        .unwrap_or(FinishReason::Stop),
+                ),
+                delta.usage.map(Usage::from),
+            )),
+            CohereStreamEventType::ContentStart
+            | CohereStreamEventType::ContentEnd
+            | CohereStreamEventType::ToolCallEnd
+            | CohereStreamEventType::CitationStart
+            | CohereStreamEventType::CitationEnd
+            | CohereStreamEventType::Debug
+            | CohereStreamEventType::Other => Ok(create_empty_openai_chunk()),
+        }
+    }
+}
+
+impl TryFrom<OllamaChatResponse> for ChatCompletionsStreamResponse {
+    type Error = TransformError;
+
+    /// Each NDJSON line carries a content delta; tool calls arrive whole.
+    /// The `done` line carries the finish reason and token counts. It does
+    /// not say whether tools were called, so those streams finish with `stop`.
+    fn try_from(line: OllamaChatResponse) -> Result<Self, Self::Error> {
+        let usage = line.done.then(|| ollama_usage_to_openai(&line));
+        let message = line.message.unwrap_or_else(|| {
+            crate::apis::ollama::OllamaMessage::text(crate::apis::ollama::OllamaRole::Assistant, "")
+        });
+        let tool_calls: Vec<ToolCallDelta> = message
+            .tool_calls
+            .unwrap_or_default()
+            .into_iter()
+            .enumerate()
+            .map(|(position, call)| {
+                let index = call.function.index.unwrap_or(position as u32);
+                let call = convert_ollama_tool_call_to_openai(call);
+                ToolCallDelta {
+                    index,
+                    id: Some(call.id),
+                    call_type: Some(call.call_type),
+                    function: Some(FunctionCallDelta {
+                        name: Some(call.function.name),
+                        arguments: Some(call.function.arguments),
+                    }),
+                }
+            })
+            .collect();
+        let has_tool_calls = !tool_calls.is_empty();
+        let finish_reason = line
+            .done
+            .then(|| ollama_done_reason_to_openai(line.done_reason.as_deref(), false));
+
+        Ok(create_openai_chunk(
+            "stream",
+            &line.model,
+            MessageDelta {
+                role: None,
+                content: (!message.content.is_empty()).then_some(message.content),
+                reasoning_content: message.thinking.filter(|thinking| !thinking.is_empty()),
+                refusal: None,
+                annotations: None,
+                function_call: None,
+                tool_calls: has_tool_calls.then_some(tool_calls),
+                thinking_blocks: None,
+            },
+            finish_reason,
+            usage,
+        ))
+    }
+}
+
+impl TryFrom<InvokeModelStreamChunk> for ChatCompletionsStreamResponse {
+    type Error = TransformError;
+
+    /// Claude chunks are Messages stream events; Titan chunks carry a text
+    /// delta, with the completion reason and token counts on the last one
+    fn try_from(chunk: InvokeModelStreamChunk) -> Result<Self, Self::Error> {
+        let titan = match chunk {
+            InvokeModelStreamChunk::Anthropic(event) => return event.try_into(),
+            InvokeModelStreamChunk::TitanText(titan) => titan,
+        };
+
+        let usage = titan.invocation_metrics.map(|metrics| {
+            let prompt_tokens = metrics.input_token_count.unwrap_or(0);
+            let completion_tokens = metrics.output_token_count.unwrap_or(0);
+            Usage {
+                prompt_tokens,
+                completion_tokens,
+                total_tokens: prompt_tokens + completion_tokens,
+                prompt_tokens_details: None,
+                completion_tokens_details: None,
+            }
+        });
+
+        Ok(create_openai_chunk(
+            "stream",
+            "unknown",
+            MessageDelta {
+                role: None,
+                content: (!titan.output_text.is_empty()).then_some(titan.output_text),
+                reasoning_content: None,
+                refusal: None,
+                annotations: None,
+                function_call: None,
+                tool_calls: None,
+                thinking_blocks: None,
+            },
+            titan
+                .completion_reason
+                .as_deref()
+                .map(titan_completion_reason_to_openai),
+            usage,
+        ))
+    }
+}
+
 /// Convert content block start to OpenAI chunk
 fn convert_content_block_start(
     content_block: MessagesContentBlock,
@@ -284,6 +587,26 @@ fn convert_content_block_start(
             // No immediate output for text block start
             Ok(create_empty_openai_chunk())
         }
+        MessagesContentBlock::Thinking { .. } => {
+            // Thinking text and its signature follow as deltas
+            Ok(create_empty_openai_chunk())
+        }
+        MessagesContentBlock::RedactedThinking { data } => Ok(create_openai_chunk(
+            "stream",
+            "unknown",
+            MessageDelta {
+                role: None,
+                content: None,
+                reasoning_content: None,
+                refusal: None,
+                annotations: None,
+                function_call: None,
+                tool_calls: None,
+                thinking_blocks: Some(vec![ThinkingBlock::RedactedThinking { data }]),
+            },
+            None,
+            None,
+        )),
         MessagesContentBlock::ToolUse { id, name, .. }
         | MessagesContentBlock::ServerToolUse { id, name, .. }
         | MessagesContentBlock::McpToolUse { id, name, .. } => {
@@ -294,7 +617,9 @@ fn convert_content_block_start(
                 MessageDelta {
                     role: None,
                     content: None,
+                    reasoning_content: None,
                     refusal: None,
+                    annotations: None,
                     function_call: None,
                     tool_calls: Some(vec![ToolCallDelta {
                         index,
@@ -305,6 +630,7 @@ fn convert_content_block_start(
                             arguments: Some("".to_string()),
                         }),
                     }]),
+                    thinking_blocks: None,
                 },
                 None,
                 None,
@@ -328,9 +654,12 @@ fn convert_content_delta(
             MessageDelta {
                 role: None,
                 content: Some(text),
+                reasoning_content: None,
                 refusal: None,
+                annotations: None,
                 function_call: None,
                 tool_calls: None,
+                thinking_blocks: None,
             },
             None,
             None,
@@ -340,10 +669,16 @@ fn convert_content_delta(
             "unknown",
             MessageDelta {
                 role: None,
-                content: Some(format!("thinking: {}", thinking)),
+                content: None,
+                reasoning_content: Some(thinking.clone()),
                 refusal: None,
+                annotations: None,
                 function_call: None,
                 tool_calls: None,
+                thinking_blocks: Some(vec![ThinkingBlock::Thinking {
+                    thinking,
+                    signature: None,
+                }]),
             },
             None,
             None,
@@ -354,7 +689,9 @@ fn convert_content_delta(
             MessageDelta {
                 role: None,
                 content: None,
+                reasoning_content: None,
                 refusal: None,
+                annotations: None,
                 function_call: None,
                 tool_calls: Some(vec![ToolCallDelta {
                     index,
@@ -365,27 +702,49 @@ fn convert_content_delta(
                         arguments: Some(partial_json),
                     }),
                 }]),
+                thinking_blocks: None,
             },
             None,
             None,
         )),
-        MessagesContentDelta::SignatureDelta { signature: _ } => {
-            // Signature delta is cryptographic verification metadata, not content
-            // Create an empty delta chunk to maintain stream continuity
+        MessagesContentDelta::SignatureDelta { signature } => {
+            // The signature closes the thinking block streamed before it
             Ok(create_openai_chunk(
                 "stream",
                 "unknown",
                 MessageDelta {
                     role: None,
                     content: None,
+                    reasoning_content: None,
                     refusal: None,
+                    annotations: None,
                     function_call: None,
                     tool_calls: None,
+                    thinking_blocks: Some(vec![ThinkingBlock::Thinking {
+                        thinking: String::new(),
+                        signature: Some(signature),
+                    }]),
                 },
                 None,
                 None,
             ))
         }
+        MessagesContentDelta::CitationsDelta { citation } => Ok(create_openai_chunk(
+            "stream",
+            "unknown",
+            MessageDelta {
+                role: None,
+                content: None,
+                reasoning_content: None,
+                refusal: None,
+                annotations: Some(vec![citation_annotation(&citation, None)]),
+                function_call: None,
+                tool_calls: None,
+                thinking_blocks: None,
+            },
+            None,
+            None,
+        )),
     }
 }
 
@@ -411,6 +770,8 @@ fn create_openai_chunk(
         usage,
         system_fingerprint: None,
         service_tier: None,
+        citations: None,
+        search_results: None,
     }
 }
 
@@ -422,9 +783,12 @@ fn create_empty_openai_chunk() -> ChatCompletionsStreamResponse {
         MessageDelta {
             role: None,
             content: None,
+            reasoning_content: None,
             refusal: None,
+            annotations: None,
             function_call: None,
             tool_calls: None,
+            thinking_blocks: None,
         },
         None,
         None,
@@ -465,11 +829,13 @@ impl TryFrom<ChatCompletionsStreamResponse> for ResponsesAPIStreamEvent {
 
                     // Check if we have function metadata (name, id)
                     if let Some(function) = &tool_call.function {
-                        // If we have arguments delta, return that
+                        // If we have arguments delta, return that. The tool call
+                        // index stands in for the output index; the buffer
+                        // allocates the real one
                         if let Some(args) = &function.arguments {
                             return Ok(
                                 ResponsesAPIStreamEvent::ResponseFunctionCallArgumentsDelta {
-                                    output_index: choice.index as i32,
+                                    output_index: tool_call.index as i32,
                                     item_id: "".to_string(), // Buffer will fill this
                                     delta: args.clone(),
                                     sequence_number: 0, // Buffer will fill this
@@ -484,7 +850,7 @@ impl TryFrom<ChatCompletionsStreamResponse> for ResponsesAPIStreamEvent {
                         if function.name.is_some() {
                             return Ok(
                                 ResponsesAPIStreamEvent::ResponseFunctionCallArgumentsDelta {
-                                    output_index: choice.index as i32,
+                                    output_index: tool_call.index as i32,
                                     item_id: "".to_string(), // Buffer will fill this
                                     delta: "".to_string(), // Empty delta signals this is the initial event
                                     sequence_number: 0,    // Buffer will fill this
@@ -497,6 +863,19 @@ impl TryFrom<ChatCompletionsStreamResponse> for ResponsesAPIStreamEvent {
                 }
             }
 
+            // Reasoning delta; the buffer gives it its own reasoning item
+            if let Some(reasoning) = &delta.reasoning_content {
+                if !reasoning.is_empty() {
+                    return Ok(ResponsesAPIStreamEvent::ResponseReasoningSummaryTextDelta {
+                        item_id: "".to_string(), // Buffer will fill this
+                        output_index: choice.index as i32,
+                        summary_index: 0,
+                        delta: reasoning.clone(),
+                        sequence_number: 0, // Buffer will fill this
+                    });
+                }
+            }
+
             // Text content delta
             if let Some(content) = &delta.content {
                 if !content.is_empty() {
@@ -505,7 +884,9 @@ impl TryFrom<ChatCompletionsStreamResponse> for ResponsesAPIStreamEvent {
                         output_index: choice.index as i32,
                         content_index: 0,
                         delta: content.clone(),
-                        logprobs: vec![],
+                        logprobs: crate::transforms::response::to_openai::responses_logprobs(
+                            choice.logprobs.as_ref(),
+                        ),
                         obfuscation: None,
                         sequence_number: 0, // Buffer will fill this
                     });
@@ -515,26 +896,35 @@ impl TryFrom<ChatCompletionsStreamResponse> for ResponsesAPIStreamEvent {
             // Handle finish_reason - this is a completion signal.
             // Emit an explicit Done marker so the buffering layer can finalize
             // even if an upstream [DONE] marker is missing/delayed.
-            if choice.finish_reason.is_some() {
+            if let Some(finish_reason) = &choice.finish_reason {
                 return Ok(ResponsesAPIStreamEvent::Done {
                     sequence_number: 0, // Buffer will assign final sequence
+                    incomplete_details: incomplete_details(finish_reason),
+                    usage: chunk.usage.clone().map(ResponseUsage::from),
                 });
             }
 
             // Empty delta with role only (common at stream start)
             if delta.role.is_some() {
-                // This is typically the first chunk establishing the assistant role
-                // Return an empty text delta that the buffer can use to initialize state
-                return Ok(ResponsesAPIStreamEvent::ResponseOutputTextDelta {
-                    item_id: "".to_string(),
-                    output_index: choice.index as i32,
-                    content_index: 0,
-                    delta: "".to_string(),
-                    logprobs: vec![],
-                    obfuscation: None,
+                // This is typically the first chunk establishing the assistant role;
+                // it tells the buffer which response and model the stream belongs to
+                return Ok(ResponsesAPIStreamEvent::ResponseCreated {
+                    response: ResponsesAPIResponse::in_progress(
+                        response_id(&chunk.id),
+                        chunk.model.clone(),
+                        chunk.created as i64,
+                    ),
                     sequence_number: 0,
                 });
             }
+        } else if let Some(usage) = chunk.usage {
+            // With stream_options.include_usage, usage arrives in a final chunk
+            // without choices
+            return Ok(ResponsesAPIStreamEvent::Done {
+                sequence_number: 0,
+                incomplete_details: None,
+                usage: Some(usage.into()),
+            });
         }
 
         // Empty chunk or no convertible content (e.g., keep-alive chunks with delta: {})
@@ -545,3 +935,144 @@ impl TryFrom<ChatCompletionsStreamResponse> for ResponsesAPIStreamEvent {
         ))
     }
 }
+
+/// Why a response stopped early, if it did
+fn incomplete_details(finish_reason: &FinishReason) -> Option<IncompleteDetails> {
+    let reason = match finish_reason {
+        FinishReason::Length => IncompleteReason::MaxOutputTokens,
+        FinishReason::ContentFilter => IncompleteReason::ContentFilter,
+        _ => return None,
+    };
+    Some(IncompleteDetails { reason })
+}
+
+/// Response id for an upstream message or completion id
+fn response_id(upstream_id: &str) -> String {
+    if upstream_id.starts_with("resp_") {
+        upstream_id.to_string()
+    } else {
+        format!("resp_{}", uuid::Uuid::new_v4().to_string().replace("-", ""))
+    }
+}
+
+impl TryFrom<MessagesStreamEvent> for ResponsesAPIStreamEvent {
+    type Error = TransformError;
+
+    /// Stateless like the ChatCompletions conversion above: content block
+    /// indices stand in for output indices and the buffer fills in item ids,
+    /// sequence numbers and the lifecycle events. Thinking becomes reasoning
+    /// summary text rather than being folded into the answer.
+    fn try_from(event: MessagesStreamEvent) -> Result<Self, TransformError> {
+        match event {
+            MessagesStreamEvent::MessageStart { message } => {
+                Ok(ResponsesAPIStreamEvent::ResponseCreated {
+                    response: ResponsesAPIResponse::in_progress(
+                        response_id(&message.id),
+                        message.model,
+                        current_timestamp() as i64,
+                    ),
+                    sequence_number: 0,
+                })
+            }
+            // Server tools with a built-in counterpart stream as built-in
+            // calls: added here, done with their result block below
+            MessagesStreamEvent::ContentBlockStart {
+                index,
+                content_block: MessagesContentBlock::ServerToolUse { id, name, input },
+            } if server_tool_call(&id, &name, &input, &[]).is_some() => {
+                Ok(ResponsesAPIStreamEvent::ResponseOutputItemAdded {
+                    output_index: index as i32,
+                    item: server_tool_call(&id, &name, &input, &[]).unwrap(),
+                    sequence_number: 0,
+                })
+            }
+            MessagesStreamEvent::ContentBlockStart {
+                index,
+                content_block,
+            } if server_tool_result_call(&content_block).is_some() => {
+                Ok(ResponsesAPIStreamEvent::ResponseOutputItemDone {
+                    output_index: index as i32,
+                    item: server_tool_result_call(&content_block).unwrap(),
+                    sequence_number: 0,
+                })
+            }
+            MessagesStreamEvent::ContentBlockStart {
+                index,
+                content_block:
+                    MessagesContentBlock::ToolUse { id, name, .. }
+                    | MessagesContentBlock::ServerToolUse { id, name, .. }
+                    | MessagesContentBlock::McpToolUse { id, name, .. },
+            } => Ok(
+                ResponsesAPIStreamEvent::ResponseFunctionCallArgumentsDelta {
+                    output_index: index as i32,
+                    item_id: "".to_string(),
+                    delta: "".to_string(),
+                    sequence_number: 0,
+                    call_id: Some(id),
+                    name: Some(name),
+                },
+            ),
+            MessagesStreamEvent::ContentBlockDelta { index, delta } => match delta {
+                MessagesContentDelta::TextDelta { text } => {
+                    Ok(ResponsesAPIStreamEvent::ResponseOutputTextDelta {
+                        item_id: "".to_string(),
+                        output_index: index as i32,
+                        content_index: 0,
+                        delta: text,
+                        logprobs: vec![],
+                        obfuscation: None,
+                        sequence_number: 0,
+                    })
+                }
+                MessagesContentDelta::ThinkingDelta { thinking } => {
+                    Ok(ResponsesAPIStreamEvent::ResponseReasoningSummaryTextDelta {
+                        item_id: "".to_string(),
+                        output_index: index as i32,
+                        summary_index: 0,
+                        delta: thinking,
+                        sequence_number: 0,
+                    })
+                }
+                MessagesContentDelta::InputJsonDelta { partial_json } => Ok(
+                    ResponsesAPIStreamEvent::ResponseFunctionCallArgumentsDelta {
+                        output_index: index as i32,
+                        item_id: "".to_string(),
+                        delta: partial_json,
+                        sequence_number: 0,
+                        call_id: None,
+                        name: None,
+                    },
+                ),
+                MessagesContentDelta::SignatureDelta { .. } => {
+                    Err(TransformError::UnsupportedConversion(
+                        "Thinking signatures have no Responses API counterpart".to_string(),
+                    ))
+                }
+                MessagesContentDelta::CitationsDelta { .. } => {
+                    Err(TransformError::UnsupportedConversion(
+                        "Streamed citations have no Responses API counterpart".to_string(),
+                    ))
+                }
+            },
+            MessagesStreamEvent::MessageDelta { delta, usage } => {
+                Ok(ResponsesAPIStreamEvent::Done {
+                    sequence_number: 0,
+                    incomplete_details: incomplete_details(&delta.stop_reason.into()),
+                    usage: Some(ResponseUsage::from(Usage::from(usage))),
+                })
+            }
+            MessagesStreamEvent::MessageStop => Ok(ResponsesAPIStreamEvent::Done {
+                sequence_number: 0,
+                incomplete_details: None,
+                usage: None,
+            }),
+            // Block starts for text and thinking, block stops and pings carry
+            // nothing the buffer needs
+            MessagesStreamEvent::ContentBlockStart { .. }
+            | MessagesStreamEvent::ContentBlockStop { .. }
+            | MessagesStreamEvent::Ping => Err(TransformError::UnsupportedConversion(
+                "Anthropic event has no Responses API counterpart".to_string(),
+            )),
+        }
+    }
+}
@@ -0,0 +1,330 @@
+//! Tool call ids across providers.
+//!
+//! Clients send back the ids of earlier tool calls verbatim, but not every
+//! upstream accepts every id: Anthropic and Bedrock only take
+//! `[a-zA-Z0-9_-]`, Bedrock caps ids at 64 characters and Mistral wants
+//! exactly nine alphanumerics. A conversation that started on one provider
+//! and continues on another gets a 400 for ids the first one minted.
+//!
+//! [`ToolCallIdMap`] rewrites the ids of a conversation into the upstream's
+//! format, giving a tool call and its result the same new id. Rewritten ids
+//! are derived from the original, so each turn of a conversation rewrites
+//! its history the same way and upstream prompt caches keep matching.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::apis::amazon_bedrock::{ContentBlock, InvokeModelBody, InvokeModelRequest};
+use crate::apis::anthropic::{MessagesContentBlock, MessagesMessageContent, MessagesRequest};
+use crate::clients::endpoints::SupportedUpstreamAPIs;
+use crate::providers::id::ProviderId;
+use crate::providers::request::ProviderRequestType;
+
+const BEDROCK_MAX_ID_LEN: usize = 64;
+const MISTRAL_ID_LEN: usize = 9;
+
+/// The tool call ids an upstream accepts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToolCallIdFormat {
+    /// `[a-zA-Z0-9_-]+`, minted as `toolu_...`
+    Anthropic,
+    /// `[a-zA-Z0-9_-]{1,64}`
+    Bedrock,
+    /// `[a-zA-Z0-9]{9}`
+    Mistral,
+}
+
+impl ToolCallIdFormat {
+    /// The format `provider_id` enforces on `upstream_api`, if it restricts ids
+    pub fn for_upstream(
+        provider_id: ProviderId,
+        upstream_api: &SupportedUpstreamAPIs,
+    ) -> Option<Self> {
+        match upstream_api {
+            SupportedUpstreamAPIs::AnthropicMessagesAPI(_)
+            | SupportedUpstreamAPIs::AmazonBedrockInvokeModel(_)
+            | SupportedUpstreamAPIs::AmazonBedrockInvokeModelStream(_) => Some(Self::Anthropic),
+            SupportedUpstreamAPIs::AmazonBedrockConverse(_)
+            | SupportedUpstreamAPIs::AmazonBedrockConverseStream(_) => Some(Self::Bedrock),
+            SupportedUpstreamAPIs::OpenAIChatCompletions(_)
+                if provider_id == ProviderId::Mistral =>
+            {
+                Some(Self::Mistral)
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether the upstream takes `id` as is
+    pub fn accepts(&self, id: &str) -> bool {
+        match self {
+            Self::Anthropic => !id.is_empty() && id.chars().all(is_id_char),
+            Self::Bedrock => {
+                !id.is_empty() && id.len() <= BEDROCK_MAX_ID_LEN && id.chars().all(is_id_char)
+            }
+            Self::Mistral => {
+                id.len() == MISTRAL_ID_LEN && id.chars().all(|c| c.is_ascii_alphanumeric())
+            }
+        }
+    }
+
+    /// The `attempt`th id in this format standing in for `id`
+    fn mint(&self, id: &str, attempt: u64) -> String {
+        let suffix = match attempt {
+            0 => String::new(),
+            n => format!("_{}", n),
+        };
+        let sanitized: String = id
+            .chars()
+            .map(|c| if is_id_char(c) { c } else { '_' })
+            .collect();
+        match self {
+            Self::Anthropic if sanitized.starts_with("toolu_") => sanitized + &suffix,
+            Self::Anthropic => format!("toolu_{}{}", sanitized, suffix),
+            Self::Bedrock => {
+                let mut minted = if sanitized.is_empty() {
+                    "tooluse".to_string()
+                } else {
+                    sanitized
+                };
+                minted.truncate(BEDROCK_MAX_ID_LEN - suffix.len());
+                minted + &suffix
+            }
+            Self::Mistral => base62(fnv1a(id, attempt), MISTRAL_ID_LEN),
+        }
+    }
+}
+
+/// Original to upstream tool call ids for one conversation
+#[derive(Debug, Clone)]
+pub struct ToolCallIdMap {
+    format: ToolCallIdFormat,
+    ids: HashMap<String, String>,
+    taken: HashSet<String>,
+}
+
+impl ToolCallIdMap {
+    pub fn new(format: ToolCallIdFormat) -> Self {
+        Self {
+            format,
+            ids: HashMap::new(),
+            taken: HashSet::new(),
+        }
+    }
+
+    /// The id to send upstream for `id`; the same `id` always maps to the
+    /// same upstream id, and different ids never share one
+    pub fn upstream_id(&mut self, id: &str) -> String {
+        if let Some(mapped) = self.ids.get(id) {
+            return mapped.clone();
+        }
+        let mapped = if self.format.accepts(id) && !self.taken.contains(id) {
+            id.to_string()
+        } else {
+            (0..)
+                .map(|attempt| self.format.mint(id, attempt))
+                .find(|candidate| !self.taken.contains(candidate))
+                .expect("an unused id is always found")
+        };
+        self.taken.insert(mapped.clone());
+        self.ids.insert(id.to_string(), mapped.clone());
+        mapped
+    }
+
+    /// The original id `upstream_id` stands in for
+    pub fn original_id(&self, upstream_id: &str) -> Option<&str> {
+        self.ids
+            .iter()
+            .find(|(_, mapped)| mapped.as_str() == upstream_id)
+            .map(|(original, _)| original.as_str())
+    }
+
+    fn rewrite(&mut self, id: &mut String) {
+        let mapped = self.upstream_id(id);
+        *id = mapped;
+    }
+}
+
+impl ProviderRequestType {
+    /// Rewrite the tool call ids of the conversation through `ids`, keeping
+    /// each tool call paired with its result
+    pub fn adapt_tool_call_ids(&mut self, ids: &mut ToolCallIdMap) {
+        match self {
+            Self::ChatCompletionsRequest(r) => {
+                for message in &mut r.messages {
+                    for call in message.tool_calls.iter_mut().flatten() {
+                        ids.rewrite(&mut call.id);
+                    }
+                    if let Some(id) = message.tool_call_id.as_mut() {
+                        ids.rewrite(id);
+                    }
+                }
+            }
+            Self::MessagesRequest(r)
+            | Self::BedrockInvokeModel(InvokeModelRequest {
+                body: InvokeModelBody::Anthropic(r),
+                ..
+            }) => adapt_messages_ids(r, ids),
+            Self::BedrockConverse(r) | Self::BedrockConverseStream(r) => {
+                for message in r.messages.iter_mut().flatten() {
+                    for block in &mut message.content {
+                        match block {
+                            ContentBlock::ToolUse { tool_use } => {
+                                ids.rewrite(&mut tool_use.tool_use_id)
+                            }
+                            ContentBlock::ToolResult { tool_result } => {
+                                ids.rewrite(&mut tool_result.tool_use_id)
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn adapt_messages_ids(request: &mut MessagesRequest, ids: &mut ToolCallIdMap) {
+    for message in &mut request.messages {
+        let MessagesMessageContent::Blocks(blocks) = &mut message.content else {
+            continue;
+        };
+        for block in blocks {
+            match block {
+                MessagesContentBlock::ToolUse { id, .. } => ids.rewrite(id),
+                MessagesContentBlock::ToolResult { tool_use_id, .. } => ids.rewrite(tool_use_id),
+                _ => {}
+            }
+        }
+    }
+}
+
+fn is_id_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '-'
+}
+
+/// FNV-1a, stable across processes unlike `DefaultHasher`
+fn fnv1a(id: &str, salt: u64) -> u64 {
+    id.bytes()
+        .chain(salt.to_le_bytes())
+        .fold(0xcbf29ce484222325, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+        })
+}
+
+fn base62(mut value: u64, len: usize) -> String {
+    const ALPHABET: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+    (0..len)
+        .map(|_| {
+            let c = ALPHABET[(value % 62) as usize] as char;
+            value /= 62;
+            c
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::apis::openai::ChatCompletionsRequest;
+    use crate::providers::request::ProviderRequest;
+    use serde_json::json;
+
+    #[test]
+    fn keeps_tool_calls_paired_with_their_results() {
+        let request: MessagesRequest = serde_json::from_value(json!({
+            "model": "claude-sonnet-4-5",
+            "max_tokens": 100,
+            "messages": [
+                {"role": "user", "content": "Weather in Paris and Rome?"},
+                {"role": "assistant", "content": [
+                    {"type": "tool_use", "id": "functions.get_weather:0", "name": "get_weather", "input": {"city": "Paris"}},
+                    {"type": "tool_use", "id": "functions.get_weather:1", "name": "get_weather", "input": {"city": "Rome"}},
+                    {"type": "tool_use", "id": "toolu_01", "name": "get_weather", "input": {"city": "Oslo"}}
+                ]},
+                {"role": "user", "content": [
+                    {"type": "tool_result", "tool_use_id": "functions.get_weather:1", "content": "sunny"},
+                    {"type": "tool_result", "tool_use_id": "functions.get_weather:0", "content": "rainy"},
+                    {"type": "tool_result", "tool_use_id": "toolu_01", "content": "snow"}
+                ]}
+            ]
+        }))
+        .unwrap();
+        let mut request = ProviderRequestType::MessagesRequest(request);
+
+        let mut ids = ToolCallIdMap::new(ToolCallIdFormat::Anthropic);
+        request.adapt_tool_call_ids(&mut ids);
+
+        let body: serde_json::Value = serde_json::from_slice(&request.to_bytes().unwrap()).unwrap();
+        let uses = &body["messages"][1]["content"];
+        let results = &body["messages"][2]["content"];
+        assert_eq!(uses[0]["id"], "toolu_functions_get_weather_0");
+        assert_eq!(uses[1]["id"], "toolu_functions_get_weather_1");
+        assert_eq!(uses[2]["id"], "toolu_01");
+        assert_eq!(results[0]["tool_use_id"], uses[1]["id"]);
+        assert_eq!(results[1]["tool_use_id"], uses[0]["id"]);
+        assert_eq!(results[2]["tool_use_id"], "toolu_01");
+        assert_eq!(
+            ids.original_id("toolu_functions_get_weather_0"),
+            Some("functions.get_weather:0")
+        );
+    }
+
+    #[test]
+    fn mints_distinct_mistral_ids_the_same_way_every_turn() {
+        let request: ChatCompletionsRequest = serde_json::from_value(json!({
+            "model": "mistral-large-latest",
+            "messages": [
+                {"role": "user", "content": "Weather?"},
+                {"role": "assistant", "tool_calls": [
+                    {"id": "toolu_01A09q90qw90lq917835lq9", "type": "function",
+                     "function": {"name": "get_weather", "arguments": "{}"}},
+                    {"id": "abc123XYZ", "type": "function",
+                     "function": {"name": "get_weather", "arguments": "{}"}}
+                ]},
+                {"role": "tool", "tool_call_id": "toolu_01A09q90qw90lq917835lq9", "content": "sunny"},
+                {"role": "tool", "tool_call_id": "abc123XYZ", "content": "rainy"}
+            ]
+        }))
+        .unwrap();
+
+        let adapt = || {
+            let mut request = ProviderRequestType::ChatCompletionsRequest(request.clone());
+            request.adapt_tool_call_ids(&mut ToolCallIdMap::new(ToolCallIdFormat::Mistral));
+            let ProviderRequestType::ChatCompletionsRequest(r) = request else {
+                unreachable!()
+            };
+            r
+        };
+        let adapted = adapt();
+        let calls = adapted.messages[1].tool_calls.as_ref().unwrap();
+        assert!(ToolCallIdFormat::Mistral.accepts(&calls[0].id));
+        assert_eq!(calls[1].id, "abc123XYZ");
+        assert_eq!(
+            adapted.messages[2].tool_call_id.as_ref(),
+            Some(&calls[0].id)
+        );
+        assert_eq!(
+            adapted.messages[3].tool_call_id.as_deref(),
+            Some("abc123XYZ")
+        );
+        assert_eq!(
+            adapt().messages[1].tool_calls,
+            adapted.messages[1].tool_calls
+        );
+    }
+
+    #[test]
+    fn bedrock_ids_are_truncated_without_colliding() {
+        let mut ids = ToolCallIdMap::new(ToolCallIdFormat::Bedrock);
+        let long = "a".repeat(80);
+        let longer = "a".repeat(90);
+
+        let first = ids.upstream_id(&long);
+        let second = ids.upstream_id(&longer);
+        assert_eq!(first, "a".repeat(64));
+        assert_ne!(first, second);
+        assert!(ToolCallIdFormat::Bedrock.accepts(&second));
+        assert_eq!(ids.upstream_id(&long), first);
+    }
+}
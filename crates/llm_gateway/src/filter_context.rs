@@ -1,5 +1,6 @@
 use crate::metrics::Metrics;
 use crate::stream_context::StreamContext;
+use common::config_validation::validate_config;
 use common::configuration::Configuration;
 use common::configuration::Overrides;
 use common::http::Client;
@@ -56,11 +57,13 @@ impl RootContext for FilterContext {
             .get_plugin_configuration()
             .expect("Arch config cannot be empty");
 
-        let config: Configuration = match serde_yaml::from_slice(&config_bytes) {
-            Ok(config) => config,
-            Err(err) => panic!("Invalid arch config \"{:?}\"", err),
-        };
+        let config: Configuration =
+            match validate_config(&String::from_utf8_lossy(&config_bytes)).into_result() {
+                Ok(config) => config,
+                Err(errors) => panic!("Invalid arch config:\n{}", errors),
+            };
 
+        config.register_custom_providers();
         ratelimit::ratelimits(Some(config.ratelimits.unwrap_or_default()));
         self.overrides = Rc::new(config.overrides);
 
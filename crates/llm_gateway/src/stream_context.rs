@@ -1,4 +1,21 @@
-use hermesllm::clients::endpoints::SupportedUpstreamAPIs;
+use hermesllm::apis::audio::{TranscriptionRequest, UpstreamTranscriptionRequest};
+use hermesllm::apis::embeddings::{EmbeddingsRequest, UpstreamEmbeddingsRequest};
+use hermesllm::apis::images::{ImageGenerationRequest, UpstreamImagesRequest};
+use hermesllm::apis::moderations::{ModerationRequest, UpstreamModerationRequest};
+use hermesllm::apis::multipart::form_boundary;
+use hermesllm::apis::openai::{
+    ChatCompletionsRequest, ChatCompletionsResponse, ChatCompletionsStreamResponse,
+    CompletionsRequest, CompletionsResponse, Usage,
+};
+use hermesllm::apis::realtime::realtime_path;
+use hermesllm::apis::OpenAIApi;
+use hermesllm::clients::aws_sigv4::{self, AwsCredentials, SignableRequest};
+use hermesllm::clients::endpoints::{
+    endpoint_path_override, legacy_completions_endpoint, serves_legacy_completions,
+    SupportedUpstreamAPIs, CLIENT_AUTH_HEADERS,
+};
+use hermesllm::transforms::completions::chat_stream_to_completions;
+use hermesllm::transforms::TransformOptions;
 use http::StatusCode;
 use log::{debug, error, info, warn};
 use proxy_wasm::hostcalls::get_current_time;
@@ -10,22 +27,34 @@ use std::sync::Arc;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use crate::metrics::Metrics;
-use common::configuration::{LlmProvider, LlmProviderType, Overrides};
+use common::configuration::{is_secret_reference, LlmProvider, LlmProviderType, Overrides};
 use common::consts::{
-    ARCH_IS_STREAMING_HEADER, ARCH_PROVIDER_HINT_HEADER, ARCH_ROUTING_HEADER, HEALTHZ_PATH,
-    RATELIMIT_SELECTOR_HEADER_KEY, REQUEST_ID_HEADER, TRACE_PARENT_HEADER,
+    ANTHROPIC_COUNT_TOKENS_PATH, ANTHROPIC_MESSAGE_BATCHES_PATH, ARCH_ACCESS_KEY_SLOT_HEADER,
+    ARCH_IS_STREAMING_HEADER, ARCH_PROVIDER_HINT_HEADER, ARCH_ROUTING_HEADER,
+    ARCH_STRICT_TRANSFORM_HEADER, ARCH_UPSTREAM_ACCESS_KEY_HEADER,
+    ARCH_UPSTREAM_AWS_CREDENTIALS_HEADER, ARCH_UPSTREAM_ENDPOINT_HEADER,
+    ARCH_UPSTREAM_TOKEN_HEADER, AUDIO_TRANSCRIPTIONS_PATH, BATCHES_PATH, CHAT_COMPLETIONS_PATH,
+    COMPLETIONS_PATH, EMBEDDINGS_PATH, ENVOY_RETRIABLE_STATUS_CODES_HEADER, ENVOY_RETRY_HEADER,
+    ENVOY_RETRY_ON_HEADER, ENVOY_UPSTREAM_TIMEOUT_HEADER, FILES_PATH, HEALTHZ_PATH,
+    IMAGES_GENERATIONS_PATH, MODERATIONS_PATH, RATELIMIT_SELECTOR_HEADER_KEY, REALTIME_PATH,
+    REQUEST_ID_HEADER, TRACE_PARENT_HEADER, UPSTREAM_OVERRIDE_CLUSTER,
+    UPSTREAM_OVERRIDE_TLS_CLUSTER, WARMUP_PATH,
 };
 use common::errors::ServerError;
 use common::llm_providers::LlmProviders;
+use common::provider_params::apply_provider_params;
 use common::ratelimit::Header;
 use common::stats::{IncrementingMetric, RecordingMetric};
+use common::utils::parse_duration_ms;
 use common::{ratelimit, routing, tokenizer};
 use hermesllm::apis::streaming_shapes::amazon_bedrock_binary_frame::BedrockBinaryFrameDecoder;
+use hermesllm::apis::streaming_shapes::passthrough_streaming_buffer::PassthroughStreamBuffer;
 use hermesllm::apis::streaming_shapes::sse::{SseEvent, SseStreamBuffer, SseStreamBufferTrait};
 use hermesllm::apis::streaming_shapes::sse_chunk_processor::SseChunkProcessor;
 use hermesllm::clients::endpoints::SupportedAPIsFromClient;
-use hermesllm::providers::response::ProviderResponse;
-use hermesllm::providers::streaming_response::ProviderStreamResponse;
+use hermesllm::clients::errors::{translate_error_body, ErrorDialect};
+use hermesllm::providers::response::{ProviderResponse, UsageDetails};
+use hermesllm::providers::streaming_response::{needs_buffering, ProviderStreamResponse};
 use hermesllm::{
     DecodedFrame, ProviderId, ProviderRequest, ProviderRequestType, ProviderResponseType,
     ProviderStreamResponseType,
@@ -35,6 +64,7 @@ pub struct StreamContext {
     metrics: Rc<Metrics>,
     ratelimit_selector: Option<Header>,
     streaming_response: bool,
+    transform_options: TransformOptions,
     response_tokens: usize,
     /// The API that is requested by the client (before compatibility mapping)
     client_api: Option<SupportedAPIsFromClient>,
@@ -48,7 +78,7 @@ pub struct StreamContext {
     ttft_time: Option<u128>,
     traceparent: Option<String>,
     request_body_sent_time: Option<u128>,
-    _overrides: Rc<Option<Overrides>>,
+    overrides: Rc<Option<Overrides>>,
     user_message: Option<String>,
     upstream_status_code: Option<StatusCode>,
     binary_frame_decoder: Option<BedrockBinaryFrameDecoder<bytes::BytesMut>>,
@@ -56,6 +86,37 @@ pub struct StreamContext {
     http_protocol: Option<String>,
     sse_buffer: Option<SseStreamBuffer>,
     sse_chunk_processor: Option<SseChunkProcessor>,
+    /// Request is forwarded to the provider without translation (count_tokens, warm-up).
+    passthrough: bool,
+    /// Credentials that sign the upstream request once its body is final.
+    aws_credentials: Option<AwsCredentials>,
+    /// Client called `/v1/embeddings`, which has its own translation path.
+    embeddings: bool,
+    /// The embeddings request as sent upstream, kept to translate its response.
+    embeddings_request: Option<UpstreamEmbeddingsRequest>,
+    /// Client called `/v1/images/generations`.
+    images: bool,
+    /// The image generation request as sent upstream, kept to translate its response.
+    images_request: Option<UpstreamImagesRequest>,
+    /// Client called `/v1/moderations`.
+    moderations: bool,
+    /// The moderation request as sent upstream, kept to translate its response.
+    moderations_request: Option<UpstreamModerationRequest>,
+    /// Client called `/v1/audio/transcriptions`, a multipart form.
+    transcription: bool,
+    /// The transcription request as sent upstream, kept to translate its response.
+    transcription_request: Option<UpstreamTranscriptionRequest>,
+    /// Client called the legacy `/v1/completions`, served here as chat completions.
+    legacy_completions: bool,
+    /// Client asked for usage in the stream (`stream_options.include_usage`);
+    /// it is estimated if the upstream leaves it out.
+    stream_usage_requested: bool,
+    /// Tokenizer count of the request's messages.
+    input_tokens: usize,
+    /// Text streamed so far and the id and model of the stream's chunks,
+    /// kept to estimate usage when the upstream reports none.
+    streamed_text: String,
+    stream_id_and_model: Option<(String, String)>,
 }
 
 impl StreamContext {
@@ -66,7 +127,7 @@ impl StreamContext {
     ) -> Self {
         StreamContext {
             metrics,
-            _overrides: overrides,
+            overrides,
             ratelimit_selector: None,
             streaming_response: false,
             response_tokens: 0,
@@ -87,6 +148,22 @@ impl StreamContext {
             http_protocol: None,
             sse_buffer: None,
             sse_chunk_processor: None,
+            passthrough: false,
+            aws_credentials: None,
+            embeddings: false,
+            embeddings_request: None,
+            images: false,
+            images_request: None,
+            moderations: false,
+            moderations_request: None,
+            transcription: false,
+            transcription_request: None,
+            legacy_completions: false,
+            transform_options: TransformOptions::default(),
+            stream_usage_requested: false,
+            input_tokens: 0,
+            streamed_text: String::new(),
+            stream_id_and_model: None,
         }
     }
 
@@ -113,23 +190,62 @@ impl StreamContext {
     fn update_upstream_path(&mut self, request_path: &str) {
         let hermes_provider_id = self.llm_provider().to_provider_id();
         if let Some(api) = &self.client_api {
-            let target_endpoint = api.target_endpoint_for_provider(
-                &hermes_provider_id,
-                request_path,
-                self.llm_provider()
-                    .model
-                    .as_ref()
-                    .unwrap_or(&"".to_string()),
-                self.streaming_response,
-                self.llm_provider().base_url_path_prefix.as_deref(),
-                self.llm_provider().name.starts_with("perplexity/"),
-            );
+            let model_id = self.llm_provider().upstream_model().unwrap_or_default();
+            let upstream_api = self
+                .llm_provider()
+                .compatible_api_for_client(api, self.streaming_response);
+            let path_override = self
+                .llm_provider()
+                .resolve_custom_provider()
+                .and_then(|custom| custom.endpoint_for(model_id))
+                .or_else(|| {
+                    self.llm_provider()
+                        .endpoint_paths
+                        .as_ref()
+                        .and_then(|paths| upstream_api.path_override(paths, model_id))
+                })
+                .or_else(|| {
+                    upstream_api.invoke_model_path(
+                        model_id,
+                        self.llm_provider().base_url_path_prefix.as_deref(),
+                    )
+                })
+                .or_else(|| {
+                    upstream_api.vertex_anthropic_path(
+                        &hermes_provider_id,
+                        model_id,
+                        self.streaming_response,
+                        self.llm_provider().base_url_path_prefix.as_deref(),
+                    )
+                });
+            let target_endpoint = path_override.unwrap_or_else(|| {
+                api.target_endpoint_for_provider(
+                    &hermes_provider_id,
+                    request_path,
+                    model_id,
+                    self.streaming_response,
+                    self.llm_provider().base_url_path_prefix.as_deref(),
+                    self.llm_provider().name.starts_with("perplexity/"),
+                    self.llm_provider().azure_api_version(),
+                )
+            });
             if target_endpoint != request_path {
                 self.set_http_request_header(":path", Some(&target_endpoint));
             }
         }
     }
 
+    /// `default`, unless the provider configures an `endpoint_paths` entry
+    /// under `key`.
+    fn configured_path(&self, key: &str, default: String) -> String {
+        let model_id = self.llm_provider().upstream_model().unwrap_or_default();
+        self.llm_provider()
+            .endpoint_paths
+            .as_ref()
+            .and_then(|paths| endpoint_path_override(paths, key, model_id))
+            .unwrap_or(default)
+    }
+
     fn select_llm_provider(&mut self) -> Result<(), String> {
         let provider_hint = self
             .get_http_request_header(ARCH_PROVIDER_HINT_HEADER)
@@ -168,80 +284,1129 @@ impl StreamContext {
         info!(
             "request_id={}: provider selected, hint='{}' selected='{}'",
             self.request_identifier(),
-            self.get_http_request_header(ARCH_PROVIDER_HINT_HEADER)
-                .unwrap_or("none".to_string()),
-            self.llm_provider.as_ref().unwrap().name
+            self.get_http_request_header(ARCH_PROVIDER_HINT_HEADER)
+                .unwrap_or("none".to_string()),
+            self.llm_provider.as_ref().unwrap().name
+        );
+
+        Ok(())
+    }
+
+    /// API whose header templates carry the credential upstream. Embeddings,
+    /// image, moderation and transcription requests use the same headers as
+    /// the provider's chat API.
+    fn header_api(&self) -> SupportedUpstreamAPIs {
+        let chat = SupportedUpstreamAPIs::OpenAIChatCompletions(OpenAIApi::ChatCompletions);
+        match &self.resolved_api {
+            Some(api) => api.clone(),
+            None if self.embeddings || self.images || self.moderations || self.transcription => {
+                self.llm_provider().compatible_api_for_client(
+                    &SupportedAPIsFromClient::OpenAIChatCompletions(OpenAIApi::ChatCompletions),
+                    false,
+                )
+            }
+            None => chat,
+        }
+    }
+
+    fn modify_auth_headers(&mut self) -> Result<(), ServerError> {
+        let minted_token = self.get_http_request_header(ARCH_UPSTREAM_TOKEN_HEADER);
+        self.remove_http_request_header(ARCH_UPSTREAM_TOKEN_HEADER);
+        let resolved_aws_credentials =
+            self.get_http_request_header(ARCH_UPSTREAM_AWS_CREDENTIALS_HEADER);
+        self.remove_http_request_header(ARCH_UPSTREAM_AWS_CREDENTIALS_HEADER);
+        let resolved_access_key = self.get_http_request_header(ARCH_UPSTREAM_ACCESS_KEY_HEADER);
+        self.remove_http_request_header(ARCH_UPSTREAM_ACCESS_KEY_HEADER);
+        // The key slot is brightstaff's to pick; a client calling this
+        // listener directly may not switch keys.
+        let use_secondary = match self.get_http_request_header(ARCH_ACCESS_KEY_SLOT_HEADER) {
+            Some(slot) if !self.is_local_downstream() => {
+                warn!(
+                    "request_id={}: ignoring {}={} from a non-local client",
+                    self.request_identifier(),
+                    ARCH_ACCESS_KEY_SLOT_HEADER,
+                    slot
+                );
+                false
+            }
+            slot => slot.is_some_and(|slot| slot == "secondary"),
+        };
+        self.remove_http_request_header(ARCH_ACCESS_KEY_SLOT_HEADER);
+
+        if let Some(bedrock) = self.llm_provider().bedrock_sigv4() {
+            // Configured keys win over the ones brightstaff resolved from the
+            // default chain; the signature itself is added with the final body.
+            let configured = match (&bedrock.access_key_id, &bedrock.secret_access_key) {
+                (Some(access_key_id), Some(secret_access_key)) => Some(AwsCredentials {
+                    access_key_id: access_key_id.clone(),
+                    secret_access_key: secret_access_key.clone(),
+                    session_token: bedrock.session_token.clone(),
+                }),
+                _ => None,
+            };
+            let credentials = configured
+                .or_else(|| {
+                    resolved_aws_credentials.and_then(|json| serde_json::from_str(&json).ok())
+                })
+                .ok_or(ServerError::BadRequest {
+                    why: format!(
+                        "No AWS credentials available for selected LLM Provider \"{}\"",
+                        self.llm_provider()
+                    ),
+                })?;
+            self.aws_credentials = Some(credentials);
+
+            let headers = self.header_api().upstream_headers(
+                &self.llm_provider().to_provider_id(),
+                "",
+                self.llm_provider().http_headers.as_ref(),
+            );
+            for name in CLIENT_AUTH_HEADERS {
+                self.remove_http_request_header(name);
+            }
+            for (name, value) in headers {
+                if name != "authorization" {
+                    self.set_http_request_header(&name, Some(&value));
+                }
+            }
+            return Ok(());
+        }
+
+        let custom_provider = self.llm_provider().resolve_custom_provider();
+        if let Some(custom) = custom_provider
+            .as_ref()
+            .filter(|custom| !custom.requires_credential())
+        {
+            let headers = custom.upstream_headers("", self.llm_provider().http_headers.as_ref());
+            for name in CLIENT_AUTH_HEADERS {
+                self.remove_http_request_header(name);
+            }
+            for (name, value) in headers {
+                self.set_http_request_header(&name, Some(&value));
+            }
+            return Ok(());
+        }
+
+        // Determine the credential to forward upstream. Either the client
+        // supplied one (passthrough_auth) or it's configured on the provider.
+        let credential: String = if self.llm_provider().passthrough_auth == Some(true) {
+            // Client auth may arrive in either Anthropic-style (`x-api-key`)
+            // or OpenAI-style (`Authorization: Bearer ...`). Accept both so
+            // clients using Anthropic SDKs (which default to `x-api-key`)
+            // work when the upstream is OpenAI-compatible, and vice versa.
+            let authorization = self.get_http_request_header("Authorization");
+            let x_api_key = self.get_http_request_header("x-api-key");
+            match extract_client_credential(authorization.as_deref(), x_api_key.as_deref()) {
+                Some(key) => {
+                    debug!(
+                        "request_id={}: forwarding client credential to provider '{}'",
+                        self.request_identifier(),
+                        self.llm_provider().name
+                    );
+                    key
+                }
+                None => {
+                    warn!(
+                        "request_id={}: passthrough_auth enabled but no Authorization / x-api-key header present in client request",
+                        self.request_identifier()
+                    );
+                    return Ok(());
+                }
+            }
+        } else if let Some(token) = minted_token
+            .filter(|_| self.llm_provider().provider_interface == LlmProviderType::VertexAI)
+        {
+            // Access token brightstaff minted from the service account key
+            token
+        } else {
+            let secondary = self.llm_provider().secondary_access_key.as_ref();
+            if use_secondary && secondary.is_none() {
+                warn!(
+                    "request_id={}: secondary access key requested but not configured for provider '{}', using primary",
+                    self.request_identifier(),
+                    self.llm_provider().name
+                );
+            }
+            let access_key = secondary
+                .filter(|_| use_secondary)
+                .or(self.llm_provider().access_key.as_ref())
+                .ok_or(ServerError::BadRequest {
+                    why: format!(
+                        "No access key configured for selected LLM Provider \"{}\"",
+                        self.llm_provider()
+                    ),
+                })?;
+            if is_secret_reference(access_key) {
+                // Only brightstaff reads file: and vault: references
+                resolved_access_key.ok_or(ServerError::BadRequest {
+                    why: format!(
+                        "Access key of LLM Provider \"{}\" is a secret reference, which is only resolved for requests routed through brightstaff",
+                        self.llm_provider()
+                    ),
+                })?
+            } else {
+                access_key.clone()
+            }
+        };
+
+        // Normalize the credential into whichever header the upstream expects.
+        // This lets an Anthropic-SDK client reach an OpenAI-compatible upstream
+        // (and vice versa) without the caller needing to know what format the
+        // upstream uses. Header templates live with the upstream API definitions.
+        let headers = match &custom_provider {
+            Some(custom) => {
+                custom.upstream_headers(&credential, self.llm_provider().http_headers.as_ref())
+            }
+            None => self.header_api().upstream_headers(
+                &self.llm_provider().to_provider_id(),
+                &credential,
+                self.llm_provider().http_headers.as_ref(),
+            ),
+        };
+        for name in CLIENT_AUTH_HEADERS {
+            self.remove_http_request_header(name);
+        }
+        for (name, value) in headers {
+            self.set_http_request_header(&name, Some(&value));
+        }
+
+        Ok(())
+    }
+
+    /// Whether the downstream connection comes from this host, as brightstaff's
+    /// does, rather than from a client calling the LLM listener directly.
+    fn is_local_downstream(&self) -> bool {
+        self.get_property(vec!["source", "address"])
+            .and_then(|address| String::from_utf8(address).ok())
+            .is_some_and(|address| is_loopback_address(&address))
+    }
+
+    /// Redirect this request to the base URL in `x-arch-upstream-endpoint`,
+    /// when the override is enabled and the host is allowed. Routing switches to
+    /// a dynamic forward proxy cluster and the URL's path is prefixed onto `:path`.
+    fn apply_upstream_endpoint_override(&mut self) -> Result<(), ServerError> {
+        let Some(endpoint) = self.get_http_request_header(ARCH_UPSTREAM_ENDPOINT_HEADER) else {
+            return Ok(());
+        };
+        self.remove_http_request_header(ARCH_UPSTREAM_ENDPOINT_HEADER);
+
+        let overrides = self.overrides.as_ref().as_ref();
+        if overrides.and_then(|o| o.allow_upstream_endpoint_override) != Some(true) {
+            warn!(
+                "request_id={}: ignoring {} header, upstream endpoint override is disabled",
+                self.request_identifier(),
+                ARCH_UPSTREAM_ENDPOINT_HEADER
+            );
+            return Ok(());
+        }
+
+        let target = parse_upstream_endpoint(&endpoint).ok_or_else(|| ServerError::BadRequest {
+            why: format!(
+                "invalid {} value: {}",
+                ARCH_UPSTREAM_ENDPOINT_HEADER, endpoint
+            ),
+        })?;
+        let allowed_hosts = overrides.and_then(|o| o.upstream_endpoint_allowed_hosts.as_deref());
+        if !is_host_allowed(&target.host, allowed_hosts) {
+            return Err(ServerError::BadRequest {
+                why: format!("upstream endpoint host '{}' is not allowed", target.host),
+            });
+        }
+
+        info!(
+            "request_id={}: overriding upstream endpoint for provider '{}' to {}",
+            self.request_identifier(),
+            self.llm_provider().name,
+            endpoint
+        );
+        let cluster = if target.tls {
+            UPSTREAM_OVERRIDE_TLS_CLUSTER
+        } else {
+            UPSTREAM_OVERRIDE_CLUSTER
+        };
+        self.set_http_request_header(ARCH_ROUTING_HEADER, Some(cluster));
+        self.set_http_request_header(":authority", Some(&target.authority()));
+        if !target.path_prefix.is_empty() {
+            let path = self.get_http_request_header(":path").unwrap_or_default();
+            self.set_http_request_header(":path", Some(&format!("{}{}", target.path_prefix, path)));
+        }
+        Ok(())
+    }
+
+    /// Per-provider `timeout` and `max_retries`, carried to Envoy's router
+    /// filter in `x-envoy-*` headers.
+    fn set_upstream_policy_headers(&mut self) {
+        let timeout_ms = self
+            .llm_provider()
+            .timeout
+            .as_deref()
+            .and_then(parse_duration_ms);
+        let max_retries = self.llm_provider().max_retries;
+        if let Some(timeout_ms) = timeout_ms {
+            self.set_http_request_header(
+                ENVOY_UPSTREAM_TIMEOUT_HEADER,
+                Some(&timeout_ms.to_string()),
+            );
+        }
+        if let Some(max_retries) = max_retries {
+            self.set_http_request_header(ENVOY_RETRY_HEADER, Some(&max_retries.to_string()));
+            self.set_http_request_header(
+                ENVOY_RETRY_ON_HEADER,
+                Some("5xx,connect-failure,refused-stream,reset,retriable-status-codes"),
+            );
+            self.set_http_request_header(ENVOY_RETRIABLE_STATUS_CODES_HEADER, Some("429"));
+        }
+    }
+
+    /// Forward an Anthropic-only endpoint (`/v1/messages/count_tokens`,
+    /// `/v1/messages/batches`) untouched to the selected provider, which must
+    /// speak the Anthropic API.
+    fn route_anthropic_passthrough(&mut self, endpoint: &str) -> Action {
+        if self.get_provider_id() != ProviderId::Anthropic {
+            self.send_server_error(
+                ServerError::BadRequest {
+                    why: format!(
+                        "{} is only supported for anthropic providers, selected '{}'",
+                        endpoint,
+                        self.llm_provider().name
+                    ),
+                },
+                Some(StatusCode::BAD_REQUEST),
+            );
+            return Action::Pause;
+        }
+        self.route_passthrough()
+    }
+
+    /// Forward the request untouched to the selected provider, with only its
+    /// credentials applied.
+    fn route_passthrough(&mut self) -> Action {
+        self.passthrough = true;
+        self.set_routing_header();
+        if let Err(error) = self.modify_auth_headers() {
+            self.send_server_error(error, Some(StatusCode::BAD_REQUEST));
+            return Action::Pause;
+        }
+        self.delete_content_length_header();
+        self.request_id = self.get_http_request_header(REQUEST_ID_HEADER);
+        Action::Continue
+    }
+
+    /// Route a Realtime API WebSocket upgrade to the provider's realtime
+    /// endpoint for the selected model. Frames relay untouched once Envoy has
+    /// upgraded the connection.
+    fn route_realtime(&mut self) -> Action {
+        let model = self
+            .llm_provider()
+            .upstream_model()
+            .or(self.llm_provider().model.as_deref())
+            .unwrap_or_default()
+            .to_string();
+        let Some(path) = realtime_path(
+            &self.get_provider_id(),
+            &model,
+            self.llm_provider().base_url_path_prefix.as_deref(),
+            self.llm_provider().azure_api_version(),
+        ) else {
+            self.send_server_error(
+                ServerError::BadRequest {
+                    why: format!(
+                        "{} is only supported for openai and azure_openai providers, selected '{}'",
+                        REALTIME_PATH,
+                        self.llm_provider().name
+                    ),
+                },
+                Some(StatusCode::BAD_REQUEST),
+            );
+            return Action::Pause;
+        };
+        self.set_http_request_header(":path", Some(&path));
+        self.route_passthrough()
+    }
+
+    /// Route the legacy `/v1/completions`. Providers that still serve it get
+    /// the request untouched; for the rest it goes through the chat
+    /// completions path, translated both ways. Returns `None` in that case.
+    fn route_legacy_completions(&mut self) -> Option<Action> {
+        if serves_legacy_completions(&self.get_provider_id()) {
+            let path = self.configured_path(
+                "completions",
+                legacy_completions_endpoint(
+                    self.llm_provider().base_url_path_prefix.as_deref(),
+                    self.llm_provider().name.starts_with("perplexity/"),
+                ),
+            );
+            self.set_http_request_header(":path", Some(&path));
+            return Some(self.route_passthrough());
+        }
+        self.legacy_completions = true;
+        self.set_http_request_header(":path", Some(CHAT_COMPLETIONS_PATH));
+        None
+    }
+
+    /// Chat completions form of a legacy completions request body.
+    fn legacy_completions_as_chat(body: &[u8]) -> Result<Vec<u8>, String> {
+        let request: CompletionsRequest =
+            serde_json::from_slice(body).map_err(|e| e.to_string())?;
+        let request = ChatCompletionsRequest::try_from(request).map_err(|e| e.to_string())?;
+        serde_json::to_vec(&request).map_err(|e| e.to_string())
+    }
+
+    /// Legacy completions form of a chat completions response body or stream.
+    fn chat_as_legacy_completions(&self, body: Vec<u8>) -> Vec<u8> {
+        if self.streaming_response {
+            return chat_stream_to_completions(&body);
+        }
+        serde_json::from_slice::<ChatCompletionsResponse>(&body)
+            .ok()
+            .and_then(|response| serde_json::to_vec(&CompletionsResponse::from(response)).ok())
+            .unwrap_or(body)
+    }
+
+    /// Route `/v1/embeddings`. Headers are held until the body is read, since
+    /// the upstream path depends on the provider's embeddings API.
+    fn route_embeddings(&mut self) -> Action {
+        self.embeddings = true;
+        self.hold_for_request_body()
+    }
+
+    /// Route `/v1/images/generations`. Like embeddings, the upstream path
+    /// depends on the provider's image API, so headers wait for the body.
+    fn route_images(&mut self) -> Action {
+        self.images = true;
+        self.hold_for_request_body()
+    }
+
+    /// Route `/v1/moderations`. Bedrock guardrails take a different path and
+    /// body, so headers wait for the body.
+    fn route_moderations(&mut self) -> Action {
+        self.moderations = true;
+        self.hold_for_request_body()
+    }
+
+    /// Route `/v1/audio/transcriptions`. Like embeddings, the upstream path
+    /// and content type depend on the provider, so headers wait for the body.
+    fn route_transcriptions(&mut self) -> Action {
+        self.transcription = true;
+        self.hold_for_request_body()
+    }
+
+    /// Apply routing and credentials, then hold the headers until the body
+    /// has been translated.
+    fn hold_for_request_body(&mut self) -> Action {
+        self.request_id = self.get_http_request_header(REQUEST_ID_HEADER);
+        self.traceparent = self.get_http_request_header(TRACE_PARENT_HEADER);
+        self.set_routing_header();
+        self.set_upstream_policy_headers();
+        if let Err(error) = self.modify_auth_headers() {
+            self.send_server_error(error, Some(StatusCode::BAD_REQUEST));
+            return Action::Pause;
+        }
+        self.delete_content_length_header();
+        self.save_ratelimit_header();
+        Action::Pause
+    }
+
+    /// Translate an embeddings request for the provider, then set its path
+    /// and sign it.
+    fn handle_embeddings_request_body(&mut self, body_size: usize, end_of_stream: bool) -> Action {
+        if self.request_body_sent_time.is_none() {
+            self.request_body_sent_time = Some(current_time_ns());
+        }
+        if !end_of_stream {
+            return Action::Pause;
+        }
+
+        let request = self
+            .get_http_request_body(0, body_size)
+            .ok_or_else(|| "empty request body".to_string())
+            .and_then(|body| {
+                serde_json::from_slice::<EmbeddingsRequest>(&body).map_err(|e| e.to_string())
+            });
+        let mut request = match request {
+            Ok(request) => request,
+            Err(e) => {
+                self.send_server_error(
+                    ServerError::BadRequest {
+                        why: format!("Embeddings request parsing error: {}", e),
+                    },
+                    Some(StatusCode::BAD_REQUEST),
+                );
+                return Action::Pause;
+            }
+        };
+
+        let Some(resolved_model) = self.llm_provider().model.clone() else {
+            self.send_server_error(
+                ServerError::BadRequest {
+                    why: format!(
+                        "No model configured for provider '{}'",
+                        self.llm_provider().name
+                    ),
+                },
+                Some(StatusCode::BAD_REQUEST),
+            );
+            return Action::Pause;
+        };
+        info!(
+            "request_id={}: embeddings request, req_model='{}' -> resolved_model='{}' provider='{}'",
+            self.request_identifier(),
+            request.model,
+            resolved_model,
+            self.llm_provider().name
+        );
+        request.model = self
+            .llm_provider()
+            .upstream_model()
+            .unwrap_or(&resolved_model)
+            .to_string();
+
+        let input_text = request.input.texts().unwrap_or_default().join(" ");
+        if let Err(e) = self.enforce_ratelimits(&resolved_model, &input_text) {
+            self.send_server_error(
+                ServerError::ExceededRatelimit(e),
+                Some(StatusCode::TOO_MANY_REQUESTS),
+            );
+            self.metrics.ratelimited_rq.increment(1);
+            return Action::Pause;
+        }
+
+        let provider_id = self.get_provider_id();
+        let upstream = UpstreamEmbeddingsRequest::for_provider(request, &provider_id)
+            .and_then(|upstream| upstream.to_bytes().map(|bytes| (upstream, bytes)));
+        let (upstream, body) = match upstream {
+            Ok(upstream) => upstream,
+            Err(e) => {
+                self.send_server_error(
+                    ServerError::BadRequest { why: e.to_string() },
+                    Some(StatusCode::BAD_REQUEST),
+                );
+                return Action::Pause;
+            }
+        };
+
+        let path = self.configured_path(
+            "embeddings",
+            upstream.path(
+                &provider_id,
+                self.llm_provider().base_url_path_prefix.as_deref(),
+                self.llm_provider().name.starts_with("perplexity/"),
+                self.llm_provider().azure_api_version(),
+            ),
+        );
+        self.set_http_request_header(":path", Some(&path));
+        if let Err(error) = self.apply_upstream_endpoint_override() {
+            self.send_server_error(error, Some(StatusCode::BAD_REQUEST));
+            return Action::Pause;
+        }
+        debug!(
+            "request_id={}: upstream embeddings request, path='{}' payload: {}",
+            self.request_identifier(),
+            path,
+            String::from_utf8_lossy(&body)
+        );
+
+        self.set_http_request_body(0, body_size, &body);
+        self.sign_upstream_request(&body);
+        self.embeddings_request = Some(upstream);
+        Action::Continue
+    }
+
+    /// Translate a native embeddings response into the OpenAI shape. Responses
+    /// of OpenAI-compatible providers are only read for usage.
+    fn handle_embeddings_response(&mut self, body_size: usize, end_of_stream: bool) -> Action {
+        if !end_of_stream {
+            return Action::Pause;
+        }
+        let (Some(upstream), Some(body)) = (
+            self.embeddings_request.as_ref(),
+            self.get_http_response_body(0, body_size),
+        ) else {
+            return Action::Continue;
+        };
+        let is_native = upstream.is_native();
+        match upstream.parse_response(&body) {
+            Ok(response) => {
+                info!(
+                    "request_id={}: embeddings response, vectors={} prompt_tokens={}",
+                    self.request_identifier(),
+                    response.data.len(),
+                    response.usage.prompt_tokens
+                );
+                if is_native {
+                    match serde_json::to_vec(&response) {
+                        Ok(bytes) => self.set_http_response_body(0, body_size, &bytes),
+                        Err(e) => warn!(
+                            "request_id={}: failed to serialize embeddings response: {}",
+                            self.request_identifier(),
+                            e
+                        ),
+                    }
+                }
+            }
+            Err(e) => {
+                warn!(
+                    "request_id={}: upstream embeddings response parse error: {} | body: {}",
+                    self.request_identifier(),
+                    e,
+                    String::from_utf8_lossy(&body)
+                );
+                if is_native {
+                    self.send_server_error(
+                        ServerError::LogicError(format!("Response parsing error: {}", e)),
+                        Some(StatusCode::INTERNAL_SERVER_ERROR),
+                    );
+                }
+            }
+        }
+        Action::Continue
+    }
+
+    /// Translate an image generation request for the provider, then set its
+    /// path and sign it.
+    fn handle_images_request_body(&mut self, body_size: usize, end_of_stream: bool) -> Action {
+        if self.request_body_sent_time.is_none() {
+            self.request_body_sent_time = Some(current_time_ns());
+        }
+        if !end_of_stream {
+            return Action::Pause;
+        }
+
+        let request = self
+            .get_http_request_body(0, body_size)
+            .ok_or_else(|| "empty request body".to_string())
+            .and_then(|body| {
+                serde_json::from_slice::<ImageGenerationRequest>(&body).map_err(|e| e.to_string())
+            });
+        let mut request = match request {
+            Ok(request) => request,
+            Err(e) => {
+                self.send_server_error(
+                    ServerError::BadRequest {
+                        why: format!("Image generation request parsing error: {}", e),
+                    },
+                    Some(StatusCode::BAD_REQUEST),
+                );
+                return Action::Pause;
+            }
+        };
+
+        let Some(resolved_model) = self.llm_provider().model.clone() else {
+            self.send_server_error(
+                ServerError::BadRequest {
+                    why: format!(
+                        "No model configured for provider '{}'",
+                        self.llm_provider().name
+                    ),
+                },
+                Some(StatusCode::BAD_REQUEST),
+            );
+            return Action::Pause;
+        };
+        info!(
+            "request_id={}: image generation request, req_model='{}' -> resolved_model='{}' provider='{}'",
+            self.request_identifier(),
+            request.model,
+            resolved_model,
+            self.llm_provider().name
+        );
+        request.model = self
+            .llm_provider()
+            .upstream_model()
+            .unwrap_or(&resolved_model)
+            .to_string();
+
+        let prompt = request.prompt.clone();
+        if let Err(e) = self.enforce_ratelimits(&resolved_model, &prompt) {
+            self.send_server_error(
+                ServerError::ExceededRatelimit(e),
+                Some(StatusCode::TOO_MANY_REQUESTS),
+            );
+            self.metrics.ratelimited_rq.increment(1);
+            return Action::Pause;
+        }
+
+        let provider_id = self.get_provider_id();
+        let upstream = UpstreamImagesRequest::for_provider(request, &provider_id)
+            .and_then(|upstream| upstream.to_bytes().map(|bytes| (upstream, bytes)));
+        let (upstream, body) = match upstream {
+            Ok(upstream) => upstream,
+            Err(e) => {
+                self.send_server_error(
+                    ServerError::BadRequest { why: e.to_string() },
+                    Some(StatusCode::BAD_REQUEST),
+                );
+                return Action::Pause;
+            }
+        };
+
+        let path = self.configured_path(
+            "images_generations",
+            upstream.path(
+                &provider_id,
+                self.llm_provider().base_url_path_prefix.as_deref(),
+                self.llm_provider().name.starts_with("perplexity/"),
+                self.llm_provider().azure_api_version(),
+            ),
+        );
+        self.set_http_request_header(":path", Some(&path));
+        if let Err(error) = self.apply_upstream_endpoint_override() {
+            self.send_server_error(error, Some(StatusCode::BAD_REQUEST));
+            return Action::Pause;
+        }
+        debug!(
+            "request_id={}: upstream image generation request, path='{}' payload: {}",
+            self.request_identifier(),
+            path,
+            String::from_utf8_lossy(&body)
+        );
+
+        self.set_http_request_body(0, body_size, &body);
+        self.sign_upstream_request(&body);
+        self.images_request = Some(upstream);
+        Action::Continue
+    }
+
+    /// Translate a native image generation response into the OpenAI shape.
+    /// Responses of OpenAI-compatible providers pass through unchanged.
+    fn handle_images_response(&mut self, body_size: usize, end_of_stream: bool) -> Action {
+        if !self
+            .images_request
+            .as_ref()
+            .is_some_and(|upstream| upstream.is_native())
+        {
+            return Action::Continue;
+        }
+        if !end_of_stream {
+            return Action::Pause;
+        }
+        let (Some(upstream), Some(body)) = (
+            self.images_request.as_ref(),
+            self.get_http_response_body(0, body_size),
+        ) else {
+            return Action::Continue;
+        };
+        let translated = upstream
+            .parse_response(&body)
+            .map_err(|e| e.to_string())
+            .and_then(|response| serde_json::to_vec(&response).map_err(|e| e.to_string()));
+        match translated {
+            Ok(translated) => {
+                info!(
+                    "request_id={}: image generation response translated, bytes={}",
+                    self.request_identifier(),
+                    translated.len()
+                );
+                self.set_http_response_body(0, body_size, &translated);
+            }
+            Err(e) => {
+                warn!(
+                    "request_id={}: upstream image generation response parse error: {}",
+                    self.request_identifier(),
+                    e
+                );
+                self.send_server_error(
+                    ServerError::LogicError(format!("Response parsing error: {}", e)),
+                    Some(StatusCode::INTERNAL_SERVER_ERROR),
+                );
+            }
+        }
+        Action::Continue
+    }
+
+    /// Translate a moderation request for the provider, then set its path and
+    /// sign it.
+    fn handle_moderations_request_body(&mut self, body_size: usize, end_of_stream: bool) -> Action {
+        if self.request_body_sent_time.is_none() {
+            self.request_body_sent_time = Some(current_time_ns());
+        }
+        if !end_of_stream {
+            return Action::Pause;
+        }
+
+        let request = self
+            .get_http_request_body(0, body_size)
+            .ok_or_else(|| "empty request body".to_string())
+            .and_then(|body| {
+                serde_json::from_slice::<ModerationRequest>(&body).map_err(|e| e.to_string())
+            });
+        let mut request = match request {
+            Ok(request) => request,
+            Err(e) => {
+                self.send_server_error(
+                    ServerError::BadRequest {
+                        why: format!("Moderation request parsing error: {}", e),
+                    },
+                    Some(StatusCode::BAD_REQUEST),
+                );
+                return Action::Pause;
+            }
+        };
+
+        let Some(resolved_model) = self.llm_provider().model.clone() else {
+            self.send_server_error(
+                ServerError::BadRequest {
+                    why: format!(
+                        "No model configured for provider '{}'",
+                        self.llm_provider().name
+                    ),
+                },
+                Some(StatusCode::BAD_REQUEST),
+            );
+            return Action::Pause;
+        };
+        info!(
+            "request_id={}: moderation request, req_model='{}' -> resolved_model='{}' provider='{}'",
+            self.request_identifier(),
+            request.model.as_deref().unwrap_or_default(),
+            resolved_model,
+            self.llm_provider().name
+        );
+        request.model = Some(
+            self.llm_provider()
+                .upstream_model()
+                .unwrap_or(&resolved_model)
+                .to_string(),
+        );
+
+        let input = request.input.texts().join("\n");
+        if let Err(e) = self.enforce_ratelimits(&resolved_model, &input) {
+            self.send_server_error(
+                ServerError::ExceededRatelimit(e),
+                Some(StatusCode::TOO_MANY_REQUESTS),
+            );
+            self.metrics.ratelimited_rq.increment(1);
+            return Action::Pause;
+        }
+
+        let provider_id = self.get_provider_id();
+        let upstream = UpstreamModerationRequest::for_provider(request, &provider_id)
+            .and_then(|upstream| upstream.to_bytes().map(|bytes| (upstream, bytes)));
+        let (upstream, body) = match upstream {
+            Ok(upstream) => upstream,
+            Err(e) => {
+                self.send_server_error(
+                    ServerError::BadRequest { why: e.to_string() },
+                    Some(StatusCode::BAD_REQUEST),
+                );
+                return Action::Pause;
+            }
+        };
+
+        let path = self.configured_path(
+            "moderations",
+            upstream.path(
+                self.llm_provider().base_url_path_prefix.as_deref(),
+                self.llm_provider().name.starts_with("perplexity/"),
+            ),
+        );
+        self.set_http_request_header(":path", Some(&path));
+        if let Err(error) = self.apply_upstream_endpoint_override() {
+            self.send_server_error(error, Some(StatusCode::BAD_REQUEST));
+            return Action::Pause;
+        }
+        debug!(
+            "request_id={}: upstream moderation request, path='{}' payload: {}",
+            self.request_identifier(),
+            path,
+            String::from_utf8_lossy(&body)
+        );
+
+        self.set_http_request_body(0, body_size, &body);
+        self.sign_upstream_request(&body);
+        self.moderations_request = Some(upstream);
+        Action::Continue
+    }
+
+    /// Translate a guardrail assessment into the OpenAI moderation shape.
+    /// Responses of OpenAI-compatible providers pass through unchanged.
+    fn handle_moderations_response(&mut self, body_size: usize, end_of_stream: bool) -> Action {
+        if !self
+            .moderations_request
+            .as_ref()
+            .is_some_and(|upstream| upstream.is_native())
+        {
+            return Action::Continue;
+        }
+        if !end_of_stream {
+            return Action::Pause;
+        }
+        let (Some(upstream), Some(body)) = (
+            self.moderations_request.as_ref(),
+            self.get_http_response_body(0, body_size),
+        ) else {
+            return Action::Continue;
+        };
+        let translated = upstream
+            .parse_response(&body, &self.request_identifier())
+            .map_err(|e| e.to_string())
+            .and_then(|response| serde_json::to_vec(&response).map_err(|e| e.to_string()));
+        match translated {
+            Ok(translated) => {
+                info!(
+                    "request_id={}: moderation response translated, bytes={}",
+                    self.request_identifier(),
+                    translated.len()
+                );
+                self.set_http_response_body(0, body_size, &translated);
+            }
+            Err(e) => {
+                warn!(
+                    "request_id={}: upstream moderation response parse error: {}",
+                    self.request_identifier(),
+                    e
+                );
+                self.send_server_error(
+                    ServerError::LogicError(format!("Response parsing error: {}", e)),
+                    Some(StatusCode::INTERNAL_SERVER_ERROR),
+                );
+            }
+        }
+        Action::Continue
+    }
+
+    /// Translate a transcription form for the provider, then set its path,
+    /// content type and body.
+    fn handle_transcription_request_body(
+        &mut self,
+        body_size: usize,
+        end_of_stream: bool,
+    ) -> Action {
+        if self.request_body_sent_time.is_none() {
+            self.request_body_sent_time = Some(current_time_ns());
+        }
+        if !end_of_stream {
+            return Action::Pause;
+        }
+
+        let boundary = self
+            .get_http_request_header("content-type")
+            .as_deref()
+            .and_then(form_boundary);
+        let request = match boundary {
+            Some(boundary) => self
+                .get_http_request_body(0, body_size)
+                .ok_or_else(|| "empty request body".to_string())
+                .and_then(|body| TranscriptionRequest::parse(&body, &boundary))
+                .map(|request| (request, boundary)),
+            None => Err("expected a multipart/form-data body".to_string()),
+        };
+        let (mut request, boundary) = match request {
+            Ok(request) => request,
+            Err(e) => {
+                self.send_server_error(
+                    ServerError::BadRequest {
+                        why: format!("Transcription request parsing error: {}", e),
+                    },
+                    Some(StatusCode::BAD_REQUEST),
+                );
+                return Action::Pause;
+            }
+        };
+
+        let Some(resolved_model) = self.llm_provider().model.clone() else {
+            self.send_server_error(
+                ServerError::BadRequest {
+                    why: format!(
+                        "No model configured for provider '{}'",
+                        self.llm_provider().name
+                    ),
+                },
+                Some(StatusCode::BAD_REQUEST),
+            );
+            return Action::Pause;
+        };
+        info!(
+            "request_id={}: transcription request, req_model='{}' -> resolved_model='{}' provider='{}'",
+            self.request_identifier(),
+            request.model(),
+            resolved_model,
+            self.llm_provider().name
+        );
+        let upstream_model = self
+            .llm_provider()
+            .upstream_model()
+            .unwrap_or(&resolved_model)
+            .to_string();
+        request.set_model(&upstream_model);
+
+        // Audio has no prompt tokens; only the request itself is counted
+        if let Err(e) = self.enforce_ratelimits(&resolved_model, "") {
+            self.send_server_error(
+                ServerError::ExceededRatelimit(e),
+                Some(StatusCode::TOO_MANY_REQUESTS),
+            );
+            self.metrics.ratelimited_rq.increment(1);
+            return Action::Pause;
+        }
+
+        let provider_id = self.get_provider_id();
+        let upstream = match UpstreamTranscriptionRequest::for_provider(request, &provider_id) {
+            Ok(upstream) => upstream,
+            Err(e) => {
+                self.send_server_error(
+                    ServerError::BadRequest { why: e.to_string() },
+                    Some(StatusCode::BAD_REQUEST),
+                );
+                return Action::Pause;
+            }
+        };
+
+        let path = self.configured_path(
+            "audio_transcriptions",
+            upstream.path(
+                &provider_id,
+                self.llm_provider().base_url_path_prefix.as_deref(),
+                self.llm_provider().name.starts_with("perplexity/"),
+                self.llm_provider().azure_api_version(),
+            ),
+        );
+        self.set_http_request_header(":path", Some(&path));
+        if let Err(error) = self.apply_upstream_endpoint_override() {
+            self.send_server_error(error, Some(StatusCode::BAD_REQUEST));
+            return Action::Pause;
+        }
+        let (content_type, body) = upstream.to_bytes(&boundary);
+        debug!(
+            "request_id={}: upstream transcription request, path='{}' content_type='{}' bytes={}",
+            self.request_identifier(),
+            path,
+            content_type,
+            body.len()
         );
 
-        Ok(())
+        self.set_http_request_header("content-type", Some(&content_type));
+        self.set_http_request_body(0, body_size, &body);
+        self.sign_upstream_request(&body);
+        self.transcription_request = Some(upstream);
+        Action::Continue
     }
 
-    fn modify_auth_headers(&mut self) -> Result<(), ServerError> {
-        // Determine the credential to forward upstream. Either the client
-        // supplied one (passthrough_auth) or it's configured on the provider.
-        let credential: String = if self.llm_provider().passthrough_auth == Some(true) {
-            // Client auth may arrive in either Anthropic-style (`x-api-key`)
-            // or OpenAI-style (`Authorization: Bearer ...`). Accept both so
-            // clients using Anthropic SDKs (which default to `x-api-key`)
-            // work when the upstream is OpenAI-compatible, and vice versa.
-            let authorization = self.get_http_request_header("Authorization");
-            let x_api_key = self.get_http_request_header("x-api-key");
-            match extract_client_credential(authorization.as_deref(), x_api_key.as_deref()) {
-                Some(key) => {
-                    debug!(
-                        "request_id={}: forwarding client credential to provider '{}'",
-                        self.request_identifier(),
-                        self.llm_provider().name
-                    );
-                    key
-                }
-                None => {
-                    warn!(
-                        "request_id={}: passthrough_auth enabled but no Authorization / x-api-key header present in client request",
-                        self.request_identifier()
-                    );
-                    return Ok(());
-                }
+    /// Render a native transcription response in the format the client asked
+    /// for. Responses of OpenAI-compatible providers pass through, streamed
+    /// or not.
+    fn handle_transcription_response(&mut self, body_size: usize, end_of_stream: bool) -> Action {
+        if !self
+            .transcription_request
+            .as_ref()
+            .is_some_and(|upstream| upstream.is_native())
+        {
+            return Action::Continue;
+        }
+        if !end_of_stream {
+            return Action::Pause;
+        }
+        let (Some(upstream), Some(body)) = (
+            self.transcription_request.as_ref(),
+            self.get_http_response_body(0, body_size),
+        ) else {
+            return Action::Continue;
+        };
+        match upstream.translate_response(&body) {
+            Ok(translated) => self.set_http_response_body(0, body_size, &translated),
+            Err(e) => {
+                warn!(
+                    "request_id={}: upstream transcription response parse error: {} | body: {}",
+                    self.request_identifier(),
+                    e,
+                    String::from_utf8_lossy(&body)
+                );
+                self.send_server_error(
+                    ServerError::LogicError(format!("Response parsing error: {}", e)),
+                    Some(StatusCode::INTERNAL_SERVER_ERROR),
+                );
             }
-        } else {
-            self.llm_provider()
-                .access_key
-                .as_ref()
-                .ok_or(ServerError::BadRequest {
-                    why: format!(
-                        "No access key configured for selected LLM Provider \"{}\"",
-                        self.llm_provider()
-                    ),
-                })?
-                .clone()
+        }
+        Action::Continue
+    }
+
+    /// Send a bare `GET` to the provider's health check path (or `/`) so Envoy
+    /// opens its upstream connection. No credentials are attached.
+    fn route_warmup(&mut self) -> Action {
+        self.passthrough = true;
+        self.set_routing_header();
+        let path = self
+            .llm_provider()
+            .health_check
+            .as_ref()
+            .map_or_else(|| "/".to_string(), |check| check.path.clone());
+        self.set_http_request_header(":path", Some(&path));
+        self.set_http_request_header(":method", Some("GET"));
+        self.request_id = self.get_http_request_header(REQUEST_ID_HEADER);
+        Action::Continue
+    }
+
+    /// Add the SigV4 headers for `body`. Only valid while request headers are
+    /// still held, which `on_http_request_headers` does for signed providers.
+    fn sign_upstream_request(&mut self, body: &[u8]) {
+        let Some(credentials) = self.aws_credentials.take() else {
+            return;
+        };
+        let Some(host) = self
+            .llm_provider()
+            .endpoint
+            .clone()
+            .or_else(|| self.get_http_request_header(":authority"))
+        else {
+            warn!(
+                "request_id={}: no upstream host to sign request for provider '{}'",
+                self.request_identifier(),
+                self.llm_provider().name
+            );
+            return;
+        };
+        let bedrock = self.llm_provider().bedrock_sigv4();
+        let Some(region) = bedrock
+            .and_then(|bedrock| bedrock.region.clone())
+            .or_else(|| aws_sigv4::region_from_host(&host).map(str::to_string))
+        else {
+            warn!(
+                "request_id={}: no AWS region configured or found in host '{}'",
+                self.request_identifier(),
+                host
+            );
+            return;
         };
 
-        // Normalize the credential into whichever header the upstream expects.
-        // This lets an Anthropic-SDK client reach an OpenAI-compatible upstream
-        // (and vice versa) without the caller needing to know what format the
-        // upstream uses.
-        match self.resolved_api.as_ref() {
-            Some(SupportedUpstreamAPIs::AnthropicMessagesAPI(_)) => {
-                // Anthropic expects `x-api-key` + `anthropic-version`.
-                self.remove_http_request_header("Authorization");
-                self.set_http_request_header("x-api-key", Some(&credential));
-                self.set_http_request_header("anthropic-version", Some("2023-06-01"));
-            }
-            Some(
-                SupportedUpstreamAPIs::OpenAIChatCompletions(_)
-                | SupportedUpstreamAPIs::AmazonBedrockConverse(_)
-                | SupportedUpstreamAPIs::AmazonBedrockConverseStream(_)
-                | SupportedUpstreamAPIs::OpenAIResponsesAPI(_),
-            )
-            | None => {
-                // OpenAI (and default): `Authorization: Bearer ...`.
-                self.remove_http_request_header("x-api-key");
-                let authorization_header_value = format!("Bearer {}", credential);
-                self.set_http_request_header("Authorization", Some(&authorization_header_value));
-            }
+        let method = self
+            .get_http_request_header(":method")
+            .unwrap_or_else(|| "POST".to_string());
+        let path = self.get_http_request_header(":path").unwrap_or_default();
+        let now = self
+            .get_current_time()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let headers = aws_sigv4::sign(
+            &SignableRequest {
+                method: &method,
+                host: &host,
+                path: &path,
+                body,
+            },
+            &credentials,
+            &region,
+            aws_sigv4::BEDROCK_SIGNING_SERVICE,
+            now,
+        );
+        self.set_http_request_header(":authority", Some(&host));
+        for (name, value) in headers {
+            self.set_http_request_header(name, Some(&value));
         }
+    }
 
-        Ok(())
+    fn set_routing_header(&mut self) {
+        // Clone cluster_name to avoid borrowing self while calling add_http_request_header (which requires mut self)
+        let cluster_name_opt = self.llm_provider().cluster_name.clone();
+
+        if let Some(cluster_name) = cluster_name_opt {
+            self.add_http_request_header(ARCH_ROUTING_HEADER, &cluster_name);
+        } else {
+            self.add_http_request_header(
+                ARCH_ROUTING_HEADER,
+                &self.llm_provider().provider_interface.to_string(),
+            );
+        }
     }
 
     fn delete_content_length_header(&mut self) {
@@ -284,6 +1449,7 @@ impl StreamContext {
     ) -> Result<(), ratelimit::Error> {
         // Tokenize and record token count.
         let token_count = tokenizer::token_count(model, json_string).unwrap_or(0);
+        self.input_tokens = token_count;
 
         debug!(
             "request_id={}: token count, model='{}' input_tokens={}",
@@ -350,6 +1516,13 @@ impl StreamContext {
         }
     }
     fn handle_end_of_request_metrics_and_traces(&mut self, current_time: SystemTime) {
+        // Output tokens the upstream reported beat the estimate from content deltas
+        if let Some(usage) = self.sse_buffer.as_ref().and_then(|b| b.final_usage()) {
+            if usage.completion_tokens > 0 {
+                self.response_tokens = usage.completion_tokens;
+            }
+        }
+
         // All streaming responses end with bytes=0 and end_stream=true
         // Record the latency for the request
         match current_time.duration_since(self.start_time) {
@@ -462,17 +1635,23 @@ impl StreamContext {
         match self.client_api.as_ref() {
             Some(client_api) => {
                 let client_api = client_api.clone(); // Clone to avoid borrowing issues
-                let upstream_api =
-                    provider_id.compatible_api_for_client(&client_api, self.streaming_response);
+                let upstream_api = self
+                    .llm_provider()
+                    .compatible_api_for_client(&client_api, self.streaming_response);
 
                 // Check if this is Bedrock binary stream
                 if matches!(
                     upstream_api,
                     SupportedUpstreamAPIs::AmazonBedrockConverseStream(_)
+                        | SupportedUpstreamAPIs::AmazonBedrockInvokeModelStream(_)
                 ) {
                     return self.handle_bedrock_binary_stream(body, &client_api, &upstream_api);
                 }
 
+                if self.is_passthrough(&client_api, &upstream_api) {
+                    return self.scan_passthrough_stream(body, &client_api, &upstream_api);
+                }
+
                 // Initialize SSE chunk processor if not present
                 if self.sse_chunk_processor.is_none() {
                     self.sse_chunk_processor = Some(SseChunkProcessor::new());
@@ -557,6 +1736,9 @@ impl StreamContext {
                                         estimated_tokens.max(1),
                                         self.response_tokens
                                     );
+                                    if self.stream_usage_requested {
+                                        self.streamed_text.push_str(content);
+                                    }
                                 }
                             }
                             Err(e) => {
@@ -570,12 +1752,42 @@ impl StreamContext {
                         }
                     }
 
+                    if self.stream_usage_requested {
+                        if let Some(ProviderStreamResponseType::ChatCompletionsStreamResponse(
+                            chunk,
+                        )) = &transformed_event.provider_stream_response
+                        {
+                            self.stream_id_and_model
+                                .get_or_insert_with(|| (chunk.id.clone(), chunk.model.clone()));
+                        }
+                        if transformed_event.is_done() {
+                            self.add_estimated_usage_chunk();
+                        }
+                    }
+
                     // Add transformed event to buffer (buffer may inject lifecycle events)
                     if let Some(buffer) = self.sse_buffer.as_mut() {
                         buffer.add_transformed_event(transformed_event);
                     }
                 }
 
+                // End the stream in the client's shape when the upstream reported an error
+                let upstream_error = self
+                    .sse_chunk_processor
+                    .as_mut()
+                    .and_then(SseChunkProcessor::take_upstream_error);
+                if let Some(error) = upstream_error {
+                    warn!(
+                        "request_id={}: upstream error mid-stream, status={} message={}",
+                        self.request_identifier(),
+                        error.status,
+                        error.message
+                    );
+                    if let Some(buffer) = self.sse_buffer.as_mut() {
+                        buffer.add_upstream_error(&error);
+                    }
+                }
+
                 // Get accumulated bytes from buffer and return
                 match self.sse_buffer.as_mut() {
                     Some(buffer) => {
@@ -610,6 +1822,85 @@ impl StreamContext {
         }
     }
 
+    /// Whether the upstream response can be forwarded untouched: the upstream
+    /// speaks the client's API and no usage chunk or legacy completions
+    /// reshaping has to be added to it.
+    fn is_passthrough(
+        &self,
+        client_api: &SupportedAPIsFromClient,
+        upstream_api: &SupportedUpstreamAPIs,
+    ) -> bool {
+        !needs_buffering(client_api, upstream_api)
+            && !self.stream_usage_requested
+            && !self.legacy_completions
+    }
+
+    /// Scan a passthrough stream chunk for usage and the end of the stream
+    /// without parsing or re-serializing it; the chunk is forwarded as is.
+    fn scan_passthrough_stream(
+        &mut self,
+        body: &[u8],
+        client_api: &SupportedAPIsFromClient,
+        upstream_api: &SupportedUpstreamAPIs,
+    ) -> Result<Vec<u8>, Action> {
+        if self.sse_buffer.is_none() {
+            self.sse_buffer = Some(SseStreamBuffer::Passthrough(PassthroughStreamBuffer::new()));
+        }
+        let Some(SseStreamBuffer::Passthrough(buffer)) = self.sse_buffer.as_mut() else {
+            return Err(Action::Continue);
+        };
+        let events = buffer.scan_chunk(body, client_api, upstream_api);
+        let finished = buffer.is_finished();
+        if events > 0 {
+            self.record_ttft_if_needed();
+            // Without decoding deltas, count an event as a token until the
+            // upstream reports usage
+            self.response_tokens += events;
+        }
+        if finished {
+            debug!(
+                "request_id={}: passthrough stream finished, total_tokens={}",
+                self.request_identifier(),
+                self.response_tokens
+            );
+        }
+        Err(Action::Continue)
+    }
+
+    /// Add a usage chunk ahead of `[DONE]` when the client asked for usage
+    /// but the upstream streamed none, as some OpenAI-compatible servers
+    /// ignore `stream_options`. Token counts come from the tokenizer.
+    fn add_estimated_usage_chunk(&mut self) {
+        let (id, model) = self
+            .stream_id_and_model
+            .clone()
+            .unwrap_or_else(|| ("stream".to_string(), self.llm_provider().name.clone()));
+        let created = self
+            .get_current_time()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let Some(buffer) = self.sse_buffer.as_mut() else {
+            return;
+        };
+        let estimated = add_estimated_usage(
+            buffer,
+            id,
+            model,
+            self.input_tokens,
+            &self.streamed_text,
+            created,
+        );
+        if let Some(usage) = estimated {
+            debug!(
+                "request_id={}: upstream streamed no usage, estimated prompt_tokens={} completion_tokens={}",
+                self.request_identifier(),
+                usage.prompt_tokens,
+                usage.completion_tokens
+            );
+        }
+    }
+
     fn handle_bedrock_binary_stream(
         &mut self,
         body: &[u8],
@@ -740,7 +2031,35 @@ impl StreamContext {
 
         let response: ProviderResponseType = match self.client_api.as_ref() {
             Some(client_api) => {
-                match ProviderResponseType::try_from((body, client_api, &provider_id)) {
+                let upstream_api = self
+                    .llm_provider()
+                    .compatible_api_for_client(client_api, self.streaming_response);
+                if self.is_passthrough(client_api, &upstream_api) {
+                    // Forward the body untouched, reading only its usage
+                    match UsageDetails::from_response_body(body, client_api) {
+                        Some(usage) => self.response_tokens = usage.completion_tokens,
+                        None => warn!(
+                            "request_id={}: response usage, no usage information found",
+                            self.request_identifier()
+                        ),
+                    }
+                    return Err(Action::Continue);
+                }
+                let converted = match self.llm_provider().resolve_custom_provider() {
+                    Some(custom) => custom.transform_response(
+                        body,
+                        client_api,
+                        &upstream_api,
+                        self.transform_options,
+                    ),
+                    None => ProviderResponseType::try_from((
+                        body,
+                        client_api,
+                        &upstream_api,
+                        self.transform_options,
+                    )),
+                };
+                match converted {
                     Ok(response) => response,
                     Err(e) => {
                         warn!(
@@ -829,6 +2148,9 @@ impl HttpContext for StreamContext {
             .get_http_request_header(ARCH_IS_STREAMING_HEADER)
             .map(|val| val == "true")
             .unwrap_or(false);
+        self.transform_options.strict = self
+            .get_http_request_header(ARCH_STRICT_TRANSFORM_HEADER)
+            .is_some_and(|val| val == "true");
 
         // let routing_header_value = self.get_http_request_header(ARCH_ROUTING_HEADER);
 
@@ -840,6 +2162,46 @@ impl HttpContext for StreamContext {
             );
             return Action::Continue;
         }
+        if let Some(default_max_tokens) = self.llm_provider().default_max_tokens {
+            self.transform_options.default_max_tokens = default_max_tokens;
+        }
+
+        if request_path == ANTHROPIC_COUNT_TOKENS_PATH {
+            return self.route_anthropic_passthrough(ANTHROPIC_COUNT_TOKENS_PATH);
+        }
+        if request_path.starts_with(ANTHROPIC_MESSAGE_BATCHES_PATH) {
+            return self.route_anthropic_passthrough(ANTHROPIC_MESSAGE_BATCHES_PATH);
+        }
+        // Batches and their files on OpenAI-compatible providers
+        if request_path.starts_with(BATCHES_PATH) || request_path.starts_with(FILES_PATH) {
+            return self.route_passthrough();
+        }
+        if request_path.split('?').next() == Some(REALTIME_PATH) {
+            return self.route_realtime();
+        }
+        if request_path == WARMUP_PATH {
+            return self.route_warmup();
+        }
+        if request_path == EMBEDDINGS_PATH {
+            return self.route_embeddings();
+        }
+        if request_path == IMAGES_GENERATIONS_PATH {
+            return self.route_images();
+        }
+        if request_path == AUDIO_TRANSCRIPTIONS_PATH {
+            return self.route_transcriptions();
+        }
+        if request_path == MODERATIONS_PATH {
+            return self.route_moderations();
+        }
+        let request_path = if request_path == COMPLETIONS_PATH {
+            match self.route_legacy_completions() {
+                Some(action) => return action,
+                None => CHAT_COMPLETIONS_PATH.to_string(),
+            }
+        } else {
+            request_path
+        };
 
         // Check if this is a supported API endpoint
         if SupportedAPIsFromClient::from_endpoint(&request_path).is_none() {
@@ -855,9 +2217,8 @@ impl HttpContext for StreamContext {
         // Debug: log provider, client API, resolved API, and request path
         if let (Some(api), Some(provider)) = (self.client_api.as_ref(), self.llm_provider.as_ref())
         {
-            let provider_id = provider.to_provider_id();
             self.resolved_api =
-                Some(provider_id.compatible_api_for_client(api, self.streaming_response));
+                Some(provider.compatible_api_for_client(api, self.streaming_response));
 
             debug!(
                 "request_id={}: routing info, provider='{}' client_api={:?} resolved_api={:?} request_path='{}'",
@@ -871,16 +2232,11 @@ impl HttpContext for StreamContext {
             //We need to update the upstream path if there is a variation for a provider like Gemini/Groq, etc.
             self.update_upstream_path(&request_path);
 
-            // Clone cluster_name to avoid borrowing self while calling add_http_request_header (which requires mut self)
-            let cluster_name_opt = self.llm_provider().cluster_name.clone();
-
-            if let Some(cluster_name) = cluster_name_opt {
-                self.add_http_request_header(ARCH_ROUTING_HEADER, &cluster_name);
-            } else {
-                self.add_http_request_header(
-                    ARCH_ROUTING_HEADER,
-                    &self.llm_provider().provider_interface.to_string(),
-                );
+            self.set_routing_header();
+            self.set_upstream_policy_headers();
+            if let Err(error) = self.apply_upstream_endpoint_override() {
+                self.send_server_error(error, Some(StatusCode::BAD_REQUEST));
+                return Action::Pause;
             }
             if let Err(error) = self.modify_auth_headers() {
                 // ensure that the provider has an endpoint if the access key is missing else return a bad request
@@ -899,6 +2255,10 @@ impl HttpContext for StreamContext {
         self.request_id = self.get_http_request_header(REQUEST_ID_HEADER);
         self.traceparent = self.get_http_request_header(TRACE_PARENT_HEADER);
 
+        if self.aws_credentials.is_some() {
+            // Hold the headers: the SigV4 signature covers the final body
+            return Action::Pause;
+        }
         Action::Continue
     }
 
@@ -910,6 +2270,22 @@ impl HttpContext for StreamContext {
             end_of_stream
         );
 
+        if self.passthrough {
+            return Action::Continue;
+        }
+        if self.embeddings {
+            return self.handle_embeddings_request_body(body_size, end_of_stream);
+        }
+        if self.images {
+            return self.handle_images_request_body(body_size, end_of_stream);
+        }
+        if self.moderations {
+            return self.handle_moderations_request_body(body_size, end_of_stream);
+        }
+        if self.transcription {
+            return self.handle_transcription_request_body(body_size, end_of_stream);
+        }
+
         // Let the client send the gateway all the data before sending to the LLM_provider.
         // TODO: consider a streaming API.
 
@@ -939,6 +2315,23 @@ impl HttpContext for StreamContext {
             }
         };
 
+        let body_bytes = if self.legacy_completions {
+            match Self::legacy_completions_as_chat(&body_bytes) {
+                Ok(body_bytes) => body_bytes,
+                Err(e) => {
+                    self.send_server_error(
+                        ServerError::BadRequest {
+                            why: format!("Invalid completions request: {}", e),
+                        },
+                        Some(StatusCode::BAD_REQUEST),
+                    );
+                    return Action::Pause;
+                }
+            }
+        } else {
+            body_bytes
+        };
+
         //We need to deserialize the request body based on the resolved API
         let mut deserialized_client_request: ProviderRequestType = match self.client_api.as_ref() {
             Some(the_client_api) => {
@@ -1015,8 +2408,14 @@ impl HttpContext for StreamContext {
             }
         };
 
-        // Set the resolved model using the trait method
-        deserialized_client_request.set_model(resolved_model.clone());
+        // Set the resolved model using the trait method. Azure OpenAI takes
+        // the deployment name here.
+        let upstream_model = self
+            .llm_provider()
+            .upstream_model()
+            .unwrap_or(&resolved_model)
+            .to_string();
+        deserialized_client_request.set_model(upstream_model);
 
         // Extract user message for tracing
         self.user_message = deserialized_client_request.get_recent_user_message();
@@ -1035,6 +2434,13 @@ impl HttpContext for StreamContext {
         if !self.streaming_response {
             self.streaming_response = deserialized_client_request.is_streaming();
         }
+        if let ProviderRequestType::ChatCompletionsRequest(request) = &deserialized_client_request {
+            self.stream_usage_requested = request
+                .stream_options
+                .as_ref()
+                .and_then(|options| options.include_usage)
+                .unwrap_or(false);
+        }
 
         // Use provider interface for text extraction (after potential mutation)
         let input_tokens_str = deserialized_client_request.extract_messages_text();
@@ -1058,8 +2464,27 @@ impl HttpContext for StreamContext {
                     upstream
                 );
 
-                match ProviderRequestType::try_from((deserialized_client_request, upstream)) {
-                    Ok(mut request) => {
+                // Custom providers run their registered hooks after the conversion
+                let converted = match self.llm_provider().resolve_custom_provider() {
+                    Some(custom) => custom
+                        .transform_request(
+                            deserialized_client_request,
+                            upstream,
+                            self.transform_options,
+                        )
+                        .map(|upstream| (upstream.request, upstream.headers)),
+                    None => ProviderRequestType::try_from((
+                        deserialized_client_request,
+                        upstream,
+                        self.transform_options,
+                    ))
+                    .map(|request| (request, Vec::new())),
+                };
+                match converted {
+                    Ok((mut request, hook_headers)) => {
+                        for (name, value) in hook_headers {
+                            self.set_http_request_header(&name, Some(&value));
+                        }
                         request.normalize_for_upstream(self.get_provider_id(), upstream);
                         debug!(
                             "request_id={}: upstream request payload: {}",
@@ -1068,7 +2493,18 @@ impl HttpContext for StreamContext {
                         );
 
                         match request.to_bytes() {
-                            Ok(bytes) => bytes,
+                            Ok(bytes) => match apply_provider_params(&bytes, self.llm_provider()) {
+                                Ok(Some(with_params)) => with_params,
+                                Ok(None) => bytes,
+                                Err(e) => {
+                                    warn!(
+                                        "request_id={}: failed to apply provider params: {}",
+                                        self.request_identifier(),
+                                        e
+                                    );
+                                    bytes
+                                }
+                            },
                             Err(e) => {
                                 warn!(
                                     "request_id={}: failed to serialize request body: {}",
@@ -1114,6 +2550,7 @@ impl HttpContext for StreamContext {
         };
 
         self.set_http_request_body(0, body_size, &serialized_body_bytes_upstream);
+        self.sign_upstream_request(&serialized_body_bytes_upstream);
         Action::Continue
     }
 
@@ -1134,6 +2571,32 @@ impl HttpContext for StreamContext {
         self.remove_http_response_header("content-length");
         self.remove_http_response_header("content-encoding");
 
+        // Ollama streams application/x-ndjson, which is reframed as SSE
+        if self.streaming_response
+            && matches!(
+                self.resolved_api,
+                Some(SupportedUpstreamAPIs::OllamaChat(_))
+            )
+            && self
+                .upstream_status_code
+                .is_some_and(|status| status.is_success())
+        {
+            self.set_http_response_header("content-type", Some("text/event-stream"));
+        }
+        // Native transcriptions are rendered in the format the client asked for
+        if let Some(upstream) = self
+            .transcription_request
+            .as_ref()
+            .filter(|upstream| upstream.is_native())
+            .filter(|_| {
+                self.upstream_status_code
+                    .is_some_and(|status| status.is_success())
+            })
+        {
+            let content_type = upstream.response_content_type();
+            self.set_http_response_header("content-type", Some(content_type));
+        }
+
         self.set_property(
             vec!["metadata", "filter_metadata", "llm_filter", "user_prompt"],
             Some("hello world from filter".as_bytes()),
@@ -1172,22 +2635,57 @@ impl HttpContext for StreamContext {
                     body_size
                 );
 
-                // For error responses, forward the upstream error directly without parsing
+                // Errors from an upstream speaking a different dialect are
+                // buffered in full and reshaped into the client's error format.
+                let needs_translation = match (&self.resolved_api, &self.client_api) {
+                    (Some(upstream_api), Some(client_api)) => {
+                        ErrorDialect::from(upstream_api) != ErrorDialect::from(client_api)
+                    }
+                    _ => false,
+                };
+                if needs_translation && !end_of_stream {
+                    return Action::Pause;
+                }
+
                 if body_size > 0 {
-                    if let Ok(body) = self.read_raw_response_body(body_size) {
+                    if let Some(body) = self.get_http_response_body(0, body_size) {
                         debug!(
                             "request_id={}: upstream error body: {}",
                             self.request_identifier(),
                             String::from_utf8_lossy(&body)
                         );
-                        // Forward the error response as-is
-                        self.set_http_response_body(0, body_size, &body);
+                        let translated = match (&self.resolved_api, &self.client_api) {
+                            (Some(upstream_api), Some(client_api)) if needs_translation => {
+                                translate_error_body(
+                                    &body,
+                                    status_code.as_u16(),
+                                    upstream_api,
+                                    client_api,
+                                )
+                            }
+                            _ => None,
+                        };
+                        // Otherwise forward the error response as-is
+                        self.set_http_response_body(0, body_size, &translated.unwrap_or(body));
                     }
                 }
                 return Action::Continue;
             }
         }
 
+        if self.embeddings {
+            return self.handle_embeddings_response(body_size, end_of_stream);
+        }
+        if self.images {
+            return self.handle_images_response(body_size, end_of_stream);
+        }
+        if self.moderations {
+            return self.handle_moderations_response(body_size, end_of_stream);
+        }
+        if self.transcription {
+            return self.handle_transcription_response(body_size, end_of_stream);
+        }
+
         match self.client_api {
             Some(SupportedAPIsFromClient::OpenAIChatCompletions(_)) => {}
             Some(SupportedAPIsFromClient::AnthropicMessagesAPI(_)) => {}
@@ -1219,20 +2717,20 @@ impl HttpContext for StreamContext {
         );
 
         let provider_id = self.get_provider_id();
-        if self.streaming_response {
-            match self.handle_streaming_response(&body, provider_id) {
-                Ok(serialized_body) => {
-                    self.set_http_response_body(0, body_size, &serialized_body);
-                }
-                Err(action) => return action,
-            }
+        let serialized_body = if self.streaming_response {
+            self.handle_streaming_response(&body, provider_id)
         } else {
-            match self.handle_non_streaming_response(&body, provider_id) {
-                Ok(serialized_body) => {
-                    self.set_http_response_body(0, body_size, &serialized_body);
-                }
-                Err(action) => return action,
+            self.handle_non_streaming_response(&body, provider_id)
+        };
+        match serialized_body {
+            Ok(serialized_body) if self.legacy_completions => {
+                let serialized_body = self.chat_as_legacy_completions(serialized_body);
+                self.set_http_response_body(0, body_size, &serialized_body);
+            }
+            Ok(serialized_body) => {
+                self.set_http_response_body(0, body_size, &serialized_body);
             }
+            Err(action) => return action,
         }
 
         Action::Continue
@@ -1279,9 +2777,173 @@ fn extract_client_credential(
         .map(|s| s.to_string())
 }
 
+/// Base URL parsed from the `x-arch-upstream-endpoint` header.
+#[derive(Debug, PartialEq)]
+struct UpstreamEndpoint {
+    tls: bool,
+    host: String,
+    port: u16,
+    /// Path of the base URL without a trailing slash, e.g. `/openai`.
+    path_prefix: String,
+}
+
+impl UpstreamEndpoint {
+    fn authority(&self) -> String {
+        format!("{}:{}", self.host, self.port)
+    }
+}
+
+/// Parse an `http(s)://host[:port][/path]` base URL. Query strings, fragments
+/// and credentials are rejected.
+fn parse_upstream_endpoint(value: &str) -> Option<UpstreamEndpoint> {
+    let value = value.trim();
+    let (tls, rest) = if let Some(rest) = value.strip_prefix("https://") {
+        (true, rest)
+    } else if let Some(rest) = value.strip_prefix("http://") {
+        (false, rest)
+    } else {
+        return None;
+    };
+    if rest.contains(['?', '#', '@']) {
+        return None;
+    }
+
+    let (authority, path) = match rest.find('/') {
+        Some(idx) => rest.split_at(idx),
+        None => (rest, ""),
+    };
+    let (host, port) = match authority.rsplit_once(':') {
+        Some((host, port)) => (host, port.parse::<u16>().ok()?),
+        None => (authority, if tls { 443 } else { 80 }),
+    };
+    if host.is_empty()
+        || !host
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-')
+    {
+        return None;
+    }
+
+    Some(UpstreamEndpoint {
+        tls,
+        host: host.to_ascii_lowercase(),
+        port,
+        path_prefix: path.trim_end_matches('/').to_string(),
+    })
+}
+
+/// Whether a `host:port` socket address (`[::1]:port` for IPv6) is loopback
+fn is_loopback_address(address: &str) -> bool {
+    address
+        .parse::<std::net::SocketAddr>()
+        .map(|address| address.ip())
+        .or_else(|_| address.parse::<std::net::IpAddr>())
+        .is_ok_and(|ip| ip.is_loopback())
+}
+
+/// Whether `host` matches the allow list. Entries are exact hostnames or
+/// `*.domain` wildcards; a missing or empty list allows no host.
+fn is_host_allowed(host: &str, allowed_hosts: Option<&[String]>) -> bool {
+    allowed_hosts.unwrap_or_default().iter().any(|allowed| {
+        let allowed = allowed.to_ascii_lowercase();
+        match allowed.strip_prefix("*.") {
+            Some(domain) => host
+                .strip_suffix(domain)
+                .is_some_and(|sub| sub.ends_with('.') && sub.len() > 1),
+            None => host == allowed,
+        }
+    })
+}
+
+/// Add a chat completions chunk with empty `choices` and estimated usage to
+/// `buffer`, unless the stream already carried usage. Returns the usage added.
+fn add_estimated_usage(
+    buffer: &mut SseStreamBuffer,
+    id: String,
+    model: String,
+    prompt_tokens: usize,
+    streamed_text: &str,
+    created: u64,
+) -> Option<Usage> {
+    if buffer.final_usage().is_some() {
+        return None;
+    }
+    let completion_tokens = tokenizer::token_count(&model, streamed_text).unwrap_or(0);
+    let usage = Usage {
+        prompt_tokens: prompt_tokens as u32,
+        completion_tokens: completion_tokens as u32,
+        total_tokens: (prompt_tokens + completion_tokens) as u32,
+        prompt_tokens_details: None,
+        completion_tokens_details: None,
+    };
+    let chunk = ChatCompletionsStreamResponse {
+        id,
+        object: Some("chat.completion.chunk".to_string()),
+        created,
+        model,
+        choices: vec![],
+        usage: Some(usage.clone()),
+        system_fingerprint: None,
+        service_tier: None,
+        citations: None,
+        search_results: None,
+    };
+    buffer.add_transformed_event(SseEvent::from_provider_response(
+        ProviderStreamResponseType::ChatCompletionsStreamResponse(chunk),
+    ));
+    Some(usage)
+}
+
 #[cfg(test)]
 mod tests {
-    use super::extract_client_credential;
+    use super::{
+        add_estimated_usage, extract_client_credential, is_host_allowed, is_loopback_address,
+        parse_upstream_endpoint,
+    };
+    use hermesllm::apis::streaming_shapes::sse::{SseStreamBuffer, SseStreamBufferTrait};
+    use hermesllm::apis::streaming_shapes::sse_chunk_processor::SseChunkProcessor;
+    use hermesllm::apis::OpenAIApi;
+    use hermesllm::clients::endpoints::{SupportedAPIsFromClient, SupportedUpstreamAPIs};
+    use serde_json::Value;
+
+    /// Run an upstream chat completions stream through the buffer the way
+    /// `on_http_response_body` does for a client that asked for usage, and
+    /// return the `data:` payloads sent to the client.
+    fn stream_with_usage_requested(upstream: &str) -> Vec<String> {
+        let client_api = SupportedAPIsFromClient::OpenAIChatCompletions(OpenAIApi::ChatCompletions);
+        let upstream_api = SupportedUpstreamAPIs::OpenAIChatCompletions(OpenAIApi::ChatCompletions);
+        let mut buffer = SseStreamBuffer::try_from((&client_api, &upstream_api)).unwrap();
+        let events = SseChunkProcessor::new()
+            .process_chunk(upstream.as_bytes(), &client_api, &upstream_api)
+            .unwrap();
+        for event in events {
+            if event.is_done() {
+                add_estimated_usage(
+                    &mut buffer,
+                    "chatcmpl-1".to_string(),
+                    "gpt-4o".to_string(),
+                    12,
+                    "Hello there",
+                    1700000000,
+                );
+            }
+            buffer.add_transformed_event(event);
+        }
+        String::from_utf8(buffer.to_bytes())
+            .unwrap()
+            .lines()
+            .filter_map(|line| line.strip_prefix("data: "))
+            .map(str::to_string)
+            .collect()
+    }
+
+    fn usage_chunks(payloads: &[String]) -> Vec<Value> {
+        payloads
+            .iter()
+            .filter_map(|payload| serde_json::from_str::<Value>(payload).ok())
+            .filter(|chunk| !chunk["usage"].is_null())
+            .collect()
+    }
 
     #[test]
     fn authorization_bearer_strips_prefix() {
@@ -1329,4 +2991,77 @@ mod tests {
         assert!(extract_client_credential(Some("Bearer "), None).is_none());
         assert!(extract_client_credential(Some("   "), Some("   ")).is_none());
     }
+
+    #[test]
+    fn upstream_endpoint_parsing() {
+        let endpoint = parse_upstream_endpoint("https://eu.api.example.com/openai/").unwrap();
+        assert!(endpoint.tls);
+        assert_eq!(endpoint.authority(), "eu.api.example.com:443");
+        assert_eq!(endpoint.path_prefix, "/openai");
+
+        let endpoint = parse_upstream_endpoint("http://10.0.0.5:8000").unwrap();
+        assert!(!endpoint.tls);
+        assert_eq!(endpoint.authority(), "10.0.0.5:8000");
+        assert_eq!(endpoint.path_prefix, "");
+
+        assert!(parse_upstream_endpoint("ftp://example.com").is_none());
+        assert!(parse_upstream_endpoint("https://user:pw@example.com").is_none());
+        assert!(parse_upstream_endpoint("https://example.com:notaport").is_none());
+        assert!(parse_upstream_endpoint("https://example.com/v1?x=1").is_none());
+    }
+
+    #[test]
+    fn upstream_endpoint_host_allow_list() {
+        let allowed = vec!["api.openai.com".to_string(), "*.example.com".to_string()];
+        assert!(is_host_allowed("api.openai.com", Some(&allowed)));
+        assert!(is_host_allowed("eu.example.com", Some(&allowed)));
+        assert!(!is_host_allowed("example.com", Some(&allowed)));
+        assert!(!is_host_allowed("evilexample.com", Some(&allowed)));
+        assert!(!is_host_allowed("api.openai.com.evil.io", Some(&allowed)));
+    }
+
+    #[test]
+    fn only_loopback_sources_are_local() {
+        assert!(is_loopback_address("127.0.0.1:51234"));
+        assert!(is_loopback_address("[::1]:51234"));
+        assert!(!is_loopback_address("172.17.0.1:51234"));
+        assert!(!is_loopback_address("not-an-address"));
+    }
+
+    #[test]
+    fn upstream_endpoint_without_allow_list_allows_no_host() {
+        assert!(!is_host_allowed("api.openai.com", None));
+        assert!(!is_host_allowed("api.openai.com", Some(&[])));
+    }
+
+    #[test]
+    fn estimated_usage_chunk_precedes_done_when_upstream_sends_none() {
+        let payloads = stream_with_usage_requested(concat!(
+            "data: {\"id\":\"chatcmpl-1\",\"object\":\"chat.completion.chunk\",\"created\":1700000000,\"model\":\"gpt-4o\",\"choices\":[{\"index\":0,\"delta\":{\"role\":\"assistant\",\"content\":\"Hello there\"},\"finish_reason\":null}]}\n\n",
+            "data: {\"id\":\"chatcmpl-1\",\"object\":\"chat.completion.chunk\",\"created\":1700000000,\"model\":\"gpt-4o\",\"choices\":[{\"index\":0,\"delta\":{},\"finish_reason\":\"stop\"}]}\n\n",
+            "data: [DONE]\n\n",
+        ));
+
+        let usage = usage_chunks(&payloads);
+        assert_eq!(usage.len(), 1, "{:?}", payloads);
+        assert_eq!(usage[0]["choices"], Value::Array(vec![]));
+        assert_eq!(usage[0]["usage"]["prompt_tokens"], 12);
+        assert!(usage[0]["usage"]["completion_tokens"].as_u64().unwrap() > 0);
+        assert_eq!(payloads[payloads.len() - 2], usage[0].to_string());
+        assert_eq!(payloads.last().map(String::as_str), Some("[DONE]"));
+    }
+
+    #[test]
+    fn no_estimated_usage_chunk_when_upstream_reports_usage() {
+        let payloads = stream_with_usage_requested(concat!(
+            "data: {\"id\":\"chatcmpl-1\",\"object\":\"chat.completion.chunk\",\"created\":1700000000,\"model\":\"gpt-4o\",\"choices\":[{\"index\":0,\"delta\":{\"role\":\"assistant\",\"content\":\"Hello there\"},\"finish_reason\":\"stop\"}]}\n\n",
+            "data: {\"id\":\"chatcmpl-1\",\"object\":\"chat.completion.chunk\",\"created\":1700000000,\"model\":\"gpt-4o\",\"choices\":[],\"usage\":{\"prompt_tokens\":9,\"completion_tokens\":2,\"total_tokens\":11}}\n\n",
+            "data: [DONE]\n\n",
+        ));
+
+        let usage = usage_chunks(&payloads);
+        assert_eq!(usage.len(), 1, "{:?}", payloads);
+        assert_eq!(usage[0]["usage"]["prompt_tokens"], 9);
+        assert_eq!(payloads.last().map(String::as_str), Some("[DONE]"));
+    }
 }
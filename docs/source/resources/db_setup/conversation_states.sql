@@ -8,9 +8,13 @@ CREATE TABLE IF NOT EXISTS conversation_states (
     created_at BIGINT NOT NULL,
     model TEXT NOT NULL,
     provider TEXT NOT NULL,
+    response JSONB,
     updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
 );
 
+-- Tables created before background responses need the response column
+ALTER TABLE conversation_states ADD COLUMN IF NOT EXISTS response JSONB;
+
 -- Indexes for common query patterns
 CREATE INDEX IF NOT EXISTS idx_conversation_states_created_at
     ON conversation_states(created_at);
@@ -29,3 +33,4 @@ COMMENT ON COLUMN conversation_states.input_items IS 'JSONB array of conversatio
 COMMENT ON COLUMN conversation_states.created_at IS 'Unix timestamp (seconds) when the conversation started';
 COMMENT ON COLUMN conversation_states.model IS 'Model name used for this conversation';
 COMMENT ON COLUMN conversation_states.provider IS 'LLM provider (e.g., openai, anthropic, bedrock)';
+COMMENT ON COLUMN conversation_states.response IS 'Response object of a background request, polled through GET /v1/responses/{id}';